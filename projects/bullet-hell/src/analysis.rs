@@ -0,0 +1,190 @@
+use crate::damage::DamageBreakdown;
+use crate::game::{MAP_HEIGHT, MAP_WIDTH, ProtoProjectile};
+use crate::simulation::{Bot, run_headless};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+/// How long a tick lasts in the real game (the input poll interval).
+pub const TICK_MS: u64 = 100;
+
+/// Characters used to draw the heatmap, from "never hit" to "hit the most".
+const DENSITY_RAMP: [char; 6] = [' ', '.', ':', '+', '#', '@'];
+
+/// Number of hits landed on each cell of the map.
+#[derive(Debug, PartialEq)]
+pub struct Heatmap {
+    counts: Vec<Vec<u32>>,
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Heatmap {
+    pub fn new() -> Self {
+        Self {
+            counts: vec![vec![0; MAP_WIDTH as usize]; MAP_HEIGHT as usize],
+        }
+    }
+
+    pub fn record(&mut self, x: u16, y: u16) {
+        self.counts[y as usize][x as usize] += 1;
+    }
+
+    pub fn max(&self) -> u32 {
+        self.counts.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    /// Draws the map with one character per cell, denser characters meaning more hits.
+    pub fn render(&self) -> String {
+        let max = self.max();
+        let mut out = String::new();
+        for row in &self.counts {
+            for &count in row {
+                // Rounding up makes any hit at all show up, and the busiest cell gets
+                // the last character.
+                let level = if max == 0 {
+                    0
+                } else {
+                    (count as usize * (DENSITY_RAMP.len() - 1)).div_ceil(max as usize)
+                };
+                out.push(DENSITY_RAMP[level]);
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[derive(Debug)]
+pub struct BotReport {
+    pub bot: Bot,
+    pub runs: u32,
+    pub deaths: u32,
+    pub mean_survival_ticks: f64,
+    pub heatmap: Heatmap,
+    pub damage: DamageBreakdown,
+}
+
+impl BotReport {
+    pub fn mean_survival_secs(&self) -> f64 {
+        self.mean_survival_ticks * TICK_MS as f64 / 1000.0
+    }
+}
+
+/// Plays the stage `runs` times with every bot and aggregates how long they lasted
+/// and where they got hit. The same `seed` always produces the same report.
+pub fn analyze_stage(
+    blueprints: &[ProtoProjectile],
+    runs: u32,
+    max_ticks: u32,
+    seed: u64,
+) -> Vec<BotReport> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    [Bot::Random, Bot::GreedyDodge]
+        .into_iter()
+        .map(|bot| {
+            let mut heatmap = Heatmap::new();
+            let mut damage = DamageBreakdown::new();
+            let mut deaths = 0;
+            let mut total_ticks = 0;
+
+            for _ in 0..runs {
+                let outcome = run_headless(blueprints, bot, max_ticks, &mut rng);
+                total_ticks += outcome.ticks_survived as u64;
+                if outcome.died {
+                    deaths += 1;
+                }
+                for hit in &outcome.hits {
+                    heatmap.record(hit.x, hit.y);
+                    damage.record(hit);
+                }
+            }
+
+            BotReport {
+                bot,
+                runs,
+                deaths,
+                mean_survival_ticks: if runs == 0 {
+                    0.0
+                } else {
+                    total_ticks as f64 / runs as f64
+                },
+                heatmap,
+                damage,
+            }
+        })
+        .collect()
+}
+
+pub fn format_report(reports: &[BotReport], max_ticks: u32) -> String {
+    let mut out = String::new();
+    for report in reports {
+        out.push_str(&format!("== {:?} bot ==\n", report.bot));
+        out.push_str(&format!(
+            "Survival: {:.1} ticks (~{:.1}s) on average, capped at {} ticks\n",
+            report.mean_survival_ticks,
+            report.mean_survival_secs(),
+            max_ticks
+        ));
+        out.push_str(&format!("Deaths: {}/{} runs\n", report.deaths, report.runs));
+        out.push_str("Hit density:\n");
+        out.push_str(&report.heatmap.render());
+        out.push_str("Damage sources:\n");
+        out.push_str(&report.damage.render());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heatmap_render_has_one_line_per_row() {
+        let heatmap = Heatmap::new();
+        let rendered = heatmap.render();
+        assert_eq!(rendered.lines().count(), MAP_HEIGHT as usize);
+        assert!(rendered.lines().all(|l| l.chars().count() == MAP_WIDTH as usize));
+        assert!(rendered.chars().all(|c| c == ' ' || c == '\n'));
+    }
+
+    #[test]
+    fn heatmap_render_marks_busiest_cell_densest() {
+        let mut heatmap = Heatmap::new();
+        heatmap.record(1, 1);
+        for _ in 0..8 {
+            heatmap.record(2, 1);
+        }
+        let rendered = heatmap.render();
+        let row: Vec<char> = rendered.lines().nth(1).unwrap().chars().collect();
+        assert_eq!(row[1], '.');
+        assert_eq!(row[2], '@');
+        assert_eq!(heatmap.max(), 8);
+    }
+
+    #[test]
+    fn analyze_empty_stage_reports_full_survival() {
+        let reports = analyze_stage(&[], 3, 40, 1);
+        assert_eq!(reports.len(), 2);
+        for report in reports {
+            assert_eq!(report.deaths, 0);
+            assert_eq!(report.mean_survival_ticks, 40.0);
+            assert_eq!(report.mean_survival_secs(), 4.0);
+            assert_eq!(report.heatmap.max(), 0);
+            assert_eq!(report.damage.total_damage(), 0);
+        }
+    }
+
+    #[test]
+    fn analyze_stage_is_reproducible_with_seed() {
+        let blueprints = crate::data::load_blueprints("assets/projectiles.ron");
+        let first = format_report(&analyze_stage(&blueprints, 5, 300, 9), 300);
+        let second = format_report(&analyze_stage(&blueprints, 5, 300, 9), 300);
+        assert_eq!(first, second);
+    }
+}
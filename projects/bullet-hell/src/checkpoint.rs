@@ -0,0 +1,126 @@
+use crate::game::Projectile;
+
+/// Score lost every time the player respawns at a checkpoint.
+pub const CHECKPOINT_PENALTY: u32 = 50;
+
+/// Snapshot of the world taken when the player reaches a checkpoint marker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub tick: u32,
+    pub score: u32,
+    pub projectiles: Vec<Projectile>,
+}
+
+pub struct CheckpointTracker {
+    markers: Vec<u32>,
+    last: Option<Checkpoint>,
+}
+
+impl CheckpointTracker {
+    pub fn new(markers: &[u32]) -> Self {
+        Self {
+            markers: markers.to_vec(),
+            last: None,
+        }
+    }
+
+    /// Saves the world if `tick` is one of the stage's checkpoint markers.
+    /// Returns whether a checkpoint was saved.
+    pub fn reach(&mut self, tick: u32, score: u32, projectiles: &[Projectile]) -> bool {
+        if !self.markers.contains(&tick) {
+            return false;
+        }
+        self.last = Some(Checkpoint {
+            tick,
+            score,
+            projectiles: projectiles.to_vec(),
+        });
+        true
+    }
+
+    /// Returns the world to restart from after a death, with the score penalty applied,
+    /// or `None` when no checkpoint was reached yet. Penalties add up on repeated deaths.
+    pub fn respawn(&mut self) -> Option<Checkpoint> {
+        let checkpoint = self.last.as_mut()?;
+        checkpoint.score = checkpoint.score.saturating_sub(CHECKPOINT_PENALTY);
+        Some(checkpoint.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Source;
+
+    fn projectile_at(x: u16, y: u16) -> Projectile {
+        Projectile {
+            id: 0,
+            source: Source::Endless { wave: 1 },
+            x,
+            y,
+            pattern: vec![(1, 0)].into(),
+            step: 0,
+            active: true,
+        }
+    }
+
+    #[test]
+    fn reach_ignores_ticks_without_marker() {
+        let mut tracker = CheckpointTracker::new(&[10]);
+        assert!(!tracker.reach(9, 9, &[]));
+        assert!(tracker.last.is_none());
+    }
+
+    #[test]
+    fn reach_saves_world_on_marker() {
+        let mut tracker = CheckpointTracker::new(&[10, 20]);
+        let projectiles = vec![projectile_at(3, 4)];
+
+        assert!(tracker.reach(10, 120, &projectiles));
+
+        let expected = Checkpoint {
+            tick: 10,
+            score: 120,
+            projectiles,
+        };
+        assert_eq!(tracker.last, Some(expected));
+    }
+
+    #[test]
+    fn reach_keeps_latest_checkpoint() {
+        let mut tracker = CheckpointTracker::new(&[10, 20]);
+        tracker.reach(10, 100, &[]);
+        tracker.reach(20, 200, &[projectile_at(1, 1)]);
+        assert_eq!(tracker.last.unwrap().tick, 20);
+    }
+
+    #[test]
+    fn respawn_without_checkpoint_is_none() {
+        let mut tracker = CheckpointTracker::new(&[10]);
+        assert!(tracker.respawn().is_none());
+    }
+
+    #[test]
+    fn respawn_applies_penalty_every_time() {
+        let mut tracker = CheckpointTracker::new(&[10]);
+        tracker.reach(10, 120, &[]);
+
+        assert_eq!(tracker.respawn().unwrap().score, 70);
+        assert_eq!(tracker.respawn().unwrap().score, 20);
+        assert_eq!(tracker.respawn().unwrap().score, 0);
+    }
+
+    #[test]
+    fn respawn_restores_projectiles_as_they_were() {
+        let mut tracker = CheckpointTracker::new(&[10]);
+        let mut projectiles = vec![projectile_at(3, 4)];
+        tracker.reach(10, 0, &projectiles);
+
+        projectiles[0].x = 30;
+        projectiles[0].active = false;
+
+        let checkpoint = tracker.respawn().unwrap();
+        assert_eq!(checkpoint.tick, 10);
+        assert_eq!(checkpoint.projectiles, vec![projectile_at(3, 4)]);
+    }
+}
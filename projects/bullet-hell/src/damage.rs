@@ -0,0 +1,144 @@
+use crate::simulation::Hit;
+use std::collections::BTreeMap;
+
+/// Hits and damage dealt by one source over a run. Projectiles of the same
+/// spawner count as a single source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceDamage {
+    pub source: String,
+    pub hits: u32,
+    pub damage: u32,
+}
+
+/// Damage taken per source, to tell which part of a stage is the hardest to dodge.
+#[derive(Debug, Default, PartialEq)]
+pub struct DamageBreakdown {
+    by_source: BTreeMap<String, SourceDamage>,
+}
+
+impl DamageBreakdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, hit: &Hit) {
+        let source = hit.source.to_string();
+        let entry = self
+            .by_source
+            .entry(source.clone())
+            .or_insert_with(|| SourceDamage {
+                source,
+                hits: 0,
+                damage: 0,
+            });
+        entry.hits += 1;
+        entry.damage += hit.damage as u32;
+    }
+
+    pub fn total_damage(&self) -> u32 {
+        self.by_source.values().map(|s| s.damage).sum()
+    }
+
+    /// Every source that hit the player, most damage first.
+    pub fn sources(&self) -> Vec<&SourceDamage> {
+        let mut sources: Vec<_> = self.by_source.values().collect();
+        sources.sort_by_key(|s| std::cmp::Reverse(s.damage));
+        sources
+    }
+
+    /// One line per source, e.g. "60% of damage came from the spiral spawner (3 hits)".
+    pub fn render(&self) -> String {
+        let total = self.total_damage();
+        if total == 0 {
+            return "No damage taken\n".to_string();
+        }
+        let mut out = String::new();
+        for s in self.sources() {
+            out.push_str(&format!(
+                "{:.0}% of damage came from {} ({} {})\n",
+                s.damage as f64 * 100.0 / total as f64,
+                s.source,
+                s.hits,
+                if s.hits == 1 { "hit" } else { "hits" }
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Source;
+
+    fn hit(source: Source) -> Hit {
+        Hit {
+            projectile_id: 0,
+            source,
+            x: 1,
+            y: 1,
+            damage: 1,
+        }
+    }
+
+    fn breakdown(hits: &[Hit]) -> DamageBreakdown {
+        let mut breakdown = DamageBreakdown::new();
+        for hit in hits {
+            breakdown.record(hit);
+        }
+        breakdown
+    }
+
+    fn spawner(index: usize, name: &str) -> Source {
+        Source::Stage {
+            index,
+            spawner: Some(name.to_string()),
+        }
+    }
+
+    #[test]
+    fn render_without_hits() {
+        assert_eq!(DamageBreakdown::new().render(), "No damage taken\n");
+    }
+
+    #[test]
+    fn breakdown_groups_hits_by_source_most_damage_first() {
+        let hits = vec![
+            hit(spawner(0, "wall")),
+            hit(spawner(1, "spiral")),
+            hit(spawner(2, "spiral")),
+            hit(spawner(1, "spiral")),
+            hit(Source::Stage {
+                index: 4,
+                spawner: None,
+            }),
+        ];
+        let breakdown = breakdown(&hits);
+
+        assert_eq!(breakdown.total_damage(), 5);
+        assert_eq!(breakdown.sources()[0].source, "the spiral spawner");
+        assert_eq!(
+            breakdown.render(),
+            "60% of damage came from the spiral spawner (3 hits)\n\
+             20% of damage came from projectile #5 (1 hit)\n\
+             20% of damage came from the wall spawner (1 hit)\n"
+        );
+    }
+
+    #[test]
+    fn endless_hits_are_grouped_by_wave() {
+        let hits = vec![
+            hit(Source::Endless { wave: 1 }),
+            hit(Source::Endless { wave: 2 }),
+            hit(Source::Endless { wave: 2 }),
+            hit(Source::Endless { wave: 2 }),
+        ];
+        let breakdown = breakdown(&hits);
+
+        assert_eq!(
+            breakdown.render(),
+            "75% of damage came from endless wave 2 (3 hits)\n\
+             25% of damage came from endless wave 1 (1 hit)\n"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::game::ProtoProjectile;
+use crate::game::{ProtoProjectile, Stage};
 
 pub fn load_blueprints(path: &str) -> Vec<ProtoProjectile> {
     let txt =
@@ -6,6 +6,20 @@ pub fn load_blueprints(path: &str) -> Vec<ProtoProjectile> {
     ron::from_str(&txt).expect("bad RON")
 }
 
+/// Loads a stage file. A plain list of projectiles is accepted as a stage without checkpoints.
+pub fn load_stage(path: &str) -> Stage {
+    let txt =
+        std::fs::read_to_string(path).unwrap_or_else(|e| panic!("cannot read {}: {}", path, e));
+    if txt.trim_start().starts_with('[') {
+        return Stage {
+            projectiles: load_blueprints(path),
+            checkpoints: Vec::new(),
+            adaptive: None,
+        };
+    }
+    ron::from_str(&txt).expect("bad RON")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -27,16 +41,19 @@ mod tests {
                 x: 1,
                 y: 10,
                 pattern: vec![(1, 0)],
+                spawner: None,
             },
             ProtoProjectile {
                 x: 38,
                 y: 8,
                 pattern: vec![(-1, 0)],
+                spawner: None,
             },
             ProtoProjectile {
                 x: 5,
                 y: 2,
                 pattern: vec![(1, 1)],
+                spawner: None,
             },
             ProtoProjectile {
                 x: 20,
@@ -52,6 +69,7 @@ mod tests {
                     (1, 0),
                     (1, 1),
                 ],
+                spawner: None,
             },
         ];
         assert_eq!(projectiles, expected);
@@ -72,11 +90,13 @@ mod tests {
                 x: 1,
                 y: 2,
                 pattern: vec![(1, 0)],
+                spawner: None,
             },
             ProtoProjectile {
                 x: 10,
                 y: 20,
                 pattern: vec![(0, 1)],
+                spawner: None,
             },
         ];
 
@@ -97,4 +117,56 @@ mod tests {
         let path = temp_file.path().to_str().unwrap();
         load_blueprints(path);
     }
+
+    #[test]
+    fn successfully_loads_stage_from_assets_file() {
+        let stage = load_stage("assets/stage.ron");
+        let untagged: Vec<ProtoProjectile> = stage
+            .projectiles
+            .iter()
+            .map(|p| ProtoProjectile {
+                spawner: None,
+                ..p.clone()
+            })
+            .collect();
+        assert_eq!(untagged, load_blueprints("assets/projectiles.ron"));
+        let spawners: Vec<_> = stage.projectiles.iter().map(|p| p.spawner.as_deref()).collect();
+        assert_eq!(
+            spawners,
+            vec![Some("left wall"), Some("right wall"), Some("diagonal"), Some("dance")]
+        );
+        assert_eq!(stage.checkpoints, vec![150, 300, 450]);
+    }
+
+    #[test]
+    fn loads_plain_projectile_list_as_stage() {
+        let stage = load_stage("assets/projectiles.ron");
+        assert_eq!(stage.projectiles, load_blueprints("assets/projectiles.ron"));
+        assert!(stage.checkpoints.is_empty());
+    }
+
+    #[test]
+    fn stage_checkpoints_default_to_empty() {
+        let ron_content = r#"(projectiles: [(x: 1, y: 2, pattern: [(1, 0)])])"#;
+        let temp_file = create_temp_ron_file(ron_content).unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let stage = load_stage(path);
+        assert_eq!(stage.projectiles.len(), 1);
+        assert!(stage.checkpoints.is_empty());
+        assert!(stage.adaptive.is_none());
+    }
+
+    #[test]
+    fn stage_adaptive_section_fills_in_defaults() {
+        let ron_content =
+            r#"(projectiles: [], adaptive: Some((step: 0.5, max_multiplier: 3.0)))"#;
+        let temp_file = create_temp_ron_file(ron_content).unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let adaptive = load_stage(path).adaptive.unwrap();
+        assert_eq!(adaptive.step, 0.5);
+        assert_eq!(adaptive.max_multiplier, 3.0);
+        assert_eq!(adaptive.quick_death_ticks, 150);
+    }
 }
@@ -0,0 +1,275 @@
+use crate::game::Projectile;
+use macro_workshop::config_struct;
+use serde::{Deserialize, Serialize};
+
+/// Where the difficulty multiplier is kept between runs.
+pub const DIFFICULTY_PATH: &str = "assets/difficulty.ron";
+
+config_struct! {
+    /// Rubber-banding rules, read from the `adaptive` section of the stage file.
+    /// Adaptive difficulty is off when the section is missing.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct AdaptiveConfig with overrides AdaptiveOverrides {
+        /// Dying within this many ticks of (re)spawning counts as a quick death.
+        pub quick_death_ticks: u32 = 150,
+        /// Quick deaths in a row that lower the multiplier by one step.
+        pub quick_deaths_to_ease: u32 = 2,
+        /// Ticks without being hit that raise the multiplier by one step.
+        pub no_hit_ticks_to_harden: u32 = 300,
+        pub step: f64 = 0.25,
+        pub min_multiplier: f64 = 0.5,
+        pub max_multiplier: f64 = 2.0,
+    }
+}
+
+/// What carries over to the next run, so that repeated quick runs ease the game too.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DifficultyState {
+    pub multiplier: f64,
+    pub quick_deaths: u32,
+}
+
+impl Default for DifficultyState {
+    fn default() -> Self {
+        Self {
+            multiplier: 1.0,
+            quick_deaths: 0,
+        }
+    }
+}
+
+/// Scales spawn density up while the player dodges everything and down while they
+/// keep dying early.
+#[derive(Debug)]
+pub struct Difficulty {
+    config: AdaptiveConfig,
+    pub state: DifficultyState,
+    ticks_alive: u32,
+    ticks_since_hit: u32,
+}
+
+impl Difficulty {
+    pub fn new(config: AdaptiveConfig, state: DifficultyState) -> Self {
+        let mut difficulty = Self {
+            config,
+            state,
+            ticks_alive: 0,
+            ticks_since_hit: 0,
+        };
+        // The config may have been tightened since the state was saved
+        difficulty.adjust(0.0);
+        difficulty
+    }
+
+    pub fn multiplier(&self) -> f64 {
+        self.state.multiplier
+    }
+
+    fn adjust(&mut self, delta: f64) {
+        self.state.multiplier = (self.state.multiplier + delta)
+            .clamp(self.config.min_multiplier, self.config.max_multiplier);
+    }
+
+    /// Records a tick in which the player took `hits` hits.
+    pub fn tick(&mut self, hits: usize) {
+        self.ticks_alive += 1;
+        if hits > 0 {
+            self.ticks_since_hit = 0;
+            return;
+        }
+        self.ticks_since_hit += 1;
+        if self.ticks_since_hit >= self.config.no_hit_ticks_to_harden {
+            self.adjust(self.config.step);
+            self.ticks_since_hit = 0;
+        }
+    }
+
+    /// Records a death, the next life starts counting from zero.
+    pub fn death(&mut self) {
+        if self.ticks_alive < self.config.quick_death_ticks {
+            self.state.quick_deaths += 1;
+            if self.state.quick_deaths >= self.config.quick_deaths_to_ease {
+                self.adjust(-self.config.step);
+                self.state.quick_deaths = 0;
+            }
+        } else {
+            self.state.quick_deaths = 0;
+        }
+        self.ticks_alive = 0;
+        self.ticks_since_hit = 0;
+    }
+
+    /// Drops projectiles evenly until only `multiplier` of them are left. Stage
+    /// layouts are never made denser than authored, so this does nothing above 1.
+    pub fn thin(&self, projectiles: &mut Vec<Projectile>) {
+        let share = self.multiplier().min(1.0);
+        let mut index = 0;
+        projectiles.retain(|_| {
+            let keep = ((index + 1) as f64 * share).floor() > (index as f64 * share).floor();
+            index += 1;
+            keep
+        });
+    }
+
+    /// Line shown in the debug overlay.
+    pub fn overlay(&self) -> String {
+        format!(
+            "Difficulty: x{:.2} (quick deaths {}, {} ticks without a hit)",
+            self.multiplier(),
+            self.state.quick_deaths,
+            self.ticks_since_hit
+        )
+    }
+}
+
+/// How many projectiles to spawn instead of `base` at `density`, never fewer than one.
+pub fn scaled_count(base: u32, density: f64) -> u32 {
+    ((base as f64 * density).round() as u32).max(1)
+}
+
+/// Loads the saved difficulty, starting at x1 if the file doesn't exist yet.
+pub fn load_difficulty(path: &str) -> DifficultyState {
+    match std::fs::read_to_string(path) {
+        Ok(txt) => ron::from_str(&txt).expect("bad RON"),
+        Err(_) => DifficultyState::default(),
+    }
+}
+
+pub fn save_difficulty(path: &str, state: &DifficultyState) -> std::io::Result<()> {
+    let txt = ron::ser::to_string_pretty(state, ron::ser::PrettyConfig::default())
+        .expect("difficulty always serializes");
+    std::fs::write(path, txt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Source;
+
+    fn config() -> AdaptiveConfig {
+        AdaptiveConfig {
+            quick_death_ticks: 10,
+            quick_deaths_to_ease: 2,
+            no_hit_ticks_to_harden: 5,
+            step: 0.5,
+            min_multiplier: 0.5,
+            max_multiplier: 2.0,
+        }
+    }
+
+    fn projectiles(count: u32) -> Vec<Projectile> {
+        (0..count)
+            .map(|id| Projectile {
+                id,
+                source: Source::Endless { wave: 1 },
+                x: 1,
+                y: 1,
+                pattern: vec![(1, 0)].into(),
+                step: 0,
+                active: true,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn adaptive_config_fills_in_missing_rules() {
+        let config: AdaptiveConfig = ron::from_str("(step: 0.5)").unwrap();
+        assert_eq!(config, AdaptiveConfig { step: 0.5, ..AdaptiveConfig::default() });
+
+        let mut tuned = config.clone();
+        tuned.merge(AdaptiveOverrides { max_multiplier: Some(3.0), ..Default::default() });
+        assert_eq!(tuned, AdaptiveConfig { max_multiplier: 3.0, ..config });
+    }
+
+    #[test]
+    fn repeated_quick_deaths_lower_the_multiplier() {
+        let mut difficulty = Difficulty::new(config(), DifficultyState::default());
+        difficulty.death();
+        assert_eq!(difficulty.multiplier(), 1.0);
+        difficulty.death();
+        assert_eq!(difficulty.multiplier(), 0.5);
+        difficulty.death();
+        difficulty.death();
+        assert_eq!(difficulty.multiplier(), 0.5);
+    }
+
+    #[test]
+    fn a_long_life_resets_the_quick_deaths() {
+        let mut difficulty = Difficulty::new(config(), DifficultyState::default());
+        difficulty.death();
+        for _ in 0..10 {
+            difficulty.tick(1);
+        }
+        difficulty.death();
+        assert_eq!(difficulty.state.quick_deaths, 0);
+        assert_eq!(difficulty.multiplier(), 1.0);
+    }
+
+    #[test]
+    fn no_hit_streaks_raise_the_multiplier_up_to_the_max() {
+        let mut difficulty = Difficulty::new(config(), DifficultyState::default());
+        for _ in 0..4 {
+            difficulty.tick(0);
+        }
+        difficulty.tick(1);
+        for _ in 0..4 {
+            difficulty.tick(0);
+        }
+        assert_eq!(difficulty.multiplier(), 1.0);
+        difficulty.tick(0);
+        assert_eq!(difficulty.multiplier(), 1.5);
+        for _ in 0..50 {
+            difficulty.tick(0);
+        }
+        assert_eq!(difficulty.multiplier(), 2.0);
+    }
+
+    #[test]
+    fn saved_state_is_clamped_to_the_config() {
+        let state = DifficultyState {
+            multiplier: 5.0,
+            quick_deaths: 1,
+        };
+        assert_eq!(Difficulty::new(config(), state).multiplier(), 2.0);
+    }
+
+    #[test]
+    fn scaled_count_never_stops_spawning() {
+        assert_eq!(scaled_count(4, 1.5), 6);
+        assert_eq!(scaled_count(3, 1.0), 3);
+        assert_eq!(scaled_count(1, 0.5), 1);
+    }
+
+    #[test]
+    fn thin_keeps_an_even_share_of_the_projectiles() {
+        let mut state = DifficultyState {
+            multiplier: 0.5,
+            quick_deaths: 0,
+        };
+        let mut thinned = projectiles(6);
+        Difficulty::new(config(), state.clone()).thin(&mut thinned);
+        let ids: Vec<u32> = thinned.iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec![1, 3, 5]);
+
+        state.multiplier = 2.0;
+        let mut untouched = projectiles(6);
+        Difficulty::new(config(), state).thin(&mut untouched);
+        assert_eq!(untouched.len(), 6);
+    }
+
+    #[test]
+    fn difficulty_round_trips_through_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+        let state = DifficultyState {
+            multiplier: 0.75,
+            quick_deaths: 1,
+        };
+        save_difficulty(path, &state).unwrap();
+        assert_eq!(load_difficulty(path), state);
+        assert_eq!(
+            load_difficulty("/nonexistent/difficulty.ron"),
+            DifficultyState::default()
+        );
+    }
+}
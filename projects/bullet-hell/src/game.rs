@@ -1,4 +1,7 @@
+use crate::difficulty::AdaptiveConfig;
 use serde::Deserialize;
+use std::fmt;
+use std::sync::Arc;
 
 pub const MAP_WIDTH: u16 = 40;
 pub const MAP_HEIGHT: u16 = 20;
@@ -8,8 +11,23 @@ pub struct ProtoProjectile {
     pub x: u16,
     pub y: u16,
     pub pattern: Vec<(i8, i8)>,
+    /// Name of the spawner the projectile belongs to, hits are attributed to it.
+    #[serde(default)]
+    pub spawner: Option<String>,
 }
-#[derive(PartialEq, Debug)]
+/// A playable stage: the projectiles it spawns, the ticks at which the player
+/// reaches a checkpoint and, optionally, how the difficulty adapts to the player.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Stage {
+    pub projectiles: Vec<ProtoProjectile>,
+    #[serde(default)]
+    pub checkpoints: Vec<u32>,
+    /// Turns on adaptive difficulty with these rules.
+    #[serde(default)]
+    pub adaptive: Option<AdaptiveConfig>,
+}
+
+#[derive(PartialEq, Debug, Clone)]
 pub struct Player {
     pub x: u16,
     pub y: u16,
@@ -17,11 +35,37 @@ pub struct Player {
     pub hp: u16,
 }
 
+/// Where a projectile came from, so that the hits it lands can be attributed.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Source {
+    /// The `index`-th projectile of the stage file, part of `spawner` if it names one.
+    Stage { index: usize, spawner: Option<String> },
+    /// Spawned by endless mode during `wave`.
+    Endless { wave: u32 },
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Stage {
+                spawner: Some(spawner),
+                ..
+            } => write!(f, "the {} spawner", spawner),
+            Source::Stage { index, .. } => write!(f, "projectile #{}", index + 1),
+            Source::Endless { wave } => write!(f, "endless wave {}", wave),
+        }
+    }
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub struct Projectile {
+    /// Unique within a run, and kept when the world is restored from a checkpoint.
+    pub id: u32,
+    pub source: Source,
     pub x: u16,
     pub y: u16,
-    pub pattern: Vec<(i8, i8)>,
+    /// Shared with every other projectile moving the same way, cloning is cheap.
+    pub pattern: Arc<[(i8, i8)]>,
     pub step: usize,
     pub active: bool,
 }
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+/// How many scores a table keeps.
+pub const MAX_ENTRIES: usize = 10;
+
+/// Best scores of a mode, highest first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct HighScores {
+    pub scores: Vec<u32>,
+}
+
+impl HighScores {
+    /// Adds `score` to the table and returns its 1-based rank, or `None` if it
+    /// didn't make the cut.
+    pub fn submit(&mut self, score: u32) -> Option<usize> {
+        let position = self.scores.iter().position(|&s| score > s).unwrap_or(self.scores.len());
+        if position >= MAX_ENTRIES {
+            return None;
+        }
+        self.scores.insert(position, score);
+        self.scores.truncate(MAX_ENTRIES);
+        Some(position + 1)
+    }
+}
+
+/// Loads a high-score table, starting an empty one if the file doesn't exist yet.
+pub fn load_high_scores(path: &str) -> HighScores {
+    match std::fs::read_to_string(path) {
+        Ok(txt) => ron::from_str(&txt).expect("bad RON"),
+        Err(_) => HighScores::default(),
+    }
+}
+
+pub fn save_high_scores(path: &str, high_scores: &HighScores) -> std::io::Result<()> {
+    let txt = ron::ser::to_string_pretty(high_scores, ron::ser::PrettyConfig::default())
+        .expect("high scores always serialize");
+    std::fs::write(path, txt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn submit_keeps_scores_sorted() {
+        let mut table = HighScores::default();
+        assert_eq!(table.submit(50), Some(1));
+        assert_eq!(table.submit(80), Some(1));
+        assert_eq!(table.submit(60), Some(2));
+        assert_eq!(table.scores, vec![80, 60, 50]);
+    }
+
+    #[test]
+    fn submit_ties_rank_after_existing_score() {
+        let mut table = HighScores {
+            scores: vec![80, 60],
+        };
+        assert_eq!(table.submit(60), Some(3));
+    }
+
+    #[test]
+    fn submit_drops_scores_below_full_table() {
+        let mut table = HighScores {
+            scores: (1..=MAX_ENTRIES as u32).rev().map(|s| s * 10).collect(),
+        };
+        assert_eq!(table.submit(5), None);
+        assert_eq!(table.submit(15), Some(MAX_ENTRIES));
+        assert_eq!(table.scores.len(), MAX_ENTRIES);
+        assert_eq!(*table.scores.last().unwrap(), 15);
+    }
+
+    #[test]
+    fn load_missing_file_is_empty_table() {
+        assert_eq!(
+            load_high_scores("/nonexistent/highscores.ron"),
+            HighScores::default()
+        );
+    }
+
+    #[test]
+    fn save_and_load_roundtrip() {
+        let file = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        let path = file.path().to_str().unwrap();
+        let table = HighScores {
+            scores: vec![300, 200, 100],
+        };
+
+        save_high_scores(path, &table).unwrap();
+        assert_eq!(load_high_scores(path), table);
+    }
+}
@@ -12,6 +12,7 @@ pub enum InputCommand {
     MoveDown,
     MoveLeft,
     MoveRight,
+    ToggleDebug,
     Quit,
     None,
 }
@@ -26,6 +27,7 @@ pub fn handle_input() -> std::io::Result<InputCommand> {
                     KeyCode::Down => InputCommand::MoveDown,
                     KeyCode::Left => InputCommand::MoveLeft,
                     KeyCode::Right => InputCommand::MoveRight,
+                    KeyCode::Char('d') => InputCommand::ToggleDebug,
                     _ => InputCommand::None,
                 });
             }
@@ -4,19 +4,98 @@
 #![warn(you_are_editing_an_coverage_excluded_file)] // ← unknown attribute
 // TODO: this always warns, right way would be to warn only when the file is actually changed, on a CI tool
 
+mod analysis;
+mod checkpoint;
+mod damage;
 mod data;
+mod difficulty;
 mod game;
+mod highscores;
 mod input;
+mod modes;
 mod player;
+mod pool;
 mod projectile;
+mod projectile_gen;
 mod rendering;
+mod simulation;
 
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use std::io;
 
+/// Bot runs per bot when analyzing a stage.
+const ANALYSIS_RUNS: u32 = 50;
+/// Longest run considered when analyzing a stage, one minute of play.
+const ANALYSIS_MAX_TICKS: u32 = 600;
+/// Endless ticks played by `bench` when no count is given, over eight minutes of play.
+const BENCH_TICKS: u32 = 5000;
+
 fn main() -> io::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("analyze") {
+        let path = args.get(2).map(String::as_str).unwrap_or("assets/stage.ron");
+        let stage = data::load_stage(path);
+        let reports =
+            analysis::analyze_stage(&stage.projectiles, ANALYSIS_RUNS, ANALYSIS_MAX_TICKS, 42);
+        print!("{}", analysis::format_report(&reports, ANALYSIS_MAX_TICKS));
+        return Ok(());
+    }
+    if args.get(1).map(String::as_str) == Some("bench") {
+        let ticks = match args.get(2).map(|arg| arg.parse::<u32>()) {
+            None => BENCH_TICKS,
+            Some(Ok(ticks)) => ticks,
+            Some(Err(_)) => {
+                eprintln!("Expected a number of ticks, got '{}'", args[2]);
+                return Ok(());
+            }
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let report = simulation::bench_endless(simulation::Bot::GreedyDodge, ticks, &mut rng);
+        print!("{}", simulation::format_bench(&report));
+        return Ok(());
+    }
+
+    let mode = match args.get(1) {
+        None => modes::Mode::Stage,
+        Some(arg) => match modes::Mode::from_arg(arg) {
+            Some(mode) => mode,
+            None => {
+                eprintln!("Unknown mode '{}', expected stage, time-attack, endless, analyze or bench", arg);
+                return Ok(());
+            }
+        },
+    };
+
     let mut player = player::create_player();
-    let blueprints = data::load_blueprints("assets/projectiles.ron");
-    let mut projectiles = projectile::create_projectiles_from_blueprints(blueprints);
+    let stage = data::load_stage("assets/stage.ron");
+    let mut difficulty = stage.adaptive.clone().map(|config| {
+        difficulty::Difficulty::new(config, difficulty::load_difficulty(difficulty::DIFFICULTY_PATH))
+    });
+    let (mut projectiles, mut checkpoints) = match mode {
+        modes::Mode::Stage => (
+            projectile::create_projectiles_from_blueprints(stage.projectiles),
+            checkpoint::CheckpointTracker::new(&stage.checkpoints),
+        ),
+        modes::Mode::TimeAttack => (
+            projectile::create_projectiles_from_blueprints(stage.projectiles),
+            checkpoint::CheckpointTracker::new(&[]),
+        ),
+        modes::Mode::Endless => (Vec::new(), checkpoint::CheckpointTracker::new(&[])),
+    };
+    if let Some(difficulty) = &difficulty {
+        difficulty.thin(&mut projectiles);
+    }
+    let mut rng = rand::rng();
+    // Endless ids start after the stage ones and keep growing across respawns.
+    let mut next_projectile_id = projectiles.len() as u32;
+    let mut projectiles = pool::ProjectilePool::from(projectiles);
+    let patterns = projectile_gen::PatternBank::random(projectile_gen::PATTERN_BANK_SIZE, &mut rng);
+    let mut damage = damage::DamageBreakdown::new();
+    let mut tick: u32 = 0;
+    let mut score: u32 = 0;
+    let mut finished = false;
+    let mut show_debug = false;
 
     rendering::setup_terminal()?;
 
@@ -29,30 +108,99 @@ fn main() -> io::Result<()> {
             input::InputCommand::MoveDown => player::move_player(&mut player, 0, 1),
             input::InputCommand::MoveLeft => player::move_player(&mut player, -1, 0),
             input::InputCommand::MoveRight => player::move_player(&mut player, 1, 0),
+            input::InputCommand::ToggleDebug => show_debug = !show_debug,
             input::InputCommand::None => {}
         }
 
         // Update game state
-        for projectile in &mut projectiles {
-            if projectile::check_collision(&player, projectile) {
-                player::damage_player(&mut player, 1);
-                projectile.active = false;
-            }
+        if mode == modes::Mode::Endless {
+            let density = difficulty.as_ref().map_or(1.0, |d| d.multiplier());
+            modes::endless_spawn(
+                tick,
+                &mut projectiles,
+                &mut next_projectile_id,
+                density,
+                &patterns,
+                &mut rng,
+            );
         }
-
-        for projectile in &mut projectiles {
-            projectile::update_projectile(projectile);
+        let hits = simulation::step(&mut player, projectiles.projectiles_mut());
+        for hit in &hits {
+            damage.record(hit);
+        }
+        if let Some(difficulty) = &mut difficulty {
+            difficulty.tick(hits.len());
         }
+        tick += 1;
+        score = mode.score_tick(score, hits.len());
+        checkpoints.reach(tick, score, projectiles.projectiles());
 
         // Render
-        rendering::draw_game(&player, &projectiles)?;
+        let mut debug_overlay = Vec::new();
+        if show_debug {
+            debug_overlay.push(format!(
+                "Tick {} | {} projectiles",
+                tick,
+                projectiles.active_count()
+            ));
+            if let Some(difficulty) = &difficulty {
+                debug_overlay.push(difficulty.overlay());
+            }
+        }
+        rendering::draw_game(
+            &player,
+            projectiles.projectiles(),
+            score,
+            mode.hud(tick).as_deref(),
+            &debug_overlay,
+        )?;
 
-        // Check game over
-        if player.hp == 0 {
-            running = false;
+        // Check game over, respawning at the last checkpoint if there is one
+        if mode.is_over(tick, &player) {
+            if let Some(difficulty) = difficulty.as_mut().filter(|_| player.hp == 0) {
+                difficulty.death();
+            }
+            match checkpoints.respawn() {
+                Some(restart) if player.hp == 0 => {
+                    player = player::create_player();
+                    let mut restarted = restart.projectiles;
+                    if let Some(difficulty) = &difficulty {
+                        difficulty.thin(&mut restarted);
+                    }
+                    projectiles = pool::ProjectilePool::from(restarted);
+                    tick = restart.tick;
+                    score = restart.score;
+                }
+                _ => {
+                    running = false;
+                    finished = true;
+                }
+            }
         }
     }
 
     rendering::restore_terminal()?;
+
+    if let Some(difficulty) = &difficulty {
+        difficulty::save_difficulty(difficulty::DIFFICULTY_PATH, &difficulty.state)?;
+    }
+
+    print!("{}", damage.render());
+
+    if finished {
+        let final_score = mode.final_score(score, &player);
+        println!("Final score: {}", final_score);
+        if let Some(path) = mode.high_scores_path() {
+            let mut high_scores = highscores::load_high_scores(path);
+            if let Some(rank) = high_scores.submit(final_score) {
+                println!("New high score! Rank #{}", rank);
+                highscores::save_high_scores(path, &high_scores)?;
+            }
+            println!("High scores:");
+            for (i, entry) in high_scores.scores.iter().enumerate() {
+                println!("{:>3}. {}", i + 1, entry);
+            }
+        }
+    }
     Ok(())
 }
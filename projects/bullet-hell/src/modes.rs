@@ -0,0 +1,222 @@
+use crate::analysis::TICK_MS;
+use crate::difficulty::scaled_count;
+use crate::game::{Player, Source};
+use crate::pool::ProjectilePool;
+use crate::projectile_gen::{PatternBank, random_projectile};
+use rand::Rng;
+
+/// Time-attack lasts two minutes.
+pub const TIME_ATTACK_TICKS: u32 = 2 * 60 * 1000 / TICK_MS as u32;
+/// Score lost per hit in time-attack, where surviving the whole run is a given.
+pub const TIME_ATTACK_HIT_PENALTY: u32 = 25;
+/// Score gained per HP left when the time-attack clock runs out.
+pub const TIME_ATTACK_HP_BONUS: u32 = 100;
+
+/// Ticks between two endless-mode spawns.
+pub const ENDLESS_SPAWN_INTERVAL: u32 = 20;
+/// Ticks between two endless-mode waves, each wave spawning one more projectile at a time.
+pub const ENDLESS_WAVE_TICKS: u32 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// Plays the stage file, respawning at checkpoints.
+    Stage,
+    /// Fixed two minutes on the stage file, as many points as possible.
+    TimeAttack,
+    /// Empty arena that keeps spawning more projectiles until the player dies.
+    Endless,
+}
+
+impl Mode {
+    pub fn from_arg(arg: &str) -> Option<Mode> {
+        match arg {
+            "stage" => Some(Mode::Stage),
+            "time-attack" => Some(Mode::TimeAttack),
+            "endless" => Some(Mode::Endless),
+            _ => None,
+        }
+    }
+
+    /// High-score table for this mode, `None` for modes that don't keep one.
+    pub fn high_scores_path(&self) -> Option<&'static str> {
+        match self {
+            Mode::Stage => None,
+            Mode::TimeAttack => Some("assets/highscores_time_attack.ron"),
+            Mode::Endless => Some("assets/highscores_endless.ron"),
+        }
+    }
+
+    /// Score after a tick in which the player took `hits` hits.
+    pub fn score_tick(&self, score: u32, hits: usize) -> u32 {
+        match self {
+            Mode::TimeAttack => {
+                (score + 1).saturating_sub(hits as u32 * TIME_ATTACK_HIT_PENALTY)
+            }
+            Mode::Stage | Mode::Endless => score + 1,
+        }
+    }
+
+    /// Whether the run ends after `tick`, not counting respawns.
+    pub fn is_over(&self, tick: u32, player: &Player) -> bool {
+        match self {
+            Mode::TimeAttack => player.hp == 0 || tick >= TIME_ATTACK_TICKS,
+            Mode::Stage | Mode::Endless => player.hp == 0,
+        }
+    }
+
+    /// Score once the run is over.
+    pub fn final_score(&self, score: u32, player: &Player) -> u32 {
+        match self {
+            Mode::TimeAttack => score + player.hp as u32 * TIME_ATTACK_HP_BONUS,
+            Mode::Stage | Mode::Endless => score,
+        }
+    }
+
+    /// Mode-specific line shown below the HP bar.
+    pub fn hud(&self, tick: u32) -> Option<String> {
+        match self {
+            Mode::Stage => None,
+            Mode::TimeAttack => {
+                let remaining_secs =
+                    TIME_ATTACK_TICKS.saturating_sub(tick) as u64 * TICK_MS / 1000;
+                Some(format!(
+                    "Time left: {}:{:02}",
+                    remaining_secs / 60,
+                    remaining_secs % 60
+                ))
+            }
+            Mode::Endless => Some(format!("Wave: {}", endless_wave(tick))),
+        }
+    }
+}
+
+pub fn endless_wave(tick: u32) -> u32 {
+    1 + tick / ENDLESS_WAVE_TICKS
+}
+
+/// Adds endless-mode projectiles when `tick` is a spawn tick, one per wave reached.
+/// Slots of projectiles that already hit the player are reused before the pool grows.
+/// `next_id` is the id given to the next projectile spawned, and is advanced past it.
+/// `density` scales how many projectiles a spawn adds, 1.0 unless difficulty is adaptive.
+pub fn endless_spawn<R: Rng>(
+    tick: u32,
+    pool: &mut ProjectilePool,
+    next_id: &mut u32,
+    density: f64,
+    patterns: &PatternBank,
+    rng: &mut R,
+) {
+    if tick == 0 || !tick.is_multiple_of(ENDLESS_SPAWN_INTERVAL) {
+        return;
+    }
+    pool.reclaim();
+    let wave = endless_wave(tick);
+    for _ in 0..scaled_count(wave, density) {
+        pool.spawn(random_projectile(*next_id, Source::Endless { wave }, patterns, rng));
+        *next_id += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::create_player;
+    use crate::projectile_gen::PATTERN_BANK_SIZE;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn from_arg_parses_every_mode() {
+        assert_eq!(Mode::from_arg("stage"), Some(Mode::Stage));
+        assert_eq!(Mode::from_arg("time-attack"), Some(Mode::TimeAttack));
+        assert_eq!(Mode::from_arg("endless"), Some(Mode::Endless));
+        assert_eq!(Mode::from_arg("zen"), None);
+    }
+
+    #[test]
+    fn time_attack_lasts_two_minutes() {
+        assert_eq!(TIME_ATTACK_TICKS, 1200);
+        let player = create_player();
+        assert!(!Mode::TimeAttack.is_over(1199, &player));
+        assert!(Mode::TimeAttack.is_over(1200, &player));
+        assert!(!Mode::Endless.is_over(100_000, &player));
+    }
+
+    #[test]
+    fn time_attack_hits_cost_points() {
+        assert_eq!(Mode::TimeAttack.score_tick(100, 0), 101);
+        assert_eq!(Mode::TimeAttack.score_tick(100, 1), 76);
+        assert_eq!(Mode::TimeAttack.score_tick(10, 2), 0);
+        assert_eq!(Mode::Endless.score_tick(10, 2), 11);
+    }
+
+    #[test]
+    fn time_attack_rewards_remaining_hp() {
+        let mut player = create_player();
+        player.hp = 3;
+        assert_eq!(Mode::TimeAttack.final_score(500, &player), 800);
+        assert_eq!(Mode::Endless.final_score(500, &player), 500);
+    }
+
+    #[test]
+    fn hud_is_mode_specific() {
+        assert_eq!(Mode::Stage.hud(0), None);
+        assert_eq!(Mode::TimeAttack.hud(0), Some("Time left: 2:00".to_string()));
+        assert_eq!(Mode::TimeAttack.hud(1195), Some("Time left: 0:00".to_string()));
+        assert_eq!(Mode::Endless.hud(650), Some("Wave: 3".to_string()));
+    }
+
+    #[test]
+    fn only_new_modes_keep_high_scores() {
+        assert!(Mode::Stage.high_scores_path().is_none());
+        assert_ne!(
+            Mode::TimeAttack.high_scores_path(),
+            Mode::Endless.high_scores_path()
+        );
+    }
+
+    #[test]
+    fn endless_spawns_only_on_interval() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut pool = ProjectilePool::new();
+        let patterns = PatternBank::random(PATTERN_BANK_SIZE, &mut rng);
+        let mut next_id = 0;
+        endless_spawn(0, &mut pool, &mut next_id, 1.0, &patterns, &mut rng);
+        endless_spawn(ENDLESS_SPAWN_INTERVAL - 1, &mut pool, &mut next_id, 1.0, &patterns, &mut rng);
+        assert_eq!(pool.slot_count(), 0);
+
+        endless_spawn(ENDLESS_SPAWN_INTERVAL, &mut pool, &mut next_id, 1.0, &patterns, &mut rng);
+        assert_eq!(pool.active_count(), 1);
+    }
+
+    #[test]
+    fn endless_spawns_more_each_wave_and_reuses_spent_slots() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut pool = ProjectilePool::new();
+        let patterns = PatternBank::random(PATTERN_BANK_SIZE, &mut rng);
+        let mut next_id = 0;
+        endless_spawn(ENDLESS_SPAWN_INTERVAL, &mut pool, &mut next_id, 1.0, &patterns, &mut rng);
+        pool.projectiles_mut()[0].active = false;
+
+        endless_spawn(2 * ENDLESS_WAVE_TICKS, &mut pool, &mut next_id, 1.0, &patterns, &mut rng);
+        assert_eq!(pool.slot_count(), 3);
+        assert!(pool.projectiles().iter().all(|p| p.active));
+        assert_eq!(next_id, 4);
+        let mut ids: Vec<u32> = pool.projectiles().iter().map(|p| p.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert!(pool.projectiles().iter().all(|p| p.source == Source::Endless { wave: 3 }));
+    }
+
+    #[test]
+    fn endless_spawn_density_scales_each_spawn() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut pool = ProjectilePool::new();
+        let patterns = PatternBank::random(PATTERN_BANK_SIZE, &mut rng);
+        let mut next_id = 0;
+        endless_spawn(2 * ENDLESS_WAVE_TICKS, &mut pool, &mut next_id, 2.0, &patterns, &mut rng);
+        assert_eq!(pool.active_count(), 6);
+        endless_spawn(ENDLESS_SPAWN_INTERVAL, &mut pool, &mut next_id, 0.25, &patterns, &mut rng);
+        assert_eq!(pool.active_count(), 7);
+    }
+}
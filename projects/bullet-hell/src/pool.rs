@@ -0,0 +1,119 @@
+use crate::game::Projectile;
+
+/// Projectile storage that never shrinks: a projectile going inactive leaves its slot
+/// behind, and the next spawn reuses that slot instead of growing the list.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectilePool {
+    slots: Vec<Projectile>,
+    /// Indices of inactive slots, the last one is reused first.
+    free: Vec<usize>,
+}
+
+impl ProjectilePool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Puts `projectile` in a free slot if there is one, growing the pool otherwise.
+    /// Returns the slot index.
+    pub fn spawn(&mut self, projectile: Projectile) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.slots[index] = projectile;
+                index
+            }
+            None => {
+                self.slots.push(projectile);
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    /// Rebuilds the free list from the slots that went inactive since the last call,
+    /// lowest index first.
+    pub fn reclaim(&mut self) {
+        self.free.clear();
+        self.free.extend(
+            self.slots
+                .iter()
+                .enumerate()
+                .rev()
+                .filter(|(_, p)| !p.active)
+                .map(|(index, _)| index),
+        );
+    }
+
+    /// Every slot, inactive ones included.
+    pub fn projectiles(&self) -> &[Projectile] {
+        &self.slots
+    }
+
+    pub fn projectiles_mut(&mut self) -> &mut [Projectile] {
+        &mut self.slots
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.slots.iter().filter(|p| p.active).count()
+    }
+
+    /// Slots allocated so far, the most projectiles ever alive at once.
+    pub fn slot_count(&self) -> usize {
+        self.slots.len()
+    }
+}
+
+impl From<Vec<Projectile>> for ProjectilePool {
+    fn from(slots: Vec<Projectile>) -> Self {
+        let mut pool = Self { slots, free: Vec::new() };
+        pool.reclaim();
+        pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Source;
+
+    fn projectile(id: u32, active: bool) -> Projectile {
+        Projectile {
+            id,
+            source: Source::Endless { wave: 1 },
+            x: 1,
+            y: 1,
+            pattern: vec![(1, 0)].into(),
+            step: 0,
+            active,
+        }
+    }
+
+    #[test]
+    fn spawn_grows_the_pool_until_a_slot_is_reclaimed() {
+        let mut pool = ProjectilePool::new();
+        assert_eq!(pool.spawn(projectile(0, true)), 0);
+        assert_eq!(pool.spawn(projectile(1, true)), 1);
+        pool.projectiles_mut()[0].active = false;
+
+        // Not reclaimed yet, the inactive slot is left alone
+        assert_eq!(pool.spawn(projectile(2, true)), 2);
+        pool.reclaim();
+        assert_eq!(pool.spawn(projectile(3, true)), 0);
+        assert_eq!(pool.slot_count(), 3);
+        assert_eq!(pool.active_count(), 3);
+        assert_eq!(pool.projectiles()[0].id, 3);
+    }
+
+    #[test]
+    fn reclaim_reuses_the_lowest_slots_first() {
+        let mut pool = ProjectilePool::from(vec![
+            projectile(0, true),
+            projectile(1, false),
+            projectile(2, true),
+            projectile(3, false),
+        ]);
+        assert_eq!(pool.active_count(), 2);
+        assert_eq!(pool.spawn(projectile(4, true)), 1);
+        assert_eq!(pool.spawn(projectile(5, true)), 3);
+        assert_eq!(pool.spawn(projectile(6, true)), 4);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::game::{MAP_HEIGHT, MAP_WIDTH, Player, Projectile};
+use crate::game::{MAP_HEIGHT, MAP_WIDTH, Player, Projectile, Source};
 
 pub fn update_projectile(projectile: &mut Projectile) {
     if !projectile.active {
@@ -14,15 +14,22 @@ pub fn check_collision(player: &Player, projectile: &Projectile) -> bool {
     projectile.active && player.x == projectile.x && player.y == projectile.y
 }
 
+/// Projectiles get their position in the stage file as id.
 pub fn create_projectiles_from_blueprints(
     blueprints: Vec<crate::game::ProtoProjectile>,
 ) -> Vec<Projectile> {
     blueprints
         .into_iter()
-        .map(|p| Projectile {
+        .enumerate()
+        .map(|(index, p)| Projectile {
+            id: index as u32,
+            source: Source::Stage {
+                index,
+                spawner: p.spawner,
+            },
             x: p.x,
             y: p.y,
-            pattern: p.pattern,
+            pattern: p.pattern.into(),
             step: 0,
             active: true,
         })
@@ -39,11 +46,13 @@ mod tests {
                 x: 1,
                 y: 10,
                 pattern: vec![(1, 0)],
+                spawner: None,
             },
             crate::game::ProtoProjectile {
                 x: 38,
                 y: 8,
                 pattern: vec![(-1, 0)],
+                spawner: None,
             },
         ];
         let projectiles = create_projectiles_from_blueprints(blueprints);
@@ -53,6 +62,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn create_projectiles_from_blueprints_numbers_ids_and_keeps_spawner() {
+        let blueprint = |spawner: Option<&str>| crate::game::ProtoProjectile {
+            x: 1,
+            y: 1,
+            pattern: vec![(1, 0)],
+            spawner: spawner.map(str::to_string),
+        };
+        let projectiles =
+            create_projectiles_from_blueprints(vec![blueprint(None), blueprint(Some("spiral"))]);
+        assert_eq!(projectiles[0].id, 0);
+        assert_eq!(projectiles[1].id, 1);
+        assert_eq!(
+            projectiles[1].source,
+            Source::Stage {
+                index: 1,
+                spawner: Some("spiral".to_string()),
+            }
+        );
+    }
+
     #[test]
     fn check_collision_projectile_inactive() {
         let player = Player {
@@ -62,9 +92,11 @@ mod tests {
             max_hp: 1,
         };
         let projectile = Projectile {
+            id: 0,
+            source: Source::Endless { wave: 1 },
             x: 0,
             y: 0,
-            pattern: vec![(1, 1)],
+            pattern: vec![(1, 1)].into(),
             step: 0,
             active: false,
         };
@@ -80,9 +112,11 @@ mod tests {
             max_hp: 1,
         };
         let projectile = Projectile {
+            id: 0,
+            source: Source::Endless { wave: 1 },
             x: 3,
             y: 0,
-            pattern: vec![(1, 1)],
+            pattern: vec![(1, 1)].into(),
             step: 0,
             active: true,
         };
@@ -98,9 +132,11 @@ mod tests {
             max_hp: 1,
         };
         let projectile = Projectile {
+            id: 0,
+            source: Source::Endless { wave: 1 },
             x: 0,
             y: 3,
-            pattern: vec![(1, 1)],
+            pattern: vec![(1, 1)].into(),
             step: 0,
             active: true,
         };
@@ -116,9 +152,11 @@ mod tests {
             max_hp: 1,
         };
         let projectile = Projectile {
+            id: 0,
+            source: Source::Endless { wave: 1 },
             x: 0,
             y: 0,
-            pattern: vec![(1, 1)],
+            pattern: vec![(1, 1)].into(),
             step: 0,
             active: true,
         };
@@ -128,9 +166,11 @@ mod tests {
     #[test]
     fn update_projectile_does_nothing_when_inactive() {
         let mut projectile = Projectile {
+            id: 0,
+            source: Source::Endless { wave: 1 },
             x: 0,
             y: 0,
-            pattern: vec![(1, 1), (-1, -1)],
+            pattern: vec![(1, 1), (-1, -1)].into(),
             step: 0,
             active: false,
         };
@@ -142,9 +182,11 @@ mod tests {
     #[test]
     fn update_projectile_updates_x_y_and_step() {
         let mut projectile = Projectile {
+            id: 0,
+            source: Source::Endless { wave: 1 },
             x: 2,
             y: 2,
-            pattern: vec![(1, 1), (-1, -1)],
+            pattern: vec![(1, 1), (-1, -1)].into(),
             step: 0,
             active: true,
         };
@@ -163,9 +205,11 @@ mod tests {
     #[test]
     fn update_projectile_step_cyclic_mod() {
         let mut projectile = Projectile {
+            id: 0,
+            source: Source::Endless { wave: 1 },
             x: 0,
             y: 0,
-            pattern: vec![(1, 1), (0, 1), (1, 0)],
+            pattern: vec![(1, 1), (0, 1), (1, 0)].into(),
             step: 0,
             active: true,
         };
@@ -180,9 +224,11 @@ mod tests {
     #[test]
     fn update_projectile_clamp_x_y_small_case() {
         let mut projectile = Projectile {
+            id: 0,
+            source: Source::Endless { wave: 1 },
             x: 0,
             y: 0,
-            pattern: vec![(-10, -10), (1, 1)],
+            pattern: vec![(-10, -10), (1, 1)].into(),
             step: 0,
             active: true,
         };
@@ -197,9 +243,11 @@ mod tests {
     #[test]
     fn update_projectile_clamp_x_y_big_case() {
         let mut projectile = Projectile {
+            id: 0,
+            source: Source::Endless { wave: 1 },
             x: MAP_WIDTH + 10,
             y: MAP_HEIGHT + 10,
-            pattern: vec![(1, 1), (-1, -1)],
+            pattern: vec![(1, 1), (-1, -1)].into(),
             step: 0,
             active: true,
         };
@@ -1,5 +1,134 @@
-use crate::game::{MAP_HEIGHT, MAP_WIDTH, Projectile}
+use crate::game::{MAP_HEIGHT, MAP_WIDTH, Projectile, Source};
+use rand::Rng;
+use std::sync::Arc;
 
 // Possible starting points: anywhere in an edge, because starting in the middle of the grid is unfair, it might be too close to the player
 // Patterns are always (x,y) with x between -1,1 and y between -1,1, because larger strides would be too hard too.
-// The amount of patterns can vary maybe between 1 to 20?
\ No newline at end of file
+// The amount of patterns can vary maybe between 1 to 20?
+const MAX_PATTERN_LEN: usize = 20;
+
+/// Standing still is left out, a projectile that never moves is just a wall.
+const DIRECTIONS: [(i8, i8); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Patterns generated once per run, so endless spawns share them instead of
+/// allocating a fresh one per projectile.
+pub const PATTERN_BANK_SIZE: usize = 64;
+
+#[derive(Debug, Clone)]
+pub struct PatternBank {
+    patterns: Vec<Arc<[(i8, i8)]>>,
+}
+
+impl PatternBank {
+    pub fn random<R: Rng>(size: usize, rng: &mut R) -> Self {
+        Self {
+            patterns: (0..size.max(1)).map(|_| random_pattern(rng)).collect(),
+        }
+    }
+
+    pub fn pick<R: Rng>(&self, rng: &mut R) -> Arc<[(i8, i8)]> {
+        Arc::clone(&self.patterns[rng.random_range(0..self.patterns.len())])
+    }
+}
+
+pub fn random_pattern<R: Rng>(rng: &mut R) -> Arc<[(i8, i8)]> {
+    let len = rng.random_range(1..=MAX_PATTERN_LEN);
+    (0..len)
+        .map(|_| DIRECTIONS[rng.random_range(0..DIRECTIONS.len())])
+        .collect()
+}
+
+pub fn random_projectile<R: Rng>(
+    id: u32,
+    source: Source,
+    patterns: &PatternBank,
+    rng: &mut R,
+) -> Projectile {
+    let (x, y) = random_edge_cell(rng);
+
+    Projectile {
+        id,
+        source,
+        x,
+        y,
+        pattern: patterns.pick(rng),
+        step: 0,
+        active: true,
+    }
+}
+
+/// A cell on the border of the playable area (the map minus its walls).
+fn random_edge_cell<R: Rng>(rng: &mut R) -> (u16, u16) {
+    let (min_x, max_x) = (1, MAP_WIDTH - 2);
+    let (min_y, max_y) = (1, MAP_HEIGHT - 2);
+    match rng.random_range(0..4) {
+        0 => (rng.random_range(min_x..=max_x), min_y),
+        1 => (rng.random_range(min_x..=max_x), max_y),
+        2 => (min_x, rng.random_range(min_y..=max_y)),
+        _ => (max_x, rng.random_range(min_y..=max_y)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn random_projectile_starts_on_an_edge() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let patterns = PatternBank::random(PATTERN_BANK_SIZE, &mut rng);
+        for _ in 0..200 {
+            let p = random_projectile(0, Source::Endless { wave: 1 }, &patterns, &mut rng);
+            let on_edge = p.x == 1 || p.x == MAP_WIDTH - 2 || p.y == 1 || p.y == MAP_HEIGHT - 2;
+            assert!(on_edge, "({}, {}) is not on an edge", p.x, p.y);
+            assert!(p.x >= 1 && p.x <= MAP_WIDTH - 2);
+            assert!(p.y >= 1 && p.y <= MAP_HEIGHT - 2);
+        }
+    }
+
+    #[test]
+    fn random_pattern_is_short_and_unit_sized() {
+        let mut rng = StdRng::seed_from_u64(6);
+        for _ in 0..200 {
+            let pattern = random_pattern(&mut rng);
+            assert!(!pattern.is_empty() && pattern.len() <= MAX_PATTERN_LEN);
+            for &(dx, dy) in pattern.iter() {
+                assert!((-1..=1).contains(&dx) && (-1..=1).contains(&dy));
+                assert_ne!((dx, dy), (0, 0));
+            }
+        }
+    }
+
+    #[test]
+    fn random_projectile_starts_active_at_step_zero() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let patterns = PatternBank::random(1, &mut rng);
+        let p = random_projectile(0, Source::Endless { wave: 1 }, &patterns, &mut rng);
+        assert!(p.active);
+        assert_eq!(p.step, 0);
+    }
+
+    #[test]
+    fn pattern_bank_hands_out_shared_patterns() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let patterns = PatternBank::random(4, &mut rng);
+        assert_eq!(patterns.patterns.len(), 4);
+        assert_eq!(PatternBank::random(0, &mut rng).patterns.len(), 1);
+
+        let picked: Vec<_> = (0..50).map(|_| patterns.pick(&mut rng)).collect();
+        for pattern in &picked {
+            assert!(patterns.patterns.iter().any(|p| Arc::ptr_eq(p, pattern)));
+        }
+    }
+}
@@ -25,7 +25,13 @@ pub fn restore_terminal() -> io::Result<()> {
     Ok(())
 }
 
-pub fn draw_game(player: &Player, projectiles: &[Projectile]) -> io::Result<()> {
+pub fn draw_game(
+    player: &Player,
+    projectiles: &[Projectile],
+    score: u32,
+    mode_hud: Option<&str>,
+    debug_overlay: &[String],
+) -> io::Result<()> {
     let mut stdout = io::stdout();
 
     execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
@@ -59,12 +65,28 @@ pub fn draw_game(player: &Player, projectiles: &[Projectile]) -> io::Result<()>
         MoveTo(2, MAP_HEIGHT),
         Print(format!("HP: {}/{}", player.hp, player.max_hp))
     )?;
+    execute!(
+        stdout,
+        MoveTo(16, MAP_HEIGHT),
+        Print(format!("Score: {}", score))
+    )?;
 
     let bar_len = 10;
     let filled = ((player.hp as usize * bar_len) / player.max_hp as usize).min(bar_len);
     let bar = "█".repeat(filled) + &"░".repeat(bar_len - filled);
     execute!(stdout, MoveTo(2, MAP_HEIGHT + 1), Print(bar))?;
 
+    if let Some(hud) = mode_hud {
+        execute!(stdout, MoveTo(2, MAP_HEIGHT + 2), Print(hud))?;
+    }
+
+    // Debug overlay, below the HUD
+    execute!(stdout, SetForegroundColor(Color::DarkGrey))?;
+    for (row, line) in debug_overlay.iter().enumerate() {
+        execute!(stdout, MoveTo(2, MAP_HEIGHT + 3 + row as u16), Print(line))?;
+    }
+    execute!(stdout, SetForegroundColor(Color::White))?;
+
     stdout.flush()?;
     Ok(())
 }
@@ -0,0 +1,305 @@
+use crate::game::{Player, ProtoProjectile, Projectile, Source};
+use crate::modes::endless_spawn;
+use crate::player::{create_player, damage_player, move_player};
+use crate::pool::ProjectilePool;
+use crate::projectile::{check_collision, create_projectiles_from_blueprints, update_projectile};
+use crate::projectile_gen::{PATTERN_BANK_SIZE, PatternBank};
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+/// Every move a player can make in a single tick, "stay" first.
+pub const MOVES: [(i16, i16); 5] = [(0, 0), (0, -1), (0, 1), (-1, 0), (1, 0)];
+
+/// Damage a projectile deals when it hits the player.
+pub const HIT_DAMAGE: u16 = 1;
+
+/// A projectile hitting the player, and where.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub projectile_id: u32,
+    pub source: Source,
+    pub x: u16,
+    pub y: u16,
+    pub damage: u16,
+}
+
+/// Advances the world by one tick: projectiles hitting the player deal damage and
+/// disappear, then every projectile moves. Returns the hits the player took.
+pub fn step(player: &mut Player, projectiles: &mut [Projectile]) -> Vec<Hit> {
+    let mut hits = Vec::new();
+    for projectile in projectiles.iter_mut() {
+        if check_collision(player, projectile) {
+            damage_player(player, HIT_DAMAGE);
+            projectile.active = false;
+            hits.push(Hit {
+                projectile_id: projectile.id,
+                source: projectile.source.clone(),
+                x: projectile.x,
+                y: projectile.y,
+                damage: HIT_DAMAGE,
+            });
+        }
+    }
+
+    for projectile in projectiles.iter_mut() {
+        update_projectile(projectile);
+    }
+    hits
+}
+
+/// Simple automated players used to estimate how hard a stage is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bot {
+    /// Picks any move uniformly at random.
+    Random,
+    /// Picks the move that collides with the fewest projectiles now and on the next tick.
+    GreedyDodge,
+}
+
+pub fn choose_move<R: Rng>(
+    bot: Bot,
+    player: &Player,
+    projectiles: &[Projectile],
+    rng: &mut R,
+) -> (i16, i16) {
+    match bot {
+        Bot::Random => MOVES[rng.random_range(0..MOVES.len())],
+        Bot::GreedyDodge => *MOVES
+            .iter()
+            .min_by_key(|(dx, dy)| danger_after_move(player, projectiles, *dx, *dy))
+            .unwrap(),
+    }
+}
+
+fn danger_after_move(player: &Player, projectiles: &[Projectile], dx: i16, dy: i16) -> u32 {
+    let mut moved = player.clone();
+    move_player(&mut moved, dx, dy);
+
+    let mut danger = 0;
+    for projectile in projectiles {
+        if check_collision(&moved, projectile) {
+            danger += 10;
+        }
+        let mut next = projectile.clone();
+        update_projectile(&mut next);
+        if check_collision(&moved, &next) {
+            danger += 1;
+        }
+    }
+    danger
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RunOutcome {
+    /// Ticks played until the player died, or `max_ticks` if they survived.
+    pub ticks_survived: u32,
+    pub died: bool,
+    pub hits: Vec<Hit>,
+}
+
+/// Plays a stage without a terminal, with `bot` in control of the player.
+pub fn run_headless<R: Rng>(
+    blueprints: &[ProtoProjectile],
+    bot: Bot,
+    max_ticks: u32,
+    rng: &mut R,
+) -> RunOutcome {
+    let mut player = create_player();
+    let mut projectiles = create_projectiles_from_blueprints(blueprints.to_vec());
+    let mut hits = Vec::new();
+
+    for tick in 1..=max_ticks {
+        let (dx, dy) = choose_move(bot, &player, &projectiles, rng);
+        move_player(&mut player, dx, dy);
+        hits.extend(step(&mut player, &mut projectiles));
+
+        if player.hp == 0 {
+            return RunOutcome {
+                ticks_survived: tick,
+                died: true,
+                hits,
+            };
+        }
+    }
+
+    RunOutcome {
+        ticks_survived: max_ticks,
+        died: false,
+        hits,
+    }
+}
+
+/// How fast the endless mode runs without a terminal, and how much the pool grew.
+#[derive(Debug)]
+pub struct BenchReport {
+    pub ticks: u32,
+    pub elapsed: Duration,
+    /// Projectiles spawned over the whole run.
+    pub spawned: u32,
+    /// Pool slots allocated for them, spawns beyond this count reused a slot.
+    pub slots: usize,
+    /// Projectiles still active when the run ended.
+    pub active: usize,
+    pub deaths: u32,
+}
+
+/// Plays `ticks` ticks of endless mode with `bot` in control, bringing the player
+/// back at full HP whenever they die so the waves keep growing.
+pub fn bench_endless<R: Rng>(bot: Bot, ticks: u32, rng: &mut R) -> BenchReport {
+    let mut player = create_player();
+    let mut pool = ProjectilePool::new();
+    let patterns = PatternBank::random(PATTERN_BANK_SIZE, rng);
+    let mut next_id = 0;
+    let mut deaths = 0;
+
+    let start = Instant::now();
+    for tick in 1..=ticks {
+        endless_spawn(tick, &mut pool, &mut next_id, 1.0, &patterns, rng);
+        let (dx, dy) = choose_move(bot, &player, pool.projectiles(), rng);
+        move_player(&mut player, dx, dy);
+        step(&mut player, pool.projectiles_mut());
+
+        if player.hp == 0 {
+            deaths += 1;
+            player = create_player();
+        }
+    }
+
+    BenchReport {
+        ticks,
+        elapsed: start.elapsed(),
+        spawned: next_id,
+        slots: pool.slot_count(),
+        active: pool.active_count(),
+        deaths,
+    }
+}
+
+pub fn format_bench(report: &BenchReport) -> String {
+    let millis = report.elapsed.as_secs_f64() * 1000.0;
+    format!(
+        "{} ticks in {:.1} ms ({:.1} us/tick)\n\
+         Spawned {} projectiles into {} slots, {} still active\n\
+         Deaths: {}\n",
+        report.ticks,
+        millis,
+        millis * 1000.0 / report.ticks.max(1) as f64,
+        report.spawned,
+        report.slots,
+        report.active,
+        report.deaths
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn projectile_at(x: u16, y: u16, pattern: Vec<(i8, i8)>) -> Projectile {
+        Projectile {
+            id: 7,
+            source: Source::Endless { wave: 2 },
+            x,
+            y,
+            pattern: pattern.into(),
+            step: 0,
+            active: true,
+        }
+    }
+
+    #[test]
+    fn step_damages_player_and_deactivates_projectile() {
+        let mut player = create_player();
+        let mut projectiles = vec![projectile_at(player.x, player.y, vec![(1, 0)])];
+
+        let hits = step(&mut player, &mut projectiles);
+
+        assert_eq!(
+            hits,
+            vec![Hit {
+                projectile_id: 7,
+                source: Source::Endless { wave: 2 },
+                x: 20,
+                y: 10,
+                damage: 1,
+            }]
+        );
+        assert_eq!(player.hp, 4);
+        assert!(!projectiles[0].active);
+    }
+
+    #[test]
+    fn step_moves_projectiles_that_miss() {
+        let mut player = create_player();
+        let mut projectiles = vec![projectile_at(2, 2, vec![(1, 0)])];
+
+        let hits = step(&mut player, &mut projectiles);
+
+        assert!(hits.is_empty());
+        assert_eq!(player.hp, 5);
+        assert_eq!((projectiles[0].x, projectiles[0].y), (3, 2));
+    }
+
+    #[test]
+    fn greedy_dodge_leaves_cell_about_to_be_hit() {
+        let player = create_player();
+        // Sits right on the player's cell, so staying put is the worst move.
+        let projectiles = vec![projectile_at(player.x, player.y, vec![(0, 0)])];
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let chosen = choose_move(Bot::GreedyDodge, &player, &projectiles, &mut rng);
+
+        assert_ne!(chosen, (0, 0));
+    }
+
+    #[test]
+    fn greedy_dodge_stays_when_nothing_is_around() {
+        let player = create_player();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        assert_eq!(choose_move(Bot::GreedyDodge, &player, &[], &mut rng), (0, 0));
+    }
+
+    #[test]
+    fn run_headless_survives_empty_stage() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let outcome = run_headless(&[], Bot::Random, 50, &mut rng);
+
+        assert_eq!(
+            outcome,
+            RunOutcome {
+                ticks_survived: 50,
+                died: false,
+                hits: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn run_headless_is_reproducible_with_seed() {
+        let blueprints = crate::data::load_blueprints("assets/projectiles.ron");
+
+        let first = run_headless(&blueprints, Bot::Random, 200, &mut StdRng::seed_from_u64(3));
+        let second = run_headless(&blueprints, Bot::Random, 200, &mut StdRng::seed_from_u64(3));
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn bench_endless_reuses_slots_of_spent_projectiles() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let report = bench_endless(Bot::Random, 2000, &mut rng);
+        assert_eq!(report.ticks, 2000);
+        assert!(report.spawned > 0);
+        assert!(report.slots <= report.spawned as usize);
+        assert!(report.active <= report.slots);
+        if report.deaths > 0 {
+            // Every death took hits, and every hit freed a slot for a later spawn
+            assert!(report.slots < report.spawned as usize);
+        }
+        assert!(format_bench(&report).starts_with("2000 ticks in "));
+    }
+}
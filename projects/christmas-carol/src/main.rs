@@ -1,4 +1,40 @@
-fn main() {
+use std::io::{self, Write};
+use std::time::Duration;
+use term_anim::Pacer;
+
+/// The whole song, as sung along with `--sing`.
+const LYRICS: &str = include_str!("lyrics.txt");
+/// Default wait after each sung line, in milliseconds. Verse breaks last twice as long.
+const DEFAULT_TEMPO_MS: u64 = 800;
+
+/// Usage: `christmas-carol [--sing [--tempo <ms per line>] [--instant]]`
+fn main() -> io::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.iter().any(|a| a == "--sing") {
+        print_song();
+        return Ok(());
+    }
+
+    let tempo_ms = match args.iter().position(|a| a == "--tempo") {
+        Some(i) => match args.get(i + 1).and_then(|t| t.parse().ok()) {
+            Some(ms) => ms,
+            None => {
+                eprintln!("--tempo expects the milliseconds to wait after each line");
+                return Ok(());
+            }
+        },
+        None => DEFAULT_TEMPO_MS,
+    };
+    let pacer = if args.iter().any(|a| a == "--instant") { Pacer::Instant } else { Pacer::default() };
+    sing(&mut io::stdout(), pacer, Duration::from_millis(tempo_ms))
+}
+
+/// Prints the lyrics line by line, waiting `tempo` after each one.
+fn sing<W: Write>(out: &mut W, pacer: Pacer, tempo: Duration) -> io::Result<()> {
+    pacer.print_lines(out, LYRICS.lines(), tempo, tempo * 2)
+}
+
+fn print_song() {
     let days = [
         "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth",
         "tenth", "eleventh", "twelfth",
@@ -44,3 +80,17 @@ In a pear tree
         idx += 1;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instant_sing_prints_every_line() {
+        let mut out = Vec::new();
+        sing(&mut out, Pacer::Instant, Duration::from_secs(10)).unwrap();
+        let sung = String::from_utf8(out).unwrap();
+        assert_eq!(sung.lines().collect::<Vec<_>>(), LYRICS.lines().collect::<Vec<_>>());
+        assert!(sung.starts_with("On the first day\n"));
+    }
+}
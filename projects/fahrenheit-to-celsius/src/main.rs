@@ -1,25 +1,68 @@
+mod table;
+mod temperature;
+
 use std::io;
+use temperature::{Temperature, Unit};
 
+/// Usage: `fahrenheit-to-celsius [--table --from -40F --to 120F --step 10 [--markdown]]`
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--table") {
+        if let Err(e) = print_table(&args) {
+            println!("{}", e);
+        }
+        return;
+    }
+
     println!("Enter the temperature");
     
     let mut temperature : String = String::new();
 
     io::stdin().read_line(&mut temperature).expect("Failed to read temperature");
     
-    let mut temperature = temperature.trim().to_string();
-    let unit : Option<char> = temperature.pop();
-    let temperature : f64 = temperature.parse().expect("Failed to parse temperature as integer");
+    let temperature : Temperature = match temperature.parse() {
+        Ok(temperature) => temperature,
+        Err(_) => {
+            println!("Invalid temperature format.");
+            return;
+        }
+    };
 
-    if unit == Some('F') {
-        let converted = (temperature - 32.0) * 5.0/9.0;
-        println!("Conversion to Celsius: {converted}");
-    }
-    else if unit == Some('C') {
-        let converted = (temperature * 5.0/9.0) + 32.0;
-        println!("Conversion to Fahrenheit: {converted}");
+    match temperature.unit {
+        Unit::Fahrenheit => {
+            let converted = temperature.to(Unit::Celsius).value;
+            println!("Conversion to Celsius: {converted}");
+        }
+        Unit::Celsius => {
+            let converted = temperature.to(Unit::Fahrenheit).value;
+            println!("Conversion to Fahrenheit: {converted}");
+        }
+        Unit::Kelvin => {
+            let converted = temperature.to(Unit::Celsius).value;
+            println!("Conversion to Celsius: {converted}");
+        }
     }
-    else {
-        println!("Invalid temperature format.");
+}
+
+fn print_table(args: &[String]) -> Result<(), String> {
+    let value_of = |flag: &str| -> Result<&str, String> {
+        args.iter()
+            .position(|a| a == flag)
+            .and_then(|i| args.get(i + 1))
+            .map(String::as_str)
+            .ok_or_else(|| format!("{} is missing its value", flag))
+    };
+    let from: Temperature = value_of("--from")?.parse()?;
+    let to: Temperature = value_of("--to")?.parse()?;
+    let step: f64 = value_of("--step")?
+        .parse()
+        .map_err(|_| "--step must be a number".to_string())?;
+
+    let rows = table::steps(from, to, step)?;
+    if args.iter().any(|a| a == "--markdown") {
+        print!("{}", table::render_markdown(&rows));
+    } else {
+        print!("{}", table::render(&rows));
     }
+    Ok(())
 }
@@ -0,0 +1,117 @@
+use crate::temperature::{Temperature, Unit};
+
+/// Decimals shown in every cell.
+const PRECISION: usize = 2;
+
+/// Temperatures from `from` to `to`, both included, `step` apart in the unit of `from`.
+pub fn steps(from: Temperature, to: Temperature, step: f64) -> Result<Vec<Temperature>, String> {
+    if step.is_nan() || step <= 0.0 {
+        return Err("The step must be greater than 0".to_string());
+    }
+    let end = to.to(from.unit).value;
+    if end < from.value {
+        return Err(format!("{} is below {}", to, from));
+    }
+
+    // Multiplying instead of adding up the step keeps rounding errors from piling up
+    let count = ((end - from.value) / step + 1e-9).floor() as usize;
+    Ok((0..=count)
+        .map(|i| Temperature::new(from.value + i as f64 * step, from.unit))
+        .collect())
+}
+
+/// One row per temperature with its value in every unit, columns right-aligned.
+pub fn render(rows: &[Temperature]) -> String {
+    let cells = cells(rows);
+    let width = cells
+        .iter()
+        .flatten()
+        .chain(headers().iter())
+        .map(|c| c.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut out = String::new();
+    for row in std::iter::once(headers()).chain(cells) {
+        let line: Vec<String> = row.iter().map(|c| format!("{:>width$}", c)).collect();
+        out.push_str(line.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// The same table as a Markdown table.
+pub fn render_markdown(rows: &[Temperature]) -> String {
+    let mut out = format!("| {} |\n", headers().join(" | "));
+    out.push_str(&format!("|{}\n", "---:|".repeat(Unit::ALL.len())));
+    for row in cells(rows) {
+        out.push_str(&format!("| {} |\n", row.join(" | ")));
+    }
+    out
+}
+
+fn headers() -> Vec<String> {
+    Unit::ALL.iter().map(|u| u.symbol().to_string()).collect()
+}
+
+fn cells(rows: &[Temperature]) -> Vec<Vec<String>> {
+    rows.iter()
+        .map(|t| {
+            Unit::ALL
+                .iter()
+                .map(|&u| format!("{:.*}", PRECISION, t.to(u).value))
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn t(s: &str) -> Temperature {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn steps_include_both_ends() {
+        let rows = steps(t("-40F"), t("120F"), 10.0).unwrap();
+        assert_eq!(rows.len(), 17);
+        assert_eq!(rows[0], t("-40F"));
+        assert_eq!(rows[16], t("120F"));
+    }
+
+    #[test]
+    fn steps_stop_before_passing_the_end() {
+        let rows = steps(t("0C"), t("1C"), 0.3).unwrap();
+        assert_eq!(rows.len(), 4);
+        // The end can be in another unit
+        assert_eq!(steps(t("0C"), t("212F"), 50.0).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn steps_reject_bad_ranges() {
+        assert!(steps(t("0C"), t("10C"), 0.0).is_err());
+        assert!(steps(t("10C"), t("0C"), 1.0).is_err());
+    }
+
+    #[test]
+    fn render_aligns_columns() {
+        let table = render(&steps(t("-40F"), t("-30F"), 10.0).unwrap());
+        assert_eq!(
+            table,
+            "    °C      °F       K\n\
+             -40.00  -40.00  233.15\n\
+             -34.44  -30.00  238.71\n"
+        );
+    }
+
+    #[test]
+    fn render_markdown_table() {
+        let table = render_markdown(&[t("0C")]);
+        assert_eq!(
+            table,
+            "| °C | °F | K |\n|---:|---:|---:|\n| 0.00 | 32.00 | 273.15 |\n"
+        );
+    }
+}
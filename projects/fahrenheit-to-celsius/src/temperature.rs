@@ -0,0 +1,125 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl Unit {
+    pub const ALL: [Unit; 3] = [Unit::Celsius, Unit::Fahrenheit, Unit::Kelvin];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Celsius => "°C",
+            Unit::Fahrenheit => "°F",
+            Unit::Kelvin => "K",
+        }
+    }
+
+    fn from_letter(letter: char) -> Option<Unit> {
+        match letter.to_ascii_uppercase() {
+            'C' => Some(Unit::Celsius),
+            'F' => Some(Unit::Fahrenheit),
+            'K' => Some(Unit::Kelvin),
+            _ => None,
+        }
+    }
+}
+
+/// A temperature in any unit, e.g. parsed from "-40F" or "21.5C".
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Temperature {
+    pub value: f64,
+    pub unit: Unit,
+}
+
+impl Temperature {
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Self { value, unit }
+    }
+
+    fn to_celsius(self) -> f64 {
+        match self.unit {
+            Unit::Celsius => self.value,
+            Unit::Fahrenheit => (self.value - 32.0) * 5.0 / 9.0,
+            Unit::Kelvin => self.value - 273.15,
+        }
+    }
+
+    /// The same temperature in `unit`.
+    pub fn to(self, unit: Unit) -> Temperature {
+        let celsius = self.to_celsius();
+        let value = match unit {
+            Unit::Celsius => celsius,
+            Unit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+            Unit::Kelvin => celsius + 273.15,
+        };
+        Temperature::new(value, unit)
+    }
+}
+
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Honor the caller's precision, e.g. `{:.1}`
+        match f.precision() {
+            Some(precision) => write!(f, "{:.*}{}", precision, self.value, self.unit.symbol()),
+            None => write!(f, "{}{}", self.value, self.unit.symbol()),
+        }
+    }
+}
+
+impl FromStr for Temperature {
+    type Err = String;
+
+    /// A number followed by its unit letter: "-40F", "21.5c", "300K".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let letter = s.chars().last().ok_or("Empty temperature")?;
+        let unit = Unit::from_letter(letter)
+            .ok_or_else(|| format!("'{}' doesn't end with a unit (C, F or K)", s))?;
+        let number = &s[..s.len() - letter.len_utf8()];
+        let value = number
+            .trim()
+            .parse()
+            .map_err(|_| format!("'{}' is not a number", number))?;
+        Ok(Temperature::new(value, unit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Temperature, value: f64, unit: Unit) {
+        assert_eq!(actual.unit, unit);
+        assert!((actual.value - value).abs() < 1e-9, "{} != {}", actual.value, value);
+    }
+
+    #[test]
+    fn converts_between_every_unit() {
+        let boiling = Temperature::new(100.0, Unit::Celsius);
+        assert_close(boiling.to(Unit::Fahrenheit), 212.0, Unit::Fahrenheit);
+        assert_close(boiling.to(Unit::Kelvin), 373.15, Unit::Kelvin);
+        assert_close(Temperature::new(-40.0, Unit::Fahrenheit).to(Unit::Celsius), -40.0, Unit::Celsius);
+        assert_close(Temperature::new(0.0, Unit::Kelvin).to(Unit::Fahrenheit), -459.67, Unit::Fahrenheit);
+    }
+
+    #[test]
+    fn parses_value_and_unit() {
+        assert_eq!("-40F".parse(), Ok(Temperature::new(-40.0, Unit::Fahrenheit)));
+        assert_eq!(" 21.5c".parse(), Ok(Temperature::new(21.5, Unit::Celsius)));
+        assert_eq!("300 K".parse(), Ok(Temperature::new(300.0, Unit::Kelvin)));
+        assert!("40".parse::<Temperature>().is_err());
+        assert!("F".parse::<Temperature>().is_err());
+        assert!("".parse::<Temperature>().is_err());
+    }
+
+    #[test]
+    fn display_uses_the_unit_symbol() {
+        assert_eq!(format!("{:.1}", Temperature::new(-12.222, Unit::Celsius)), "-12.2°C");
+        assert_eq!(Temperature::new(300.0, Unit::Kelvin).to_string(), "300K");
+    }
+}
@@ -0,0 +1,120 @@
+/// The limit of the ratio between consecutive Fibonacci numbers.
+pub const GOLDEN_RATIO: f64 = 1.618_033_988_749_895;
+
+/// The Fibonacci numbers from F(1) = 1, F(2) = 1 on, for as long as they fit in a `u64`.
+pub fn fibonacci_numbers() -> impl Iterator<Item = u64> {
+    // Each pair is a number and the next one, if that one still fits
+    std::iter::successors(Some((1u64, Some(1u64))), |&(current, next)| {
+        let next = next?;
+        Some((next, current.checked_add(next)))
+    })
+    .map(|(current, _)| current)
+}
+
+/// F(n), counting from F(1) = 1. `None` for n = 0 or when it doesn't fit in a `u64`.
+pub fn fibonacci(n: usize) -> Option<u64> {
+    n.checked_sub(1).and_then(|i| fibonacci_numbers().nth(i))
+}
+
+/// F(n + 1) / F(n), which gets closer to the golden ratio as n grows.
+pub fn ratio(n: usize) -> Option<f64> {
+    Some(fibonacci(n + 1)? as f64 / fibonacci(n)? as f64)
+}
+
+/// Writes `x` as a sum of non-consecutive Fibonacci numbers, largest first. Every
+/// positive integer has exactly one such representation, 0 has an empty one.
+pub fn zeckendorf(x: u64) -> Vec<u64> {
+    // F(1) and F(2) are both 1, only one of them can be used
+    let mut candidates: Vec<u64> = fibonacci_numbers().skip(1).take_while(|&f| f <= x).collect();
+    let mut terms = Vec::new();
+    let mut rest = x;
+    while let Some(f) = candidates.pop() {
+        if f <= rest {
+            terms.push(f);
+            rest -= f;
+            // Taking the largest one that fits never leaves room for its neighbour
+            candidates.pop();
+        }
+    }
+    terms
+}
+
+/// The index n of the largest F(n) that is at most `x`, at least 1.
+pub fn largest_index_up_to(x: u64) -> usize {
+    fibonacci_numbers().take_while(|&f| f <= x).count().max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Small deterministic generator so the property tests cover big numbers too.
+    fn samples() -> impl Iterator<Item = u64> {
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        (0..2_000u64).chain(std::iter::repeat_with(move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        }).take(2_000)).chain([u64::MAX])
+    }
+
+    #[test]
+    fn fibonacci_starts_at_one_one() {
+        let first: Vec<u64> = fibonacci_numbers().take(8).collect();
+        assert_eq!(first, vec![1, 1, 2, 3, 5, 8, 13, 21]);
+        assert_eq!(fibonacci(0), None);
+        assert_eq!(fibonacci(10), Some(55));
+    }
+
+    #[test]
+    fn fibonacci_stops_before_overflowing() {
+        assert_eq!(fibonacci_numbers().count(), 93);
+        assert_eq!(fibonacci(93), Some(12_200_160_415_121_876_738));
+        assert_eq!(fibonacci(94), None);
+    }
+
+    #[test]
+    fn ratio_approaches_the_golden_ratio() {
+        assert_eq!(ratio(1), Some(1.0));
+        assert_eq!(ratio(4), Some(5.0 / 3.0));
+        assert!((ratio(40).unwrap() - GOLDEN_RATIO).abs() < 1e-12);
+        assert_eq!(ratio(93), None);
+    }
+
+    #[test]
+    fn zeckendorf_known_values() {
+        assert_eq!(zeckendorf(0), Vec::<u64>::new());
+        assert_eq!(zeckendorf(1), vec![1]);
+        assert_eq!(zeckendorf(4), vec![3, 1]);
+        assert_eq!(zeckendorf(100), vec![89, 8, 3]);
+    }
+
+    #[test]
+    fn zeckendorf_sums_back_to_x() {
+        for x in samples() {
+            assert_eq!(zeckendorf(x).iter().sum::<u64>(), x, "x = {}", x);
+        }
+    }
+
+    #[test]
+    fn zeckendorf_uses_distinct_non_consecutive_fibonacci_numbers() {
+        let fibs: Vec<u64> = fibonacci_numbers().skip(1).collect();
+        for x in samples() {
+            let indexes: Vec<usize> = zeckendorf(x)
+                .iter()
+                .map(|f| fibs.iter().position(|g| g == f).expect("not a Fibonacci number"))
+                .collect();
+            assert!(indexes.windows(2).all(|w| w[0] >= w[1] + 2), "x = {}: {:?}", x, indexes);
+        }
+    }
+
+    #[test]
+    fn largest_index_brackets_x() {
+        for x in samples().filter(|&x| x > 0) {
+            let n = largest_index_up_to(x);
+            assert!(fibonacci(n).unwrap() <= x);
+            assert!(fibonacci(n + 1).is_none_or(|next| next > x), "x = {}", x);
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use fibonacci::{fibonacci, fibonacci_numbers, largest_index_up_to, ratio, zeckendorf, GOLDEN_RATIO};
+
 fn n_th_fibonacci_number(n: i32) -> i32 {
     let mut left = 1;
     let mut right = 1;
@@ -14,6 +16,37 @@ fn n_th_fibonacci_number(n: i32) -> i32 {
     right
 }
 
+/// Usage: `fibonacci [--analyze X]`
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(i) = args.iter().position(|a| a == "--analyze") {
+        match args.get(i + 1).map(|x| x.parse::<u64>()) {
+            Some(Ok(x)) => analyze(x),
+            _ => println!("--analyze needs a non-negative integer"),
+        }
+        return;
+    }
+
     println!("{}", n_th_fibonacci_number(8));
 }
+
+fn analyze(x: u64) {
+    let terms = zeckendorf(x);
+    let sum: Vec<String> = terms.iter().map(|t| t.to_string()).collect();
+    println!("Zeckendorf: {} = {}", x, if sum.is_empty() { "0".to_string() } else { sum.join(" + ") });
+
+    // The ratio around the largest Fibonacci number up to x, the last one has no successor
+    let n = largest_index_up_to(x).min(fibonacci_numbers().count() - 1);
+    let (current, next) = (fibonacci(n).unwrap(), fibonacci(n + 1).unwrap());
+    let ratio = ratio(n).unwrap();
+    println!(
+        "Ratio: F({})/F({}) = {}/{} = {:.12} (golden ratio {:.12}, off by {:.3e})",
+        n + 1,
+        n,
+        next,
+        current,
+        ratio,
+        GOLDEN_RATIO,
+        (ratio - GOLDEN_RATIO).abs()
+    );
+}
@@ -0,0 +1,150 @@
+// benches/battle.rs
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rinha_de_neopets::battle::{
+    Action, BattleConfig, BattleEvent, BattleState, battle_loop, battle_loop_with_config, process_turn_with_state,
+};
+use rinha_de_neopets::free_for_all::free_for_all;
+use rinha_de_neopets::neopets::Neopet;
+use rinha_de_neopets::storage::{BattleRecord, Storage};
+use std::hint::black_box;
+use tempfile::tempdir;
+
+/// Battles stored for the storage benchmarks.
+const STORED_BATTLES: usize = 10_000;
+
+/// Turn limits the long battle benchmarks are run with.
+const MAX_TURNS: [u32; 3] = [10, 50, 200];
+
+/// Fighter counts the free-for-all benchmarks are run with.
+const FIGHTER_COUNTS: [usize; 4] = [2, 4, 8, 16];
+
+/// Built from JSON like the stored fighters, so stats added later take their defaults.
+fn fighter(name: &str) -> Neopet {
+    serde_json::from_value(serde_json::json!({
+        "name": name,
+        "health": 100,
+        "heal_delta": 10,
+        "base_attack": 8,
+        "base_defense": 4,
+        "spells": [],
+        "behavior": { "attack_chance": 0.7, "spell_chances": [], "heal_chance": 0.3 },
+    }))
+    .expect("bench fighter is valid")
+}
+
+/// Two fighters sturdy enough to last `max_turns`, and a config without fatigue to
+/// let them.
+fn long_battle(max_turns: u32) -> (Neopet, Neopet, BattleConfig) {
+    let sturdy = |name| Neopet { health: 20 * max_turns, ..fighter(name) };
+    let config = BattleConfig { max_turns, fatigue_start_turn: None, ..BattleConfig::default() };
+    (sturdy("Acara"), sturdy("Usul"), config)
+}
+
+fn battle_record(id: usize, events: Vec<BattleEvent>) -> BattleRecord {
+    BattleRecord {
+        events,
+        winner: Some("Acara".to_string()),
+        is_completed: true,
+        ..BattleRecord::pending(format!("battle_{}", id), "Acara", "Usul", "2024-01-01T00:00:00Z")
+    }
+}
+
+fn bench_battle_loop(c: &mut Criterion) {
+    let (acara, usul) = (fighter("Acara"), fighter("Usul"));
+    let mut rng = StdRng::seed_from_u64(42);
+    c.bench_function("battle_loop", |b| {
+        b.iter(|| battle_loop(black_box(&acara), black_box(&usul), &mut rng).unwrap())
+    });
+
+    let mut group = c.benchmark_group("battle_loop/max_turns");
+    for max_turns in MAX_TURNS {
+        let (acara, usul, config) = long_battle(max_turns);
+        group.bench_with_input(BenchmarkId::from_parameter(max_turns), &config, |b, config| {
+            b.iter(|| battle_loop_with_config(black_box(&acara), black_box(&usul), config, &mut rng).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_free_for_all(c: &mut Criterion) {
+    let config = BattleConfig::default();
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut group = c.benchmark_group("free_for_all/fighters");
+    for count in FIGHTER_COUNTS {
+        let fighters: Vec<Neopet> = (0..count).map(|i| fighter(&format!("Fighter{}", i))).collect();
+        group.bench_with_input(BenchmarkId::from_parameter(count), &fighters, |b, fighters| {
+            b.iter(|| free_for_all(black_box(fighters), &config, &mut rng).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_process_turn(c: &mut Criterion) {
+    let (acara, usul) = (fighter("Acara"), fighter("Usul"));
+    let config = BattleConfig::default();
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut group = c.benchmark_group("process_turn_with_state");
+    for (name, action) in [("attack", Action::Attack), ("heal", Action::Heal), ("defend", Action::Defend)] {
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || BattleState::new(&acara, &usul, 10),
+                |mut state| {
+                    process_turn_with_state(&acara, &usul, &action, 1, &mut state, &config, &mut rng).unwrap()
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_event_serialization(c: &mut Criterion) {
+    let mut group = c.benchmark_group("events");
+    for max_turns in MAX_TURNS {
+        let (acara, usul, config) = long_battle(max_turns);
+        let events = battle_loop_with_config(&acara, &usul, &config, &mut StdRng::seed_from_u64(42)).unwrap();
+        let json = serde_json::to_string(&events).unwrap();
+
+        group.throughput(Throughput::Elements(events.len() as u64));
+        group.bench_with_input(BenchmarkId::new("serialize", max_turns), &events, |b, events| {
+            b.iter(|| serde_json::to_string(black_box(events)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("deserialize", max_turns), &json, |b, json| {
+            b.iter(|| serde_json::from_str::<Vec<BattleEvent>>(black_box(json)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_storage(c: &mut Criterion) {
+    let temp_dir = tempdir().unwrap();
+    let neopets_path = temp_dir.path().join("neopets.json");
+    let battles_path = temp_dir.path().join("complete_battles.json");
+    let (neopets_path, battles_path) = (neopets_path.to_str().unwrap(), battles_path.to_str().unwrap());
+
+    let (acara, usul) = (fighter("Acara"), fighter("Usul"));
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut storage = Storage::new(neopets_path, battles_path).unwrap();
+    for id in 0..STORED_BATTLES {
+        storage.add_complete_battle(battle_record(id, battle_loop(&acara, &usul, &mut rng).unwrap())).unwrap();
+    }
+    storage.save().unwrap();
+
+    let mut group = c.benchmark_group("storage_10k_battles");
+    group.sample_size(10);
+    group.bench_function("save", |b| b.iter(|| storage.save().unwrap()));
+    group.bench_function("load", |b| b.iter(|| Storage::new(neopets_path, battles_path).unwrap()));
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_battle_loop,
+    bench_free_for_all,
+    bench_process_turn,
+    bench_event_serialization,
+    bench_storage
+);
+criterion_main!(benches);
@@ -27,6 +27,95 @@ macro_rules! count_args {
     };
 }
 
+/// Asserts that an event is the expected variant and that the listed fields have
+/// the expected values, the other fields are ignored:
+///
+/// ```ignore
+/// event_match!(events[2], Attack { actual_damage: 11, actor: "Alice" });
+/// ```
+///
+/// The variant defaults to a `BattleEvent` one, give the enum to match anything
+/// else, e.g. `event_match!(reason, Outcome::Knockout { winner: "Alice" })`. Tuple
+/// variants name their fields by position, `event_match!(reason, Outcome::Draw { 0: 5 })`.
+/// Failures name the field and print the whole event.
+#[macro_export]
+macro_rules! event_match {
+    ($event:expr, $enum:ident :: $variant:ident { $( $field:tt : $value:expr ),* $(,)? }) => {{
+        let event = &$event;
+        if !matches!(event, $enum::$variant { .. }) {
+            panic!("Expected {}::{} but got {:?}", stringify!($enum), stringify!($variant), event);
+        }
+        $(
+            // One match per field keeps `actual` from shadowing names used in the values
+            if let $enum::$variant { $field: actual, .. } = event {
+                assert_eq!(*actual, $value, "`{}` of {:?}", stringify!($field), event);
+            }
+        )*
+    }};
+    ($event:expr, $variant:ident { $( $field:tt : $value:expr ),* $(,)? }) => {
+        $crate::event_match!($event, BattleEvent::$variant { $( $field: $value ),* })
+    };
+}
+
+/// Declares a config struct whose fields each come with their default value:
+///
+/// ```ignore
+/// config_struct! {
+///     #[derive(Debug, Clone)]
+///     pub struct Settings with overrides SettingsOverrides {
+///         /// Milliseconds between frames
+///         pub delay_ms: u64 = 50,
+///         pub colors: bool = true,
+///     }
+/// }
+/// ```
+///
+/// Besides the struct this generates its `Default`, serde derives (missing fields
+/// are filled in from the defaults) and an overrides struct with every field
+/// optional, applied with `merge`. The caller needs `serde` as a dependency.
+#[macro_export]
+macro_rules! config_struct {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident with overrides $overrides:ident {
+            $( $(#[$field_meta:meta])* $field_vis:vis $field:ident : $ty:ty = $default:expr ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(serde::Serialize, serde::Deserialize)]
+        #[serde(default)]
+        $vis struct $name {
+            $( $(#[$field_meta])* $field_vis $field: $ty, )*
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self { $( $field: $default, )* }
+            }
+        }
+
+        #[doc = concat!("Fields to change in a `", stringify!($name), "`, `None` keeps the current value.")]
+        #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+        $vis struct $overrides {
+            $(
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+                $field_vis $field: Option<$ty>,
+            )*
+        }
+
+        impl $name {
+            /// Replaces every field that `overrides` sets.
+            pub fn merge(&mut self, overrides: $overrides) {
+                $(
+                    if let Some(value) = overrides.$field {
+                        self.$field = value;
+                    }
+                )*
+            }
+        }
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,4 +160,80 @@ mod tests {
     fn trailing_comma() {
         assert_eq!(count_args!("x", "y",), 2);
     }
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    enum BattleEvent {
+        Attack { actor: String, target: String, damage: u32 },
+        Heal { actor: String, amount: u32 },
+        Timeout(u32),
+    }
+
+    fn attack() -> BattleEvent {
+        BattleEvent::Attack { actor: "Alice".to_string(), target: "Bob".to_string(), damage: 11 }
+    }
+
+    #[test]
+    fn event_match_checks_selected_fields() {
+        let events = [attack()];
+        event_match!(events[0], Attack { damage: 11, actor: "Alice" });
+        event_match!(events[0], BattleEvent::Attack { target: "Bob", });
+        // Values may use names that are also fields
+        let damage = 11;
+        event_match!(events[0], Attack { damage: damage });
+    }
+
+    #[test]
+    fn event_match_checks_tuple_fields_by_position() {
+        event_match!(BattleEvent::Timeout(30), Timeout { 0: 30 });
+    }
+
+    #[test]
+    #[should_panic(expected = "`damage` of Attack")]
+    fn event_match_names_the_wrong_field() {
+        event_match!(attack(), Attack { actor: "Alice", damage: 12 });
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected BattleEvent::Heal but got Attack")]
+    fn event_match_rejects_other_variants() {
+        event_match!(attack(), Heal { amount: 10 });
+    }
+
+    config_struct! {
+        #[derive(Debug, Clone, PartialEq)]
+        struct Settings with overrides SettingsOverrides {
+            /// Milliseconds between frames
+            delay_ms: u64 = 50,
+            title: String = "Arena".to_string(),
+            colors: bool = true,
+        }
+    }
+
+    #[test]
+    fn config_struct_defaults_every_field() {
+        let settings = Settings::default();
+        assert_eq!(settings.delay_ms, 50);
+        assert_eq!(settings.title, "Arena");
+        assert!(settings.colors);
+    }
+
+    #[test]
+    fn config_struct_fills_missing_fields_with_defaults() {
+        let settings: Settings = serde_json::from_str(r#"{ "colors": false }"#).unwrap();
+        assert_eq!(settings, Settings { colors: false, ..Settings::default() });
+    }
+
+    #[test]
+    fn config_struct_merge_only_replaces_set_fields() {
+        let mut settings = Settings::default();
+        settings.merge(SettingsOverrides { delay_ms: Some(10), ..Default::default() });
+        assert_eq!(settings, Settings { delay_ms: 10, ..Settings::default() });
+
+        let overrides: SettingsOverrides = serde_json::from_str(r#"{ "title": "Colosseum" }"#).unwrap();
+        settings.merge(overrides);
+        assert_eq!(settings.title, "Colosseum");
+        assert_eq!(settings.delay_ms, 10);
+        assert_eq!(serde_json::to_string(&SettingsOverrides::default()).unwrap(), "{}");
+    }
 }
@@ -0,0 +1,101 @@
+// src/arena.rs
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Where a battle is fought, bending the rules for everyone in it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Arena {
+    pub name: String,
+    pub description: String,
+    /// HP every fighter loses at the start of each of their turns.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub damage_per_turn: u32,
+    /// Percent added to every heal, e.g. 50 heals half as much again and -50 half as much.
+    #[serde(default, skip_serializing_if = "is_zero_percent")]
+    pub heal_bonus_percent: i32,
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+fn is_zero_percent(value: &i32) -> bool {
+    *value == 0
+}
+
+impl Arena {
+    /// HP the arena adds to (or takes off) a heal of `amount`.
+    pub fn heal_bonus(&self, amount: u32) -> i32 {
+        (f64::from(amount) * f64::from(self.heal_bonus_percent) / 100.0).round() as i32
+    }
+}
+
+impl fmt::Display for Arena {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut modifiers = Vec::new();
+        if self.damage_per_turn > 0 {
+            modifiers.push(format!("{} damage per turn", self.damage_per_turn));
+        }
+        if self.heal_bonus_percent != 0 {
+            modifiers.push(format!("heals {:+}%", self.heal_bonus_percent));
+        }
+        if modifiers.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{} ({})", self.name, modifiers.join(", "))
+        }
+    }
+}
+
+/// The arenas battles can be created in, see `assets/arenas.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(transparent)]
+pub struct Arenas {
+    arenas: Vec<Arena>,
+}
+
+impl Arenas {
+    /// Loads the arenas, or none at all if the file doesn't exist.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// The arena called `name`, ignoring case.
+    pub fn get(&self, name: &str) -> Option<&Arena> {
+        self.arenas.iter().find(|arena| arena.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arena> {
+        self.arenas.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arenas_from_json() {
+        let arenas: Arenas = serde_json::from_str(
+            r#"[
+                { "name": "Lava Pit", "description": "Burning floor", "damage_per_turn": 2 },
+                { "name": "Healing Springs", "description": "Soothing water", "heal_bonus_percent": 50 }
+            ]"#,
+        )
+        .unwrap();
+        let lava = arenas.get("lava pit").unwrap();
+        assert_eq!((lava.damage_per_turn, lava.heal_bonus_percent), (2, 0));
+        assert_eq!(arenas.get("Healing Springs").unwrap().heal_bonus(9), 5);
+        assert!(arenas.get("Colosseum").is_none());
+        assert_eq!(arenas.iter().count(), 2);
+        assert_eq!(lava.to_string(), "Lava Pit (2 damage per turn)");
+    }
+}
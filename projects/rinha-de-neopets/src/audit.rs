@@ -0,0 +1,268 @@
+// src/audit.rs
+use crate::battle::BattleEvent;
+use crate::battle_log::BattleLog;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// `tracing` target of the battle state changes, see `BattleState`.
+pub const AUDIT_TARGET: &str = "rinha_de_neopets::audit";
+
+/// One change to a battle's state, with the value before and after it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub kind: String, // "join", "turn", "fatigue", "damage", "heal", "effect" or "complete"
+    pub turn: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fighter: Option<String>, // Who changed, the winner for "complete"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<u32>, // HP, or the number of over-time effects for "effect"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>, // The completion reason as JSON, the spell for "effect"
+}
+
+impl fmt::Display for AuditEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "T{} {}", self.turn, self.kind)?;
+        if let Some(fighter) = &self.fighter {
+            write!(f, " {}", fighter)?;
+        }
+        if let (Some(before), Some(after)) = (self.before, self.after) {
+            write!(f, " {}→{}", before, after)?;
+        }
+        if let Some(detail) = &self.detail {
+            write!(f, " {}", detail)?;
+        }
+        Ok(())
+    }
+}
+
+impl Visit for AuditEntry {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        let value = value as u32;
+        match field.name() {
+            "turn" => self.turn = value,
+            "before" => self.before = Some(value),
+            "after" => self.after = Some(value),
+            _ => {}
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "kind" => self.kind = value.to_string(),
+            "fighter" => self.fighter = Some(value.to_string()),
+            "detail" => self.detail = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record_str(field, &format!("{:?}", value));
+    }
+}
+
+/// Every state change of one battle, in the order they happened.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditTrail {
+    pub battle_id: String,
+    pub entries: Vec<AuditEntry>,
+}
+
+impl AuditTrail {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    /// Each fighter's HP after the last change, rebuilt from the trail alone.
+    pub fn final_hps(&self) -> HashMap<&str, u32> {
+        let mut hps = HashMap::new();
+        let changes_hp = |entry: &&AuditEntry| matches!(entry.kind.as_str(), "join" | "damage" | "heal");
+        for entry in self.entries.iter().filter(changes_hp) {
+            if let (Some(fighter), Some(after)) = (&entry.fighter, entry.after) {
+                hps.insert(fighter.as_str(), after);
+            }
+        }
+        hps
+    }
+
+    /// The fighter the trail says won, `None` if the battle never completed.
+    pub fn winner(&self) -> Option<&str> {
+        self.entries.iter().find(|e| e.kind == "complete").and_then(|e| e.fighter.as_deref())
+    }
+
+    /// Checks that `events` tell the same story as the trail: every HP change shows
+    /// up in both, in the same order and with the same values, and the winner and
+    /// final HPs of the battle are the ones the trail ends with.
+    pub fn verify(&self, events: &[BattleEvent]) -> Result<(), String> {
+        let mut audited = self
+            .entries
+            .iter()
+            .filter(|e| (e.kind == "damage" || e.kind == "heal") && e.before != e.after);
+        let updates = events.iter().filter_map(|e| match e {
+            BattleEvent::HealthUpdate { fighter_name, from, to, turn, .. } if from != to => {
+                Some((*turn, fighter_name, *from, *to))
+            }
+            _ => None,
+        });
+
+        for (i, (turn, fighter, from, to)) in updates.enumerate() {
+            let Some(entry) = audited.next() else {
+                return Err(format!("Turn {}: {} went {}→{} but the trail has no such change", turn, fighter, from, to));
+            };
+            if entry.fighter.as_deref() != Some(&**fighter) || entry.before != Some(from) || entry.after != Some(to) {
+                return Err(format!(
+                    "HP change #{} differs: the events say T{} {} {}→{}, the trail says {}",
+                    i + 1, turn, fighter, from, to, entry
+                ));
+            }
+        }
+        if let Some(entry) = audited.next() {
+            return Err(format!("The events are missing a change the trail recorded: {}", entry));
+        }
+
+        let log = BattleLog::new(events);
+        if log.winner() != self.winner() {
+            return Err(format!(
+                "The events name {} the winner, the trail {}",
+                log.winner().unwrap_or("nobody"),
+                self.winner().unwrap_or("nobody")
+            ));
+        }
+        let final_hps = self.final_hps();
+        for (fighter, hp) in log.final_hps().into_iter().flatten() {
+            if final_hps.get(fighter) != Some(&hp) {
+                return Err(format!("{} ends on {} HP in the events but not in the trail", fighter, hp));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `tracing` subscriber that keeps the battle state changes and ignores everything else.
+struct AuditRecorder {
+    entries: Arc<Mutex<Vec<AuditEntry>>>,
+}
+
+impl Subscriber for AuditRecorder {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == AUDIT_TARGET
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1) // State changes are plain events, never spans
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut entry = AuditEntry::default();
+        event.record(&mut entry);
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push(entry);
+        }
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+/// Runs `f` and returns what it returned along with every battle state change it made
+/// on this thread.
+pub fn record_audit<T>(f: impl FnOnce() -> T) -> (T, Vec<AuditEntry>) {
+    let entries = Arc::new(Mutex::new(Vec::new()));
+    let recorder = AuditRecorder { entries: Arc::clone(&entries) };
+    let result = tracing::subscriber::with_default(recorder, f);
+    let entries = entries.lock().map(|entries| entries.clone()).unwrap_or_default();
+    (result, entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::{battle_loop_with_config, BattleConfig};
+    use crate::neopets::Neopet;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use tempfile::tempdir;
+
+    fn audited_battle(seed: u64) -> (Vec<BattleEvent>, AuditTrail) {
+        let (acara, usul) = (Neopet::test_fighter("Acara"), Neopet::test_fighter("Usul"));
+        // Fatigue on, so its bonus shows up in the trail too
+        let config = BattleConfig { fatigue_start_turn: Some(6), ..BattleConfig::default() };
+        let (events, entries) = record_audit(|| {
+            battle_loop_with_config(&acara, &usul, &config, &mut StdRng::seed_from_u64(seed)).unwrap()
+        });
+        (events, AuditTrail { battle_id: "battle_1".to_string(), entries })
+    }
+
+    #[test]
+    fn test_trail_records_every_state_change() {
+        let (events, trail) = audited_battle(3);
+        let kinds: Vec<&str> = trail.entries.iter().map(|e| e.kind.as_str()).collect();
+        assert_eq!(&kinds[..2], &["join", "join"]);
+        assert_eq!(kinds.last(), Some(&"complete"));
+        assert_eq!(kinds.iter().filter(|k| **k == "complete").count(), 1);
+        assert!(kinds.contains(&"turn") && kinds.contains(&"fatigue"));
+
+        assert_eq!(trail.winner(), BattleLog::new(&events).winner());
+        assert_eq!(trail.verify(&events), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_catches_tampered_events() {
+        for seed in 0..10 {
+            let (mut events, trail) = audited_battle(seed);
+            let Some(BattleEvent::HealthUpdate { to, .. }) =
+                events.iter_mut().find(|e| matches!(e, BattleEvent::HealthUpdate { .. }))
+            else {
+                continue;
+            };
+            *to += 1;
+            assert!(trail.verify(&events).is_err(), "seed {}", seed);
+        }
+
+        let (mut events, trail) = audited_battle(4);
+        if let Some(BattleEvent::BattleComplete { winner, loser, .. }) = events.last_mut() {
+            std::mem::swap(winner, loser);
+        }
+        assert!(trail.verify(&events).unwrap_err().contains("winner"));
+    }
+
+    #[test]
+    fn test_nothing_is_recorded_outside_record_audit() {
+        let (_, entries) = record_audit(|| ());
+        assert!(entries.is_empty());
+    }
+
+    #[test]
+    fn test_trail_round_trips_through_a_file() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("audits").join("battle_1.json");
+        let (_, trail) = audited_battle(5);
+        trail.save(&path).unwrap();
+        assert_eq!(AuditTrail::load(&path).unwrap(), trail);
+    }
+}
@@ -0,0 +1,262 @@
+// src/backup.rs
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read};
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the manifest inside every archive, next to the files it lists.
+pub const MANIFEST: &str = "manifest.json";
+
+/// What an archive holds and when it was made, written to it as `manifest.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub created_at: String, // RFC 3339 timestamp
+    /// Version of the crate that made the backup.
+    pub crate_version: String,
+    pub files: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Relative to the assets directory, with `/` separators.
+    pub path: String,
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+impl Manifest {
+    pub fn total_bytes(&self) -> u64 {
+        self.files.iter().map(|file| file.bytes).sum()
+    }
+}
+
+/// Where `create` writes a backup made at `now`: `destination` itself if it names a
+/// `.tar` file, `rinha-backup-<timestamp>.tar` inside it otherwise.
+pub fn archive_path(destination: &Path, now: DateTime<FixedOffset>) -> PathBuf {
+    if destination.extension().is_some_and(|ext| ext == "tar") {
+        destination.to_path_buf()
+    } else {
+        destination.join(format!("rinha-backup-{}.tar", now.format("%Y%m%d-%H%M%S")))
+    }
+}
+
+/// Archives every file under `data_dir` (fighters, battles, events, ratings, the
+/// cassino's bets and ledger, a SQLite database...) as a tar file with a manifest,
+/// returning where it went. Files the storage config places outside `data_dir` are
+/// left out.
+pub fn create(data_dir: &Path, destination: &Path, now: DateTime<FixedOffset>) -> Result<(PathBuf, Manifest), Box<dyn std::error::Error>> {
+    let archive = archive_path(destination, now);
+    if let Some(parent) = archive.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    // An archive written inside the assets must not end up in the next one
+    let skipped = archive.canonicalize().ok();
+    let mut files = Vec::new();
+    collect_files(data_dir, data_dir, &mut files)?;
+    files.retain(|(path, _)| path.canonicalize().ok() != skipped);
+
+    let mut contents = Vec::new();
+    let mut entries = Vec::new();
+    for (path, relative) in files {
+        let bytes = fs::read(&path)?;
+        entries.push(ManifestEntry { path: relative.clone(), bytes: bytes.len() as u64, sha256: sha256(&bytes) });
+        contents.push((relative, bytes));
+    }
+    let manifest = Manifest {
+        created_at: now.to_rfc3339(),
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        files: entries,
+    };
+
+    let mut builder = tar::Builder::new(BufWriter::new(File::create(&archive)?));
+    let mtime = u64::try_from(now.timestamp()).unwrap_or_default();
+    append(&mut builder, MANIFEST, &serde_json::to_vec_pretty(&manifest)?, mtime)?;
+    for (relative, bytes) in &contents {
+        append(&mut builder, relative, bytes, mtime)?;
+    }
+    builder.into_inner()?.into_inner().map_err(|e| e.into_error())?;
+    Ok((archive, manifest))
+}
+
+/// The manifest of a backup, without unpacking the rest.
+pub fn read_manifest(archive: &Path) -> Result<Manifest, Box<dyn std::error::Error>> {
+    let mut archive = tar::Archive::new(BufReader::new(File::open(archive)?));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.as_ref() == Path::new(MANIFEST) {
+            let mut manifest = Vec::new();
+            entry.read_to_end(&mut manifest)?;
+            return Ok(serde_json::from_slice(&manifest)?);
+        }
+    }
+    Err(format!("No {} in the archive, it isn't a backup", MANIFEST).into())
+}
+
+/// Puts the files of a backup back into `data_dir`, overwriting those there. Every file
+/// is checked against the manifest before anything is written, so a damaged archive
+/// changes nothing. Files the backup doesn't have are left alone.
+pub fn restore(archive: &Path, data_dir: &Path) -> Result<Manifest, Box<dyn std::error::Error>> {
+    let mut manifest = None;
+    let mut contents = HashMap::new();
+    let mut tar = tar::Archive::new(BufReader::new(File::open(archive)?));
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_string_lossy().replace('\\', "/");
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes)?;
+        if path == MANIFEST {
+            manifest = Some(serde_json::from_slice::<Manifest>(&bytes)?);
+        } else {
+            contents.insert(path, bytes);
+        }
+    }
+    let manifest = manifest.ok_or_else(|| format!("No {} in the archive, it isn't a backup", MANIFEST))?;
+
+    for file in &manifest.files {
+        let is_inside = Path::new(&file.path).components().all(|c| matches!(c, Component::Normal(_)));
+        if !is_inside {
+            return Err(format!("'{}' would be restored outside the assets directory", file.path).into());
+        }
+        let bytes = contents.get(&file.path).ok_or_else(|| format!("'{}' is missing from the archive", file.path))?;
+        if sha256(bytes) != file.sha256 {
+            return Err(format!("'{}' doesn't match its checksum, the archive is damaged", file.path).into());
+        }
+    }
+    for file in &manifest.files {
+        let path = data_dir.join(&file.path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, &contents[&file.path])?;
+    }
+    Ok(manifest)
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<(PathBuf, String)>) -> std::io::Result<()> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?.map(|entry| entry.map(|e| e.path())).collect::<Result<_, _>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let relative = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+            files.push((path, relative));
+        }
+    }
+    Ok(())
+}
+
+fn append<W: std::io::Write>(builder: &mut tar::Builder<W>, path: &str, bytes: &[u8], mtime: u64) -> std::io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    builder.append_data(&mut header, path, bytes)
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The `backup` subcommands of both CLIs, which keep their files in the same directory.
+#[cfg(feature = "cli")]
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum BackupAction {
+    /// Archive every asset file, with a manifest, as a timestamped .tar
+    Create {
+        /// Directory to write the archive to, or the .tar file to write
+        path: PathBuf,
+    },
+    /// Put the files of a backup back, overwriting the current ones
+    Restore {
+        path: PathBuf,
+        /// Don't ask for confirmation first
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[cfg(feature = "cli")]
+impl BackupAction {
+    pub fn run(&self, data_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            BackupAction::Create { path } => {
+                let (archive, manifest) = create(data_dir, path, chrono::Local::now().fixed_offset())?;
+                println!("✅ Backed up {} files ({} bytes) to {}", manifest.files.len(), manifest.total_bytes(), archive.display());
+            }
+            BackupAction::Restore { path, yes } => {
+                let manifest = read_manifest(path)?;
+                println!("Backup from {}: {} files ({} bytes)", manifest.created_at, manifest.files.len(), manifest.total_bytes());
+                let confirmed = *yes
+                    || dialoguer::Confirm::new()
+                        .with_prompt(format!("Overwrite the files in {} with it?", data_dir.display()))
+                        .default(false)
+                        .interact()?;
+                if !confirmed {
+                    println!("Nothing was restored.");
+                    return Ok(());
+                }
+                restore(path, data_dir)?;
+                println!("✅ Restored {} files into {}", manifest.files.len(), data_dir.display());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2024-03-01T12:30:00+00:00").unwrap()
+    }
+
+    #[test]
+    fn test_backup_restores_every_file() {
+        let assets = tempdir().unwrap();
+        fs::write(assets.path().join("neopets.json"), "[]").unwrap();
+        fs::create_dir(assets.path().join("battle_events")).unwrap();
+        fs::write(assets.path().join("battle_events").join("battle_1.jsonl"), "{}\n").unwrap();
+
+        let backups = tempdir().unwrap();
+        let (archive, manifest) = create(assets.path(), backups.path(), now()).unwrap();
+        assert_eq!(archive, backups.path().join("rinha-backup-20240301-123000.tar"));
+        let paths: Vec<&str> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["battle_events/battle_1.jsonl", "neopets.json"]);
+        assert_eq!(read_manifest(&archive).unwrap(), manifest);
+
+        fs::write(assets.path().join("neopets.json"), "[1]").unwrap();
+        fs::remove_dir_all(assets.path().join("battle_events")).unwrap();
+        fs::write(assets.path().join("ratings.json"), "{}").unwrap();
+        restore(&archive, assets.path()).unwrap();
+        assert_eq!(fs::read_to_string(assets.path().join("neopets.json")).unwrap(), "[]");
+        assert_eq!(fs::read_to_string(assets.path().join("battle_events").join("battle_1.jsonl")).unwrap(), "{}\n");
+        assert!(assets.path().join("ratings.json").exists());
+    }
+
+    #[test]
+    fn test_damaged_backups_restore_nothing() {
+        let assets = tempdir().unwrap();
+        fs::write(assets.path().join("neopets.json"), "[]").unwrap();
+        fs::write(assets.path().join("ratings.json"), "{}").unwrap();
+        // Written inside the assets, and left out of its own archive
+        let (archive, manifest) = create(assets.path(), &assets.path().join("backup.tar"), now()).unwrap();
+        assert_eq!(manifest.files.len(), 2);
+
+        let mut bytes = fs::read(&archive).unwrap();
+        let at = bytes.windows(4).position(|w| w == b"{}\0\0").unwrap();
+        bytes[at] = b'[';
+        fs::write(&archive, bytes).unwrap();
+        fs::write(assets.path().join("neopets.json"), "[1]").unwrap();
+        assert!(restore(&archive, assets.path()).unwrap_err().to_string().contains("ratings.json"));
+        assert_eq!(fs::read_to_string(assets.path().join("neopets.json")).unwrap(), "[1]");
+
+        let not_a_backup = assets.path().join("neopets.json");
+        assert!(read_manifest(&not_a_backup).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+// src/batch.rs
+use crate::battle::{battle_loop_with_config, drop_loot, BattleConfig, BattleError, BattleEvent};
+use crate::arena::Arena;
+use crate::loot::{Item, LootTables};
+use crate::neopets::Neopet;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// One battle of a batch, with the fighters as they enter it.
+#[derive(Debug, Clone)]
+pub struct BatchBattle {
+    pub id: String,
+    pub fighter1: Neopet,
+    pub fighter2: Neopet,
+    pub seed: u64, // `StdRng` seed of the battle and its loot
+    pub arena: Option<Arena>, // Replaces the config's arena for this battle
+}
+
+/// How one battle of a batch went: its events, loot included, and the items the
+/// winner looted.
+#[derive(Debug, Clone)]
+pub struct BatchResult {
+    pub id: String,
+    pub seed: u64,
+    pub outcome: Result<(Vec<BattleEvent>, Vec<Item>), BattleError>,
+}
+
+/// Fights every battle on up to `jobs` threads and returns the results in the order
+/// of `battles`. Each battle rolls with its own seed, so the results are the same
+/// whatever the number of threads and the same as fighting the battles one by one.
+pub fn run_batch(battles: &[BatchBattle], config: &BattleConfig, loot_tables: &LootTables, jobs: usize) -> Vec<BatchResult> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(battles.len()));
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, battles.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(battle) = battles.get(index) else {
+                    break;
+                };
+                let result = fight(battle, config, loot_tables);
+                if let Ok(mut results) = results.lock() {
+                    results.push((index, result));
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+fn fight(battle: &BatchBattle, config: &BattleConfig, loot_tables: &LootTables) -> BatchResult {
+    let mut rng = StdRng::seed_from_u64(battle.seed);
+    let config = &BattleConfig { arena: battle.arena.clone(), ..config.clone() };
+    let outcome = battle_loop_with_config(&battle.fighter1, &battle.fighter2, config, &mut rng).map(|mut events| {
+        let loot = drop_loot(&mut events, loot_tables, &mut rng);
+        (events, loot)
+    });
+    BatchResult { id: battle.id.clone(), seed: battle.seed, outcome }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn battles(count: u64) -> Vec<BatchBattle> {
+        (0..count)
+            .map(|seed| BatchBattle {
+                id: format!("battle_{}", seed),
+                fighter1: Neopet::test_fighter("Acara"),
+                fighter2: Neopet::test_fighter("Usul"),
+                seed,
+                arena: None,
+            })
+            .collect()
+    }
+
+    fn outcomes(results: Vec<BatchResult>) -> Vec<(String, Vec<BattleEvent>, Vec<Item>)> {
+        results.into_iter().map(|r| {
+            let (events, loot) = r.outcome.unwrap();
+            (r.id, events, loot)
+        }).collect()
+    }
+
+    #[test]
+    fn test_batch_matches_battles_fought_one_by_one() {
+        let (config, tables) = (BattleConfig::default(), LootTables::default());
+        let battles = battles(12);
+        let parallel = outcomes(run_batch(&battles, &config, &tables, 4));
+        assert_eq!(parallel.len(), 12);
+
+        for (battle, (id, events, loot)) in battles.iter().zip(&parallel) {
+            let mut rng = StdRng::seed_from_u64(battle.seed);
+            let mut expected = battle_loop_with_config(&battle.fighter1, &battle.fighter2, &config, &mut rng).unwrap();
+            let expected_loot = drop_loot(&mut expected, &tables, &mut rng);
+            assert_eq!((id, events, loot), (&battle.id, &expected, &expected_loot));
+        }
+        assert_eq!(outcomes(run_batch(&battles, &config, &tables, 1)), parallel);
+    }
+
+    #[test]
+    fn test_failed_battles_dont_stop_the_batch() {
+        let mut battles = battles(3);
+        battles[1].fighter2 = Neopet::test_fighter("Acara");
+        let results = run_batch(&battles, &BattleConfig::default(), &LootTables::default(), 8);
+        assert!(matches!(results[1].outcome, Err(BattleError::DuplicateFighter(_))));
+        assert!(results[0].outcome.is_ok() && results[2].outcome.is_ok());
+
+        assert!(run_batch(&[], &BattleConfig::default(), &LootTables::default(), 0).is_empty());
+    }
+}
@@ -1,68 +1,524 @@
-use crate::neopets::Neopet;
+use crate::arena::Arena;
+use crate::dice::{ActionDice, Dice};
+use crate::loot::{Item, LootTables};
+use crate::neopets::{Neopet, Spell};
+use crate::observer::Observer;
+use crate::runner::BattleRunner;
+use crate::spells::SpellEffect;
+use crate::strategy::BattleStrategy;
 use rand::Rng;
 use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Logs a `BattleState` change for the audit trail, see `crate::audit`. Does nothing
+/// without the `audit` feature.
+macro_rules! audit {
+    ($($field:tt)*) => {
+        #[cfg(feature = "audit")]
+        tracing::info!(target: crate::audit::AUDIT_TARGET, $($field)*);
+    };
+}
+
+/// Why the engine refused to run a battle or update its state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BattleError {
+    /// A name that belongs to neither fighter of the battle.
+    UnknownFighter(String),
+    /// Both fighters share this name, so their HP can't be told apart.
+    DuplicateFighter(String),
+    /// `max_turns` is 0, so the battle could never be decided.
+    NoTurns,
+    /// A free-for-all with fewer than two fighters, nobody to fight.
+    NotEnoughFighters(usize),
+}
+
+impl fmt::Display for BattleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BattleError::UnknownFighter(name) => write!(f, "Unknown fighter: {}", name),
+            BattleError::DuplicateFighter(name) => write!(f, "Both fighters are named '{}'", name),
+            BattleError::NoTurns => write!(f, "A battle needs at least one turn"),
+            BattleError::NotEnoughFighters(count) => write!(f, "A battle needs at least two fighters, got {}", count),
+        }
+    }
+}
+
+impl std::error::Error for BattleError {}
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
 pub enum BattleCompletionReason {
     HpDepleted(String), // Fighter name who reached 0 HP
     MaxTurnsReached(u32), // Maximum turns reached
+    LastStanding(String), // Free-for-all winner, everyone else reached 0 HP
+}
+
+/// How far healing can raise a fighter's HP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealCap {
+    /// Never above the HP the fighter started with.
+    #[default]
+    MaxHp,
+    /// Up to this percentage of the starting HP, e.g. 150 for half again as much.
+    Overheal(u32),
+    /// No limit at all.
+    Uncapped,
+}
+
+impl HealCap {
+    /// Highest HP healing can reach for a fighter who started with `max_hp`.
+    pub fn limit(&self, max_hp: u32) -> u32 {
+        match *self {
+            HealCap::MaxHp => max_hp,
+            HealCap::Overheal(percent) => {
+                let limit = max_hp as u64 * percent as u64 / 100;
+                limit.clamp(max_hp as u64, u32::MAX as u64) as u32
+            }
+            HealCap::Uncapped => u32::MAX,
+        }
+    }
+}
+
+/// Who acts first when both initiative rolls are equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InitiativeTie {
+    /// Both fighters roll again until one rolls higher.
+    #[default]
+    Reroll,
+    /// The fighter listed first.
+    FirstFighter,
+    /// The fighter with more health, the one listed first if that ties too.
+    HigherHealth,
+}
+
+/// Who a fighter attacks in a free-for-all. Two-fighter battles only ever have one target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetSelection {
+    /// Any opponent still standing.
+    #[default]
+    Random,
+    /// The standing opponent with the least HP, to finish them off.
+    Weakest,
+    /// The standing opponent with the most HP, the biggest threat.
+    Strongest,
+}
+
+/// Tunable rules of a battle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BattleConfig {
+    /// Turns after which the battle ends even if both fighters are still standing.
+    pub max_turns: u32,
+    /// First turn on which fighters are fatigued, `None` disables fatigue.
+    pub fatigue_start_turn: Option<u32>,
+    /// Extra damage every attack deals per fatigue level. The level starts at 1 on
+    /// `fatigue_start_turn` and goes up by one every turn.
+    pub fatigue_damage_per_level: u32,
+    /// Emit a `Prediction` event with the estimated win probability before every turn.
+    pub predictions: bool,
+    /// Emit a `WinProbability` event, rolled out by `predictor`, on turn 1 and then
+    /// every this many turns. `None` (or 0) disables them.
+    pub win_probability_every: Option<u32>,
+    /// Battles `predictor` plays out to the end for each `WinProbability` event.
+    pub rollouts: u32,
+    /// Dice rolled for attack, defense, heal and initiative by every fighter
+    /// without an entry in `fighter_dice`.
+    pub dice: ActionDice,
+    /// Dice of specific fighters, keyed by name.
+    pub fighter_dice: HashMap<String, ActionDice>,
+    /// How many of the latest events and actions strategies get to see.
+    pub history_window: usize,
+    /// Critical attacks deal, and critical heals restore, this many times as much.
+    pub crit_multiplier: u32,
+    /// A roll is critical when it is one of this many highest results of its dice,
+    /// 1 for only the top one and 0 for no crits.
+    pub crit_range: u32,
+    /// A roll fumbles when it is one of this many lowest results of its dice,
+    /// 1 for only the lowest one and 0 for no fumbles.
+    pub fumble_range: u32,
+    pub heal_cap: HealCap,
+    pub initiative_tie: InitiativeTie,
+    pub target_selection: TargetSelection,
+    /// A fighter whose speed beats their opponent's by at least this much acts twice
+    /// in a row every time their turn comes up, `None` disables extra actions.
+    pub extra_action_speed_gap: Option<u32>,
+    /// Attack points a taunted fighter loses on the attack they are goaded into.
+    pub taunt_penalty: u32,
+    /// Where the battle is fought, `None` for a plain arena without modifiers. Picked
+    /// per battle rather than in `battle_config.json`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arena: Option<Arena>,
+}
+
+impl Default for BattleConfig {
+    fn default() -> Self {
+        Self {
+            max_turns: 10,
+            fatigue_start_turn: None,
+            fatigue_damage_per_level: 2,
+            predictions: false,
+            win_probability_every: None,
+            rollouts: 100,
+            dice: ActionDice::default(),
+            fighter_dice: HashMap::new(),
+            history_window: 10,
+            crit_multiplier: 2,
+            crit_range: 1,
+            fumble_range: 1,
+            heal_cap: HealCap::MaxHp,
+            initiative_tie: InitiativeTie::Reroll,
+            target_selection: TargetSelection::Random,
+            extra_action_speed_gap: None,
+            taunt_penalty: 3,
+            arena: None,
+        }
+    }
+}
+
+impl BattleConfig {
+    /// Loads the config, or the default one if the file doesn't exist.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Whether `roll` of `dice` is a critical success.
+    pub fn is_crit(&self, dice: Dice, roll: u32) -> bool {
+        roll + self.crit_range > dice.max()
+    }
+
+    /// Whether `roll` of `dice` is a fumble.
+    pub fn is_fumble(&self, dice: Dice, roll: u32) -> bool {
+        roll < dice.min() + self.fumble_range
+    }
+
+    /// Whether `fighter` is fast enough to act twice in a row against `opponent`.
+    pub fn has_extra_action(&self, fighter: &Neopet, opponent: &Neopet) -> bool {
+        self.extra_action_speed_gap
+            .is_some_and(|gap| fighter.speed >= opponent.speed.saturating_add(gap.max(1)))
+    }
+
+    /// The dice `fighter_name` rolls.
+    pub fn dice_for(&self, fighter_name: &str) -> ActionDice {
+        self.fighter_dice.get(fighter_name).copied().unwrap_or(self.dice)
+    }
+
+    /// Fatigue level on `turn`, 0 while fighters are still fresh.
+    pub fn fatigue_level(&self, turn: u32) -> u32 {
+        match self.fatigue_start_turn {
+            Some(start) if turn >= start => turn - start + 1,
+            _ => 0,
+        }
+    }
+}
+
+/// How many poison stacks a fighter can carry at once, see `SpellEffect::Poison`.
+pub const MAX_POISON_STACKS: usize = 3;
+
+/// Kind of an effect that ticks at the start of its bearer's turns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum TickKind {
+    Burn,
+    Poison,
+    Regen,
+}
+
+/// A fighter's name as the battle state and its events carry it, shared rather than
+/// copied into every event. Written as a plain string.
+pub type FighterName = Arc<str>;
+
+/// The spell behind an over-time effect, and who cast it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EffectSource {
+    pub kind: TickKind,
+    pub spell: String,
+    pub caster: FighterName,
+}
+
+/// An over-time effect on a fighter, dealing or restoring `amount` HP on each of
+/// its bearer's next `turns_left` turns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActiveEffect {
+    pub source: EffectSource,
+    pub amount: u32,
+    pub turns_left: u32,
+}
+
+impl ActiveEffect {
+    /// The effect `spell` leaves behind when `caster` casts it, `None` for spells that
+    /// don't tick (or whose effect isn't known).
+    pub fn from_spell(spell: &Spell, caster: impl Into<FighterName>) -> Option<Self> {
+        let (kind, amount, turns) = match serde_json::from_value(spell.effect.clone()).ok()? {
+            SpellEffect::Burn { damage, turns } => (TickKind::Burn, damage, turns),
+            SpellEffect::Poison { damage, turns } => (TickKind::Poison, damage, turns),
+            SpellEffect::Regen { amount, turns } => (TickKind::Regen, amount, turns),
+            _ => return None,
+        };
+        (turns > 0).then(|| Self {
+            source: EffectSource { kind, spell: spell.name.clone(), caster: caster.into() },
+            amount,
+            turns_left: turns,
+        })
+    }
 }
 
-/// Battle state that tracks HP and determines when battle ends
+/// One fighter's side of a `BattleState`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FighterState {
+    pub hp: u32,
+    pub max_hp: u32,
+    #[serde(default)]
+    pub dice: ActionDice,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub effects: Vec<ActiveEffect>, // Over-time effects, oldest first
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub spells_cast: HashMap<String, u32>, // Turn each spell was last cast on
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub defending: bool, // Rolls double defense until their next turn
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub taunted: bool, // Has to attack, with the taunt penalty, on their next turn
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+/// Battle state that tracks HP and determines when battle ends. Any number of
+/// fighters can take part, the battle is over once at most one is left standing.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BattleState {
-    pub fighter1_name: String,
-    pub fighter2_name: String,
-    pub fighter1_hp: u32,
-    pub fighter2_hp: u32,
-    pub fighter1_max_hp: u32,
-    pub fighter2_max_hp: u32,
+    pub fighter_names: Vec<FighterName>, // In the order they were listed, which breaks ties
+    pub fighters: HashMap<String, FighterState>,
+    #[serde(default)]
+    pub knocked_out: Vec<String>, // In the order they went down
     pub current_turn: u32,
     pub max_turns: u32,
     pub is_complete: bool,
     pub completion_reason: Option<BattleCompletionReason>,
+    #[serde(default)]
+    pub fatigue_bonus: u32, // Extra damage every landed attack deals
 }
 
 impl BattleState {
     pub fn new(fighter1: &Neopet, fighter2: &Neopet, max_turns: u32) -> Self {
+        Self::with_fighters(&[fighter1, fighter2], max_turns)
+    }
+
+    /// A free-for-all between every fighter in `fighters`, whose names must all differ.
+    pub fn with_fighters(fighters: &[&Neopet], max_turns: u32) -> Self {
+        #[cfg(feature = "audit")]
+        for fighter in fighters {
+            audit!(kind = "join", turn = 0u32, fighter = fighter.name.as_str(), before = 0u32, after = fighter.health);
+        }
         Self {
-            fighter1_name: fighter1.name.clone(),
-            fighter2_name: fighter2.name.clone(),
-            fighter1_hp: fighter1.health,
-            fighter2_hp: fighter2.health,
-            fighter1_max_hp: fighter1.health,
-            fighter2_max_hp: fighter2.health,
+            fighter_names: fighters.iter().map(|f| FighterName::from(f.name.as_str())).collect(),
+            fighters: fighters
+                .iter()
+                .map(|f| (f.name.clone(), FighterState {
+                    hp: f.health,
+                    max_hp: f.health,
+                    dice: ActionDice::default(),
+                    effects: Vec::new(),
+                    spells_cast: HashMap::new(),
+                    defending: false,
+                    taunted: false,
+                }))
+                .collect(),
+            knocked_out: Vec::new(),
             current_turn: 0,
             max_turns,
             is_complete: false,
             completion_reason: None,
+            fatigue_bonus: 0,
+        }
+    }
+
+    /// Sets the dice each fighter rolls, d20s until this is called.
+    pub fn with_dice(mut self, fighter1_dice: ActionDice, fighter2_dice: ActionDice) -> Self {
+        for (name, dice) in self.fighter_names.iter().zip([fighter1_dice, fighter2_dice]) {
+            if let Some(fighter) = self.fighters.get_mut(&**name) {
+                fighter.dice = dice;
+            }
+        }
+        self
+    }
+
+    /// Gives every fighter the dice `config` assigns them.
+    pub fn with_config_dice(mut self, config: &BattleConfig) -> Self {
+        for (name, fighter) in self.fighters.iter_mut() {
+            fighter.dice = config.dice_for(name);
         }
+        self
+    }
+
+    pub fn fighter(&self, fighter_name: &str) -> Result<&FighterState, BattleError> {
+        self.fighters
+            .get(fighter_name)
+            .ok_or_else(|| BattleError::UnknownFighter(fighter_name.to_string()))
+    }
+
+    /// The shared name of `fighter_name`, to hand out in events without copying it.
+    pub fn name(&self, fighter_name: &str) -> Result<FighterName, BattleError> {
+        self.fighter_names
+            .iter()
+            .find(|name| &name[..] == fighter_name)
+            .cloned()
+            .ok_or_else(|| BattleError::UnknownFighter(fighter_name.to_string()))
+    }
+
+    fn fighter_mut(&mut self, fighter_name: &str) -> Result<&mut FighterState, BattleError> {
+        self.fighters
+            .get_mut(fighter_name)
+            .ok_or_else(|| BattleError::UnknownFighter(fighter_name.to_string()))
     }
     
     /// Apply damage to a fighter and return the new HP
-    pub fn apply_damage(&mut self, fighter_name: &str, damage: u32) -> u32 {
-        if fighter_name == &self.fighter1_name {
-            self.fighter1_hp = self.fighter1_hp.saturating_sub(damage);
-            self.fighter1_hp
-        } else if fighter_name == &self.fighter2_name {
-            self.fighter2_hp = self.fighter2_hp.saturating_sub(damage);
-            self.fighter2_hp
-        } else {
-            panic!("Unknown fighter: {}", fighter_name);
+    pub fn apply_damage(&mut self, fighter_name: &str, damage: u32) -> Result<u32, BattleError> {
+        let fighter = self.fighter_mut(fighter_name)?;
+        let before = fighter.hp;
+        let hp = before.saturating_sub(damage);
+        fighter.hp = hp;
+        audit!(kind = "damage", turn = self.current_turn, fighter = fighter_name, before, after = hp);
+        if hp == 0 && !self.knocked_out.iter().any(|n| n == fighter_name) {
+            self.knocked_out.push(fighter_name.to_string());
         }
+        Ok(hp)
     }
     
     /// Apply healing to a fighter and return the new HP
-    pub fn apply_healing(&mut self, fighter_name: &str, amount: u32) -> u32 {
-        if fighter_name == &self.fighter1_name {
-            self.fighter1_hp = (self.fighter1_hp + amount).min(self.fighter1_max_hp);
-            self.fighter1_hp
-        } else if fighter_name == &self.fighter2_name {
-            self.fighter2_hp = (self.fighter2_hp + amount).min(self.fighter2_max_hp);
-            self.fighter2_hp
-        } else {
-            panic!("Unknown fighter: {}", fighter_name);
+    pub fn apply_healing(&mut self, fighter_name: &str, amount: u32) -> Result<u32, BattleError> {
+        self.apply_healing_capped(fighter_name, amount, HealCap::MaxHp)
+    }
+
+    /// Apply healing to a fighter, up to what `cap` allows, and return the new HP
+    pub fn apply_healing_capped(&mut self, fighter_name: &str, amount: u32, cap: HealCap) -> Result<u32, BattleError> {
+        let fighter = self.fighter_mut(fighter_name)?;
+        let before = fighter.hp;
+        let hp = before.saturating_add(amount).min(cap.limit(fighter.max_hp));
+        fighter.hp = hp;
+        audit!(kind = "heal", turn = self.current_turn, fighter = fighter_name, before, after = hp);
+        if hp > 0 {
+            self.knocked_out.retain(|n| n != fighter_name);
+        }
+        Ok(hp)
+    }
+
+    /// Moves the battle on to `turn`.
+    pub fn advance_turn(&mut self, turn: u32) {
+        audit!(kind = "turn", turn, before = self.current_turn, after = turn);
+        self.current_turn = turn;
+    }
+
+    /// Sets the extra damage every landed attack deals from now on.
+    pub fn set_fatigue_bonus(&mut self, bonus: u32) {
+        audit!(kind = "fatigue", turn = self.current_turn, before = self.fatigue_bonus, after = bonus);
+        self.fatigue_bonus = bonus;
+    }
+
+    /// Puts an over-time effect on a fighter. Burns and regens refresh one already there,
+    /// keeping the stronger amount and the longer duration; poison stacks, the stack
+    /// closest to running out making room once `MAX_POISON_STACKS` is reached.
+    pub fn add_effect(&mut self, fighter_name: &str, effect: ActiveEffect) -> Result<(), BattleError> {
+        #[cfg(feature = "audit")]
+        let (turn, before, spell) =
+            (self.current_turn, self.fighter(fighter_name)?.effects.len() as u32, effect.source.spell.clone());
+        let fighter = self.fighter_mut(fighter_name)?;
+        let same_kind = fighter.effects.iter().position(|e| e.source.kind == effect.source.kind);
+        match (effect.source.kind, same_kind) {
+            (TickKind::Burn | TickKind::Regen, Some(index)) => {
+                let existing = &mut fighter.effects[index];
+                existing.amount = existing.amount.max(effect.amount);
+                existing.turns_left = existing.turns_left.max(effect.turns_left);
+                existing.source = effect.source;
+            }
+            (TickKind::Poison, _)
+                if fighter.effects.iter().filter(|e| e.source.kind == TickKind::Poison).count() >= MAX_POISON_STACKS =>
+            {
+                let weakest = fighter
+                    .effects
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, e)| e.source.kind == TickKind::Poison)
+                    .min_by_key(|(_, e)| e.turns_left)
+                    .map(|(index, _)| index);
+                if let Some(index) = weakest {
+                    fighter.effects.remove(index);
+                }
+                fighter.effects.push(effect);
+            }
+            _ => fighter.effects.push(effect),
         }
+        audit!(kind = "effect", turn, fighter = fighter_name, before, after = fighter.effects.len() as u32, detail = spell.as_str());
+        Ok(())
+    }
+
+    /// Removes the oldest burn or poison on a fighter and returns it, `None` when
+    /// there was nothing to cleanse.
+    pub fn cleanse(&mut self, fighter_name: &str) -> Result<Option<ActiveEffect>, BattleError> {
+        #[cfg(feature = "audit")]
+        let turn = self.current_turn;
+        let fighter = self.fighter_mut(fighter_name)?;
+        let Some(index) = fighter.effects.iter().position(|e| e.source.kind != TickKind::Regen) else {
+            return Ok(None);
+        };
+        let effect = fighter.effects.remove(index);
+        audit!(
+            kind = "effect",
+            turn,
+            fighter = fighter_name,
+            before = fighter.effects.len() as u32 + 1,
+            after = fighter.effects.len() as u32,
+            detail = effect.source.spell.as_str(),
+        );
+        Ok(Some(effect))
+    }
+
+    /// Turns left before `fighter_name` can cast `spell` again, 0 once it's ready.
+    pub fn cooldown_left(&self, fighter_name: &str, spell: &Spell, turn: u32) -> Result<u32, BattleError> {
+        let last_cast = self.fighter(fighter_name)?.spells_cast.get(&spell.name);
+        Ok(last_cast.map_or(0, |last| (last + spell.cooldown + 1).saturating_sub(turn)))
+    }
+
+    /// Fighters with HP left, in the order they were listed.
+    pub fn standing(&self) -> Vec<&str> {
+        self.fighter_names
+            .iter()
+            .filter(|name| self.fighters.get(&name[..]).is_some_and(|f| f.hp > 0))
+            .map(|name| &**name)
+            .collect()
+    }
+
+    /// Every fighter from best to worst placed: more HP first, then whoever went down
+    /// last, then more max HP, and finally the order they were listed in.
+    pub fn ranking(&self) -> Vec<&str> {
+        let mut ranking: Vec<&str> = self.fighter_names.iter().map(|name| &**name).collect();
+        ranking.sort_by_key(|name| {
+            let fighter = &self.fighters[*name];
+            let knocked_out_at = self.knocked_out.iter().position(|n| n == name).unwrap_or(usize::MAX);
+            (Reverse(fighter.hp), Reverse(knocked_out_at), Reverse(fighter.max_hp))
+        });
+        ranking
     }
     
     /// Check if battle should end and set completion reason
@@ -71,83 +527,82 @@ impl BattleState {
             return self.completion_reason.clone();
         }
         
-        if self.fighter1_hp == 0 {
+        let reason = self.completion_check();
+        if let Some(reason) = &reason {
             self.is_complete = true;
-            self.completion_reason = Some(BattleCompletionReason::HpDepleted(self.fighter1_name.clone()));
-            return self.completion_reason.clone();
+            self.completion_reason = Some(reason.clone());
+            audit!(
+                kind = "complete",
+                turn = self.current_turn,
+                fighter = self.ranking()[0],
+                detail = serde_json::to_string(reason).unwrap_or_default().as_str(),
+            );
         }
-        
-        if self.fighter2_hp == 0 {
-            self.is_complete = true;
-            self.completion_reason = Some(BattleCompletionReason::HpDepleted(self.fighter2_name.clone()));
-            return self.completion_reason.clone();
+        reason
+    }
+
+    /// Why the battle is over, `None` while it goes on.
+    fn completion_check(&self) -> Option<BattleCompletionReason> {
+        if self.standing().len() <= 1 {
+            return Some(if self.fighter_names.len() > 2 {
+                BattleCompletionReason::LastStanding(self.ranking()[0].to_string())
+            } else {
+                let fallen = self.fighter_names.iter().find(|name| self.fighters[&name[..]].hp == 0);
+                BattleCompletionReason::HpDepleted(fallen.map(|name| name.to_string()).unwrap_or_default())
+            });
         }
         
         if self.current_turn >= self.max_turns {
-            self.is_complete = true;
-            self.completion_reason = Some(BattleCompletionReason::MaxTurnsReached(self.max_turns));
-            return self.completion_reason.clone();
+            return Some(BattleCompletionReason::MaxTurnsReached(self.max_turns));
         }
         
         None
     }
     
-    /// Get the winner and loser (if battle is complete)
+    /// Get the winner and loser (if battle is complete). With more than two fighters
+    /// the loser is the one placed last.
     pub fn get_winner_loser(&self) -> Option<(String, String)> {
         if !self.is_complete {
             return None;
         }
         
-        if self.fighter1_hp > self.fighter2_hp {
-            Some((self.fighter1_name.clone(), self.fighter2_name.clone()))
-        } else if self.fighter2_hp > self.fighter1_hp {
-            Some((self.fighter2_name.clone(), self.fighter1_name.clone()))
-        } else {
-            // It's a draw - use max HP as tiebreaker, otherwise first fighter wins
-            if self.fighter1_max_hp >= self.fighter2_max_hp {
-                Some((self.fighter1_name.clone(), self.fighter2_name.clone()))
-            } else {
-                Some((self.fighter2_name.clone(), self.fighter1_name.clone()))
-            }
-        }
+        let ranking = self.ranking();
+        Some((ranking.first()?.to_string(), ranking.last()?.to_string()))
     }
     
     /// Get current HP for a fighter
-    pub fn get_hp(&self, fighter_name: &str) -> u32 {
-        if fighter_name == &self.fighter1_name {
-            self.fighter1_hp
-        } else if fighter_name == &self.fighter2_name {
-            self.fighter2_hp
-        } else {
-            panic!("Unknown fighter: {}", fighter_name);
-        }
+    pub fn get_hp(&self, fighter_name: &str) -> Result<u32, BattleError> {
+        Ok(self.fighter(fighter_name)?.hp)
+    }
+
+    /// Get the dice a fighter rolls
+    pub fn get_dice(&self, fighter_name: &str) -> Result<ActionDice, BattleError> {
+        Ok(self.fighter(fighter_name)?.dice)
     }
 }
 
 #[cfg(test)]
 mod battle_state_tests {
     use super::*;
+    use macro_workshop::event_match;
     use crate::neopets::{Neopet, Spell, Behavior};
 
     // Helper function to create a test Neopet
     fn create_test_neopet(name: &str) -> Neopet {
         Neopet {
-            name: name.to_string(),
             health: 100,
             heal_delta: 10,
             base_attack: 5,
-            base_defense: 3,
             spells: vec![
                 Spell {
                     name: "Fireball".to_string(),
                     effect: serde_json::Value::Object(serde_json::Map::new()),
+                    cooldown: 0,
+                    inline: false,
                 },
             ],
-            behavior: Behavior {
-                attack_chance: 0.5,
-                spell_chances: vec![0.1],
-                heal_chance: 0.4,
-            },
+            behavior: Behavior { spell_chances: vec![0.1], heal_chance: 0.4, ..Behavior::attack_or_heal(0.5) },
+            ..Neopet::test_fighter(name)
         }
     }
 
@@ -157,28 +612,42 @@ mod battle_state_tests {
         let fighter2 = create_test_neopet("Fighter2");
         let battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
-        assert_eq!(battle_state.fighter1_name, "Fighter1");
-        assert_eq!(battle_state.fighter2_name, "Fighter2");
-        assert_eq!(battle_state.fighter1_hp, 100);
-        assert_eq!(battle_state.fighter2_hp, 100);
-        assert_eq!(battle_state.fighter1_max_hp, 100);
-        assert_eq!(battle_state.fighter2_max_hp, 100);
+        assert_eq!(battle_state.fighter_names[0], "Fighter1".into());
+        assert_eq!(battle_state.fighter_names[1], "Fighter2".into());
+        assert_eq!(battle_state.get_hp("Fighter1").unwrap(), 100);
+        assert_eq!(battle_state.get_hp("Fighter2").unwrap(), 100);
+        assert_eq!(battle_state.fighter("Fighter1").unwrap().max_hp, 100);
+        assert_eq!(battle_state.fighter("Fighter2").unwrap().max_hp, 100);
         assert_eq!(battle_state.current_turn, 0);
         assert_eq!(battle_state.max_turns, 10);
         assert!(!battle_state.is_complete);
         assert!(battle_state.completion_reason.is_none());
     }
 
+    #[test]
+    fn test_heal_cap_limits() {
+        assert_eq!(HealCap::MaxHp.limit(80), 80);
+        assert_eq!(HealCap::Overheal(150).limit(80), 120);
+        assert_eq!(HealCap::Overheal(50).limit(80), 80); // Never below max HP
+        assert_eq!(HealCap::Uncapped.limit(80), u32::MAX);
+
+        let fighter1 = create_test_neopet("Fighter1");
+        let fighter2 = create_test_neopet("Fighter2");
+        let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
+        assert_eq!(battle_state.apply_healing_capped("Fighter1", 30, HealCap::Overheal(120)).unwrap(), 120);
+        assert_eq!(battle_state.apply_healing_capped("Fighter1", 30, HealCap::Uncapped).unwrap(), 150);
+    }
+
     #[test]
     fn test_apply_damage_normal() {
         let fighter1 = create_test_neopet("Fighter1");
         let fighter2 = create_test_neopet("Fighter2");
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
-        let new_hp = battle_state.apply_damage("Fighter1", 20);
+        let new_hp = battle_state.apply_damage("Fighter1", 20).unwrap();
         assert_eq!(new_hp, 80);
-        assert_eq!(battle_state.fighter1_hp, 80);
-        assert_eq!(battle_state.fighter2_hp, 100); // Unchanged
+        assert_eq!(battle_state.get_hp("Fighter1").unwrap(), 80);
+        assert_eq!(battle_state.get_hp("Fighter2").unwrap(), 100); // Unchanged
     }
 
     #[test]
@@ -188,9 +657,9 @@ mod battle_state_tests {
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
         // Apply damage that would reduce HP below 0
-        let new_hp = battle_state.apply_damage("Fighter1", 150);
+        let new_hp = battle_state.apply_damage("Fighter1", 150).unwrap();
         assert_eq!(new_hp, 0);
-        assert_eq!(battle_state.fighter1_hp, 0);
+        assert_eq!(battle_state.get_hp("Fighter1").unwrap(), 0);
     }
 
     #[test]
@@ -199,19 +668,21 @@ mod battle_state_tests {
         let fighter2 = create_test_neopet("Fighter2");
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
-        let new_hp = battle_state.apply_damage("Fighter1", 0);
+        let new_hp = battle_state.apply_damage("Fighter1", 0).unwrap();
         assert_eq!(new_hp, 100);
-        assert_eq!(battle_state.fighter1_hp, 100);
+        assert_eq!(battle_state.get_hp("Fighter1").unwrap(), 100);
     }
 
     #[test]
-    #[should_panic(expected = "Unknown fighter")]
     fn test_apply_damage_invalid_fighter() {
         let fighter1 = create_test_neopet("Fighter1");
         let fighter2 = create_test_neopet("Fighter2");
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
-        battle_state.apply_damage("NonExistentFighter", 10);
+        assert_eq!(
+            battle_state.apply_damage("NonExistentFighter", 10),
+            Err(BattleError::UnknownFighter("NonExistentFighter".to_string()))
+        );
     }
 
     #[test]
@@ -221,13 +692,13 @@ mod battle_state_tests {
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
         // First reduce HP
-        battle_state.apply_damage("Fighter1", 20);
-        assert_eq!(battle_state.fighter1_hp, 80);
+        battle_state.apply_damage("Fighter1", 20).unwrap();
+        assert_eq!(battle_state.get_hp("Fighter1").unwrap(), 80);
         
         // Then heal
-        let new_hp = battle_state.apply_healing("Fighter1", 15);
+        let new_hp = battle_state.apply_healing("Fighter1", 15).unwrap();
         assert_eq!(new_hp, 95);
-        assert_eq!(battle_state.fighter1_hp, 95);
+        assert_eq!(battle_state.get_hp("Fighter1").unwrap(), 95);
     }
 
     #[test]
@@ -237,13 +708,13 @@ mod battle_state_tests {
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
         // First reduce HP
-        battle_state.apply_damage("Fighter1", 20);
-        assert_eq!(battle_state.fighter1_hp, 80);
+        battle_state.apply_damage("Fighter1", 20).unwrap();
+        assert_eq!(battle_state.get_hp("Fighter1").unwrap(), 80);
         
         // Then heal beyond max HP
-        let new_hp = battle_state.apply_healing("Fighter1", 50);
+        let new_hp = battle_state.apply_healing("Fighter1", 50).unwrap();
         assert_eq!(new_hp, 100); // Should be capped at max
-        assert_eq!(battle_state.fighter1_hp, 100);
+        assert_eq!(battle_state.get_hp("Fighter1").unwrap(), 100);
     }
 
     #[test]
@@ -253,9 +724,9 @@ mod battle_state_tests {
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
         // Try to heal from full HP
-        let new_hp = battle_state.apply_healing("Fighter1", 20);
+        let new_hp = battle_state.apply_healing("Fighter1", 20).unwrap();
         assert_eq!(new_hp, 100); // Should stay at max
-        assert_eq!(battle_state.fighter1_hp, 100);
+        assert_eq!(battle_state.get_hp("Fighter1").unwrap(), 100);
     }
 
     #[test]
@@ -264,19 +735,21 @@ mod battle_state_tests {
         let fighter2 = create_test_neopet("Fighter2");
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
-        let new_hp = battle_state.apply_healing("Fighter1", 0);
+        let new_hp = battle_state.apply_healing("Fighter1", 0).unwrap();
         assert_eq!(new_hp, 100);
-        assert_eq!(battle_state.fighter1_hp, 100);
+        assert_eq!(battle_state.get_hp("Fighter1").unwrap(), 100);
     }
 
     #[test]
-    #[should_panic(expected = "Unknown fighter")]
     fn test_apply_healing_invalid_fighter() {
         let fighter1 = create_test_neopet("Fighter1");
         let fighter2 = create_test_neopet("Fighter2");
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
-        battle_state.apply_healing("NonExistentFighter", 10);
+        assert_eq!(
+            battle_state.apply_healing("NonExistentFighter", 10),
+            Err(BattleError::UnknownFighter("NonExistentFighter".to_string()))
+        );
     }
 
     #[test]
@@ -298,14 +771,11 @@ mod battle_state_tests {
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
         // Deplete fighter1's HP
-        battle_state.apply_damage("Fighter1", 100);
+        battle_state.apply_damage("Fighter1", 100).unwrap();
         
         let completion = battle_state.check_battle_completion();
         assert!(completion.is_some());
-        match completion.unwrap() {
-            BattleCompletionReason::HpDepleted(name) => assert_eq!(name, "Fighter1"),
-            _ => panic!("Expected HP depletion completion"),
-        }
+        event_match!(completion.unwrap(), BattleCompletionReason::HpDepleted { 0: "Fighter1" });
         assert!(battle_state.is_complete);
         assert!(battle_state.completion_reason.is_some());
     }
@@ -317,14 +787,11 @@ mod battle_state_tests {
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
         // Deplete fighter2's HP
-        battle_state.apply_damage("Fighter2", 100);
+        battle_state.apply_damage("Fighter2", 100).unwrap();
         
         let completion = battle_state.check_battle_completion();
         assert!(completion.is_some());
-        match completion.unwrap() {
-            BattleCompletionReason::HpDepleted(name) => assert_eq!(name, "Fighter2"),
-            _ => panic!("Expected HP depletion completion"),
-        }
+        event_match!(completion.unwrap(), BattleCompletionReason::HpDepleted { 0: "Fighter2" });
         assert!(battle_state.is_complete);
     }
 
@@ -339,10 +806,7 @@ mod battle_state_tests {
         
         let completion = battle_state.check_battle_completion();
         assert!(completion.is_some());
-        match completion.unwrap() {
-            BattleCompletionReason::MaxTurnsReached(turns) => assert_eq!(turns, 5),
-            _ => panic!("Expected max turns completion"),
-        }
+        event_match!(completion.unwrap(), BattleCompletionReason::MaxTurnsReached { 0: 5 });
         assert!(battle_state.is_complete);
     }
 
@@ -359,10 +823,7 @@ mod battle_state_tests {
         // Check completion again
         let completion = battle_state.check_battle_completion();
         assert!(completion.is_some());
-        match completion.unwrap() {
-            BattleCompletionReason::HpDepleted(name) => assert_eq!(name, "Fighter1"),
-            _ => panic!("Expected cached completion reason"),
-        }
+        event_match!(completion.unwrap(), BattleCompletionReason::HpDepleted { 0: "Fighter1" });
     }
 
     #[test]
@@ -382,8 +843,8 @@ mod battle_state_tests {
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
         // Set different HP values
-        battle_state.fighter1_hp = 50;
-        battle_state.fighter2_hp = 30;
+        battle_state.fighters.get_mut("Fighter1").unwrap().hp = 50;
+        battle_state.fighters.get_mut("Fighter2").unwrap().hp = 30;
         battle_state.is_complete = true;
         
         let result = battle_state.get_winner_loser();
@@ -400,8 +861,8 @@ mod battle_state_tests {
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
         // Set different HP values
-        battle_state.fighter1_hp = 20;
-        battle_state.fighter2_hp = 60;
+        battle_state.fighters.get_mut("Fighter1").unwrap().hp = 20;
+        battle_state.fighters.get_mut("Fighter2").unwrap().hp = 60;
         battle_state.is_complete = true;
         
         let result = battle_state.get_winner_loser();
@@ -418,10 +879,10 @@ mod battle_state_tests {
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
         // Set equal HP but different max HP
-        battle_state.fighter1_hp = 50;
-        battle_state.fighter2_hp = 50;
-        battle_state.fighter1_max_hp = 120;
-        battle_state.fighter2_max_hp = 100;
+        battle_state.fighters.get_mut("Fighter1").unwrap().hp = 50;
+        battle_state.fighters.get_mut("Fighter2").unwrap().hp = 50;
+        battle_state.fighters.get_mut("Fighter1").unwrap().max_hp = 120;
+        battle_state.fighters.get_mut("Fighter2").unwrap().max_hp = 100;
         battle_state.is_complete = true;
         
         let result = battle_state.get_winner_loser();
@@ -438,10 +899,10 @@ mod battle_state_tests {
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
         // Set equal HP but different max HP
-        battle_state.fighter1_hp = 50;
-        battle_state.fighter2_hp = 50;
-        battle_state.fighter1_max_hp = 100;
-        battle_state.fighter2_max_hp = 150;
+        battle_state.fighters.get_mut("Fighter1").unwrap().hp = 50;
+        battle_state.fighters.get_mut("Fighter2").unwrap().hp = 50;
+        battle_state.fighters.get_mut("Fighter1").unwrap().max_hp = 100;
+        battle_state.fighters.get_mut("Fighter2").unwrap().max_hp = 150;
         battle_state.is_complete = true;
         
         let result = battle_state.get_winner_loser();
@@ -458,10 +919,10 @@ mod battle_state_tests {
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
         // Set equal everything
-        battle_state.fighter1_hp = 50;
-        battle_state.fighter2_hp = 50;
-        battle_state.fighter1_max_hp = 100;
-        battle_state.fighter2_max_hp = 100;
+        battle_state.fighters.get_mut("Fighter1").unwrap().hp = 50;
+        battle_state.fighters.get_mut("Fighter2").unwrap().hp = 50;
+        battle_state.fighters.get_mut("Fighter1").unwrap().max_hp = 100;
+        battle_state.fighters.get_mut("Fighter2").unwrap().max_hp = 100;
         battle_state.is_complete = true;
         
         let result = battle_state.get_winner_loser();
@@ -479,21 +940,76 @@ mod battle_state_tests {
         let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
         // Modify HP
-        battle_state.apply_damage("Fighter1", 20);
-        battle_state.apply_damage("Fighter2", 30);
+        battle_state.apply_damage("Fighter1", 20).unwrap();
+        battle_state.apply_damage("Fighter2", 30).unwrap();
         
-        assert_eq!(battle_state.get_hp("Fighter1"), 80);
-        assert_eq!(battle_state.get_hp("Fighter2"), 70);
+        assert_eq!(battle_state.get_hp("Fighter1").unwrap(), 80);
+        assert_eq!(battle_state.get_hp("Fighter2").unwrap(), 70);
     }
 
     #[test]
-    #[should_panic(expected = "Unknown fighter")]
     fn test_get_hp_invalid_fighter() {
         let fighter1 = create_test_neopet("Fighter1");
         let fighter2 = create_test_neopet("Fighter2");
         let battle_state = BattleState::new(&fighter1, &fighter2, 10);
         
-        battle_state.get_hp("NonExistentFighter");
+        assert_eq!(
+            battle_state.get_hp("NonExistentFighter"),
+            Err(BattleError::UnknownFighter("NonExistentFighter".to_string()))
+        );
+    }
+
+    fn effect(kind: TickKind, spell: &str, amount: u32, turns_left: u32) -> ActiveEffect {
+        ActiveEffect {
+            source: EffectSource { kind, spell: spell.to_string(), caster: "Fighter2".into() },
+            amount,
+            turns_left,
+        }
+    }
+
+    #[test]
+    fn test_effect_stacking_rules() {
+        let fighter1 = create_test_neopet("Fighter1");
+        let fighter2 = create_test_neopet("Fighter2");
+        let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
+
+        // A second burn refreshes the first, keeping the stronger and longer of both
+        battle_state.add_effect("Fighter1", effect(TickKind::Burn, "Ember", 5, 2)).unwrap();
+        battle_state.add_effect("Fighter1", effect(TickKind::Burn, "Scorch", 3, 4)).unwrap();
+        let burns = &battle_state.fighter("Fighter1").unwrap().effects;
+        assert_eq!(burns.len(), 1);
+        assert_eq!((burns[0].amount, burns[0].turns_left, burns[0].source.spell.as_str()), (5, 4, "Scorch"));
+
+        // Poison stacks, the stack closest to running out goes once there are too many
+        for turns in [2, 1, 3, 4] {
+            battle_state.add_effect("Fighter1", effect(TickKind::Poison, "Venom", 2, turns)).unwrap();
+        }
+        let poison: Vec<u32> = battle_state.fighter("Fighter1").unwrap().effects.iter()
+            .filter(|e| e.source.kind == TickKind::Poison)
+            .map(|e| e.turns_left)
+            .collect();
+        assert_eq!(poison, vec![2, 3, 4]);
+        assert_eq!(poison.len(), MAX_POISON_STACKS);
+
+        assert_eq!(
+            battle_state.add_effect("Nobody", effect(TickKind::Regen, "Bloom", 1, 1)),
+            Err(BattleError::UnknownFighter("Nobody".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_effects_come_from_ticking_spells_only() {
+        let burn = Spell { name: "Ember".to_string(), effect: serde_json::json!({"type": "burn", "damage": 5, "turns": 2}), cooldown: 0, inline: false };
+        let effect = ActiveEffect::from_spell(&burn, "Fighter2").unwrap();
+        assert_eq!(effect.source.kind, TickKind::Burn);
+        assert_eq!((effect.amount, effect.turns_left), (5, 2));
+
+        let damage = Spell { name: "Bolt".to_string(), effect: serde_json::json!({"type": "damage", "amount": 5}), cooldown: 0, inline: false };
+        let unknown = Spell { name: "Fireball".to_string(), effect: serde_json::json!({}), cooldown: 0, inline: false };
+        let no_turns = Spell { name: "Fizzle".to_string(), effect: serde_json::json!({"type": "regen", "amount": 5, "turns": 0}), cooldown: 0, inline: false };
+        for spell in [damage, unknown, no_turns] {
+            assert!(ActiveEffect::from_spell(&spell, "Fighter2").is_none(), "{}", spell.name);
+        }
     }
 
     // Integration test: Full battle state lifecycle
@@ -505,16 +1021,16 @@ mod battle_state_tests {
         
         // Simulate a battle
         battle_state.current_turn = 1;
-        battle_state.apply_damage("Fighter1", 30); // Fighter1: 70 HP
-        battle_state.apply_damage("Fighter2", 20); // Fighter2: 80 HP
-        battle_state.apply_healing("Fighter1", 10); // Fighter1: 80 HP
+        battle_state.apply_damage("Fighter1", 30).unwrap(); // Fighter1: 70 HP
+        battle_state.apply_damage("Fighter2", 20).unwrap(); // Fighter2: 80 HP
+        battle_state.apply_healing("Fighter1", 10).unwrap(); // Fighter1: 80 HP
         
-        assert_eq!(battle_state.fighter1_hp, 80);
-        assert_eq!(battle_state.fighter2_hp, 80);
+        assert_eq!(battle_state.get_hp("Fighter1").unwrap(), 80);
+        assert_eq!(battle_state.get_hp("Fighter2").unwrap(), 80);
         assert!(!battle_state.is_complete);
         
         // Deplete Fighter2's HP
-        battle_state.apply_damage("Fighter2", 100); // Fighter2: 0 HP
+        battle_state.apply_damage("Fighter2", 100).unwrap(); // Fighter2: 0 HP
         
         let completion = battle_state.check_battle_completion();
         assert!(completion.is_some());
@@ -528,60 +1044,275 @@ mod battle_state_tests {
     }
 }
 
-fn roll_d20<R: Rng>(rng: &mut R) -> u8 {
-    rng.random_range(1..=20)
-}
-
-#[derive(Debug, PartialEq)]
-enum Action {
+/// What a fighter does on their turn.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Action {
     Attack,
     CastSpell(usize),
     Heal,
+    /// Brace for the next hit: the defense roll counts twice until the fighter's next turn.
+    Defend,
+    /// Goad the opponent into attacking on their next turn, at an attack penalty.
+    Taunt,
 }
 
+/// Something that happened in a battle. Written as JSON objects tagged with their
+/// snake_case `type`, like `{"type": "heal", "turn": 3, "actor": "Acara", "amount": 5}`.
+/// Field names are part of the format: rename one and `event_schema` needs a new
+/// version to read the old name.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum BattleEvent {
+    /// A fighter rolled the dice, for initiative (turn 0), an attack, a defense or a heal.
     Roll {
         turn: u32,
-        actor: String,
-        dice: u8,
+        actor: FighterName,
+        /// Sum of the rolled dice
+        dice: u32,
+        /// The dice rolled, like `2d10` or `d20adv`. Missing from events saved before
+        /// rolls recorded it.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+        dice_expr: Option<Dice>,
         final_value: u32,
         is_positive_crit: bool,
         is_negative_crit: bool,
         goal: String,
     },
+    /// An attack landed, `actual_damage` is what got through the target's defense.
     Attack {
         turn: u32,
-        actor: String,
-        target: String,
+        actor: FighterName,
+        target: FighterName,
         raw_damage: u32,
         shield_value: u32,
         actual_damage: u32, 
+        /// The attack knocked the target out.
+        #[serde(default, skip_serializing_if = "is_false")]
+        is_killing_blow: bool,
+        /// Damage beyond the HP the target had left, 0 for an exact kill or when the
+        /// target survives.
+        #[serde(default, skip_serializing_if = "is_zero")]
+        overkill_amount: u32,
     },
+    /// A fighter's HP changed.
     HealthUpdate {
-        fighter_name: String,
+        fighter_name: FighterName,
         from: u32,
         to: u32,
         turn: u32,
+        /// The over-time effect that changed the HP, `None` for attacks and heals.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source: Option<EffectSource>,
     },
+    /// The actor healed themselves.
     Heal {
         turn: u32,
-        actor: String,
+        actor: FighterName,
         amount: u32,
     },
+    /// The actor cast one of their spells on `target`.
     SpellCast {
         turn: u32,
-        actor: String,
-        target: String,
+        actor: FighterName,
+        target: FighterName,
+        spell_name: String,
+    },
+    /// The actor picked a spell still on cooldown and rolled their behavior again.
+    SpellOnCooldown {
+        turn: u32,
+        actor: FighterName,
         spell_name: String,
+        turns_left: u32,
+    },
+    /// The defender evaded the attack entirely; no defense roll or damage follows.
+    Dodge {
+        turn: u32,
+        /// The defender
+        actor: FighterName,
+        attacker: FighterName,
+    },
+    /// The defender blocked part of the attack before rolling defense.
+    Block {
+        turn: u32,
+        /// The defender
+        actor: FighterName,
+        attacker: FighterName,
+        /// Attack points taken off the hit
+        blocked: u32,
+    },
+    /// A free-for-all fighter reached 0 HP and is out of the battle.
+    KnockedOut {
+        turn: u32,
+        fighter: FighterName,
+        by: FighterName,
+        /// Fighters still standing afterwards
+        remaining: u32,
+    },
+    /// The battle dragged on, every hit deals `bonus_damage` more from now on.
+    FatigueApplied {
+        turn: u32,
+        level: u32,
+        bonus_damage: u32,
+    },
+    /// The actor braces, doubling their defense rolls until their next turn.
+    Defend {
+        turn: u32,
+        actor: FighterName,
+    },
+    /// The actor goads `target` into attacking on their next turn, with `penalty` fewer
+    /// attack points.
+    Taunt {
+        turn: u32,
+        actor: FighterName,
+        target: FighterName,
+        penalty: u32,
     },
+    /// The arena changed a fighter's HP, by hurting them at the start of their turn
+    /// (negative) or boosting a heal. A `HealthUpdate` follows the damage, the `Heal`
+    /// already counts the boost.
+    ArenaEffect {
+        turn: u32,
+        arena: String,
+        fighter: FighterName,
+        hp_change: i32,
+    },
+    /// The odds of `fighter1` winning as the turn starts.
+    Prediction {
+        turn: u32,
+        fighter1: FighterName,
+        fighter2: FighterName,
+        /// fighter2's is 1.0 minus this
+        fighter1_win_probability: f64,
+    },
+    /// The odds of `fighter1` winning as the turn starts, and the HP both fighters are
+    /// expected to end the battle with, from playing the rest of it out `rollouts` times.
+    WinProbability {
+        turn: u32,
+        fighter1: FighterName,
+        fighter2: FighterName,
+        /// fighter2's is 1.0 minus this, a draw counting half for each
+        fighter1_win_probability: f64,
+        fighter1_expected_hp: f64,
+        fighter2_expected_hp: f64,
+        rollouts: u32,
+    },
+    /// The battle is over.
     BattleComplete {
         turn: u32,
-        winner: String,
-        loser: String,
+        winner: FighterName,
+        loser: FighterName,
         winner_final_hp: u32,
         loser_final_hp: u32,
         completion_reason: BattleCompletionReason,
+    },
+    /// The winner looted `items` from the loser.
+    LootDropped {
+        turn: u32,
+        winner: FighterName,
+        loser: FighterName,
+        items: Vec<Item>,
+    },
+}
+
+impl BattleEvent {
+    /// Turn the event happened on, 0 for the initiative rolls.
+    pub fn turn(&self) -> u32 {
+        match self {
+            BattleEvent::Roll { turn, .. }
+            | BattleEvent::Attack { turn, .. }
+            | BattleEvent::HealthUpdate { turn, .. }
+            | BattleEvent::Heal { turn, .. }
+            | BattleEvent::SpellCast { turn, .. }
+            | BattleEvent::SpellOnCooldown { turn, .. }
+            | BattleEvent::Defend { turn, .. }
+            | BattleEvent::Taunt { turn, .. }
+            | BattleEvent::Dodge { turn, .. }
+            | BattleEvent::Block { turn, .. }
+            | BattleEvent::KnockedOut { turn, .. }
+            | BattleEvent::FatigueApplied { turn, .. }
+            | BattleEvent::ArenaEffect { turn, .. }
+            | BattleEvent::Prediction { turn, .. }
+            | BattleEvent::WinProbability { turn, .. }
+            | BattleEvent::BattleComplete { turn, .. }
+            | BattleEvent::LootDropped { turn, .. } => *turn,
+        }
+    }
+
+    /// Whether `fighter` acts in the event, is on the receiving end of it or is named in it.
+    /// Fatigue hits everyone at once, so it involves nobody in particular.
+    pub fn involves(&self, fighter: &str) -> bool {
+        let is = |name: &str| name == fighter;
+        match self {
+            BattleEvent::Roll { actor, .. }
+            | BattleEvent::Heal { actor, .. }
+            | BattleEvent::SpellOnCooldown { actor, .. }
+            | BattleEvent::Defend { actor, .. } => is(actor),
+            BattleEvent::Attack { actor, target, .. }
+            | BattleEvent::SpellCast { actor, target, .. }
+            | BattleEvent::Taunt { actor, target, .. } => is(actor) || is(target),
+            BattleEvent::Dodge { actor, attacker, .. } | BattleEvent::Block { actor, attacker, .. } => {
+                is(actor) || is(attacker)
+            }
+            BattleEvent::HealthUpdate { fighter_name, source, .. } => {
+                is(fighter_name) || source.as_ref().is_some_and(|s| is(&s.caster))
+            }
+            BattleEvent::KnockedOut { fighter: knocked_out, by, .. } => is(knocked_out) || is(by),
+            BattleEvent::ArenaEffect { fighter: affected, .. } => is(affected),
+            BattleEvent::Prediction { fighter1, fighter2, .. } | BattleEvent::WinProbability { fighter1, fighter2, .. } => {
+                is(fighter1) || is(fighter2)
+            }
+            BattleEvent::BattleComplete { winner, loser, .. } | BattleEvent::LootDropped { winner, loser, .. } => {
+                is(winner) || is(loser)
+            }
+            BattleEvent::FatigueApplied { .. } => false,
+        }
+    }
+
+    /// Replaces `from` with `to` wherever `involves` would find it, for renamed fighters.
+    pub fn rename_fighter(&mut self, from: &str, to: &str) {
+        let rename = |name: &mut FighterName| {
+            if &**name == from {
+                *name = to.into();
+            }
+        };
+        match self {
+            BattleEvent::Roll { actor, .. }
+            | BattleEvent::Heal { actor, .. }
+            | BattleEvent::SpellOnCooldown { actor, .. }
+            | BattleEvent::Defend { actor, .. } => rename(actor),
+            BattleEvent::Attack { actor, target, .. }
+            | BattleEvent::SpellCast { actor, target, .. }
+            | BattleEvent::Taunt { actor, target, .. } => {
+                rename(actor);
+                rename(target);
+            }
+            BattleEvent::Dodge { actor, attacker, .. } | BattleEvent::Block { actor, attacker, .. } => {
+                rename(actor);
+                rename(attacker);
+            }
+            BattleEvent::HealthUpdate { fighter_name, source, .. } => {
+                rename(fighter_name);
+                if let Some(source) = source {
+                    rename(&mut source.caster);
+                }
+            }
+            BattleEvent::KnockedOut { fighter: knocked_out, by, .. } => {
+                rename(knocked_out);
+                rename(by);
+            }
+            BattleEvent::ArenaEffect { fighter: affected, .. } => rename(affected),
+            BattleEvent::Prediction { fighter1, fighter2, .. } | BattleEvent::WinProbability { fighter1, fighter2, .. } => {
+                rename(fighter1);
+                rename(fighter2);
+            }
+            BattleEvent::BattleComplete { winner, loser, .. } | BattleEvent::LootDropped { winner, loser, .. } => {
+                rename(winner);
+                rename(loser);
+            }
+            BattleEvent::FatigueApplied { .. } => {}
+        }
     }
 }
 
@@ -591,31 +1322,33 @@ fn process_turn<R: Rng>(actor: &Neopet, other: &Neopet, action: &Action, turn_nu
         Action::Attack => {
             let mut events = Vec::new();
             
-            let attack_roll = roll_d20(rng);
-            let attack_val = (attack_roll as u32) + actor.base_attack;
-            let attack_is_positive_crit = attack_roll == 20;
-            let attack_is_negative_crit = attack_roll == 1;
+            let attack_roll = Dice::D20.roll(rng);
+            let attack_val = attack_roll + actor.base_attack;
+            let attack_is_positive_crit = Dice::D20.is_positive_crit(attack_roll);
+            let attack_is_negative_crit = Dice::D20.is_negative_crit(attack_roll);
             
             events.push(BattleEvent::Roll {
                 turn: turn_number,
-                actor: actor.name.clone(),
+                actor: actor.name.as_str().into(),
                 dice: attack_roll,
+                dice_expr: Some(Dice::D20),
                 final_value: attack_val,
                 is_positive_crit: attack_is_positive_crit,
                 is_negative_crit: attack_is_negative_crit,
                 goal: "attack".to_string(),
             });
 
-            let defense_roll = roll_d20(rng);
-            let defense_val = (defense_roll as u32) + other.base_defense;
+            let defense_roll = Dice::D20.roll(rng);
+            let defense_val = defense_roll + other.base_defense;
             
             events.push(BattleEvent::Roll {
                 turn: turn_number,
-                actor: other.name.clone(),
+                actor: other.name.as_str().into(),
                 dice: defense_roll,
+                dice_expr: Some(Dice::D20),
                 final_value: defense_val,
-                is_positive_crit: defense_roll == 20,
-                is_negative_crit: defense_roll == 1,
+                is_positive_crit: Dice::D20.is_positive_crit(defense_roll),
+                is_negative_crit: Dice::D20.is_negative_crit(defense_roll),
                 goal: "defense".to_string(),
             });
             
@@ -629,11 +1362,13 @@ fn process_turn<R: Rng>(actor: &Neopet, other: &Neopet, action: &Action, turn_nu
             
             events.push(BattleEvent::Attack {
                 turn: turn_number,
-                actor: actor.name.clone(),
-                target: other.name.clone(),
+                actor: actor.name.as_str().into(),
+                target: other.name.as_str().into(),
                 raw_damage: attack_val,
                 shield_value: defense_val,
                 actual_damage: actual_damage,
+                is_killing_blow: false,
+                overkill_amount: 0,
             });
             
             events
@@ -641,10 +1376,10 @@ fn process_turn<R: Rng>(actor: &Neopet, other: &Neopet, action: &Action, turn_nu
         Action::Heal => {
             let mut events = Vec::new();
             
-            let heal_roll = roll_d20(rng);
+            let heal_roll = Dice::D20.roll(rng);
 
-            let is_positive_crit = heal_roll == 20;
-            let is_negative_crit = heal_roll == 1;
+            let is_positive_crit = Dice::D20.is_positive_crit(heal_roll);
+            let is_negative_crit = Dice::D20.is_negative_crit(heal_roll);
             let mut heal_val = actor.heal_delta;
             if is_positive_crit {
                 heal_val = heal_val * 2;
@@ -655,8 +1390,9 @@ fn process_turn<R: Rng>(actor: &Neopet, other: &Neopet, action: &Action, turn_nu
             
             events.push(BattleEvent::Roll {
                 turn: turn_number,
-                actor: actor.name.clone(),
+                actor: actor.name.as_str().into(),
                 dice: heal_roll,
+                dice_expr: Some(Dice::D20),
                 final_value: heal_val,
                 is_positive_crit: is_positive_crit,
                 is_negative_crit: is_negative_crit,
@@ -665,7 +1401,7 @@ fn process_turn<R: Rng>(actor: &Neopet, other: &Neopet, action: &Action, turn_nu
             
             events.push(BattleEvent::Heal {
                 turn: turn_number,
-                actor: actor.name.clone(),
+                actor: actor.name.as_str().into(),
                 amount: heal_val,
             });
             
@@ -675,60 +1411,74 @@ fn process_turn<R: Rng>(actor: &Neopet, other: &Neopet, action: &Action, turn_nu
             let spell_name = if let Some(spell) = actor.spells.get(*spell_index) {
                 spell.name.clone()
             } else {
-                println!("Error: No spell found at index {}", spell_index);
+                #[cfg(feature = "log")]
+                tracing::warn!(actor = %actor.name, spell_index, "no spell found at index");
                 "Unknown Spell".to_string()
             };
             
             vec![BattleEvent::SpellCast {
                 turn: turn_number,
-                actor: actor.name.clone(),
-                target: other.name.clone(),
+                actor: actor.name.as_str().into(),
+                target: other.name.as_str().into(),
                 spell_name: spell_name,
             }]
         }
+        Action::Defend => vec![BattleEvent::Defend { turn: turn_number, actor: actor.name.as_str().into() }],
+        Action::Taunt => vec![BattleEvent::Taunt {
+            turn: turn_number,
+            actor: actor.name.as_str().into(),
+            target: other.name.as_str().into(),
+            penalty: BattleConfig::default().taunt_penalty,
+        }],
     }
 }
 
-fn roll_for_initiative<'a, R: Rng>(
+pub(crate) fn roll_for_initiative<'a, R: Rng>(
     fighter1: &'a Neopet,
     fighter2: &'a Neopet,
+    config: &BattleConfig,
     rng: &mut R,
 ) -> (Vec<BattleEvent>, &'a Neopet, &'a Neopet) {
+    let dice1 = config.dice_for(&fighter1.name).initiative;
+    let dice2 = config.dice_for(&fighter2.name).initiative;
     let mut fighter1_initiative = 0;
     let mut fighter2_initiative = 0;
     let mut events = Vec::new();
 
-    while fighter1_initiative == fighter2_initiative {
-        let roll1 = roll_d20(rng);
+    while events.is_empty() || fighter1_initiative == fighter2_initiative && config.initiative_tie == InitiativeTie::Reroll {
+        let roll1 = dice1.roll(rng);
         events.push(BattleEvent::Roll {
             turn: 0, // Turn 0 for initiative phase
-            actor: fighter1.name.clone(),
+            actor: fighter1.name.as_str().into(),
             dice: roll1,
-            final_value: roll1 as u32,
-            is_positive_crit: roll1 == 20,
-            is_negative_crit: roll1 == 1,
+            dice_expr: Some(dice1),
+            final_value: roll1 + fighter1.speed,
+            is_positive_crit: config.is_crit(dice1, roll1),
+            is_negative_crit: config.is_fumble(dice1, roll1),
             goal: "initiative".to_string(),
         });
         
-        let roll2 = roll_d20(rng);
+        let roll2 = dice2.roll(rng);
         events.push(BattleEvent::Roll {
             turn: 0, // Turn 0 for initiative phase
-            actor: fighter2.name.clone(),
+            actor: fighter2.name.as_str().into(),
             dice: roll2,
-            final_value: roll2 as u32,
-            is_positive_crit: roll2 == 20,
-            is_negative_crit: roll2 == 1,
+            dice_expr: Some(dice2),
+            final_value: roll2 + fighter2.speed,
+            is_positive_crit: config.is_crit(dice2, roll2),
+            is_negative_crit: config.is_fumble(dice2, roll2),
             goal: "initiative".to_string(),
         });
         
-        fighter1_initiative = roll1;
-        fighter2_initiative = roll2;
+        fighter1_initiative = roll1 + fighter1.speed;
+        fighter2_initiative = roll2 + fighter2.speed;
     }
 
     let mut first: &Neopet = fighter1;
     let mut second: &Neopet = fighter2;
 
-    if fighter2_initiative > fighter1_initiative {
+    let tie_goes_to_fighter2 = config.initiative_tie == InitiativeTie::HigherHealth && fighter2.health > fighter1.health;
+    if fighter2_initiative > fighter1_initiative || fighter2_initiative == fighter1_initiative && tie_goes_to_fighter2 {
         first = fighter2;
         second = fighter1;
     }
@@ -736,14 +1486,20 @@ fn roll_for_initiative<'a, R: Rng>(
     (events, first, second)
 }
 
-fn choose_action<R: Rng>(neopet: &Neopet, rng: &mut R) -> Action {
+pub(crate) fn choose_action<R: Rng + ?Sized>(neopet: &Neopet, rng: &mut R) -> Action {
+    let behavior = &neopet.behavior;
     let roll: f64 = rng.random();
-    if roll < neopet.behavior.attack_chance {
+    if roll < behavior.attack_chance {
         Action::Attack
-    } else if roll < neopet.behavior.attack_chance + neopet.behavior.heal_chance {
+    } else if roll < behavior.attack_chance + behavior.heal_chance {
         Action::Heal
+    } else if roll < behavior.attack_chance + behavior.heal_chance + behavior.defend_chance {
+        Action::Defend
+    } else if roll < behavior.attack_chance + behavior.heal_chance + behavior.defend_chance + behavior.taunt_chance {
+        Action::Taunt
     } else {
-        let spell_roll = roll - (neopet.behavior.attack_chance + neopet.behavior.heal_chance);
+        let spell_roll =
+            roll - (behavior.attack_chance + behavior.heal_chance + behavior.defend_chance + behavior.taunt_chance);
         let mut cumulative = 0.0;
         for (index, &chance) in neopet.behavior.spell_chances.iter().enumerate() {
             cumulative += chance;
@@ -757,53 +1513,102 @@ fn choose_action<R: Rng>(neopet: &Neopet, rng: &mut R) -> Action {
 }
 
 /// Process a turn with HP tracking and HealthUpdate events
-fn process_turn_with_state<R: Rng>(
-    actor_name: &str,
-    target_name: &str,
+#[cfg_attr(
+    feature = "log",
+    tracing::instrument(name = "turn", level = "debug", skip_all, err, fields(turn = turn_number, actor = %actor_stats.name, target = %target_stats.name, action = ?action))
+)]
+pub fn process_turn_with_state<R: Rng>(
     actor_stats: &Neopet, // Contains attack/defense stats
     target_stats: &Neopet, // Contains attack/defense stats
     action: &Action,
     turn_number: u32,
     battle_state: &mut BattleState,
+    config: &BattleConfig,
     rng: &mut R,
-) -> Vec<BattleEvent> {
+) -> Result<Vec<BattleEvent>, BattleError> {
+    let actor_name = actor_stats.name.as_str();
+    let target_name = target_stats.name.as_str();
     let mut events = Vec::new();
     
     // If battle is already complete, return empty events
     if battle_state.is_complete {
-        return events;
+        return Ok(events);
     }
 
-    battle_state.current_turn = turn_number;
+    battle_state.advance_turn(turn_number);
+    events.extend(tick_effects(actor_name, turn_number, battle_state, config)?);
+    events.extend(arena_damage(actor_name, turn_number, battle_state, config)?);
+    // A tick or the arena can knock the actor out before it gets to act
+    if battle_state.get_hp(actor_name)? == 0 {
+        return Ok(events);
+    }
+    // Stances wear off once the fighter's turn comes around again, and a taunted
+    // fighter can only attack
+    let stance = battle_state.fighter_mut(actor_name)?;
+    stance.defending = false;
+    let taunted = std::mem::take(&mut stance.taunted);
+    let action = &if taunted {
+        Action::Attack
+    } else {
+        ready_action(actor_name, actor_stats, action, turn_number, battle_state, rng, &mut events)?
+    };
+    let (actor, target) = (battle_state.name(actor_name)?, battle_state.name(target_name)?);
     
     match action {
         Action::Attack => {
             // Roll for attack
-            let attack_roll = roll_d20(rng);
-            let attack_val = (attack_roll as u32) + actor_stats.base_attack;
-            let attack_is_positive_crit = attack_roll == 20;
-            let attack_is_negative_crit = attack_roll == 1;
+            let attack_dice = battle_state.get_dice(actor_name)?.attack;
+            let attack_roll = attack_dice.roll(rng);
+            let penalty = if taunted { config.taunt_penalty } else { 0 };
+            let attack_val = (attack_roll + actor_stats.base_attack).saturating_sub(penalty);
+            let attack_is_positive_crit = config.is_crit(attack_dice, attack_roll);
+            let attack_is_negative_crit = config.is_fumble(attack_dice, attack_roll);
             
             events.push(BattleEvent::Roll {
                 turn: turn_number,
-                actor: actor_name.to_string(),
+                actor: actor.clone(),
                 dice: attack_roll,
+                dice_expr: Some(attack_dice),
                 final_value: attack_val,
                 is_positive_crit: attack_is_positive_crit,
                 is_negative_crit: attack_is_negative_crit,
                 goal: "attack".to_string(),
             });
+
+            // Only agile fighters roll to dodge, so everyone else's rolls stay the same
+            if target_stats.dodge_chance > 0.0 && rng.random::<f64>() < target_stats.dodge_chance {
+                events.push(BattleEvent::Dodge {
+                    turn: turn_number,
+                    actor: target.clone(),
+                    attacker: actor.clone(),
+                });
+                return Ok(events);
+            }
+
+            let blocked = target_stats.block_value.min(attack_val);
+            let attack_val = attack_val - blocked;
+            if blocked > 0 {
+                events.push(BattleEvent::Block {
+                    turn: turn_number,
+                    actor: target.clone(),
+                    attacker: actor.clone(),
+                    blocked,
+                });
+            }
             
             // Roll for defense
-            let defense_roll = roll_d20(rng);
-            let defense_val = (defense_roll as u32) + target_stats.base_defense;
-            let defense_is_positive_crit = defense_roll == 20;
-            let defense_is_negative_crit = defense_roll == 1;
+            let defense_dice = battle_state.get_dice(target_name)?.defense;
+            let defense_roll = defense_dice.roll(rng);
+            let braced = if battle_state.fighter(target_name)?.defending { 2 } else { 1 };
+            let defense_val = defense_roll * braced + target_stats.base_defense;
+            let defense_is_positive_crit = config.is_crit(defense_dice, defense_roll);
+            let defense_is_negative_crit = config.is_fumble(defense_dice, defense_roll);
             
             events.push(BattleEvent::Roll {
                 turn: turn_number,
-                actor: target_name.to_string(),
+                actor: target.clone(),
                 dice: defense_roll,
+                dice_expr: Some(defense_dice),
                 final_value: defense_val,
                 is_positive_crit: defense_is_positive_crit,
                 is_negative_crit: defense_is_negative_crit,
@@ -813,43 +1618,57 @@ fn process_turn_with_state<R: Rng>(
             // Calculate damage
             let mut actual_damage = attack_val.saturating_sub(defense_val);
             if attack_is_positive_crit {
-                actual_damage *= 2;
+                actual_damage *= config.crit_multiplier;
             }
+            if let Some(class) = actor_stats.class {
+                let attacker = battle_state.fighter(actor_name)?;
+                actual_damage = class.damage_dealt(actual_damage, attacker.hp, attacker.max_hp);
+            }
+            if let Some(class) = target_stats.class {
+                actual_damage = class.damage_taken(actual_damage, attack_is_positive_crit);
+            }
+            // Tired fighters can't fully block anymore, only a fumble still misses
+            actual_damage += battle_state.fatigue_bonus;
             if attack_is_negative_crit {
                 actual_damage = 0;
             }
+            let old_hp = battle_state.get_hp(target_name)?;
+            let is_killing_blow = actual_damage > 0 && actual_damage >= old_hp;
             
             events.push(BattleEvent::Attack {
                 turn: turn_number,
-                actor: actor_name.to_string(),
-                target: target_name.to_string(),
+                actor: actor.clone(),
+                target: target.clone(),
                 raw_damage: attack_val,
                 shield_value: defense_val,
                 actual_damage,
+                is_killing_blow,
+                overkill_amount: if is_killing_blow { actual_damage - old_hp } else { 0 },
             });
             
             // Apply damage and generate HealthUpdate event
             if actual_damage > 0 {
-                let old_hp = battle_state.get_hp(target_name);
-                let new_hp = battle_state.apply_damage(target_name, actual_damage);
+                let new_hp = battle_state.apply_damage(target_name, actual_damage)?;
                 
                 events.push(BattleEvent::HealthUpdate {
-                    fighter_name: target_name.to_string(),
+                    fighter_name: target.clone(),
                     from: old_hp,
                     to: new_hp,
                     turn: turn_number,
+                    source: None,
                 });
             }
         }
         
         Action::Heal => {
-            let heal_roll = roll_d20(rng);
-            let is_positive_crit = heal_roll == 20;
-            let is_negative_crit = heal_roll == 1;
+            let heal_dice = battle_state.get_dice(actor_name)?.heal;
+            let heal_roll = heal_dice.roll(rng);
+            let is_positive_crit = config.is_crit(heal_dice, heal_roll);
+            let is_negative_crit = config.is_fumble(heal_dice, heal_roll);
             let mut heal_amount = actor_stats.heal_delta;
             
             if is_positive_crit {
-                heal_amount *= 2;
+                heal_amount *= config.crit_multiplier;
             }
             if is_negative_crit {
                 heal_amount = 0;
@@ -857,36 +1676,54 @@ fn process_turn_with_state<R: Rng>(
             
             events.push(BattleEvent::Roll {
                 turn: turn_number,
-                actor: actor_name.to_string(),
+                actor: actor.clone(),
                 dice: heal_roll,
+                dice_expr: Some(heal_dice),
                 final_value: heal_amount,
                 is_positive_crit,
                 is_negative_crit,
                 goal: "heal".to_string(),
             });
+            if let Some(arena) = &config.arena {
+                let bonus = arena.heal_bonus(heal_amount);
+                if bonus != 0 {
+                    heal_amount = heal_amount.saturating_add_signed(bonus);
+                    events.push(BattleEvent::ArenaEffect {
+                        turn: turn_number,
+                        arena: arena.name.clone(),
+                        fighter: actor.clone(),
+                        hp_change: bonus,
+                    });
+                }
+            }
             
             events.push(BattleEvent::Heal {
                 turn: turn_number,
-                actor: actor_name.to_string(),
+                actor: actor.clone(),
                 amount: heal_amount,
             });
             
             // Apply healing and generate HealthUpdate event
             if heal_amount > 0 {
-                let old_hp = battle_state.get_hp(actor_name);
-                let new_hp = battle_state.apply_healing(actor_name, heal_amount);
+                let old_hp = battle_state.get_hp(actor_name)?;
+                let new_hp = battle_state.apply_healing_capped(actor_name, heal_amount, config.heal_cap)?;
                 
                 events.push(BattleEvent::HealthUpdate {
-                    fighter_name: actor_name.to_string(),
+                    fighter_name: actor.clone(),
                     from: old_hp,
                     to: new_hp,
                     turn: turn_number,
+                    source: None,
                 });
+                if actor_stats.class.is_some_and(|class| class.cleanses()) {
+                    battle_state.cleanse(actor_name)?;
+                }
             }
         }
         
         Action::CastSpell(spell_index) => {
-            let spell_name = if let Some(spell) = actor_stats.spells.get(*spell_index) {
+            let spell = actor_stats.spells.get(*spell_index);
+            let spell_name = if let Some(spell) = spell {
                 spell.name.clone()
             } else {
                 "Unknown Spell".to_string()
@@ -894,96 +1731,541 @@ fn process_turn_with_state<R: Rng>(
             
             events.push(BattleEvent::SpellCast {
                 turn: turn_number,
-                actor: actor_name.to_string(),
-                target: target_name.to_string(),
+                actor: actor.clone(),
+                target: target.clone(),
                 spell_name,
             });
-        }
-    }
-    
-    events
-}
+            if let Some(spell) = spell {
+                battle_state.fighter_mut(actor_name)?.spells_cast.insert(spell.name.clone(), turn_number);
+            }
 
-pub fn battle_loop<R: Rng>(fighter1: &Neopet, fighter2: &Neopet, rng: &mut R) -> Vec<BattleEvent> {
-    let (initiative_events, first, second) = roll_for_initiative(fighter1, fighter2, rng);
-    
-    let max_turns = 10; // Very short for testing - will definitely complete
-    let mut battle_state = BattleState::new(fighter1, fighter2, max_turns);
-    let mut all_events = initiative_events; // Start with initiative events
+            // Burns and poison stick to the target, regens to the caster
+            if let Some(mut effect) = spell.and_then(|spell| ActiveEffect::from_spell(spell, actor.clone())) {
+                if let Some(class) = actor_stats.class {
+                    effect.turns_left = class.effect_turns(effect.turns_left);
+                }
+                let bearer = if effect.source.kind == TickKind::Regen { actor_name } else { target_name };
+                battle_state.add_effect(bearer, effect)?;
+            }
 
-    let mut turn = 1; // Start battle turns at 1
-    
-    while !battle_state.is_complete && turn <= max_turns {
-        // First fighter's turn
-        if !battle_state.is_complete {
-            let first_action = choose_action(first, rng);
-            let events = process_turn_with_state(
-                &first.name, 
-                &second.name, 
-                first, 
-                second, 
-                &first_action, 
-                turn, 
-                &mut battle_state, 
-                rng
-            );
-            all_events.extend(events);
-            
-            // Check if battle ended after first fighter's action
-            if battle_state.check_battle_completion().is_some() {
-                break;
+            // Damage, heals and drains land at once, a drain heals the caster by the HP it took
+            let instant = spell.and_then(|spell| serde_json::from_value(spell.effect.clone()).ok());
+            let mut heal = match instant {
+                Some(SpellEffect::Heal { amount }) => amount,
+                _ => 0,
+            };
+            if let Some(SpellEffect::Damage { amount } | SpellEffect::Drain { amount }) = instant {
+                let from = battle_state.get_hp(target_name)?;
+                let to = battle_state.apply_damage(target_name, amount)?;
+                if from != to {
+                    events.push(BattleEvent::HealthUpdate { fighter_name: target.clone(), from, to, turn: turn_number, source: None });
+                }
+                if matches!(instant, Some(SpellEffect::Drain { .. })) {
+                    heal = from - to;
+                }
             }
-        }
-        
-        if !battle_state.is_complete && turn < max_turns {
-            turn += 1;
-            
-            // Second fighter's turn
-            let second_action = choose_action(second, rng);
-            let events = process_turn_with_state(
-                &second.name, 
-                &first.name, 
-                second, 
-                first, 
-                &second_action, 
-                turn, 
-                &mut battle_state, 
-                rng
-            );
-            all_events.extend(events);
-            
-            // Check if battle ended after second fighter's action
-            if battle_state.check_battle_completion().is_some() {
-                break;
+            if heal > 0 {
+                let from = battle_state.get_hp(actor_name)?;
+                let to = battle_state.apply_healing_capped(actor_name, heal, config.heal_cap)?;
+                if from != to {
+                    events.push(BattleEvent::HealthUpdate { fighter_name: actor.clone(), from, to, turn: turn_number, source: None });
+                }
             }
         }
-        
-        if !battle_state.is_complete {
-            turn += 1;
+
+        Action::Defend => {
+            battle_state.fighter_mut(actor_name)?.defending = true;
+            events.push(BattleEvent::Defend { turn: turn_number, actor: actor.clone() });
+        }
+
+        Action::Taunt => {
+            battle_state.fighter_mut(target_name)?.taunted = true;
+            events.push(BattleEvent::Taunt {
+                turn: turn_number,
+                actor: actor.clone(),
+                target: target.clone(),
+                penalty: config.taunt_penalty,
+            });
         }
     }
     
-    // Generate BattleComplete event if battle ended
-    if let Some((winner, loser)) = battle_state.get_winner_loser() {
-        let winner_hp = battle_state.get_hp(&winner);
-        let loser_hp = battle_state.get_hp(&loser);
-        
-        all_events.push(BattleEvent::BattleComplete {
-            turn: battle_state.current_turn,
+    Ok(events)
+}
+
+/// `action`, unless it's a spell the actor can't cast again yet: then it emits a
+/// `SpellOnCooldown` and rolls the actor's behavior again until it lands on something
+/// they can do. Actors with no other choice attack.
+fn ready_action<R: Rng>(
+    actor_name: &str,
+    actor_stats: &Neopet,
+    action: &Action,
+    turn: u32,
+    battle_state: &BattleState,
+    rng: &mut R,
+    events: &mut Vec<BattleEvent>,
+) -> Result<Action, BattleError> {
+    let on_cooldown = |action: &Action| -> Result<Option<(&Spell, u32)>, BattleError> {
+        let Action::CastSpell(index) = action else {
+            return Ok(None);
+        };
+        let Some(spell) = actor_stats.spells.get(*index) else {
+            return Ok(None);
+        };
+        let turns_left = battle_state.cooldown_left(actor_name, spell, turn)?;
+        Ok((turns_left > 0).then_some((spell, turns_left)))
+    };
+
+    let Some((spell, turns_left)) = on_cooldown(action)? else {
+        return Ok(action.clone());
+    };
+    events.push(BattleEvent::SpellOnCooldown {
+        turn,
+        actor: battle_state.name(actor_name)?,
+        spell_name: spell.name.clone(),
+        turns_left,
+    });
+
+    let behavior = &actor_stats.behavior;
+    let mut ready_spells = Vec::new();
+    for (index, chance) in behavior.spell_chances.iter().enumerate() {
+        if *chance > 0.0 && on_cooldown(&Action::CastSpell(index))?.is_none() {
+            ready_spells.push(index);
+        }
+    }
+    let other_chances = behavior.attack_chance + behavior.heal_chance + behavior.defend_chance + behavior.taunt_chance;
+    if other_chances <= 0.0 && ready_spells.is_empty() {
+        return Ok(Action::Attack);
+    }
+    loop {
+        let action = choose_action(actor_stats, rng);
+        if on_cooldown(&action)?.is_none() {
+            return Ok(action);
+        }
+    }
+}
+
+/// Applies the over-time effects on `fighter_name` at the start of its turn, oldest
+/// first, and drops the ones that ran out. Each tick that changes the HP emits a
+/// `HealthUpdate` naming its source.
+fn tick_effects(
+    fighter_name: &str,
+    turn: u32,
+    battle_state: &mut BattleState,
+    config: &BattleConfig,
+) -> Result<Vec<BattleEvent>, BattleError> {
+    let effects = std::mem::take(&mut battle_state.fighter_mut(fighter_name)?.effects);
+    let name = battle_state.name(fighter_name)?;
+    let mut events = Vec::new();
+    let mut remaining = Vec::new();
+    for mut effect in effects {
+        let from = battle_state.get_hp(fighter_name)?;
+        let to = match effect.source.kind {
+            TickKind::Burn | TickKind::Poison => battle_state.apply_damage(fighter_name, effect.amount)?,
+            TickKind::Regen => battle_state.apply_healing_capped(fighter_name, effect.amount, config.heal_cap)?,
+        };
+        if from != to {
+            events.push(BattleEvent::HealthUpdate {
+                fighter_name: name.clone(),
+                from,
+                to,
+                turn,
+                source: Some(effect.source.clone()),
+            });
+        }
+        effect.turns_left -= 1;
+        if effect.turns_left > 0 {
+            remaining.push(effect);
+        }
+        if to == 0 {
+            break;
+        }
+    }
+    battle_state.fighter_mut(fighter_name)?.effects = remaining;
+    Ok(events)
+}
+
+/// Hurts `fighter_name` at the start of its turn if the arena deals damage every turn.
+fn arena_damage(
+    fighter_name: &str,
+    turn: u32,
+    battle_state: &mut BattleState,
+    config: &BattleConfig,
+) -> Result<Vec<BattleEvent>, BattleError> {
+    let Some(arena) = config.arena.as_ref().filter(|arena| arena.damage_per_turn > 0) else {
+        return Ok(vec![]);
+    };
+    let from = battle_state.get_hp(fighter_name)?;
+    if from == 0 {
+        return Ok(vec![]);
+    }
+    let to = battle_state.apply_damage(fighter_name, arena.damage_per_turn)?;
+    let name = battle_state.name(fighter_name)?;
+    Ok(vec![
+        BattleEvent::ArenaEffect {
+            turn,
+            arena: arena.name.clone(),
+            fighter: name.clone(),
+            hp_change: -((from - to) as i32),
+        },
+        BattleEvent::HealthUpdate { fighter_name: name, from, to, turn, source: None },
+    ])
+}
+
+/// Raises the fatigue bonus for `turn`, emitting a `FatigueApplied` event once fatigue kicks in.
+pub(crate) fn apply_fatigue(config: &BattleConfig, turn: u32, battle_state: &mut BattleState, events: &mut Vec<BattleEvent>) {
+    let level = config.fatigue_level(turn);
+    if level == 0 {
+        return;
+    }
+    battle_state.set_fatigue_bonus(level * config.fatigue_damage_per_level);
+    events.push(BattleEvent::FatigueApplied {
+        turn,
+        level,
+        bonus_damage: battle_state.fatigue_bonus,
+    });
+}
+
+/// Average damage of one attack, going through every pair of attack and defense rolls
+/// weighted by how likely each is. Dodged attacks deal nothing and blocks shave every hit.
+fn expected_attack_damage(
+    attacker: &Neopet,
+    defender: &Neopet,
+    attack_dice: Dice,
+    defense_dice: Dice,
+    fatigue_bonus: u32,
+    config: &BattleConfig,
+) -> f64 {
+    let defense_outcomes = defense_dice.outcomes();
+    let mut total = 0.0;
+    let mut combinations = 0.0;
+    for (attack_roll, attack_ways) in attack_dice.outcomes() {
+        for &(defense_roll, defense_ways) in &defense_outcomes {
+            let attack_val = (attack_roll + attacker.base_attack).saturating_sub(defender.block_value);
+            let defense_val = defense_roll + defender.base_defense;
+            let mut damage = attack_val.saturating_sub(defense_val);
+            let is_crit = config.is_crit(attack_dice, attack_roll);
+            if is_crit {
+                damage *= config.crit_multiplier;
+            }
+            if let Some(class) = defender.class {
+                damage = class.damage_taken(damage, is_crit);
+            }
+            damage += fatigue_bonus;
+            if config.is_fumble(attack_dice, attack_roll) {
+                damage = 0;
+            }
+            let ways = (attack_ways * defense_ways) as f64;
+            total += damage as f64 * ways;
+            combinations += ways;
+        }
+    }
+    (1.0 - defender.dodge_chance) * total / combinations
+}
+
+/// Average HP one heal restores, before the heal cap: crits multiply it and fumbles cancel it.
+fn expected_heal(neopet: &Neopet, heal_dice: Dice, config: &BattleConfig) -> f64 {
+    let mut total = 0.0;
+    let mut combinations = 0.0;
+    for (roll, ways) in heal_dice.outcomes() {
+        let amount = if config.is_fumble(heal_dice, roll) {
+            0
+        } else if config.is_crit(heal_dice, roll) {
+            neopet.heal_delta * config.crit_multiplier
+        } else {
+            neopet.heal_delta
+        };
+        total += amount as f64 * ways as f64;
+        combinations += ways as f64;
+    }
+    total / combinations
+}
+
+/// Cheap estimate of fighter 1's chance to win from the current state: each fighter's
+/// expected damage per turn, minus the opponent's expected healing, gives the turns
+/// they need to knock the other out, and the faster one is favored. Spells are ignored.
+pub fn estimate_win_probability(
+    fighter1: &Neopet,
+    fighter2: &Neopet,
+    battle_state: &BattleState,
+    config: &BattleConfig,
+) -> Result<f64, BattleError> {
+    let heal_rate = |neopet: &Neopet| -> Result<f64, BattleError> {
+        Ok(neopet.behavior.heal_chance * expected_heal(neopet, battle_state.get_dice(&neopet.name)?.heal, config))
+    };
+    let damage_rate = |attacker: &Neopet, defender: &Neopet| -> Result<f64, BattleError> {
+        let rate = attacker.behavior.attack_chance
+            * expected_attack_damage(
+                attacker,
+                defender,
+                battle_state.get_dice(&attacker.name)?.attack,
+                battle_state.get_dice(&defender.name)?.defense,
+                battle_state.fatigue_bonus,
+                config,
+            )
+            - heal_rate(defender)?;
+        Ok(rate.max(0.0))
+    };
+
+    let hp1 = battle_state.get_hp(&fighter1.name)? as f64;
+    let hp2 = battle_state.get_hp(&fighter2.name)? as f64;
+    if hp1 == 0.0 || hp2 == 0.0 {
+        return Ok(if hp1 > hp2 { 1.0 } else if hp2 > hp1 { 0.0 } else { 0.5 });
+    }
+
+    let rate1 = damage_rate(fighter1, fighter2)?;
+    let rate2 = damage_rate(fighter2, fighter1)?;
+    if rate1 == 0.0 && rate2 == 0.0 {
+        // Nobody can finish the other, the one with more HP left wins on time
+        return Ok(hp1 / (hp1 + hp2));
+    }
+
+    // Turns needed to knock out the opponent, infinite when a fighter can't
+    let turns1 = hp2 / rate1;
+    let turns2 = hp1 / rate2;
+    Ok(if turns1.is_infinite() {
+        0.0
+    } else if turns2.is_infinite() {
+        1.0
+    } else {
+        turns2 / (turns1 + turns2)
+    })
+}
+
+/// Emits a `Prediction` event for `turn` when the config asks for them.
+pub(crate) fn predict(
+    config: &BattleConfig,
+    fighter1: &Neopet,
+    fighter2: &Neopet,
+    turn: u32,
+    battle_state: &BattleState,
+    events: &mut Vec<BattleEvent>,
+) -> Result<(), BattleError> {
+    if !config.predictions {
+        return Ok(());
+    }
+    events.push(BattleEvent::Prediction {
+        turn,
+        fighter1: battle_state.name(&fighter1.name)?,
+        fighter2: battle_state.name(&fighter2.name)?,
+        fighter1_win_probability: estimate_win_probability(fighter1, fighter2, battle_state, config)?,
+    });
+    Ok(())
+}
+
+pub fn battle_loop<R: Rng>(fighter1: &Neopet, fighter2: &Neopet, rng: &mut R) -> Result<Vec<BattleEvent>, BattleError> {
+    battle_loop_with_config(fighter1, fighter2, &BattleConfig::default(), rng)
+}
+
+/// Runs a battle where each fighter acts on the strategy picked in their definition.
+pub fn battle_loop_with_config<R: Rng>(
+    fighter1: &Neopet,
+    fighter2: &Neopet,
+    config: &BattleConfig,
+    rng: &mut R,
+) -> Result<Vec<BattleEvent>, BattleError> {
+    let (mut strategy1, mut strategy2) = (fighter1.strategy.build(), fighter2.strategy.build());
+    battle_loop_with_strategies(fighter1, fighter2, config, &mut *strategy1, &mut *strategy2, rng)
+}
+
+/// Runs a battle where each fighter's actions are picked by their own strategy.
+/// Use a `BattleRunner` directly to get the events as they happen.
+#[cfg_attr(
+    feature = "log",
+    tracing::instrument(name = "battle", skip_all, err, fields(fighter1 = %fighter1.name, fighter2 = %fighter2.name))
+)]
+pub fn battle_loop_with_strategies<R: Rng>(
+    fighter1: &Neopet,
+    fighter2: &Neopet,
+    config: &BattleConfig,
+    strategy1: &mut dyn BattleStrategy,
+    strategy2: &mut dyn BattleStrategy,
+    rng: &mut R,
+) -> Result<Vec<BattleEvent>, BattleError> {
+    let runner = BattleRunner::with_strategies(fighter1, fighter2, config, Box::new(strategy1), Box::new(strategy2), rng)?;
+    run_observed(runner, &mut [])
+}
+
+/// Like `battle_loop_with_config`, also handing every event to each of `observers`
+/// as it happens. They hear about the end of the battle unless it failed.
+#[cfg_attr(
+    feature = "log",
+    tracing::instrument(name = "battle", skip_all, err, fields(fighter1 = %fighter1.name, fighter2 = %fighter2.name))
+)]
+pub fn battle_loop_with_observers<R: Rng>(
+    fighter1: &Neopet,
+    fighter2: &Neopet,
+    config: &BattleConfig,
+    observers: &mut [&mut dyn Observer],
+    rng: &mut R,
+) -> Result<Vec<BattleEvent>, BattleError> {
+    run_observed(BattleRunner::new(fighter1, fighter2, config, rng)?, observers)
+}
+
+fn run_observed<R: Rng>(mut runner: BattleRunner<'_, R>, observers: &mut [&mut dyn Observer]) -> Result<Vec<BattleEvent>, BattleError> {
+    let mut events = Vec::new();
+    for event in runner.by_ref() {
+        for observer in observers.iter_mut() {
+            observer.on_event(&event);
+        }
+        events.push(event);
+    }
+    if let Some(error) = runner.error() {
+        return Err(error.clone());
+    }
+    for observer in observers.iter_mut() {
+        observer.on_battle_end();
+    }
+    #[cfg(feature = "log")]
+    tracing::info!(events = events.len(), "battle complete");
+    Ok(events)
+}
+
+/// Checks the events of a finished duel between `fighters` against the rules every
+/// battle follows, naming the first one broken. See the `check_*` helpers for each rule.
+pub fn check_invariants(events: &[BattleEvent], fighters: &[&Neopet], config: &BattleConfig) -> Result<(), String> {
+    check_turn_order(events)?;
+    check_completion(events)?;
+    check_rolls(events)?;
+    check_hp(events, fighters, config)?;
+    check_damage(events)
+}
+
+/// Events come in turn order, turns never go back.
+pub fn check_turn_order(events: &[BattleEvent]) -> Result<(), String> {
+    for (index, pair) in events.windows(2).enumerate() {
+        if pair[1].turn() < pair[0].turn() {
+            return Err(format!(
+                "Event {} is on turn {}, after an event on turn {}",
+                index + 1,
+                pair[1].turn(),
+                pair[0].turn()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Exactly one `BattleComplete`, with nothing but the loot after it.
+pub fn check_completion(events: &[BattleEvent]) -> Result<(), String> {
+    let completions: Vec<usize> = events
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| matches!(e, BattleEvent::BattleComplete { .. }))
+        .map(|(index, _)| index)
+        .collect();
+    let [index] = completions[..] else {
+        return Err(format!("Expected one BattleComplete, found {}", completions.len()));
+    };
+    if let Some(late) = events[index + 1..].iter().find(|e| !matches!(e, BattleEvent::LootDropped { .. })) {
+        return Err(format!("{:?} comes after the BattleComplete", late));
+    }
+    Ok(())
+}
+
+/// No roll is a crit and a fumble at once.
+pub fn check_rolls(events: &[BattleEvent]) -> Result<(), String> {
+    match events.iter().find(|e| matches!(e, BattleEvent::Roll { is_positive_crit: true, is_negative_crit: true, .. })) {
+        Some(roll) => Err(format!("{:?} is both a crit and a fumble", roll)),
+        None => Ok(()),
+    }
+}
+
+/// Every `HealthUpdate` starts from the HP the last one left, HP never goes past
+/// what `config.heal_cap` allows, and the `BattleComplete` reports the HP left.
+pub fn check_hp(events: &[BattleEvent], fighters: &[&Neopet], config: &BattleConfig) -> Result<(), String> {
+    let mut hp: HashMap<&str, (u32, u32)> = fighters
+        .iter()
+        .map(|f| (f.name.as_str(), (f.health, config.heal_cap.limit(f.health))))
+        .collect();
+    let current = |hp: &HashMap<&str, (u32, u32)>, name: &str| {
+        hp.get(name).map(|(hp, _)| *hp).ok_or_else(|| format!("Unknown fighter '{}'", name))
+    };
+    for event in events {
+        match event {
+            BattleEvent::HealthUpdate { fighter_name, from, to, turn, .. } => {
+                let (now, limit) = hp.get_mut(&**fighter_name).ok_or_else(|| format!("Unknown fighter '{}'", fighter_name))?;
+                if from != now {
+                    return Err(format!("{} goes from {} HP on turn {}, but had {}", fighter_name, from, turn, now));
+                }
+                if to > limit {
+                    return Err(format!("{} reaches {} HP on turn {}, past the cap of {}", fighter_name, to, turn, limit));
+                }
+                *now = *to;
+            }
+            BattleEvent::BattleComplete { winner, loser, winner_final_hp, loser_final_hp, .. } => {
+                for (name, reported) in [(winner, winner_final_hp), (loser, loser_final_hp)] {
+                    let actual = current(&hp, name)?;
+                    if actual != *reported {
+                        return Err(format!("{} ends with {} HP, but the battle reports {}", name, actual, reported));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// An attack takes exactly its damage off the target (down to 0) in the
+/// `HealthUpdate` right after it, and is a killing blow, with the right overkill,
+/// exactly when that leaves the target with no HP.
+pub fn check_damage(events: &[BattleEvent]) -> Result<(), String> {
+    for (index, event) in events.iter().enumerate() {
+        let BattleEvent::Attack { turn, target, actual_damage, is_killing_blow, overkill_amount, .. } = event else {
+            continue;
+        };
+        let (from, to) = match events.get(index + 1) {
+            Some(BattleEvent::HealthUpdate { fighter_name, from, to, source: None, .. }) if fighter_name == target => (*from, *to),
+            _ if *actual_damage == 0 => {
+                if *is_killing_blow || *overkill_amount > 0 {
+                    return Err(format!("Attack on turn {} deals no damage but is a killing blow", turn));
+                }
+                continue;
+            }
+            _ => return Err(format!("Attack on turn {} deals {} damage without a HealthUpdate", turn, actual_damage)),
+        };
+        if from - to != (*actual_damage).min(from) {
+            return Err(format!("Attack on turn {} deals {} damage, but {} goes from {} to {} HP", turn, actual_damage, target, from, to));
+        }
+        if (*is_killing_blow, *overkill_amount) != (*actual_damage >= from, actual_damage.saturating_sub(from)) {
+            return Err(format!(
+                "Attack on turn {} against {} HP reports killing blow {} with {} overkill",
+                turn, from, is_killing_blow, overkill_amount
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Rolls the loser's loot table for the winner of a finished battle and appends a
+/// `LootDropped` event when anything dropped. Returns the dropped items, empty if
+/// nothing dropped or the battle has no winner.
+pub fn drop_loot<R: Rng>(events: &mut Vec<BattleEvent>, loot_tables: &LootTables, rng: &mut R) -> Vec<Item> {
+    let completion = events.iter().find_map(|e| match e {
+        BattleEvent::BattleComplete { turn, winner, loser, .. } => Some((*turn, winner.clone(), loser.clone())),
+        _ => None,
+    });
+    let Some((turn, winner, loser)) = completion else {
+        return Vec::new();
+    };
+
+    let items = loot_tables.for_opponent(&loser).roll(rng);
+    if !items.is_empty() {
+        events.push(BattleEvent::LootDropped {
+            turn,
             winner,
             loser,
-            winner_final_hp: winner_hp,
-            loser_final_hp: loser_hp,
-            completion_reason: battle_state.completion_reason.unwrap(),
+            items: items.clone(),
         });
     }
-    
-    all_events
+    items
 }
 
 #[cfg(test)]
 mod process_turn_with_state_tests {
     use super::*;
+    use macro_workshop::event_match;
+    use crate::classes::FighterClass;
     use crate::neopets::{Neopet, Spell, Behavior};
     use crate::battle::{BattleState, BattleEvent};
     use rand::SeedableRng;
@@ -991,23 +2273,131 @@ mod process_turn_with_state_tests {
     
     fn create_test_neopet(name: &str, health: u32, attack: u32, defense: u32) -> Neopet {
         Neopet {
-            name: name.to_string(),
             health,
             base_attack: attack,
             base_defense: defense,
             heal_delta: 10,
-            spells: vec![],
-            behavior: Behavior {
-                attack_chance: 0.5,
-                spell_chances: vec![],
-                heal_chance: 0.3,
-            },
+            behavior: Behavior { heal_chance: 0.3, ..Behavior::attack_or_heal(0.5) },
+            ..Neopet::test_fighter(name)
         }
     }
     
     fn create_seeded_rng() -> impl Rng {
         rand::rngs::StdRng::seed_from_u64(42)
     }
+
+    #[test]
+    fn test_events_share_the_fighter_names() {
+        let fighter1 = create_test_neopet("Fighter1", 100, 10, 5);
+        let fighter2 = create_test_neopet("Fighter2", 100, 10, 5);
+        let mut battle_state = BattleState::new(&fighter1, &fighter2, 10);
+        let mut rng = create_seeded_rng();
+        let config = BattleConfig::default();
+        let events = process_turn_with_state(
+            &fighter1, &fighter2, &Action::Attack, 1, &mut battle_state, &config, &mut rng,
+        )
+        .unwrap();
+
+        let shared = battle_state.name("Fighter1").unwrap();
+        let BattleEvent::Roll { actor, .. } = &events[0] else {
+            panic!("Expected the attack roll, got {:?}", events[0]);
+        };
+        assert!(Arc::ptr_eq(actor, &shared));
+        assert!(battle_state.name("Nobody").is_err());
+    }
+
+    #[test]
+    fn test_crit_range_and_multiplier() {
+        let config = BattleConfig { crit_range: 3, fumble_range: 2, ..BattleConfig::default() };
+        assert!(config.is_crit(Dice::D20, 18) && !config.is_crit(Dice::D20, 17));
+        assert!(config.is_fumble(Dice::D20, 2) && !config.is_fumble(Dice::D20, 3));
+        let no_crits = BattleConfig { crit_range: 0, fumble_range: 0, ..BattleConfig::default() };
+        assert!(!no_crits.is_crit(Dice::D20, 20) && !no_crits.is_fumble(Dice::D20, 1));
+
+        // Every roll crits, so damage is always tripled
+        let actor = create_test_neopet("Attacker", 100, 30, 0);
+        let target = create_test_neopet("Defender", 1000, 5, 0);
+        let config = BattleConfig { crit_range: 20, fumble_range: 0, crit_multiplier: 3, ..BattleConfig::default() };
+        let mut battle_state = BattleState::new(&actor, &target, 10);
+        let events = process_turn_with_state(
+            &actor, &target,
+            &Action::Attack,
+            1, &mut battle_state, &config, &mut create_seeded_rng()
+        ).unwrap();
+
+        let attack = events.iter().find_map(|e| match e {
+            BattleEvent::Attack { raw_damage, shield_value, actual_damage, .. } => Some((*raw_damage, *shield_value, *actual_damage)),
+            _ => None,
+        }).unwrap();
+        assert_eq!(attack.2, (attack.0 - attack.1) * 3);
+    }
+
+    #[test]
+    fn test_heal_cap_allows_overheal() {
+        let actor = create_test_neopet("Healer", 100, 10, 5);
+        let target = create_test_neopet("Target", 100, 5, 3);
+        let config = BattleConfig {
+            crit_range: 0,
+            fumble_range: 0,
+            heal_cap: HealCap::Overheal(105),
+            ..BattleConfig::default()
+        };
+        let mut battle_state = BattleState::new(&actor, &target, 10);
+        let mut rng = create_seeded_rng();
+
+        for turn in 1..=3 {
+            process_turn_with_state(
+                &actor, &target,
+                &Action::Heal,
+                turn, &mut battle_state, &config, &mut rng
+            ).unwrap();
+        }
+        assert_eq!(battle_state.get_hp("Healer").unwrap(), 105);
+    }
+
+    #[test]
+    fn test_dodge_skips_defense_and_damage() {
+        let actor = create_test_neopet("Attacker", 100, 30, 0);
+        let target = Neopet { dodge_chance: 1.0, ..create_test_neopet("Defender", 100, 5, 0) };
+        let config = BattleConfig::default();
+        let mut battle_state = BattleState::new(&actor, &target, 10);
+        let events = process_turn_with_state(
+            &actor, &target,
+            &Action::Attack,
+            1, &mut battle_state, &config, &mut create_seeded_rng()
+        ).unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], BattleEvent::Roll { goal, .. } if goal == "attack"));
+        event_match!(events[1], Dodge { turn: 1, actor: "Defender".into(), attacker: "Attacker".into() });
+        assert_eq!(battle_state.get_hp("Defender").unwrap(), 100);
+        assert_eq!(expected_attack_damage(&actor, &target, Dice::D20, Dice::D20, 0, &config), 0.0);
+    }
+
+    #[test]
+    fn test_block_shaves_the_attack_before_defense() {
+        let actor = create_test_neopet("Attacker", 100, 30, 0);
+        let target = Neopet { block_value: 4, ..create_test_neopet("Defender", 100, 5, 0) };
+        let config = BattleConfig { crit_range: 0, fumble_range: 0, ..BattleConfig::default() };
+        let mut battle_state = BattleState::new(&actor, &target, 10);
+        let events = process_turn_with_state(
+            &actor, &target,
+            &Action::Attack,
+            1, &mut battle_state, &config, &mut create_seeded_rng()
+        ).unwrap();
+
+        let attack_value = match &events[0] {
+            BattleEvent::Roll { final_value, .. } => *final_value,
+            other => panic!("expected the attack roll, got {:?}", other),
+        };
+        event_match!(events[1], Block { turn: 1, actor: "Defender".into(), attacker: "Attacker".into(), blocked: 4 });
+        assert!(matches!(&events[2], BattleEvent::Roll { goal, .. } if goal == "defense"));
+        let raw_damage = events.iter().find_map(|e| match e {
+            BattleEvent::Attack { raw_damage, .. } => Some(*raw_damage),
+            _ => None,
+        }).unwrap();
+        assert_eq!(raw_damage, attack_value - 4);
+    }
     
     #[test]
     fn test_process_turn_with_state_attack_basic() {
@@ -1017,11 +2407,10 @@ mod process_turn_with_state_tests {
         let mut rng = create_seeded_rng();
         
         let events = process_turn_with_state(
-            "Attacker", "Defender",
             &actor, &target,
             &Action::Attack,
-            1, &mut battle_state, &mut rng
-        );
+            1, &mut battle_state, &BattleConfig::default(), &mut rng
+        ).unwrap();
         
         assert!(!events.is_empty());
         
@@ -1043,15 +2432,14 @@ mod process_turn_with_state_tests {
         let mut battle_state = BattleState::new(&actor, &target, 10);
         let mut rng = create_seeded_rng();
         
-        battle_state.apply_damage("Healer", 30);
-        assert_eq!(battle_state.get_hp("Healer"), 50);
+        battle_state.apply_damage("Healer", 30).unwrap();
+        assert_eq!(battle_state.get_hp("Healer").unwrap(), 50);
         
         let events = process_turn_with_state(
-            "Healer", "Target",
             &actor, &target,
             &Action::Heal,
-            1, &mut battle_state, &mut rng
-        );
+            1, &mut battle_state, &BattleConfig::default(), &mut rng
+        ).unwrap();
         
         let heal_events: Vec<_> = events.iter()
             .filter(|e| matches!(e, BattleEvent::Heal { .. }))
@@ -1067,11 +2455,10 @@ mod process_turn_with_state_tests {
         let mut rng = create_seeded_rng();
         
         let events = process_turn_with_state(
-            "Fighter", "Target",
             &actor, &target,
             &Action::Attack,
-            7, &mut battle_state, &mut rng
-        );
+            7, &mut battle_state, &BattleConfig::default(), &mut rng
+        ).unwrap();
         
         for event in &events {
             match event {
@@ -1082,6 +2469,209 @@ mod process_turn_with_state_tests {
             }
         }
     }
+
+    fn spell(name: &str, effect: serde_json::Value) -> Spell {
+        Spell { name: name.to_string(), effect, cooldown: 0, inline: false }
+    }
+
+    #[test]
+    fn test_spells_leave_effects_that_tick_on_the_bearers_turn() {
+        let caster = Neopet {
+            spells: vec![
+                spell("Ember", serde_json::json!({"type": "burn", "damage": 5, "turns": 2})),
+                spell("Bloom", serde_json::json!({"type": "regen", "amount": 4, "turns": 1})),
+            ],
+            ..create_test_neopet("Caster", 100, 10, 5)
+        };
+        let target = create_test_neopet("Target", 100, 5, 3);
+        let config = BattleConfig::default();
+        let mut battle_state = BattleState::new(&caster, &target, 10);
+        battle_state.apply_damage("Caster", 10).unwrap();
+        let mut rng = create_seeded_rng();
+
+        let cast = |spell: usize, turn: u32, state: &mut BattleState, rng: &mut _| {
+            process_turn_with_state(&caster, &target, &Action::CastSpell(spell), turn, state, &config, rng)
+                .unwrap()
+        };
+        cast(0, 1, &mut battle_state, &mut rng);
+        cast(1, 2, &mut battle_state, &mut rng);
+        assert_eq!(battle_state.fighter("Target").unwrap().effects.len(), 1);
+        assert_eq!(battle_state.fighter("Caster").unwrap().effects.len(), 1);
+
+        // The burn ticks when the target's turn starts, before it acts
+        let events = process_turn_with_state(
+            &target, &caster, &Action::Heal, 3, &mut battle_state, &config, &mut rng,
+        ).unwrap();
+        let source = EffectSource { kind: TickKind::Burn, spell: "Ember".to_string(), caster: "Caster".into() };
+        assert_eq!(
+            events[0],
+            BattleEvent::HealthUpdate { fighter_name: "Target".into(), from: 100, to: 95, turn: 3, source: Some(source) }
+        );
+
+        // The regen heals the caster once, then runs out
+        let events = cast(0, 4, &mut battle_state, &mut rng);
+        assert!(matches!(
+            &events[0],
+            BattleEvent::HealthUpdate { from: 90, to: 94, source: Some(EffectSource { kind: TickKind::Regen, .. }), .. }
+        ));
+        assert!(battle_state.fighter("Caster").unwrap().effects.is_empty());
+    }
+
+    #[test]
+    fn test_damage_heal_and_drain_spells_land_at_once() {
+        let caster = Neopet {
+            spells: vec![
+                spell("Zap", serde_json::json!({"type": "damage", "amount": 12})),
+                spell("Mend", serde_json::json!({"type": "heal", "amount": 30})),
+                spell("Leech", serde_json::json!({"type": "drain", "amount": 8})),
+            ],
+            ..create_test_neopet("Caster", 100, 10, 5)
+        };
+        let target = create_test_neopet("Target", 100, 5, 3);
+        let config = BattleConfig::default();
+        let mut battle_state = BattleState::new(&caster, &target, 10);
+        battle_state.apply_damage("Caster", 20).unwrap();
+        let mut rng = create_seeded_rng();
+        let mut cast = |spell: usize, turn: u32, state: &mut BattleState| {
+            process_turn_with_state(&caster, &target, &Action::CastSpell(spell), turn, state, &config, &mut rng)
+                .unwrap()
+        };
+        let update = |fighter: &str, from, to, turn| {
+            BattleEvent::HealthUpdate { fighter_name: fighter.into(), from, to, turn, source: None }
+        };
+
+        assert_eq!(cast(0, 1, &mut battle_state)[1..], [update("Target", 100, 88, 1)]);
+        // Capped at the caster's max HP like any heal
+        assert_eq!(cast(1, 2, &mut battle_state)[1..], [update("Caster", 80, 100, 2)]);
+
+        // A drain only gives back what the target had left
+        battle_state.apply_damage("Caster", 10).unwrap();
+        battle_state.apply_damage("Target", 83).unwrap();
+        assert_eq!(cast(2, 3, &mut battle_state)[1..], [update("Target", 5, 0, 3), update("Caster", 90, 95, 3)]);
+        assert!(matches!(battle_state.check_battle_completion(), Some(BattleCompletionReason::HpDepleted(name)) if name == "Target"));
+    }
+
+    #[test]
+    fn test_effect_tick_can_knock_out_the_actor() {
+        let actor = create_test_neopet("Actor", 3, 10, 5);
+        let target = create_test_neopet("Target", 100, 5, 3);
+        let mut battle_state = BattleState::new(&actor, &target, 10);
+        let poison = ActiveEffect {
+            source: EffectSource { kind: TickKind::Poison, spell: "Venom".to_string(), caster: "Target".into() },
+            amount: 5,
+            turns_left: 3,
+        };
+        battle_state.add_effect("Actor", poison).unwrap();
+
+        let events = process_turn_with_state(
+            &actor, &target, &Action::Attack, 1, &mut battle_state, &BattleConfig::default(),
+            &mut create_seeded_rng(),
+        ).unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], BattleEvent::HealthUpdate { to: 0, .. }));
+        assert!(matches!(battle_state.check_battle_completion(), Some(BattleCompletionReason::HpDepleted(name)) if name == "Actor"));
+    }
+
+    #[test]
+    fn test_spells_on_cooldown_roll_again() {
+        let nova = Spell { cooldown: 2, ..spell("Nova", serde_json::json!({"type": "damage", "amount": 5})) };
+        let caster = Neopet {
+            spells: vec![nova.clone()],
+            behavior: Behavior { spell_chances: vec![0.5], heal_chance: 0.0, ..Behavior::attack_or_heal(0.5) },
+            ..create_test_neopet("Caster", 100, 10, 5)
+        };
+        let target = create_test_neopet("Target", 100, 5, 3);
+        let config = BattleConfig::default();
+        let mut battle_state = BattleState::new(&caster, &target, 10);
+        let mut rng = create_seeded_rng();
+        let mut cast = |turn: u32, caster: &Neopet, state: &mut BattleState| {
+            process_turn_with_state(caster, &target, &Action::CastSpell(0), turn, state, &config, &mut rng)
+                .unwrap()
+        };
+
+        assert!(matches!(cast(1, &caster, &mut battle_state)[0], BattleEvent::SpellCast { .. }));
+        assert_eq!(battle_state.cooldown_left("Caster", &nova, 2).unwrap(), 2);
+
+        // Blocked, the caster rolls again and can only land on an attack
+        let events = cast(3, &caster, &mut battle_state);
+        assert_eq!(
+            events[0],
+            BattleEvent::SpellOnCooldown { turn: 3, actor: "Caster".into(), spell_name: "Nova".to_string(), turns_left: 1 }
+        );
+        assert!(events.iter().any(|e| matches!(e, BattleEvent::Attack { .. })));
+        assert!(!events.iter().any(|e| matches!(e, BattleEvent::SpellCast { .. })));
+
+        assert!(matches!(cast(4, &caster, &mut battle_state)[0], BattleEvent::SpellCast { turn: 4, .. }));
+        assert_eq!(battle_state.cooldown_left("Caster", &nova, 5).unwrap(), 2);
+
+        // Casters with nothing else to do attack
+        let spell_only = Neopet {
+            behavior: Behavior { spell_chances: vec![1.0], heal_chance: 0.0, ..Behavior::attack_or_heal(0.0) },
+            ..caster.clone()
+        };
+        let events = cast(5, &spell_only, &mut battle_state);
+        assert!(matches!(events[0], BattleEvent::SpellOnCooldown { .. }));
+        assert!(events.iter().any(|e| matches!(e, BattleEvent::Attack { .. })));
+    }
+
+    #[test]
+    fn test_class_passives_hook_into_turns() {
+        let attack = |actor: &Neopet, target: &Neopet, state: &mut BattleState, config: &BattleConfig| {
+            let events = process_turn_with_state(
+                actor, target, &Action::Attack, 1, state, config, &mut create_seeded_rng(),
+            ).unwrap();
+            events.iter().find_map(|e| match e {
+                BattleEvent::Attack { raw_damage, shield_value, actual_damage, .. } => Some((raw_damage - shield_value, *actual_damage)),
+                _ => None,
+            }).unwrap()
+        };
+
+        // Tanks shrug off half of every critical hit
+        let attacker = create_test_neopet("Attacker", 100, 30, 0);
+        let tank = Neopet { class: Some(FighterClass::Tank), ..create_test_neopet("Tank", 1000, 5, 0) };
+        let crits = BattleConfig { crit_range: 20, fumble_range: 0, crit_multiplier: 3, ..BattleConfig::default() };
+        let (damage, actual) = attack(&attacker, &tank, &mut BattleState::new(&attacker, &tank, 10), &crits);
+        assert_eq!(actual, damage * 3 - damage * 3 / 2);
+
+        // Berserkers hit harder once they're down to half their HP
+        let berserker = Neopet { class: Some(FighterClass::Berserker), ..create_test_neopet("Berserker", 100, 30, 0) };
+        let target = create_test_neopet("Target", 1000, 5, 0);
+        let plain = BattleConfig { crit_range: 0, fumble_range: 0, ..BattleConfig::default() };
+        let mut battle_state = BattleState::new(&berserker, &target, 10);
+        let (damage, actual) = attack(&berserker, &target, &mut battle_state, &plain);
+        assert_eq!(actual, damage);
+        battle_state.apply_damage("Berserker", 50).unwrap();
+        let (damage, actual) = attack(&berserker, &target, &mut battle_state, &plain);
+        assert_eq!(actual, damage + damage / 2);
+
+        // A mage's burn lasts one turn longer
+        let mage = Neopet {
+            class: Some(FighterClass::Mage),
+            spells: vec![spell("Ember", serde_json::json!({"type": "burn", "damage": 5, "turns": 2}))],
+            ..create_test_neopet("Mage", 100, 10, 5)
+        };
+        let mut battle_state = BattleState::new(&mage, &target, 10);
+        process_turn_with_state(
+            &mage, &target, &Action::CastSpell(0), 1, &mut battle_state, &plain, &mut create_seeded_rng(),
+        ).unwrap();
+        assert_eq!(battle_state.fighter("Target").unwrap().effects[0].turns_left, 3);
+
+        // A cleric's heal also gets rid of the oldest burn or poison, regens stay
+        let cleric = Neopet { class: Some(FighterClass::Cleric), ..create_test_neopet("Cleric", 100, 10, 5) };
+        let mut battle_state = BattleState::new(&cleric, &target, 10);
+        battle_state.apply_damage("Cleric", 50).unwrap();
+        for (kind, name) in [(TickKind::Regen, "Bloom"), (TickKind::Burn, "Ember"), (TickKind::Poison, "Venom")] {
+            let source = EffectSource { kind, spell: name.to_string(), caster: "Target".into() };
+            battle_state.add_effect("Cleric", ActiveEffect { source, amount: 1, turns_left: 5 }).unwrap();
+        }
+        process_turn_with_state(
+            &cleric, &target, &Action::Heal, 1, &mut battle_state, &plain, &mut create_seeded_rng(),
+        ).unwrap();
+        let left: Vec<&str> = battle_state.fighter("Cleric").unwrap().effects.iter().map(|e| e.source.spell.as_str()).collect();
+        assert_eq!(left, vec!["Bloom", "Venom"]);
+        assert_eq!(battle_state.cleanse("Cleric").unwrap().unwrap().source.spell, "Venom");
+        assert_eq!(battle_state.cleanse("Cleric").unwrap(), None);
+    }
 }
 
 #[cfg(test)]
@@ -1089,6 +2679,7 @@ mod tests {
     use super::*;
     use crate::neopets::Behavior;
     use crate::neopets::Spell;
+    use macro_workshop::event_match;
     use rand::SeedableRng;
     use rand::rngs::StdRng;
 
@@ -1098,23 +2689,27 @@ mod tests {
 
     fn get_testing_neopets_with_name(name: &str) -> Neopet {
         Neopet {
-            name: name.to_string(),
             health: 100,
             heal_delta: 10,
             base_attack: 5,
-            base_defense: 3,
             spells: vec![
                 Spell {
                     name: "Spell1".to_string(),
                     effect: serde_json::Value::Object(serde_json::Map::new()),
+                    cooldown: 0,
+                    inline: false,
                 },
                 Spell {
                     name: "Spell2".to_string(),
                     effect: serde_json::Value::Object(serde_json::Map::new()),
+                    cooldown: 0,
+                    inline: false,
                 },
                 Spell {
                     name: "Spell3".to_string(),
                     effect: serde_json::Value::Object(serde_json::Map::new()),
+                    cooldown: 0,
+                    inline: false,
                 },
             ],
             behavior: Behavior {
@@ -1126,7 +2721,10 @@ mod tests {
                     0.10, // 0.90 to 1.0 -> spell 3
                 ],
                 heal_chance: 0.20, // 0.40 to 0.60 -> heal
+                defend_chance: 0.0,
+                taunt_chance: 0.0,
             },
+            ..Neopet::test_fighter(name)
         }
     }
 
@@ -1135,11 +2733,11 @@ mod tests {
         let fighter2 = get_testing_neopets_with_name("Fighter2");
         let mut rng = StdRng::seed_from_u64(seed);
         
-        let (events, _first, _second) = roll_for_initiative(&fighter1, &fighter2, &mut rng);
+        let (events, _first, _second) = roll_for_initiative(&fighter1, &fighter2, &BattleConfig::default(), &mut rng);
         
         let fighter1_rolls: Vec<_> = events.iter().filter(|e| {
             if let BattleEvent::Roll { actor, .. } = e {
-                actor == "TestPet"
+                &**actor == "TestPet"
             } else { false }
         }).collect();
         
@@ -1159,11 +2757,28 @@ mod tests {
         assert!(tie_seed.is_some(), "Should find at least one seed that produces a tie");
     }
 
+    #[test]
+    fn test_initiative_tie_policies_skip_the_reroll() {
+        let fighter1 = get_testing_neopet();
+        let fighter2 = Neopet { health: 150, ..get_testing_neopets_with_name("Fighter2") };
+        let seed = (0..=100).find(|&seed| seed_produces_initiative_tie(seed)).unwrap();
+
+        let config = BattleConfig { initiative_tie: InitiativeTie::FirstFighter, ..BattleConfig::default() };
+        let (events, first, _) = roll_for_initiative(&fighter1, &fighter2, &config, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(events.len(), 2);
+        assert_eq!(first.name, "TestPet");
+
+        let config = BattleConfig { initiative_tie: InitiativeTie::HigherHealth, ..BattleConfig::default() };
+        let (events, first, _) = roll_for_initiative(&fighter1, &fighter2, &config, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(events.len(), 2);
+        assert_eq!(first.name, "Fighter2");
+    }
+
     #[test]
     fn test_roll_d20_always_within_range() {
         let mut rng = rand::rng();
         for _unused in 0..100 {
-            let result = roll_d20(&mut rng);
+            let result = Dice::D20.roll(&mut rng);
             assert!(result >= 1 && result <= 20);
         }
     }
@@ -1225,7 +2840,7 @@ mod tests {
         ];
 
         for i in 0..5 {
-            let (_, first, second) = roll_for_initiative(&fighter1, &fighter2, &mut rng);
+            let (_, first, second) = roll_for_initiative(&fighter1, &fighter2, &BattleConfig::default(), &mut rng);
             assert_eq!((first, second), expected[i])
         }
     }
@@ -1236,29 +2851,23 @@ mod tests {
         let fighter2 = get_testing_neopet();
         let mut rng = StdRng::seed_from_u64(42);
         
-        let (events, first, _unused_second) = roll_for_initiative(&fighter1, &fighter2, &mut rng);
+        let (events, first, _unused_second) = roll_for_initiative(&fighter1, &fighter2, &BattleConfig::default(), &mut rng);
         
         assert!(!events.is_empty(), "Should generate initiative events");
         
         for event in &events {
-            match event {
-                BattleEvent::Roll { turn, goal, .. } => {
-                    assert_eq!(*turn, 0, "Initiative events should have turn 0");
-                    assert_eq!(goal, "initiative", "Goal should be 'initiative'");
-                }
-                _ => panic!("All initiative events should be Roll type"),
-            }
+            event_match!(event, Roll { turn: 0, goal: "initiative" });
         }
         
         assert_eq!(events.len() % 2, 0, "Should have pairs of rolls, one per fighter");
         
         if let Some(BattleEvent::Roll { actor, dice, .. }) = events.last() {
-            let last_roller = if actor == &fighter1.name { &fighter1 } else { &fighter2 };
-            let other = if actor == &fighter1.name { &fighter2 } else { &fighter1 };
+            let last_roller = if **actor == fighter1.name { &fighter1 } else { &fighter2 };
+            let other = if **actor == fighter1.name { &fighter2 } else { &fighter1 };
             
             if dice > &0 { // Dice will always be > 0, this just ensures we got a value
                 if last_roller.name == first.name {
-                    assert_eq!(*actor, first.name, "Last roller with higher roll should be first");
+                    assert_eq!(**actor, first.name, "Last roller with higher roll should be first");
                 } else {
                     assert_eq!(other.name, first.name, "Other fighter should be first if they rolled higher");
                 }
@@ -1273,17 +2882,17 @@ mod tests {
         
         let mut rng = StdRng::seed_from_u64(25);
         
-        let (events, first, _second) = roll_for_initiative(&fighter1, &fighter2, &mut rng);
+        let (events, first, _second) = roll_for_initiative(&fighter1, &fighter2, &BattleConfig::default(), &mut rng);
         
         let fighter1_rolls: Vec<_> = events.iter().filter(|e| {
             if let BattleEvent::Roll { actor, .. } = e {
-                actor == "TestPet"
+                &**actor == "TestPet"
             } else { false }
         }).collect();
         
         let fighter2_rolls: Vec<_> = events.iter().filter(|e| {
             if let BattleEvent::Roll { actor, .. } = e {
-                actor == "Fighter2"
+                &**actor == "Fighter2"
             } else { false }
         }).collect();
         
@@ -1303,7 +2912,7 @@ mod tests {
         }
         
         if let Some(BattleEvent::Roll { actor, dice, .. }) = fighter1_rolls.last() {
-            assert_eq!(*actor, fighter1.name);
+            assert_eq!(**actor, fighter1.name);
             
             if let Some(BattleEvent::Roll { dice: dice2, .. }) = fighter2_rolls.last() {
                 if dice > dice2 {
@@ -1314,11 +2923,28 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_speed_adds_to_initiative() {
+        let slow = get_testing_neopets_with_name("Slow");
+        let fast = Neopet { speed: 50, ..get_testing_neopets_with_name("Fast") };
+        for seed in 0..10 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let (events, first, _) = roll_for_initiative(&slow, &fast, &BattleConfig::default(), &mut rng);
+            assert_eq!(first.name, "Fast", "seed {}", seed);
+            for event in &events {
+                let BattleEvent::Roll { actor, dice, final_value, .. } = event else { panic!() };
+                let speed = if &**actor == "Fast" { 50 } else { 0 };
+                assert_eq!(*final_value, dice + speed);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod process_turn_tests {
     use super::*;
+    use macro_workshop::event_match;
     use std::cell::Cell;
     
     
@@ -1373,17 +2999,13 @@ mod process_turn_tests {
     /// Helper to create a test Neopet with full control
     fn test_neopet(name: &str, attack: u32, defense: u32, heal_delta: u32, spells: Vec<crate::neopets::Spell>) -> crate::neopets::Neopet {
         crate::neopets::Neopet {
-            name: name.to_string(),
             health: 100,
             heal_delta,
             base_attack: attack,
             base_defense: defense,
             spells,
-            behavior: crate::neopets::Behavior {
-                attack_chance: 0.5,
-                spell_chances: vec![],
-                heal_chance: 0.5,
-            },
+            behavior: crate::neopets::Behavior::attack_or_heal(0.5),
+            ..crate::neopets::Neopet::test_fighter(name)
         }
     }
 
@@ -1393,10 +3015,14 @@ mod process_turn_tests {
             crate::neopets::Spell {
                 name: "Fireball".to_string(),
                 effect: serde_json::Value::Object(serde_json::Map::new()),
+                cooldown: 0,
+                inline: false,
             },
             crate::neopets::Spell {
                 name: "Ice Storm".to_string(),
                 effect: serde_json::Value::Object(serde_json::Map::new()),
+                cooldown: 0,
+                inline: false,
             },
         ])
     }
@@ -1417,45 +3043,36 @@ mod process_turn_tests {
         assert_eq!(events.len(), 3);
 
         // Verify attack roll event
-        match &events[0] {
-            BattleEvent::Roll { turn, actor, dice, final_value, is_positive_crit, is_negative_crit, goal } => {
-                assert_eq!(*turn, 1);
-                assert_eq!(actor, "Alice");
-                assert_eq!(*dice, 14);
-                assert_eq!(*final_value, 24); // 14 + 10 base_attack
-                assert!(!is_positive_crit);
-                assert!(!is_negative_crit);
-                assert_eq!(goal, "attack");
-            }
-            _ => panic!("Expected Roll event for attack"),
-        }
+        event_match!(events[0], Roll {
+            turn: 1,
+            actor: "Alice".into(),
+            dice: 14,
+            final_value: 24, // 14 + 10 base_attack
+            is_positive_crit: false,
+            is_negative_crit: false,
+            goal: "attack",
+        });
 
         // Verify defense roll event
-        match &events[1] {
-            BattleEvent::Roll { turn, actor, dice, final_value, is_positive_crit, is_negative_crit, goal } => {
-                assert_eq!(*turn, 1);
-                assert_eq!(actor, "Bob");
-                assert_eq!(*dice, 8);
-                assert_eq!(*final_value, 13); // 8 + 5 base_defense
-                assert!(!is_positive_crit);
-                assert!(!is_negative_crit);
-                assert_eq!(goal, "defense");
-            }
-            _ => panic!("Expected Roll event for defense"),
-        }
+        event_match!(events[1], Roll {
+            turn: 1,
+            actor: "Bob".into(),
+            dice: 8,
+            final_value: 13, // 8 + 5 base_defense
+            is_positive_crit: false,
+            is_negative_crit: false,
+            goal: "defense",
+        });
 
         // Verify attack event with damage calculation
-        match &events[2] {
-            BattleEvent::Attack { turn, actor, target, raw_damage, shield_value, actual_damage } => {
-                assert_eq!(*turn, 1);
-                assert_eq!(actor, "Alice");
-                assert_eq!(target, "Bob");
-                assert_eq!(*raw_damage, 24);
-                assert_eq!(*shield_value, 13);
-                assert_eq!(*actual_damage, 11); // 24 - 13 = 11
-            }
-            _ => panic!("Expected Attack event"),
-        }
+        event_match!(events[2], Attack {
+            turn: 1,
+            actor: "Alice".into(),
+            target: "Bob".into(),
+            raw_damage: 24,
+            shield_value: 13,
+            actual_damage: 11, // 24 - 13 = 11
+        });
     }
 
     #[test]
@@ -1471,36 +3088,27 @@ mod process_turn_tests {
         assert_eq!(events.len(), 3);
 
         // Verify attack roll is marked as positive crit
-        match &events[0] {
-            BattleEvent::Roll { dice, final_value, is_positive_crit, is_negative_crit, .. } => {
-                assert_eq!(*dice, 20);
-                assert_eq!(*final_value, 30); // 20 + 10
-                assert!(is_positive_crit);
-                assert!(!is_negative_crit);
-            }
-            _ => panic!("Expected Roll event"),
-        }
+        event_match!(events[0], Roll {
+            dice: 20,
+            final_value: 30, // 20 + 10
+            is_positive_crit: true,
+            is_negative_crit: false,
+        });
 
         // Verify defense roll
-        match &events[1] {
-            BattleEvent::Roll { dice, final_value, .. } => {
-                assert_eq!(*dice, 5);
-                assert_eq!(*final_value, 13); // 5 + 8
-            }
-            _ => panic!("Expected Roll event"),
-        }
+        event_match!(events[1], Roll {
+            dice: 5,
+            final_value: 13, // 5 + 8
+        });
 
         // Verify damage is doubled due to crit
-        match &events[2] {
-            BattleEvent::Attack { raw_damage, shield_value, actual_damage, .. } => {
-                assert_eq!(*raw_damage, 30);
-                assert_eq!(*shield_value, 13);
-                // Normal damage: 30 - 13 = 17
-                // Crit doubles it: 17 * 2 = 34
-                assert_eq!(*actual_damage, 34);
-            }
-            _ => panic!("Expected Attack event"),
-        }
+        event_match!(events[2], Attack {
+            raw_damage: 30,
+            shield_value: 13,
+            // Normal damage: 30 - 13 = 17
+            // Crit doubles it: 17 * 2 = 34
+            actual_damage: 34,
+        });
     }
 
     #[test]
@@ -1516,22 +3124,16 @@ mod process_turn_tests {
         assert_eq!(events.len(), 3);
 
         // Verify attack roll is marked as negative crit
-        match &events[0] {
-            BattleEvent::Roll { dice, is_positive_crit, is_negative_crit, .. } => {
-                assert_eq!(*dice, 1);
-                assert!(!is_positive_crit);
-                assert!(is_negative_crit);
-            }
-            _ => panic!("Expected Roll event"),
-        }
+        event_match!(events[0], Roll {
+            dice: 1,
+            is_positive_crit: false,
+            is_negative_crit: true,
+        });
 
         // Verify damage is 0 due to negative crit
-        match &events[2] {
-            BattleEvent::Attack { actual_damage, .. } => {
-                assert_eq!(*actual_damage, 0); // Negative crit zeros all damage
-            }
-            _ => panic!("Expected Attack event"),
-        }
+        event_match!(events[2], Attack {
+            actual_damage: 0, // Negative crit zeros all damage
+        });
     }
 
     #[test]
@@ -1547,14 +3149,11 @@ mod process_turn_tests {
         assert_eq!(events.len(), 3);
 
         // Verify damage is 0 due to saturating subtraction
-        match &events[2] {
-            BattleEvent::Attack { raw_damage, shield_value, actual_damage, .. } => {
-                assert_eq!(*raw_damage, 6);  // 5 + 1
-                assert_eq!(*shield_value, 35); // 15 + 20
-                assert_eq!(*actual_damage, 0); // saturating_sub results in 0
-            }
-            _ => panic!("Expected Attack event"),
-        }
+        event_match!(events[2], Attack {
+            raw_damage: 6, // 5 + 1
+            shield_value: 35, // 15 + 20
+            actual_damage: 0, // saturating_sub results in 0
+        });
     }
 
     #[test]
@@ -1570,29 +3169,12 @@ mod process_turn_tests {
         assert_eq!(events.len(), 3);
 
         // Both rolls should be marked as positive crits
-        match &events[0] {
-            BattleEvent::Roll { dice, is_positive_crit, .. } => {
-                assert_eq!(*dice, 20);
-                assert!(is_positive_crit);
-            }
-            _ => panic!("Expected Roll event"),
-        }
+        event_match!(events[0], Roll { dice: 20, is_positive_crit: true });
 
-        match &events[1] {
-            BattleEvent::Roll { dice, is_positive_crit, .. } => {
-                assert_eq!(*dice, 20);
-                assert!(is_positive_crit);
-            }
-            _ => panic!("Expected Roll event"),
-        }
+        event_match!(events[1], Roll { dice: 20, is_positive_crit: true });
 
         // Attack: (20 + 10) - (20 + 10) = 0, then * 2 (crit) = 0
-        match &events[2] {
-            BattleEvent::Attack { actual_damage, .. } => {
-                assert_eq!(*actual_damage, 0);
-            }
-            _ => panic!("Expected Attack event"),
-        }
+        event_match!(events[2], Attack { actual_damage: 0 });
     }
 
     // ==================== Heal Action Tests ====================
@@ -1612,27 +3194,21 @@ mod process_turn_tests {
         assert_eq!(events.len(), 2);
 
         // Verify heal roll event
-        match &events[0] {
-            BattleEvent::Roll { turn, actor, dice, is_positive_crit, is_negative_crit, goal, .. } => {
-                assert_eq!(*turn, 1);
-                assert_eq!(actor, "Alice");
-                assert_eq!(*dice, 10);
-                assert!(!is_positive_crit);
-                assert!(!is_negative_crit);
-                assert_eq!(goal, "heal");
-            }
-            _ => panic!("Expected Roll event"),
-        }
+        event_match!(events[0], Roll {
+            turn: 1,
+            actor: "Alice".into(),
+            dice: 10,
+            is_positive_crit: false,
+            is_negative_crit: false,
+            goal: "heal",
+        });
 
         // Verify heal event
-        match &events[1] {
-            BattleEvent::Heal { turn, actor, amount } => {
-                assert_eq!(*turn, 1);
-                assert_eq!(actor, "Alice");
-                assert_eq!(*amount, 15); // Normal heal_delta
-            }
-            _ => panic!("Expected Heal event"),
-        }
+        event_match!(events[1], Heal {
+            turn: 1,
+            actor: "Alice".into(),
+            amount: 15, // Normal heal_delta
+        });
     }
 
     #[test]
@@ -1649,22 +3225,16 @@ mod process_turn_tests {
         assert_eq!(events.len(), 2);
 
         // Verify heal roll is marked as positive crit
-        match &events[0] {
-            BattleEvent::Roll { dice, is_positive_crit, is_negative_crit, .. } => {
-                assert_eq!(*dice, 20);
-                assert!(is_positive_crit);
-                assert!(!is_negative_crit);
-            }
-            _ => panic!("Expected Roll event"),
-        }
+        event_match!(events[0], Roll {
+            dice: 20,
+            is_positive_crit: true,
+            is_negative_crit: false,
+        });
 
         // Verify heal is doubled
-        match &events[1] {
-            BattleEvent::Heal { amount, .. } => {
-                assert_eq!(*amount, 20); // 10 * 2 = 20
-            }
-            _ => panic!("Expected Heal event"),
-        }
+        event_match!(events[1], Heal {
+            amount: 20, // 10 * 2 = 20
+        });
     }
 
     #[test]
@@ -1681,22 +3251,16 @@ mod process_turn_tests {
         assert_eq!(events.len(), 2);
 
         // Verify heal roll is marked as negative crit
-        match &events[0] {
-            BattleEvent::Roll { dice, is_positive_crit, is_negative_crit, .. } => {
-                assert_eq!(*dice, 1);
-                assert!(!is_positive_crit);
-                assert!(is_negative_crit);
-            }
-            _ => panic!("Expected Roll event"),
-        }
+        event_match!(events[0], Roll {
+            dice: 1,
+            is_positive_crit: false,
+            is_negative_crit: true,
+        });
 
         // Verify heal is 0
-        match &events[1] {
-            BattleEvent::Heal { amount, .. } => {
-                assert_eq!(*amount, 0); // Negative crit zeros heal
-            }
-            _ => panic!("Expected Heal event"),
-        }
+        event_match!(events[1], Heal {
+            amount: 0, // Negative crit zeros heal
+        });
     }
 
     // ==================== CastSpell Action Tests ====================
@@ -1713,15 +3277,12 @@ mod process_turn_tests {
         // Should have 1 event: spell cast
         assert_eq!(events.len(), 1);
 
-        match &events[0] {
-            BattleEvent::SpellCast { turn, actor, target: tgt, spell_name } => {
-                assert_eq!(*turn, 1);
-                assert_eq!(actor, "Alice");
-                assert_eq!(tgt, "Bob");
-                assert_eq!(spell_name, "Fireball"); // First spell in test_neopet_simple
-            }
-            _ => panic!("Expected SpellCast event"),
-        }
+        event_match!(events[0], SpellCast {
+            turn: 1,
+            actor: "Alice".into(),
+            target: "Bob".into(),
+            spell_name: "Fireball", // First spell in test_neopet_simple
+        });
     }
 
     #[test]
@@ -1735,12 +3296,9 @@ mod process_turn_tests {
 
         assert_eq!(events.len(), 1);
 
-        match &events[0] {
-            BattleEvent::SpellCast { spell_name, .. } => {
-                assert_eq!(spell_name, "Ice Storm"); // Second spell
-            }
-            _ => panic!("Expected SpellCast event"),
-        }
+        event_match!(events[0], SpellCast {
+            spell_name: "Ice Storm", // Second spell
+        });
     }
 
     #[test]
@@ -1754,12 +3312,9 @@ mod process_turn_tests {
 
         assert_eq!(events.len(), 1);
 
-        match &events[0] {
-            BattleEvent::SpellCast { spell_name, .. } => {
-                assert_eq!(spell_name, "Unknown Spell"); // Fallback for out of bounds
-            }
-            _ => panic!("Expected SpellCast event"),
-        }
+        event_match!(events[0], SpellCast {
+            spell_name: "Unknown Spell", // Fallback for out of bounds
+        });
     }
 
     // ==================== Additional Edge Case Tests ====================
@@ -1777,14 +3332,11 @@ mod process_turn_tests {
         assert_eq!(events.len(), 3);
 
         // With 0 base stats and normal rolls, damage should be 0 (10 - 10 = 0)
-        match &events[2] {
-            BattleEvent::Attack { raw_damage, shield_value, actual_damage, .. } => {
-                assert_eq!(*raw_damage, 10);
-                assert_eq!(*shield_value, 10);
-                assert_eq!(*actual_damage, 0);
-            }
-            _ => panic!("Expected Attack event"),
-        }
+        event_match!(events[2], Attack {
+            raw_damage: 10,
+            shield_value: 10,
+            actual_damage: 0,
+        });
     }
 
     #[test]
@@ -1849,25 +3401,13 @@ mod process_turn_tests {
         let events = process_turn(&attacker, &defender, &Action::Attack, 1, &mut rng);
 
         // Check attack roll has correct actor
-        match &events[0] {
-            BattleEvent::Roll { actor, .. } => assert_eq!(actor, "Pikachu"),
-            _ => panic!("Expected Roll event"),
-        }
+        event_match!(events[0], Roll { actor: "Pikachu".into() });
 
         // Check defense roll has correct actor (the defender)
-        match &events[1] {
-            BattleEvent::Roll { actor, .. } => assert_eq!(actor, "Charizard"),
-            _ => panic!("Expected Roll event"),
-        }
+        event_match!(events[1], Roll { actor: "Charizard".into() });
 
         // Check attack event has correct actor and target
-        match &events[2] {
-            BattleEvent::Attack { actor, target, .. } => {
-                assert_eq!(actor, "Pikachu");
-                assert_eq!(target, "Charizard");
-            }
-            _ => panic!("Expected Attack event"),
-        }
+        event_match!(events[2], Attack { actor: "Pikachu".into(), target: "Charizard".into() });
     }
 
     #[test]
@@ -1900,43 +3440,37 @@ mod battle_integration_tests {
     // Helper function to create a test Neopet
     fn create_test_neopet(name: &str) -> Neopet {
         Neopet {
-            name: name.to_string(),
             health: 100,
             heal_delta: 10,
             base_attack: 5,
-            base_defense: 3,
             spells: vec![
                 Spell {
                     name: "Fireball".to_string(),
                     effect: serde_json::Value::Object(serde_json::Map::new()),
+                    cooldown: 0,
+                    inline: false,
                 },
                 Spell {
                     name: "Ice Storm".to_string(),
                     effect: serde_json::Value::Object(serde_json::Map::new()),
+                    cooldown: 0,
+                    inline: false,
                 },
             ],
-            behavior: Behavior {
-                attack_chance: 0.5,
-                spell_chances: vec![0.2, 0.1],
-                heal_chance: 0.2,
-            },
+            behavior: Behavior { spell_chances: vec![0.2, 0.1], heal_chance: 0.2, ..Behavior::attack_or_heal(0.5) },
+            ..Neopet::test_fighter(name)
         }
     }
 
     // Helper function to create a simple test Neopet with specific stats
     fn create_simple_neopet(name: &str, health: u32, attack: u32, defense: u32) -> Neopet {
         Neopet {
-            name: name.to_string(),
             health,
             heal_delta: 10,
             base_attack: attack,
             base_defense: defense,
-            spells: vec![],
-            behavior: Behavior {
-                attack_chance: 0.8,
-                spell_chances: vec![],
-                heal_chance: 0.2,
-            },
+            behavior: Behavior::attack_or_heal(0.8),
+            ..Neopet::test_fighter(name)
         }
     }
 
@@ -1946,7 +3480,7 @@ mod battle_integration_tests {
         let fighter2 = create_test_neopet("Fighter2");
         let mut rng = StdRng::seed_from_u64(42); // Fixed seed for reproducibility
         
-        let events = battle_loop(&fighter1, &fighter2, &mut rng);
+        let events = battle_loop(&fighter1, &fighter2, &mut rng).unwrap();
         
         // Battle should complete and generate events
         assert!(!events.is_empty());
@@ -1984,6 +3518,7 @@ mod battle_integration_tests {
                 BattleCompletionReason::MaxTurnsReached(max_turns) => {
                     assert_eq!(*max_turns, 10); // Default max turns
                 },
+                BattleCompletionReason::LastStanding(_) => panic!("Only free-for-alls end with a last pet standing"),
             }
         }
     }
@@ -1994,7 +3529,7 @@ mod battle_integration_tests {
         let fighter2 = create_test_neopet("Fighter2");
         let mut rng = StdRng::seed_from_u64(123);
         
-        let events = battle_loop(&fighter1, &fighter2, &mut rng);
+        let events = battle_loop(&fighter1, &fighter2, &mut rng).unwrap();
         
         // Should have HealthUpdate events
         let health_updates: Vec<_> = events.iter().filter(|e| {
@@ -2006,7 +3541,7 @@ mod battle_integration_tests {
         
         // Verify health update structure
         for update in &health_updates {
-            if let BattleEvent::HealthUpdate { fighter_name, from, to, turn } = update {
+            if let BattleEvent::HealthUpdate { fighter_name, from, to, turn, .. } = update {
                 assert!(!fighter_name.is_empty());
                 assert!(from != to); // Health should actually change
                 assert!(*turn > 0);
@@ -2022,7 +3557,7 @@ mod battle_integration_tests {
         let fighter2 = create_test_neopet("Fighter2");
         let mut rng = StdRng::seed_from_u64(456);
         
-        let events = battle_loop(&fighter1, &fighter2, &mut rng);
+        let events = battle_loop(&fighter1, &fighter2, &mut rng).unwrap();
         
         // Should have Attack events
         let attack_events: Vec<_> = events.iter().filter(|e| {
@@ -2034,7 +3569,7 @@ mod battle_integration_tests {
         
         // Verify attack event structure
         for attack in &attack_events {
-            if let BattleEvent::Attack { turn, actor, target, raw_damage, shield_value, actual_damage } = attack {
+            if let BattleEvent::Attack { turn, actor, target, raw_damage, shield_value, actual_damage, .. } = attack {
                 assert!(*turn > 0);
                 assert!(!actor.is_empty());
                 assert!(!target.is_empty());
@@ -2052,7 +3587,7 @@ mod battle_integration_tests {
         let fighter2 = create_test_neopet("Fighter2");
         let mut rng = StdRng::seed_from_u64(789);
         
-        let events = battle_loop(&fighter1, &fighter2, &mut rng);
+        let events = battle_loop(&fighter1, &fighter2, &mut rng).unwrap();
         
         // Should have Heal events
         let heal_events: Vec<_> = events.iter().filter(|e| {
@@ -2079,7 +3614,7 @@ mod battle_integration_tests {
         let fighter2 = create_test_neopet("Fighter2");
         let mut rng = StdRng::seed_from_u64(101112);
         
-        let events = battle_loop(&fighter1, &fighter2, &mut rng);
+        let events = battle_loop(&fighter1, &fighter2, &mut rng).unwrap();
         
         // Should have SpellCast events
         let spell_events: Vec<_> = events.iter().filter(|e| {
@@ -2109,7 +3644,7 @@ mod battle_integration_tests {
         let fighter2 = create_test_neopet("Fighter2");
         let mut rng = StdRng::seed_from_u64(131415);
         
-        let events = battle_loop(&fighter1, &fighter2, &mut rng);
+        let events = battle_loop(&fighter1, &fighter2, &mut rng).unwrap();
         
         // Should have Roll events
         let roll_events: Vec<_> = events.iter().filter(|e| {
@@ -2121,8 +3656,9 @@ mod battle_integration_tests {
         
         // Verify roll event structure
         for roll in &roll_events {
-            if let BattleEvent::Roll { turn, actor, dice, final_value, is_positive_crit, is_negative_crit, goal } = roll {
+            if let BattleEvent::Roll { turn, actor, dice, dice_expr, final_value, is_positive_crit, is_negative_crit, goal } = roll {
                 assert!(*turn >= 0);
+                assert_eq!(*dice_expr, Some(Dice::D20));
                 assert!(!actor.is_empty());
                 assert!(*dice >= 1 && *dice <= 20);
                 assert!(*final_value > 0);
@@ -2153,7 +3689,7 @@ mod battle_integration_tests {
         let fighter2 = create_simple_neopet("Quick2", 20, 10, 0);
         let mut rng = StdRng::seed_from_u64(161718);
         
-        let events = battle_loop(&fighter1, &fighter2, &mut rng);
+        let events = battle_loop(&fighter1, &fighter2, &mut rng).unwrap();
         
         // Should still complete
         let complete_events: Vec<_> = events.iter().filter(|e| {
@@ -2172,7 +3708,7 @@ mod battle_integration_tests {
         let fighter2 = create_simple_neopet("Weak", 30, 2, 1);       // Low HP, low stats
         let mut rng = StdRng::seed_from_u64(192021);
         
-        let events = battle_loop(&fighter1, &fighter2, &mut rng);
+        let events = battle_loop(&fighter1, &fighter2, &mut rng).unwrap();
         
         // Should complete
         let complete_events: Vec<_> = events.iter().filter(|e| {
@@ -2182,8 +3718,8 @@ mod battle_integration_tests {
         
         if let BattleEvent::BattleComplete { winner, loser, .. } = &complete_events[0] {
             // Strong fighter should usually win in a one-sided battle
-            assert_eq!(winner, "Strong");
-            assert_eq!(loser, "Weak");
+            assert_eq!(&**winner, "Strong");
+            assert_eq!(&**loser, "Weak");
         }
     }
 
@@ -2194,7 +3730,7 @@ mod battle_integration_tests {
         let fighter2 = create_simple_neopet("Tank2", 80, 5, 15);   // High defense
         let mut rng = StdRng::seed_from_u64(222324);
         
-        let events = battle_loop(&fighter1, &fighter2, &mut rng);
+        let events = battle_loop(&fighter1, &fighter2, &mut rng).unwrap();
         
         // Should complete (likely by max turns due to low damage)
         let complete_events: Vec<_> = events.iter().filter(|e| {
@@ -2220,8 +3756,8 @@ mod battle_integration_tests {
         let mut rng1 = StdRng::seed_from_u64(252627);
         let mut rng2 = StdRng::seed_from_u64(252627);
         
-        let events1 = battle_loop(&fighter1, &fighter2, &mut rng1);
-        let events2 = battle_loop(&fighter1, &fighter2, &mut rng2);
+        let events1 = battle_loop(&fighter1, &fighter2, &mut rng1).unwrap();
+        let events2 = battle_loop(&fighter1, &fighter2, &mut rng2).unwrap();
         
         // Should have same number of events
         assert_eq!(events1.len(), events2.len());
@@ -2241,8 +3777,8 @@ mod battle_integration_tests {
         let mut rng1 = StdRng::seed_from_u64(282930);
         let mut rng2 = StdRng::seed_from_u64(313233);
         
-        let events1 = battle_loop(&fighter1, &fighter2, &mut rng1);
-        let events2 = battle_loop(&fighter1, &fighter2, &mut rng2);
+        let events1 = battle_loop(&fighter1, &fighter2, &mut rng1).unwrap();
+        let events2 = battle_loop(&fighter1, &fighter2, &mut rng2).unwrap();
         
         // Very likely to have different results with different seeds
         // (Though theoretically possible to be the same, extremely unlikely)
@@ -2258,4 +3794,712 @@ mod battle_integration_tests {
         assert!(different_length || different_events || !same_winner, 
                 "Different seeds should produce different results");
     }
+
+    #[test]
+    fn test_fatigue_level_escalates_from_start_turn() {
+        let config = BattleConfig {
+            max_turns: 20,
+            fatigue_start_turn: Some(5),
+            fatigue_damage_per_level: 3,
+            predictions: false,
+            ..BattleConfig::default()
+        };
+        assert_eq!(config.fatigue_level(4), 0);
+        assert_eq!(config.fatigue_level(5), 1);
+        assert_eq!(config.fatigue_level(8), 4);
+
+        let no_fatigue = BattleConfig { fatigue_start_turn: None, ..config };
+        assert_eq!(no_fatigue.fatigue_level(100), 0);
+        assert_eq!(BattleConfig::default().fatigue_level(100), 0, "fatigue is opt-in");
+    }
+
+    #[test]
+    fn test_battle_rules_from_json() {
+        let config: BattleConfig = serde_json::from_str(
+            r#"{ "crit_range": 2, "heal_cap": { "overheal": 150 }, "initiative_tie": "higher_health" }"#,
+        ).unwrap();
+        assert_eq!(config.crit_range, 2);
+        assert_eq!(config.heal_cap, HealCap::Overheal(150));
+        assert_eq!(config.initiative_tie, InitiativeTie::HigherHealth);
+        assert_eq!(config.crit_multiplier, 2);
+
+        let uncapped: BattleConfig = serde_json::from_str(r#"{ "heal_cap": "uncapped" }"#).unwrap();
+        assert_eq!(uncapped.heal_cap, HealCap::Uncapped);
+    }
+
+    #[test]
+    fn test_battle_without_fatigue_emits_no_fatigue_events() {
+        let fighter1 = create_simple_neopet("Fighter1", 500, 1, 30);
+        let fighter2 = create_simple_neopet("Fighter2", 500, 1, 30);
+        let config = BattleConfig {
+            fatigue_start_turn: None,
+            ..BattleConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let events = battle_loop_with_config(&fighter1, &fighter2, &config, &mut rng).unwrap();
+        assert!(!events.iter().any(|e| matches!(e, BattleEvent::FatigueApplied { .. })));
+    }
+
+    #[test]
+    fn test_fatigue_events_escalate_each_turn() {
+        let fighter1 = create_simple_neopet("Fighter1", 500, 1, 30);
+        let fighter2 = create_simple_neopet("Fighter2", 500, 1, 30);
+        let config = BattleConfig {
+            max_turns: 10,
+            fatigue_start_turn: Some(3),
+            fatigue_damage_per_level: 5,
+            predictions: false,
+            ..BattleConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let events = battle_loop_with_config(&fighter1, &fighter2, &config, &mut rng).unwrap();
+        let fatigue: Vec<(u32, u32, u32)> = events
+            .iter()
+            .filter_map(|e| match e {
+                BattleEvent::FatigueApplied { turn, level, bonus_damage } => Some((*turn, *level, *bonus_damage)),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(fatigue.first(), Some(&(3, 1, 5)));
+        for (turn, level, bonus_damage) in fatigue {
+            assert_eq!(level, turn - 2);
+            assert_eq!(bonus_damage, level * 5);
+        }
+    }
+
+    #[test]
+    fn test_fatigue_breaks_through_impenetrable_defense() {
+        // Attack can never beat defense, so only fatigue can deal damage
+        let fighter1 = create_simple_neopet("Fighter1", 100, 0, 100);
+        let fighter2 = create_simple_neopet("Fighter2", 100, 0, 100);
+        let fresh = BattleConfig {
+            max_turns: 30,
+            fatigue_start_turn: None,
+            fatigue_damage_per_level: 0,
+            predictions: false,
+            ..BattleConfig::default()
+        };
+        let tired = BattleConfig {
+            fatigue_start_turn: Some(1),
+            fatigue_damage_per_level: 10,
+            ..fresh.clone()
+        };
+
+        let events = battle_loop_with_config(&fighter1, &fighter2, &fresh, &mut StdRng::seed_from_u64(3)).unwrap();
+        assert!(matches!(
+            events.last(),
+            Some(BattleEvent::BattleComplete { completion_reason: BattleCompletionReason::MaxTurnsReached(30), .. })
+        ));
+
+        let events = battle_loop_with_config(&fighter1, &fighter2, &tired, &mut StdRng::seed_from_u64(3)).unwrap();
+        assert!(matches!(
+            events.last(),
+            Some(BattleEvent::BattleComplete { completion_reason: BattleCompletionReason::HpDepleted(_), .. })
+        ));
+    }
+
+    fn certain_loot(item_name: &str) -> crate::loot::LootTables {
+        crate::loot::LootTables {
+            default: crate::loot::LootTable {
+                entries: vec![crate::loot::LootEntry {
+                    item: Item {
+                        name: item_name.to_string(),
+                        rarity: crate::loot::Rarity::Common,
+                    },
+                    chance: 1.0,
+                }],
+            },
+            by_opponent: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_drop_loot_rewards_winner_after_completion() {
+        let fighter1 = create_test_neopet("Fighter1");
+        let fighter2 = create_test_neopet("Fighter2");
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut events = battle_loop(&fighter1, &fighter2, &mut rng).unwrap();
+
+        let items = drop_loot(&mut events, &certain_loot("Trophy"), &mut rng);
+        assert_eq!(items.len(), 1);
+
+        let (winner, loser) = match &events[events.len() - 2] {
+            BattleEvent::BattleComplete { winner, loser, .. } => (winner.clone(), loser.clone()),
+            other => panic!("Expected BattleComplete before loot, got {:?}", other),
+        };
+        match events.last() {
+            Some(BattleEvent::LootDropped { winner: w, loser: l, items, .. }) => {
+                assert_eq!(*w, winner);
+                assert_eq!(*l, loser);
+                assert_eq!(items[0].name, "Trophy");
+            }
+            other => panic!("Expected LootDropped, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_drop_loot_without_drops_adds_no_event() {
+        let fighter1 = create_test_neopet("Fighter1");
+        let fighter2 = create_test_neopet("Fighter2");
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut events = battle_loop(&fighter1, &fighter2, &mut rng).unwrap();
+        let event_count = events.len();
+
+        let items = drop_loot(&mut events, &LootTables::default(), &mut rng);
+        assert!(items.is_empty());
+        assert_eq!(events.len(), event_count);
+    }
+
+    #[test]
+    fn test_drop_loot_needs_finished_battle() {
+        let mut events = Vec::new();
+        let mut rng = StdRng::seed_from_u64(42);
+        assert!(drop_loot(&mut events, &certain_loot("Trophy"), &mut rng).is_empty());
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_predictions_are_off_by_default() {
+        let fighter1 = create_test_neopet("Fighter1");
+        let fighter2 = create_test_neopet("Fighter2");
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let events = battle_loop(&fighter1, &fighter2, &mut rng).unwrap();
+        assert!(!events.iter().any(|e| matches!(e, BattleEvent::Prediction { .. })));
+    }
+
+    #[test]
+    fn test_prediction_before_every_turn() {
+        let fighter1 = create_test_neopet("Fighter1");
+        let fighter2 = create_test_neopet("Fighter2");
+        let config = BattleConfig {
+            predictions: true,
+            ..BattleConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let events = battle_loop_with_config(&fighter1, &fighter2, &config, &mut rng).unwrap();
+        let mut predicted_turns = Vec::new();
+        for (i, event) in events.iter().enumerate() {
+            if let BattleEvent::Prediction { turn, fighter1: f1, fighter1_win_probability, .. } = event {
+                assert_eq!(&**f1, "Fighter1");
+                assert!((0.0..=1.0).contains(fighter1_win_probability));
+                // The prediction comes before anything else that happens on its turn
+                assert!(events[..i].iter().all(|e| !matches!(e, BattleEvent::Attack { turn: t, .. } if t == turn)));
+                predicted_turns.push(*turn);
+            }
+        }
+        let action_turns: std::collections::BTreeSet<u32> = events
+            .iter()
+            .filter_map(|e| match e {
+                BattleEvent::Roll { turn, .. } if *turn > 0 => Some(*turn),
+                BattleEvent::SpellCast { turn, .. } => Some(*turn),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(predicted_turns, action_turns.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_estimate_favors_stronger_fighter() {
+        let strong = create_simple_neopet("Strong", 100, 15, 10);
+        let weak = create_simple_neopet("Weak", 100, 2, 2);
+        let state = BattleState::new(&strong, &weak, 10);
+
+        let p = estimate_win_probability(&strong, &weak, &state, &BattleConfig::default()).unwrap();
+        assert!(p > 0.5);
+        assert!((estimate_win_probability(&weak, &strong, &state, &BattleConfig::default()).unwrap() - (1.0 - p)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_mirror_match_is_even() {
+        let fighter1 = create_simple_neopet("Fighter1", 100, 5, 5);
+        let fighter2 = create_simple_neopet("Fighter2", 100, 5, 5);
+        let state = BattleState::new(&fighter1, &fighter2, 10);
+        assert!((estimate_win_probability(&fighter1, &fighter2, &state, &BattleConfig::default()).unwrap() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_follows_hp() {
+        let fighter1 = create_simple_neopet("Fighter1", 100, 5, 5);
+        let fighter2 = create_simple_neopet("Fighter2", 100, 5, 5);
+        let mut state = BattleState::new(&fighter1, &fighter2, 10);
+
+        state.apply_damage("Fighter1", 60).unwrap();
+        assert!(estimate_win_probability(&fighter1, &fighter2, &state, &BattleConfig::default()).unwrap() < 0.5);
+
+        state.apply_damage("Fighter1", 40).unwrap();
+        assert_eq!(estimate_win_probability(&fighter1, &fighter2, &state, &BattleConfig::default()).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_rolls_use_configured_dice() {
+        let fighter1 = create_test_neopet("Fighter1");
+        let fighter2 = create_test_neopet("Fighter2");
+        let two_d10 = Dice::TWO_D10.with_advantage();
+        let config = BattleConfig {
+            dice: ActionDice { attack: Dice::D6, ..ActionDice::default() },
+            fighter_dice: HashMap::from([("Fighter2".to_string(), ActionDice::all(two_d10))]),
+            ..BattleConfig::default()
+        };
+
+        for seed in 0..20 {
+            let events = battle_loop_with_config(&fighter1, &fighter2, &config, &mut StdRng::seed_from_u64(seed)).unwrap();
+            for event in &events {
+                if let BattleEvent::Roll { actor, dice, dice_expr, is_positive_crit, is_negative_crit, goal, .. } = event {
+                    let rolled = if &**actor == "Fighter2" {
+                        two_d10
+                    } else if goal == "attack" {
+                        Dice::D6
+                    } else {
+                        Dice::D20
+                    };
+                    assert_eq!(*dice_expr, Some(rolled));
+                    assert!((rolled.min()..=rolled.max()).contains(dice), "{} rolled {} on {}", actor, dice, rolled);
+                    assert_eq!(*is_positive_crit, *dice == rolled.max());
+                    assert_eq!(*is_negative_crit, *dice == rolled.min());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_estimate_accounts_for_dice() {
+        let fighter1 = create_simple_neopet("Fighter1", 100, 5, 5);
+        let fighter2 = create_simple_neopet("Fighter2", 100, 5, 5);
+        let state = BattleState::new(&fighter1, &fighter2, 10)
+            .with_dice(ActionDice::all(Dice::new(2, 12).unwrap()), ActionDice::all(Dice::D6));
+        assert!(estimate_win_probability(&fighter1, &fighter2, &state, &BattleConfig::default()).unwrap() > 0.5);
+
+        let state = BattleState::new(&fighter1, &fighter2, 10)
+            .with_dice(ActionDice::all(Dice::D20.with_disadvantage()), ActionDice::all(Dice::D20.with_advantage()));
+        assert!(estimate_win_probability(&fighter1, &fighter2, &state, &BattleConfig::default()).unwrap() < 0.5);
+    }
+
+    #[test]
+    fn test_d20_expected_damage_matches_every_roll_pair() {
+        let attacker = create_simple_neopet("Attacker", 100, 8, 4);
+        let defender = create_simple_neopet("Defender", 100, 8, 4);
+        let mut total = 0;
+        for attack_roll in 1..=20u32 {
+            for defense_roll in 1..=20u32 {
+                let mut damage = (attack_roll + 8).saturating_sub(defense_roll + 4);
+                if attack_roll == 20 {
+                    damage *= 2;
+                }
+                if attack_roll == 1 {
+                    damage = 0;
+                }
+                total += damage;
+            }
+        }
+        let expected = expected_attack_damage(&attacker, &defender, Dice::D20, Dice::D20, 0, &BattleConfig::default());
+        assert!((expected - total as f64 / 400.0).abs() < 1e-9);
+    }
+
+    /// Always heals, remembering how much history it was shown.
+    struct HealingStrategy {
+        seen: Vec<(usize, usize)>,
+    }
+
+    impl BattleStrategy for HealingStrategy {
+        fn choose_action(&mut self, context: &crate::strategy::BattleContext, _rng: &mut dyn rand::RngCore) -> Action {
+            self.seen.push((context.recent_events().len(), context.recent_actions().len()));
+            Action::Heal
+        }
+    }
+
+    #[test]
+    fn test_strategies_pick_actions_with_limited_history() {
+        let fighter1 = create_test_neopet("Fighter1");
+        let fighter2 = create_test_neopet("Fighter2");
+        let config = BattleConfig { history_window: 3, ..BattleConfig::default() };
+        let mut healer1 = HealingStrategy { seen: vec![] };
+        let mut healer2 = HealingStrategy { seen: vec![] };
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let events = battle_loop_with_strategies(&fighter1, &fighter2, &config, &mut healer1, &mut healer2, &mut rng).unwrap();
+        assert!(events.iter().all(|e| !matches!(e, BattleEvent::Attack { .. } | BattleEvent::SpellCast { .. })));
+        assert_eq!(healer1.seen.len() + healer2.seen.len(), config.max_turns as usize);
+        for (events_seen, actions_seen) in healer1.seen.iter().chain(&healer2.seen) {
+            assert!(*events_seen <= 3 && *actions_seen <= 3);
+        }
+        assert!(healer1.seen.iter().chain(&healer2.seen).any(|&(e, a)| e == 3 && a == 3));
+    }
+
+    #[test]
+    fn test_battle_loop_rejects_bad_setup() {
+        let fighter = create_test_neopet("Fighter1");
+        let mut rng = StdRng::seed_from_u64(42);
+        assert_eq!(
+            battle_loop(&fighter, &fighter, &mut rng),
+            Err(BattleError::DuplicateFighter("Fighter1".to_string()))
+        );
+
+        let config = BattleConfig { max_turns: 0, ..BattleConfig::default() };
+        let fighter2 = create_test_neopet("Fighter2");
+        assert_eq!(battle_loop_with_config(&fighter, &fighter2, &config, &mut rng), Err(BattleError::NoTurns));
+    }
+
+    fn swamp() -> Arena {
+        Arena {
+            name: "Swamp".to_string(),
+            description: "Muddy".to_string(),
+            damage_per_turn: 5,
+            heal_bonus_percent: 50,
+        }
+    }
+
+    #[test]
+    fn test_arena_hurts_actor_and_boosts_heals() {
+        let fighter1 = create_test_neopet("Fighter1");
+        let fighter2 = create_test_neopet("Fighter2");
+        let config = BattleConfig { arena: Some(swamp()), crit_range: 0, fumble_range: 0, ..BattleConfig::default() };
+        let mut state = BattleState::new(&fighter1, &fighter2, 10);
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let events = process_turn_with_state(
+            &fighter1, &fighter2, &Action::Heal, 1, &mut state, &config, &mut rng,
+        )
+        .unwrap();
+        let arena = |hp_change| BattleEvent::ArenaEffect {
+            turn: 1,
+            arena: "Swamp".to_string(),
+            fighter: "Fighter1".into(),
+            hp_change,
+        };
+        assert_eq!(events[0], arena(-5));
+        assert!(matches!(events[1], BattleEvent::HealthUpdate { from: 100, to: 95, source: None, .. }));
+        assert_eq!(events[3], arena(5));
+        assert!(matches!(events[4], BattleEvent::Heal { amount: 15, .. }));
+        assert!(matches!(events[5], BattleEvent::HealthUpdate { from: 95, to: 100, .. }));
+        assert_eq!(state.get_hp("Fighter2"), Ok(100));
+    }
+
+    #[test]
+    fn test_arena_can_knock_out_before_acting() {
+        let fighter1 = Neopet { health: 4, ..create_test_neopet("Fighter1") };
+        let fighter2 = create_test_neopet("Fighter2");
+        let config = BattleConfig { arena: Some(swamp()), ..BattleConfig::default() };
+        let mut state = BattleState::new(&fighter1, &fighter2, 10);
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let events = process_turn_with_state(
+            &fighter1, &fighter2, &Action::Attack, 1, &mut state, &config, &mut rng,
+        )
+        .unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], BattleEvent::ArenaEffect { hp_change: -4, .. }));
+        assert_eq!(state.get_hp("Fighter1"), Ok(0));
+        assert_eq!(state.get_hp("Fighter2"), Ok(100));
+    }
+
+    #[test]
+    fn test_defend_doubles_defense_roll_until_next_turn() {
+        let fighter1 = create_test_neopet("Fighter1");
+        let fighter2 = create_test_neopet("Fighter2");
+        let config = BattleConfig::default();
+        let mut state = BattleState::new(&fighter1, &fighter2, 10);
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut turn = |actor: &str, action: &Action, turn: u32, state: &mut BattleState| {
+            let (actor_stats, target_stats) = if actor == "Fighter1" {
+                (&fighter1, &fighter2)
+            } else {
+                (&fighter2, &fighter1)
+            };
+            process_turn_with_state(actor_stats, target_stats, action, turn, state, &config, &mut rng).unwrap()
+        };
+        let defense = |events: &[BattleEvent]| {
+            events
+                .iter()
+                .find_map(|e| match e {
+                    BattleEvent::Roll { dice, final_value, goal, .. } if goal == "defense" => Some((*dice, *final_value)),
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        let events = turn("Fighter2", &Action::Defend, 1, &mut state);
+        assert_eq!(events, vec![BattleEvent::Defend { turn: 1, actor: "Fighter2".into() }]);
+        let (dice, final_value) = defense(&turn("Fighter1", &Action::Attack, 2, &mut state));
+        assert_eq!(final_value, dice * 2 + 3);
+        let (dice, final_value) = defense(&turn("Fighter1", &Action::Attack, 3, &mut state));
+        assert_eq!(final_value, dice * 2 + 3);
+
+        // The stance drops as soon as Fighter2 acts again
+        turn("Fighter2", &Action::Heal, 4, &mut state);
+        let (dice, final_value) = defense(&turn("Fighter1", &Action::Attack, 5, &mut state));
+        assert_eq!(final_value, dice + 3);
+    }
+
+    #[test]
+    fn test_taunt_forces_a_weakened_attack() {
+        let fighter1 = create_test_neopet("Fighter1");
+        let fighter2 = create_test_neopet("Fighter2");
+        let config = BattleConfig { taunt_penalty: 4, ..BattleConfig::default() };
+        let mut state = BattleState::new(&fighter1, &fighter2, 10);
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let events = process_turn_with_state(
+            &fighter1, &fighter2, &Action::Taunt, 1, &mut state, &config, &mut rng,
+        )
+        .unwrap();
+        assert_eq!(
+            events,
+            vec![BattleEvent::Taunt {
+                turn: 1,
+                actor: "Fighter1".into(),
+                target: "Fighter2".into(),
+                penalty: 4,
+            }]
+        );
+        assert!(state.fighter("Fighter2").unwrap().taunted);
+
+        // Fighter2 wanted to heal but has to attack instead
+        let events = process_turn_with_state(
+            &fighter2, &fighter1, &Action::Heal, 2, &mut state, &config, &mut rng,
+        )
+        .unwrap();
+        assert!(!events.iter().any(|e| matches!(e, BattleEvent::Heal { .. })));
+        assert!(events.iter().any(|e| matches!(e, BattleEvent::Attack { actor, .. } if &**actor == "Fighter2")));
+        let (dice, final_value) = events
+            .iter()
+            .find_map(|e| match e {
+                BattleEvent::Roll { dice, final_value, goal, .. } if goal == "attack" => Some((*dice, *final_value)),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(final_value, (dice + 5).saturating_sub(4));
+        assert!(!state.fighter("Fighter2").unwrap().taunted);
+    }
+
+    #[test]
+    fn test_attack_reports_killing_blow_and_overkill() {
+        let fighter1 = Neopet { base_attack: 100, ..create_test_neopet("Fighter1") };
+        let fighter2 = Neopet { health: 150, ..create_test_neopet("Fighter2") };
+        let config = BattleConfig { crit_range: 0, fumble_range: 0, ..BattleConfig::default() };
+        let mut state = BattleState::new(&fighter1, &fighter2, 10);
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut attack = |turn, state: &mut BattleState| {
+            let events = process_turn_with_state(
+                &fighter1, &fighter2, &Action::Attack, turn, state, &config, &mut rng,
+            )
+            .unwrap();
+            events
+                .into_iter()
+                .find_map(|e| match e {
+                    BattleEvent::Attack { actual_damage, is_killing_blow, overkill_amount, .. } => {
+                        Some((actual_damage, is_killing_blow, overkill_amount))
+                    }
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        let (damage, is_killing_blow, overkill) = attack(1, &mut state);
+        assert!(damage < 150);
+        assert_eq!((is_killing_blow, overkill), (false, 0));
+        let hp_left = state.get_hp("Fighter2").unwrap();
+        let (damage, is_killing_blow, overkill) = attack(3, &mut state);
+        assert!(is_killing_blow);
+        assert_eq!(overkill, damage - hp_left);
+        assert_eq!(state.get_hp("Fighter2"), Ok(0));
+    }
+
+    #[test]
+    fn test_choose_action_picks_stances() {
+        let mut neopet = create_test_neopet("Fighter1");
+        neopet.behavior = Behavior {
+            spell_chances: vec![0.0],
+            heal_chance: 0.0,
+            defend_chance: 1.0,
+            ..Behavior::attack_or_heal(0.0)
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        assert_eq!(choose_action(&neopet, &mut rng), Action::Defend);
+        neopet.behavior.defend_chance = 0.0;
+        neopet.behavior.taunt_chance = 1.0;
+        assert_eq!(choose_action(&neopet, &mut rng), Action::Taunt);
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<BattleEvent>,
+        ended: bool,
+    }
+
+    impl Observer for Recorder {
+        fn on_event(&mut self, event: &BattleEvent) {
+            assert!(!self.ended);
+            self.events.push(event.clone());
+        }
+
+        fn on_battle_end(&mut self) {
+            self.ended = true;
+        }
+    }
+
+    #[test]
+    fn test_observers_see_every_event() {
+        let fighter1 = create_test_neopet("Fighter1");
+        let fighter2 = create_test_neopet("Fighter2");
+        let config = BattleConfig::default();
+        let (mut first, mut second) = (Recorder::default(), Recorder::default());
+
+        let events = battle_loop_with_observers(&fighter1, &fighter2, &config, &mut [&mut first, &mut second], &mut StdRng::seed_from_u64(42)).unwrap();
+        assert_eq!(events, battle_loop_with_config(&fighter1, &fighter2, &config, &mut StdRng::seed_from_u64(42)).unwrap());
+        for recorder in [first, second] {
+            assert!(recorder.ended);
+            assert_eq!(recorder.events, events);
+        }
+
+        let mut recorder = Recorder::default();
+        let result = battle_loop_with_observers(&fighter1, &fighter1, &config, &mut [&mut recorder], &mut StdRng::seed_from_u64(42));
+        assert_eq!(result, Err(BattleError::DuplicateFighter("Fighter1".to_string())));
+        assert!(!recorder.ended);
+    }
+
+    #[test]
+    fn test_process_turn_with_unknown_fighter_is_an_error() {
+        let fighter1 = create_test_neopet("Fighter1");
+        let fighter2 = create_test_neopet("Fighter2");
+        let mut state = BattleState::new(&fighter1, &fighter2, 10);
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let result = process_turn_with_state(
+            &create_test_neopet("Stranger"), &fighter2, &Action::Attack, 1, &mut state, &BattleConfig::default(), &mut rng,
+        );
+        assert_eq!(result, Err(BattleError::UnknownFighter("Stranger".to_string())));
+        assert_eq!(state.get_hp("Fighter2"), Ok(fighter2.health));
+    }
+}
+
+#[cfg(test)]
+mod invariant_tests {
+    use super::*;
+    use crate::classes::FighterClass;
+    use crate::neopets::Behavior;
+    use crate::strategy::StrategyKind;
+    use proptest::prelude::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn spell() -> impl Strategy<Value = Spell> {
+        let effect = prop_oneof![
+            Just(serde_json::json!({ "type": "cosmetic" })),
+            (1..10u32, 1..4u32).prop_map(|(damage, turns)| serde_json::json!({ "type": "burn", "damage": damage, "turns": turns })),
+            (1..10u32, 1..4u32).prop_map(|(damage, turns)| serde_json::json!({ "type": "poison", "damage": damage, "turns": turns })),
+            (1..10u32, 1..4u32).prop_map(|(amount, turns)| serde_json::json!({ "type": "regen", "amount": amount, "turns": turns })),
+        ];
+        (effect, 0..3u32).prop_map(|(effect, cooldown)| Spell { name: String::new(), effect, cooldown, inline: false })
+    }
+
+    /// A fighter whose behavior chances sum to 1.0, like a loaded one.
+    fn neopet(name: &'static str) -> impl Strategy<Value = Neopet> {
+        let stats = (1..200u32, 0..30u32, 0..20u32, 0..20u32, 0..10u32, 0.0..0.5f64, 0..5u32);
+        let class = prop_oneof![Just(None), proptest::sample::select(FighterClass::ALL.to_vec()).prop_map(Some)];
+        let strategy = proptest::sample::select(vec![
+            StrategyKind::Probabilistic,
+            StrategyKind::Adaptive,
+            StrategyKind::AggressiveWhenWinning,
+            StrategyKind::HealWhenLow,
+        ]);
+        let spells = proptest::collection::vec((spell(), 0.0..1.0f64), 0..3);
+        (stats, proptest::array::uniform4(0.0..1.0f64), spells, class, strategy).prop_map(
+            move |((health, heal_delta, base_attack, base_defense, speed, dodge_chance, block_value), weights, spells, class, strategy)| {
+                let total = weights.iter().sum::<f64>() + spells.iter().map(|(_, w)| w).sum::<f64>() + 0.1;
+                let chance = |weight: f64| weight / total;
+                let spell_chances: Vec<f64> = spells.iter().map(|(_, w)| chance(*w)).collect();
+                let spells = spells
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (spell, _))| Spell { name: format!("{} Spell {}", name, i), ..spell })
+                    .collect();
+                Neopet {
+                    name: name.to_string(),
+                    health,
+                    heal_delta,
+                    base_attack,
+                    base_defense,
+                    spells,
+                    behavior: Behavior {
+                        // Whatever rounding leaves over goes to the attack
+                        attack_chance: 1.0 - (weights[1] + weights[2] + weights[3]) / total
+                            - spell_chances.iter().sum::<f64>(),
+                        spell_chances,
+                        heal_chance: chance(weights[1]),
+                        defend_chance: chance(weights[2]),
+                        taunt_chance: chance(weights[3]),
+                    },
+                    strategy,
+                    dodge_chance,
+                    block_value,
+                    speed,
+                    class,
+                }
+            },
+        )
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn battles_keep_their_invariants(
+            fighter1 in neopet("Acara"),
+            fighter2 in neopet("Usul"),
+            max_turns in 1..60u32,
+            seed in any::<u64>(),
+        ) {
+            let config = BattleConfig { max_turns, ..BattleConfig::default() };
+            let events = battle_loop_with_config(&fighter1, &fighter2, &config, &mut StdRng::seed_from_u64(seed)).unwrap();
+            prop_assert_eq!(check_invariants(&events, &[&fighter1, &fighter2], &config), Ok(()));
+        }
+    }
+
+    fn attack(actual_damage: u32, is_killing_blow: bool, overkill_amount: u32) -> BattleEvent {
+        BattleEvent::Attack {
+            turn: 1,
+            actor: "Acara".into(),
+            target: "Usul".into(),
+            raw_damage: actual_damage,
+            shield_value: 0,
+            actual_damage,
+            is_killing_blow,
+            overkill_amount,
+        }
+    }
+
+    fn health(from: u32, to: u32) -> BattleEvent {
+        BattleEvent::HealthUpdate { fighter_name: "Usul".into(), from, to, turn: 1, source: None }
+    }
+
+    #[test]
+    fn test_checks_catch_broken_battles() {
+        assert!(check_damage(&[attack(5, false, 0), health(20, 15)]).is_ok());
+        assert!(check_damage(&[attack(25, true, 5), health(20, 0)]).is_ok());
+        assert!(check_damage(&[attack(0, false, 0)]).is_ok());
+        assert!(check_damage(&[attack(5, false, 0), health(20, 16)]).is_err());
+        assert!(check_damage(&[attack(25, true, 0), health(20, 0)]).is_err());
+        assert!(check_damage(&[attack(20, false, 0), health(20, 0)]).is_err());
+        assert!(check_damage(&[attack(5, false, 0)]).is_err());
+
+        assert!(check_turn_order(&[health(20, 15), BattleEvent::Defend { turn: 0, actor: "Usul".into() }]).is_err());
+        assert!(check_completion(&[health(20, 15)]).is_err());
+
+        let usul = Neopet {
+            health: 20,
+            heal_delta: 10,
+            base_attack: 5,
+            behavior: Behavior::attack_or_heal(1.0),
+            ..Neopet::test_fighter("Usul")
+        };
+        let config = BattleConfig::default();
+        assert!(check_hp(&[health(20, 15), health(15, 20)], &[&usul], &config).is_ok());
+        assert!(check_hp(&[health(20, 15), health(16, 10)], &[&usul], &config).is_err());
+        assert!(check_hp(&[health(20, 25)], &[&usul], &config).is_err());
+    }
 }
@@ -0,0 +1,452 @@
+// src/battle_log.rs
+use crate::battle::{BattleCompletionReason, BattleEvent, FighterName};
+use std::collections::HashMap;
+
+/// HP a fighter lost to an attack.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DamageDealt {
+    pub turn: u32,
+    pub attacker: FighterName,
+    pub target: FighterName,
+    pub damage: u32,  // HP actually lost, never more than the target had
+    pub hp_left: u32,
+}
+
+/// What differs between two battles on the same turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Divergence {
+    /// A fighter picked a different action, or a different spell.
+    Action,
+    /// A roll landed on a different value or crit.
+    Roll,
+    /// HP changed by different amounts.
+    Damage,
+    /// Anything else, e.g. a dodge, fatigue or the loot.
+    Other,
+}
+
+impl std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Divergence::Action => "action",
+            Divergence::Roll => "roll",
+            Divergence::Damage => "damage",
+            Divergence::Other => "other",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One turn of two battles side by side, see `BattleLog::diff`. A side is empty when
+/// its battle was already over.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnDiff<'a> {
+    pub turn: u32,
+    pub first: Vec<&'a BattleEvent>,
+    pub second: Vec<&'a BattleEvent>,
+    pub divergences: Vec<Divergence>,
+}
+
+impl TurnDiff<'_> {
+    pub fn diverged(&self) -> bool {
+        !self.divergences.is_empty()
+    }
+}
+
+/// Read-only queries over the events of one battle, so callers don't each walk the
+/// events looking for the variants they care about.
+#[derive(Debug, Clone, Copy)]
+pub struct BattleLog<'a> {
+    events: &'a [BattleEvent],
+}
+
+impl<'a> From<&'a [BattleEvent]> for BattleLog<'a> {
+    fn from(events: &'a [BattleEvent]) -> Self {
+        Self { events }
+    }
+}
+
+impl<'a> From<&'a Vec<BattleEvent>> for BattleLog<'a> {
+    fn from(events: &'a Vec<BattleEvent>) -> Self {
+        Self { events }
+    }
+}
+
+impl<'a> BattleLog<'a> {
+    pub fn new(events: &'a [BattleEvent]) -> Self {
+        Self { events }
+    }
+
+    pub fn events(&self) -> &'a [BattleEvent] {
+        self.events
+    }
+
+    /// Every event `fighter` is involved in, see `BattleEvent::involves`.
+    pub fn events_for_fighter<'n>(&self, fighter: &'n str) -> impl Iterator<Item = &'a BattleEvent> + 'n
+    where
+        'a: 'n,
+    {
+        self.events.iter().filter(move |e| e.involves(fighter))
+    }
+
+    /// Events of turn `turn`, 0 being the initiative.
+    pub fn events_in_turn(&self, turn: u32) -> impl Iterator<Item = &'a BattleEvent> + 'a {
+        self.events.iter().filter(move |e| e.turn() == turn)
+    }
+
+    /// Events grouped by turn, in turn order.
+    pub fn turns(&self) -> Vec<(u32, Vec<&'a BattleEvent>)> {
+        let mut turns: Vec<(u32, Vec<&'a BattleEvent>)> = Vec::new();
+        for event in self.events {
+            match turns.iter_mut().find(|(turn, _)| *turn == event.turn()) {
+                Some((_, events)) => events.push(event),
+                None => turns.push((event.turn(), vec![event])),
+            }
+        }
+        turns.sort_by_key(|(turn, _)| *turn);
+        turns
+    }
+
+    /// Aligns this battle with `other` turn by turn, noting what differs on each turn.
+    /// Predictions are left out, they only follow the HP.
+    pub fn diff<'b>(&self, other: &BattleLog<'b>) -> Vec<TurnDiff<'b>>
+    where
+        'a: 'b,
+    {
+        let (first, second) = (self.turns(), other.turns());
+        let last = self.total_turns().max(other.total_turns());
+        (0..=last)
+            .map(|turn| {
+                let side = |turns: &[(u32, Vec<&'b BattleEvent>)]| -> Vec<&'b BattleEvent> {
+                    turns
+                        .iter()
+                        .find(|(t, _)| *t == turn)
+                        .map(|(_, events)| events.iter().copied().filter(|e| !matches!(e, BattleEvent::Prediction { .. } | BattleEvent::WinProbability { .. })).collect())
+                        .unwrap_or_default()
+                };
+                let (first, second) = (side(&first), side(&second));
+                let divergences = divergences(&first, &second);
+                TurnDiff { turn, first, second, divergences }
+            })
+            .collect()
+    }
+
+    /// The last turn played, 0 when the battle never got past the initiative.
+    pub fn total_turns(&self) -> u32 {
+        self.events.iter().map(BattleEvent::turn).max().unwrap_or(0)
+    }
+
+    /// Every attack that took HP off its target, in the order they landed.
+    pub fn damage_timeline(&self) -> Vec<DamageDealt> {
+        self.events
+            .iter()
+            .zip(self.events.iter().skip(1))
+            .filter_map(|pair| match pair {
+                (
+                    BattleEvent::Attack { turn, actor, target, .. },
+                    BattleEvent::HealthUpdate { fighter_name, from, to, .. },
+                ) if fighter_name == target && to < from => Some(DamageDealt {
+                    turn: *turn,
+                    attacker: actor.clone(),
+                    target: target.clone(),
+                    damage: from - to,
+                    hp_left: *to,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Damage each fighter's attacks dealt after defense, before it was capped by the
+    /// HP the target had left.
+    pub fn damage_by_fighter(&self) -> HashMap<FighterName, u32> {
+        let mut damage = HashMap::new();
+        for event in self.events {
+            if let BattleEvent::Attack { actor, actual_damage, .. } = event {
+                *damage.entry(actor.clone()).or_insert(0) += actual_damage;
+            }
+        }
+        damage
+    }
+
+    /// Healing each fighter rolled, before the heal cap.
+    pub fn healing_by_fighter(&self) -> HashMap<FighterName, u32> {
+        let mut healing = HashMap::new();
+        for event in self.events {
+            if let BattleEvent::Heal { actor, amount, .. } = event {
+                *healing.entry(actor.clone()).or_insert(0) += amount;
+            }
+        }
+        healing
+    }
+
+    /// Names of the spells each fighter cast, in casting order.
+    pub fn spells_by_fighter(&self) -> HashMap<FighterName, Vec<String>> {
+        let mut spells: HashMap<FighterName, Vec<String>> = HashMap::new();
+        for event in self.events {
+            if let BattleEvent::SpellCast { actor, spell_name, .. } = event {
+                spells.entry(actor.clone()).or_default().push(spell_name.clone());
+            }
+        }
+        spells
+    }
+
+    /// Rolls that came up critical, initiative included.
+    pub fn crits(&self) -> impl Iterator<Item = &'a BattleEvent> + 'a {
+        self.events.iter().filter(|e| matches!(e, BattleEvent::Roll { is_positive_crit: true, .. }))
+    }
+
+    /// Rolls that fumbled, initiative included.
+    pub fn fumbles(&self) -> impl Iterator<Item = &'a BattleEvent> + 'a {
+        self.events.iter().filter(|e| matches!(e, BattleEvent::Roll { is_negative_crit: true, .. }))
+    }
+
+    /// The `BattleComplete` event, `None` if the battle was cut short.
+    pub fn completion(&self) -> Option<&'a BattleEvent> {
+        self.events.iter().find(|e| matches!(e, BattleEvent::BattleComplete { .. }))
+    }
+
+    pub fn winner(&self) -> Option<&'a str> {
+        match self.completion()? {
+            BattleEvent::BattleComplete { winner, .. } => Some(winner),
+            _ => None,
+        }
+    }
+
+    pub fn completion_reason(&self) -> Option<&'a BattleCompletionReason> {
+        match self.completion()? {
+            BattleEvent::BattleComplete { completion_reason, .. } => Some(completion_reason),
+            _ => None,
+        }
+    }
+
+    /// The winner's and the loser's HP when the battle ended.
+    pub fn final_hps(&self) -> Option<[(&'a str, u32); 2]> {
+        match self.completion()? {
+            BattleEvent::BattleComplete { winner, loser, winner_final_hp, loser_final_hp, .. } => {
+                Some([(winner, *winner_final_hp), (loser, *loser_final_hp)])
+            }
+            _ => None,
+        }
+    }
+
+    /// The last attack, spell or over-time effect tick of the battle, the finishing
+    /// blow of a knockout.
+    pub fn last_action(&self) -> Option<&'a BattleEvent> {
+        self.events.iter().rev().find(|e| {
+            matches!(
+                e,
+                BattleEvent::Attack { .. }
+                    | BattleEvent::SpellCast { .. }
+                    | BattleEvent::HealthUpdate { source: Some(_), .. }
+            )
+        })
+    }
+}
+
+fn divergences(first: &[&BattleEvent], second: &[&BattleEvent]) -> Vec<Divergence> {
+    if first == second {
+        return vec![];
+    }
+    let actions = |events: &[&BattleEvent]| -> Vec<(FighterName, String)> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                BattleEvent::Attack { actor, .. } => Some((actor.clone(), "attack".to_string())),
+                BattleEvent::Heal { actor, .. } => Some((actor.clone(), "heal".to_string())),
+                BattleEvent::Defend { actor, .. } => Some((actor.clone(), "defend".to_string())),
+                BattleEvent::Taunt { actor, .. } => Some((actor.clone(), "taunt".to_string())),
+                BattleEvent::SpellCast { actor, spell_name, .. } => Some((actor.clone(), spell_name.clone())),
+                _ => None,
+            })
+            .collect()
+    };
+    let rolls = |events: &[&BattleEvent]| -> Vec<(FighterName, String, u32, bool, bool)> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                BattleEvent::Roll { actor, goal, final_value, is_positive_crit, is_negative_crit, .. } => {
+                    Some((actor.clone(), goal.clone(), *final_value, *is_positive_crit, *is_negative_crit))
+                }
+                _ => None,
+            })
+            .collect()
+    };
+    let hp_changes = |events: &[&BattleEvent]| -> Vec<(FighterName, u32, u32)> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                BattleEvent::HealthUpdate { fighter_name, from, to, .. } => Some((fighter_name.clone(), *from, *to)),
+                _ => None,
+            })
+            .collect()
+    };
+
+    let mut divergences = Vec::new();
+    if actions(first) != actions(second) {
+        divergences.push(Divergence::Action);
+    }
+    if rolls(first) != rolls(second) {
+        divergences.push(Divergence::Roll);
+    }
+    if hp_changes(first) != hp_changes(second) {
+        divergences.push(Divergence::Damage);
+    }
+    if divergences.is_empty() {
+        divergences.push(Divergence::Other);
+    }
+    divergences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roll(turn: u32, actor: &str, is_positive_crit: bool, is_negative_crit: bool) -> BattleEvent {
+        BattleEvent::Roll {
+            turn,
+            actor: actor.into(),
+            dice: 10,
+            dice_expr: None,
+            final_value: 10,
+            is_positive_crit,
+            is_negative_crit,
+            goal: "attack".to_string(),
+        }
+    }
+
+    fn attack(turn: u32, actor: &str, target: &str, actual_damage: u32) -> BattleEvent {
+        BattleEvent::Attack {
+            turn,
+            actor: actor.into(),
+            target: target.into(),
+            raw_damage: actual_damage + 5,
+            shield_value: 5,
+            actual_damage,
+            is_killing_blow: false,
+            overkill_amount: 0,
+        }
+    }
+
+    fn health(turn: u32, fighter_name: &str, from: u32, to: u32) -> BattleEvent {
+        BattleEvent::HealthUpdate { fighter_name: fighter_name.into(), from, to, turn, source: None }
+    }
+
+    fn sample() -> Vec<BattleEvent> {
+        vec![
+            roll(0, "Acara", true, false),
+            roll(0, "Usul", false, false),
+            roll(1, "Acara", false, false),
+            attack(1, "Acara", "Usul", 8),
+            health(1, "Usul", 20, 12),
+            BattleEvent::Heal { turn: 2, actor: "Usul".into(), amount: 5 },
+            health(2, "Usul", 12, 17),
+            roll(3, "Acara", false, true),
+            attack(3, "Acara", "Usul", 0),
+            BattleEvent::SpellCast {
+                turn: 4,
+                actor: "Usul".into(),
+                target: "Acara".into(),
+                spell_name: "Fireball".to_string(),
+            },
+            roll(5, "Acara", true, false),
+            attack(5, "Acara", "Usul", 30),
+            health(5, "Usul", 17, 0),
+            BattleEvent::BattleComplete {
+                turn: 5,
+                winner: "Acara".into(),
+                loser: "Usul".into(),
+                winner_final_hp: 20,
+                loser_final_hp: 0,
+                completion_reason: BattleCompletionReason::HpDepleted("Usul".to_string()),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_events_for_fighter_and_turn() {
+        let events = sample();
+        let log = BattleLog::from(&events);
+        assert_eq!(log.events_for_fighter("Usul").count(), 10);
+        assert_eq!(log.events_for_fighter("Kacheek").count(), 0);
+        assert_eq!(log.events_in_turn(0).count(), 2);
+        assert_eq!(log.events_in_turn(5).count(), 4);
+        assert_eq!(log.total_turns(), 5);
+
+        let turns: Vec<u32> = log.turns().iter().map(|(turn, _)| *turn).collect();
+        assert_eq!(turns, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(log.turns().iter().map(|(_, events)| events.len()).sum::<usize>(), events.len());
+    }
+
+    #[test]
+    fn test_damage_timeline_counts_hp_actually_lost() {
+        let events = sample();
+        let timeline = BattleLog::from(&events).damage_timeline();
+        assert_eq!(
+            timeline,
+            vec![
+                DamageDealt { turn: 1, attacker: "Acara".into(), target: "Usul".into(), damage: 8, hp_left: 12 },
+                DamageDealt { turn: 5, attacker: "Acara".into(), target: "Usul".into(), damage: 17, hp_left: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_totals_by_fighter() {
+        let events = sample();
+        let log = BattleLog::from(&events);
+        assert_eq!(log.damage_by_fighter().get("Acara"), Some(&38));
+        assert_eq!(log.healing_by_fighter().get("Usul"), Some(&5));
+        assert_eq!(log.spells_by_fighter().get("Usul"), Some(&vec!["Fireball".to_string()]));
+        assert_eq!(log.crits().count(), 2);
+        assert_eq!(log.fumbles().count(), 1);
+    }
+
+    #[test]
+    fn test_completion_queries() {
+        let events = sample();
+        let log = BattleLog::from(&events);
+        assert_eq!(log.winner(), Some("Acara"));
+        assert_eq!(log.completion_reason(), Some(&BattleCompletionReason::HpDepleted("Usul".to_string())));
+        assert_eq!(log.final_hps(), Some([("Acara", 20), ("Usul", 0)]));
+        assert!(matches!(log.last_action(), Some(BattleEvent::Attack { turn: 5, .. })));
+
+        let unfinished = BattleLog::new(&events[..5]);
+        assert_eq!(unfinished.winner(), None);
+        assert_eq!(unfinished.final_hps(), None);
+    }
+
+    #[test]
+    fn test_diff_finds_where_battles_diverge() {
+        let events = sample();
+        let mut other = sample();
+        other[2] = roll(1, "Acara", true, false);
+        other[3] = attack(1, "Acara", "Usul", 12);
+        other[4] = health(1, "Usul", 20, 8);
+        other.truncate(9);
+        other.push(BattleEvent::Dodge { turn: 3, actor: "Usul".into(), attacker: "Acara".into() });
+        other.push(BattleEvent::Prediction {
+            turn: 4,
+            fighter1: "Acara".into(),
+            fighter2: "Usul".into(),
+            fighter1_win_probability: 0.5,
+        });
+        other.push(BattleEvent::Heal { turn: 4, actor: "Usul".into(), amount: 0 });
+
+        let diff = BattleLog::from(&events).diff(&BattleLog::from(&other));
+        let divergences: Vec<(u32, Vec<Divergence>)> =
+            diff.iter().filter(|t| t.diverged()).map(|t| (t.turn, t.divergences.clone())).collect();
+        assert_eq!(
+            divergences,
+            vec![
+                (1, vec![Divergence::Roll, Divergence::Damage]),
+                (3, vec![Divergence::Other]),
+                (4, vec![Divergence::Action]),
+                (5, vec![Divergence::Action, Divergence::Roll, Divergence::Damage]),
+            ]
+        );
+        assert_eq!(diff.len(), 6);
+        assert!(diff[5].second.is_empty());
+        assert!(BattleLog::from(&events).diff(&BattleLog::from(&events)).iter().all(|t| !t.diverged()));
+    }
+}
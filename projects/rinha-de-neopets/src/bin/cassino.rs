@@ -1,11 +1,28 @@
 use clap::{Parser, Subcommand};
-use dialoguer::Input;
+use dialoguer::{Input, Select};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::io::{self, Write};
 use std::path::Path;
+use std::sync::OnceLock;
 use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime};
+use crossterm::{cursor, execute, queue};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use rinha_de_neopets::audit::AuditTrail;
+use rinha_de_neopets::backup::BackupAction;
 use rinha_de_neopets::cassino_display::CassinoDisplay;
-use rinha_de_neopets::cassino::{CassinoEvent, CompletedEvent, ExpiredBet, ExpiredAccumulatedBet, DoneEvents, ExpiredBets};
+use rinha_de_neopets::cassino::{check_accumulator, BetOutcome, CassinoEvent, Market, VictoryMethod, CompletedEvent, DueReason, ExpiredBet, ExpiredAccumulatedBet, DoneEvents, ExpiredBets, OddsChangeSource};
+use rinha_de_neopets::ids::{IdGenerator, IdKind};
+use rinha_de_neopets::migrations::{self, AssetFile, Versioned};
+use rinha_de_neopets::ledger::{Ledger, Transaction, TransactionKind};
+use rinha_de_neopets::promotions::{Promotion, PromotionKind, Promotions};
+use rinha_de_neopets::engine::Engine;
+use rinha_de_neopets::store::StorageConfig;
+use rinha_de_neopets::config::{CliConfig, ConfigAction, ConfigArgs};
+use rinha_de_neopets::logging::LogArgs;
 use rand;
 use colored::Colorize;
 
@@ -16,19 +33,36 @@ use colored::Colorize;
 struct Cli {
 	#[command(subcommand)]
 	command: Commands,
+	#[command(flatten)]
+	log: LogArgs,
+	#[command(flatten)]
+	config: ConfigArgs,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-	Event,
+	/// Create an event interactively, or manage an existing one
+	Event {
+	    #[command(subcommand)]
+	    action: Option<EventAction>,
+	},
 	Cash,
 	Bet {
 	    #[arg(short, long)]
 	    event_id: String,
 	    #[arg(short, long)]
 	    amount: f64,
+	    /// Apply a free bet or odds boost from the wallet
+	    #[arg(short, long, value_name = "PROMOTION_ID")]
+	    promotion: Option<String>,
 	},
 	ListEvents,
+	/// Keep a live board of open events with their odds, stakes and time to lock
+	Board {
+	    /// Seconds between redraws, changes from other processes show up right away
+	    #[arg(short, long, default_value_t = 5)]
+	    refresh: u64,
+	},
 	AccumulatedBet {
 	    #[arg(short, long, num_args = 1..)]
 	    event_ids: Vec<String>,
@@ -40,20 +74,109 @@ enum Commands {
 	    event_id: String,
 	},
 	RunAllEvents,
+	/// Settle only events whose linked battle completed or whose expiry passed (safe to run from cron)
+	RunDue,
+	/// Show betting totals and profit from the transaction ledger
+	Stats {
+	    /// Also chart the bankroll day by day
+	    #[arg(long)]
+	    chart: bool,
+	},
+	/// Check a battle against its audit trail and show how its linked events settle
+	Audit {
+	    battle_id: String,
+	},
+	/// Show the real balance and the promotions available
+	Wallet,
+	/// Grant promotional credit
+	Promo {
+	    #[command(subcommand)]
+	    action: PromoAction,
+	},
+	/// Archive every asset file, colosseum's included, or put an archive back
+	Backup {
+	    #[command(subcommand)]
+	    action: BackupAction,
+	},
+	/// Write or show the config file both CLIs read
+	Config {
+	    #[command(subcommand)]
+	    action: ConfigAction,
+	},
+}
+
+#[derive(Subcommand)]
+enum PromoAction {
+	/// Grant a free bet or an odds boost
+	Grant {
+	    /// Free bet worth this amount, its stake is not returned when it wins
+	    #[arg(long, conflicts_with = "odds_boost", required_unless_present = "odds_boost")]
+	    free_bet: Option<f64>,
+	    /// Raise the winnings of one bet by this fraction, e.g. 0.25 for +25%
+	    #[arg(long)]
+	    odds_boost: Option<f64>,
+	    /// Days until the promotion expires, never if omitted
+	    #[arg(long, value_name = "DAYS")]
+	    expires_in_days: Option<i64>,
+	},
 }
 
 
 
+#[derive(Subcommand)]
+enum EventAction {
+	/// Show every odds change of an event, with a chart
+	History {
+	    event_id: String,
+	},
+	/// Manually change the odd of an active event
+	SetOdd {
+	    #[arg(short, long)]
+	    event_id: String,
+	    #[arg(short, long)]
+	    odd: f64,
+	},
+}
+
+/// Fighters and battles, kept by colosseum
+/// Read from `--config` or `~/.config/rinha/config.toml` before any command runs.
+static CONFIG: OnceLock<CliConfig> = OnceLock::new();
+
+fn cli_config() -> &'static CliConfig {
+    CONFIG.get_or_init(CliConfig::default)
+}
+
+/// A file in the assets directory the config points to, shared with colosseum.
+fn asset(name: &str) -> String {
+    cli_config().asset(name)
+}
+
+/// Shared with colosseum, so IDs stay unique across both CLIs. Found where the
+/// storage config of the assets says, like the fighters and battles.
+fn ids() -> IdGenerator {
+    let dir = &cli_config().assets.dir;
+    let config = StorageConfig::load(dir).unwrap_or_else(|_| StorageConfig::in_dir(dir));
+    IdGenerator::new(config.path(&config.ids))
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct Bet {
+    #[serde(default)]
+    id: String, // empty for bets placed before bets had IDs
     event_id: String,
     amount: f64,
     potential_win: f64,
     timestamp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    promotion_id: Option<String>,
+    #[serde(default)]
+    bonus_win: f64, // part of potential_win paid as a bonus
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct AccumulatedBet {
+    #[serde(default)]
+    id: String,
     event_ids: Vec<String>,
     amount: f64,
     combined_odds: f64,
@@ -77,7 +200,7 @@ struct AccumulatedBets {
 }
 
 fn load_events_and_odds() -> EventsAndOdds {
-    let path = "assets/events_and_odds.json";
+    let path = &asset("events_and_odds.json");
     if Path::new(path).exists() {
         match fs::read_to_string(path) {
             Ok(content) => match serde_json::from_str(&content) {
@@ -92,17 +215,17 @@ fn load_events_and_odds() -> EventsAndOdds {
 }
 
 fn save_events_and_odds(events_and_odds: &EventsAndOdds) {
-    let path = "assets/events_and_odds.json";
+    let path = &asset("events_and_odds.json");
     let json = serde_json::to_string_pretty(events_and_odds)
         .expect("Failed to serialize events and odds");
     fs::write(path, json).expect("Failed to write events and odds to file");
 }
 
 fn load_bets() -> Bets {
-    let path = "assets/bets.json";
+    let path = &asset("bets.json");
     if Path::new(path).exists() {
         match fs::read_to_string(path) {
-            Ok(content) => match serde_json::from_str(&content) {
+            Ok(content) => match migrations::read(AssetFile::Bets, content.as_bytes()) {
                 Ok(bets) => bets,
                 Err(_) => Bets::default(),
             },
@@ -114,17 +237,17 @@ fn load_bets() -> Bets {
 }
 
 fn save_bets(bets: &Bets) {
-    let path = "assets/bets.json";
-    let json = serde_json::to_string_pretty(bets)
+    let path = &asset("bets.json");
+    let json = serde_json::to_string_pretty(&Versioned::new(AssetFile::Bets, bets))
         .expect("Failed to serialize bets");
     fs::write(path, json).expect("Failed to write bets to file");
 }
 
 fn load_accumulated_bets() -> AccumulatedBets {
-    let path = "assets/accumulated_bets.json";
+    let path = &asset("accumulated_bets.json");
     if Path::new(path).exists() {
         match fs::read_to_string(path) {
-            Ok(content) => match serde_json::from_str(&content) {
+            Ok(content) => match migrations::read(AssetFile::AccumulatedBets, content.as_bytes()) {
                 Ok(accumulated_bets) => accumulated_bets,
                 Err(_) => AccumulatedBets::default(),
             },
@@ -136,14 +259,14 @@ fn load_accumulated_bets() -> AccumulatedBets {
 }
 
 fn save_accumulated_bets(accumulated_bets: &AccumulatedBets) {
-    let path = "assets/accumulated_bets.json";
-    let json = serde_json::to_string_pretty(accumulated_bets)
+    let path = &asset("accumulated_bets.json");
+    let json = serde_json::to_string_pretty(&Versioned::new(AssetFile::AccumulatedBets, accumulated_bets))
         .expect("Failed to serialize accumulated bets");
     fs::write(path, json).expect("Failed to write accumulated bets to file");
 }
 
 fn load_done_events() -> DoneEvents {
-    let path = "assets/done.json";
+    let path = &asset("done.json");
     if Path::new(path).exists() {
         match fs::read_to_string(path) {
             Ok(content) => match serde_json::from_str(&content) {
@@ -158,14 +281,14 @@ fn load_done_events() -> DoneEvents {
 }
 
 fn save_done_events(done_events: &DoneEvents) {
-    let path = "assets/done.json";
+    let path = &asset("done.json");
     let json = serde_json::to_string_pretty(done_events)
         .expect("Failed to serialize done events");
     fs::write(path, json).expect("Failed to write done events to file");
 }
 
 fn load_expired_bets() -> ExpiredBets {
-    let path = "assets/expired_bets.json";
+    let path = &asset("expired_bets.json");
     if Path::new(path).exists() {
         match fs::read_to_string(path) {
             Ok(content) => match serde_json::from_str(&content) {
@@ -180,24 +303,65 @@ fn load_expired_bets() -> ExpiredBets {
 }
 
 fn save_expired_bets(expired_bets: &ExpiredBets) {
-    let path = "assets/expired_bets.json";
+    let path = &asset("expired_bets.json");
     let json = serde_json::to_string_pretty(expired_bets)
         .expect("Failed to serialize expired bets");
     fs::write(path, json).expect("Failed to write expired bets to file");
 }
 
-fn place_bet_with_display(event_id: String, amount: f64, display: &CassinoDisplay) {
+fn record_transaction(kind: TransactionKind, amount: f64, bet_id: &str) {
+    let mut ledger = Ledger::load(&asset("ledger.json")).expect("Failed to load ledger");
+    let id = ids().next(IdKind::Transaction).expect("Failed to generate a transaction ID");
+    ledger.transactions.push(Transaction {
+        id,
+        kind,
+        amount,
+        bet_id: bet_id.to_string(),
+        timestamp: chrono::Local::now().to_rfc3339(),
+    });
+    ledger.save(&asset("ledger.json")).expect("Failed to write ledger to file");
+}
+
+/// Gives back what was really staked on a bet whose event was voided, and returns it.
+fn refund_stake(bet_id: &str) -> f64 {
+    let ledger = Ledger::load(&asset("ledger.json")).expect("Failed to load ledger");
+    let refund = ledger.staked_on(bet_id);
+    if refund > 0.0 {
+        record_transaction(TransactionKind::Refund, refund, bet_id);
+    }
+    refund
+}
+
+/// Records the payout of a winning bet, the part owed to a promotion as a bonus.
+fn record_payout(actual_payout: f64, bonus_win: f64, bet_id: &str) {
+    if actual_payout <= 0.0 {
+        return;
+    }
+    let bonus = bonus_win.min(actual_payout);
+    if actual_payout - bonus > 0.0 {
+        record_transaction(TransactionKind::Payout, actual_payout - bonus, bet_id);
+    }
+    if bonus > 0.0 {
+        record_transaction(TransactionKind::Bonus, bonus, bet_id);
+    }
+}
+
+fn place_bet_with_display(event_id: String, amount: f64, promotion_id: Option<String>, display: &CassinoDisplay) {
     // Validate that event_id is not empty
     if event_id.trim().is_empty() {
         display.show_error("No event ID provided for bet!");
         return;
     }
     
-    // Validate that amount is positive
+    // Validate that amount is positive, and one the house takes
     if amount <= 0.0 {
         display.show_error("Bet amount must be greater than 0!");
         return;
     }
+    if let Err(e) = cli_config().house.check_stake(amount) {
+        display.show_error(&e);
+        return;
+    }
     
     // Show loading animation
     display.show_loading_animation("🔍 Verifying event...");
@@ -206,29 +370,62 @@ fn place_bet_with_display(event_id: String, amount: f64, display: &CassinoDispla
     let events_and_odds = load_events_and_odds();
     
     if let Some(event) = events_and_odds.events.get(&event_id) {
-        // Calculate potential win (amount * odd)
-        let potential_win = amount * event.odd;
-        
         // Show processing animation
         display.show_loading_animation("💰 Processing bet...");
         
+        let id = match ids().next(IdKind::Bet) {
+            Ok(id) => id,
+            Err(e) => {
+                display.show_error(&format!("Failed to generate a bet ID: {}", e));
+                return;
+            }
+        };
+        
+        // Calculate potential win (amount * odd), unless a promotion changes the terms
+        let (real_stake, odd, potential_win, bonus_win) = match &promotion_id {
+            Some(promotion_id) => {
+                let mut promotions = Promotions::load(&asset("promotions.json")).expect("Failed to load promotions");
+                let now = chrono::Local::now().fixed_offset();
+                match promotions.redeem(promotion_id, &id, amount, event.odd, now) {
+                    Ok(promoted) => {
+                        promotions.save(&asset("promotions.json")).expect("Failed to write promotions to file");
+                        (promoted.real_stake, promoted.odd, promoted.potential_win, promoted.bonus_win)
+                    }
+                    Err(e) => {
+                        display.show_error(&e);
+                        return;
+                    }
+                }
+            }
+            None => (amount, event.odd, amount * event.odd, 0.0),
+        };
+        
         // Create the bet
         let bet = Bet {
+            id,
             event_id: event_id.clone(),
             amount,
             potential_win,
             timestamp: chrono::Local::now().to_rfc3339(),
+            promotion_id,
+            bonus_win,
         };
         
-        // Load existing bets and add the new one
+        // Load existing bets and add the new one, free bets cost no real money
         let mut bets = load_bets();
+        if real_stake > 0.0 {
+            record_transaction(TransactionKind::Stake, real_stake, &bet.id);
+        }
         bets.bets.push(bet);
         
         // Save bets
         save_bets(&bets);
         
         // Display beautiful bet confirmation
-        display.show_bet_placement(&event_id, amount, potential_win, event.odd, false);
+        display.show_bet_placement(&event_id, amount, potential_win, odd, false);
+        if real_stake == 0.0 {
+            display.show_info("🎟️  Free bet: only the winnings are paid, the stake is not returned");
+        }
     } else {
         display.show_error(&format!("Event '{}' not found! Use 'cassino list-events' to see available events.", event_id));
     }
@@ -242,11 +439,16 @@ fn place_accumulated_bet_with_display(event_ids: Vec<String>, amount: f64, displ
         return;
     }
     
-    // Validate that amount is positive
+    // Validate that amount is positive, and one the house takes
     if amount <= 0.0 {
         display.show_error("Bet amount must be greater than 0!");
         return;
     }
+    let house = &cli_config().house;
+    if let Err(e) = house.check_stake(amount).and_then(|_| house.check_accumulator(event_ids.len())) {
+        display.show_error(&e);
+        return;
+    }
     
     display.show_loading_animation("🔍 Verifying events...");
     
@@ -265,13 +467,32 @@ fn place_accumulated_bet_with_display(event_ids: Vec<String>, amount: f64, displ
         }
     }
     
+    // Outcomes of the same market exclude each other, such a bet could never win
+    let events: Vec<(&str, &CassinoEvent)> = event_ids
+        .iter()
+        .map(|id| (id.as_str(), &events_and_odds.events[id]))
+        .collect();
+    if let Err(e) = check_accumulator(&events) {
+        display.show_error(&e);
+        return;
+    }
+    
     // Calculate potential win (amount * combined_odds)
     let potential_win = amount * combined_odds;
     
     display.show_loading_animation("🎯 Processing accumulated bet...");
     
+    let id = match ids().next(IdKind::Bet) {
+        Ok(id) => id,
+        Err(e) => {
+            display.show_error(&format!("Failed to generate a bet ID: {}", e));
+            return;
+        }
+    };
+    
     // Create the accumulated bet
     let accumulated_bet = AccumulatedBet {
+        id,
         event_ids: event_ids.clone(),
         amount,
         combined_odds,
@@ -281,6 +502,7 @@ fn place_accumulated_bet_with_display(event_ids: Vec<String>, amount: f64, displ
     
     // Load existing accumulated bets and add the new one
     let mut accumulated_bets = load_accumulated_bets();
+    record_transaction(TransactionKind::Stake, amount, &accumulated_bet.id);
     accumulated_bets.accumulated_bets.push(accumulated_bet);
     
     // Save accumulated bets
@@ -324,10 +546,32 @@ fn create_event_interactively_with_display(display: &CassinoDisplay) {
         .interact_text()
         .expect("Failed to read odd");
     
+    // Optionally tie the event to an outcome of a colosseum battle
+    let battle_id: String = Input::new()
+        .with_prompt("⚔️  Link to battle ID (leave empty for none)")
+        .allow_empty(true)
+        .interact_text()
+        .expect("Failed to read battle ID");
+    let market = if battle_id.trim().is_empty() {
+        None
+    } else {
+        Some(prompt_market(battle_id.trim().to_string()))
+    };
+
+    let expires_in_minutes: i64 = Input::new()
+        .with_prompt("⏰ Expires in how many minutes (0 for never)")
+        .default(0)
+        .interact_text()
+        .expect("Failed to read expiry");
+    let expires_at = (expires_in_minutes > 0).then(|| {
+        (chrono::Local::now() + chrono::Duration::minutes(expires_in_minutes)).to_rfc3339()
+    });
+
     // Create the event
     let event = CassinoEvent {
-        description: description.clone(),
-        odd,
+        market,
+        expires_at,
+        ..CassinoEvent::new(description.clone(), odd)
     };
     
     // Show loading animation while processing
@@ -336,8 +580,17 @@ fn create_event_interactively_with_display(display: &CassinoDisplay) {
     // Load existing events and odds
     let mut events_and_odds = load_events_and_odds();
     
-    // Generate a unique ID for the event
-    let event_id = format!("event_{}", events_and_odds.events.len() + 1);
+    // Skip IDs of events created before the counter existed, running or already done
+    let done_events = load_done_events();
+    let event_id = match ids().next_unused(IdKind::Event, |id| {
+        events_and_odds.events.contains_key(id) || done_events.completed_events.iter().any(|e| e.event_id == id)
+    }) {
+        Ok(id) => id,
+        Err(e) => {
+            display.show_error(&format!("Failed to generate an event ID: {}", e));
+            return;
+        }
+    };
     
     // Add the new event
     events_and_odds.events.insert(event_id.clone(), event.clone());
@@ -349,6 +602,101 @@ fn create_event_interactively_with_display(display: &CassinoDisplay) {
     display.show_event_success(&event_id, &event.description, event.odd);
 }
 
+fn prompt_market(battle_id: String) -> Market {
+    let kinds = ["🏆 Winner", "💥 Method of victory", "⏱️  Over/under total turns", "💀 Overkill on the finishing blow"];
+    let kind = Select::new()
+        .with_prompt("📊 Market")
+        .items(&kinds)
+        .default(0)
+        .interact()
+        .expect("Failed to read market");
+
+    match kind {
+        0 => {
+            let fighter: String = Input::new()
+                .with_prompt("🏆 Fighter that must win for the event to occur")
+                .interact_text()
+                .expect("Failed to read winner");
+            Market::Winner { battle_id, fighter }
+        }
+        1 => {
+            let methods: Vec<String> = VictoryMethod::ALL.iter().map(|m| m.to_string()).collect();
+            let method = Select::new()
+                .with_prompt("💥 How the battle must be won")
+                .items(&methods)
+                .default(0)
+                .interact()
+                .expect("Failed to read method of victory");
+            Market::MethodOfVictory { battle_id, method: VictoryMethod::ALL[method] }
+        }
+        2 => {
+            let line: f64 = Input::new()
+                .with_prompt("⏱️  Total turns line (e.g. 6.5)")
+                .interact_text()
+                .expect("Failed to read line");
+            let over = Select::new()
+                .with_prompt("⏱️  Over or under")
+                .items(&["Over", "Under"])
+                .default(0)
+                .interact()
+                .expect("Failed to read over/under")
+                == 0;
+            Market::TotalTurns { battle_id, line, over }
+        }
+        _ => {
+            let line: u32 = Input::new()
+                .with_prompt("💀 Overkill line (e.g. 10)")
+                .interact_text()
+                .expect("Failed to read line");
+            let over = Select::new()
+                .with_prompt("💀 Finishing blow with at least that much overkill, or not")
+                .items(&["At least", "Less (or no knockout by attack)"])
+                .default(0)
+                .interact()
+                .expect("Failed to read over/under")
+                == 0;
+            Market::Overkill { battle_id, line, over }
+        }
+    }
+}
+
+fn set_odd_with_display(event_id: String, odd: f64, display: &CassinoDisplay) {
+    if odd <= 0.0 {
+        display.show_error("Odd must be greater than 0!");
+        return;
+    }
+
+    let mut events_and_odds = load_events_and_odds();
+
+    if let Some(event) = events_and_odds.events.get_mut(&event_id) {
+        let previous = event.odd;
+        event.set_odd(odd, OddsChangeSource::Manual, chrono::Local::now().to_rfc3339());
+        save_events_and_odds(&events_and_odds);
+        display.show_info(&format!("Odd of {} changed from {:.2}x to {:.2}x", event_id, previous, odd));
+    } else {
+        display.show_error(&format!("Event '{}' not found! Use 'cassino list-events' to see available events.", event_id));
+    }
+}
+
+fn show_odds_history_with_display(event_id: &str, display: &CassinoDisplay) {
+    let events_and_odds = load_events_and_odds();
+
+    // Settled events keep their history in done.json
+    let found = match events_and_odds.events.get(event_id) {
+        Some(event) => Some((event.description.clone(), event.odds_history.clone())),
+        None => load_done_events()
+            .completed_events
+            .into_iter()
+            .find(|e| e.event_id == event_id)
+            .map(|e| (e.description, e.odds_history)),
+    };
+
+    match found {
+        Some((description, history)) => display.show_odds_history(event_id, &description, &history),
+        None => display.show_error(&format!("Event '{}' not found!", event_id)),
+    }
+}
+
 fn list_events_with_display(display: &CassinoDisplay) {
     display.show_loading_animation("📋 Loading available events...");
     
@@ -356,6 +704,85 @@ fn list_events_with_display(display: &CassinoDisplay) {
     display.show_events_list(&events_and_odds.events);
 }
 
+/// Files other cassino and colosseum processes write that change what the board shows
+const BOARD_FILES: [&str; 3] = ["events_and_odds.json", "bets.json", "accumulated_bets.json"];
+
+/// Amount staked on each event: its single bets plus every accumulated bet it is a leg of.
+fn staked_per_event() -> HashMap<String, f64> {
+    let mut staked: HashMap<String, f64> = HashMap::new();
+    for bet in load_bets().bets {
+        *staked.entry(bet.event_id).or_default() += bet.amount;
+    }
+    for bet in load_accumulated_bets().accumulated_bets {
+        for event_id in bet.event_ids {
+            *staked.entry(event_id).or_default() += bet.amount;
+        }
+    }
+    staked
+}
+
+fn board_file_times() -> Vec<Option<SystemTime>> {
+    BOARD_FILES.iter().map(|name| fs::metadata(asset(name)).and_then(|m| m.modified()).ok()).collect()
+}
+
+/// Raw mode on the alternate screen while the board is open, restored when dropped so
+/// the shell comes back intact even if drawing fails.
+struct BoardScreen;
+
+impl BoardScreen {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, cursor::Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for BoardScreen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn show_board(refresh_secs: u64, display: &CassinoDisplay) -> io::Result<()> {
+    let _screen = BoardScreen::enter()?;
+    let refresh = Duration::from_secs(refresh_secs.max(1));
+    let mut drawn_file_times = None;
+    let mut last_draw = Instant::now();
+
+    loop {
+        // Redraw when another process touched the files, or for the countdowns to tick
+        let file_times = board_file_times();
+        if drawn_file_times.as_ref() != Some(&file_times) || last_draw.elapsed() >= refresh {
+            let events_and_odds = load_events_and_odds();
+            let now = chrono::Local::now().fixed_offset();
+            let mut stdout = io::stdout();
+            queue!(stdout, cursor::MoveTo(0, 0), Clear(ClearType::All))?;
+            for line in display.board_lines(&events_and_odds.events, &staked_per_event(), now) {
+                queue!(stdout, Print(line), Print("\r\n"))?;
+            }
+            stdout.flush()?;
+            drawn_file_times = Some(file_times);
+            last_draw = Instant::now();
+        }
+
+        // Short polls keep file changes and key presses responsive
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                    return Ok(());
+                }
+            }
+            Event::Resize(..) => drawn_file_times = None,
+            _ => {}
+        }
+    }
+}
+
 fn run_event_with_display(event_id: String, display: &CassinoDisplay) {
     display.show_loading_animation(&format!("🎲 Running event {}...", event_id));
     
@@ -374,6 +801,8 @@ fn run_event_with_display(event_id: String, display: &CassinoDisplay) {
             odd: event.odd,
             result: event_occurred,
             timestamp: chrono::Local::now().to_rfc3339(),
+            odds_history: event.odds_history.clone(),
+            voided: false,
         };
         
         // Remove event from active events
@@ -405,14 +834,18 @@ fn run_event_with_display(event_id: String, display: &CassinoDisplay) {
                 total_earned += actual_payout;
                 
                 let expired_bet = ExpiredBet {
+                    bet_id: bet.id,
                     event_id: bet.event_id,
                     amount: bet.amount,
                     potential_win: bet.potential_win,
                     result: event_occurred,
                     actual_payout,
                     timestamp: bet.timestamp,
+                    promotion_id: bet.promotion_id,
+                    refunded: false,
                 };
                 
+                record_payout(actual_payout, bet.bonus_win, &expired_bet.bet_id);
                 expired_bets.expired_bets.push(expired_bet);
             } else {
                 // Keep bets for other events
@@ -434,6 +867,7 @@ fn run_event_with_display(event_id: String, display: &CassinoDisplay) {
                 // Since we're only running one event at a time, we'll consider it a loss
                 // In a real system, you'd wait for all events to be run
                 let expired_acc_bet = ExpiredAccumulatedBet {
+                    bet_id: acc_bet.id,
                     event_ids: acc_bet.event_ids,
                     amount: acc_bet.amount,
                     combined_odds: acc_bet.combined_odds,
@@ -441,8 +875,12 @@ fn run_event_with_display(event_id: String, display: &CassinoDisplay) {
                     all_events_occurred: false, // Simplified: assume loss when any event is run
                     actual_payout: 0.0,
                     timestamp: acc_bet.timestamp,
+                    refunded: false,
                 };
                 
+                if expired_acc_bet.actual_payout > 0.0 {
+                    record_transaction(TransactionKind::Payout, expired_acc_bet.actual_payout, &expired_acc_bet.bet_id);
+                }
                 expired_bets.expired_accumulated_bets.push(expired_acc_bet);
                 // Don't add to remaining since this bet is now expired
             } else {
@@ -486,73 +924,21 @@ fn run_all_events_with_display(display: &CassinoDisplay) {
     // Run each event
     for event_id in event_ids {
         // Load fresh data for each event since previous events may have modified the state
-        let mut current_events = load_events_and_odds();
+        let current_events = load_events_and_odds();
         
         if let Some(event) = current_events.events.get(&event_id).cloned() {
             // Randomly determine if event occurred
             let event_occurred = rand::random::<bool>();
-            
-            // Create completed event
-            let completed_event = CompletedEvent {
-                event_id: event_id.clone(),
-                description: event.description.clone(),
-                odd: event.odd,
-                result: event_occurred,
-                timestamp: chrono::Local::now().to_rfc3339(),
-            };
-            
-            // Remove event from active events
-            current_events.events.remove(&event_id);
-            
-            // Load existing done events and add the new one
-            let mut done_events = load_done_events();
-            done_events.completed_events.push(completed_event.clone());
-            
-            // Process bets for this event
-            let mut bets = load_bets();
-            let mut expired_bets = load_expired_bets();
-            
-            // Process individual bets
-            let mut remaining_bets = Vec::new();
-            for bet in bets.bets {
-                if bet.event_id == event_id {
-                    total_spent += bet.amount;
-                    
-                    let actual_payout = if event_occurred {
-                        bet.potential_win
-                    } else {
-                        0.0
-                    };
-                    
-                    total_earned += actual_payout;
-                    
-                    let expired_bet = ExpiredBet {
-                        event_id: bet.event_id,
-                        amount: bet.amount,
-                        potential_win: bet.potential_win,
-                        result: event_occurred,
-                        actual_payout,
-                        timestamp: bet.timestamp,
-                    };
-                    
-                    expired_bets.expired_bets.push(expired_bet);
-                } else {
-                    remaining_bets.push(bet);
-                }
-            }
-            bets.bets = remaining_bets;
-            
+
+            let (spent, earned) = settle_event(&event_id, &event, Some(event_occurred));
+            total_spent += spent;
+            total_earned += earned;
+
             // For accumulated bets, we need to track which events have been processed
             // and only mark them as expired when all their events have been run
             // For now, let's just collect the results and process accumulated bets at the end
-            
+
             results.push((event_id.clone(), event.description.clone(), event_occurred, event.odd));
-            
-            // Save changes for this event
-            save_events_and_odds(&current_events);
-            save_done_events(&done_events);
-            save_bets(&bets);
-            save_expired_bets(&expired_bets);
         }
     }
     
@@ -563,15 +949,243 @@ fn run_all_events_with_display(display: &CassinoDisplay) {
     display.show_all_events_result(results, total_spent, total_earned);
 }
 
+/// Moves an event to done and pays out (or not) the single bets placed on it.
+/// Accumulated bets are left to `process_accumulated_bets_after_all_events`.
+/// Returns the amount spent and earned on the settled bets.
+#[tracing::instrument(skip(event))]
+/// Settles `event` and the single bets on it, `None` voiding it and refunding the stakes.
+fn settle_event(event_id: &str, event: &CassinoEvent, event_occurred: Option<bool>) -> (f64, f64) {
+    let mut current_events = load_events_and_odds();
+
+    // Create completed event
+    let completed_event = CompletedEvent {
+        event_id: event_id.to_string(),
+        description: event.description.clone(),
+        odd: event.odd,
+        result: event_occurred.unwrap_or(false),
+        timestamp: chrono::Local::now().to_rfc3339(),
+        odds_history: event.odds_history.clone(),
+        voided: event_occurred.is_none(),
+    };
+    let outcome = BetOutcome::of([&completed_event]);
+
+    // Remove event from active events
+    current_events.events.remove(event_id);
+
+    // Load existing done events and add the new one
+    let mut done_events = load_done_events();
+    done_events.completed_events.push(completed_event);
+
+    // Process bets for this event
+    let mut bets = load_bets();
+    let mut expired_bets = load_expired_bets();
+    let mut total_spent = 0.0;
+    let mut total_earned = 0.0;
+
+    // Process individual bets
+    let mut remaining_bets = Vec::new();
+    for bet in bets.bets {
+        if bet.event_id == event_id {
+            total_spent += bet.amount;
+
+            let actual_payout = match outcome {
+                BetOutcome::Won => {
+                    record_payout(bet.potential_win, bet.bonus_win, &bet.id);
+                    bet.potential_win
+                }
+                BetOutcome::Lost => 0.0,
+                BetOutcome::Refunded => refund_stake(&bet.id),
+            };
+
+            total_earned += actual_payout;
+
+            let expired_bet = ExpiredBet {
+                bet_id: bet.id,
+                event_id: bet.event_id,
+                amount: bet.amount,
+                potential_win: bet.potential_win,
+                result: outcome == BetOutcome::Won,
+                actual_payout,
+                timestamp: bet.timestamp,
+                promotion_id: bet.promotion_id,
+                refunded: outcome == BetOutcome::Refunded,
+            };
+            expired_bets.expired_bets.push(expired_bet);
+        } else {
+            remaining_bets.push(bet);
+        }
+    }
+    bets.bets = remaining_bets;
+
+    // Save changes for this event
+    save_events_and_odds(&current_events);
+    save_done_events(&done_events);
+    save_bets(&bets);
+    save_expired_bets(&expired_bets);
+
+    tracing::info!(spent = total_spent, earned = total_earned, "event settled");
+    (total_spent, total_earned)
+}
+
+fn run_due_events_with_display(display: &CassinoDisplay) {
+    display.show_loading_animation("⏰ Looking for due events...");
+
+    let now = chrono::Local::now().fixed_offset();
+    let mut engine = match Engine::open(&cli_config().assets.dir) {
+        Ok(engine) => engine,
+        Err(e) => {
+            display.show_error(&format!("Failed to load battles: {}", e));
+            return;
+        }
+    };
+
+    // Pending battles older than the config's TTL were never going to be fought
+    if let Some(ttl) = cli_config().battles.pending_ttl() {
+        let removed = engine.storage_mut().expire_pending(now - ttl);
+        if !removed.is_empty() {
+            if let Err(e) = engine.save() {
+                display.show_error(&format!("Failed to remove stale pending battles: {}", e));
+                return;
+            }
+            display.show_info(&format!("Removed {} stale pending battles: {}", removed.len(), removed.join(", ")));
+        }
+    }
+
+    let complete_battles = match engine.storage().load_complete_battles() {
+        Ok(battles) => battles,
+        Err(e) => {
+            display.show_error(&format!("Failed to load battles: {}", e));
+            return;
+        }
+    };
+
+    let events_and_odds = load_events_and_odds();
+    let done_events = load_done_events();
+
+    // Sorted so that repeated runs settle events in the same order
+    let mut event_ids: Vec<&String> = events_and_odds.events.keys().collect();
+    event_ids.sort();
+
+    let mut due = Vec::new();
+    for event_id in event_ids {
+        // Already settled by an earlier run, never pay out twice
+        if done_events.completed_events.iter().any(|e| e.event_id == *event_id) {
+            continue;
+        }
+        let event = &events_and_odds.events[event_id];
+        if let Some(reason) = event.due_reason(&complete_battles, now) {
+            due.push((event_id.clone(), event.clone(), reason));
+        }
+    }
+
+    if due.is_empty() {
+        display.show_info("No events are due, nothing to settle.");
+        return;
+    }
+
+    let mut total_spent = 0.0;
+    let mut total_earned = 0.0;
+    let mut results = Vec::new();
+
+    for (event_id, event, reason) in due {
+        let (event_occurred, why) = match reason {
+            DueReason::BattleCompleted { occurred } => (Some(occurred), "linked battle completed"),
+            // Nothing could decide an unlinked event, so it's drawn like `run-event`
+            DueReason::Expired => (Some(rand::random::<bool>()), "expired"),
+            DueReason::Voided => (None, "expired before its battle was fought, voided and the stakes refunded"),
+        };
+        display.show_info(&format!("Settling {} ({})", event_id, why));
+
+        let (spent, earned) = settle_event(&event_id, &event, event_occurred);
+        total_spent += spent;
+        total_earned += earned;
+        if let Some(occurred) = event_occurred {
+            results.push((event_id, event.description, occurred, event.odd));
+        }
+    }
+
+    process_accumulated_bets_after_all_events(&mut total_spent, &mut total_earned);
+
+    display.show_all_events_result(results, total_spent, total_earned);
+}
+
+/// Replays a battle's audit trail against its stored events, so a disputed settlement
+/// can be checked HP by HP.
+fn audit_battle_with_display(battle_id: &str, display: &CassinoDisplay) {
+    let engine = match Engine::open(&cli_config().assets.dir) {
+        Ok(engine) => engine,
+        Err(e) => {
+            display.show_error(&format!("Failed to load battles: {}", e));
+            return;
+        }
+    };
+    let battle = match engine.storage().load_battle(battle_id) {
+        Ok(battle) => battle.filter(|b| b.is_completed),
+        Err(e) => {
+            display.show_error(&format!("Failed to load battle '{}': {}", battle_id, e));
+            return;
+        }
+    };
+    let Some(battle) = &battle else {
+        display.show_error(&format!("Completed battle '{}' not found", battle_id));
+        return;
+    };
+    // Written by `colosseum battle start --audit`
+    let trail_path = cli_config().assets.dir.join("audits").join(format!("{}.json", battle_id));
+    let trail = match AuditTrail::load(&trail_path) {
+        Ok(trail) => trail,
+        Err(e) => {
+            display.show_error(&format!(
+                "No audit trail for '{}' ({}), run it with `colosseum battle start --audit`",
+                battle_id, e
+            ));
+            return;
+        }
+    };
+
+    if let Err(e) = trail.verify(&battle.events) {
+        display.show_error(&format!("Battle '{}' does not match its audit trail: {}", battle_id, e));
+        return;
+    }
+
+    let mut final_hps: Vec<(&str, u32)> = trail.final_hps().into_iter().collect();
+    final_hps.sort();
+    let hps: Vec<String> = final_hps.iter().map(|(name, hp)| format!("{} {} HP", name, hp)).collect();
+    display.show_info(&format!(
+        "✅ {} state changes replayed, the stored battle matches them\n🏆 Winner: {}\n❤️  Final HP: {}",
+        trail.entries.len(),
+        trail.winner().unwrap_or("nobody"),
+        hps.join(", ")
+    ));
+
+    let events_and_odds = load_events_and_odds();
+    let mut event_ids: Vec<&String> = events_and_odds.events.keys().collect();
+    event_ids.sort();
+    let now = chrono::Local::now().fixed_offset();
+    for event_id in event_ids {
+        let event = &events_and_odds.events[event_id];
+        let linked = event.market.as_ref().map(|m| m.battle_id())
+            .or(event.battle.as_ref().map(|link| link.battle_id.as_str()));
+        if linked != Some(battle_id) {
+            continue;
+        }
+        if let Some(DueReason::BattleCompleted { occurred }) = event.due_reason(std::slice::from_ref(battle), now) {
+            let outcome = if occurred { "occurred" } else { "did not occur" };
+            println!("  {}: {} → {}", event_id, event.description, outcome);
+        }
+    }
+}
+
+#[tracing::instrument(skip_all)]
 fn process_accumulated_bets_after_all_events(total_spent: &mut f64, total_earned: &mut f64) {
     let mut accumulated_bets = load_accumulated_bets();
     let mut expired_bets = load_expired_bets();
     let done_events = load_done_events();
     
     // Create a map of event results for quick lookup
-    let event_results: HashMap<String, bool> = done_events.completed_events
+    let event_results: HashMap<&str, &CompletedEvent> = done_events.completed_events
         .iter()
-        .map(|e| (e.event_id.clone(), e.result))
+        .map(|e| (e.event_id.as_str(), e))
         .collect();
     
     let mut remaining_accumulated_bets = Vec::new();
@@ -579,33 +1193,37 @@ fn process_accumulated_bets_after_all_events(total_spent: &mut f64, total_earned
     for acc_bet in accumulated_bets.accumulated_bets {
         // Check if all events in this accumulated bet have been processed
         let all_events_processed = acc_bet.event_ids.iter()
-            .all(|event_id| event_results.contains_key(event_id));
+            .all(|event_id| event_results.contains_key(event_id.as_str()));
         
         if all_events_processed {
             // All events have been processed, determine if bet won
             *total_spent += acc_bet.amount;
             
-            let all_events_occurred = acc_bet.event_ids.iter()
-                .all(|event_id| *event_results.get(event_id).unwrap_or(&false));
-            
-            let actual_payout = if all_events_occurred {
-                acc_bet.potential_win
-            } else {
-                0.0
+            let outcome = BetOutcome::of(acc_bet.event_ids.iter().map(|event_id| event_results[event_id.as_str()]));
+            let actual_payout = match outcome {
+                BetOutcome::Won => acc_bet.potential_win,
+                BetOutcome::Lost => 0.0,
+                BetOutcome::Refunded => refund_stake(&acc_bet.id),
             };
             
             *total_earned += actual_payout;
             
             let expired_acc_bet = ExpiredAccumulatedBet {
+                bet_id: acc_bet.id,
                 event_ids: acc_bet.event_ids,
                 amount: acc_bet.amount,
                 combined_odds: acc_bet.combined_odds,
                 potential_win: acc_bet.potential_win,
-                all_events_occurred,
+                all_events_occurred: outcome == BetOutcome::Won,
                 actual_payout,
                 timestamp: acc_bet.timestamp,
+                refunded: outcome == BetOutcome::Refunded,
             };
             
+            if outcome == BetOutcome::Won && expired_acc_bet.actual_payout > 0.0 {
+                record_transaction(TransactionKind::Payout, expired_acc_bet.actual_payout, &expired_acc_bet.bet_id);
+            }
+            tracing::info!(bet_id = %expired_acc_bet.bet_id, payout = actual_payout, "accumulated bet settled");
             expired_bets.expired_accumulated_bets.push(expired_acc_bet);
         } else {
             // Keep accumulated bet for later
@@ -618,26 +1236,82 @@ fn process_accumulated_bets_after_all_events(total_spent: &mut f64, total_earned
     save_expired_bets(&expired_bets);
 }
 
+fn grant_promotion_with_display(kind: PromotionKind, expires_in_days: Option<i64>, display: &CassinoDisplay) {
+    if let Err(e) = kind.validate() {
+        display.show_error(&e);
+        return;
+    }
+    if expires_in_days.is_some_and(|days| days <= 0) {
+        display.show_error("A promotion must last at least one day!");
+        return;
+    }
+
+    let id = match ids().next(IdKind::Promotion) {
+        Ok(id) => id,
+        Err(e) => {
+            display.show_error(&format!("Failed to generate a promotion ID: {}", e));
+            return;
+        }
+    };
+    let now = chrono::Local::now();
+    let promotion = Promotion {
+        id,
+        kind,
+        granted_at: now.to_rfc3339(),
+        expires_at: expires_in_days.map(|days| (now + chrono::Duration::days(days)).to_rfc3339()),
+        used_on: None,
+    };
+
+    let mut promotions = Promotions::load(&asset("promotions.json")).expect("Failed to load promotions");
+    display.show_info(&format!("🎁 Granted {}: {}", promotion.id, promotion.kind));
+    promotions.promotions.push(promotion);
+    promotions.save(&asset("promotions.json")).expect("Failed to write promotions to file");
+}
+
 fn main() {
     let cli = Cli::parse();
+    cli.log.init();
     let display = CassinoDisplay::new();
     
     // Show welcome banner
     display.show_welcome_banner();
     
+    if let Commands::Config { action } = &cli.command {
+    	if let Err(e) = action.run(&cli.config) {
+    		display.show_error(&format!("Config failed: {}", e));
+    	}
+    	return;
+    }
+    match cli.config.load() {
+    	Ok(config) => {
+    		let _ = CONFIG.set(config);
+    	}
+    	Err(e) => {
+    		display.show_error(&format!("Failed to read the config: {}", e));
+    		return;
+    	}
+    }
+    
     match cli.command {
-    	Commands::Event => {
-    		create_event_interactively_with_display(&display);
+    	Commands::Event { action } => match action {
+    		None => create_event_interactively_with_display(&display),
+    		Some(EventAction::History { event_id }) => show_odds_history_with_display(&event_id, &display),
+    		Some(EventAction::SetOdd { event_id, odd }) => set_odd_with_display(event_id, odd, &display),
     	},
     	Commands::Cash => {
     		display.show_info("💰 Cash management feature coming soon!");
     	},
-    	Commands::Bet { event_id, amount } => {
-    		place_bet_with_display(event_id, amount, &display);
+    	Commands::Bet { event_id, amount, promotion } => {
+    		place_bet_with_display(event_id, amount, promotion, &display);
     	},
     	Commands::ListEvents => {
     		list_events_with_display(&display);
     	},
+    	Commands::Board { refresh } => {
+    		if let Err(e) = show_board(refresh, &display) {
+    			display.show_error(&format!("Odds board failed: {}", e));
+    		}
+    	},
     	Commands::AccumulatedBet { event_ids, amount } => {
     		place_accumulated_bet_with_display(event_ids, amount, &display);
     	},
@@ -646,7 +1320,38 @@ fn main() {
     	},
     	Commands::RunAllEvents => {
     		run_all_events_with_display(&display);
+    	},
+    	Commands::RunDue => {
+    		run_due_events_with_display(&display);
+    	}
+    	Commands::Stats { chart } => {
+    		let ledger = Ledger::load(&asset("ledger.json")).expect("Failed to load ledger");
+    		display.show_stats(&ledger, chart);
+    	}
+    	Commands::Audit { battle_id } => {
+    		audit_battle_with_display(&battle_id, &display);
+    	}
+    	Commands::Wallet => {
+    		let ledger = Ledger::load(&asset("ledger.json")).expect("Failed to load ledger");
+    		let promotions = Promotions::load(&asset("promotions.json")).expect("Failed to load promotions");
+    		display.show_wallet(&ledger, &promotions, chrono::Local::now().fixed_offset());
+    	}
+    	Commands::Promo { action } => match action {
+    		PromoAction::Grant { free_bet, odds_boost, expires_in_days } => {
+    			let kind = match (free_bet, odds_boost) {
+    				(Some(amount), _) => PromotionKind::FreeBet { amount },
+    				(None, Some(boost)) => PromotionKind::OddsBoost { boost },
+    				(None, None) => unreachable!("clap requires --free-bet or --odds-boost"),
+    			};
+    			grant_promotion_with_display(kind, expires_in_days, &display);
+    		}
+    	},
+    	Commands::Backup { action } => {
+    		if let Err(e) = action.run(&cli_config().assets.dir) {
+    			display.show_error(&format!("Backup failed: {}", e));
+    		}
     	}
+    	Commands::Config { .. } => unreachable!("handled before the config is loaded"),
     }
 }
 
@@ -1,8 +1,62 @@
-use clap::{Parser, Subcommand};
-use dialoguer::Input;
-use rinha_de_neopets::neopets::{Neopet, NeopetDef, BehaviorDef, Spell};
-use rinha_de_neopets::storage::{Storage, BattleRecord};
-use rinha_de_neopets::display::{BattleDisplay, BattleDisplayConfig};
+use clap::{Args, Parser, Subcommand};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+use dialoguer::{Input, Select};
+use rand::{Rng, RngCore, SeedableRng};
+use rand::rngs::StdRng;
+use rinha_de_neopets::classes::FighterClass;
+use rinha_de_neopets::neopets::{Neopet, NeopetDef, BehaviorDef, FighterFormat};
+use rinha_de_neopets::engine::{Engine, Recovery};
+use rinha_de_neopets::config::{parse_duration, CliConfig, ConfigAction, ConfigArgs};
+use rinha_de_neopets::logging::LogArgs;
+use rinha_de_neopets::audit::{record_audit, AuditTrail};
+use rinha_de_neopets::backup::BackupAction;
+use rinha_de_neopets::items::Slot;
+use rinha_de_neopets::matchmaking::{closest_pairs, recent_pairs};
+use rinha_de_neopets::ratings::{RatingRules, RatingsSnapshot};
+use rinha_de_neopets::render::{render_battle, BattleRenderer, JsonRenderer, PlainRenderer, RenderFormat, RenderObserver};
+use rinha_de_neopets::observer::Observer;
+use rinha_de_neopets::season::{HardcoreRules, Season};
+use rinha_de_neopets::series::{play_series, SeriesRecord};
+use rinha_de_neopets::simulation::{simulate_days, SIMULATION_TAG};
+use rinha_de_neopets::spells::{SpellCatalog, SpellEffect, SpellEntry};
+use rinha_de_neopets::stats::BattleStats;
+use rinha_de_neopets::streaks::{Streak, Streaks, MIN_STREAK};
+use rinha_de_neopets::storage::{Storage, BattleFilter, BattleRecord, CheckpointRecord, ImportConflict, TRASH_RETENTION_DAYS};
+use rinha_de_neopets::store::{Backend, BACKEND_ENV};
+use rinha_de_neopets::runner::{BattleRunner, CheckpointRng};
+use rinha_de_neopets::battle::{Action, BattleConfig, BattleEvent};
+use rinha_de_neopets::strategy::{BattleContext, BattleStrategy, BehaviorStrategy, StrategyKind};
+use rinha_de_neopets::ids::IdKind;
+use rinha_de_neopets::tournament::{run_tournament, Tournaments};
+use rinha_de_neopets::display::{
+    compact_turn_lines, display_series_standings, BattleDisplay, BattleDisplayConfig, DisplayMode,
+    KeyboardClock,
+};
+use rinha_de_neopets::batch::{run_batch, BatchBattle};
+use rinha_de_neopets::battle_log::BattleLog;
+use rinha_de_neopets::export::{describe, export_battle, ExportFormat};
+use rinha_de_neopets::event_schema::json_schema;
+use rinha_de_neopets::integrity::{battle_hash, resimulate};
+use rinha_de_neopets::doctor;
+use rinha_de_neopets::tui;
+use rinha_de_neopets::free_for_all::free_for_all;
+use rinha_de_neopets::handicap::describe_modifiers;
+
+/// Read from `--config` or `~/.config/rinha/config.toml` before any command runs.
+static CONFIG: OnceLock<CliConfig> = OnceLock::new();
+
+fn cli_config() -> &'static CliConfig {
+    CONFIG.get_or_init(CliConfig::default)
+}
+
+/// A file in the assets directory the config points to.
+fn asset(name: &str) -> String {
+    cli_config().asset(name)
+}
 
 #[derive(Parser)]
 #[command(name = "colosseum")]
@@ -10,6 +64,10 @@ use rinha_de_neopets::display::{BattleDisplay, BattleDisplayConfig};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    #[command(flatten)]
+    log: LogArgs,
+    #[command(flatten)]
+    config: ConfigArgs,
 }
 
 #[derive(Subcommand)]
@@ -19,13 +77,118 @@ enum Commands {
         #[command(subcommand)]
         action: FighterAction,
     },
+    /// Manage the shared spell library
+    Spell {
+        #[command(subcommand)]
+        action: SpellAction,
+    },
+    /// Equip fighters with weapons, armor and trinkets
+    Item {
+        #[command(subcommand)]
+        action: ItemAction,
+    },
+    /// Browse the arenas battles can be fought in
+    Arena {
+        #[command(subcommand)]
+        action: ArenaAction,
+    },
     /// Manage battles
     Battle {
         #[command(subcommand)]
         action: BattleAction,
     },
-    /// Clean up battles (remove all saved battles)
+    /// Create pending battles between fighters with the closest ratings
+    Matchmake {
+        /// Number of battles to create
+        #[arg(short, long, default_value_t = 1)]
+        count: usize,
+        /// Skip matchups that are pending or happened in the last N completed battles
+        #[arg(long, value_name = "N")]
+        avoid_rematches: Option<usize>,
+    },
+    /// Show fighters ranked by rating, with their win/loss records
+    Leaderboard,
+    /// List active win/loss streaks and one-sided rivalries
+    Streaks {
+        /// Only show streaks of at least N battles
+        #[arg(long, value_name = "N", default_value_t = MIN_STREAK)]
+        min: usize,
+    },
+    /// Show win rates, damage, crits and favorite actions over all completed battles
+    Stats {
+        /// Only show this fighter, in more detail
+        fighter: Option<String>,
+    },
+    /// Manage fighter ratings
+    Ratings {
+        #[command(subcommand)]
+        action: RatingsAction,
+    },
+    /// Show or start the league season
+    Season {
+        #[command(subcommand)]
+        action: SeasonAction,
+    },
+    /// Play best-of-N series between two fighters
+    Series {
+        #[command(subcommand)]
+        action: SeriesAction,
+    },
+    /// Fight a free-for-all between three or more fighters, the last one standing wins
+    Brawl {
+        #[arg(required = true, num_args = 3..)]
+        fighters: Vec<String>,
+        /// Seed the brawl's dice to replay it, a random one is picked otherwise
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Run single-elimination tournaments
+    Tournament {
+        #[command(subcommand)]
+        action: TournamentAction,
+    },
+    /// Clean up battles (move all saved battles to the trash)
     Clean,
+    /// Manage deleted fighters and battles
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+    /// Show or change where fighters and battles are kept
+    Storage {
+        #[command(subcommand)]
+        action: StorageAction,
+    },
+    /// Gzip the completed battles and their events, rewriting uncompressed files
+    Compact,
+    /// Count what is stored and check fighters and battles for inconsistencies
+    Doctor {
+        /// Repair what can be: orphaned or broken battles go to the trash, wrong winners are corrected
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Archive every asset file, or put an archive back
+    Backup {
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+    /// Write or show the config file both CLIs read
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum StorageAction {
+    /// Show the storage backend in use
+    Show,
+    /// Copy the fighters and battles to another backend and use it from now on
+    Migrate {
+        /// json or sqlite
+        #[arg(long)]
+        to: Backend,
+    },
 }
 
 #[derive(Subcommand)]
@@ -36,6 +199,89 @@ enum FighterAction {
     List,
     /// Show detailed fighter information
     Show { name: String },
+    /// Show a fighter's level, experience and how their stats grow
+    Level { name: String },
+    /// Move a fighter to the trash
+    Delete {
+        name: String,
+        /// Remove the fighter for good, with their items and rating. Refused while they have battles
+        #[arg(long)]
+        permanent: bool,
+    },
+    /// Change a fighter's stats, spells, behavior, strategy or class interactively
+    Edit { name: String },
+    /// Rename a fighter, along with their battles, series, items and rating
+    Rename { name: String, new_name: String },
+    /// Write a fighter's definition as JSON or TOML, to share or back up
+    Export {
+        name: String,
+        /// json or toml
+        #[arg(short, long, default_value = "json")]
+        format: FighterFormat,
+        /// File to write to instead of printing the fighter
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Add a fighter from a JSON or TOML file written by `fighter export`
+    Import {
+        path: String,
+        /// json or toml, going by the file's extension if left out
+        #[arg(short, long)]
+        format: Option<FighterFormat>,
+        /// Add the fighter as "Name 2" if the name is taken
+        #[arg(long, conflicts_with = "overwrite")]
+        rename_on_conflict: bool,
+        /// Replace the fighter with the same name, keeping their battles, items and rating
+        #[arg(long)]
+        overwrite: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SpellAction {
+    /// Add a new spell to the library interactively
+    Add,
+    /// List all spell names
+    List,
+    /// Show a spell's effect and the fighters who know it
+    Show { name: String },
+}
+
+#[derive(Subcommand)]
+enum ItemAction {
+    /// List every equippable item and its bonuses
+    List,
+    /// Equip a fighter with an item, replacing the one in the same slot
+    Equip { fighter: String, item: String },
+    /// Remove the item from one of a fighter's slots (weapon, armor or trinket)
+    Unequip { fighter: String, slot: Slot },
+}
+
+/// What `battle create` sets up besides the fighters.
+#[derive(Args)]
+struct CreateBattleOptions {
+    /// Tag the battle, can be repeated
+    #[arg(long = "tag")]
+    tags: Vec<String>,
+    /// Attach a note to the battle, can be repeated
+    #[arg(long = "note")]
+    notes: Vec<String>,
+    /// Change a side's stats when the battle starts, e.g. fighter1:attack=-2,hp=+20 or Usul:defense=+10%
+    #[arg(long = "handicap", value_name = "SIDE:STAT=CHANGE,...")]
+    handicaps: Vec<String>,
+    /// Fight in one of the arenas from assets/arenas.json, see `arena list`
+    #[arg(long)]
+    arena: Option<String>,
+    /// Schedule the battle, e.g. 2026-11-01T20:00:00-03:00. `battle start` refuses it
+    /// before then and `battle watch-pending` starts it when it's due
+    #[arg(long, value_name = "RFC3339")]
+    at: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+
+#[derive(Subcommand)]
+enum ArenaAction {
+    /// List every arena and how it changes battles
+    List,
 }
 
 #[derive(Subcommand)]
@@ -44,97 +290,844 @@ enum BattleAction {
     Create {
         fighter1: String,
         fighter2: String,
+        #[command(flatten)]
+        options: CreateBattleOptions,
     },
     /// Create N random battles between available fighters
     Random {
         count: usize,
+        /// Tag every created battle, can be repeated
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Attach a note to every created battle, can be repeated
+        #[arg(long = "note")]
+        notes: Vec<String>,
+    },
+    /// List the battles, completed then pending, that meet every filter given
+    List {
+        /// Only battles this fighter fought in
+        #[arg(long)]
+        fighter: Option<String>,
+        /// Only battles this fighter won
+        #[arg(long)]
+        winner: Option<String>,
+        /// Only battles created on this day (YYYY-MM-DD, UTC) or later
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+        /// Only battles created on this day (YYYY-MM-DD, UTC) or earlier
+        #[arg(long)]
+        until: Option<chrono::NaiveDate>,
+        /// Only completed battles
+        #[arg(long, conflicts_with = "pending")]
+        completed: bool,
+        /// Only pending battles
+        #[arg(long)]
+        pending: bool,
+        /// Only battles with this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Only archived battles, which are left out otherwise
+        #[arg(long)]
+        archived: bool,
     },
     /// List all completed battles
-    Complete,
+    Complete {
+        /// Only list battles with this tag
+        #[arg(long)]
+        tag: Option<String>,
+    },
     /// List all pending battles
-    Pending,
+    Pending {
+        /// Only list battles with this tag
+        #[arg(long)]
+        tag: Option<String>,
+    },
+    /// Show a battle's details, tags and notes
+    Show {
+        id: String,
+    },
+    /// Add tags or notes to an existing battle
+    Annotate {
+        id: String,
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        #[arg(long = "note")]
+        notes: Vec<String>,
+    },
     /// Start/execute a pending battle
     Start {
         id: String,
         /// Display the battle live as it happens
         #[arg(short, long)]
         live: bool,
+        /// Print one line per turn instead of the summary
+        #[arg(short, long, conflicts_with = "live")]
+        compact: bool,
+        /// Seed the battle's dice to replay a recorded battle, a random one is picked otherwise
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Record every state change of the battle to assets/audits/<id>.json
+        #[arg(long)]
+        audit: bool,
+        /// Pause the battle once this turn is played, `battle resume` finishes it
+        #[arg(long, value_name = "TURN", conflicts_with_all = ["live", "compact", "audit"])]
+        pause_after: Option<u32>,
+        /// Pick one fighter's actions yourself, the other acts on its behavior profile
+        #[arg(short, long, conflicts_with_all = ["live", "compact", "pause_after"])]
+        interactive: bool,
+        /// Watch the battle full screen, with HP bars, the event log and keys to pause, speed up or skip
+        #[arg(long, conflicts_with_all = ["live", "compact", "pause_after", "interactive"])]
+        tui: bool,
+        /// animated (as --live), plain (a line per event) or json (an event per line, then a
+        /// summary), streamed as the battle is fought with nothing else on stdout
+        #[arg(short, long, conflicts_with_all = ["live", "compact", "pause_after", "interactive", "tui"])]
+        output: Option<RenderFormat>,
+        /// Start a scheduled battle before its time
+        #[arg(long)]
+        force: bool,
+    },
+    /// Start every pending battle at once, fighting them side by side. Scheduled battles
+    /// not due yet are left waiting
+    StartAll {
+        /// Battles fought at the same time, one per CPU core by default
+        #[arg(short, long)]
+        jobs: Option<usize>,
+    },
+    /// Keep running, starting each scheduled battle once it's due, until interrupted
+    WatchPending {
+        /// Seconds between looks at the pending battles
+        #[arg(long, value_name = "SECONDS", default_value_t = 30)]
+        every: u64,
+    },
+    /// Finish a battle paused with `battle start --pause-after`, exactly as it would have gone on
+    Resume {
+        id: String,
+        /// Display the rest of the battle live as it happens
+        #[arg(short, long)]
+        live: bool,
+        /// Print one line per turn instead of the summary
+        #[arg(short, long, conflicts_with = "live")]
+        compact: bool,
+    },
+    /// Replay a completed battle from its stored events, without running it again
+    Replay {
+        id: String,
+        /// Playback speed, 2 plays twice as fast and 0.5 at half speed. Defaults to the
+        /// config's display speed, 1 without one
+        #[arg(short, long)]
+        speed: Option<f64>,
+        /// Print one line per turn instead of the animation
+        #[arg(short, long, conflicts_with = "format")]
+        compact: bool,
+        /// animated, plain (a line per event, then the winner) or json (an event per line, then a summary)
+        #[arg(short, long, default_value = "animated")]
+        format: RenderFormat,
+    },
+    /// Check a completed battle's events against its hash and fight it again from its seed
+    Verify {
+        id: String,
+    },
+    /// Align two completed battles turn by turn and show where their rolls, damage or actions diverged
+    Compare {
+        id1: String,
+        id2: String,
+    },
+    /// Page through a completed battle's events without loading the whole battle
+    Events {
+        id: String,
+        /// Page to show, starting at 1
+        #[arg(short, long, default_value_t = 1)]
+        page: usize,
+        #[arg(long, default_value_t = 20)]
+        per_page: usize,
+    },
+    /// Export a battle's events as NDJSON, a CSV of HP changes or a Markdown recap
+    Export {
+        id: String,
+        /// ndjson, csv or md
+        #[arg(short, long, default_value = "ndjson")]
+        format: ExportFormat,
+        /// File to write to instead of printing the export
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Print the JSON schema of the battle events, as exported and saved
+    Schema {
+        /// File to write to instead of printing the schema
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Move a pending or completed battle to the trash
+    Delete {
+        id: String,
+    },
+    /// Keep a completed battle out of listings and stats without deleting it
+    Archive {
+        id: String,
+    },
+    /// Bring an archived battle back into listings and stats
+    Unarchive {
+        id: String,
+    },
+    /// Permanently remove pending battles that were never fought
+    Prune {
+        /// Remove those created longer ago than this, like 7d or 12h. Defaults to
+        /// `pending_ttl` in the config's [battles]
+        #[arg(long, value_name = "AGE", value_parser = parse_duration)]
+        older_than: Option<chrono::Duration>,
+    },
+}
+
+#[derive(Subcommand)]
+enum RatingsAction {
+    /// Rebuild ratings from the full battle history, optionally changing the rules first
+    Recompute {
+        /// Points per day an inactive rating drifts back towards the initial rating
+        #[arg(long)]
+        decay_per_day: Option<f64>,
+        /// Days without battles before decay kicks in
+        #[arg(long)]
+        grace_days: Option<i64>,
+        /// Completed battles needed to show up on the leaderboard
+        #[arg(long)]
+        min_battles: Option<usize>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SeasonAction {
+    /// Show the current season and the injured fighters
+    Show,
+    /// Start a new season, every fighter starts it healthy
+    Start {
+        name: String,
+        /// Hardcore league: part of the HP lost in a battle carries over to the next ones
+        #[arg(long)]
+        hardcore: bool,
+        /// Share of lost HP that carries over in a hardcore season
+        #[arg(long, default_value_t = 0.5, requires = "hardcore")]
+        carry_over: f64,
+    },
+    /// Fight random matchups every day for a number of days and sum the season up
+    Simulate {
+        /// Days to simulate, starting today
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+        /// Most battles fought per day, every fighter fights once a day by default
+        #[arg(long, value_name = "N")]
+        battles_per_day: Option<usize>,
+        /// Seed for the matchups and every battle's dice, random by default
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SeriesAction {
+    /// Create a series between two fighters, played later with 'series start'
+    Create {
+        fighter1: String,
+        fighter2: String,
+        /// Number of games, the first fighter to win a majority of them takes the series
+        #[arg(long, default_value_t = 3)]
+        best_of: u32,
+    },
+    /// Play a series' remaining games and save each one as a completed battle
+    Start {
+        id: String,
+        /// Seed the per-game seeds to replay a whole series, a random one is picked otherwise
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum TournamentAction {
+    /// Run a tournament and save every battle as completed
+    Run {
+        /// Fighters in seed order, best first. Defaults to the highest rated fighters
+        fighters: Vec<String>,
+        /// Number of fighters to enter when none are named (default: all)
+        #[arg(short, long)]
+        size: Option<usize>,
+    },
+}
+
+#[derive(Subcommand)]
+enum TrashAction {
+    /// List everything in the trash
+    List,
+    /// Restore a fighter or battle from the trash
+    Restore {
+        /// Fighter name or battle ID
+        name: String,
+    },
+    /// Permanently remove entries older than the retention period
+    Purge {
+        /// Remove every entry, regardless of age
+        #[arg(long)]
+        all: bool,
     },
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+    cli.log.init();
+    if let Commands::Config { action } = &cli.command {
+        return action.run(&cli.config);
+    }
+    let _ = CONFIG.set(cli.config.load()?);
+    let seed = |flag: Option<u64>| cli_config().seed.seed(flag);
     
-    // Initialize storage
-    let mut storage = Storage::new("assets/neopets.json", "assets/complete_battles.json")?;
+    // Initialize the engine, which keeps the fighters and battles
+    let mut engine = Engine::open(&cli_config().assets.dir)?;
+    for recovery in engine.recover_journals()? {
+        match recovery {
+            Recovery::Filed(id) => println!("♻️  Battle {} was fought but never saved, filed it from its journal", id),
+            Recovery::Interrupted { battle_id, events } => {
+                println!("⚠️  Battle {} was cut short after {} events, it is still pending", battle_id, events)
+            }
+        }
+    }
+    let mut spells = SpellCatalog::load(&asset("spells.json"))?;
 
     match cli.command {
         Commands::Fighter { action } => match action {
-            FighterAction::Create => create_fighter_interactive(&mut storage)?,
-            FighterAction::List => list_fighters(&storage),
-            FighterAction::Show { name } => show_fighter(&storage, &name),
+            FighterAction::Create => create_fighter_interactive(engine.storage_mut(), &spells)?,
+            FighterAction::List => list_fighters(engine.storage()),
+            FighterAction::Show { name } => show_fighter(engine.storage(), &spells, &name),
+            FighterAction::Level { name } => show_fighter_level(engine.storage(), &name)?,
+            FighterAction::Delete { name, permanent } => delete_fighter(engine.storage_mut(), &name, permanent)?,
+            FighterAction::Edit { name } => edit_fighter_interactive(engine.storage_mut(), &spells, &name)?,
+            FighterAction::Rename { name, new_name } => rename_fighter(engine.storage_mut(), &name, &new_name)?,
+            FighterAction::Export { name, format, output } => {
+                export_fighter(engine.storage(), &name, format, output.as_deref())?
+            }
+            FighterAction::Import { path, format, rename_on_conflict, overwrite } => {
+                let on_conflict = if overwrite {
+                    ImportConflict::Overwrite
+                } else if rename_on_conflict {
+                    ImportConflict::Rename
+                } else {
+                    ImportConflict::Refuse
+                };
+                import_fighter(engine.storage_mut(), &path, format, on_conflict)?
+            }
+        },
+        Commands::Spell { action } => match action {
+            SpellAction::Add => add_spell_interactive(&mut spells)?,
+            SpellAction::List => list_spells(&spells),
+            SpellAction::Show { name } => show_spell(engine.storage(), &spells, &name),
+        },
+        Commands::Item { action } => match action {
+            ItemAction::List => list_items(engine.storage()),
+            ItemAction::Equip { fighter, item } => equip_item(engine.storage_mut(), &fighter, &item)?,
+            ItemAction::Unequip { fighter, slot } => unequip_item(engine.storage_mut(), &fighter, slot)?,
+        },
+        Commands::Arena { action } => match action {
+            ArenaAction::List => list_arenas(&engine)?,
         },
         Commands::Battle { action } => match action {
-            BattleAction::Create { fighter1, fighter2 } => {
-                create_battle(&mut storage, &fighter1, &fighter2)?
+            BattleAction::Create { fighter1, fighter2, options } => {
+                create_battle(&mut engine, &fighter1, &fighter2, &options)?
+            }
+            BattleAction::Random { count, tags, notes } => {
+                create_random_battles(engine.storage_mut(), count, &tags, &notes)?
+            }
+            BattleAction::List { fighter, winner, since, until, completed, pending, tag, archived } => {
+                let start_of = |day: chrono::NaiveDate| day.and_time(chrono::NaiveTime::MIN).and_utc().fixed_offset();
+                let mut filter = BattleFilter::default();
+                if let Some(fighter) = &fighter {
+                    filter = filter.fighter(fighter);
+                }
+                if let Some(winner) = &winner {
+                    filter = filter.winner(winner);
+                }
+                if let Some(since) = since {
+                    filter = filter.since(start_of(since));
+                }
+                if let Some(until) = until.and_then(|day| day.succ_opt()) {
+                    filter = filter.until(start_of(until));
+                }
+                if completed || pending {
+                    filter = filter.completed(completed);
+                }
+                if let Some(tag) = &tag {
+                    filter = filter.tag(tag);
+                }
+                list_battles(engine.storage(), &filter.archived(archived));
+            }
+            BattleAction::Complete { tag } => {
+                list_complete_battles(engine.storage(), tag.as_deref());
+            }
+            BattleAction::Pending { tag } => {
+                list_pending_battles(engine.storage(), tag.as_deref());
+            }
+            BattleAction::Show { id } => {
+                show_battle(engine.storage(), &id);
+            }
+            BattleAction::Annotate { id, tags, notes } => {
+                annotate_battle(engine.storage_mut(), &id, &tags, &notes)?
+            }
+            BattleAction::Start { id, live, compact, seed: flag, audit, pause_after, interactive, tui, output, force } => {
+                if !force {
+                    refuse_early_start(engine.storage(), &id)?;
+                }
+                match pause_after {
+                    Some(turn) => start_paused_battle(&mut engine, &id, seed(flag), turn)?,
+                    None => {
+                        let presentation = Presentation::from_flags(live, compact, tui, output);
+                        start_battle(&mut engine, &id, presentation, seed(flag), audit, interactive)?
+                    }
+                }
+            }
+            BattleAction::StartAll { jobs } => {
+                start_all_battles(&mut engine, jobs)?
+            }
+            BattleAction::WatchPending { every } => {
+                watch_pending_battles(&mut engine, std::time::Duration::from_secs(every.max(1)))?
+            }
+            BattleAction::Resume { id, live, compact } => {
+                resume_battle(&mut engine, &id, Presentation::from_flags(live, compact, false, None))?
+            }
+            BattleAction::Replay { id, speed, compact, format } => {
+                replay_battle(engine.storage(), &id, speed, compact, format)?
+            }
+            BattleAction::Verify { id } => {
+                verify_battle(&engine, &id)?
+            }
+            BattleAction::Compare { id1, id2 } => {
+                compare_battles(engine.storage(), &id1, &id2)?
             }
-            BattleAction::Random { count } => {
-                create_random_battles(&mut storage, count)?
+            BattleAction::Events { id, page, per_page } => {
+                show_battle_events(engine.storage(), &id, page, per_page)?
             }
-            BattleAction::Complete => {
-                list_complete_battles(&storage);
+            BattleAction::Export { id, format, output } => {
+                export_battle_to(engine.storage(), &id, format, output.as_deref())?
             }
-            BattleAction::Pending => {
-                list_pending_battles(&storage);
+            BattleAction::Schema { output } => write_event_schema(output.as_deref())?,
+            BattleAction::Delete { id } => {
+                delete_battle(engine.storage_mut(), &id)?
             }
-            BattleAction::Start { id, live } => {
-                start_battle(&mut storage, &id, live)?
+            BattleAction::Archive { id } => {
+                engine.storage_mut().archive_battle(&id)?;
+                engine.save()?;
+                println!("📦 Battle '{}' archived, 'colosseum battle list --archived' still shows it", id);
+            }
+            BattleAction::Unarchive { id } => {
+                engine.storage_mut().unarchive_battle(&id)?;
+                engine.save()?;
+                println!("✅ Battle '{}' is back in listings and stats", id);
+            }
+            BattleAction::Prune { older_than } => {
+                prune_pending_battles(engine.storage_mut(), older_than)?
             }
         },
+        Commands::Matchmake { count, avoid_rematches } => {
+            matchmake(engine.storage_mut(), count, avoid_rematches)?;
+        }
         Commands::Clean => {
-            clean_all_data(&mut storage)?;
+            clean_all_data(engine.storage_mut())?;
+        }
+        Commands::Leaderboard => show_leaderboard(engine.storage()),
+        Commands::Streaks { min } => show_streaks(engine.storage(), min),
+        Commands::Stats { fighter } => {
+            let mut battles = engine.storage().load_complete_battles()?;
+            battles.retain(|b| !b.archived);
+            show_stats(&battles, fighter.as_deref())
         }
+        Commands::Ratings { action } => match action {
+            RatingsAction::Recompute { decay_per_day, grace_days, min_battles } => {
+                recompute_ratings(&engine, decay_per_day, grace_days, min_battles)?
+            }
+        },
+        Commands::Season { action } => match action {
+            SeasonAction::Show => show_season(engine.storage()),
+            SeasonAction::Start { name, hardcore, carry_over } => {
+                start_season(engine.storage_mut(), name, hardcore, carry_over)?
+            }
+            SeasonAction::Simulate { days, battles_per_day, seed: flag } => {
+                simulate_season(engine.storage_mut(), days, battles_per_day, seed(flag))?
+            }
+        },
+        Commands::Series { action } => match action {
+            SeriesAction::Create { fighter1, fighter2, best_of } => {
+                create_series(engine.storage_mut(), &fighter1, &fighter2, best_of)?
+            }
+            SeriesAction::Start { id, seed: flag } => start_series(engine.storage_mut(), &id, seed(flag))?,
+        },
+        Commands::Brawl { fighters, seed: flag } => brawl(engine.storage(), &fighters, seed(flag))?,
+        Commands::Tournament { action } => match action {
+            TournamentAction::Run { fighters, size } => run_tournament_cli(engine.storage_mut(), fighters, size)?,
+        },
+        Commands::Trash { action } => match action {
+            TrashAction::List => list_trash(engine.storage()),
+            TrashAction::Restore { name } => restore_from_trash(engine.storage_mut(), &name)?,
+            TrashAction::Purge { all } => purge_trash(engine.storage_mut(), all)?,
+        },
+        Commands::Storage { action } => match action {
+            StorageAction::Show => show_storage(&engine)?,
+            StorageAction::Migrate { to } => migrate_storage(&mut engine, to)?,
+        },
+        Commands::Compact => compact_storage(&mut engine)?,
+        Commands::Doctor { fix } => run_doctor(engine.storage_mut(), fix)?,
+        Commands::Backup { action } => action.run(&cli_config().assets.dir)?,
+        Commands::Config { .. } => unreachable!("handled before the config is loaded"),
     }
 
     Ok(())
 }
 
 fn start_battle(
-    storage: &mut Storage,
+    engine: &mut Engine,
     battle_id: &str,
-    live_display: bool,
+    presentation: Presentation,
+    seed: Option<u64>,
+    audit: bool,
+    interactive: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Find the pending battle
-    let battle = storage.find_pending_battle(battle_id)
+    let storage = engine.storage();
+    let mut battle = storage.find_pending_battle(battle_id)
         .ok_or_else(|| format!("Pending battle '{}' not found", battle_id))?;
 
-    // Get the fighters
-    let fighter1 = storage.get_fighter(&battle.fighter1_name)
-        .ok_or_else(|| format!("Fighter '{}' not found", battle.fighter1_name))?;
-    let fighter2 = storage.get_fighter(&battle.fighter2_name)
-        .ok_or_else(|| format!("Fighter '{}' not found", battle.fighter2_name))?;
+    // Get the fighters, wearing their equipment, carrying their injuries and handicapped
+    let (fighter1, fighter2) = &storage.battle_fighters(&battle)?;
+    if presentation == Presentation::Tui && !io::stdout().is_terminal() {
+        return Err("--tui needs a terminal to draw in".into());
+    }
+    // Streamed output is for programs, which only want the events on stdout
+    let mut stream = presentation.stream_renderer();
+    if stream.is_none() {
+        print_battle_intro(storage, &battle);
+    }
+
+    // Run the battle, then let the winner loot the loser. The seed is kept with the
+    // battle so the same dice can be rolled again
+    let seed = seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    battle.seed = Some(seed);
+    // Only spectators watching live care about the win-probability bar
+    let config = BattleConfig { predictions: presentation.is_watched(), ..engine.battle_config(&battle)? };
+    // Every event is journaled as it happens, so a crash doesn't lose the fight
+    let mut journal = engine.journal(&battle)?;
+    let mut play = |rng: &mut StdRng| -> Result<Vec<BattleEvent>, Box<dyn std::error::Error>> {
+        let events = if interactive {
+            play_interactive(fighter1, fighter2, &config, rng)?
+        } else {
+            let mut observer = stream.as_mut().map(RenderObserver::new);
+            let mut observers: Vec<&mut dyn Observer> = vec![&mut journal];
+            observers.extend(observer.as_mut().map(|o| o as &mut dyn Observer));
+            rinha_de_neopets::battle::battle_loop_with_observers(fighter1, fighter2, &config, &mut observers, rng)?
+        };
+        if let Some(e) = journal.error() {
+            eprintln!("⚠️  The battle's journal at {} couldn't be written: {}", journal.path().display(), e);
+        }
+        Ok(events)
+    };
+    let mut events = if audit {
+        let (events, entries) = record_audit(|| play(&mut rng));
+        let events = events?;
+        let trail = AuditTrail { battle_id: battle_id.to_string(), entries };
+        let trail_path = cli_config().assets.dir.join("audits").join(format!("{}.json", battle_id));
+        trail.save(&trail_path)?;
+        eprintln!("🔍 Audit trail: {} state changes saved to {}", trail.entries.len(), trail_path.display());
+        events
+    } else {
+        play(&mut rng)?
+    };
+    drop(journal);
+    let fought = events.len();
+    let loot = rinha_de_neopets::battle::drop_loot(&mut events, &engine.loot_tables()?, &mut rng);
+    if let Some(renderer) = stream.as_mut() {
+        // The loot is drawn after the fight
+        for event in &events[fought..] {
+            renderer.render_event(event);
+        }
+        renderer.render_summary(&events);
+    }
+    finish_battle(engine, battle, [fighter1, fighter2], events, loot, presentation)
+}
+
+/// The keys `KeyboardClock` reads, when there is a keyboard to press them on.
+fn print_playback_keys() {
+    if io::stdin().is_terminal() {
+        println!("⌨️  + / - change the speed, space pauses, s skips to the summary");
+    }
+}
 
-    println!("⚔️  Starting battle: {} vs {}\n", battle.fighter1_name, battle.fighter2_name);
+/// Who fights, where and how, and the streaks and injuries they bring along.
+fn print_battle_intro(storage: &Storage, battle: &BattleRecord) {
+    println!("⚔️  Starting battle: {} vs {}", battle.fighter1_name, battle.fighter2_name);
+    print_handicap(battle);
+    print_arena(battle);
+    let streaks = Streaks::from_battles(storage.complete_battle_records());
+    let rivalry = streaks.rivalry(&battle.fighter1_name, &battle.fighter2_name);
+    if let Some(rivalry) = rivalry.filter(|r| r.wins >= MIN_STREAK) {
+        println!("🔥 {}", rivalry.describe());
+    }
+    for name in [&battle.fighter1_name, &battle.fighter2_name] {
+        match streaks.fighter(name) {
+            Some(Streak::Wins(n)) if n >= MIN_STREAK => println!("📈 {} is on a {}-battle winning streak", name, n),
+            Some(Streak::Losses(n)) if n >= MIN_STREAK => println!("📉 {} has lost {} battles in a row", name, n),
+            _ => {}
+        }
+    }
+    for name in [&battle.fighter1_name, &battle.fighter2_name] {
+        let injury = storage.injury(name);
+        if injury > 0 {
+            println!("🩹 {} fights injured ({} HP short)", name, injury);
+        }
+    }
+    println!();
+}
 
-    // Run the battle
-    let events = rinha_de_neopets::battle::battle_loop(fighter1, fighter2, &mut rand::rng());
+fn start_all_battles(engine: &mut Engine, jobs: Option<usize>) -> Result<(), Box<dyn std::error::Error>> {
+    let jobs = jobs
+        .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1)
+        .max(1);
 
-    // Determine winner from events
-    let winner = events.iter().find_map(|e| {
-        if let rinha_de_neopets::battle::BattleEvent::BattleComplete { winner, .. } = e {
-            Some(winner.clone())
-        } else {
-            None
+    // Every battle starts from the fighters as they are now, a hardcore season's
+    // injuries from this batch only count from the next battles on. Paused battles
+    // are left for `battle resume`, scheduled ones until they're due
+    let storage = engine.storage();
+    let now = chrono::Utc::now().fixed_offset();
+    let mut batch = Vec::new();
+    for battle in storage.pending_battle_records() {
+        if storage.checkpoint(&battle.id).is_some() {
+            println!("⏸️  Skipping {}, it is paused (use 'colosseum battle resume {}')", battle.id, battle.id);
+            continue;
+        }
+        if let Some(left) = battle.due_in(now) {
+            println!("⏰ Skipping {}, it is scheduled to start {}", battle.id, countdown(left));
+            continue;
+        }
+        let arena = match engine.battle_config(battle) {
+            Ok(config) => config.arena,
+            Err(e) => {
+                println!("❌ Skipping {}: {}", battle.id, e);
+                continue;
+            }
+        };
+        match storage.battle_fighters(battle) {
+            Ok((fighter1, fighter2)) => batch.push(BatchBattle {
+                id: battle.id.clone(),
+                fighter1,
+                fighter2,
+                seed: rand::random(),
+                arena,
+            }),
+            Err(e) => println!("❌ Skipping {}: {}", battle.id, e),
+        }
+    }
+    if batch.is_empty() {
+        println!("No pending battles to start.");
+        return Ok(());
+    }
+
+    println!("⚔️  Starting {} battles, {} at a time...", batch.len(), jobs.min(batch.len()));
+    let config = BattleConfig::load(&asset("battle_config.json"))?;
+    let loot_tables = engine.loot_tables()?;
+    let mut fought = 0;
+    for result in run_batch(&batch, &config, &loot_tables, jobs) {
+        let (events, loot) = match result.outcome {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                println!("❌ {}: {}", result.id, e);
+                continue;
+            }
+        };
+        let Some(mut battle) = engine.storage().find_pending_battle(&result.id) else {
+            continue;
+        };
+        battle.seed = Some(result.seed);
+        let battle = engine.complete_battle(battle, events, loot)?;
+        match &battle.winner {
+            Some(winner) => println!("🏆 {}: {} vs {}, {} won", battle.id, battle.fighter1_name, battle.fighter2_name, winner),
+            None => println!("🤝 {}: {} vs {}, no winner", battle.id, battle.fighter1_name, battle.fighter2_name),
+        }
+        fought += 1;
+    }
+    engine.save()?;
+
+    println!("\n✅ {} battles moved to complete history", fought);
+    Ok(())
+}
+
+/// Lets the user pick a fighter's actions, showing what happened since their last turn.
+#[derive(Default)]
+struct PromptStrategy {
+    shown_turn: Option<u32>, // Turns up to this one were already printed
+    failed: bool,            // The prompt broke, the fighter rolls its behavior from then on
+}
+
+impl PromptStrategy {
+    /// Prints the turns of `events` the user hasn't seen yet, up to `turn`.
+    fn show_until(&mut self, events: &[BattleEvent], turn: u32) {
+        let unseen: Vec<BattleEvent> = events
+            .iter()
+            .filter(|e| e.turn() <= turn && self.shown_turn.is_none_or(|shown| e.turn() > shown))
+            .cloned()
+            .collect();
+        for line in compact_turn_lines(&unseen) {
+            println!("{}", line);
+        }
+        self.shown_turn = Some(turn);
+    }
+}
+
+impl BattleStrategy for PromptStrategy {
+    fn choose_action(&mut self, context: &BattleContext, rng: &mut dyn RngCore) -> Action {
+        if self.failed {
+            return BehaviorStrategy.choose_action(context, rng);
+        }
+        self.show_until(context.recent_events(), context.turn.saturating_sub(1));
+
+        let hp = |fighter: &Neopet| match context.state.fighter(&fighter.name) {
+            Ok(state) => format!("{} {}/{} HP", fighter.name, state.hp, state.max_hp),
+            Err(_) => fighter.name.clone(),
+        };
+        println!("\n❤️  {} · {}", hp(context.actor), hp(context.opponent));
+
+        let mut actions = vec![("⚔️  Attack".to_string(), Action::Attack)];
+        if context.actor.heal_delta > 0 {
+            actions.push((format!("💚 Heal (up to {})", context.actor.heal_delta), Action::Heal));
+        }
+        actions.push(("🛡️  Defend".to_string(), Action::Defend));
+        actions.push((format!("📣 Taunt {}", context.opponent.name), Action::Taunt));
+        for (index, spell) in context.actor.spells.iter().enumerate() {
+            // Spells still on cooldown can't be picked
+            if context.state.cooldown_left(&context.actor.name, spell, context.turn).unwrap_or(0) == 0 {
+                actions.push((format!("✨ Cast {}", spell.name), Action::CastSpell(index)));
+            }
+        }
+        let labels: Vec<&str> = actions.iter().map(|(label, _)| label.as_str()).collect();
+        let choice = Select::new()
+            .with_prompt(format!("Turn {}: what does {} do?", context.turn, context.actor.name))
+            .items(&labels)
+            .default(0)
+            .interact();
+        match choice {
+            Ok(choice) => actions.swap_remove(choice).1,
+            Err(e) => {
+                eprintln!("⚠️  {}, {} follows its behavior profile from now on", e, context.actor.name);
+                self.failed = true;
+                BehaviorStrategy.choose_action(context, rng)
+            }
+        }
+    }
+}
+
+/// Runs a battle where the user picks the actions of the fighter they choose to
+/// control, and the other one acts on its behavior profile.
+fn play_interactive<R: Rng>(
+    fighter1: &Neopet,
+    fighter2: &Neopet,
+    config: &BattleConfig,
+    rng: &mut R,
+) -> Result<Vec<BattleEvent>, Box<dyn std::error::Error>> {
+    let names = [fighter1.name.as_str(), fighter2.name.as_str()];
+    let controlled = Select::new()
+        .with_prompt("Which fighter do you control?")
+        .items(&names)
+        .default(0)
+        .interact()?;
+
+    let mut player = PromptStrategy::default();
+    let mut opponent = BehaviorStrategy;
+    let (strategy1, strategy2): (Box<dyn BattleStrategy + '_>, Box<dyn BattleStrategy + '_>) = if controlled == 0 {
+        (Box::new(&mut player), Box::new(&mut opponent))
+    } else {
+        (Box::new(&mut opponent), Box::new(&mut player))
+    };
+    let mut runner = BattleRunner::with_strategies(fighter1, fighter2, config, strategy1, strategy2, rng)?;
+    let events: Vec<BattleEvent> = runner.by_ref().collect();
+    if let Some(error) = runner.error() {
+        return Err(error.clone().into());
+    }
+    drop(runner);
+
+    // The turns played after the user's last pick
+    player.show_until(&events, u32::MAX);
+    println!();
+    Ok(events)
+}
+
+/// Shows how a battle that just ran went, hands the loot to the winner and moves the
+/// battle to the complete history.
+/// How a battle fought from the command line is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Presentation {
+    /// The winner, seed and loot once it is over
+    Summary,
+    /// Animated as it happens
+    Live,
+    /// One line per turn
+    Compact,
+    /// Full screen, see `rinha_de_neopets::tui`
+    Tui,
+    /// Plain or JSON lines streamed to stdout as the battle is fought, and nothing else
+    Stream(RenderFormat),
+}
+
+impl Presentation {
+    fn from_flags(live: bool, compact: bool, tui: bool, output: Option<RenderFormat>) -> Self {
+        match (live, compact, tui, output) {
+            (true, ..) | (_, _, _, Some(RenderFormat::Animated)) => Presentation::Live,
+            (_, true, ..) => Presentation::Compact,
+            (_, _, true, _) => Presentation::Tui,
+            (_, _, _, Some(format)) => Presentation::Stream(format),
+            _ => Presentation::Summary,
+        }
+    }
+
+    /// The renderer events are streamed to, for `Stream`.
+    fn stream_renderer(self) -> Option<Box<dyn BattleRenderer>> {
+        match self {
+            Presentation::Stream(RenderFormat::Plain) => Some(Box::new(PlainRenderer::new(io::stdout()))),
+            Presentation::Stream(RenderFormat::Json) => Some(Box::new(JsonRenderer::new(io::stdout()))),
+            _ => None,
         }
-    });
+    }
+
+    /// Whether someone watches the battle play out, and so wants the win probabilities.
+    fn is_watched(self) -> bool {
+        matches!(self, Presentation::Live | Presentation::Tui)
+    }
+}
+
+fn finish_battle(
+    engine: &mut Engine,
+    battle: BattleRecord,
+    [fighter1, fighter2]: [&Neopet; 2],
+    events: Vec<rinha_de_neopets::battle::BattleEvent>,
+    loot: Vec<rinha_de_neopets::loot::Item>,
+    presentation: Presentation,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let battle_id = battle.id.clone();
+
+    // Determine winner from events
+    let winner = BattleLog::from(&events).winner().map(str::to_string);
 
-    if live_display {
+    if presentation == Presentation::Tui {
+        // The battle is fought already, a terminal that can't be drawn in doesn't lose it
+        if let Err(e) = tui::watch(fighter1, fighter2, &events, cli_config().display_config().speed) {
+            println!("⚠️  The battle couldn't be shown: {}", e);
+        }
+    }
+    if presentation == Presentation::Live {
         // Display the battle live
-        let config = rinha_de_neopets::display::BattleDisplayConfig::default();
-        let mut display = rinha_de_neopets::display::BattleDisplay::with_config(fighter1, fighter2, config);
+        let config = cli_config().display_config();
+        print_playback_keys();
+        let mut display = rinha_de_neopets::display::BattleDisplay::with_config(fighter1, fighter2, config)
+            .with_clock(KeyboardClock::new());
         display.display_battle_events(&events, Some((fighter1.health, fighter2.health)));
         display.display_battle_summary(&events);
-    } else {
+    } else if presentation == Presentation::Compact {
+        let config = BattleDisplayConfig { mode: DisplayMode::Compact, ..cli_config().display_config() };
+        BattleDisplay::with_config(fighter1, fighter2, config).display_battle_events(&events, None);
+    } else if presentation == Presentation::Summary {
         // Just show summary without live display
         println!("✅ Battle completed!");
         if let Some(ref winner_name) = winner {
@@ -143,91 +1136,1058 @@ fn start_battle(
             println!("🤝 Battle ended in a tie or max turns reached");
         }
         println!("📊 Total events: {}", events.len());
+        if let Some(seed) = battle.seed {
+            println!("🎲 Seed: {}", seed);
+        }
+        if !loot.is_empty() {
+            let items: Vec<String> = loot.iter().map(|item| item.to_string()).collect();
+            println!("🎁 Loot: {}", items.join(", "));
+        }
+    }
+
+    // Move battle from pending to complete, the winner taking the loot and both
+    // fighters their experience
+    let names = [battle.fighter1_name.clone(), battle.fighter2_name.clone()];
+    let levels = names.clone().map(|name| engine.storage().level(&name));
+    engine.complete_battle(battle, events, loot)?;
+    engine.save()?;
+    if matches!(presentation, Presentation::Stream(_)) {
+        return Ok(());
+    }
+
+    println!("\n✅ Battle moved to complete history with ID: {}", battle_id);
+    for (name, before) in names.iter().zip(levels) {
+        let after = engine.storage().level(name);
+        if after > before {
+            println!("⬆️  {} reached level {}!", name, after);
+        }
+    }
+
+    Ok(())
+}
+
+/// Fails if the pending battle `battle_id` is scheduled for later, `--force` skips this.
+fn refuse_early_start(storage: &Storage, battle_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(battle) = storage.find_pending_battle(battle_id) else {
+        return Ok(());
+    };
+    match (battle.due_in(chrono::Utc::now().fixed_offset()), &battle.scheduled_at) {
+        (Some(left), Some(scheduled_at)) => Err(format!(
+            "Battle '{}' is scheduled for {}, {} (use --force to start it now)",
+            battle_id, scheduled_at, countdown(left)
+        ).into()),
+        _ => Ok(()),
+    }
+}
+
+/// Looks at the pending battles every `every` and fights the scheduled ones that are
+/// due, saving after each. The storage is opened again before each look, to pick up
+/// battles created or scheduled since by other commands. A battle that fails to start
+/// isn't tried again until the worker restarts.
+fn watch_pending_battles(engine: &mut Engine, every: std::time::Duration) -> Result<(), Box<dyn std::error::Error>> {
+    println!("👀 Watching the pending battles every {}s, Ctrl-C to stop", every.as_secs());
+    let mut failed = HashSet::new();
+    loop {
+        let now = chrono::Utc::now().fixed_offset();
+        let due: Vec<String> = engine.storage().due_battles(now).iter()
+            .filter(|b| !failed.contains(&b.id))
+            .map(|b| b.id.clone())
+            .collect();
+        for id in due {
+            match engine.run_battle(&id, None) {
+                Ok(battle) => {
+                    engine.save()?;
+                    match &battle.winner {
+                        Some(winner) => println!("🏆 {}: {} vs {}, {} won", battle.id, battle.fighter1_name, battle.fighter2_name, winner),
+                        None => println!("🤝 {}: {} vs {}, no winner", battle.id, battle.fighter1_name, battle.fighter2_name),
+                    }
+                }
+                Err(e) => {
+                    println!("❌ {}: {}", id, e);
+                    failed.insert(id);
+                }
+            }
+        }
+        std::thread::sleep(every);
+        *engine = Engine::open(&cli_config().assets.dir)?;
+    }
+}
+
+/// Plays a pending battle up to the end of turn `pause_after` and saves where it got
+/// to, the battle stays pending until `battle resume` finishes it.
+fn start_paused_battle(
+    engine: &mut Engine,
+    battle_id: &str,
+    seed: Option<u64>,
+    pause_after: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let battle = engine.storage().find_pending_battle(battle_id)
+        .ok_or_else(|| format!("Pending battle '{}' not found", battle_id))?;
+    let (fighter1, fighter2) = engine.storage().battle_fighters(&battle)?;
+    let config = engine.battle_config(&battle)?;
+
+    let seed = seed.unwrap_or_else(rand::random);
+    let mut rng = CheckpointRng::seed_from_u64(seed);
+    let mut runner = BattleRunner::new(&fighter1, &fighter2, &config, &mut rng)?;
+    let mut events = Vec::new();
+    let checkpoint = loop {
+        if let Some(checkpoint) = runner.checkpoint().filter(|c| c.turn > pause_after) {
+            break checkpoint;
+        }
+        match runner.next() {
+            Some(event) => events.push(event),
+            None => {
+                return Err(format!("The battle ended before turn {}, start it without --pause-after", pause_after).into())
+            }
+        }
+    };
+    drop(runner);
+
+    println!("⏸️  Battle {} paused after turn {} ({} events so far)", battle_id, pause_after, events.len());
+    for name in [&battle.fighter1_name, &battle.fighter2_name] {
+        println!("   {}: {} HP", name, checkpoint.state.get_hp(name)?);
+    }
+    println!("🎲 Seed: {}", seed);
+    engine.storage_mut().save_checkpoint(battle_id, CheckpointRecord {
+        saved_at: chrono::Local::now().to_rfc3339(),
+        seed,
+        fighter1,
+        fighter2,
+        config,
+        events,
+        checkpoint,
+    })?;
+    engine.save()?;
+    println!("Resume it with `colosseum battle resume {}`", battle_id);
+    Ok(())
+}
+
+fn resume_battle(
+    engine: &mut Engine,
+    battle_id: &str,
+    presentation: Presentation,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut battle = engine.storage().find_pending_battle(battle_id)
+        .ok_or_else(|| format!("Pending battle '{}' not found", battle_id))?;
+    let mut saved = engine.storage_mut().take_checkpoint(battle_id)
+        .ok_or_else(|| format!("Battle '{}' was never paused, start it with `battle start`", battle_id))?;
+    println!("▶️  Resuming {} vs {} at turn {} (paused {})",
+        battle.fighter1_name, battle.fighter2_name, saved.checkpoint.turn, saved.saved_at);
+    println!();
+
+    // Fighters and config as the battle started with them
+    let mut events = saved.events;
+    let mut runner = BattleRunner::resume(&saved.fighter1, &saved.fighter2, &saved.config, &mut saved.checkpoint)?;
+    events.extend(runner.by_ref());
+    if let Some(error) = runner.error() {
+        return Err(error.clone().into());
+    }
+    drop(runner);
+
+    let loot = rinha_de_neopets::battle::drop_loot(&mut events, &engine.loot_tables()?, &mut saved.checkpoint.rng);
+    battle.seed = Some(saved.seed);
+    finish_battle(engine, battle, [&saved.fighter1, &saved.fighter2], events, loot, presentation)
+}
+
+fn replay_battle(
+    storage: &Storage,
+    battle_id: &str,
+    speed: Option<f64>,
+    compact: bool,
+    format: RenderFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if speed.is_some_and(|speed| speed.is_nan() || speed <= 0.0) {
+        return Err("Speed must be greater than 0".into());
+    }
+    let battle = storage.load_battle(battle_id)?
+        .filter(|b| b.is_completed)
+        .ok_or_else(|| format!("Completed battle '{}' not found", battle_id))?;
+
+    // The display only needs the names and starting HP, the events already hold the rest
+    let fighter1 = storage.get_fighter(&battle.fighter1_name)
+        .ok_or_else(|| format!("Fighter '{}' not found", battle.fighter1_name))?;
+    let fighter2 = storage.get_fighter(&battle.fighter2_name)
+        .ok_or_else(|| format!("Fighter '{}' not found", battle.fighter2_name))?;
+
+    let mut renderer: Box<dyn BattleRenderer> = match format {
+        RenderFormat::Animated => {
+            println!("📼 Replaying battle {}: {} vs {}", battle.id, battle.fighter1_name, battle.fighter2_name);
+            let mut config = cli_config().display_config();
+            if compact {
+                config.mode = DisplayMode::Compact;
+            }
+            config.speed = speed.unwrap_or(config.speed);
+            if config.mode == DisplayMode::Animated {
+                print_playback_keys();
+            }
+            Box::new(BattleDisplay::with_config(fighter1, fighter2, config).with_clock(KeyboardClock::new()))
+        }
+        RenderFormat::Plain => Box::new(PlainRenderer::new(io::stdout())),
+        RenderFormat::Json => Box::new(JsonRenderer::new(io::stdout())),
+    };
+    render_battle(&mut renderer, &battle.events);
+
+    Ok(())
+}
+
+fn verify_battle(engine: &Engine, battle_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let storage = engine.storage();
+    let battle = &storage.load_battle(battle_id)?
+        .filter(|b| b.is_completed)
+        .ok_or_else(|| format!("Completed battle '{}' not found", battle_id))?;
+
+    let stored_hash = battle_hash(&battle.events);
+    let mut failed = false;
+    match &battle.hash {
+        Some(hash) if *hash == stored_hash => println!("✅ The events match the battle's hash {}", hash),
+        Some(hash) => {
+            println!("❌ The events don't match the battle's hash, they were changed after the battle");
+            println!("   recorded {}", hash);
+            println!("   events   {}", stored_hash);
+            failed = true;
+        }
+        None => println!("⚠️  No hash was recorded for this battle, only the seed can vouch for it"),
+    }
+    let expected = battle.hash.as_deref().unwrap_or(&stored_hash);
+
+    match battle.seed {
+        Some(seed) => {
+            let (fighter1, fighter2) = storage.battle_fighters(battle)?;
+            let config = engine.battle_config(battle)?;
+            let rerun = resimulate(&battle.events, &fighter1, &fighter2, &config, &engine.loot_tables()?, seed)?;
+            if battle_hash(&rerun) == expected {
+                println!("✅ Fighting it again with seed {} gives the same battle", seed);
+            } else {
+                println!("❌ Fighting it again with seed {} gives a different battle", seed);
+                println!("   The record was tampered with, or the fighters, their equipment or injuries,");
+                println!("   the battle config or the loot tables changed since (or the battle was played interactively)");
+                failed = true;
+            }
+        }
+        None => println!("⚠️  No seed was recorded for this battle, it can't be fought again"),
+    }
+
+    if failed {
+        return Err(format!("Battle '{}' failed verification", battle_id).into());
+    }
+    Ok(())
+}
+
+fn compare_battles(storage: &Storage, id1: &str, id2: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let find = |id: &str| -> Result<BattleRecord, Box<dyn std::error::Error>> {
+        storage.load_battle(id)?
+            .filter(|b| b.is_completed)
+            .ok_or_else(|| format!("Completed battle '{}' not found", id).into())
+    };
+    let (first, second) = (find(id1)?, find(id2)?);
+
+    println!("🔍 {} ({} vs {}) against {} ({} vs {})",
+        first.id, first.fighter1_name, first.fighter2_name, second.id, second.fighter1_name, second.fighter2_name);
+    let diff = BattleLog::from(&first.events).diff(&BattleLog::from(&second.events));
+    for turn in &diff {
+        let label = if turn.turn == 0 { "Initiative".to_string() } else { format!("T{}", turn.turn) };
+        if !turn.diverged() {
+            println!("   {:<10} same", label);
+            continue;
+        }
+        let what: Vec<String> = turn.divergences.iter().map(|d| d.to_string()).collect();
+        println!("⚡ {:<10} differs in {}", label, what.join(", "));
+        for (id, events) in [(&first.id, &turn.first), (&second.id, &turn.second)] {
+            let lines: Vec<String> = events.iter().copied().filter_map(describe).collect();
+            if lines.is_empty() {
+                println!("     {}: -", id);
+            }
+            for line in lines {
+                println!("     {}: {}", id, line);
+            }
+        }
+    }
+
+    let diverged = diff.iter().filter(|t| t.diverged()).count();
+    match diff.iter().find(|t| t.diverged()) {
+        Some(turn) => println!("\n{} of {} turns differ, starting at turn {}", diverged, diff.len(), turn.turn),
+        None => println!("\nThe battles play out the same"),
+    }
+    for battle in [first, second] {
+        match &battle.winner {
+            Some(winner) => println!("{}: 🏆 {} won", battle.id, winner),
+            None => println!("{}: 🤝 no winner", battle.id),
+        }
+    }
+    Ok(())
+}
+
+fn show_battle_events(storage: &Storage, battle_id: &str, page: usize, per_page: usize) -> Result<(), Box<dyn std::error::Error>> {
+    if page == 0 || per_page == 0 {
+        return Err("Pages start at 1 and hold at least one event".into());
+    }
+    let count = storage.event_count(battle_id)?;
+    let pages = count.div_ceil(per_page).max(1);
+    let offset = (page - 1) * per_page;
+    let events = storage.events_page(battle_id, offset, per_page)?;
+
+    println!("=== Battle {} events, page {} of {} ({} events) ===", battle_id, page, pages, count);
+    if events.is_empty() {
+        println!("No events on this page.");
+    }
+    for (n, event) in events.iter().enumerate() {
+        if let Some(line) = describe(event) {
+            println!("{:>5}  T{:<3} {}", offset + n + 1, event.turn(), line);
+        }
+    }
+    if page < pages {
+        println!("\nNext: colosseum battle events {} --page {}", battle_id, page + 1);
+    }
+    Ok(())
+}
+
+fn export_battle_to(
+    storage: &Storage,
+    battle_id: &str,
+    format: ExportFormat,
+    output: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let battle = &storage.load_battle(battle_id)?
+        .ok_or_else(|| format!("Battle '{}' not found", battle_id))?;
+
+    match output {
+        Some(path) => {
+            let mut writer = BufWriter::new(File::create(path)?);
+            export_battle(battle, format, &mut writer)?;
+            writer.flush()?;
+            println!("✅ Exported battle {} to {}", battle.id, path);
+        }
+        None => export_battle(battle, format, &mut std::io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+fn write_event_schema(output: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = serde_json::to_string_pretty(&json_schema())?;
+    match output {
+        Some(path) => {
+            std::fs::write(path, schema + "\n")?;
+            println!("✅ Wrote the battle event schema to {}", path);
+        }
+        None => println!("{}", schema),
+    }
+    Ok(())
+}
+
+fn clean_all_data(storage: &mut Storage) -> Result<(), Box<dyn std::error::Error>> {
+    // For testing purposes, we'll skip the interactive confirmation;
+    // battles go to the trash so an accidental clean can be undone
+    let trashed = storage.trash_all_battles(&chrono::Utc::now().to_rfc3339());
+    
+    storage.save()?;
+    
+    println!("✅ All battle data has been cleaned!");
+    println!("Note: Fighter data has been preserved.");
+    println!("{} battles moved to the trash, use 'colosseum trash restore <ID>' to bring one back.", trashed);
+
+    Ok(())
+}
+
+fn delete_fighter(storage: &mut Storage, name: &str, permanent: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if permanent {
+        storage.remove_neopet(name)?;
+        storage.save()?;
+        println!("🗑️  Fighter '{}' deleted for good", name);
+        return Ok(());
+    }
+
+    storage.trash_fighter(name, &chrono::Utc::now().to_rfc3339())?;
+    storage.save()?;
+
+    println!("🗑️  Fighter '{}' moved to the trash", name);
+    println!("It will be kept for {} days, use 'colosseum trash restore {}' to undo", TRASH_RETENTION_DAYS, name);
+
+    Ok(())
+}
+
+fn rename_fighter(storage: &mut Storage, name: &str, new_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    storage.rename_neopet(name, new_name)?;
+    storage.save()?;
+
+    println!("✅ Fighter '{}' is now '{}'", name, new_name.trim());
+
+    Ok(())
+}
+
+fn export_fighter(
+    storage: &Storage,
+    name: &str,
+    format: FighterFormat,
+    output: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let neopet = storage.get_fighter(name)
+        .ok_or_else(|| format!("Fighter '{}' not found", name))?;
+    let text = format.write(neopet)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, text + "\n")?;
+            println!("✅ Exported fighter '{}' to {}", name, path);
+        }
+        None => println!("{}", text),
+    }
+    Ok(())
+}
+
+fn import_fighter(
+    storage: &mut Storage,
+    path: &str,
+    format: Option<FighterFormat>,
+    on_conflict: ImportConflict,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let format = format
+        .or_else(|| FighterFormat::from_path(Path::new(path)))
+        .unwrap_or(FighterFormat::Json);
+    let text = std::fs::read_to_string(path)?;
+    let neopet = format.read(&text).map_err(|e| format!("{} isn't a valid fighter: {}", path, e))?;
+    let requested = neopet.name.clone();
+    let name = storage.import_neopet(neopet, on_conflict)?;
+    storage.save()?;
+
+    println!("✅ Imported fighter '{}' from {}", name, path);
+    if name != requested {
+        println!("'{}' was taken, so the fighter was renamed", requested);
+    }
+    Ok(())
+}
+
+fn delete_battle(storage: &mut Storage, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    storage.trash_battle(id, &chrono::Utc::now().to_rfc3339())?;
+    storage.save()?;
+
+    println!("🗑️  Battle '{}' moved to the trash", id);
+    println!("It will be kept for {} days, use 'colosseum trash restore {}' to undo", TRASH_RETENTION_DAYS, id);
+
+    Ok(())
+}
+
+fn list_trash(storage: &Storage) {
+    let entries = storage.trash_entries();
+
+    if entries.is_empty() {
+        println!("The trash is empty.");
+        return;
+    }
+
+    println!("=== Trash (kept for {} days) ===", TRASH_RETENTION_DAYS);
+    println!("{:<30} {:<18} {:<20}", "Name / ID", "Kind", "Deleted At");
+    println!("{}", "─".repeat(70));
+
+    for entry in entries {
+        let deleted_at = if entry.deleted_at.len() > 19 {
+            &entry.deleted_at[..19]
+        } else {
+            &entry.deleted_at
+        };
+        println!("{:<30} {:<18} {:<20}", entry.key(), entry.kind(), deleted_at);
+    }
+}
+
+fn restore_from_trash(storage: &mut Storage, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let entry = storage.restore_from_trash(name)?;
+    storage.save()?;
+
+    println!("♻️  Restored {} '{}'", entry.kind(), entry.key());
+
+    Ok(())
+}
+
+fn purge_trash(storage: &mut Storage, all: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let purged = if all {
+        storage.purge_all_trash()
+    } else {
+        let now = chrono::Utc::now().fixed_offset();
+        storage.purge_expired_trash(now, chrono::Duration::days(TRASH_RETENTION_DAYS))
+    };
+    storage.save()?;
+
+    println!("🔥 Permanently removed {} entries from the trash", purged);
+
+    Ok(())
+}
+
+fn prune_pending_battles(storage: &mut Storage, older_than: Option<chrono::Duration>) -> Result<(), Box<dyn std::error::Error>> {
+    let ttl = older_than.or_else(|| cli_config().battles.pending_ttl())
+        .ok_or("Pass --older-than, or set pending_ttl in the config's [battles]")?;
+    let removed = storage.expire_pending(chrono::Utc::now().fixed_offset() - ttl);
+    storage.save()?;
+
+    if removed.is_empty() {
+        println!("No pending battles are that old.");
+    } else {
+        println!("🧹 Removed {} stale pending battles:", removed.len());
+        for id in &removed {
+            println!("  • {}", id);
+        }
+    }
+
+    Ok(())
+}
+
+fn create_random_battles(
+    storage: &mut Storage,
+    count: usize,
+    tags: &[String],
+    notes: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let fighters = storage.list_fighters();
+    
+    if fighters.len() < 2 {
+        return Err("Need at least 2 fighters to create battles".into());
+    }
+
+    let mut created_count = 0;
+    
+    for _ in 0..count {
+        // Randomly select two different fighters
+        use rand::Rng;
+        
+        let mut rng = rand::rng();
+        let fighter1_idx = rng.random_range(0..fighters.len());
+        let mut fighter2_idx = rng.random_range(0..fighters.len());
+        
+        // Ensure different fighters
+        while fighter2_idx == fighter1_idx {
+            fighter2_idx = rng.random_range(0..fighters.len());
+        }
+        
+        let fighter1 = &fighters[fighter1_idx];
+        let fighter2 = &fighters[fighter2_idx];
+        
+        // Create the battle
+        let battle_id = storage.generate_battle_id()?;
+        let created_at = chrono::Utc::now().to_rfc3339();
+
+        let mut battle_record = BattleRecord::pending(battle_id.clone(), fighter1, fighter2, &created_at);
+        battle_record.annotate(tags, notes);
+
+        storage.add_pending_battle(battle_record)?;
+        created_count += 1;
+    }
+
+    storage.save()?;
+
+    println!("✅ Created {} random battles successfully!", created_count);
+    println!("Use 'colosseum battle pending' to see all pending battles");
+
+    Ok(())
+}
+
+fn matchmake(
+    storage: &mut Storage,
+    count: usize,
+    avoid_rematches: Option<usize>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let ratings = storage.ratings().current(&storage.list_fighters());
+    let excluded = match avoid_rematches {
+        Some(recent) => recent_pairs(
+            storage.complete_battle_records(),
+            storage.pending_battle_records(),
+            recent,
+        ),
+        None => HashSet::new(),
+    };
+
+    let pairs = closest_pairs(&ratings, &excluded, count)?;
+
+    for (fighter1, fighter2) in &pairs {
+        let battle_record = BattleRecord::pending(storage.generate_battle_id()?, fighter1, fighter2, &chrono::Utc::now().to_rfc3339());
+        storage.add_pending_battle(battle_record)?;
+
+        println!(
+            "⚔️  {} ({:.0}) vs {} ({:.0})",
+            fighter1, ratings[fighter1], fighter2, ratings[fighter2]
+        );
+    }
+
+    // Everyone left out of this round of matches gets to recover
+    let matched: Vec<String> = pairs.iter().flat_map(|(f1, f2)| [f1.clone(), f2.clone()]).collect();
+    for name in storage.sit_out_round(&matched) {
+        println!("🩹 {} sat this round out and recovered", name);
+    }
+
+    storage.save()?;
+
+    println!("\n✅ Matched {} battles by rating!", pairs.len());
+    if pairs.len() < count {
+        println!("⚠️  Only {} of the {} asked for, every other matchup is taken or was fought recently", pairs.len(), count);
+    }
+    println!("Use 'colosseum battle pending' to see all pending battles");
+
+    Ok(())
+}
+
+fn show_season(storage: &Storage) {
+    println!("📅 {}", storage.season());
+    let mut injured: Vec<(String, u32)> = storage
+        .list_fighters()
+        .into_iter()
+        .map(|name| {
+            let injury = storage.injury(&name);
+            (name, injury)
+        })
+        .filter(|(_, injury)| *injury > 0)
+        .collect();
+    injured.sort();
+    for (name, injury) in injured {
+        println!("  🩹 {}: {} HP short", name, injury);
+    }
+}
+
+fn start_season(
+    storage: &mut Storage,
+    name: String,
+    hardcore: bool,
+    carry_over: f64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !(0.0..=1.0).contains(&carry_over) {
+        return Err("The carry-over must be between 0 and 1".into());
+    }
+    let hardcore = hardcore.then_some(HardcoreRules { injury_carry_over: carry_over });
+    storage.start_season(Season { name, hardcore });
+    storage.save()?;
+    println!("✅ Started {}", storage.season());
+    Ok(())
+}
+
+fn simulate_season(
+    storage: &mut Storage,
+    days: u32,
+    battles_per_day: Option<usize>,
+    seed: Option<u64>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let seed = seed.unwrap_or_else(rand::random);
+    let config = BattleConfig::load(&asset("battle_config.json"))?;
+    let start = chrono::Utc::now().fixed_offset();
+    let summary = simulate_days(storage, days, battles_per_day, &config, start, &mut StdRng::seed_from_u64(seed))?;
+    storage.save()?;
+
+    println!("📅 {}: {} days, {} battles\n", storage.season(), summary.days.len(), summary.battles());
+    for day in &summary.days {
+        let results: Vec<String> = day
+            .battles
+            .iter()
+            .map(|b| match &b.winner {
+                Some(winner) => format!("{} beat {}", winner, if *winner == b.fighter1 { &b.fighter2 } else { &b.fighter1 }),
+                None => format!("{} drew with {}", b.fighter1, b.fighter2),
+            })
+            .collect();
+        println!("Day {:>3}: {}", day.day, results.join(", "));
+    }
+
+    println!("\n=== Standings ===");
+    println!("{:>3}  {:<20} {:>6} {:>6}  {:>3} {:>3} {:>3} {:>5}", "#", "Fighter", "Rating", "+/-", "W", "L", "D", "Level");
+    for (rank, standing) in summary.standings.iter().enumerate() {
+        println!(
+            "{:>3}. {:<20} {:>6.0} {:>+6.0}  {:>3} {:>3} {:>3} {:>5}",
+            rank + 1,
+            standing.fighter,
+            standing.rating,
+            standing.rating_change,
+            standing.wins,
+            standing.losses,
+            standing.draws,
+            standing.level
+        );
+    }
+
+    if let Some((name, wins)) = &summary.longest_win_streak {
+        println!("\n🔥 Longest winning streak: {} with {} wins in a row", name, wins);
+    }
+    for (name, streak) in &summary.active_streaks {
+        match streak {
+            Streak::Wins(n) => println!("📈 {} is on a {}-battle winning streak", name, n),
+            Streak::Losses(n) => println!("📉 {} has lost {} battles in a row", name, n),
+        }
+    }
+    for rivalry in &summary.active_rivalries {
+        println!("⚔️  {}", rivalry.describe());
+    }
+
+    println!("\n🎲 Seed: {}", seed);
+    println!("✅ Battles saved, see them with 'colosseum battle complete --tag {}'", SIMULATION_TAG);
+    Ok(())
+}
+
+fn create_series(
+    storage: &mut Storage,
+    fighter1: &str,
+    fighter2: &str,
+    best_of: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let id = storage.generate_series_id()?;
+    let created_at = chrono::Utc::now().to_rfc3339();
+    let series = SeriesRecord::new(&id, fighter1, fighter2, best_of, &created_at)?;
+    storage.add_series(series)?;
+    storage.save()?;
+
+    println!("✅ Created series {}: {} vs {}, best of {}", id, fighter1, fighter2, best_of);
+    println!("Use 'colosseum series start {}' to play it", id);
+    Ok(())
+}
+
+fn start_series(storage: &mut Storage, id: &str, seed: Option<u64>) -> Result<(), Box<dyn std::error::Error>> {
+    let seed = seed.unwrap_or_else(rand::random);
+    let config = rinha_de_neopets::battle::BattleConfig::load(&asset("battle_config.json"))?;
+    let created_at = chrono::Utc::now().to_rfc3339();
+    let series = play_series(storage, id, &config, &created_at, &mut StdRng::seed_from_u64(seed))?;
+    storage.save()?;
+
+    println!("⚔️  Series {}: {} vs {}\n", series.id, series.fighter1, series.fighter2);
+    display_series_standings(&series);
+    println!("\n🎲 Seed: {}", seed);
+    println!("✅ Games saved, see them with 'colosseum battle complete --tag {}'", id);
+    Ok(())
+}
+
+fn brawl(storage: &Storage, names: &[String], seed: Option<u64>) -> Result<(), Box<dyn std::error::Error>> {
+    let fighters = names
+        .iter()
+        .map(|name| storage.battle_fighter(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let seed = seed.unwrap_or_else(rand::random);
+    let config = rinha_de_neopets::battle::BattleConfig::load(&asset("battle_config.json"))?;
+    let events = free_for_all(&fighters, &config, &mut StdRng::seed_from_u64(seed))?;
+
+    println!("⚔️  Brawl: {}\n", names.join(" vs "));
+    for line in compact_turn_lines(&events) {
+        println!("{}", line);
+    }
+    if let Some(winner) = BattleLog::from(&events).winner() {
+        println!("\n🏆 Winner: {}", winner);
+    }
+    println!("🎲 Seed: {}", seed);
+    Ok(())
+}
+
+fn run_tournament_cli(
+    storage: &mut Storage,
+    fighters: Vec<String>,
+    size: Option<usize>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Without a list, seed the registered fighters by rating
+    let fighters = if fighters.is_empty() {
+        let ratings = storage.ratings().current(&storage.list_fighters());
+        let mut seeded: Vec<String> = storage.list_fighters();
+        seeded.sort_by(|a, b| ratings[b].total_cmp(&ratings[a]));
+        seeded.truncate(size.unwrap_or(seeded.len()));
+        seeded
+    } else {
+        fighters
+    };
+
+    let id = storage.ids().next(IdKind::Tournament)?;
+    let config = rinha_de_neopets::battle::BattleConfig::load(&asset("battle_config.json"))?;
+    let created_at = chrono::Utc::now().to_rfc3339();
+    let record = run_tournament(storage, &id, &fighters, &config, &created_at, &mut rand::rng())?;
+
+    println!("🏆 Tournament {} ({} fighters)\n", record.id, record.fighters.len());
+    for round in &record.rounds {
+        println!("=== Round {} ===", round.number);
+        for m in &round.matches {
+            match (&m.fighter2, &m.battle_id) {
+                (Some(fighter2), Some(battle_id)) => {
+                    println!("  {} vs {} → {} wins ({})", m.fighter1, fighter2, m.winner, battle_id)
+                }
+                _ => println!("  {} advances with a bye", m.fighter1),
+            }
+        }
+        println!();
+    }
+    println!("👑 Champion: {}", record.champion);
+
+    storage.save()?;
+    let mut tournaments = Tournaments::load(&asset("tournaments.json"))?;
+    tournaments.records.push(record);
+    tournaments.save(&asset("tournaments.json"))?;
+
+    println!("\n✅ Battles saved, see them with 'colosseum battle complete --tag {}'", id);
+
+    Ok(())
+}
+
+fn show_leaderboard(storage: &Storage) {
+    let ratings = storage.ratings();
+    let leaderboard = ratings.leaderboard();
+
+    println!("=== Leaderboard ===");
+    if leaderboard.is_empty() {
+        println!("No fighter has {} completed battles yet.", ratings.rules.min_battles);
+        return;
+    }
+    println!("{:>3}  {:<20} {:>6}  {:>3} {:>3} {:>3}", "#", "Fighter", "Rating", "W", "L", "D");
+    for (rank, (name, rating)) in leaderboard.iter().enumerate() {
+        println!(
+            "{:>3}. {:<20} {:>6.0}  {:>3} {:>3} {:>3}",
+            rank + 1, name, rating.rating, rating.wins, rating.losses, rating.draws
+        );
+    }
+
+    let unranked = ratings.ratings.len() - leaderboard.len();
+    if unranked > 0 {
+        println!("\n{} fighters need {}+ battles to be ranked", unranked, ratings.rules.min_battles);
+    }
+}
+
+fn show_streaks(storage: &Storage, min: usize) {
+    let streaks = Streaks::from_battles(storage.complete_battle_records());
+    let fighters = streaks.active_fighter_streaks(min);
+    let rivalries = streaks.active_rivalries(min);
+
+    println!("=== Streaks ===");
+    if fighters.is_empty() && rivalries.is_empty() {
+        println!("No active streaks of {}+ battles.", min);
+        return;
+    }
+    for (name, streak) in fighters {
+        match streak {
+            Streak::Wins(n) => println!("📈 {:<20} {} wins in a row", name, n),
+            Streak::Losses(n) => println!("📉 {:<20} {} losses in a row", name, n),
+        }
+    }
+    if !rivalries.is_empty() {
+        println!("\n=== Rivalries ===");
+        for rivalry in rivalries {
+            println!("🔥 {}", rivalry.describe());
+        }
+    }
+}
+
+fn show_stats(battles: &[BattleRecord], fighter: Option<&str>) {
+    let stats = BattleStats::from_battles(battles);
+
+    let Some(name) = fighter else {
+        println!("=== Stats ===");
+        if stats.fighters().next().is_none() {
+            println!("No completed battles yet.");
+            return;
+        }
+        println!("Fighter                B   Win%   Dealt   Taken  Crit%  Favorite");
+        for (name, fighter) in stats.fighters() {
+            println!(
+                "{:<20} {:>3} {:>5.0}% {:>7.1} {:>7.1} {:>5.0}%  {}",
+                name,
+                fighter.battles,
+                fighter.win_rate() * 100.0,
+                fighter.avg_damage_dealt(),
+                fighter.avg_damage_taken(),
+                fighter.crit_rate() * 100.0,
+                fighter.favorite_action().map_or("-", |(action, _)| action)
+            );
+        }
+        return;
+    };
+
+    let Some(fighter) = stats.fighter(name) else {
+        println!("{} has no completed battles.", name);
+        return;
+    };
+    println!("=== Stats: {} ===", name);
+    println!(
+        "Battles: {} ({}W {}L {}D, {:.0}% won)",
+        fighter.battles, fighter.wins, fighter.losses, fighter.draws, fighter.win_rate() * 100.0
+    );
+    println!("Damage dealt: {} ({:.1} per battle)", fighter.damage_dealt, fighter.avg_damage_dealt());
+    println!("Damage taken: {} ({:.1} per battle)", fighter.damage_taken, fighter.avg_damage_taken());
+    println!("Crits: {} of {} rolls ({:.0}%)", fighter.crits, fighter.rolls, fighter.crit_rate() * 100.0);
+    if let Some((action, count)) = fighter.favorite_action() {
+        println!("Favorite action: {} ({} times)", action, count);
     }
+    if fighter.killing_blows > 0 {
+        println!(
+            "Killing blows: {} ({} exact, biggest overkill {})",
+            fighter.killing_blows, fighter.exact_kills, fighter.max_overkill
+        );
+    }
+    if let Some((id, turns)) = &fighter.longest_battle {
+        println!("Longest battle: {} ({} turns)", id, turns);
+    }
+}
 
-    // Move battle from pending to complete
-    storage.remove_pending_battle(battle_id);
-    storage.move_battle_to_complete(battle, events, winner.clone());
-    storage.save()?;
+fn show_storage(engine: &Engine) -> Result<(), Box<dyn std::error::Error>> {
+    let config = engine.storage_config();
+    println!("=== Storage ===");
+    println!("Data directory: {}", config.data_dir.display());
+    match config.backend {
+        Backend::Json => {
+            println!("Backend: json");
+            for file in [&config.neopets, &config.complete_battles, &config.pending_battles, &config.battle_events] {
+                println!("  {}", config.path(file).display());
+            }
+            if config.compress_battles {
+                println!("Completed battles and their events are gzipped");
+            }
+        }
+        Backend::Sqlite => println!("Backend: sqlite ({})", config.path(&config.sqlite_path).display()),
+    }
+    if std::env::var(BACKEND_ENV).is_ok() {
+        println!("Picked by {}, which overrides {}/storage.json", BACKEND_ENV, config.data_dir.display());
+    }
+    println!("Fighters: {}", engine.fighters().len());
+    println!(
+        "Battles: {} completed, {} pending",
+        engine.storage().complete_battle_records().len(),
+        engine.storage().pending_battle_records().len()
+    );
+    Ok(())
+}
 
-    println!("\n✅ Battle moved to complete history with ID: {}", battle_id);
+fn migrate_storage(engine: &mut Engine, to: Backend) -> Result<(), Box<dyn std::error::Error>> {
+    let from = engine.storage_config().backend;
+    if from == to {
+        return Err(format!("Already using the {} backend", to).into());
+    }
+    engine.migrate(to)?;
+    println!(
+        "✅ Moved {} fighters and {} battles from {} to {}",
+        engine.fighters().len(),
+        engine.storage().complete_battle_records().len() + engine.storage().pending_battle_records().len(),
+        from,
+        to
+    );
+    if std::env::var(BACKEND_ENV).is_ok() {
+        println!(
+            "⚠️  {} is set and still picks the backend over {}/storage.json",
+            BACKEND_ENV,
+            engine.storage_config().data_dir.display()
+        );
+    }
+    Ok(())
+}
 
+fn compact_storage(engine: &mut Engine) -> Result<(), Box<dyn std::error::Error>> {
+    let before = engine.storage_config().complete_battles_size()?;
+    engine.compact()?;
+    let after = engine.storage_config().complete_battles_size()?;
+    println!(
+        "✅ Compressed {} completed battles: {} bytes down to {}",
+        engine.storage().complete_battle_records().len(),
+        before,
+        after
+    );
     Ok(())
 }
 
-fn clean_all_data(storage: &mut Storage) -> Result<(), Box<dyn std::error::Error>> {
-    // For testing purposes, we'll skip the interactive confirmation
-    // and just clear the battle data directly
-    
-    // Clear all battle data
-    storage.clear_complete_battles();
-    storage.clear_pending_battles();
-    
-    storage.save()?;
-    
-    println!("✅ All battle data has been cleaned!");
-    println!("Note: Fighter data has been preserved.");
-    println!("All battles have been cleared.");
+fn run_doctor(storage: &mut Storage, fix: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let stats = doctor::stats(storage)?;
+    println!("=== Storage ===");
+    println!("Fighters: {}", stats.fighters);
+    println!(
+        "Battles: {} completed ({} archived), {} pending",
+        stats.complete_battles, stats.archived_battles, stats.pending_battles
+    );
+    println!("Events: {}", stats.events);
+    println!("Trash: {} entries", stats.trash_entries);
+    println!();
 
+    let issues = doctor::check(storage)?;
+    if issues.is_empty() {
+        println!("✅ No problems found");
+        return Ok(());
+    }
+    for issue in &issues {
+        let mark = if issue.is_fixable() { "🔧" } else { "❌" };
+        println!("{} {}", mark, issue);
+    }
+    let fixable = issues.iter().filter(|issue| issue.is_fixable()).count();
+    if !fix {
+        println!();
+        println!("{} problems found, {} can be fixed with 'colosseum doctor --fix'", issues.len(), fixable);
+        return Ok(());
+    }
+    let fixed = doctor::fix(storage, &issues, &chrono::Utc::now().to_rfc3339())?;
+    storage.save()?;
+    println!();
+    println!("✅ Fixed {} of {} problems, trashed battles can be restored with 'colosseum trash restore <ID>'", fixed, issues.len());
+    if fixed < issues.len() {
+        println!("The rest need fixing by hand");
+    }
     Ok(())
 }
 
-fn create_random_battles(
-    storage: &mut Storage,
-    count: usize,
+fn recompute_ratings(
+    engine: &Engine,
+    decay_per_day: Option<f64>,
+    grace_days: Option<i64>,
+    min_battles: Option<usize>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let fighters = storage.list_fighters();
-    
-    if fighters.len() < 2 {
-        return Err("Need at least 2 fighters to create battles".into());
+    let (storage, config) = (engine.storage(), engine.storage_config());
+    let rules_path = config.path(&config.rating_rules).to_string_lossy().into_owned();
+    let ratings_path = config.path(&config.ratings).to_string_lossy().into_owned();
+    let mut rules = RatingRules::load(&rules_path)?;
+    if let Some(decay_per_day) = decay_per_day {
+        rules.decay_per_day = decay_per_day;
+    }
+    if let Some(grace_days) = grace_days {
+        rules.inactivity_grace_days = grace_days;
     }
+    if let Some(min_battles) = min_battles {
+        rules.min_battles = min_battles;
+    }
+    rules.save(&rules_path)?;
 
-    let mut created_count = 0;
-    
-    for _ in 0..count {
-        // Randomly select two different fighters
-        use rand::Rng;
-        
-        let mut rng = rand::rng();
-        let fighter1_idx = rng.random_range(0..fighters.len());
-        let mut fighter2_idx = rng.random_range(0..fighters.len());
-        
-        // Ensure different fighters
-        while fighter2_idx == fighter1_idx {
-            fighter2_idx = rng.random_range(0..fighters.len());
-        }
-        
-        let fighter1 = &fighters[fighter1_idx];
-        let fighter2 = &fighters[fighter2_idx];
-        
-        // Create the battle
-        let battle_id = storage.generate_battle_id();
-        let created_at = chrono::Utc::now().to_rfc3339();
+    let snapshot = RatingsSnapshot::recompute(
+        &storage.list_fighters(),
+        storage.complete_battle_records(),
+        &rules,
+        chrono::Utc::now().fixed_offset(),
+    );
+    snapshot.save(&ratings_path)?;
 
-        let battle_record = BattleRecord {
-            id: battle_id.clone(),
-            fighter1_name: fighter1.clone(),
-            fighter2_name: fighter2.clone(),
-            created_at: created_at.clone(),
-            events: Vec::new(),
-            winner: None,
-            is_completed: false,
-        };
+    println!(
+        "=== Ratings (decay {:.1}/day after {} idle days, {}+ battles to rank) ===",
+        rules.decay_per_day, rules.inactivity_grace_days, rules.min_battles
+    );
+    let leaderboard = snapshot.leaderboard();
+    if leaderboard.is_empty() {
+        println!("No fighter has enough battles to be ranked yet.");
+    }
+    for (rank, (name, rating)) in leaderboard.iter().enumerate() {
+        println!("{:>3}. {:<20} {:>6.0} ({} battles)", rank + 1, name, rating.rating, rating.battles);
+    }
 
-        storage.add_pending_battle(battle_record);
-        created_count += 1;
+    let unranked = snapshot.ratings.len() - leaderboard.len();
+    if unranked > 0 {
+        println!("\n{} fighters need more battles to be ranked", unranked);
     }
+    println!("\n✅ Ratings saved to {}", ratings_path);
 
-    storage.save()?;
+    Ok(())
+}
 
-    println!("✅ Created {} random battles successfully!", created_count);
-    println!("Use 'colosseum battle pending' to see all pending battles");
+fn list_battles(storage: &Storage, filter: &BattleFilter) {
+    let battles = storage.find_battles(filter);
+    if battles.is_empty() {
+        println!("No battles found.");
+        return;
+    }
 
-    Ok(())
+    println!("=== Battles ({}) ===", battles.len());
+    println!("{:<20} {:<30} {:<20} {:<20}", "ID", "Matchup", "Created At", "Result");
+    println!("{}", "─".repeat(90));
+
+    for battle in battles {
+        let matchup = format!("{} vs {}", battle.fighter1_name, battle.fighter2_name);
+        let created_at = battle.created_at.get(..19).unwrap_or(&battle.created_at);
+        let result = match (&battle.winner, battle.is_completed) {
+            (Some(winner), true) => format!("🏆 {}", winner),
+            (None, true) => "Draw".to_string(),
+            (_, false) => "Pending".to_string(),
+        };
+        println!("{:<20} {:<30} {:<20} {:<20}", battle.id, matchup, created_at, result);
+    }
 }
 
-fn list_complete_battles(storage: &Storage) {
-    let battles = storage.list_complete_battles();
+fn list_complete_battles(storage: &Storage, tag: Option<&str>) {
+    let battles: Vec<&BattleRecord> = storage.complete_battle_records()
+        .iter()
+        .filter(|b| !b.archived && tag.is_none_or(|tag| b.has_tag(tag)))
+        .collect();
     
     if battles.is_empty() {
         println!("No completed battles found.");
@@ -235,16 +2195,21 @@ fn list_complete_battles(storage: &Storage) {
     }
 
     println!("=== Completed Battles ===");
-    println!("{:<20} {:<30} {:<10}", "ID", "Matchup", "Status");
-    println!("{}", "─".repeat(70));
+    println!("{:<20} {:<30} {:<10} {:<20}", "ID", "Matchup", "Status", "Tags");
+    println!("{}", "─".repeat(90));
     
-    for (id, matchup, status) in battles {
-        println!("{:<20} {:<30} {:<10}", id, matchup, status);
+    for battle in battles {
+        let matchup = format!("{} vs {}", battle.fighter1_name, battle.fighter2_name);
+        let status = if battle.is_completed { "Completed" } else { "Pending" };
+        println!("{:<20} {:<30} {:<10} {:<20}", battle.id, matchup, status, battle.tags.join(", "));
     }
 }
 
-fn list_pending_battles(storage: &Storage) {
-    let battles = storage.list_pending_battles();
+fn list_pending_battles(storage: &Storage, tag: Option<&str>) {
+    let battles: Vec<&BattleRecord> = storage.pending_battle_records()
+        .iter()
+        .filter(|b| tag.is_none_or(|tag| b.has_tag(tag)))
+        .collect();
     
     if battles.is_empty() {
         println!("No pending battles found.");
@@ -252,22 +2217,132 @@ fn list_pending_battles(storage: &Storage) {
     }
 
     println!("=== Pending Battles ===");
-    println!("{:<20} {:<30} {:<20}", "ID", "Matchup", "Created At");
-    println!("{}", "─".repeat(80));
+    println!("{:<20} {:<30} {:<20} {:<14} {:<20}", "ID", "Matchup", "Created At", "Starts", "Tags");
+    println!("{}", "─".repeat(115));
     
-    for (id, matchup, created_at) in battles {
+    let now = chrono::Utc::now().fixed_offset();
+    for battle in battles {
+        let matchup = format!("{} vs {}", battle.fighter1_name, battle.fighter2_name);
         // Format the timestamp to be more readable
-        let formatted_time = if created_at.len() > 19 {
-            &created_at[..19] // Take first 19 chars (YYYY-MM-DDTHH:MM:SS)
+        let formatted_time = if battle.created_at.len() > 19 {
+            &battle.created_at[..19] // Take first 19 chars (YYYY-MM-DDTHH:MM:SS)
         } else {
-            &created_at
+            &battle.created_at
         };
-        println!("{:<20} {:<30} {:<20}", id, matchup, formatted_time);
+        let starts = match (battle.due_in(now), &battle.scheduled_at) {
+            (Some(left), _) => countdown(left),
+            (None, Some(_)) => "due".to_string(),
+            (None, None) => "-".to_string(),
+        };
+        println!("{:<20} {:<30} {:<20} {:<14} {:<20}", battle.id, matchup, formatted_time, starts, battle.tags.join(", "));
+    }
+}
+
+/// How long until a scheduled battle starts, e.g. "in 1d 02h", "in 2h 05m" or "in 4m 09s".
+fn countdown(left: chrono::Duration) -> String {
+    let seconds = left.num_seconds();
+    let (days, hours, minutes) = (seconds / 86_400, seconds / 3_600 % 24, seconds / 60 % 60);
+    if days > 0 {
+        format!("in {}d {:02}h", days, hours)
+    } else if hours > 0 {
+        format!("in {}h {:02}m", hours, minutes)
+    } else {
+        format!("in {}m {:02}s", minutes, seconds % 60)
+    }
+}
+
+fn show_battle(storage: &Storage, id: &str) {
+    let Some(battle) = storage.find_battle(id) else {
+        println!("Battle '{}' not found.", id);
+        return;
+    };
+
+    println!("=== Battle {} ===", battle.id);
+    println!("Matchup: {} vs {}", battle.fighter1_name, battle.fighter2_name);
+    println!("Created: {}", battle.created_at);
+    if battle.is_completed {
+        match &battle.winner {
+            Some(winner) => println!("Status: Completed, 🏆 {} won", winner),
+            None => println!("Status: Completed, 🤝 no winner"),
+        }
+        match storage.event_count(&battle.id) {
+            Ok(count) => println!("Events: {}", count),
+            Err(e) => println!("Events: unreadable ({})", e),
+        }
+        if let Some(seed) = battle.seed {
+            println!("Seed: {}", seed);
+        }
+    } else {
+        println!("Status: Pending");
+    }
+    let streaks = Streaks::from_battles(storage.complete_battle_records());
+    let rivalry = streaks.rivalry(&battle.fighter1_name, &battle.fighter2_name);
+    if let Some(rivalry) = rivalry.filter(|r| r.wins >= MIN_STREAK) {
+        println!("Rivalry: 🔥 {}", rivalry.describe());
+    }
+
+    print_handicap(battle);
+    print_arena(battle);
+    print_schedule(battle);
+    if !battle.tags.is_empty() {
+        println!("Tags: {}", battle.tags.join(", "));
     }
+    if !battle.notes.is_empty() {
+        println!("Notes:");
+        for note in &battle.notes {
+            println!("  📝 {}", note);
+        }
+    }
+}
+
+fn print_handicap(battle: &BattleRecord) {
+    for (name, modifiers) in [(&battle.fighter1_name, &battle.handicap.fighter1), (&battle.fighter2_name, &battle.handicap.fighter2)] {
+        if !modifiers.is_empty() {
+            println!("Handicap: ⚖️  {} {}", name, describe_modifiers(modifiers));
+        }
+    }
+}
+
+fn print_arena(battle: &BattleRecord) {
+    if let Some(arena) = &battle.arena {
+        println!("Arena: 🏟️  {}", arena);
+    }
+}
+
+fn print_schedule(battle: &BattleRecord) {
+    let Some(scheduled_at) = &battle.scheduled_at else {
+        return;
+    };
+    match battle.due_in(chrono::Utc::now().fixed_offset()) {
+        Some(left) if !battle.is_completed => println!("Scheduled: ⏰ {}, starts {}", scheduled_at, countdown(left)),
+        _ => println!("Scheduled: {}", scheduled_at),
+    }
+}
+
+/// The battle config with the battle's arena, which has to still be in `assets/arenas.json`.
+fn annotate_battle(
+    storage: &mut Storage,
+    id: &str,
+    tags: &[String],
+    notes: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    if tags.is_empty() && notes.is_empty() {
+        return Err("Nothing to add, use --tag and/or --note".into());
+    }
+    storage.annotate_battle(id, tags, notes)?;
+    storage.save()?;
+
+    println!("✅ Battle '{}' annotated", id);
+    show_battle(storage, id);
+
+    Ok(())
 }
 
 // Interactive fighter creation
-fn create_fighter_interactive(storage: &mut Storage) -> Result<(), Box<dyn std::error::Error>> {
+fn create_fighter_interactive(
+    storage: &mut Storage,
+    catalog: &SpellCatalog,
+) -> Result<(), Box<dyn std::error::Error>> {
     println!("=== Create New Fighter ===\n");
 
     let name: String = Input::new()
@@ -294,7 +2369,7 @@ fn create_fighter_interactive(storage: &mut Storage) -> Result<(), Box<dyn std::
         .default(10)
         .interact_text()?;
 
-    // Spells
+    // Spells, picked from the shared library
     let mut spells = Vec::new();
     loop {
         let spell_name: String = Input::new()
@@ -306,10 +2381,13 @@ fn create_fighter_interactive(storage: &mut Storage) -> Result<(), Box<dyn std::
             break;
         }
 
-        spells.push(Spell {
-            name: spell_name,
-            effect: serde_json::json!({}),
-        });
+        match catalog.get(&spell_name) {
+            Some(entry) => spells.push(entry.to_spell()),
+            None => println!(
+                "❌ Unknown spell '{}'. Add it first with 'colosseum spell add'.",
+                spell_name
+            ),
+        }
     }
 
     // Behavior
@@ -326,6 +2404,16 @@ fn create_fighter_interactive(storage: &mut Storage) -> Result<(), Box<dyn std::
         .default(0.25)
         .interact_text()?;
 
+    let defend_chance: f64 = Input::new()
+        .with_prompt("Defend chance (0.0-1.0)")
+        .default(0.0)
+        .interact_text()?;
+
+    let taunt_chance: f64 = Input::new()
+        .with_prompt("Taunt chance (0.0-1.0)")
+        .default(0.0)
+        .interact_text()?;
+
     let mut spell_chances = Vec::new();
     for (_i, spell) in spells.iter().enumerate() {
         let chance: f64 = Input::new()
@@ -339,8 +2427,13 @@ fn create_fighter_interactive(storage: &mut Storage) -> Result<(), Box<dyn std::
         attack_chance,
         spell_chances,
         heal_chance,
+        defend_chance,
+        taunt_chance,
     };
 
+    let strategy = select_strategy(StrategyKind::Probabilistic)?;
+    let class = select_class(None)?;
+
     // Construct and validate
     let neopet_def = NeopetDef {
         name: name.clone(),
@@ -350,6 +2443,11 @@ fn create_fighter_interactive(storage: &mut Storage) -> Result<(), Box<dyn std::
         base_defense,
         spells,
         behavior: behavior_def,
+        strategy,
+        dodge_chance: 0.0,
+        block_value: 0,
+        speed: 0,
+        class,
     };
 
     match Neopet::try_from(neopet_def) {
@@ -367,6 +2465,130 @@ fn create_fighter_interactive(storage: &mut Storage) -> Result<(), Box<dyn std::
     Ok(())
 }
 
+const STRATEGIES: [(&str, StrategyKind); 4] = [
+    ("Probabilistic (always roll the chances above)", StrategyKind::Probabilistic),
+    ("Adaptive (attack when the opponent keeps healing)", StrategyKind::Adaptive),
+    ("Aggressive when winning", StrategyKind::AggressiveWhenWinning),
+    ("Heal when low", StrategyKind::HealWhenLow),
+];
+
+fn select_strategy(current: StrategyKind) -> Result<StrategyKind, Box<dyn std::error::Error>> {
+    let labels: Vec<&str> = STRATEGIES.iter().map(|(label, _)| *label).collect();
+    let strategy = Select::new()
+        .with_prompt("Strategy")
+        .items(&labels)
+        .default(STRATEGIES.iter().position(|(_, kind)| *kind == current).unwrap_or(0))
+        .interact()?;
+    Ok(STRATEGIES[strategy].1)
+}
+
+fn select_class(current: Option<FighterClass>) -> Result<Option<FighterClass>, Box<dyn std::error::Error>> {
+    let mut classes = vec!["None".to_string()];
+    classes.extend(FighterClass::ALL.iter().map(|class| format!("{} ({})", class, class.passive())));
+    let class = Select::new()
+        .with_prompt("Class")
+        .items(&classes)
+        .default(current.and_then(|current| FighterClass::ALL.iter().position(|c| *c == current)).map_or(0, |index| index + 1))
+        .interact()?;
+    Ok(class.checked_sub(1).map(|index| FighterClass::ALL[index]))
+}
+
+/// Asks for every field of an existing fighter, their current values filled in, and
+/// saves the result if it still validates.
+fn edit_fighter_interactive(
+    storage: &mut Storage,
+    catalog: &SpellCatalog,
+    name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let current = storage.get_fighter(name)
+        .ok_or_else(|| format!("Fighter '{}' not found", name))?
+        .clone();
+    println!("=== Edit Fighter '{}' ===", name);
+    println!("Press Enter to keep a value, use 'colosseum fighter rename' to change the name\n");
+
+    let health: u32 = Input::new().with_prompt("Health").default(current.health).interact_text()?;
+    let base_attack: u32 = Input::new().with_prompt("Base attack").default(current.base_attack).interact_text()?;
+    let base_defense: u32 = Input::new().with_prompt("Base defense").default(current.base_defense).interact_text()?;
+    let heal_delta: u32 = Input::new().with_prompt("Heal delta").default(current.heal_delta).interact_text()?;
+    let dodge_chance: f64 = Input::new().with_prompt("Dodge chance (0.0-1.0)").default(current.dodge_chance).interact_text()?;
+    let block_value: u32 = Input::new().with_prompt("Block value").default(current.block_value).interact_text()?;
+    let speed: u32 = Input::new().with_prompt("Speed").default(current.speed).interact_text()?;
+
+    // Spells, picked from the shared library
+    let known: Vec<&str> = current.spells.iter().map(|spell| spell.name.as_str()).collect();
+    let spells = loop {
+        let spell_names: String = Input::new()
+            .with_prompt("Spells, comma separated (or leave empty for none)")
+            .default(known.join(", "))
+            .allow_empty(true)
+            .interact_text()?;
+        let names: Vec<&str> = spell_names.split(',').map(str::trim).filter(|n| !n.is_empty()).collect();
+        match names.iter().find(|n| catalog.get(n).is_none()) {
+            Some(unknown) => println!(
+                "❌ Unknown spell '{}'. Add it first with 'colosseum spell add'.",
+                unknown
+            ),
+            None => break names.iter().filter_map(|n| catalog.get(n)).map(|entry| entry.to_spell()).collect::<Vec<_>>(),
+        }
+    };
+
+    // Behavior
+    println!("\n=== Behavior Configuration ===");
+    println!("Probabilities must sum to 1.0");
+    let behavior = &current.behavior;
+    let attack_chance: f64 = Input::new().with_prompt("Attack chance (0.0-1.0)").default(behavior.attack_chance).interact_text()?;
+    let heal_chance: f64 = Input::new().with_prompt("Heal chance (0.0-1.0)").default(behavior.heal_chance).interact_text()?;
+    let defend_chance: f64 = Input::new().with_prompt("Defend chance (0.0-1.0)").default(behavior.defend_chance).interact_text()?;
+    let taunt_chance: f64 = Input::new().with_prompt("Taunt chance (0.0-1.0)").default(behavior.taunt_chance).interact_text()?;
+    let mut spell_chances = Vec::new();
+    for spell in &spells {
+        // Spells the fighter already knew keep their chance, new ones start at 0
+        let chance = known.iter().position(|n| *n == spell.name).map_or(0.0, |i| behavior.spell_chances[i]);
+        let chance: f64 = Input::new()
+            .with_prompt(format!("Chance for spell '{}' (0.0-1.0)", spell.name))
+            .default(chance)
+            .interact_text()?;
+        spell_chances.push(chance);
+    }
+
+    let strategy = select_strategy(current.strategy)?;
+    let class = select_class(current.class)?;
+
+    let neopet_def = NeopetDef {
+        name: current.name.clone(),
+        health,
+        heal_delta,
+        base_attack,
+        base_defense,
+        spells,
+        behavior: BehaviorDef {
+            attack_chance,
+            spell_chances,
+            heal_chance,
+            defend_chance,
+            taunt_chance,
+        },
+        strategy,
+        dodge_chance,
+        block_value,
+        speed,
+        class,
+    };
+
+    match Neopet::try_from(neopet_def).and_then(|neopet| storage.update_neopet(neopet)) {
+        Ok(_) => {
+            storage.save()?;
+            println!("\n✅ Fighter '{}' updated successfully!", name);
+        }
+        Err(e) => {
+            println!("\n❌ Validation failed: {}", e);
+            println!("Nothing was changed, please try again with valid values.");
+        }
+    }
+
+    Ok(())
+}
+
 fn list_fighters(storage: &Storage) {
     let names = storage.list_fighters();
     if names.is_empty() {
@@ -379,53 +2601,291 @@ fn list_fighters(storage: &Storage) {
     }
 }
 
-fn show_fighter(storage: &Storage, name: &str) {
+fn show_fighter(storage: &Storage, catalog: &SpellCatalog, name: &str) {
     match storage.get_fighter(name) {
         Some(neopet) => {
             println!("=== Fighter Details ===\n");
             println!("{}", neopet);
+            for spell in &neopet.spells {
+                match catalog.get(&spell.name) {
+                    Some(entry) => println!("  🪄 {}: {}", entry.name, entry.effect),
+                    None => println!("  🪄 {}: not in the spell library", spell.name),
+                }
+            }
+
+            let loadout = storage.loadout(name);
+            if loadout.is_empty() {
+                println!("Equipment: none");
+            } else {
+                println!("Equipment:");
+                for item_name in loadout.values() {
+                    match storage.item_catalog().get(item_name) {
+                        Some(item) => println!("  {}", item),
+                        None => println!("  {}: not in the item catalog", item_name),
+                    }
+                }
+                if let Ok(equipped) = storage.equipped_fighter(name) {
+                    println!(
+                        "Equipped stats: ATK: {} | DEF: {} | Heal: +{}",
+                        equipped.base_attack, equipped.base_defense, equipped.heal_delta
+                    );
+                }
+            }
+
+            let progress = storage.progress(name);
+            match progress.next_level_xp {
+                Some(next) => println!("Level: {} ({}/{} XP)", progress.level, progress.xp, next),
+                None => println!("Level: {} (max, {} XP)", progress.level, progress.xp),
+            }
+
+            let injury = storage.injury(name);
+            if injury > 0 {
+                println!("Injury: {} HP short until they sit out a round", injury);
+            }
+
+            let inventory = storage.inventory(name);
+            if inventory.is_empty() {
+                println!("Inventory: empty");
+            } else {
+                println!("Inventory:");
+                for item in inventory {
+                    println!("  {}", item);
+                }
+            }
         }
         None => println!("Fighter '{}' not found.", name),
     }
 }
 
-fn create_battle(
-    storage: &mut Storage,
-    fighter1_name: &str,
-    fighter2_name: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Validate fighters exist
-    let _fighter1 = storage.get_fighter(fighter1_name)
-        .ok_or_else(|| format!("Fighter '{}' not found", fighter1_name))?;
-    let _fighter2 = storage.get_fighter(fighter2_name)
-        .ok_or_else(|| format!("Fighter '{}' not found", fighter2_name))?;
+fn show_fighter_level(storage: &Storage, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    const BAR_WIDTH: usize = 20;
+
+    let rules = storage.leveling_rules();
+    let progress = storage.progress(name);
+    let base = storage.get_fighter(name).ok_or_else(|| format!("Fighter '{}' not found", name))?;
+    let filled = ((progress.fraction(rules) * BAR_WIDTH as f64).round() as usize).min(BAR_WIDTH);
 
-    // Prevent self-battles
-    if fighter1_name == fighter2_name {
-        return Err("A fighter cannot battle themselves".into());
+    println!("=== {} ===\n", name);
+    match progress.next_level_xp {
+        Some(next) => println!(
+            "Level {} [{}{}] {}/{} XP, {} to level {}",
+            progress.level,
+            "█".repeat(filled),
+            "░".repeat(BAR_WIDTH - filled),
+            progress.xp,
+            next,
+            next - progress.xp,
+            progress.level + 1
+        ),
+        None => println!("Level {} (max) with {} XP", progress.level, progress.xp),
     }
+    let per_level: Vec<String> = rules.per_level.iter().map(|modifier| modifier.to_string()).collect();
+    println!(
+        "A win earns {} XP and a loss {} XP. Every level: {}\n",
+        rules.win_xp,
+        rules.loss_xp,
+        if per_level.is_empty() { "no stat changes".to_string() } else { per_level.join(", ") }
+    );
 
-    let battle_id = storage.generate_battle_id();
-    let created_at = chrono::Utc::now().to_rfc3339();
+    // The levels reached so far and the next few, at their stats
+    println!("{:>5} {:>7} {:>5} {:>4} {:>4} {:>5}", "Level", "XP", "HP", "ATK", "DEF", "Heal");
+    for level in 1..=(progress.level + 3).min(rules.max_level) {
+        let mut neopet = base.clone();
+        rules.apply(&mut neopet, level);
+        let marker = if level == progress.level { " ◀" } else { "" };
+        println!(
+            "{:>5} {:>7} {:>5} {:>4} {:>4} {:>5}{}",
+            level,
+            rules.xp_to_reach(level),
+            neopet.health,
+            neopet.base_attack,
+            neopet.base_defense,
+            neopet.heal_delta,
+            marker
+        );
+    }
+    Ok(())
+}
+
+fn list_items(storage: &Storage) {
+    let items = storage.item_catalog().items();
+    if items.is_empty() {
+        println!("No items available.");
+        return;
+    }
+    println!("=== Items ===");
+    for item in items {
+        println!("{}", item);
+        if !item.description.is_empty() {
+            println!("   {}", item.description);
+        }
+    }
+}
+
+fn list_arenas(engine: &Engine) -> Result<(), Box<dyn std::error::Error>> {
+    let arenas = engine.arenas()?;
+    if arenas.iter().next().is_none() {
+        println!("No arenas available.");
+        return Ok(());
+    }
+    println!("=== Arenas ===");
+    for arena in arenas.iter() {
+        println!("🏟️  {}", arena);
+        println!("   {}", arena.description);
+    }
+    Ok(())
+}
+
+fn equip_item(storage: &mut Storage, fighter: &str, item: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let replaced = storage.equip(fighter, item)?;
+    storage.save()?;
+    match replaced {
+        Some(old) if old != item => println!("✅ {} equipped {} instead of {}", fighter, item, old),
+        _ => println!("✅ {} equipped {}", fighter, item),
+    }
+    Ok(())
+}
+
+fn unequip_item(storage: &mut Storage, fighter: &str, slot: Slot) -> Result<(), Box<dyn std::error::Error>> {
+    match storage.unequip(fighter, slot) {
+        Some(item) => {
+            storage.save()?;
+            println!("✅ {} unequipped {}", fighter, item);
+        }
+        None => println!("{} has no {} equipped.", fighter, slot),
+    }
+    Ok(())
+}
+
+// Interactive spell creation
+fn add_spell_interactive(catalog: &mut SpellCatalog) -> Result<(), Box<dyn std::error::Error>> {
+    println!("=== Add New Spell ===\n");
+
+    let name: String = Input::new()
+        .with_prompt("Spell name")
+        .interact_text()?;
+
+    let description: String = Input::new()
+        .with_prompt("Description")
+        .allow_empty(true)
+        .interact_text()?;
+
+    let kinds = ["Damage", "Heal", "Drain", "Burn", "Poison", "Regen", "Cosmetic"];
+    let kind = Select::new()
+        .with_prompt("Effect")
+        .items(&kinds)
+        .default(0)
+        .interact()?;
+
+    let effect = if kind == 6 {
+        SpellEffect::Cosmetic
+    } else {
+        let amount: u32 = Input::new()
+            .with_prompt(if kind >= 3 { "Amount per turn" } else { "Amount" })
+            .default(10)
+            .interact_text()?;
+        let turns = if kind >= 3 {
+            Input::new().with_prompt("Turns").default(3).interact_text()?
+        } else {
+            0
+        };
+        match kind {
+            0 => SpellEffect::Damage { amount },
+            1 => SpellEffect::Heal { amount },
+            2 => SpellEffect::Drain { amount },
+            3 => SpellEffect::Burn { damage: amount, turns },
+            4 => SpellEffect::Poison { damage: amount, turns },
+            _ => SpellEffect::Regen { amount, turns },
+        }
+    };
+
+    let cooldown: u32 = Input::new()
+        .with_prompt("Cooldown (turns before it can be cast again, 0 for none)")
+        .default(0)
+        .interact_text()?;
 
-    // Create pending battle record
-    let battle_record = BattleRecord {
-        id: battle_id.clone(),
-        fighter1_name: fighter1_name.to_string(),
-        fighter2_name: fighter2_name.to_string(),
-        created_at: created_at.clone(),
-        events: Vec::new(), // Empty until battle is run
-        winner: None,
-        is_completed: false,
+    let spell = SpellEntry {
+        name: name.clone(),
+        description,
+        effect,
+        cooldown,
     };
 
-    storage.add_pending_battle(battle_record);
+    match catalog.add(spell) {
+        Ok(()) => {
+            catalog.save(&asset("spells.json"))?;
+            println!("\n✅ Spell '{}' added to the library!", name);
+        }
+        Err(e) => println!("\n❌ {}", e),
+    }
+
+    Ok(())
+}
+
+fn list_spells(catalog: &SpellCatalog) {
+    let names = catalog.list_spells();
+    if names.is_empty() {
+        println!("No spells in the library yet.");
+    } else {
+        println!("=== Spell Library ===");
+        for name in names {
+            println!("  • {}", name);
+        }
+    }
+}
+
+fn show_spell(storage: &Storage, catalog: &SpellCatalog, name: &str) {
+    match catalog.get(name) {
+        Some(entry) => {
+            println!("=== Spell Details ===\n");
+            println!("{}", entry);
+
+            let known_by: Vec<String> = storage
+                .list_fighters()
+                .into_iter()
+                .filter(|fighter| {
+                    storage
+                        .get_fighter(fighter)
+                        .is_some_and(|n| n.spells.iter().any(|s| s.name == name))
+                })
+                .collect();
+            if known_by.is_empty() {
+                println!("Known by: nobody yet");
+            } else {
+                println!("Known by: {}", known_by.join(", "));
+            }
+        }
+        None => println!("Spell '{}' not found.", name),
+    }
+}
+
+fn create_battle(
+    engine: &mut Engine,
+    fighter1_name: &str,
+    fighter2_name: &str,
+    options: &CreateBattleOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let battle = engine.create_battle(fighter1_name, fighter2_name, &options.handicaps, options.arena.as_deref())?;
+    let (battle_id, created_at) = (battle.id, battle.created_at);
+    let storage = engine.storage_mut();
+    storage.annotate_battle(&battle_id, &options.tags, &options.notes)?;
+    if let Some(at) = options.at {
+        storage.schedule_battle(&battle_id, at)?;
+    }
     storage.save()?;
 
     println!("✅ Battle created successfully!");
     println!("ID: {}", battle_id);
     println!("Matchup: {} vs {}", fighter1_name, fighter2_name);
     println!("Created: {}", created_at);
+    if let Some(battle) = storage.find_battle(&battle_id) {
+        if !battle.tags.is_empty() {
+            println!("Tags: {}", battle.tags.join(", "));
+        }
+        print_handicap(battle);
+        print_arena(battle);
+        print_schedule(battle);
+    }
     println!("\nUse 'colosseum battle pending' to see all pending battles");
 
     Ok(())
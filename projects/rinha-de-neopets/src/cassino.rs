@@ -1,9 +1,345 @@
 use serde::{Deserialize, Serialize};
+use chrono::{DateTime, FixedOffset};
+use std::fmt;
+use crate::battle::{BattleCompletionReason, BattleEvent};
+use crate::observer::Observer;
+use crate::storage::BattleRecord;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CassinoEvent {
     pub description: String,
     pub odd: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub battle: Option<BattleLink>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market: Option<Market>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>, // RFC 3339 timestamp
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub odds_history: Vec<OddsChange>, // oldest first, the last entry is the current odd
+}
+
+/// What moved an event's odd. Odds only change when set by hand, the cassino never
+/// adjusts them on its own.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OddsChangeSource {
+    Initial,
+    Manual,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OddsChange {
+    pub odd: f64,
+    pub source: OddsChangeSource,
+    pub timestamp: String,
+}
+
+/// Ties an event to a colosseum battle: the event occurs if `winner` wins it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BattleLink {
+    pub battle_id: String,
+    pub winner: String,
+}
+
+/// How a battle was won.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VictoryMethod {
+    /// The loser's HP was depleted by an attack.
+    Knockout,
+    /// The loser's HP was depleted by a spell.
+    Spell,
+    /// Nobody went down, the max turns decided the winner.
+    Decision,
+}
+
+impl VictoryMethod {
+    pub const ALL: [VictoryMethod; 3] = [VictoryMethod::Knockout, VictoryMethod::Spell, VictoryMethod::Decision];
+
+    /// How `battle` was won, or None if it has no winner.
+    pub fn of(battle: &BattleRecord) -> Option<Self> {
+        BattleOutcome::of(&battle.events).method()
+    }
+}
+
+/// What the markets of a battle settle on, gathered as the battle is fought: an
+/// `Observer` for `battle::battle_loop_with_observers`, so bets can be settled without
+/// going through the full event list again.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BattleOutcome {
+    winner: Option<String>,
+    turns: u32,
+    method: Option<VictoryMethod>,
+    // The finishing blow is the last action before the battle completed
+    finished_by_spell: bool,
+    overkill: Option<u32>,
+}
+
+impl BattleOutcome {
+    pub fn of(events: &[BattleEvent]) -> Self {
+        let mut outcome = Self::default();
+        for event in events {
+            outcome.on_event(event);
+        }
+        outcome
+    }
+
+    pub fn winner(&self) -> Option<&str> {
+        self.winner.as_deref()
+    }
+
+    pub fn turns(&self) -> u32 {
+        self.turns
+    }
+
+    /// How the battle was won, or None if it has no winner yet.
+    pub fn method(&self) -> Option<VictoryMethod> {
+        self.method
+    }
+
+    /// Damage the finishing attack had to spare, None if no attack knocked anyone out
+    /// or a spell had the last word.
+    pub fn overkill(&self) -> Option<u32> {
+        self.overkill.filter(|_| self.method == Some(VictoryMethod::Knockout))
+    }
+
+    /// Whether the picked outcome of `market` happened.
+    pub fn occurred(&self, market: &Market) -> bool {
+        match market {
+            Market::Winner { fighter, .. } => self.winner() == Some(fighter.as_str()),
+            Market::MethodOfVictory { method, .. } => self.method == Some(*method),
+            Market::TotalTurns { line, over, .. } => (self.turns as f64 > *line) == *over,
+            Market::Overkill { line, over, .. } => self.overkill().is_some_and(|overkill| overkill >= *line) == *over,
+        }
+    }
+}
+
+impl Observer for BattleOutcome {
+    fn on_event(&mut self, event: &BattleEvent) {
+        self.turns = self.turns.max(event.turn());
+        match event {
+            BattleEvent::Attack { is_killing_blow, overkill_amount, .. } => {
+                self.finished_by_spell = false;
+                if *is_killing_blow {
+                    self.overkill = Some(*overkill_amount);
+                }
+            }
+            // Burns and poison come from spells too
+            BattleEvent::SpellCast { .. } | BattleEvent::HealthUpdate { source: Some(_), .. } => {
+                self.finished_by_spell = true;
+            }
+            BattleEvent::BattleComplete { winner, completion_reason, .. } => {
+                self.winner = Some(winner.to_string());
+                self.method = Some(match completion_reason {
+                    BattleCompletionReason::MaxTurnsReached(_) => VictoryMethod::Decision,
+                    _ if self.finished_by_spell => VictoryMethod::Spell,
+                    _ => VictoryMethod::Knockout,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Display for VictoryMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            VictoryMethod::Knockout => "knockout",
+            VictoryMethod::Spell => "spell",
+            VictoryMethod::Decision => "decision",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A question about a battle together with the answer an event bets on. Events in the
+/// same market are mutually exclusive: at most one of them can occur.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Market {
+    Winner { battle_id: String, fighter: String },
+    MethodOfVictory { battle_id: String, method: VictoryMethod },
+    /// Whether the battle lasts more (`over`) or fewer turns than `line`.
+    TotalTurns { battle_id: String, line: f64, over: bool },
+    /// Whether the battle ends on an attack with at least `line` overkill (`over`), or
+    /// not: an exact kill, a spell or a decision all count as under.
+    Overkill { battle_id: String, line: u32, over: bool },
+}
+
+impl Market {
+    pub fn battle_id(&self) -> &str {
+        match self {
+            Market::Winner { battle_id, .. }
+            | Market::MethodOfVictory { battle_id, .. }
+            | Market::TotalTurns { battle_id, .. }
+            | Market::Overkill { battle_id, .. } => battle_id,
+        }
+    }
+
+    /// Identifies the market regardless of the outcome picked.
+    pub fn key(&self) -> String {
+        match self {
+            Market::Winner { battle_id, .. } => format!("{}:winner", battle_id),
+            Market::MethodOfVictory { battle_id, .. } => format!("{}:method", battle_id),
+            Market::TotalTurns { battle_id, line, .. } => format!("{}:turns:{}", battle_id, line),
+            Market::Overkill { battle_id, line, .. } => format!("{}:overkill:{}", battle_id, line),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Market::Winner { battle_id, .. } => format!("{} · Winner", battle_id),
+            Market::MethodOfVictory { battle_id, .. } => format!("{} · Method of victory", battle_id),
+            Market::TotalTurns { battle_id, line, .. } => format!("{} · Total turns {}", battle_id, line),
+            Market::Overkill { battle_id, line, .. } => format!("{} · Overkill {}", battle_id, line),
+        }
+    }
+
+    pub fn outcome(&self) -> String {
+        match self {
+            Market::Winner { fighter, .. } => fighter.clone(),
+            Market::MethodOfVictory { method, .. } => method.to_string(),
+            Market::TotalTurns { line, over: true, .. } => format!("over {}", line),
+            Market::TotalTurns { line, over: false, .. } => format!("under {}", line),
+            Market::Overkill { line, over: true, .. } => format!("overkill ≥ {}", line),
+            Market::Overkill { line, over: false, .. } => format!("overkill < {}", line),
+        }
+    }
+
+    /// Whether the picked outcome happened in the completed `battle`.
+    pub fn occurred(&self, battle: &BattleRecord) -> bool {
+        match self {
+            Market::Winner { fighter, .. } => battle.winner.as_deref() == Some(fighter.as_str()),
+            _ => BattleOutcome::of(&battle.events).occurred(self),
+        }
+    }
+}
+
+/// Rejects accumulated bets combining events from the same market, since at most one
+/// of them can occur.
+pub fn check_accumulator(events: &[(&str, &CassinoEvent)]) -> Result<(), String> {
+    for (i, (id1, event1)) in events.iter().enumerate() {
+        let Some(key) = event1.market_key() else { continue };
+        for (id2, event2) in &events[i + 1..] {
+            if event2.market_key().as_deref() == Some(key.as_str()) {
+                return Err(format!(
+                    "Events '{}' and '{}' are outcomes of the same market ({}) and can't both occur",
+                    id1, id2, key
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Why an event is ready to be settled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DueReason {
+    /// The linked battle completed, deciding whether the event occurred.
+    BattleCompleted { occurred: bool },
+    /// The expiry time passed on an event with no battle to decide it.
+    Expired,
+    /// The expiry time passed before the linked battle completed, so nothing decided
+    /// the event: it is voided and the stakes on it given back.
+    Voided,
+}
+
+/// How a bet ends once every event it is on is settled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BetOutcome {
+    Won,
+    Lost,
+    /// An event was voided and none of the others lost, the stake is given back.
+    Refunded,
+}
+
+impl BetOutcome {
+    /// The outcome of a bet on `events`, all of them settled.
+    pub fn of<'a>(events: impl IntoIterator<Item = &'a CompletedEvent>) -> Self {
+        let mut outcome = BetOutcome::Won;
+        for event in events {
+            if event.voided {
+                outcome = BetOutcome::Refunded;
+            } else if !event.result {
+                return BetOutcome::Lost;
+            }
+        }
+        outcome
+    }
+}
+
+impl CassinoEvent {
+    /// A new, unlinked event that never expires, with its initial odd recorded.
+    pub fn new(description: String, odd: f64) -> Self {
+        Self {
+            description,
+            odd,
+            battle: None,
+            market: None,
+            expires_at: None,
+            odds_history: vec![OddsChange {
+                odd,
+                source: OddsChangeSource::Initial,
+                timestamp: chrono::Local::now().to_rfc3339(),
+            }],
+        }
+    }
+
+    /// Changes the odd, recording the change in the history.
+    pub fn set_odd(&mut self, odd: f64, source: OddsChangeSource, timestamp: String) {
+        self.odd = odd;
+        self.odds_history.push(OddsChange { odd, source, timestamp });
+    }
+
+    /// The market the event belongs to. Events linked to a battle before markets
+    /// existed belong to that battle's winner market.
+    pub fn market_key(&self) -> Option<String> {
+        match (&self.market, &self.battle) {
+            (Some(market), _) => Some(market.key()),
+            (None, Some(link)) => Some(format!("{}:winner", link.battle_id)),
+            (None, None) => None,
+        }
+    }
+
+    /// Time left before the event locks at its expiry, negative once it has passed.
+    /// `None` for events without a (readable) expiry.
+    pub fn time_to_lock(&self, now: DateTime<FixedOffset>) -> Option<chrono::Duration> {
+        let expires_at = DateTime::parse_from_rfc3339(self.expires_at.as_deref()?).ok()?;
+        Some(expires_at - now)
+    }
+
+    /// Whether the event can be settled now, looking up its market's or linked battle among
+    /// `complete_battles`. Events with no battle and no expiry are never due, and events
+    /// whose battle wasn't fought by their expiry are voided.
+    pub fn due_reason(&self, complete_battles: &[BattleRecord], now: DateTime<FixedOffset>) -> Option<DueReason> {
+        if let Some(market) = &self.market {
+            let completed = complete_battles
+                .iter()
+                .find(|b| b.id == market.battle_id() && b.is_completed);
+            if let Some(battle) = completed {
+                return Some(DueReason::BattleCompleted {
+                    occurred: market.occurred(battle),
+                });
+            }
+        } else if let Some(link) = &self.battle {
+            let completed = complete_battles
+                .iter()
+                .find(|b| b.id == link.battle_id && b.is_completed);
+            if let Some(battle) = completed {
+                return Some(DueReason::BattleCompleted {
+                    occurred: battle.winner.as_deref() == Some(link.winner.as_str()),
+                });
+            }
+        }
+
+        let expires_at = self.expires_at.as_deref()?;
+        match DateTime::parse_from_rfc3339(expires_at) {
+            Ok(expiry) if expiry <= now && (self.market.is_some() || self.battle.is_some()) => Some(DueReason::Voided),
+            Ok(expiry) if expiry <= now => Some(DueReason::Expired),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -13,20 +349,32 @@ pub struct CompletedEvent {
     pub odd: f64,
     pub result: bool, // true if event occurred, false otherwise
     pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub odds_history: Vec<OddsChange>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub voided: bool, // nothing decided it, see `DueReason::Voided`
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ExpiredBet {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub bet_id: String,
     pub event_id: String,
     pub amount: f64,
     pub potential_win: f64,
     pub result: bool, // true if bet won, false if lost
     pub actual_payout: f64, // 0 if lost, potential_win if won
     pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub promotion_id: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub refunded: bool, // the event was voided, `actual_payout` is the stake given back
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ExpiredAccumulatedBet {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub bet_id: String,
     pub event_ids: Vec<String>,
     pub amount: f64,
     pub combined_odds: f64,
@@ -34,6 +382,8 @@ pub struct ExpiredAccumulatedBet {
     pub all_events_occurred: bool, // true if all events occurred, false otherwise
     pub actual_payout: f64, // 0 if lost, potential_win if won
     pub timestamp: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub refunded: bool, // an event was voided and none lost, `actual_payout` is the stake given back
 }
 
 #[derive(Serialize, Deserialize, Debug, Default)]
@@ -45,4 +395,440 @@ pub struct DoneEvents {
 pub struct ExpiredBets {
     pub expired_bets: Vec<ExpiredBet>,
     pub expired_accumulated_bets: Vec<ExpiredAccumulatedBet>,
-}
\ No newline at end of file
+}
+/// Countdown shown on the odds board, e.g. "1d 02h", "2h 05m" or "4m 09s".
+pub fn format_time_to_lock(time_to_lock: Option<chrono::Duration>) -> String {
+    let Some(left) = time_to_lock else { return "no lock".to_string() };
+    if left <= chrono::Duration::zero() {
+        return "locked".to_string();
+    }
+    let seconds = left.num_seconds();
+    let (days, hours, minutes) = (seconds / 86_400, seconds / 3_600 % 24, seconds / 60 % 60);
+    if days > 0 {
+        format!("{}d {:02}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else {
+        format!("{}m {:02}s", minutes, seconds % 60)
+    }
+}
+
+/// Live decimal odd of `fighter` winning a battle still being fought, from its latest
+/// `WinProbability` event. `margin` is the house's cut, e.g. 0.05 shortens every odd by
+/// 5%. `None` until the battle has an estimate or if `fighter` isn't in it.
+pub fn live_odd(events: &[BattleEvent], fighter: &str, margin: f64) -> Option<f64> {
+    let probability = events.iter().rev().find_map(|event| match event {
+        BattleEvent::WinProbability { fighter1, fighter2, fighter1_win_probability, .. } => {
+            if **fighter1 == *fighter {
+                Some(*fighter1_win_probability)
+            } else if **fighter2 == *fighter {
+                Some(1.0 - fighter1_win_probability)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    })?;
+    // A sure thing still pays a little, and a long shot doesn't pay forever
+    Some(1.0 / (probability.clamp(0.01, 0.99) * (1.0 + margin)))
+}
+
+/// Step chart of the odds over time, one column group per change, `height` rows tall.
+pub fn odds_chart(history: &[OddsChange], height: usize) -> String {
+    const STEP_WIDTH: usize = 4;
+
+    if history.is_empty() || height == 0 {
+        return String::new();
+    }
+
+    let min = history.iter().map(|c| c.odd).fold(f64::INFINITY, f64::min);
+    let max = history.iter().map(|c| c.odd).fold(f64::NEG_INFINITY, f64::max);
+    let rows = if max > min { height } else { 1 };
+    let row_of = |odd: f64| -> usize {
+        if rows == 1 {
+            0
+        } else {
+            ((odd - min) / (max - min) * (rows - 1) as f64).round() as usize
+        }
+    };
+
+    let mut out = String::new();
+    for row in (0..rows).rev() {
+        let label = if row == rows - 1 {
+            format!("{:>6.2}", max)
+        } else if row == 0 {
+            format!("{:>6.2}", min)
+        } else {
+            " ".repeat(6)
+        };
+        out.push_str(&label);
+        out.push_str(if row == 0 || row == rows - 1 { " ┤" } else { " │" });
+
+        for (i, change) in history.iter().enumerate() {
+            let level = row_of(change.odd);
+            let previous = if i == 0 { level } else { row_of(history[i - 1].odd) };
+            let (low, high) = (level.min(previous), level.max(previous));
+            // Vertical jump from the previous odd, then the new odd held until the next change
+            let joint = if row == level {
+                '●'
+            } else if row > low && row < high || row == previous && row != level {
+                '│'
+            } else {
+                ' '
+            };
+            let hold = if row == level { '─' } else { ' ' };
+            out.push(joint);
+            out.extend(std::iter::repeat_n(hold, STEP_WIDTH - 1));
+        }
+        out.push('\n');
+    }
+    out.push_str(&" ".repeat(7));
+    out.push('└');
+    out.push_str(&"─".repeat(history.len() * STEP_WIDTH));
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::{EffectSource, TickKind};
+
+    fn event(battle: Option<BattleLink>, expires_at: Option<&str>) -> CassinoEvent {
+        CassinoEvent {
+            description: "Acara wins".to_string(),
+            odd: 2.0,
+            battle,
+            market: None,
+            expires_at: expires_at.map(|e| e.to_string()),
+            odds_history: vec![],
+        }
+    }
+
+    fn change(odd: f64) -> OddsChange {
+        OddsChange {
+            odd,
+            source: OddsChangeSource::Manual,
+            timestamp: "2023-01-01T00:00:00+00:00".to_string(),
+        }
+    }
+
+    fn link(battle_id: &str, winner: &str) -> Option<BattleLink> {
+        Some(BattleLink {
+            battle_id: battle_id.to_string(),
+            winner: winner.to_string(),
+        })
+    }
+
+    fn battle(id: &str, winner: Option<&str>, is_completed: bool) -> BattleRecord {
+        BattleRecord {
+            winner: winner.map(|w| w.to_string()),
+            is_completed,
+            ..BattleRecord::test_battle(id, "Acara", "Usul")
+        }
+    }
+
+    fn at(timestamp: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap()
+    }
+
+    #[test]
+    fn test_unlinked_event_without_expiry_is_never_due() {
+        let event = event(None, None);
+        assert_eq!(event.due_reason(&[], at("2099-01-01T00:00:00Z")), None);
+    }
+
+    #[test]
+    fn test_linked_event_due_once_battle_completes() {
+        let event = event(link("battle_1", "Acara"), None);
+        let now = at("2023-01-02T00:00:00Z");
+
+        assert_eq!(event.due_reason(&[battle("battle_1", None, false)], now), None);
+        assert_eq!(
+            event.due_reason(&[battle("battle_1", Some("Acara"), true)], now),
+            Some(DueReason::BattleCompleted { occurred: true })
+        );
+        assert_eq!(
+            event.due_reason(&[battle("battle_1", Some("Usul"), true)], now),
+            Some(DueReason::BattleCompleted { occurred: false })
+        );
+    }
+
+    #[test]
+    fn test_event_due_after_expiry() {
+        let event = event(None, Some("2023-01-01T12:00:00+00:00"));
+        assert_eq!(event.due_reason(&[], at("2023-01-01T11:59:59Z")), None);
+        assert_eq!(event.due_reason(&[], at("2023-01-01T12:00:00Z")), Some(DueReason::Expired));
+    }
+
+    #[test]
+    fn test_linked_event_voided_when_battle_not_fought_by_expiry() {
+        let event = event(link("battle_1", "Acara"), Some("2023-01-01T12:00:00+00:00"));
+        let pending = [battle("battle_1", None, false)];
+        assert_eq!(event.due_reason(&pending, at("2023-01-01T11:59:59Z")), None);
+        assert_eq!(event.due_reason(&pending, at("2023-01-01T12:00:00Z")), Some(DueReason::Voided));
+    }
+
+    #[test]
+    fn test_bet_outcome_refunds_voided_events_unless_another_lost() {
+        let settled = |result, voided| CompletedEvent {
+            event_id: "event_1".to_string(),
+            description: "Acara wins".to_string(),
+            odd: 2.0,
+            result,
+            timestamp: "2023-01-01T12:00:00Z".to_string(),
+            odds_history: vec![],
+            voided,
+        };
+        let (occurred, missed, voided) = (settled(true, false), settled(false, false), settled(false, true));
+        assert_eq!(BetOutcome::of([&occurred]), BetOutcome::Won);
+        assert_eq!(BetOutcome::of([&missed]), BetOutcome::Lost);
+        assert_eq!(BetOutcome::of([&voided]), BetOutcome::Refunded);
+        assert_eq!(BetOutcome::of([&occurred, &voided]), BetOutcome::Refunded);
+        assert_eq!(BetOutcome::of([&voided, &missed]), BetOutcome::Lost);
+    }
+
+    #[test]
+    fn test_completed_battle_wins_over_expiry() {
+        let event = event(link("battle_1", "Acara"), Some("2023-01-01T12:00:00+00:00"));
+        let battles = [battle("battle_1", Some("Acara"), true)];
+        assert_eq!(
+            event.due_reason(&battles, at("2023-01-02T00:00:00Z")),
+            Some(DueReason::BattleCompleted { occurred: true })
+        );
+    }
+
+    #[test]
+    fn test_time_to_lock_counts_down_to_expiry() {
+        let expiring = event(None, Some("2023-01-01T12:00:00+00:00"));
+        assert_eq!(expiring.time_to_lock(at("2023-01-01T11:30:00Z")), Some(chrono::Duration::minutes(30)));
+        assert!(expiring.time_to_lock(at("2023-01-01T12:00:01Z")).unwrap() < chrono::Duration::zero());
+        assert_eq!(event(None, None).time_to_lock(at("2023-01-01T11:30:00Z")), None);
+    }
+
+    #[test]
+    fn test_format_time_to_lock() {
+        let format = |seconds| format_time_to_lock(Some(chrono::Duration::seconds(seconds)));
+        assert_eq!(format(93_600), "1d 02h");
+        assert_eq!(format(7_500), "2h 05m");
+        assert_eq!(format(249), "4m 09s");
+        assert_eq!(format(0), "locked");
+        assert_eq!(format_time_to_lock(None), "no lock");
+    }
+
+    #[test]
+    fn test_live_odd_follows_the_latest_estimate() {
+        let estimate = |turn, fighter1_win_probability| BattleEvent::WinProbability {
+            turn,
+            fighter1: "Acara".into(),
+            fighter2: "Usul".into(),
+            fighter1_win_probability,
+            fighter1_expected_hp: 20.0,
+            fighter2_expected_hp: 20.0,
+            rollouts: 100,
+        };
+        assert_eq!(live_odd(&[], "Acara", 0.0), None);
+
+        let events = vec![estimate(1, 0.5), estimate(4, 0.8)];
+        assert!((live_odd(&events, "Acara", 0.0).unwrap() - 1.25).abs() < 1e-9);
+        assert!((live_odd(&events, "Usul", 0.0).unwrap() - 5.0).abs() < 1e-9);
+        assert!(live_odd(&events, "Acara", 0.1).unwrap() < 1.25);
+        assert_eq!(live_odd(&events, "Kacheek", 0.0), None);
+        assert!((live_odd(&[estimate(7, 1.0)], "Usul", 0.0).unwrap() - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_new_event_records_initial_odd() {
+        let event = CassinoEvent::new("Acara wins".to_string(), 2.5);
+        assert_eq!(event.odds_history.len(), 1);
+        assert_eq!(event.odds_history[0].odd, 2.5);
+        assert_eq!(event.odds_history[0].source, OddsChangeSource::Initial);
+    }
+
+    #[test]
+    fn test_set_odd_appends_history() {
+        let mut event = CassinoEvent::new("Acara wins".to_string(), 2.5);
+        event.set_odd(3.0, OddsChangeSource::Manual, "2023-01-01T00:00:00+00:00".to_string());
+        event.set_odd(2.8, OddsChangeSource::Manual, "2023-01-02T00:00:00+00:00".to_string());
+
+        assert_eq!(event.odd, 2.8);
+        let sources: Vec<_> = event.odds_history.iter().map(|c| c.source).collect();
+        assert_eq!(
+            sources,
+            vec![OddsChangeSource::Initial, OddsChangeSource::Manual, OddsChangeSource::Manual]
+        );
+    }
+
+    #[test]
+    fn test_odds_chart_empty_history() {
+        assert_eq!(odds_chart(&[], 5), "");
+    }
+
+    #[test]
+    fn test_odds_chart_flat_odds_is_one_row() {
+        let chart = odds_chart(&[change(2.0), change(2.0)], 5);
+        let lines: Vec<&str> = chart.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "  2.00 ┤●───●───");
+    }
+
+    #[test]
+    fn test_odds_chart_steps_between_levels() {
+        let chart = odds_chart(&[change(1.0), change(3.0)], 3);
+        let lines: Vec<&str> = chart.lines().collect();
+        assert_eq!(lines[0], "  3.00 ┤    ●───");
+        assert_eq!(lines[1], "       │    │   ");
+        assert_eq!(lines[2], "  1.00 ┤●───│   ");
+        assert_eq!(lines[3], "       └────────");
+    }
+
+    #[test]
+    fn test_old_events_without_new_fields_still_load() {
+        let event: CassinoEvent = serde_json::from_str(r#"{"description": "Acara wins", "odd": 2.0}"#).unwrap();
+        assert!(event.battle.is_none());
+        assert!(event.expires_at.is_none());
+        assert!(event.odds_history.is_empty());
+    }
+
+    fn finished_battle(id: &str, winner: &str, last_action: BattleEvent, turn: u32, reason: BattleCompletionReason) -> BattleRecord {
+        BattleRecord {
+            events: vec![
+                last_action,
+                BattleEvent::BattleComplete {
+                    turn,
+                    winner: winner.into(),
+                    loser: "Usul".into(),
+                    winner_final_hp: 10,
+                    loser_final_hp: 0,
+                    completion_reason: reason,
+                },
+            ],
+            ..battle(id, Some(winner), true)
+        }
+    }
+
+    fn attack(turn: u32) -> BattleEvent {
+        BattleEvent::Attack {
+            turn,
+            actor: "Acara".into(),
+            target: "Usul".into(),
+            raw_damage: 10,
+            shield_value: 0,
+            actual_damage: 10,
+            is_killing_blow: false,
+            overkill_amount: 0,
+        }
+    }
+
+    fn market_event(market: Market) -> CassinoEvent {
+        CassinoEvent {
+            market: Some(market),
+            ..event(None, None)
+        }
+    }
+
+    #[test]
+    fn test_victory_method_of_battle() {
+        let knockout = finished_battle("battle_1", "Acara", attack(5), 5, BattleCompletionReason::HpDepleted("Usul".to_string()));
+        let spell = finished_battle(
+            "battle_2",
+            "Acara",
+            BattleEvent::SpellCast {
+                turn: 5,
+                actor: "Acara".into(),
+                target: "Usul".into(),
+                spell_name: "Fire Ball".to_string(),
+            },
+            5,
+            BattleCompletionReason::HpDepleted("Usul".to_string()),
+        );
+        let decision = finished_battle("battle_3", "Acara", attack(10), 10, BattleCompletionReason::MaxTurnsReached(10));
+
+        assert_eq!(VictoryMethod::of(&knockout), Some(VictoryMethod::Knockout));
+        assert_eq!(VictoryMethod::of(&spell), Some(VictoryMethod::Spell));
+        assert_eq!(VictoryMethod::of(&decision), Some(VictoryMethod::Decision));
+        assert_eq!(VictoryMethod::of(&battle("battle_4", None, true)), None);
+    }
+
+    #[test]
+    fn test_markets_settle_from_battle() {
+        let battle = finished_battle("battle_1", "Acara", attack(7), 7, BattleCompletionReason::HpDepleted("Usul".to_string()));
+        let market = |m: Market| market_event(m).due_reason(std::slice::from_ref(&battle), at("2023-01-02T00:00:00Z"));
+        let occurred = |o| Some(DueReason::BattleCompleted { occurred: o });
+
+        let id = "battle_1".to_string();
+        assert_eq!(market(Market::Winner { battle_id: id.clone(), fighter: "Acara".to_string() }), occurred(true));
+        assert_eq!(market(Market::Winner { battle_id: id.clone(), fighter: "Usul".to_string() }), occurred(false));
+        assert_eq!(market(Market::MethodOfVictory { battle_id: id.clone(), method: VictoryMethod::Knockout }), occurred(true));
+        assert_eq!(market(Market::TotalTurns { battle_id: id.clone(), line: 6.5, over: true }), occurred(true));
+        assert_eq!(market(Market::TotalTurns { battle_id: id, line: 6.5, over: false }), occurred(false));
+    }
+
+    #[test]
+    fn test_overkill_markets() {
+        let mut finisher = attack(4);
+        if let BattleEvent::Attack { is_killing_blow, overkill_amount, .. } = &mut finisher {
+            (*is_killing_blow, *overkill_amount) = (true, 12);
+        }
+        let battle = finished_battle("battle_1", "Acara", finisher, 4, BattleCompletionReason::HpDepleted("Usul".to_string()));
+        let overkill = |line, over| Market::Overkill { battle_id: "battle_1".to_string(), line, over };
+        assert_eq!(BattleOutcome::of(&battle.events).overkill(), Some(12));
+        assert!(overkill(10, true).occurred(&battle));
+        assert!(!overkill(10, false).occurred(&battle));
+        assert!(!overkill(13, true).occurred(&battle));
+        assert_eq!(overkill(10, true).outcome(), "overkill ≥ 10");
+        assert_ne!(overkill(10, true).key(), overkill(5, true).key());
+
+        // A decision has no finishing blow, so it always lands under the line
+        let decision = finished_battle("battle_1", "Acara", attack(10), 10, BattleCompletionReason::MaxTurnsReached(10));
+        assert_eq!(BattleOutcome::of(&decision.events).overkill(), None);
+        assert!(overkill(1, false).occurred(&decision));
+    }
+
+    #[test]
+    fn test_outcome_follows_the_battle() {
+        let source = EffectSource { kind: TickKind::Poison, spell: "Venom".to_string(), caster: "Acara".into() };
+        let poisoned = BattleEvent::HealthUpdate { fighter_name: "Usul".into(), from: 3, to: 0, turn: 3, source: Some(source) };
+        let battle = finished_battle("battle_1", "Acara", poisoned, 3, BattleCompletionReason::HpDepleted("Usul".to_string()));
+        let mut events = vec![attack(1), attack(2)];
+        events.extend(battle.events);
+
+        let mut outcome = BattleOutcome::default();
+        for event in &events[..2] {
+            outcome.on_event(event);
+        }
+        assert_eq!((outcome.winner(), outcome.method(), outcome.turns()), (None, None, 2));
+        for event in &events[2..] {
+            outcome.on_event(event);
+        }
+        assert_eq!(outcome, BattleOutcome::of(&events));
+        // The poison finished Usul after the attacks
+        assert_eq!((outcome.winner(), outcome.method(), outcome.turns()), (Some("Acara"), Some(VictoryMethod::Spell), 3));
+        assert!(outcome.occurred(&Market::TotalTurns { battle_id: "battle_1".to_string(), line: 2.5, over: true }));
+        assert!(!outcome.occurred(&Market::Winner { battle_id: "battle_1".to_string(), fighter: "Usul".to_string() }));
+    }
+
+    #[test]
+    fn test_accumulator_rejects_outcomes_of_the_same_market() {
+        let acara = market_event(Market::Winner { battle_id: "battle_1".to_string(), fighter: "Acara".to_string() });
+        let usul = market_event(Market::Winner { battle_id: "battle_1".to_string(), fighter: "Usul".to_string() });
+        let legacy_usul = event(link("battle_1", "Usul"), None);
+        let knockout = market_event(Market::MethodOfVictory { battle_id: "battle_1".to_string(), method: VictoryMethod::Knockout });
+        let other_battle = market_event(Market::Winner { battle_id: "battle_2".to_string(), fighter: "Usul".to_string() });
+        let unlinked = event(None, None);
+
+        assert!(check_accumulator(&[("event_1", &acara), ("event_2", &usul)]).is_err());
+        assert!(check_accumulator(&[("event_1", &acara), ("event_2", &legacy_usul)]).is_err());
+        assert!(check_accumulator(&[("event_1", &acara), ("event_3", &knockout)]).is_ok());
+        assert!(check_accumulator(&[("event_1", &acara), ("event_4", &other_battle), ("event_5", &unlinked), ("event_6", &unlinked)]).is_ok());
+    }
+
+    #[test]
+    fn test_total_turns_markets_with_different_lines_are_distinct() {
+        let over = Market::TotalTurns { battle_id: "battle_1".to_string(), line: 6.5, over: true };
+        let under = Market::TotalTurns { battle_id: "battle_1".to_string(), line: 8.5, over: false };
+        assert_ne!(over.key(), under.key());
+        assert_eq!(over.outcome(), "over 6.5");
+        assert_eq!(under.outcome(), "under 8.5");
+    }
+}
@@ -259,10 +259,24 @@ impl CassinoDisplay {
         println!("{}", "🎲 AVAILABLE EVENTS 🎲".color(self.config.color_theme.primary).bold());
         println!("{}", "═".repeat(60).color(self.config.color_theme.primary));
         
+        // Outcomes of the same market are listed together, events outside any market last
+        let mut sorted: Vec<(&String, &crate::cassino::CassinoEvent)> = events.iter().collect();
+        sorted.sort_by_key(|(event_id, event)| {
+            (event.market.is_none(), event.market.as_ref().map(|m| m.name()), event_id.to_string())
+        });
+        
+        let mut current_market = None;
         let mut event_count = 0;
-        for (event_id, event) in events {
+        for (event_id, event) in sorted {
             event_count += 1;
             
+            let market_name = event.market.as_ref().map(|m| m.name());
+            if event_count == 1 || market_name != current_market {
+                let header = market_name.clone().unwrap_or_else(|| "Other events".to_string());
+                println!("{}", format!("📊 {}", header).color(self.config.color_theme.info).bold());
+                current_market = market_name;
+            }
+            
             // Color coding based on odds (higher odds = more rare = different color)
             let odds_color = if event.odd >= 5.0 {
                 &self.config.color_theme.warning // High odds (rare events)
@@ -278,7 +292,8 @@ impl CassinoDisplay {
                  │ Description: {:<33} │\n\
                  │ Odds: {:<5.2}x {:<30} │\n\
                  └────────────────────────────────────────────────┘",
-                event_id, event.description, event.odd, ""
+                event_id, event.description, event.odd,
+                event.market.as_ref().map(|m| format!("on {}", m.outcome())).unwrap_or_default()
             );
             
             println!("{}", event_box.color(*odds_color));
@@ -292,6 +307,195 @@ impl CassinoDisplay {
         println!("{}", format!("📊 Total Events: {}", events.len()).color(self.config.color_theme.info));
     }
     
+    /// Lines of the live odds board: every open event grouped by market like the events
+    /// list, with its current odd, the amount staked on it and the time until it locks.
+    /// Lines have no trailing newline, so they can be drawn at any terminal position.
+    pub fn board_lines(
+        &self,
+        events: &std::collections::HashMap<String, crate::cassino::CassinoEvent>,
+        staked: &std::collections::HashMap<String, f64>,
+        now: chrono::DateTime<chrono::FixedOffset>,
+    ) -> Vec<String> {
+        let theme = &self.config.color_theme;
+        let mut lines = vec![
+            format!("{}  {}",
+                "🎲 ODDS BOARD 🎲".color(theme.primary).bold(),
+                format!("updated {} · q to quit", now.format("%H:%M:%S")).color(theme.info)
+            ),
+            "═".repeat(72).color(theme.primary).to_string(),
+        ];
+        if events.is_empty() {
+            lines.push("⚠️  No open events, waiting for new ones...".color(theme.warning).to_string());
+            return lines;
+        }
+
+        let mut sorted: Vec<(&String, &crate::cassino::CassinoEvent)> = events.iter().collect();
+        sorted.sort_by_key(|(event_id, event)| {
+            (event.market.is_none(), event.market.as_ref().map(|m| m.name()), event_id.to_string())
+        });
+
+        let mut current_market = None;
+        for (i, (event_id, event)) in sorted.into_iter().enumerate() {
+            let market_name = event.market.as_ref().map(|m| m.name());
+            if i == 0 || market_name != current_market {
+                let header = market_name.clone().unwrap_or_else(|| "Other events".to_string());
+                lines.push(format!("📊 {}", header).color(theme.info).bold().to_string());
+                current_market = market_name;
+            }
+
+            let outcome = event.market.as_ref().map(|m| m.outcome()).unwrap_or_else(|| event.description.clone());
+            let time_to_lock = event.time_to_lock(now);
+            let lock = crate::cassino::format_time_to_lock(time_to_lock);
+            let lock_color = match time_to_lock {
+                Some(left) if left <= chrono::Duration::minutes(5) => theme.error,
+                Some(_) => theme.secondary,
+                None => theme.info,
+            };
+            lines.push(format!(
+                "   {:<12} {:<28} {:>8} {:>12} {:>10}",
+                event_id,
+                outcome,
+                format!("{:.2}x", event.odd).color(theme.success).bold(),
+                format!("{:.2} staked", staked.get(event_id.as_str()).copied().unwrap_or(0.0)),
+                lock.color(lock_color)
+            ));
+        }
+        lines.push("═".repeat(72).color(theme.primary).to_string());
+        lines.push(format!("📊 Open events: {}", events.len()).color(theme.info).to_string());
+        lines
+    }
+
+    /// Display betting totals from the ledger, and optionally the daily bankroll chart
+    /// with each day's segment colored by its profit or loss
+    pub fn show_stats(&self, ledger: &crate::ledger::Ledger, chart: bool) {
+        use crate::ledger::TransactionKind;
+
+        println!();
+        println!("{}", "📊 BETTING STATS 📊".color(self.config.color_theme.primary).bold());
+        println!("{}", "═".repeat(60).color(self.config.color_theme.primary));
+
+        if ledger.transactions.is_empty() {
+            println!("{}", "No transactions yet, place a bet first with 'cassino bet'".color(self.config.color_theme.warning));
+            return;
+        }
+
+        let staked = ledger.total(TransactionKind::Stake);
+        let paid_out = ledger.total(TransactionKind::Payout);
+        let bonus = ledger.total(TransactionKind::Bonus);
+        let refunded = ledger.total(TransactionKind::Refund);
+        let balance = ledger.balance();
+        let balance_color = if balance >= 0.0 {
+            self.config.color_theme.success
+        } else {
+            self.config.color_theme.error
+        };
+        println!("💸 Total staked:   {:>10.2}", staked);
+        println!("💰 Total paid out: {:>10.2}", paid_out);
+        if bonus > 0.0 {
+            println!("🎁 Bonus paid out: {:>10.2}", bonus);
+        }
+        if refunded > 0.0 {
+            println!("↩️  Refunded:       {:>10.2}", refunded);
+        }
+        println!("{}", format!("📈 Profit:         {:>+10.2}", balance).color(balance_color).bold());
+        println!("🧾 Transactions:   {:>10}", ledger.transactions.len());
+
+        if !chart {
+            return;
+        }
+
+        let days = ledger.daily_bankroll();
+        println!();
+        println!("{}", "💹 BANKROLL BY DAY".color(self.config.color_theme.primary).bold());
+        for row in crate::ledger::bankroll_chart(&days, 10) {
+            print!("{}", row.label);
+            for (cell, day) in row.cells.iter().zip(&days) {
+                let color = if day.change >= 0.0 {
+                    self.config.color_theme.success
+                } else {
+                    self.config.color_theme.error
+                };
+                print!("{}", cell.color(color));
+            }
+            println!();
+        }
+        if let (Some(first), Some(last)) = (days.first(), days.last()) {
+            println!("{:>11}{} → {}", "", first.date, last.date);
+        }
+    }
+
+    /// Display the real balance next to the promotional credit still available,
+    /// which is kept out of the balance until it wins something
+    pub fn show_wallet(
+        &self,
+        ledger: &crate::ledger::Ledger,
+        promotions: &crate::promotions::Promotions,
+        now: chrono::DateTime<chrono::FixedOffset>,
+    ) {
+        println!();
+        println!("{}", "👛 WALLET 👛".color(self.config.color_theme.primary).bold());
+        println!("{}", "═".repeat(60).color(self.config.color_theme.primary));
+
+        let balance = ledger.balance();
+        let balance_color = if balance >= 0.0 {
+            self.config.color_theme.success
+        } else {
+            self.config.color_theme.error
+        };
+        println!("{}", format!("💵 Real balance:     {:>+10.2}", balance).color(balance_color).bold());
+        println!("🎟️  Free bet credit:  {:>10.2}", promotions.free_bet_credit(now));
+
+        let available: Vec<_> = promotions.available(now).collect();
+        if available.is_empty() {
+            println!("{}", "No promotions available".color(self.config.color_theme.warning));
+            return;
+        }
+        println!();
+        println!("{}", "🎁 PROMOTIONS".color(self.config.color_theme.primary).bold());
+        for promotion in available {
+            let expiry = match &promotion.expires_at {
+                Some(at) => format!("expires {}", at),
+                None => "never expires".to_string(),
+            };
+            println!("  {} {} ({})",
+                promotion.id.yellow(),
+                promotion.kind.to_string().color(self.config.color_theme.info),
+                expiry
+            );
+        }
+        println!("{}", "Use one with 'cassino bet --promotion <ID>'".color(self.config.color_theme.info));
+    }
+
+    /// Display every odds change of an event followed by a chart of the odds over time
+    pub fn show_odds_history(&self, event_id: &str, description: &str, history: &[crate::cassino::OddsChange]) {
+        println!();
+        println!("{}", format!("📈 ODDS HISTORY: {} ({})", event_id, description).color(self.config.color_theme.primary).bold());
+        println!("{}", "═".repeat(60).color(self.config.color_theme.primary));
+
+        if history.is_empty() {
+            println!("{}", "No odds changes were recorded for this event.".color(self.config.color_theme.warning));
+            return;
+        }
+
+        println!("{:<28} {:<16} {:>8}", "Timestamp", "Source", "Odds");
+        println!("{}", "─".repeat(60));
+        for change in history {
+            let timestamp = if change.timestamp.len() > 19 {
+                &change.timestamp[..19]
+            } else {
+                &change.timestamp
+            };
+            let source = match change.source {
+                crate::cassino::OddsChangeSource::Initial => "initial",
+                crate::cassino::OddsChangeSource::Manual => "manual",
+            };
+            println!("{:<28} {:<16} {:>7.2}x", timestamp, source, change.odd);
+        }
+
+        println!();
+        print!("{}", crate::cassino::odds_chart(history, 8).color(self.config.color_theme.secondary));
+    }
+
     /// Display error message with style
     pub fn show_error(&self, message: &str) {
         println!();
@@ -0,0 +1,99 @@
+// src/classes.rs
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A fighter's class, picked with the `class` field of their definition, e.g.
+/// `"class": "tank"`. Each class comes with a passive ability that
+/// `battle::process_turn_with_state` applies through the hooks below.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum FighterClass {
+    Tank,
+    Mage,
+    Berserker,
+    Cleric,
+}
+
+impl FighterClass {
+    pub const ALL: [FighterClass; 4] = [FighterClass::Tank, FighterClass::Mage, FighterClass::Berserker, FighterClass::Cleric];
+
+    pub fn passive(&self) -> &'static str {
+        match self {
+            FighterClass::Tank => "takes half damage from critical hits",
+            FighterClass::Mage => "burns, poisons and regens they cast last one more turn",
+            FighterClass::Berserker => "deals 50% more attack damage at half HP or less",
+            FighterClass::Cleric => "heals also cleanse a burn or poison",
+        }
+    }
+
+    /// Damage an attack deals to a fighter of this class, after the crit multiplier.
+    pub fn damage_taken(&self, damage: u32, is_crit: bool) -> u32 {
+        match self {
+            FighterClass::Tank if is_crit => damage - damage / 2,
+            _ => damage,
+        }
+    }
+
+    /// Damage an attack by a fighter of this class deals while they have `hp` of
+    /// their `max_hp` left, after the crit multiplier.
+    pub fn damage_dealt(&self, damage: u32, hp: u32, max_hp: u32) -> u32 {
+        match self {
+            FighterClass::Berserker if hp.saturating_mul(2) <= max_hp => damage + damage / 2,
+            _ => damage,
+        }
+    }
+
+    /// Turns an over-time effect cast by a fighter of this class lasts.
+    pub fn effect_turns(&self, turns: u32) -> u32 {
+        match self {
+            FighterClass::Mage => turns + 1,
+            _ => turns,
+        }
+    }
+
+    /// Whether a fighter of this class gets rid of a burn or poison when they heal.
+    pub fn cleanses(&self) -> bool {
+        *self == FighterClass::Cleric
+    }
+}
+
+impl fmt::Display for FighterClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FighterClass::Tank => "Tank",
+            FighterClass::Mage => "Mage",
+            FighterClass::Berserker => "Berserker",
+            FighterClass::Cleric => "Cleric",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passives_only_touch_their_own_hook() {
+        assert_eq!(FighterClass::Tank.damage_taken(9, true), 5);
+        assert_eq!(FighterClass::Tank.damage_taken(9, false), 9);
+        assert_eq!(FighterClass::Mage.damage_taken(9, true), 9);
+
+        assert_eq!(FighterClass::Berserker.damage_dealt(10, 50, 100), 15);
+        assert_eq!(FighterClass::Berserker.damage_dealt(10, 51, 100), 10);
+        assert_eq!(FighterClass::Tank.damage_dealt(10, 1, 100), 10);
+
+        assert_eq!(FighterClass::Mage.effect_turns(3), 4);
+        assert_eq!(FighterClass::Cleric.effect_turns(3), 3);
+
+        let cleansing: Vec<_> = FighterClass::ALL.into_iter().filter(FighterClass::cleanses).collect();
+        assert_eq!(cleansing, vec![FighterClass::Cleric]);
+    }
+
+    #[test]
+    fn test_class_names() {
+        assert_eq!(serde_json::to_string(&FighterClass::Berserker).unwrap(), "\"berserker\"");
+        assert_eq!(serde_json::from_str::<FighterClass>("\"cleric\"").unwrap(), FighterClass::Cleric);
+        assert_eq!(FighterClass::Mage.to_string(), "Mage");
+    }
+}
@@ -0,0 +1,345 @@
+// src/config.rs
+use crate::display::{BattleDisplayConfig, BattleDisplayOverrides};
+use chrono::Duration;
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Settings both CLIs read from `~/.config/rinha/config.toml`, or the file `--config`
+/// names. Every section is optional, and without a file everything is as it was before
+/// CLIs had one.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CliConfig {
+    pub assets: AssetsConfig,
+    /// Laid over `BattleDisplayConfig::default()` for every battle played back
+    pub display: BattleDisplayOverrides,
+    pub seed: SeedPolicy,
+    pub battles: BattlesConfig,
+    pub house: HouseRules,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AssetsConfig {
+    /// Where the fighters, battles, bets and the rest are, relative to the working
+    /// directory. The `storage.json` in it can move single files further.
+    pub dir: PathBuf,
+}
+
+impl Default for AssetsConfig {
+    fn default() -> Self {
+        Self { dir: PathBuf::from("assets") }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BattlesConfig {
+    /// How long pending battles are kept before `battle prune` and `run-due` remove
+    /// them, like `7d` (see `parse_duration`). Kept forever without it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_ttl: Option<String>,
+}
+
+impl BattlesConfig {
+    pub fn pending_ttl(&self) -> Option<Duration> {
+        self.pending_ttl.as_deref().and_then(|ttl| parse_duration(ttl).ok())
+    }
+}
+
+/// A duration written as a whole number and a unit: `90s`, `30m`, `12h`, `7d` or `2w`.
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid duration '{}', expected a number followed by s, m, h, d or w", text);
+    let text = text.trim();
+    let unit_at = text.len().checked_sub(1).filter(|&at| text.is_char_boundary(at)).ok_or_else(invalid)?;
+    let amount: i64 = text[..unit_at].parse().map_err(|_| invalid())?;
+    if amount < 0 {
+        return Err(invalid());
+    }
+    let duration = match &text[unit_at..] {
+        "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    };
+    duration.ok_or_else(invalid)
+}
+
+/// Which seed battles, series, brawls and seasons get when `--seed` is left out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum SeedPolicy {
+    /// A new random seed every time
+    #[default]
+    Random,
+    /// Always the same seed, so every run can be replayed
+    Fixed { seed: u64 },
+}
+
+impl SeedPolicy {
+    /// The seed to use given the `--seed` flag, which wins over the policy. `None`
+    /// leaves picking a random one to the command.
+    pub fn seed(self, flag: Option<u64>) -> Option<u64> {
+        flag.or(match self {
+            SeedPolicy::Random => None,
+            SeedPolicy::Fixed { seed } => Some(seed),
+        })
+    }
+}
+
+/// Limits the cassino puts on the bets it takes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HouseRules {
+    /// Smallest stake taken, bets have to be above 0 regardless
+    pub min_bet: f64,
+    pub max_bet: Option<f64>,
+    /// Most events one accumulated bet can combine
+    pub max_accumulator_events: Option<usize>,
+}
+
+impl Default for HouseRules {
+    fn default() -> Self {
+        Self { min_bet: 0.0, max_bet: None, max_accumulator_events: None }
+    }
+}
+
+impl HouseRules {
+    /// Refuses a stake the house doesn't take.
+    pub fn check_stake(&self, amount: f64) -> Result<(), String> {
+        if amount < self.min_bet {
+            return Err(format!("The house takes bets of at least {:.2}", self.min_bet));
+        }
+        match self.max_bet {
+            Some(max_bet) if amount > max_bet => Err(format!("The house takes bets of at most {:.2}", max_bet)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Refuses an accumulated bet combining more events than the house allows.
+    pub fn check_accumulator(&self, events: usize) -> Result<(), String> {
+        match self.max_accumulator_events {
+            Some(max) if events > max => Err(format!("Accumulated bets can combine at most {} events", max)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl CliConfig {
+    /// Reads the config at `path`. A missing file is the defaults, a malformed one an error.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let config: Self = toml::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        config.validate().map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.display.speed.is_some_and(|speed| speed.is_nan() || speed <= 0.0) {
+            return Err("display.speed must be greater than 0".to_string());
+        }
+        if let Some(ttl) = &self.battles.pending_ttl {
+            parse_duration(ttl).map_err(|e| format!("battles.pending_ttl: {}", e))?;
+        }
+        if self.house.min_bet.is_nan() || self.house.min_bet < 0.0 {
+            return Err("house.min_bet can't be negative".to_string());
+        }
+        if self.house.max_bet.is_some_and(|max_bet| max_bet.is_nan() || max_bet < self.house.min_bet) {
+            return Err("house.max_bet can't be below house.min_bet".to_string());
+        }
+        if self.house.max_accumulator_events.is_some_and(|max| max < 2) {
+            return Err("house.max_accumulator_events must allow at least 2 events".to_string());
+        }
+        Ok(())
+    }
+
+    /// `name` in the assets directory.
+    pub fn asset(&self, name: &str) -> String {
+        self.assets.dir.join(name).to_string_lossy().into_owned()
+    }
+
+    /// The display config battles are played back with, before any flag changes it.
+    pub fn display_config(&self) -> BattleDisplayConfig {
+        let mut config = BattleDisplayConfig::default();
+        config.merge(self.display.clone());
+        config
+    }
+
+    /// The defaults with every display setting spelled out, as `config init` writes them.
+    pub fn template() -> Self {
+        let display = serde_json::to_value(BattleDisplayConfig::default())
+            .and_then(serde_json::from_value)
+            .unwrap_or_default();
+        Self { display, ..Self::default() }
+    }
+
+    /// The config as `config show` prints it and `config init` writes it.
+    pub fn to_toml(&self) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+}
+
+/// `$XDG_CONFIG_HOME/rinha/config.toml`, or `~/.config/rinha/config.toml` without it.
+pub fn default_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("rinha").join("config.toml"))
+}
+
+/// The `--config` flag both CLIs take, see `load`.
+#[derive(Args, Debug, Clone)]
+pub struct ConfigArgs {
+    /// Config file to read instead of ~/.config/rinha/config.toml
+    #[arg(long = "config", global = true, value_name = "PATH")]
+    pub path: Option<PathBuf>,
+}
+
+impl ConfigArgs {
+    /// The file the config is read from, if there is a home to look in.
+    pub fn path(&self) -> Option<PathBuf> {
+        self.path.clone().or_else(default_path)
+    }
+
+    /// The config the flag points to. A file named with `--config` has to exist, the
+    /// default one doesn't.
+    pub fn load(&self) -> Result<CliConfig, Box<dyn std::error::Error>> {
+        if let Some(path) = self.path.as_ref().filter(|path| !path.exists()) {
+            return Err(format!("Config file {} not found", path.display()).into());
+        }
+        match self.path() {
+            Some(path) => CliConfig::load(&path),
+            None => Ok(CliConfig::default()),
+        }
+    }
+}
+
+/// The `config` subcommands of both CLIs, which share the file. Run before the config
+/// is loaded, so they work on a missing or broken one.
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum ConfigAction {
+    /// Write a config file with every setting at its default
+    Init {
+        /// Replace the file if there is one
+        #[arg(long)]
+        force: bool,
+    },
+    /// Print the settings in effect and where they were read from
+    Show,
+}
+
+impl ConfigAction {
+    pub fn run(&self, args: &ConfigArgs) -> Result<(), Box<dyn std::error::Error>> {
+        let path = args.path().ok_or("No home directory to keep the config in, pass --config")?;
+        match self {
+            ConfigAction::Init { force } => {
+                if path.exists() && !force {
+                    return Err(format!("{} already exists, pass --force to replace it", path.display()).into());
+                }
+                if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&path, CliConfig::template().to_toml()?)?;
+                println!("✅ Wrote the default config to {}", path.display());
+            }
+            ConfigAction::Show => {
+                if path.exists() {
+                    println!("# Read from {}", path.display());
+                } else {
+                    println!("# {} doesn't exist, these are the defaults", path.display());
+                }
+                print!("{}", CliConfig::load(&path)?.to_toml()?);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::DisplayMode;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_config_reads_every_section() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, r#"
+            [assets]
+            dir = "/srv/rinha"
+
+            [display]
+            mode = "compact"
+            speed = 2.0
+
+            [seed]
+            policy = "fixed"
+            seed = 42
+
+            [house]
+            min_bet = 1.0
+            max_bet = 500.0
+        "#).unwrap();
+
+        let config = CliConfig::load(&path).unwrap();
+        assert_eq!(config.asset("bets.json"), Path::new("/srv/rinha").join("bets.json").to_string_lossy());
+        assert_eq!(config.display_config().mode, DisplayMode::Compact);
+        assert_eq!(config.display_config().base_delay_ms, BattleDisplayConfig::default().base_delay_ms);
+        assert_eq!(config.seed.seed(None), Some(42));
+        assert_eq!(config.seed.seed(Some(7)), Some(7));
+        assert!(config.house.check_stake(0.5).is_err());
+        assert!(config.house.check_stake(501.0).is_err());
+        assert!(config.house.check_stake(20.0).is_ok());
+        assert!(config.house.check_accumulator(10).is_ok());
+        assert_eq!(config.battles.pending_ttl(), None);
+
+        // What `config init` writes changes nothing
+        fs::write(&path, CliConfig::template().to_toml().unwrap()).unwrap();
+        let template = CliConfig::load(&path).unwrap();
+        assert_eq!(template.display.base_delay_ms, Some(BattleDisplayConfig::default().base_delay_ms));
+        assert_eq!(template.display_config().mode, BattleDisplayConfig::default().mode);
+        assert_eq!(template.seed, SeedPolicy::Random);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("7d"), Ok(Duration::days(7)));
+        assert_eq!(parse_duration("12h"), Ok(Duration::hours(12)));
+        assert_eq!(parse_duration("2w"), Ok(Duration::weeks(2)));
+        for invalid in ["", "d", "7", "7y", "-1d", "seven d", "7é"] {
+            assert!(parse_duration(invalid).is_err(), "{}", invalid);
+        }
+
+        let config: CliConfig = toml::from_str("[battles]\npending_ttl = \"3d\"\n").unwrap();
+        assert_eq!(config.battles.pending_ttl(), Some(Duration::days(3)));
+        let config: CliConfig = toml::from_str("[battles]\npending_ttl = \"soon\"\n").unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_missing_config_is_the_defaults_but_a_bad_one_fails() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        assert_eq!(CliConfig::load(&path).unwrap(), CliConfig::default());
+        assert_eq!(CliConfig::default().seed.seed(None), None);
+
+        let args = ConfigArgs { path: Some(path.clone()) };
+        assert!(args.load().is_err());
+
+        fs::write(&path, "[house]\nmin_bet = 10.0\nmax_bet = 5.0\n").unwrap();
+        assert!(CliConfig::load(&path).unwrap_err().to_string().contains("max_bet"));
+        fs::write(&path, "[seed]\npolicy = \"sometimes\"\n").unwrap();
+        assert!(CliConfig::load(&path).is_err());
+        fs::write(&path, "[asets]\ndir = \"x\"\n").unwrap();
+        assert!(CliConfig::load(&path).is_err());
+    }
+}
@@ -0,0 +1,301 @@
+// src/dice.rs
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How many times the dice are rolled for one result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RollMode {
+    #[default]
+    Normal,
+    Advantage,    // Rolled twice, the higher sum is kept
+    Disadvantage, // Rolled twice, the lower sum is kept
+}
+
+/// `count` dice with `sides` faces each, rolled and summed, written like `d20` or `2d10`.
+/// An `adv` or `dis` suffix, like `d20adv`, rolls them twice with advantage or disadvantage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Dice {
+    pub count: u32,
+    pub sides: u32,
+    pub mode: RollMode,
+}
+
+impl Dice {
+    pub const D6: Dice = Dice { count: 1, sides: 6, mode: RollMode::Normal };
+    pub const D12: Dice = Dice { count: 1, sides: 12, mode: RollMode::Normal };
+    pub const D20: Dice = Dice { count: 1, sides: 20, mode: RollMode::Normal };
+    pub const TWO_D10: Dice = Dice { count: 2, sides: 10, mode: RollMode::Normal };
+
+    pub fn new(count: u32, sides: u32) -> Result<Self, String> {
+        if count == 0 {
+            return Err("Dice need at least one die".to_string());
+        }
+        if sides < 2 {
+            return Err(format!("A die needs at least 2 sides, got {}", sides));
+        }
+        Ok(Self { count, sides, mode: RollMode::Normal })
+    }
+
+    /// The same dice, rolled with advantage.
+    pub fn with_advantage(self) -> Self {
+        Self { mode: RollMode::Advantage, ..self }
+    }
+
+    /// The same dice, rolled with disadvantage.
+    pub fn with_disadvantage(self) -> Self {
+        Self { mode: RollMode::Disadvantage, ..self }
+    }
+
+    /// Rolls every die and returns the sum, or the kept sum with advantage or disadvantage.
+    pub fn roll<R: Rng>(&self, rng: &mut R) -> u32 {
+        let mut sum = || (0..self.count).map(|_| rng.random_range(1..=self.sides)).sum::<u32>();
+        match self.mode {
+            RollMode::Normal => sum(),
+            RollMode::Advantage => sum().max(sum()),
+            RollMode::Disadvantage => sum().min(sum()),
+        }
+    }
+
+    /// Lowest possible roll, every die on its 1 face.
+    pub fn min(&self) -> u32 {
+        self.count
+    }
+
+    /// Highest possible roll, every die on its top face.
+    pub fn max(&self) -> u32 {
+        self.count * self.sides
+    }
+
+    /// A roll is a critical success when every die lands on its top face.
+    pub fn is_positive_crit(&self, roll: u32) -> bool {
+        roll == self.max()
+    }
+
+    /// A roll is a fumble when every die lands on its 1 face.
+    pub fn is_negative_crit(&self, roll: u32) -> bool {
+        roll == self.min()
+    }
+
+    /// Every possible roll with the number of ways to get it, lowest first.
+    /// The ways add up to `sides ^ count`, squared when rolling twice.
+    pub fn outcomes(&self) -> Vec<(u32, u64)> {
+        let single = self.single_outcomes();
+        if self.mode == RollMode::Normal {
+            return single;
+        }
+        // Ways to keep a sum of at most `roll`, from the ways to roll at most it once
+        let total: u64 = single.iter().map(|(_, w)| w).sum();
+        let mut at_most = 0;
+        single
+            .into_iter()
+            .map(|(roll, w)| {
+                let (below, up_to) = (at_most, at_most + w);
+                at_most = up_to;
+                let ways = match self.mode {
+                    RollMode::Advantage => up_to * up_to - below * below,
+                    _ => (total - below) * (total - below) - (total - up_to) * (total - up_to),
+                };
+                (roll, ways)
+            })
+            .collect()
+    }
+
+    /// `outcomes` of rolling the dice once.
+    fn single_outcomes(&self) -> Vec<(u32, u64)> {
+        // ways[i] is the number of ways to roll min() + i
+        let mut ways: Vec<u64> = vec![1];
+        for _ in 0..self.count {
+            let mut next = vec![0u64; ways.len() + self.sides as usize - 1];
+            for (i, w) in ways.iter().enumerate() {
+                for face in 0..self.sides as usize {
+                    next[i + face] += w;
+                }
+            }
+            ways = next;
+        }
+        ways.into_iter()
+            .enumerate()
+            .map(|(i, w)| (self.min() + i as u32, w))
+            .collect()
+    }
+
+    /// Average roll.
+    pub fn mean(&self) -> f64 {
+        if self.mode == RollMode::Normal {
+            return self.count as f64 * (self.sides as f64 + 1.0) / 2.0;
+        }
+        let outcomes = self.outcomes();
+        let total: u64 = outcomes.iter().map(|(_, w)| w).sum();
+        outcomes.iter().map(|(roll, w)| *roll as f64 * *w as f64).sum::<f64>() / total as f64
+    }
+}
+
+impl Default for Dice {
+    fn default() -> Self {
+        Self::D20
+    }
+}
+
+impl fmt::Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.count == 1 {
+            write!(f, "d{}", self.sides)?;
+        } else {
+            write!(f, "{}d{}", self.count, self.sides)?;
+        }
+        match self.mode {
+            RollMode::Normal => Ok(()),
+            RollMode::Advantage => write!(f, "adv"),
+            RollMode::Disadvantage => write!(f, "dis"),
+        }
+    }
+}
+
+impl FromStr for Dice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid dice '{}', expected something like d20, 2d10 or d20adv", s);
+        let lower = s.trim().to_lowercase();
+        let (expression, mode) = if let Some(expression) = lower.strip_suffix("adv") {
+            (expression, RollMode::Advantage)
+        } else if let Some(expression) = lower.strip_suffix("dis") {
+            (expression, RollMode::Disadvantage)
+        } else {
+            (lower.as_str(), RollMode::Normal)
+        };
+        let (count, sides) = expression.split_once('d').ok_or_else(invalid)?;
+        let count = if count.is_empty() { 1 } else { count.parse().map_err(|_| invalid())? };
+        let sides = sides.parse().map_err(|_| invalid())?;
+        Ok(Self { mode, ..Self::new(count, sides)? })
+    }
+}
+
+impl TryFrom<String> for Dice {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Dice> for String {
+    fn from(dice: Dice) -> Self {
+        dice.to_string()
+    }
+}
+
+/// The dice a fighter rolls for each kind of roll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActionDice {
+    pub attack: Dice,
+    pub defense: Dice,
+    pub heal: Dice,
+    pub initiative: Dice,
+}
+
+impl ActionDice {
+    /// The same dice for every roll.
+    pub fn all(dice: Dice) -> Self {
+        Self {
+            attack: dice,
+            defense: dice,
+            heal: dice,
+            initiative: dice,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!("d20".parse::<Dice>().unwrap(), Dice::D20);
+        assert_eq!("2d10".parse::<Dice>().unwrap(), Dice::TWO_D10);
+        assert_eq!(" 1D6 ".parse::<Dice>().unwrap(), Dice::D6);
+        assert_eq!(Dice::TWO_D10.to_string(), "2d10");
+        assert_eq!(Dice::D12.to_string(), "d12");
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_dice() {
+        for s in ["20", "d", "0d6", "d1", "2x10", "d-4", "adv", "d20advdis", "d20 adv"] {
+            assert!(s.parse::<Dice>().is_err(), "{} should be rejected", s);
+        }
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let dice = ActionDice { attack: Dice::TWO_D10, ..ActionDice::default() };
+        let json = serde_json::to_string(&dice).unwrap();
+        assert!(json.contains("\"attack\":\"2d10\""));
+        assert_eq!(serde_json::from_str::<ActionDice>(&json).unwrap(), dice);
+
+        let partial: ActionDice = serde_json::from_str(r#"{"heal": "d6"}"#).unwrap();
+        assert_eq!(partial.heal, Dice::D6);
+        assert_eq!(partial.attack, Dice::D20);
+        assert!(serde_json::from_str::<Dice>("\"d1\"").is_err());
+    }
+
+    #[test]
+    fn test_rolls_stay_within_faces() {
+        let mut rng = rand::rng();
+        let dice = Dice::TWO_D10;
+        for _ in 0..200 {
+            let roll = dice.roll(&mut rng);
+            assert!(roll >= dice.min() && roll <= dice.max());
+        }
+    }
+
+    #[test]
+    fn test_crits_are_relative_to_faces() {
+        assert!(Dice::D6.is_positive_crit(6));
+        assert!(!Dice::D20.is_positive_crit(6));
+        let two_d10 = Dice::TWO_D10;
+        assert!(two_d10.is_positive_crit(20));
+        assert!(two_d10.is_negative_crit(2));
+        assert!(!two_d10.is_negative_crit(1));
+    }
+
+    #[test]
+    fn test_outcomes_cover_every_combination() {
+        let outcomes = Dice::new(2, 6).unwrap().outcomes();
+        assert_eq!(outcomes.first(), Some(&(2, 1)));
+        assert_eq!(outcomes.iter().find(|(roll, _)| *roll == 7), Some(&(7, 6)));
+        assert_eq!(outcomes.last(), Some(&(12, 1)));
+        assert_eq!(outcomes.iter().map(|(_, w)| w).sum::<u64>(), 36);
+        assert_eq!(Dice::D20.outcomes().len(), 20);
+    }
+
+    #[test]
+    fn test_advantage_and_disadvantage() {
+        assert_eq!("d20adv".parse::<Dice>().unwrap(), Dice::D20.with_advantage());
+        assert_eq!("2D10DIS".parse::<Dice>().unwrap(), Dice::TWO_D10.with_disadvantage());
+        assert_eq!(Dice::D12.with_advantage().to_string(), "d12adv");
+        assert_eq!(serde_json::to_string(&Dice::TWO_D10.with_disadvantage()).unwrap(), "\"2d10dis\"");
+
+        // Keeping the higher of two d6 rolls a 6 eleven times out of 36
+        let advantage = Dice::D6.with_advantage().outcomes();
+        assert_eq!(advantage.first(), Some(&(1, 1)));
+        assert_eq!(advantage.last(), Some(&(6, 11)));
+        assert_eq!(advantage.iter().map(|(_, w)| w).sum::<u64>(), 36);
+        let disadvantage = Dice::D6.with_disadvantage().outcomes();
+        assert_eq!((disadvantage.first(), disadvantage.last()), (Some(&(1, 11)), Some(&(6, 1))));
+        assert_eq!(Dice::D6.with_advantage().mean() + Dice::D6.with_disadvantage().mean(), 7.0);
+        assert!(Dice::D20.with_advantage().mean() > Dice::D20.mean());
+
+        let mut rng = rand::rng();
+        for dice in [Dice::TWO_D10.with_advantage(), Dice::D6.with_disadvantage()] {
+            for _ in 0..200 {
+                let roll = dice.roll(&mut rng);
+                assert!(roll >= dice.min() && roll <= dice.max());
+            }
+        }
+    }
+}
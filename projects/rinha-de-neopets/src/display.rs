@@ -1,32 +1,376 @@
-use crate::battle::BattleEvent;
+use crate::battle::{BattleCompletionReason, BattleEvent, EffectSource, TickKind};
+use crate::battle_log::BattleLog;
 use crate::neopets::Neopet;
+use crate::playback::Playback;
+use crate::render::BattleRenderer;
+use crate::series::SeriesRecord;
 use colored::*;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
 use indicatif::{ProgressBar, ProgressStyle, MultiProgress};
-use std::collections::HashMap;
+use macro_workshop::config_struct;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, IsTerminal};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
-
-/// Configuration for battle display animations and timing
-#[derive(Debug, Clone)]
-pub struct BattleDisplayConfig {
-    pub enable_delays: bool,
-    pub base_delay_ms: u64,
-    pub critical_delay_ms: u64,
-    pub spell_delay_ms: u64,
-    pub use_spinners: bool,
-    pub streaming_effect: bool,
+use std::time::{Duration, Instant};
+use term_anim::Pacer;
+
+config_struct! {
+    /// Configuration for battle display animations and timing
+    #[derive(Debug, Clone)]
+    pub struct BattleDisplayConfig with overrides BattleDisplayOverrides {
+        pub enable_delays: bool = true,
+        pub base_delay_ms: u64 = 600,      // Increased from 300ms
+        pub critical_delay_ms: u64 = 1200, // Increased from 800ms
+        pub spell_delay_ms: u64 = 800,     // Increased from 500ms
+        pub use_spinners: bool = true,
+        pub streaming_effect: bool = true,
+        pub win_probability_bar: bool = true, // false falls back to plain-text percentages
+        pub mode: DisplayMode = DisplayMode::Animated,
+        pub speed: f64 = 1.0, // Playback speed, every delay is divided by it (2.0 plays twice as fast)
+    }
+}
+
+/// How battle events are rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplayMode {
+    /// Spinners, delays and health bars, for watching a battle
+    #[default]
+    Animated,
+    /// One plain line per turn, for running many battles in sequence or CI logs
+    Compact,
+}
+
+/// Cells of the win-probability bar
+const WIN_BAR_WIDTH: usize = 30;
+
+/// How many of the bar's `width` cells belong to fighter 1.
+fn win_bar_split(fighter1_win_probability: f64, width: usize) -> usize {
+    ((fighter1_win_probability.clamp(0.0, 1.0) * width as f64).round() as usize).min(width)
+}
+
+/// Tug-of-war bar between two fighters, fighter 1 pulling from the left.
+pub fn win_probability_bar(fighter1: &str, fighter2: &str, fighter1_win_probability: f64, width: usize) -> String {
+    let left = win_bar_split(fighter1_win_probability, width);
+    format!(
+        "{} {:>3.0}% {}{} {:.0}% {}",
+        fighter1,
+        fighter1_win_probability * 100.0,
+        "█".repeat(left),
+        "░".repeat(width - left),
+        (1.0 - fighter1_win_probability) * 100.0,
+        fighter2
+    )
+}
+
+/// Plain-text fallback of the win-probability bar.
+pub fn win_probability_text(fighter1: &str, fighter2: &str, fighter1_win_probability: f64) -> String {
+    format!(
+        "Win chance: {} {:.0}% - {:.0}% {}",
+        fighter1,
+        fighter1_win_probability * 100.0,
+        (1.0 - fighter1_win_probability) * 100.0,
+        fighter2
+    )
+}
+
+/// Fighter 1's rollout win probability over the battle, one block per
+/// `WinProbability` event, or `None` if the battle had no estimates.
+pub fn win_probability_sparkline(events: &[BattleEvent]) -> Option<String> {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let line: String = events
+        .iter()
+        .filter_map(|event| match event {
+            BattleEvent::WinProbability { fighter1_win_probability, .. } => {
+                Some(BLOCKS[(fighter1_win_probability.clamp(0.0, 1.0) * 7.0).round() as usize])
+            }
+            _ => None,
+        })
+        .collect();
+    (!line.is_empty()).then_some(line)
+}
+
+/// Plain-text standings of a series: the score, then one line per game played.
+pub fn series_standings_lines(series: &SeriesRecord) -> Vec<String> {
+    let (wins1, wins2) = series.score();
+    let status = match series.winner() {
+        Some(winner) => format!("{} wins the series", winner),
+        None => format!("first to {} wins", series.wins_needed()),
+    };
+    let mut lines = vec![format!(
+        "{} {} - {} {} (best of {}, {})",
+        series.fighter1, wins1, wins2, series.fighter2, series.best_of, status
+    )];
+    lines.extend(series.games.iter().map(|game| {
+        format!("G{}: {} wins ({}, seed {})", game.number, game.winner, game.battle_id, game.seed)
+    }));
+    lines
+}
+
+/// Prints the series score in color, with the games played so far below it.
+pub fn display_series_standings(series: &SeriesRecord) {
+    let (wins1, wins2) = series.score();
+    println!("📊 {} {} - {} {}  (best of {})",
+        series.fighter1.bright_cyan().bold(),
+        wins1.to_string().bright_yellow().bold(),
+        wins2.to_string().bright_yellow().bold(),
+        series.fighter2.bright_red().bold(),
+        series.best_of
+    );
+    for line in series_standings_lines(series).iter().skip(1) {
+        println!("   {}", line.dimmed());
+    }
+    match series.winner() {
+        Some(winner) => println!("🏆 {} wins the series!", winner.bright_yellow().bold()),
+        None => println!("   First to {} wins", series.wins_needed()),
+    }
+}
+
+/// One summarized line per turn, e.g. `T3: Pikachu hits Charizard for 12 (85→73)`.
+/// Turn 0 holds the initiative rolls.
+pub fn compact_turn_lines(events: &[BattleEvent]) -> Vec<String> {
+    let mut turns: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+    let (mut attack_crit, mut attack_fumble) = (false, false);
+
+    for (i, event) in events.iter().enumerate() {
+        // Damage and healing are followed by the HP change they caused
+        let hp_change = |fighter: &str| match events.get(i + 1) {
+            Some(BattleEvent::HealthUpdate { fighter_name, from, to, .. }) if **fighter_name == *fighter => {
+                format!(" ({}→{})", from, to)
+            }
+            _ => String::new(),
+        };
+
+        let (turn, part) = match event {
+            BattleEvent::Roll { turn: 0, actor, dice, .. } => (0, format!("{} rolls {}", actor, dice)),
+            BattleEvent::Roll { goal, is_positive_crit, is_negative_crit, .. } => {
+                if goal == "attack" {
+                    (attack_crit, attack_fumble) = (*is_positive_crit, *is_negative_crit);
+                }
+                continue;
+            }
+            BattleEvent::Attack { turn, actor, target, actual_damage, is_killing_blow, overkill_amount, .. } => {
+                let finisher = match (*is_killing_blow, *overkill_amount) {
+                    (false, _) => String::new(),
+                    (true, 0) => ", exact kill".to_string(),
+                    (true, overkill) => format!(", overkill {}", overkill),
+                };
+                let part = if attack_fumble {
+                    format!("{} fumbles against {}", actor, target)
+                } else if *actual_damage == 0 {
+                    format!("{} misses {}", actor, target)
+                } else {
+                    let verb = if attack_crit { "crits" } else { "hits" };
+                    format!("{} {} {} for {}{}{}", actor, verb, target, actual_damage, hp_change(target), finisher)
+                };
+                (*turn, part)
+            }
+            BattleEvent::Heal { turn, actor, amount } => {
+                let part = if *amount == 0 {
+                    format!("{} fails to heal", actor)
+                } else {
+                    format!("{} heals {}{}", actor, amount, hp_change(actor))
+                };
+                (*turn, part)
+            }
+            BattleEvent::SpellCast { turn, actor, target, spell_name } => {
+                (*turn, format!("{} casts {} on {}", actor, spell_name, target))
+            }
+            BattleEvent::SpellOnCooldown { turn, actor, spell_name, .. } => {
+                (*turn, format!("{}'s {} is on cooldown", actor, spell_name))
+            }
+            BattleEvent::Defend { turn, actor } => (*turn, format!("{} defends", actor)),
+            BattleEvent::Taunt { turn, actor, target, penalty } => {
+                (*turn, format!("{} taunts {} (-{})", actor, target, penalty))
+            }
+            BattleEvent::Dodge { turn, actor, attacker } => (*turn, format!("{} dodges {}", actor, attacker)),
+            BattleEvent::Block { turn, actor, blocked, .. } => (*turn, format!("{} blocks {}", actor, blocked)),
+            BattleEvent::KnockedOut { turn, fighter, by, remaining } => {
+                (*turn, format!("{} is knocked out by {} ({} left)", fighter, by, remaining))
+            }
+            BattleEvent::FatigueApplied { turn, bonus_damage, .. } => (*turn, format!("fatigue +{}", bonus_damage)),
+            BattleEvent::ArenaEffect { turn, arena, fighter, hp_change: change } if *change < 0 => {
+                (*turn, format!("{} hurts {} for {}{}", arena, fighter, -change, hp_change(fighter)))
+            }
+            BattleEvent::ArenaEffect { turn, arena, fighter, hp_change: change } => {
+                (*turn, format!("{} boosts {}'s heal by {:+}", arena, fighter, change))
+            }
+            BattleEvent::BattleComplete { turn, winner, loser, winner_final_hp, loser_final_hp, completion_reason } => {
+                let part = match completion_reason {
+                    BattleCompletionReason::HpDepleted(_) => {
+                        format!("{} knocks out {} with {} HP left", winner, loser, winner_final_hp)
+                    }
+                    BattleCompletionReason::MaxTurnsReached(_) => {
+                        format!("{} wins on HP ({} vs {})", winner, winner_final_hp, loser_final_hp)
+                    }
+                    BattleCompletionReason::LastStanding(_) => {
+                        format!("{} is the last one standing with {} HP", winner, winner_final_hp)
+                    }
+                };
+                (*turn, part)
+            }
+            BattleEvent::LootDropped { turn, winner, items, .. } if !items.is_empty() => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                (*turn, format!("{} loots {}", winner, items.join(", ")))
+            }
+            BattleEvent::HealthUpdate { turn, fighter_name, from, to, source: Some(source) } => {
+                let what = match source.kind {
+                    TickKind::Burn => "burns",
+                    TickKind::Poison => "takes poison",
+                    TickKind::Regen => "regenerates",
+                };
+                (*turn, format!("{} {} ({}→{})", fighter_name, what, from, to))
+            }
+            BattleEvent::WinProbability { turn, fighter1, fighter1_win_probability, .. } => {
+                (*turn, format!("{} {:.0}% to win", fighter1, fighter1_win_probability * 100.0))
+            }
+            BattleEvent::LootDropped { .. } | BattleEvent::HealthUpdate { .. } | BattleEvent::Prediction { .. } => continue,
+        };
+        turns.entry(turn).or_default().push(part);
+    }
+
+    turns
+        .into_iter()
+        .map(|(turn, parts)| format!("T{}: {}", turn, parts.join(" | ")))
+        .collect()
+}
+
+/// What `BattleDisplay` waits out its delays with, so they can be skipped.
+pub trait Clock: Send {
+    fn sleep(&self, duration: Duration);
+
+    /// Whether the viewer asked to skip to the summary, the remaining events aren't shown.
+    fn skipping(&self) -> bool {
+        false
+    }
+}
+
+/// Really waits, the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// Never waits, only adds up how long it was asked to, for tests and fast-forwarding.
+/// Clones share the total.
+#[derive(Debug, Clone, Default)]
+pub struct InstantClock {
+    slept: Arc<Mutex<Duration>>,
+}
+
+impl InstantClock {
+    pub fn slept(&self) -> Duration {
+        *self.slept.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Clock for InstantClock {
+    fn sleep(&self, duration: Duration) {
+        *self.slept.lock().unwrap_or_else(|e| e.into_inner()) += duration;
+    }
 }
 
-impl Default for BattleDisplayConfig {
+/// Waits like `SystemClock` while reading the keyboard, when stdin is a terminal, with
+/// the `Playback` keys: `+` and `-` double or halve the speed, space pauses and
+/// resumes, and `s` (or Ctrl-C, which raw mode keeps from interrupting) skips to the
+/// summary. The terminal is only in raw mode while waiting, so the display prints as usual.
+#[derive(Debug)]
+pub struct KeyboardClock {
+    interactive: bool,
+    /// The speed is on top of the display's own.
+    playback: Cell<Playback>,
+}
+
+impl KeyboardClock {
+    pub fn new() -> Self {
+        Self { interactive: io::stdin().is_terminal(), playback: Cell::new(Playback::default()) }
+    }
+
+    pub fn playback(&self) -> Playback {
+        self.playback.get()
+    }
+
+    /// Applies a key press to the playback.
+    pub fn handle_key(&self, key: KeyEvent) {
+        let mut playback = self.playback.get();
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            playback.skip();
+        } else {
+            playback.handle_key(key.code);
+        }
+        self.playback.set(playback);
+    }
+
+    /// Reads keys until `duration` (at the current speed) is over and playback isn't paused.
+    fn wait(&self, duration: Duration) -> io::Result<()> {
+        let _raw = RawMode::enter()?;
+        let mut remaining = duration.div_f64(self.playback().speed());
+        loop {
+            let playback = self.playback();
+            if playback.is_skipping() || (!playback.is_paused() && remaining.is_zero()) {
+                return Ok(());
+            }
+            let wait = if playback.is_paused() { Duration::from_millis(250) } else { remaining };
+            let start = Instant::now();
+            let pressed = event::poll(wait)?;
+            if !playback.is_paused() {
+                remaining = remaining.saturating_sub(start.elapsed());
+            }
+            if !pressed {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else { continue };
+            if key.kind == KeyEventKind::Press {
+                self.handle_key(key);
+                // What is left of the delay goes by at the new speed
+                remaining = remaining.mul_f64(playback.speed() / self.playback().speed());
+            }
+        }
+    }
+}
+
+impl Default for KeyboardClock {
     fn default() -> Self {
-        Self {
-            enable_delays: true,
-            base_delay_ms: 600,      // Increased from 300ms
-            critical_delay_ms: 1200, // Increased from 800ms
-            spell_delay_ms: 800,     // Increased from 500ms
-            use_spinners: true,
-            streaming_effect: true,
+        Self::new()
+    }
+}
+
+impl Clock for KeyboardClock {
+    fn sleep(&self, duration: Duration) {
+        if self.playback().is_skipping() {
+            return;
         }
+        if !self.interactive || self.wait(duration).is_err() {
+            thread::sleep(duration.div_f64(self.playback().speed()));
+        }
+    }
+
+    fn skipping(&self) -> bool {
+        self.playback().is_skipping()
+    }
+}
+
+/// Raw mode for as long as it lives.
+struct RawMode;
+
+impl RawMode {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(RawMode)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
     }
 }
 
@@ -40,6 +384,14 @@ pub struct BattleDisplay {
     fighter2_current_hp: u32,
     config: BattleDisplayConfig,
     multi_progress: Option<MultiProgress>,
+    clock: Box<dyn Clock>,
+    /// Whether the entrance and header were shown, when rendering event by event.
+    opened: bool,
+    /// The turn being shown, and how many of its events were.
+    turn: Option<u32>,
+    events_in_turn: usize,
+    /// Events of the turn being played in compact mode, printed as one line once it ends.
+    compact_turn: Vec<BattleEvent>,
 }
 
 impl BattleDisplay {
@@ -57,9 +409,28 @@ impl BattleDisplay {
             } else {
                 None
             },
+            clock: Box::new(SystemClock),
+            opened: false,
+            turn: None,
+            events_in_turn: 0,
+            compact_turn: Vec::new(),
         }
     }
+
+    /// Waits out the delays with `clock` instead of sleeping.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
     
+    /// Sleeps for `duration_ms` at the configured playback speed
+    fn pause(&self, duration_ms: u64) {
+        let duration = Pacer::Speed(self.config.speed).scaled(Duration::from_millis(duration_ms));
+        if !duration.is_zero() {
+            self.clock.sleep(duration);
+        }
+    }
+
     /// Add suspenseful delay with optional spinner
     fn suspenseful_delay(&self, duration_ms: u64, message: &str, use_spinner: bool) {
         if !self.config.enable_delays {
@@ -88,13 +459,13 @@ impl BattleDisplay {
             let steps = (actual_duration / 100) as u32;
             for i in 0..steps {
                 pb.set_position(i as u64);
-                thread::sleep(Duration::from_millis(100));
+                self.pause(100);
             }
             
             pb.finish_and_clear();
         } else {
             // Simple delay without spinner
-            thread::sleep(Duration::from_millis(actual_duration));
+            self.pause(actual_duration);
         }
     }
     
@@ -122,7 +493,7 @@ impl BattleDisplay {
             let steps = 25; // Show spinner for ~2.5 seconds
             for i in 0..steps {
                 pb.set_position(i as u64);
-                thread::sleep(Duration::from_millis(100));
+                self.pause(100);
             }
             
             pb.finish_and_clear();
@@ -202,75 +573,87 @@ impl BattleDisplay {
             return;
         }
 
-        // Dramatic entrance
-        self.dramatic_entrance();
+        if self.config.mode == DisplayMode::Compact {
+            for line in compact_turn_lines(events) {
+                println!("{}", line);
+            }
+            return;
+        }
 
-        // Group events by turn for better organization
-        let mut events_by_turn: HashMap<u32, Vec<&BattleEvent>> = HashMap::new();
+        self.open(health_state);
         for event in events {
-            let turn = match event {
-                BattleEvent::Roll { turn, .. } => *turn,
-                BattleEvent::Attack { turn, .. } => *turn,
-                BattleEvent::Heal { turn, .. } => *turn,
-                BattleEvent::SpellCast { turn, .. } => *turn,
-                BattleEvent::HealthUpdate { turn, .. } => *turn, // Health updates now have turns
-                BattleEvent::BattleComplete { turn, .. } => *turn,
-            };
-            events_by_turn.entry(turn).or_insert_with(Vec::new).push(event);
+            if self.clock.skipping() {
+                break;
+            }
+            self.play_event(event);
         }
+        self.close();
+    }
 
-        // Sort turns (0 is initiative phase)
-        let mut turns: Vec<u32> = events_by_turn.keys().cloned().collect();
-        turns.sort_unstable();
-
-        // Display header with animation
+    /// Dramatic entrance and header, then the starting HP if `health_state` is given.
+    fn open(&mut self, health_state: Option<(u32, u32)>) {
+        self.dramatic_entrance();
         self.animate_header();
         
-        // Display initial health bars if health state is provided
         if let Some((hp1, hp2)) = health_state {
             println!("\n{}", "Initial Status:".bright_white().bold());
             self.display_health_bars_with_effect(hp1, hp2);
         }
         
         println!("{}", "═".repeat(70).bright_black());
+        self.opened = true;
+    }
 
-        // Display events grouped by turn with streaming effects
-        for turn in turns {
-            let turn_events = &events_by_turn[&turn];
-            
+    /// Shows `event` under its turn's header, closing the previous turn when it starts
+    /// a new one (0 is the initiative phase).
+    fn play_event(&mut self, event: &BattleEvent) {
+        let turn = event.turn();
+        if self.turn == Some(turn) {
+            // Small delay between events in the same turn
+            self.pause(500); // Increased from 300ms // Increased from 150ms
+        } else {
+            self.close_turn();
             if turn == 0 {
-                // Initiative phase
                 self.animate_initiative_phase();
             } else {
                 self.animate_turn_header(turn);
             }
+            self.turn = Some(turn);
+            self.events_in_turn = 0;
+        }
 
-            // Display events with spinner suspense (no streaming text)
-            for (i, event) in turn_events.iter().enumerate() {
-                self.display_event_with_spinner(event, i == 0);
-                
-                // Small delay between events in the same turn
-                if i < turn_events.len() - 1 {
-                    thread::sleep(Duration::from_millis(500)); // Increased from 300ms // Increased from 150ms
-                }
-            }
+        // Spinner suspense (no streaming text)
+        self.display_event_with_spinner(event, self.events_in_turn == 0);
+        self.events_in_turn += 1;
+    }
 
-            // Add spacing between turns (except after initiative)
-            if turn != 0 {
-                println!();
-                
-                // Show current HP status after each turn
-                self.display_turn_status(turn);
-                
-                // Dramatic pause between turns
-                if self.config.enable_delays {
-                    self.suspenseful_delay(600, "Preparing next turn...", true);
-                }
-            }
+    /// Spacing and the HP status after a turn, except the initiative.
+    fn close_turn(&mut self) {
+        let Some(turn) = self.turn.take().filter(|&turn| turn != 0) else { return };
+        println!();
+        self.display_turn_status(turn);
+        
+        // Dramatic pause between turns
+        if self.config.enable_delays {
+            self.suspenseful_delay(600, "Preparing next turn...", true);
         }
+    }
 
-        // Display footer with animation
+    fn close(&mut self) {
+        self.close_turn();
+        if self.clock.skipping() {
+            println!("{}", "⏩ Skipped to the summary".bright_black());
+        }
         self.animate_footer();
+        self.opened = false;
+    }
+
+    /// Prints the compact line of the turn collected so far.
+    fn flush_compact_turn(&mut self) {
+        for line in compact_turn_lines(&self.compact_turn) {
+            println!("{}", line);
+        }
+        self.compact_turn.clear();
     }
     
     /// Display current HP status at the end of a turn with style
@@ -291,13 +674,13 @@ impl BattleDisplay {
             let steps = 6; // Show spinner for ~0.6 seconds
             for i in 0..steps {
                 pb.set_position(i as u64);
-                thread::sleep(Duration::from_millis(100));
+                self.pause(100);
             }
             
             pb.finish_and_clear();
         } else {
             // Simple delay for suspense
-            thread::sleep(Duration::from_millis(500));
+            self.pause(500);
         }
         
         println!("\n{}", format!(" Turn {} Status ", turn).bright_blue().bold());
@@ -325,8 +708,9 @@ impl BattleDisplay {
             // Animated health bar filling
             let bar_width = 25;
             for i in 0..=bar_width {
-                let filled1 = (bar_width as f64 * percentage1 as f64 / 100.0 * i as f64 / bar_width as f64) as usize;
-                let filled2 = (bar_width as f64 * percentage2 as f64 / 100.0 * i as f64 / bar_width as f64) as usize;
+                // Overhealed fighters are past 100%, they just fill the bar
+                let filled1 = ((bar_width as f64 * percentage1 as f64 / 100.0 * i as f64 / bar_width as f64) as usize).min(bar_width);
+                let filled2 = ((bar_width as f64 * percentage2 as f64 / 100.0 * i as f64 / bar_width as f64) as usize).min(bar_width);
                 
                 let bar1 = "█".repeat(filled1) + &"░".repeat(bar_width - filled1);
                 let bar2 = "█".repeat(filled2) + &"░".repeat(bar_width - filled2);
@@ -346,14 +730,14 @@ impl BattleDisplay {
                     self.fighter2_current_hp.to_string().bright_white()
                 );
                 std::io::Write::flush(&mut std::io::stdout()).unwrap();
-                thread::sleep(Duration::from_millis(30));
+                self.pause(30);
             }
             println!(); // New line after animation
         } else {
             // Static health bars
             let bar_width = 25;
-            let filled1 = (bar_width as f64 * percentage1 as f64 / 100.0) as usize;
-            let filled2 = (bar_width as f64 * percentage2 as f64 / 100.0) as usize;
+            let filled1 = ((bar_width as f64 * percentage1 as f64 / 100.0) as usize).min(bar_width);
+            let filled2 = ((bar_width as f64 * percentage2 as f64 / 100.0) as usize).min(bar_width);
             
             let bar1 = "█".repeat(filled1) + &"░".repeat(bar_width - filled1);
             let bar2 = "█".repeat(filled2) + &"░".repeat(bar_width - filled2);
@@ -405,7 +789,7 @@ impl BattleDisplay {
             let steps = 20; // Show spinner for ~2.0 seconds
             for i in 0..steps {
                 pb.set_position(i as u64);
-                thread::sleep(Duration::from_millis(100));
+                self.pause(100);
             }
             
             pb.finish_and_clear();
@@ -438,7 +822,7 @@ impl BattleDisplay {
             let steps = 12; // Show spinner for ~1.2 seconds
             for i in 0..steps {
                 pb.set_position(i as u64);
-                thread::sleep(Duration::from_millis(100));
+                self.pause(100);
             }
             
             pb.finish_and_clear();
@@ -468,7 +852,7 @@ impl BattleDisplay {
             let steps = 8; // Show spinner for ~0.8 seconds
             for i in 0..steps {
                 pb.set_position(i as u64);
-                thread::sleep(Duration::from_millis(100));
+                self.pause(100);
             }
             
             pb.finish_and_clear();
@@ -487,11 +871,18 @@ impl BattleDisplay {
     /// Display a single event with spinner suspense (no streaming text)
     fn display_event_with_spinner(&mut self, event: &BattleEvent, is_first: bool) {
         match event {
-            BattleEvent::Roll { actor, dice, final_value, is_positive_crit, is_negative_crit, goal, .. } => {
-                self.display_roll_with_spinner(actor, *dice, *final_value, *is_positive_crit, *is_negative_crit, goal, is_first);
+            BattleEvent::Roll { actor, dice, dice_expr, final_value, is_positive_crit, is_negative_crit, goal, .. } => {
+                let rolled = match dice_expr {
+                    Some(expr) => format!("{} ({})", dice, expr),
+                    None => dice.to_string(),
+                };
+                self.display_roll_with_spinner(actor, &rolled, *final_value, *is_positive_crit, *is_negative_crit, goal, is_first);
             }
-            BattleEvent::Attack { actor, target, actual_damage, .. } => {
+            BattleEvent::Attack { actor, target, actual_damage, is_killing_blow, overkill_amount, .. } => {
                 self.display_attack_with_spinner(actor, target, *actual_damage);
+                if *is_killing_blow {
+                    self.display_killing_blow(actor, *overkill_amount);
+                }
             }
             BattleEvent::Heal { actor, amount, .. } => {
                 self.display_heal_with_spinner(actor, *amount);
@@ -499,20 +890,60 @@ impl BattleDisplay {
             BattleEvent::SpellCast { actor, target, spell_name, .. } => {
                 self.display_spell_with_spinner(actor, target, spell_name);
             }
-            BattleEvent::HealthUpdate { fighter_name, from, to, .. } => {
+            BattleEvent::SpellOnCooldown { actor, spell_name, turns_left, .. } => {
+                self.display_spell_on_cooldown(actor, spell_name, *turns_left);
+            }
+            BattleEvent::Defend { actor, .. } => {
+                self.display_defend(actor);
+            }
+            BattleEvent::Taunt { actor, target, penalty, .. } => {
+                self.display_taunt(actor, target, *penalty);
+            }
+            BattleEvent::Dodge { actor, attacker, .. } => {
+                self.display_dodge(actor, attacker);
+            }
+            BattleEvent::Block { actor, attacker, blocked, .. } => {
+                self.display_block(actor, attacker, *blocked);
+            }
+            BattleEvent::KnockedOut { fighter, by, remaining, .. } => {
+                self.display_knocked_out(fighter, by, *remaining);
+            }
+            BattleEvent::FatigueApplied { level, bonus_damage, .. } => {
+                self.display_fatigue(*level, *bonus_damage);
+            }
+            BattleEvent::ArenaEffect { arena, fighter, hp_change, .. } => {
+                self.display_arena_effect(arena, fighter, *hp_change);
+            }
+            BattleEvent::Prediction { fighter1, fighter2, fighter1_win_probability, .. } => {
+                self.display_prediction(fighter1, fighter2, *fighter1_win_probability);
+            }
+            BattleEvent::WinProbability { fighter1, fighter2, fighter1_win_probability, fighter1_expected_hp, fighter2_expected_hp, rollouts, .. } => {
+                self.display_prediction(fighter1, fighter2, *fighter1_win_probability);
+                println!("  {}", format!(
+                    "Expected HP after {} rollouts: {} {:.0} - {:.0} {}",
+                    rollouts, fighter1, fighter1_expected_hp, fighter2_expected_hp, fighter2
+                ).dimmed());
+            }
+            BattleEvent::HealthUpdate { fighter_name, from, to, source, .. } => {
+                if let Some(source) = source {
+                    self.display_effect_tick(fighter_name, *from, *to, source);
+                }
                 // Process the health update and show the change
                 self.process_health_update(fighter_name, *from, *to);
             }
             BattleEvent::BattleComplete { turn, winner, loser, winner_final_hp, loser_final_hp, completion_reason } => {
                 self.display_battle_complete_with_spinner(*turn, winner, loser, *winner_final_hp, *loser_final_hp, completion_reason);
             }
+            BattleEvent::LootDropped { winner, loser, items, .. } => {
+                self.display_loot_with_spinner(winner, loser, items);
+            }
         }
     }
     
     /// Display dice roll event with spinner suspense (no streaming text)
-    fn display_roll_with_spinner(&self, actor: &str, dice: u8, final_value: u32, is_positive_crit: bool, is_negative_crit: bool, goal: &str, is_first: bool) {
+    fn display_roll_with_spinner(&self, actor: &str, dice: &str, final_value: u32, is_positive_crit: bool, is_negative_crit: bool, goal: &str, is_first: bool) {
         if !is_first {
-            thread::sleep(Duration::from_millis(400)); // Increased from 200ms
+            self.pause(400); // Increased from 200ms
         }
         
         let goal_icon = match goal {
@@ -555,7 +986,7 @@ impl BattleDisplay {
             let steps = (spin_duration / 100) as u32;
             for i in 0..steps {
                 pb.set_position(i as u64);
-                thread::sleep(Duration::from_millis(100));
+                self.pause(100);
             }
             
             pb.finish_and_clear();
@@ -566,16 +997,16 @@ impl BattleDisplay {
             } else {
                 self.config.base_delay_ms / 2
             };
-            thread::sleep(Duration::from_millis(delay));
+            self.pause(delay);
         }
 
         // Now print the complete event instantly
         let dice_display = if is_positive_crit {
-            format!("{}", dice).on_bright_yellow().red().bold()
+            dice.on_bright_yellow().red().bold()
         } else if is_negative_crit {
-            format!("{}", dice).on_red().white().bold()
+            dice.on_red().white().bold()
         } else {
-            dice.to_string().normal()
+            dice.normal()
         };
 
         println!("  {} {} rolls {} for {}: {} = {}", 
@@ -601,7 +1032,7 @@ impl BattleDisplay {
     
     /// Display attack event with spinner suspense (no streaming text)
     fn display_attack_with_spinner(&self, actor: &str, target: &str, actual_damage: u32) {
-        thread::sleep(Duration::from_millis(500)); // Increased from 300ms
+        self.pause(500); // Increased from 300ms
         
         // Show spinner for suspense
         if self.config.use_spinners {
@@ -619,12 +1050,12 @@ impl BattleDisplay {
             let steps = ((self.config.base_delay_ms * 3/4) / 100) as u32; // 75% of base delay
             for i in 0..steps {
                 pb.set_position(i as u64);
-                thread::sleep(Duration::from_millis(100));
+                self.pause(100);
             }
             
             pb.finish_and_clear();
         } else {
-            thread::sleep(Duration::from_millis(self.config.base_delay_ms));
+            self.pause(self.config.base_delay_ms);
         }
 
         // Now print the complete event instantly
@@ -646,9 +1077,17 @@ impl BattleDisplay {
         }
     }
     
+    fn display_killing_blow(&self, actor: &str, overkill: u32) {
+        if overkill == 0 {
+            println!("  🎯 {} by {}: not a point of damage wasted!", "EXACT KILL".bright_yellow().bold(), actor.bright_blue().bold());
+        } else {
+            println!("  💀 {} by {} with {} overkill!", "FINISHING BLOW".bright_red().bold(), actor.bright_blue().bold(), overkill.to_string().bright_red().bold());
+        }
+    }
+
     /// Display healing event with spinner suspense (no streaming text)
     fn display_heal_with_spinner(&self, actor: &str, amount: u32) {
-        thread::sleep(Duration::from_millis(500)); // Increased from 300ms
+        self.pause(500); // Increased from 300ms
         
         // Show spinner for suspense
         if self.config.use_spinners {
@@ -666,12 +1105,12 @@ impl BattleDisplay {
             let steps = ((self.config.base_delay_ms * 3/4) / 100) as u32; // 75% of base delay
             for i in 0..steps {
                 pb.set_position(i as u64);
-                thread::sleep(Duration::from_millis(100));
+                self.pause(100);
             }
             
             pb.finish_and_clear();
         } else {
-            thread::sleep(Duration::from_millis(self.config.base_delay_ms));
+            self.pause(self.config.base_delay_ms);
         }
 
         // Now print the complete event instantly
@@ -699,12 +1138,12 @@ impl BattleDisplay {
             let steps = ((self.config.spell_delay_ms * 3/4) / 100) as u32; // 75% of spell delay
             for i in 0..steps {
                 pb.set_position(i as u64);
-                thread::sleep(Duration::from_millis(100));
+                self.pause(100);
             }
             
             pb.finish_and_clear();
         } else {
-            thread::sleep(Duration::from_millis(self.config.spell_delay_ms));
+            self.pause(self.config.spell_delay_ms);
         }
 
         // Now print the complete event instantly
@@ -715,13 +1154,141 @@ impl BattleDisplay {
         );
     }
     
+    /// Reveal the loot one item at a time after opening the chest
+    fn display_loot_with_spinner(&self, winner: &str, loser: &str, items: &[crate::loot::Item]) {
+        if self.config.use_spinners {
+            let pb = self.multi_progress.as_ref().unwrap().add(
+                ProgressBar::new_spinner()
+                    .with_style(
+                        ProgressStyle::default_spinner()
+                            .template("{spinner:.yellow} {msg}")
+                            .unwrap()
+                    )
+                    .with_message(format!("Opening {}'s loot chest...", loser))
+            );
+            pb.enable_steady_tick(Duration::from_millis(100));
+
+            let steps = (self.config.base_delay_ms / 100) as u32;
+            for i in 0..steps {
+                pb.set_position(i as u64);
+                self.pause(100);
+            }
+
+            pb.finish_and_clear();
+        }
+
+        println!("\n  🎁 {} found loot on {}!", winner.bright_cyan().bold(), loser.bright_red());
+        for item in items {
+            if self.config.enable_delays {
+                self.pause(self.config.base_delay_ms / 2);
+            }
+            let item_text = match item.rarity {
+                crate::loot::Rarity::Common => item.to_string().normal(),
+                crate::loot::Rarity::Rare => item.to_string().bright_blue().bold(),
+                crate::loot::Rarity::Legendary => item.to_string().bright_yellow().bold(),
+            };
+            println!("     {}", item_text);
+        }
+    }
+    
+    /// Display the win probability as a tug-of-war bar, or as percentages when the bar is off
+    fn display_prediction(&self, fighter1: &str, fighter2: &str, fighter1_win_probability: f64) {
+        if !self.config.win_probability_bar {
+            println!("  {}", win_probability_text(fighter1, fighter2, fighter1_win_probability).dimmed());
+            return;
+        }
+
+        let fighter1 = fighter1.bright_cyan().bold().to_string();
+        let fighter2 = fighter2.bright_red().bold().to_string();
+        println!("  📈 {}", win_probability_bar(&fighter1, &fighter2, fighter1_win_probability, WIN_BAR_WIDTH));
+    }
+    
+    /// Display a spell the actor couldn't cast yet
+    fn display_spell_on_cooldown(&self, actor: &str, spell_name: &str, turns_left: u32) {
+        self.pause(300);
+        println!("  ⏳ {}'s {} is on cooldown for {} more turns",
+            actor.bright_cyan().bold(),
+            spell_name.bright_magenta(),
+            turns_left.to_string().bright_white()
+        );
+    }
+
+    /// Display a dodged attack
+    fn display_dodge(&self, actor: &str, attacker: &str) {
+        self.pause(300);
+        println!("  💨 {} {} {}'s attack!",
+            actor.bright_red().bold(),
+            "DODGES".bright_cyan().bold(),
+            attacker.bright_blue().bold()
+        );
+    }
+
+    /// Display a partially blocked attack
+    fn display_block(&self, actor: &str, attacker: &str, blocked: u32) {
+        self.pause(300);
+        println!("  🛡️  {} blocks {} of {}'s attack",
+            actor.bright_red().bold(),
+            blocked.to_string().bright_cyan().bold(),
+            attacker.bright_blue().bold()
+        );
+    }
+
+    /// Display a free-for-all fighter going down
+    fn display_knocked_out(&self, fighter: &str, by: &str, remaining: u32) {
+        self.pause(300);
+        println!("  💀 {} is {} by {}, {} left standing",
+            fighter.bright_red().bold(),
+            "KNOCKED OUT".bright_red().bold(),
+            by.bright_blue().bold(),
+            remaining.to_string().bright_white()
+        );
+    }
+
+    /// Display fatigue kicking in, no spinner since nobody rolls for it
+    fn display_fatigue(&self, level: u32, bonus_damage: u32) {
+        println!("  😓 Fatigue level {}: every hit deals +{} damage",
+            level.to_string().bright_yellow().bold(),
+            bonus_damage.to_string().bright_red().bold()
+        );
+    }
+    
+    fn display_defend(&self, actor: &str) {
+        println!("  🛡️  {} braces, doubling their next defense roll", actor.bright_white().bold());
+    }
+
+    fn display_taunt(&self, actor: &str, target: &str, penalty: u32) {
+        println!("  📣 {} taunts {} into attacking at {}", actor.bright_white().bold(), target.bright_white().bold(), format!("-{}", penalty).bright_red().bold());
+    }
+
+    fn display_arena_effect(&self, arena: &str, fighter: &str, hp_change: i32) {
+        if hp_change < 0 {
+            println!("  🌋 {} hurts {} for {} HP", arena.bright_yellow(), fighter.bright_white().bold(), (-hp_change).to_string().bright_red().bold());
+        } else {
+            println!("  ⛲ {} boosts {}'s heal by {} HP", arena.bright_yellow(), fighter.bright_white().bold(), format!("+{}", hp_change).bright_green().bold());
+        }
+    }
+
+    fn display_effect_tick(&self, fighter_name: &str, from: u32, to: u32, source: &EffectSource) {
+        let (icon, what) = match source.kind {
+            TickKind::Burn => ("🔥", "burns"),
+            TickKind::Poison => ("☠️", "is poisoned"),
+            TickKind::Regen => ("🌿", "regenerates"),
+        };
+        let change = if to < from {
+            format!("-{}", from - to).bright_red().bold()
+        } else {
+            format!("+{}", to - from).bright_green().bold()
+        };
+        println!("  {} {} {} ({} from {}'s {})", icon, fighter_name.bright_white().bold(), what, change, source.caster, source.spell);
+    }
+
     /// Display health bars (no streaming animation)
     fn display_health_bars_with_effect(&self, fighter1_hp: u32, fighter2_hp: u32) {
         println!();
         
         // Simple delay for suspense, then show health bars instantly
         if self.config.enable_delays {
-            thread::sleep(Duration::from_millis(200));
+            self.pause(200);
         }
         
         self.display_health_bars(fighter1_hp, fighter2_hp);
@@ -733,7 +1300,7 @@ impl BattleDisplay {
         // Extended dramatic pause before the final announcement
         if self.config.enable_delays {
             self.suspenseful_delay(800, "BATTLE CONCLUDING...", true);
-            thread::sleep(Duration::from_millis(500));
+            self.pause(500);
         }
         
         println!("\n{}", "🏆 BATTLE COMPLETE 🏆".bright_yellow().bold().center(70));
@@ -754,6 +1321,10 @@ impl BattleDisplay {
                 ("⏰ TIME VICTORY!".bright_blue().bold(),
                  format!("Maximum turns ({}) reached - winner by endurance!", max_turns.to_string().bright_white()))
             }
+            crate::battle::BattleCompletionReason::LastStanding(_) => {
+                ("👑 LAST PET STANDING!".bright_green().bold(),
+                 format!("{} outlasted everyone!", winner.bright_cyan().bold()))
+            }
         };
         
         // Extended celebration with spinner
@@ -772,13 +1343,13 @@ impl BattleDisplay {
             let steps = 10; // Longer celebration - ~1.5 seconds
             for i in 0..steps {
                 pb.set_position(i as u64);
-                thread::sleep(Duration::from_millis(150));
+                self.pause(150);
             }
             
             pb.finish_and_clear();
         } else {
             println!("\n{}", completion_title);
-            thread::sleep(Duration::from_millis(1000));
+            self.pause(1000);
         }
         
         // Display the final results
@@ -796,7 +1367,7 @@ impl BattleDisplay {
         
         // Special celebration based on how the battle ended
         match completion_reason {
-            crate::battle::BattleCompletionReason::HpDepleted(_) => {
+            crate::battle::BattleCompletionReason::HpDepleted(_) | crate::battle::BattleCompletionReason::LastStanding(_) => {
                 println!("  ⚔️  Battle Ended: Knockout Victory");
                 if winner_final_hp > 50 {
                     println!("  💪 Decisive Victory - Winner still has plenty of fight left!");
@@ -833,24 +1404,10 @@ impl BattleDisplay {
         }
         
         // Calculate statistics from events
-        let mut total_damage_dealt: HashMap<String, u32> = HashMap::new();
-        let mut total_healing_done: HashMap<String, u32> = HashMap::new();
-        let mut spells_cast: HashMap<String, Vec<String>> = HashMap::new();
-        
-        for event in events {
-            match event {
-                BattleEvent::Attack { actor, actual_damage, .. } => {
-                    *total_damage_dealt.entry(actor.clone()).or_insert(0) += actual_damage;
-                }
-                BattleEvent::Heal { actor, amount, .. } => {
-                    *total_healing_done.entry(actor.clone()).or_insert(0) += amount;
-                }
-                BattleEvent::SpellCast { actor, spell_name, .. } => {
-                    spells_cast.entry(actor.clone()).or_insert_with(Vec::new).push(spell_name.clone());
-                }
-                _ => {}
-            }
-        }
+        let log = BattleLog::new(events);
+        let total_damage_dealt = log.damage_by_fighter();
+        let total_healing_done = log.healing_by_fighter();
+        let spells_cast = log.spells_by_fighter();
         
         println!("\n{}", "📊 BATTLE SUMMARY".bright_white().bold());
         println!("{}", "─".repeat(50).bright_black());
@@ -908,6 +1465,12 @@ impl BattleDisplay {
             }
         }
         
+        // How the odds swung over the battle
+        if let Some(sparkline) = win_probability_sparkline(events) {
+            println!("\n{}", "Win Probability:".bright_cyan().underline());
+            println!("  {} {}", self.fighter1_name.bright_cyan().bold(), sparkline.bright_cyan());
+        }
+        
         println!("\n{}", "═".repeat(70).bright_black());
     }
     
@@ -936,7 +1499,7 @@ impl BattleDisplay {
     fn display_single_health_bar(&self, name: &str, current: u32, max: u32) {
         let percentage = if max > 0 { (current as f64 / max as f64 * 100.0) as u32 } else { 0 };
         let bar_width = 30;
-        let filled_width = (bar_width as f64 * percentage as f64 / 100.0) as usize;
+        let filled_width = ((bar_width as f64 * percentage as f64 / 100.0) as usize).min(bar_width);
         let empty_width = bar_width - filled_width;
         
         let health_color = if percentage > 50 {
@@ -996,10 +1559,65 @@ impl CenterColoredText for colored::ColoredString {
     }
 }
 
+/// Shows the battle as it is played, the header coming with the first event. In compact
+/// mode a turn's line is printed once the next turn starts, and there is no summary.
+impl BattleRenderer for BattleDisplay {
+    fn render_event(&mut self, event: &BattleEvent) {
+        if self.config.mode == DisplayMode::Compact {
+            if self.compact_turn.first().is_some_and(|first| first.turn() != event.turn()) {
+                self.flush_compact_turn();
+            }
+            self.compact_turn.push(event.clone());
+            return;
+        }
+        if self.clock.skipping() {
+            return;
+        }
+        if !self.opened {
+            self.open(Some((self.fighter1_current_hp, self.fighter2_current_hp)));
+        }
+        self.play_event(event);
+    }
+
+    fn render_summary(&mut self, events: &[BattleEvent]) {
+        if self.config.mode == DisplayMode::Compact {
+            self.flush_compact_turn();
+            return;
+        }
+        if self.opened {
+            self.close();
+        }
+        self.display_battle_summary(events);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::neopets::{Neopet, Behavior};
+    use crate::render::render_battle;
+
+    fn fighter(name: &str) -> Neopet {
+        Neopet {
+            health: 100,
+            heal_delta: 10,
+            base_attack: 5,
+            behavior: Behavior::attack_or_heal(0.5),
+            ..Neopet::test_fighter(name)
+        }
+    }
+
+    #[test]
+    fn test_display_config_overrides() {
+        let config: BattleDisplayConfig = serde_json::from_str(r#"{ "mode": "compact", "speed": 2.0 }"#).unwrap();
+        assert_eq!(config.mode, DisplayMode::Compact);
+        assert_eq!(config.base_delay_ms, BattleDisplayConfig::default().base_delay_ms);
+
+        let mut config = BattleDisplayConfig::default();
+        config.merge(BattleDisplayOverrides { enable_delays: Some(false), ..Default::default() });
+        assert!(!config.enable_delays);
+        assert_eq!(config.mode, DisplayMode::Animated);
+    }
 
     #[test]
     fn test_display_empty_events() {
@@ -1012,10 +1630,91 @@ mod tests {
             fighter2_current_hp: 100,
             config: BattleDisplayConfig::default(),
             multi_progress: None,
+            clock: Box::new(SystemClock),
+            opened: false,
+            turn: None,
+            events_in_turn: 0,
+            compact_turn: Vec::new(),
         };
         display.display_battle_events(&[], None);
     }
 
+    #[test]
+    fn test_instant_clock_skips_the_delays() {
+        let config = BattleDisplayConfig {
+            use_spinners: false,
+            streaming_effect: false,
+            speed: 2.0,
+            ..Default::default()
+        };
+        let clock = InstantClock::default();
+        let mut display = BattleDisplay::with_config(&fighter("Pikachu"), &fighter("Charizard"), config)
+            .with_clock(clock.clone());
+
+        let start = std::time::Instant::now();
+        display.display_battle_events(&[BattleEvent::Heal { turn: 1, actor: "Pikachu".into(), amount: 5 }], None);
+        assert!(clock.slept() >= Duration::from_millis(300));
+        assert!(start.elapsed() < clock.slept());
+    }
+
+    #[test]
+    fn test_keyboard_clock_skips_on_ctrl_c() {
+        let clock = KeyboardClock::new();
+        clock.handle_key(KeyEvent::from(KeyCode::Char('+')));
+        assert_eq!(clock.playback().speed(), 2.0);
+        clock.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        assert!(Clock::skipping(&clock));
+
+        let start = std::time::Instant::now();
+        clock.sleep(Duration::from_secs(5));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_display_stops_showing_events_when_skipping() {
+        struct SkippingClock;
+        impl Clock for SkippingClock {
+            fn sleep(&self, _duration: Duration) {}
+            fn skipping(&self) -> bool {
+                true
+            }
+        }
+
+        let config = BattleDisplayConfig { use_spinners: false, streaming_effect: false, ..Default::default() };
+        let mut display = BattleDisplay::with_config(&fighter("Pikachu"), &fighter("Charizard"), config)
+            .with_clock(SkippingClock);
+        display.display_battle_events(&[BattleEvent::Heal { turn: 1, actor: "Pikachu".into(), amount: 5 }], None);
+        assert_eq!(display.turn, None);
+        display.render_event(&BattleEvent::Heal { turn: 1, actor: "Pikachu".into(), amount: 5 });
+        assert!(!display.opened);
+    }
+
+    #[test]
+    fn test_display_renders_event_by_event() {
+        let config = BattleDisplayConfig { use_spinners: false, streaming_effect: false, ..Default::default() };
+        let mut display = BattleDisplay::with_config(&fighter("Pikachu"), &fighter("Charizard"), config.clone())
+            .with_clock(InstantClock::default());
+        let events = [
+            BattleEvent::Heal { turn: 1, actor: "Pikachu".into(), amount: 5 },
+            BattleEvent::Heal { turn: 2, actor: "Charizard".into(), amount: 5 },
+        ];
+        display.render_event(&events[0]);
+        assert!(display.opened);
+        display.render_event(&events[1]);
+        assert_eq!(display.turn, Some(2));
+        display.render_summary(&events);
+        assert!(!display.opened);
+        assert_eq!(display.turn, None);
+
+        let compact = BattleDisplayConfig { mode: DisplayMode::Compact, ..config };
+        let mut display = BattleDisplay::with_config(&fighter("Pikachu"), &fighter("Charizard"), compact);
+        render_battle(&mut display, &events[..1]);
+        display.render_event(&events[0]);
+        display.render_event(&events[1]);
+        // Turn 1 was printed when turn 2 started
+        assert_eq!(display.compact_turn, events[1..]);
+    }
+
     #[test]
     fn test_display_with_health_state() {
         let mut config = BattleDisplayConfig::default();
@@ -1025,38 +1724,28 @@ mod tests {
         
         let mut display = BattleDisplay::with_config(
             &Neopet {
-                name: "Pikachu".to_string(),
                 health: 100,
                 heal_delta: 10,
                 base_attack: 5,
-                base_defense: 3,
-                spells: vec![],
-                behavior: Behavior {
-                    attack_chance: 0.5,
-                    spell_chances: vec![],
-                    heal_chance: 0.5,
-                },
+                behavior: Behavior::attack_or_heal(0.5),
+                ..Neopet::test_fighter("Pikachu")
             },
             &Neopet {
-                name: "Charizard".to_string(),
                 health: 120,
                 heal_delta: 15,
                 base_attack: 8,
                 base_defense: 5,
-                spells: vec![],
-                behavior: Behavior {
-                    attack_chance: 0.4,
-                    spell_chances: vec![],
-                    heal_chance: 0.6,
-                },
+                behavior: Behavior::attack_or_heal(0.4),
+                ..Neopet::test_fighter("Charizard")
             },
             config
-        );
+        ).with_clock(InstantClock::default());
         
         let events = vec![BattleEvent::Roll {
             turn: 1,
-            actor: "Pikachu".to_string(),
+            actor: "Pikachu".into(),
             dice: 15,
+            dice_expr: None,
             final_value: 25,
             is_positive_crit: false,
             is_negative_crit: false,
@@ -1071,31 +1760,20 @@ mod tests {
     fn test_battle_state() {
         
         let fighter1 = Neopet {
-            name: "TestFighter1".to_string(),
             health: 100,
             heal_delta: 10,
             base_attack: 5,
-            base_defense: 3,
-            spells: vec![],
-            behavior: Behavior {
-                attack_chance: 0.5,
-                spell_chances: vec![],
-                heal_chance: 0.5,
-            },
+            behavior: Behavior::attack_or_heal(0.5),
+            ..Neopet::test_fighter("TestFighter1")
         };
         
         let fighter2 = Neopet {
-            name: "TestFighter2".to_string(),
             health: 80,
             heal_delta: 15,
             base_attack: 8,
             base_defense: 5,
-            spells: vec![],
-            behavior: Behavior {
-                attack_chance: 0.4,
-                spell_chances: vec![],
-                heal_chance: 0.6,
-            },
+            behavior: Behavior::attack_or_heal(0.4),
+            ..Neopet::test_fighter("TestFighter2")
         };
         
         // Test health bar display directly
@@ -1111,40 +1789,192 @@ mod tests {
         config.streaming_effect = false;
         
         let fighter1 = Neopet {
-            name: "Fighter1".to_string(),
             health: 100,
             heal_delta: 10,
             base_attack: 5,
-            base_defense: 3,
-            spells: vec![],
-            behavior: Behavior {
-                attack_chance: 0.5,
-                spell_chances: vec![],
-                heal_chance: 0.5,
-            },
+            behavior: Behavior::attack_or_heal(0.5),
+            ..Neopet::test_fighter("Fighter1")
         };
         
         let fighter2 = Neopet {
-            name: "Fighter2".to_string(),
             health: 100,
             heal_delta: 10,
             base_attack: 5,
-            base_defense: 3,
-            spells: vec![],
-            behavior: Behavior {
-                attack_chance: 0.5,
-                spell_chances: vec![],
-                heal_chance: 0.5,
-            },
+            behavior: Behavior::attack_or_heal(0.5),
+            ..Neopet::test_fighter("Fighter2")
         };
         
-        let mut display = BattleDisplay::with_config(&fighter1, &fighter2, config);
+        let mut display = BattleDisplay::with_config(&fighter1, &fighter2, config).with_clock(InstantClock::default());
         let events = vec![BattleEvent::Heal {
             turn: 1,
-            actor: "Fighter1".to_string(),
+            actor: "Fighter1".into(),
             amount: 10,
         }];
         
         display.display_battle_events(&events, None);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_win_probability_bar_splits_cells() {
+        assert_eq!(
+            win_probability_bar("Acara", "Usul", 0.75, 8),
+            "Acara  75% ██████░░ 25% Usul"
+        );
+        assert_eq!(win_probability_bar("Acara", "Usul", 0.0, 4), "Acara   0% ░░░░ 100% Usul");
+        assert_eq!(win_probability_bar("Acara", "Usul", 1.0, 4), "Acara 100% ████ 0% Usul");
+    }
+
+    #[test]
+    fn test_win_bar_split_clamps_probability() {
+        assert_eq!(win_bar_split(1.5, 10), 10);
+        assert_eq!(win_bar_split(-0.5, 10), 0);
+        assert_eq!(win_bar_split(0.5, 10), 5);
+    }
+
+    #[test]
+    fn test_win_probability_text_fallback() {
+        assert_eq!(
+            win_probability_text("Acara", "Usul", 0.62),
+            "Win chance: Acara 62% - 38% Usul"
+        );
+    }
+
+    #[test]
+    fn test_display_prediction_events() {
+        let mut config = BattleDisplayConfig {
+            enable_delays: false,
+            use_spinners: false,
+            streaming_effect: false,
+            ..Default::default()
+        };
+
+        let neopet = |name: &str| Neopet {
+            health: 100,
+            heal_delta: 10,
+            base_attack: 5,
+            behavior: Behavior::attack_or_heal(0.5),
+            ..Neopet::test_fighter(name)
+        };
+        let events = vec![BattleEvent::Prediction {
+            turn: 1,
+            fighter1: "Acara".into(),
+            fighter2: "Usul".into(),
+            fighter1_win_probability: 0.6,
+        }];
+
+        let mut display = BattleDisplay::with_config(&neopet("Acara"), &neopet("Usul"), config.clone())
+            .with_clock(InstantClock::default());
+        display.display_battle_events(&events, None);
+
+        config.win_probability_bar = false;
+        let mut display = BattleDisplay::with_config(&neopet("Acara"), &neopet("Usul"), config)
+            .with_clock(InstantClock::default());
+        display.display_battle_events(&events, None);
+    }
+
+    #[test]
+    fn test_series_standings_lines() {
+        let mut series = SeriesRecord::new("series_1", "Pikachu", "Charizard", 3, "").unwrap();
+        assert_eq!(series_standings_lines(&series), vec!["Pikachu 0 - 0 Charizard (best of 3, first to 2 wins)"]);
+
+        for (number, winner) in [(1, "Charizard"), (2, "Charizard")] {
+            series.games.push(crate::series::SeriesGame {
+                number,
+                battle_id: format!("battle_{}", number),
+                seed: 40 + number as u64,
+                winner: winner.to_string(),
+            });
+        }
+        assert_eq!(
+            series_standings_lines(&series),
+            vec![
+                "Pikachu 0 - 2 Charizard (best of 3, Charizard wins the series)",
+                "G1: Charizard wins (battle_1, seed 41)",
+                "G2: Charizard wins (battle_2, seed 42)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compact_turn_lines() {
+        let events = vec![
+            BattleEvent::Roll {
+                turn: 0, actor: "Pikachu".into(), dice: 14, dice_expr: None, final_value: 14,
+                is_positive_crit: false, is_negative_crit: false, goal: "initiative".to_string(),
+            },
+            BattleEvent::Roll {
+                turn: 0, actor: "Charizard".into(), dice: 8, dice_expr: None, final_value: 8,
+                is_positive_crit: false, is_negative_crit: false, goal: "initiative".to_string(),
+            },
+            BattleEvent::Roll {
+                turn: 3, actor: "Pikachu".into(), dice: 20, dice_expr: None, final_value: 25,
+                is_positive_crit: true, is_negative_crit: false, goal: "attack".to_string(),
+            },
+            BattleEvent::Attack {
+                turn: 3, actor: "Pikachu".into(), target: "Charizard".into(),
+                raw_damage: 25, shield_value: 19, actual_damage: 12,
+                is_killing_blow: false, overkill_amount: 0,
+            },
+            BattleEvent::HealthUpdate { fighter_name: "Charizard".into(), from: 85, to: 73, turn: 3, source: None },
+            BattleEvent::FatigueApplied { turn: 4, level: 1, bonus_damage: 2 },
+            BattleEvent::Heal { turn: 4, actor: "Charizard".into(), amount: 10 },
+            BattleEvent::HealthUpdate { fighter_name: "Charizard".into(), from: 73, to: 83, turn: 4, source: None },
+            BattleEvent::BattleComplete {
+                turn: 4, winner: "Charizard".into(), loser: "Pikachu".into(),
+                winner_final_hp: 83, loser_final_hp: 40,
+                completion_reason: BattleCompletionReason::MaxTurnsReached(4),
+            },
+        ];
+
+        assert_eq!(
+            compact_turn_lines(&events),
+            vec![
+                "T0: Pikachu rolls 14 | Charizard rolls 8",
+                "T3: Pikachu crits Charizard for 12 (85→73)",
+                "T4: fatigue +2 | Charizard heals 10 (73→83) | Charizard wins on HP (83 vs 40)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compact_turn_lines_misses() {
+        let events = vec![
+            BattleEvent::Roll {
+                turn: 1, actor: "Pikachu".into(), dice: 1, dice_expr: None, final_value: 6,
+                is_positive_crit: false, is_negative_crit: true, goal: "attack".to_string(),
+            },
+            BattleEvent::Attack {
+                turn: 1, actor: "Pikachu".into(), target: "Charizard".into(),
+                raw_damage: 6, shield_value: 10, actual_damage: 0,
+                is_killing_blow: false, overkill_amount: 0,
+            },
+            BattleEvent::Roll {
+                turn: 2, actor: "Charizard".into(), dice: 5, dice_expr: None, final_value: 10,
+                is_positive_crit: false, is_negative_crit: false, goal: "attack".to_string(),
+            },
+            BattleEvent::Attack {
+                turn: 2, actor: "Charizard".into(), target: "Pikachu".into(),
+                raw_damage: 10, shield_value: 12, actual_damage: 0,
+                is_killing_blow: false, overkill_amount: 0,
+            },
+            BattleEvent::Roll {
+                turn: 3, actor: "Pikachu".into(), dice: 15, dice_expr: None, final_value: 20,
+                is_positive_crit: false, is_negative_crit: false, goal: "attack".to_string(),
+            },
+            BattleEvent::Dodge { turn: 3, actor: "Charizard".into(), attacker: "Pikachu".into() },
+            BattleEvent::Block {
+                turn: 4, actor: "Pikachu".into(), attacker: "Charizard".into(), blocked: 4,
+            },
+        ];
+
+        assert_eq!(
+            compact_turn_lines(&events),
+            vec![
+                "T1: Pikachu fumbles against Charizard",
+                "T2: Charizard misses Pikachu",
+                "T3: Charizard dodges Pikachu",
+                "T4: Pikachu blocks 4",
+            ]
+        );
+    }
+}
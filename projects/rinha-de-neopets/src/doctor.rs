@@ -0,0 +1,235 @@
+// src/doctor.rs
+use crate::battle::BattleEvent;
+use crate::integrity::battle_hash;
+use crate::neopets::{Neopet, NeopetDef};
+use crate::storage::{Storage, TrashedItem};
+use std::collections::HashSet;
+use std::fmt;
+
+/// How much the storage holds, for `colosseum doctor` to show before what is wrong with it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StorageStats {
+    pub fighters: usize,
+    pub pending_battles: usize,
+    pub complete_battles: usize,
+    pub archived_battles: usize,
+    /// Events of the complete battles, counted without loading them.
+    pub events: usize,
+    pub trash_entries: usize,
+}
+
+pub fn stats(storage: &Storage) -> Result<StorageStats, Box<dyn std::error::Error>> {
+    let complete = storage.complete_battle_records();
+    let mut events = 0;
+    for battle in complete {
+        events += storage.event_count(&battle.id)?;
+    }
+    Ok(StorageStats {
+        fighters: storage.list_fighters().len(),
+        pending_battles: storage.pending_battle_records().len(),
+        complete_battles: complete.len(),
+        archived_battles: complete.iter().filter(|b| b.archived).count(),
+        events,
+        trash_entries: storage.trash_entries().len(),
+    })
+}
+
+/// Something inconsistent in the stored data, found by `check`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Issue {
+    /// A fighter `Neopet::try_from` refuses, e.g. one edited by hand.
+    InvalidFighter { name: String, reason: String },
+    /// Two or more fighters share a name, so only the first can be picked.
+    DuplicateFighter { name: String },
+    /// A battle names a fighter that is neither registered nor in the trash.
+    UnknownFighter { battle_id: String, fighter: String, pending: bool },
+    /// A complete battle without exactly one `BattleComplete` event.
+    CompletionEvents { battle_id: String, count: usize },
+    /// A complete battle whose winner isn't the one its `BattleComplete` event names.
+    WrongWinner { battle_id: String, recorded: Option<String>, actual: String },
+    /// A complete battle whose events don't match its recorded hash.
+    HashMismatch { battle_id: String },
+}
+
+impl Issue {
+    /// Whether `fix` can repair it. Fighters and changed events are left for the user to
+    /// look into, and so are complete battles of unknown fighters, whose results count
+    /// towards ratings.
+    pub fn is_fixable(&self) -> bool {
+        match self {
+            Issue::UnknownFighter { pending, .. } => *pending,
+            Issue::CompletionEvents { .. } | Issue::WrongWinner { .. } => true,
+            Issue::InvalidFighter { .. } | Issue::DuplicateFighter { .. } | Issue::HashMismatch { .. } => false,
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::InvalidFighter { name, reason } => write!(f, "Fighter '{}' is invalid: {}", name, reason),
+            Issue::DuplicateFighter { name } => write!(f, "More than one fighter is named '{}'", name),
+            Issue::UnknownFighter { battle_id, fighter, pending } => {
+                let kind = if *pending { "Pending" } else { "Complete" };
+                write!(f, "{} battle {} names unknown fighter '{}'", kind, battle_id, fighter)
+            }
+            Issue::CompletionEvents { battle_id, count } => {
+                write!(f, "Complete battle {} has {} BattleComplete events instead of one", battle_id, count)
+            }
+            Issue::WrongWinner { battle_id, recorded, actual } => write!(
+                f,
+                "Complete battle {} records {} as the winner, but its events say '{}'",
+                battle_id,
+                recorded.as_deref().map_or("no one".to_string(), |w| format!("'{}'", w)),
+                actual
+            ),
+            Issue::HashMismatch { battle_id } => {
+                write!(f, "The events of battle {} don't match its hash, they were changed after the battle", battle_id)
+            }
+        }
+    }
+}
+
+/// Goes through every fighter and battle, events included, for what `Issue` lists.
+pub fn check(storage: &Storage) -> Result<Vec<Issue>, Box<dyn std::error::Error>> {
+    let mut issues = Vec::new();
+
+    let mut names = HashSet::new();
+    let mut duplicates = HashSet::new();
+    for name in storage.list_fighters() {
+        if !names.insert(name.clone()) && duplicates.insert(name.clone()) {
+            issues.push(Issue::DuplicateFighter { name });
+        }
+    }
+    for name in &names {
+        let Some(fighter) = storage.get_fighter(name) else { continue };
+        if let Err(reason) = Neopet::try_from(NeopetDef::from(fighter.clone())) {
+            issues.push(Issue::InvalidFighter { name: name.clone(), reason });
+        }
+    }
+    issues.sort_by_key(|issue| issue.to_string());
+
+    // A deleted fighter can still be restored, so their battles aren't orphaned
+    let trashed = storage.trash_entries().iter().filter_map(|entry| match &entry.item {
+        TrashedItem::Fighter { neopet } => Some(neopet.name.clone()),
+        _ => None,
+    });
+    names.extend(trashed);
+    for battle in storage.pending_battle_records() {
+        for fighter in [&battle.fighter1_name, &battle.fighter2_name] {
+            if !names.contains(fighter) {
+                issues.push(Issue::UnknownFighter { battle_id: battle.id.clone(), fighter: fighter.clone(), pending: true });
+            }
+        }
+    }
+
+    for record in storage.complete_battle_records() {
+        for fighter in [&record.fighter1_name, &record.fighter2_name] {
+            if !names.contains(fighter) {
+                issues.push(Issue::UnknownFighter { battle_id: record.id.clone(), fighter: fighter.clone(), pending: false });
+            }
+        }
+        let Some(battle) = storage.load_battle(&record.id)? else { continue };
+        let winners: Vec<&str> = battle.events.iter().filter_map(|e| match e {
+            BattleEvent::BattleComplete { winner, .. } => Some(winner.as_ref()),
+            _ => None,
+        }).collect();
+        match winners[..] {
+            [winner] if battle.winner.as_deref() != Some(winner) => issues.push(Issue::WrongWinner {
+                battle_id: battle.id.clone(),
+                recorded: battle.winner.clone(),
+                actual: winner.to_string(),
+            }),
+            [_] => {}
+            _ => issues.push(Issue::CompletionEvents { battle_id: battle.id.clone(), count: winners.len() }),
+        }
+        if battle.hash.as_ref().is_some_and(|hash| *hash != battle_hash(&battle.events)) {
+            issues.push(Issue::HashMismatch { battle_id: battle.id.clone() });
+        }
+    }
+    Ok(issues)
+}
+
+/// Repairs the fixable `issues`, returning how many it fixed: battles of unknown
+/// fighters and those that didn't end exactly once go to the trash, where they can be
+/// restored from, and wrong winners are set to the one the events name. The caller saves.
+pub fn fix(storage: &mut Storage, issues: &[Issue], deleted_at: &str) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut fixed = 0;
+    for issue in issues.iter().filter(|issue| issue.is_fixable()) {
+        match issue {
+            Issue::UnknownFighter { battle_id, .. } | Issue::CompletionEvents { battle_id, .. } => {
+                // Both fighters of a battle can be unknown, it only goes to the trash once
+                if storage.find_battle(battle_id).is_none() {
+                    continue;
+                }
+                storage.trash_battle(battle_id, deleted_at)?;
+            }
+            Issue::WrongWinner { battle_id, actual, .. } => {
+                let Some(battle) = storage.get_complete_battle(battle_id)? else { continue };
+                battle.winner = Some(actual.clone());
+            }
+            Issue::InvalidFighter { .. } | Issue::DuplicateFighter { .. } | Issue::HashMismatch { .. } => continue,
+        }
+        fixed += 1;
+    }
+    Ok(fixed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::BattleCompletionReason;
+    use crate::storage::BattleRecord;
+
+    fn battle(id: &str, fighter2: &str, winners: &[&str]) -> BattleRecord {
+        let events = winners.iter().map(|winner| BattleEvent::BattleComplete {
+            turn: 3,
+            winner: (*winner).into(),
+            loser: "Acara".into(),
+            winner_final_hp: 10,
+            loser_final_hp: 0,
+            completion_reason: BattleCompletionReason::HpDepleted("Acara".into()),
+        }).collect();
+        BattleRecord {
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            events,
+            winner: Some(fighter2.to_string()),
+            is_completed: !winners.is_empty(),
+            ..BattleRecord::test_battle(id, "Acara", fighter2)
+        }
+    }
+
+    #[test]
+    fn test_doctor_finds_and_fixes_inconsistencies() {
+        let mut storage = Storage::in_memory();
+        storage.add_neopet(Neopet::test_fighter("Acara")).unwrap();
+        storage.add_neopet(Neopet::test_fighter("Usul")).unwrap();
+        storage.add_neopet(Neopet { dodge_chance: 1.5, ..Neopet::test_fighter("Kacheek") }).unwrap();
+        storage.add_pending_battle(BattleRecord { is_completed: false, ..battle("battle_ghost", "Ghost", &[]) }).unwrap();
+        storage.add_complete_battle(battle("battle_ok", "Usul", &["Usul"])).unwrap();
+        storage.add_complete_battle(battle("battle_twice", "Usul", &["Usul", "Usul"])).unwrap();
+        storage.add_complete_battle(battle("battle_wrong", "Usul", &["Acara"])).unwrap();
+
+        let issues = check(&storage).unwrap();
+        assert_eq!(issues, [
+            Issue::InvalidFighter {
+                name: "Kacheek".to_string(),
+                reason: "Neopet Kacheek: dodge chance 1.5 must be between 0.0 and 1.0".to_string(),
+            },
+            Issue::UnknownFighter { battle_id: "battle_ghost".to_string(), fighter: "Ghost".to_string(), pending: true },
+            Issue::CompletionEvents { battle_id: "battle_twice".to_string(), count: 2 },
+            Issue::WrongWinner {
+                battle_id: "battle_wrong".to_string(),
+                recorded: Some("Usul".to_string()),
+                actual: "Acara".to_string(),
+            },
+        ]);
+        assert_eq!(stats(&storage).unwrap().events, 4);
+
+        assert_eq!(fix(&mut storage, &issues, "2024-01-02T00:00:00Z").unwrap(), 3);
+        assert_eq!(storage.find_battle("battle_wrong").unwrap().winner.as_deref(), Some("Acara"));
+        assert!(storage.find_battle("battle_twice").is_none());
+        assert_eq!(storage.trash_entries().len(), 2);
+        assert_eq!(check(&storage).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,378 @@
+// src/engine.rs
+use crate::arena::{Arena, Arenas};
+use crate::battle::{battle_loop_with_observers, drop_loot, BattleConfig, BattleEvent};
+use crate::battle_log::BattleLog;
+use crate::handicap::Handicap;
+use crate::journal::{self, Journal, JournaledBattle};
+use crate::loot::{Item, LootTables};
+use crate::neopets::Neopet;
+use crate::storage::{BattleRecord, Storage};
+use crate::store::{Backend, StorageConfig};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The colosseum as a library: fighters, battles and their history kept in one
+/// assets directory, laid out the way both CLIs use it. Open it, create a battle,
+/// run it and `save`. Anything the facade doesn't cover is one `storage` (or
+/// `storage_mut`) away.
+pub struct Engine {
+    config: StorageConfig,
+    storage: Storage,
+}
+
+/// What `Engine::recover_journals` did with a battle left in a journal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Recovery {
+    /// The fight had finished, the battle is filed as complete with its events
+    Filed(String),
+    /// The fight was cut short after `events` events, the battle waits to be fought again
+    Interrupted { battle_id: String, events: usize },
+}
+
+impl Engine {
+    /// Loads the fighters and battles kept in `dir`, missing files start out empty. They
+    /// are kept where `dir`'s `storage.json` says, see `StorageConfig::load`.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_config(StorageConfig::load(dir)?)
+    }
+
+    /// Loads the fighters and battles kept where `config` says, e.g. a test's own files.
+    pub fn with_config(config: StorageConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let storage = Storage::open(&config)?;
+        Ok(Self { config, storage })
+    }
+
+    pub fn storage_config(&self) -> &StorageConfig {
+        &self.config
+    }
+
+    /// Copies the fighters, battles and events to `backend` and makes it the one
+    /// `storage.json` picks. What the old backend kept is left where it was.
+    pub fn migrate(&mut self, backend: Backend) -> Result<(), Box<dyn std::error::Error>> {
+        self.reconfigure(StorageConfig { backend, ..self.config.clone() })
+    }
+
+    /// Turns on `compress_battles` and rewrites every complete battle and its events
+    /// gzipped, uncompressed files written before included.
+    pub fn compact(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.config.backend != Backend::Json {
+            return Err(format!("Only the json backend compresses its battles, this one is {}", self.config.backend).into());
+        }
+        self.reconfigure(StorageConfig { compress_battles: true, ..self.config.clone() })
+    }
+
+    fn reconfigure(&mut self, config: StorageConfig) -> Result<(), Box<dyn std::error::Error>> {
+        self.storage.move_to_store(config.open()?)?;
+        self.storage.save()?;
+        config.save()?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Path of the asset file called `name`, e.g. `arenas.json`.
+    pub fn asset_path(&self, name: &str) -> String {
+        self.config.data_dir.join(name).to_string_lossy().into_owned()
+    }
+
+    pub fn storage(&self) -> &Storage {
+        &self.storage
+    }
+
+    pub fn storage_mut(&mut self) -> &mut Storage {
+        &mut self.storage
+    }
+
+    /// Writes every change back to the assets directory, then drops the journals of the
+    /// battles it filed.
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.storage.save()?;
+        for path in journal::abandoned(&self.journal_dir())? {
+            let battle_id = path.file_stem().unwrap_or_default().to_string_lossy();
+            if self.storage.find_battle(&battle_id).is_some_and(|b| b.is_completed) {
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn journal_dir(&self) -> PathBuf {
+        self.config.path(&self.config.journals)
+    }
+
+    /// Starts the journal of `battle`'s fight, see `journal::Journal`. Drop it once the
+    /// fight is over; the next `save` removes it with the battle filed.
+    pub fn journal(&self, battle: &BattleRecord) -> Result<Journal, Box<dyn std::error::Error>> {
+        Journal::create(&self.journal_dir(), battle)
+    }
+
+    /// Picks up the battles left in journals no process is writing anymore, after a crash
+    /// or a command that never saved. Finished fights are filed as complete battles with
+    /// the journaled events (without loot, it is drawn after the fight) and saved; the
+    /// rest stay pending, to be fought again. Journals of battles filed or deleted since
+    /// are dropped.
+    pub fn recover_journals(&mut self) -> Result<Vec<Recovery>, Box<dyn std::error::Error>> {
+        let mut recovered = Vec::new();
+        for path in journal::abandoned(&self.journal_dir())? {
+            let JournaledBattle { mut battle, finished } = journal::read(&path)?;
+            let is_pending = self.storage.find_pending_battle(&battle.id).is_some();
+            if is_pending && finished {
+                let events = std::mem::take(&mut battle.events);
+                let battle = self.complete_battle(battle, events, Vec::new())?;
+                recovered.push(Recovery::Filed(battle.id));
+            } else {
+                if is_pending {
+                    recovered.push(Recovery::Interrupted { battle_id: battle.id, events: battle.events.len() });
+                }
+                fs::remove_file(&path)?;
+            }
+        }
+        if recovered.iter().any(|r| matches!(r, Recovery::Filed(_))) {
+            self.save()?;
+        }
+        Ok(recovered)
+    }
+
+    pub fn fighters(&self) -> Vec<String> {
+        self.storage.list_fighters()
+    }
+
+    pub fn fighter(&self, name: &str) -> Result<&Neopet, String> {
+        self.storage.get_fighter(name).ok_or_else(|| format!("Fighter '{}' not found", name))
+    }
+
+    pub fn arenas(&self) -> Result<Arenas, Box<dyn std::error::Error>> {
+        Arenas::load(&self.asset_path("arenas.json"))
+    }
+
+    /// The arena called `name`, ignoring case.
+    pub fn arena(&self, name: &str) -> Result<Arena, Box<dyn std::error::Error>> {
+        let arenas = self.arenas()?;
+        let arena = arenas.get(name).ok_or_else(|| format!("Arena '{}' not found, see 'colosseum arena list'", name))?;
+        Ok(arena.clone())
+    }
+
+    /// The battle config every battle starts from, set in the battle's arena if it has one.
+    pub fn battle_config(&self, battle: &BattleRecord) -> Result<BattleConfig, Box<dyn std::error::Error>> {
+        let arena = match &battle.arena {
+            Some(name) => Some(
+                self.arenas()?
+                    .get(name)
+                    .ok_or_else(|| format!("Battle '{}' is set in arena '{}', which no longer exists", battle.id, name))?
+                    .clone(),
+            ),
+            None => None,
+        };
+        Ok(BattleConfig { arena, ..BattleConfig::load(&self.asset_path("battle_config.json"))? })
+    }
+
+    pub fn loot_tables(&self) -> Result<LootTables, Box<dyn std::error::Error>> {
+        LootTables::load(&self.asset_path("loot_tables.json"))
+    }
+
+    /// Adds a pending battle between two known fighters, see `Handicap::parse` for
+    /// `handicaps`, and returns it.
+    pub fn create_battle(
+        &mut self,
+        fighter1: &str,
+        fighter2: &str,
+        handicaps: &[String],
+        arena: Option<&str>,
+    ) -> Result<BattleRecord, Box<dyn std::error::Error>> {
+        self.fighter(fighter1)?;
+        self.fighter(fighter2)?;
+        if fighter1 == fighter2 {
+            return Err("A fighter cannot battle themselves".into());
+        }
+        let handicap = Handicap::parse(handicaps, fighter1, fighter2)?;
+        let arena = match arena {
+            Some(name) => Some(self.arena(name)?.name),
+            None => None,
+        };
+
+        let battle = BattleRecord {
+            handicap,
+            arena,
+            ..BattleRecord::pending(self.storage.generate_battle_id()?, fighter1, fighter2, &chrono::Utc::now().to_rfc3339())
+        };
+        self.storage.add_pending_battle(battle.clone())?;
+        Ok(battle)
+    }
+
+    /// Fights the pending battle `battle_id` with the dice seeded by `seed` (a random
+    /// one if `None`), lets the winner loot the loser and files it with the complete
+    /// battles. The events are journaled as they happen; a journal that can't be written
+    /// doesn't stop the battle.
+    pub fn run_battle(&mut self, battle_id: &str, seed: Option<u64>) -> Result<BattleRecord, Box<dyn std::error::Error>> {
+        let mut battle = self
+            .storage
+            .find_pending_battle(battle_id)
+            .ok_or_else(|| format!("Pending battle '{}' not found", battle_id))?;
+        let (fighter1, fighter2) = self.storage.battle_fighters(&battle)?;
+        let config = self.battle_config(&battle)?;
+
+        let seed = seed.unwrap_or_else(rand::random);
+        let mut rng = StdRng::seed_from_u64(seed);
+        battle.seed = Some(seed);
+        let mut journal = self.journal(&battle)?;
+        let mut events = battle_loop_with_observers(&fighter1, &fighter2, &config, &mut [&mut journal], &mut rng)?;
+        drop(journal);
+        let loot = drop_loot(&mut events, &self.loot_tables()?, &mut rng);
+        self.complete_battle(battle, events, loot)
+    }
+
+    /// Files a pending battle fought elsewhere with its events, handing `loot` to the
+    /// winner.
+    pub fn complete_battle(&mut self, battle: BattleRecord, events: Vec<BattleEvent>, loot: Vec<Item>) -> Result<BattleRecord, Box<dyn std::error::Error>> {
+        let winner = BattleLog::from(&events).winner().map(str::to_string);
+        let battle = self.storage.move_battle_to_complete(battle, events, winner.clone())?;
+        if let Some(winner) = &winner {
+            self.storage.add_items(winner, loot);
+        }
+        Ok(battle)
+    }
+
+    /// Plays `battle` back in the terminal, its fighters as they stand now.
+    #[cfg(feature = "display")]
+    pub fn display_battle(
+        &self,
+        battle: &BattleRecord,
+        config: crate::display::BattleDisplayConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (fighter1, fighter2) = self.storage.battle_fighters(battle)?;
+        let mut display = crate::display::BattleDisplay::with_config(&fighter1, &fighter2, config);
+        display.display_battle_events(&battle.events, Some((fighter1.health, fighter2.health)));
+        display.display_battle_summary(&battle.events);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observer::Observer;
+    use tempfile::TempDir;
+
+    fn engine() -> (TempDir, Engine) {
+        let dir = TempDir::new().unwrap();
+        std::fs::copy("assets/neopets.json", dir.path().join("neopets.json")).unwrap();
+        std::fs::copy("assets/arenas.json", dir.path().join("arenas.json")).unwrap();
+        let engine = Engine::open(dir.path()).unwrap();
+        (dir, engine)
+    }
+
+    #[test]
+    fn test_create_run_and_save_battle() {
+        let (dir, mut engine) = engine();
+        let fighters = engine.fighters();
+        let (fighter1, fighter2) = (fighters[0].as_str(), fighters[1].as_str());
+
+        let created = engine.create_battle(fighter1, fighter2, &[format!("{}:attack=-1", fighter1)], Some("lava pit")).unwrap();
+        assert_eq!(created.arena.as_deref(), Some("Lava Pit"));
+        assert_eq!(engine.battle_config(&created).unwrap().arena.unwrap().name, "Lava Pit");
+        assert_eq!(engine.storage().pending_battle_records().len(), 1);
+
+        let battle = engine.run_battle(&created.id, Some(42)).unwrap();
+        assert!(battle.is_completed);
+        assert_eq!(battle.seed, Some(42));
+        assert!(engine.storage().pending_battle_records().is_empty());
+        engine.save().unwrap();
+
+        let reopened = Engine::open(dir.path()).unwrap();
+        assert!(reopened.storage().find_battle(&battle.id).unwrap().events.is_empty());
+        let saved = reopened.storage().load_battle(&battle.id).unwrap().unwrap();
+        assert_eq!(saved.winner, battle.winner);
+        assert_eq!(saved.events, battle.events);
+    }
+
+    #[test]
+    fn test_recover_journals_files_finished_fights() {
+        let (dir, mut engine) = engine();
+        let fighters = engine.fighters();
+        let finished = engine.create_battle(&fighters[0], &fighters[1], &[], None).unwrap();
+        let cut_short = engine.create_battle(&fighters[1], &fighters[0], &[], None).unwrap();
+        engine.save().unwrap();
+
+        // Fought, but the process died before saving
+        let battle = engine.run_battle(&finished.id, Some(3)).unwrap();
+        let mut journal = engine.journal(&cut_short).unwrap();
+        for event in battle.events.iter().take(4) {
+            journal.on_event(event);
+        }
+        drop(journal);
+        let mut reopened = Engine::open(dir.path()).unwrap();
+        assert_eq!(reopened.storage().pending_battle_records().len(), 2);
+
+        let recovered = reopened.recover_journals().unwrap();
+        assert_eq!(recovered, [
+            Recovery::Filed(finished.id.clone()),
+            Recovery::Interrupted { battle_id: cut_short.id.clone(), events: 4 },
+        ]);
+        assert!(reopened.recover_journals().unwrap().is_empty());
+        assert_eq!(std::fs::read_dir(dir.path().join("journals")).unwrap().count(), 0);
+
+        // The events the fight had, the loot after it is lost
+        let reopened = Engine::open(dir.path()).unwrap();
+        let filed = reopened.storage().load_battle(&finished.id).unwrap().unwrap();
+        assert!(filed.is_completed);
+        assert_eq!(filed.winner, battle.winner);
+        let fought: Vec<&BattleEvent> = battle.events.iter().filter(|e| !matches!(e, BattleEvent::LootDropped { .. })).collect();
+        assert_eq!(filed.events.iter().collect::<Vec<_>>(), fought);
+        assert!(reopened.storage().find_pending_battle(&cut_short.id).is_some());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_migrate_to_sqlite_keeps_the_battles() {
+        let (dir, mut engine) = engine();
+        let fighters = engine.fighters();
+        let created = engine.create_battle(&fighters[0], &fighters[1], &[], None).unwrap();
+        let battle = engine.run_battle(&created.id, Some(7)).unwrap();
+        engine.save().unwrap();
+
+        engine.migrate(Backend::Sqlite).unwrap();
+        assert!(dir.path().join("rinha.db").exists());
+        std::fs::remove_file(dir.path().join("neopets.json")).unwrap();
+        let reopened = Engine::open(dir.path()).unwrap();
+        assert_eq!(reopened.fighters(), fighters);
+        assert_eq!(reopened.storage().load_battle(&battle.id).unwrap().unwrap().events, battle.events);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_compact_gzips_the_complete_battles() {
+        let (dir, mut engine) = engine();
+        let fighters = engine.fighters();
+        let created = engine.create_battle(&fighters[0], &fighters[1], &[], None).unwrap();
+        let battle = engine.run_battle(&created.id, Some(7)).unwrap();
+        engine.save().unwrap();
+        let events = dir.path().join("battle_events");
+        assert!(events.join(format!("{}.jsonl", battle.id)).exists());
+        let before = engine.storage_config().complete_battles_size().unwrap();
+
+        engine.compact().unwrap();
+        assert!(!dir.path().join("complete_battles.json").exists());
+        assert!(dir.path().join("complete_battles.json.gz").exists());
+        assert!(!events.join(format!("{}.jsonl", battle.id)).exists());
+        assert!(events.join(format!("{}.jsonl.gz", battle.id)).exists());
+        assert!(engine.storage_config().complete_battles_size().unwrap() < before);
+
+        let reopened = Engine::open(dir.path()).unwrap();
+        assert!(reopened.storage_config().compress_battles);
+        assert_eq!(reopened.storage().load_battle(&battle.id).unwrap().unwrap().events, battle.events);
+        assert_eq!(reopened.storage().event_count(&battle.id).unwrap(), battle.events.len());
+    }
+
+    #[test]
+    fn test_create_battle_rejects_bad_matchups() {
+        let (_dir, mut engine) = engine();
+        let fighter = engine.fighters()[0].clone();
+        assert!(engine.create_battle(&fighter, "Nobody", &[], None).is_err());
+        assert!(engine.create_battle(&fighter, &fighter, &[], None).is_err());
+        let other = engine.fighters()[1].clone();
+        assert!(engine.create_battle(&fighter, &other, &[], Some("Moon")).is_err());
+        assert!(engine.run_battle("battle_404", None).is_err());
+        assert!(engine.storage().pending_battle_records().is_empty());
+    }
+}
@@ -0,0 +1,256 @@
+// src/event_schema.rs
+use crate::battle::BattleEvent;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::io::{BufRead, Write};
+
+/// Schema version of the battle events written to disk. Bump it whenever a change
+/// to `BattleEvent` would break reading older records, and teach `upgrade` how to
+/// bring events of the previous version up to date.
+///
+/// Version 1 is every event written before events carried their version.
+pub const EVENT_SCHEMA_VERSION: u32 = 3;
+
+/// A battle event as saved to disk, tagged with the schema it was written with.
+#[derive(Serialize)]
+struct VersionedEvent<'a> {
+    version: u32,
+    event: &'a BattleEvent,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredEvent {
+    Versioned { version: u32, event: Value },
+    Unversioned(Value),
+}
+
+impl StoredEvent {
+    fn upgrade(self) -> Result<BattleEvent, String> {
+        match self {
+            StoredEvent::Versioned { version, event } => upgrade(version, event),
+            StoredEvent::Unversioned(event) => upgrade(1, event),
+        }
+    }
+}
+
+/// Brings an event written with schema `version` up to the current `BattleEvent`.
+pub fn upgrade(version: u32, mut event: Value) -> Result<BattleEvent, String> {
+    if version == 0 || version > EVENT_SCHEMA_VERSION {
+        return Err(format!(
+            "Battle event schema v{} is unknown, this build reads up to v{}",
+            version, EVENT_SCHEMA_VERSION
+        ));
+    }
+    for from in version..EVENT_SCHEMA_VERSION {
+        event = match from {
+            // v2 wrapped the events in their envelope and left the events alone
+            1 => event,
+            // v3 tagged the events with their snake_case `type`
+            2 => retag(event),
+            _ => unreachable!("No migration from battle event schema v{}", from),
+        };
+    }
+    serde_json::from_value(event).map_err(|e| format!("Battle event (schema v{}): {}", version, e))
+}
+
+/// Turns a v2 event, `{"Heal": {"turn": 3, ...}}`, into a v3 one, `{"type": "heal",
+/// "turn": 3, ...}`, along with the completion reason it may carry.
+fn retag(event: Value) -> Value {
+    let Some((name, Value::Object(mut fields))) = single_entry(event.clone()) else {
+        return event; // Not a v2 event, let deserializing it explain why
+    };
+    if let Some(reason) = fields.remove("completion_reason") {
+        let reason = match single_entry(reason.clone()) {
+            Some((kind, value)) => Value::Object(Map::from_iter([(snake_case(&kind), value)])),
+            None => reason,
+        };
+        fields.insert("completion_reason".to_string(), reason);
+    }
+    let mut tagged = Map::from_iter([("type".to_string(), Value::String(snake_case(&name)))]);
+    tagged.extend(fields);
+    Value::Object(tagged)
+}
+
+/// The key and value of an object with exactly one entry.
+fn single_entry(value: Value) -> Option<(String, Value)> {
+    match value {
+        Value::Object(map) if map.len() == 1 => map.into_iter().next(),
+        _ => None,
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+/// JSON schema of a `BattleEvent` as `export` writes it and saved battles wrap it
+/// (with its `version`), for tools reading battle logs. `colosseum battle schema`
+/// prints it.
+#[cfg(feature = "schema")]
+pub fn json_schema() -> Value {
+    let mut schema = schemars::schema_for!(BattleEvent).to_value();
+    schema["$comment"] = Value::String(format!("Battle event schema v{}", EVENT_SCHEMA_VERSION));
+    schema
+}
+
+/// Serde adapter for saved lists of events, use it with `#[serde(with = "...")]`.
+/// Events are written in the current schema and upgraded as they are read, so
+/// records saved by older builds load like new ones.
+pub mod versioned {
+    use super::*;
+    use serde::de::Error;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(events: &[BattleEvent], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(events.iter().map(|event| VersionedEvent { version: EVENT_SCHEMA_VERSION, event }))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<BattleEvent>, D::Error> {
+        Vec::<StoredEvent>::deserialize(deserializer)?
+            .into_iter()
+            .map(StoredEvent::upgrade)
+            .collect::<Result<_, _>>()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Events written one per line, each in its versioned envelope, so that a page of
+/// them can be read without parsing the lines before it. `Storage` keeps the events
+/// of every completed battle this way.
+pub mod lines {
+    use super::*;
+
+    pub fn write<W: Write>(events: &[BattleEvent], mut writer: W) -> Result<(), Box<dyn std::error::Error>> {
+        for event in events {
+            serde_json::to_writer(&mut writer, &VersionedEvent { version: EVENT_SCHEMA_VERSION, event })?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Up to `limit` events starting at the `offset`-th one, upgraded to the current schema.
+    pub fn read<R: BufRead>(reader: R, offset: usize, limit: usize) -> Result<Vec<BattleEvent>, String> {
+        reader
+            .lines()
+            .filter(|line| !line.as_ref().is_ok_and(|line| line.trim().is_empty()))
+            .skip(offset)
+            .take(limit)
+            .map(|line| {
+                let line = line.map_err(|e| e.to_string())?;
+                let stored: StoredEvent = serde_json::from_str(&line).map_err(|e| format!("Battle event: {}", e))?;
+                stored.upgrade()
+            })
+            .collect()
+    }
+
+    /// How many events `reader` holds, without parsing them.
+    pub fn count<R: BufRead>(reader: R) -> Result<usize, String> {
+        let mut count = 0;
+        for line in reader.lines() {
+            if !line.map_err(|e| e.to_string())?.trim().is_empty() {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::BattleCompletionReason;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        #[serde(with = "versioned")]
+        events: Vec<BattleEvent>,
+    }
+
+    fn heal(turn: u32) -> BattleEvent {
+        BattleEvent::Heal { turn, actor: "Acara".into(), amount: 5 }
+    }
+
+    #[test]
+    fn test_events_are_written_with_their_version() {
+        let record = Record { events: vec![heal(1), heal(2)] };
+        let value = serde_json::to_value(&record).unwrap();
+        assert_eq!(value["events"][0]["version"], json!(EVENT_SCHEMA_VERSION));
+        assert_eq!(value["events"][1]["event"], serde_json::to_value(heal(2)).unwrap());
+        assert_eq!(serde_json::from_value::<Record>(value).unwrap(), record);
+    }
+
+    #[test]
+    fn test_unversioned_events_upgrade_from_v1() {
+        let v1 = json!({ "events": [
+            { "Heal": { "turn": 1, "actor": "Acara", "amount": 5 } },
+            { "version": 2, "event": { "Heal": { "turn": 2, "actor": "Acara", "amount": 5 } } },
+        ] });
+        assert_eq!(serde_json::from_value::<Record>(v1).unwrap(), Record { events: vec![heal(1), heal(2)] });
+    }
+
+    #[test]
+    fn test_v2_events_are_retagged() {
+        let v2 = json!({ "events": [
+            { "version": 2, "event": { "FatigueApplied": { "turn": 9, "level": 1, "bonus_damage": 2 } } },
+            { "version": 2, "event": { "BattleComplete": {
+                "turn": 9, "winner": "Acara", "loser": "Usul", "winner_final_hp": 4, "loser_final_hp": 0,
+                "completion_reason": { "HpDepleted": "Usul" },
+            } } },
+        ] });
+        let record = serde_json::from_value::<Record>(v2).unwrap();
+        assert_eq!(record.events[0], BattleEvent::FatigueApplied { turn: 9, level: 1, bonus_damage: 2 });
+        let BattleEvent::BattleComplete { completion_reason, .. } = &record.events[1] else {
+            panic!("Expected the battle to complete, got {:?}", record.events[1]);
+        };
+        assert_eq!(completion_reason, &BattleCompletionReason::HpDepleted("Usul".to_string()));
+
+        let written = serde_json::to_value(&record).unwrap();
+        assert_eq!(written["events"][0]["event"]["type"], "fatigue_applied");
+        assert_eq!(written["events"][1]["event"]["completion_reason"], json!({ "hp_depleted": "Usul" }));
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn test_json_schema_names_every_event_type() {
+        let schema = json_schema().to_string();
+        for event_type in ["\"roll\"", "\"health_update\"", "\"arena_effect\"", "\"loot_dropped\""] {
+            assert!(schema.contains(event_type), "{} missing from the schema", event_type);
+        }
+    }
+
+    #[test]
+    fn test_event_lines_read_by_page() {
+        let events: Vec<BattleEvent> = (1..=5).map(heal).collect();
+        let mut written = Vec::new();
+        lines::write(&events, &mut written).unwrap();
+        assert_eq!(String::from_utf8_lossy(&written).lines().count(), 5);
+
+        assert_eq!(lines::count(&written[..]).unwrap(), 5);
+        assert_eq!(lines::read(&written[..], 0, usize::MAX).unwrap(), events);
+        assert_eq!(lines::read(&written[..], 3, 10).unwrap(), vec![heal(4), heal(5)]);
+        assert!(lines::read(&written[..], 5, 10).unwrap().is_empty());
+
+        let v1 = r#"{ "Heal": { "turn": 1, "actor": "Acara", "amount": 5 } }"#;
+        assert_eq!(lines::read(v1.as_bytes(), 0, 1).unwrap(), vec![heal(1)]);
+    }
+
+    #[test]
+    fn test_unknown_versions_are_rejected() {
+        let event = serde_json::to_value(heal(1)).unwrap();
+        assert!(upgrade(EVENT_SCHEMA_VERSION + 1, event.clone()).unwrap_err().contains("unknown"));
+        assert!(upgrade(0, event).is_err());
+
+        let future = json!({ "events": [{ "version": 99, "event": { "Heal": {} } }] });
+        assert!(serde_json::from_value::<Record>(future).unwrap_err().to_string().contains("v99"));
+    }
+}
@@ -0,0 +1,361 @@
+// src/export.rs
+use crate::battle::{BattleCompletionReason, BattleEvent, TickKind};
+use crate::battle_log::BattleLog;
+use crate::storage::BattleRecord;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// The formats `export_battle` writes a battle in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One `BattleEvent` as JSON per line, in battle order.
+    Ndjson,
+    /// One row per HP change, for spreadsheets.
+    Csv,
+    /// A recap to read, turn by turn.
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Markdown => "md",
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ndjson" => Ok(ExportFormat::Ndjson),
+            "csv" => Ok(ExportFormat::Csv),
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            _ => Err(format!("Unknown export format '{}', expected ndjson, csv or md", s)),
+        }
+    }
+}
+
+/// Writes `battle` to `out` in `format`.
+pub fn export_battle<W: Write>(battle: &BattleRecord, format: ExportFormat, out: &mut W) -> io::Result<()> {
+    match format {
+        ExportFormat::Ndjson => write_ndjson(&battle.events, out),
+        ExportFormat::Csv => write_csv(&battle.events, out),
+        ExportFormat::Markdown => write_markdown(battle, out),
+    }
+}
+
+/// Writes every event as it goes, so exporting never holds more than one line.
+pub fn write_ndjson<W: Write>(events: &[BattleEvent], out: &mut W) -> io::Result<()> {
+    for event in events {
+        serde_json::to_writer(&mut *out, event)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+pub const CSV_HEADER: &str = "turn,fighter,change,amount,hp_before,hp_after,cause,by";
+
+/// Writes a row for every HP change: whether it was damage or a heal, how much, and
+/// the attack, heal, spell or over-time effect behind it along with who caused it.
+/// Heals that restored nothing get no row.
+pub fn write_csv<W: Write>(events: &[BattleEvent], out: &mut W) -> io::Result<()> {
+    writeln!(out, "{}", CSV_HEADER)?;
+    // The latest action, the HP changes that follow it are its doing
+    let mut cause: (String, &str) = (String::new(), "");
+    for event in events {
+        match event {
+            BattleEvent::Attack { actor, .. } => cause = ("attack".to_string(), actor),
+            BattleEvent::Heal { actor, .. } => cause = ("heal".to_string(), actor),
+            BattleEvent::SpellCast { actor, spell_name, .. } => cause = (spell_name.clone(), actor),
+            BattleEvent::HealthUpdate { fighter_name, from, to, turn, source } if from != to => {
+                let (change, amount) = if to < from { ("damage", from - to) } else { ("heal", to - from) };
+                let (cause, by) = match source {
+                    Some(source) => (format!("{} ({})", tick_name(source.kind), source.spell), &*source.caster),
+                    None => (cause.0.clone(), cause.1),
+                };
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{},{}",
+                    turn, csv_field(fighter_name), change, amount, from, to, csv_field(&cause), csv_field(by)
+                )?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Quotes fields that would otherwise break the row.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn tick_name(kind: TickKind) -> &'static str {
+    match kind {
+        TickKind::Burn => "burn",
+        TickKind::Poison => "poison",
+        TickKind::Regen => "regen",
+    }
+}
+
+/// Writes the matchup, the outcome, what each fighter dealt and a line for every
+/// thing that happened, grouped by turn.
+pub fn write_markdown<W: Write>(battle: &BattleRecord, out: &mut W) -> io::Result<()> {
+    let log = BattleLog::new(&battle.events);
+    writeln!(out, "# {} vs {}", battle.fighter1_name, battle.fighter2_name)?;
+    writeln!(out)?;
+    writeln!(out, "- **Battle:** {}", battle.id)?;
+    writeln!(out, "- **Created:** {}", battle.created_at)?;
+    if let Some(seed) = battle.seed {
+        writeln!(out, "- **Seed:** {}", seed)?;
+    }
+    if !battle.tags.is_empty() {
+        writeln!(out, "- **Tags:** {}", battle.tags.join(", "))?;
+    }
+    writeln!(out, "- **Result:** {}", outcome(battle, &log))?;
+    for note in &battle.notes {
+        writeln!(out, "- **Note:** {}", note)?;
+    }
+
+    if battle.events.is_empty() {
+        return Ok(());
+    }
+    let (damage, healing, spells) = (log.damage_by_fighter(), log.healing_by_fighter(), log.spells_by_fighter());
+    writeln!(out)?;
+    writeln!(out, "| Fighter | Damage dealt | Healing | Spells cast |")?;
+    writeln!(out, "|---|---|---|---|")?;
+    for fighter in [&battle.fighter1_name, &battle.fighter2_name] {
+        writeln!(
+            out,
+            "| {} | {} | {} | {} |",
+            fighter,
+            damage.get(fighter.as_str()).unwrap_or(&0),
+            healing.get(fighter.as_str()).unwrap_or(&0),
+            spells.get(fighter.as_str()).map_or(0, Vec::len)
+        )?;
+    }
+
+    for (turn, events) in log.turns() {
+        let lines: Vec<String> = events.into_iter().filter_map(describe).collect();
+        if lines.is_empty() {
+            continue;
+        }
+        writeln!(out)?;
+        match turn {
+            0 => writeln!(out, "## Initiative")?,
+            _ => writeln!(out, "## Turn {}", turn)?,
+        }
+        writeln!(out)?;
+        for line in lines {
+            writeln!(out, "- {}", line)?;
+        }
+    }
+    Ok(())
+}
+
+fn outcome(battle: &BattleRecord, log: &BattleLog) -> String {
+    if !battle.is_completed {
+        return "Pending".to_string();
+    }
+    let Some(BattleEvent::BattleComplete { winner, loser, winner_final_hp, loser_final_hp, completion_reason, .. }) =
+        log.completion()
+    else {
+        return format!("No winner after {} turns", log.total_turns());
+    };
+    let how = match completion_reason {
+        BattleCompletionReason::HpDepleted(_) | BattleCompletionReason::LastStanding(_) => "by knockout".to_string(),
+        BattleCompletionReason::MaxTurnsReached(turns) => format!("on HP after {} turns", turns),
+    };
+    format!("🏆 {} won {} ({} HP to {}'s {})", winner, how, winner_final_hp, loser, loser_final_hp)
+}
+
+/// One line about `event`, `None` for the ones a reader can do without.
+pub fn describe(event: &BattleEvent) -> Option<String> {
+    let line = match event {
+        BattleEvent::Roll { actor, dice_expr, final_value, is_positive_crit, is_negative_crit, goal, .. } => {
+            let luck = if *is_positive_crit { " (crit!)" } else if *is_negative_crit { " (fumble)" } else { "" };
+            let with = dice_expr.map(|dice| format!(" with {}", dice)).unwrap_or_default();
+            format!("{} rolls {} for {}{}{}", actor, final_value, goal, with, luck)
+        }
+        BattleEvent::Attack { actor, target, raw_damage, shield_value, actual_damage, is_killing_blow, overkill_amount, .. } => {
+            let finisher = match (*is_killing_blow, *overkill_amount) {
+                (false, _) => String::new(),
+                (true, 0) => ", an exact kill".to_string(),
+                (true, overkill) => format!(", a killing blow with {} overkill", overkill),
+            };
+            format!(
+                "{} attacks {} for {} damage ({} against {} defense){}",
+                actor, target, actual_damage, raw_damage, shield_value, finisher
+            )
+        }
+        BattleEvent::HealthUpdate { fighter_name, from, to, source, .. } => match source {
+            Some(source) => format!(
+                "{} {} from {}'s {}: {} → {} HP",
+                fighter_name, tick_name(source.kind), source.caster, source.spell, from, to
+            ),
+            None => format!("{}: {} → {} HP", fighter_name, from, to),
+        },
+        BattleEvent::Heal { actor, amount, .. } => format!("{} heals for {}", actor, amount),
+        BattleEvent::SpellCast { actor, target, spell_name, .. } => {
+            format!("{} casts {} on {}", actor, spell_name, target)
+        }
+        BattleEvent::SpellOnCooldown { actor, spell_name, turns_left, .. } => {
+            format!("{} can't cast {} for {} more turns", actor, spell_name, turns_left)
+        }
+        BattleEvent::Defend { actor, .. } => format!("{} braces for the next attack", actor),
+        BattleEvent::Taunt { actor, target, penalty, .. } => {
+            format!("{} taunts {} into attacking at -{}", actor, target, penalty)
+        }
+        BattleEvent::Dodge { actor, attacker, .. } => format!("{} dodges {}'s attack", actor, attacker),
+        BattleEvent::Block { actor, attacker, blocked, .. } => {
+            format!("{} blocks {} of {}'s attack", actor, blocked, attacker)
+        }
+        BattleEvent::KnockedOut { fighter, by, remaining, .. } => {
+            format!("{} is knocked out by {}, {} left standing", fighter, by, remaining)
+        }
+        BattleEvent::FatigueApplied { level, bonus_damage, .. } => {
+            format!("Fatigue level {}: attacks deal {} extra damage", level, bonus_damage)
+        }
+        BattleEvent::ArenaEffect { arena, fighter, hp_change, .. } if *hp_change < 0 => {
+            format!("{} hurts {} for {}", arena, fighter, -hp_change)
+        }
+        BattleEvent::ArenaEffect { arena, fighter, hp_change, .. } => {
+            format!("{} boosts {}'s heal by {}", arena, fighter, hp_change)
+        }
+        BattleEvent::LootDropped { winner, loser, items, .. } => format!(
+            "{} loots {} from {}",
+            winner,
+            items.iter().map(|item| item.to_string()).collect::<Vec<_>>().join(", "),
+            loser
+        ),
+        BattleEvent::Prediction { .. } | BattleEvent::WinProbability { .. } | BattleEvent::BattleComplete { .. } => {
+            return None
+        }
+    };
+    Some(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::{battle_loop, EffectSource};
+    use crate::neopets::Neopet;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn fighter(name: &str) -> Neopet {
+        Neopet { health: 40, ..Neopet::test_fighter(name) }
+    }
+
+    fn finished_battle(seed: u64) -> BattleRecord {
+        let events = battle_loop(&fighter("Acara"), &fighter("Usul"), &mut StdRng::seed_from_u64(seed)).unwrap();
+        BattleRecord {
+            created_at: "2025-01-01T00:00:00Z".to_string(),
+            winner: BattleLog::new(&events).winner().map(String::from),
+            events,
+            is_completed: true,
+            tags: vec!["grudge-match".to_string()],
+            seed: Some(seed),
+            ..BattleRecord::test_battle("battle_1", "Acara", "Usul")
+        }
+    }
+
+    fn export(battle: &BattleRecord, format: ExportFormat) -> String {
+        let mut out = Vec::new();
+        export_battle(battle, format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_format_names() {
+        assert_eq!("NDJSON".parse(), Ok(ExportFormat::Ndjson));
+        assert_eq!("markdown".parse(), Ok(ExportFormat::Markdown));
+        assert_eq!("md".parse::<ExportFormat>().unwrap().to_string(), "md");
+        assert!("xml".parse::<ExportFormat>().is_err());
+    }
+
+    #[test]
+    fn test_ndjson_has_one_event_per_line() {
+        let battle = finished_battle(1);
+        let ndjson = export(&battle, ExportFormat::Ndjson);
+        let events: Vec<BattleEvent> = ndjson.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(events, battle.events);
+    }
+
+    #[test]
+    fn test_csv_has_a_row_per_hp_change() {
+        let battle = finished_battle(2);
+        let csv = export(&battle, ExportFormat::Csv);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(CSV_HEADER));
+
+        let changes = battle
+            .events
+            .iter()
+            .filter(|e| matches!(e, BattleEvent::HealthUpdate { from, to, .. } if from != to))
+            .count();
+        let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+        assert_eq!(rows.len(), changes);
+        for row in &rows {
+            let (before, after): (u32, u32) = (row[4].parse().unwrap(), row[5].parse().unwrap());
+            assert_eq!(row[3].parse::<u32>().unwrap(), before.abs_diff(after));
+            match row[2] {
+                "damage" => assert_eq!(row[6], "attack"),
+                "heal" => assert_eq!((row[6], row[7]), ("heal", row[1])),
+                change => panic!("unexpected change {}", change),
+            }
+        }
+    }
+
+    #[test]
+    fn test_csv_credits_effects_and_quotes_fields() {
+        let events = vec![BattleEvent::HealthUpdate {
+            fighter_name: "Usul".into(),
+            from: 30,
+            to: 26,
+            turn: 3,
+            source: Some(EffectSource {
+                kind: TickKind::Poison,
+                spell: "Venom, Extra \"Strong\"".to_string(),
+                caster: "Acara".into(),
+            }),
+        }];
+        let mut out = Vec::new();
+        write_csv(&events, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap().lines().nth(1),
+            Some("3,Usul,damage,4,30,26,\"poison (Venom, Extra \"\"Strong\"\")\",Acara")
+        );
+    }
+
+    #[test]
+    fn test_markdown_recap() {
+        let battle = finished_battle(3);
+        let md = export(&battle, ExportFormat::Markdown);
+        let winner = battle.winner.as_deref().unwrap();
+        assert!(md.starts_with("# Acara vs Usul\n"));
+        assert!(md.contains("- **Seed:** 3\n") && md.contains("- **Tags:** grudge-match\n"));
+        assert!(md.contains(&format!("- **Result:** 🏆 {} won", winner)));
+        assert!(md.contains("## Initiative") && md.contains("## Turn 1\n"));
+        assert!(md.contains("| Acara |") && md.contains("| Usul |"));
+
+        let pending = BattleRecord { events: vec![], is_completed: false, winner: None, ..battle };
+        let md = export(&pending, ExportFormat::Markdown);
+        assert!(md.contains("- **Result:** Pending") && !md.contains("## "));
+    }
+}
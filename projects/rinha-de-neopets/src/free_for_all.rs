@@ -0,0 +1,308 @@
+// src/free_for_all.rs
+use crate::battle::{
+    apply_fatigue, process_turn_with_state, BattleConfig, BattleError, BattleEvent, BattleState, InitiativeTie,
+    TargetSelection,
+};
+use crate::neopets::Neopet;
+use crate::strategy::{BattleContext, BattleStrategy, TakenAction};
+use rand::Rng;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+/// Every fighter rolls initiative once and adds their speed, fighters tied with someone
+/// else keep rolling while `config.initiative_tie` asks for rerolls. Returns the rolls
+/// and the fighters from first to last to act.
+pub(crate) fn roll_initiative_order<'a, R: Rng>(
+    fighters: &'a [Neopet],
+    config: &BattleConfig,
+    rng: &mut R,
+) -> (Vec<BattleEvent>, Vec<&'a Neopet>) {
+    let mut rolls: Vec<Vec<u32>> = vec![Vec::new(); fighters.len()];
+    let mut rolling: Vec<usize> = (0..fighters.len()).collect();
+    let mut events = Vec::new();
+
+    while !rolling.is_empty() {
+        for &i in &rolling {
+            let dice = config.dice_for(&fighters[i].name).initiative;
+            let roll = dice.roll(rng);
+            events.push(BattleEvent::Roll {
+                turn: 0, // Turn 0 for initiative phase
+                actor: fighters[i].name.as_str().into(),
+                dice: roll,
+                dice_expr: Some(dice),
+                final_value: roll + fighters[i].speed,
+                is_positive_crit: config.is_crit(dice, roll),
+                is_negative_crit: config.is_fumble(dice, roll),
+                goal: "initiative".to_string(),
+            });
+            rolls[i].push(roll + fighters[i].speed);
+        }
+        if config.initiative_tie != InitiativeTie::Reroll {
+            break;
+        }
+        // Tied fighters rolled the same number of times, so their rolls compare in full
+        rolling = (0..fighters.len())
+            .filter(|&i| (0..fighters.len()).any(|j| j != i && rolls[j] == rolls[i]))
+            .collect();
+    }
+
+    let mut order: Vec<usize> = (0..fighters.len()).collect();
+    order.sort_by_key(|&i| {
+        let health = match config.initiative_tie {
+            InitiativeTie::HigherHealth => fighters[i].health,
+            InitiativeTie::Reroll | InitiativeTie::FirstFighter => 0,
+        };
+        (Reverse(rolls[i].clone()), Reverse(health))
+    });
+    (events, order.into_iter().map(|i| &fighters[i]).collect())
+}
+
+/// The opponent `actor` goes after, `None` once nobody else is standing.
+pub fn choose_target<'a, R: Rng>(
+    actor: &Neopet,
+    fighters: &[&'a Neopet],
+    state: &BattleState,
+    selection: TargetSelection,
+    rng: &mut R,
+) -> Option<&'a Neopet> {
+    let hp = |fighter: &Neopet| state.get_hp(&fighter.name).unwrap_or(0);
+    let opponents: Vec<&'a Neopet> = fighters
+        .iter()
+        .copied()
+        .filter(|f| f.name != actor.name && hp(f) > 0)
+        .collect();
+    match selection {
+        // Only roll when there is an actual choice, so one-on-one endings use no extra dice
+        TargetSelection::Random if opponents.len() > 1 => Some(opponents[rng.random_range(0..opponents.len())]),
+        TargetSelection::Random => opponents.first().copied(),
+        TargetSelection::Weakest => opponents.into_iter().min_by_key(|f| hp(f)),
+        TargetSelection::Strongest => opponents.into_iter().min_by_key(|f| Reverse(hp(f))),
+    }
+}
+
+/// Runs a battle between any number of fighters, each acting on the strategy picked in
+/// their definition. Fighters act in initiative order, skipping the ones knocked out,
+/// and attack the opponent `config.target_selection` picks. The last pet standing wins,
+/// or the one with the most HP when the turns run out. Win predictions are two-fighter
+/// only, so `config.predictions` is ignored.
+pub fn free_for_all<R: Rng>(
+    fighters: &[Neopet],
+    config: &BattleConfig,
+    rng: &mut R,
+) -> Result<Vec<BattleEvent>, BattleError> {
+    if fighters.len() < 2 {
+        return Err(BattleError::NotEnoughFighters(fighters.len()));
+    }
+    let mut names = HashSet::new();
+    if let Some(duplicate) = fighters.iter().find(|f| !names.insert(f.name.as_str())) {
+        return Err(BattleError::DuplicateFighter(duplicate.name.clone()));
+    }
+    if config.max_turns == 0 {
+        return Err(BattleError::NoTurns);
+    }
+
+    let (mut events, order) = roll_initiative_order(fighters, config, rng);
+    let mut strategies: Vec<Box<dyn BattleStrategy>> = order.iter().map(|f| f.strategy.build()).collect();
+    let listed: Vec<&Neopet> = fighters.iter().collect();
+    let mut state = BattleState::with_fighters(&listed, config.max_turns).with_config_dice(config);
+    let mut actions = Vec::new();
+    let mut next = 0;
+
+    // Turn 0 is the initiative, each action uses up a turn
+    for turn in 1..=config.max_turns {
+        if state.is_complete {
+            break;
+        }
+        while state.get_hp(&order[next % order.len()].name)? == 0 {
+            next += 1;
+        }
+        let actor_index = next % order.len();
+        let actor = order[actor_index];
+        next += 1;
+        let Some(target) = choose_target(actor, &order, &state, config.target_selection, rng) else {
+            break;
+        };
+
+        apply_fatigue(config, turn, &mut state, &mut events);
+        let context = BattleContext::new(actor, target, turn, &state, &events, &actions, config.history_window);
+        let action = strategies[actor_index].choose_action(&context, &mut *rng);
+        actions.push(TakenAction { turn, actor: actor.name.clone(), action: action.clone() });
+
+        events.extend(process_turn_with_state(
+            actor, target, &action, turn, &mut state, config, rng,
+        )?);
+        if state.get_hp(&target.name)? == 0 {
+            events.push(BattleEvent::KnockedOut {
+                turn,
+                fighter: state.name(&target.name)?,
+                by: state.name(&actor.name)?,
+                remaining: state.standing().len() as u32,
+            });
+        }
+        // Burns and poison can finish off the actor before it acts
+        if state.get_hp(&actor.name)? == 0 {
+            let by = events.iter().rev().find_map(|e| match e {
+                BattleEvent::HealthUpdate { fighter_name, source: Some(source), .. } if **fighter_name == actor.name => {
+                    Some(source.caster.clone())
+                }
+                _ => None,
+            });
+            events.push(BattleEvent::KnockedOut {
+                turn,
+                fighter: state.name(&actor.name)?,
+                by: by.unwrap_or_else(|| "".into()),
+                remaining: state.standing().len() as u32,
+            });
+        }
+        state.check_battle_completion();
+    }
+
+    if let (Some((winner, loser)), Some(completion_reason)) =
+        (state.get_winner_loser(), state.completion_reason.clone())
+    {
+        events.push(BattleEvent::BattleComplete {
+            turn: state.current_turn,
+            winner_final_hp: state.get_hp(&winner)?,
+            loser_final_hp: state.get_hp(&loser)?,
+            winner: state.name(&winner)?,
+            loser: state.name(&loser)?,
+            completion_reason,
+        });
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::BattleCompletionReason;
+    use crate::neopets::Behavior;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn fighter(name: &str, health: u32) -> Neopet {
+        Neopet {
+            health,
+            heal_delta: 5,
+            base_attack: 12,
+            base_defense: 2,
+            behavior: Behavior::attack_or_heal(1.0),
+            ..Neopet::test_fighter(name)
+        }
+    }
+
+    fn long_config() -> BattleConfig {
+        BattleConfig { max_turns: 500, ..BattleConfig::default() }
+    }
+
+    #[test]
+    fn test_free_for_all_ends_with_the_last_pet_standing() {
+        let fighters = [fighter("Acara", 40), fighter("Usul", 40), fighter("Kougra", 40), fighter("Lupe", 40)];
+        for seed in 0..10 {
+            let events = free_for_all(&fighters, &long_config(), &mut StdRng::seed_from_u64(seed)).unwrap();
+
+            let knocked_out: Vec<(&str, u32)> = events
+                .iter()
+                .filter_map(|e| match e {
+                    BattleEvent::KnockedOut { fighter, remaining, .. } => Some((&**fighter, *remaining)),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(knocked_out.iter().map(|(_, remaining)| *remaining).collect::<Vec<_>>(), vec![3, 2, 1]);
+
+            let Some(BattleEvent::BattleComplete { winner, loser, completion_reason, .. }) = events.last() else {
+                panic!("seed {}: no BattleComplete", seed);
+            };
+            assert_eq!(*completion_reason, BattleCompletionReason::LastStanding(winner.to_string()));
+            assert_eq!(&**loser, knocked_out[0].0, "the first one down places last");
+            assert!(knocked_out.iter().all(|(name, _)| *name != &**winner));
+
+            // Nobody acts after being knocked out
+            for (name, _) in &knocked_out {
+                let out_turn = events.iter().find_map(|e| match e {
+                    BattleEvent::KnockedOut { turn, fighter, .. } if &**fighter == *name => Some(*turn),
+                    _ => None,
+                });
+                assert!(!events.iter().any(|e| matches!(
+                    e,
+                    BattleEvent::Attack { turn, actor, .. } if &**actor == *name && Some(*turn) > out_turn
+                )));
+            }
+        }
+    }
+
+    #[test]
+    fn test_free_for_all_is_reproducible_with_a_seed() {
+        let fighters = [fighter("Acara", 40), fighter("Usul", 40), fighter("Kougra", 40)];
+        let first = free_for_all(&fighters, &long_config(), &mut StdRng::seed_from_u64(3)).unwrap();
+        let second = free_for_all(&fighters, &long_config(), &mut StdRng::seed_from_u64(3)).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_free_for_all_on_time_goes_to_the_most_hp() {
+        let fighters = [fighter("Acara", 500), fighter("Usul", 900), fighter("Kougra", 500)];
+        let config = BattleConfig { max_turns: 3, fatigue_start_turn: None, ..BattleConfig::default() };
+        let events = free_for_all(&fighters, &config, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert!(matches!(
+            events.last(),
+            Some(BattleEvent::BattleComplete { winner, completion_reason: BattleCompletionReason::MaxTurnsReached(3), .. })
+                if &**winner == "Usul"
+        ));
+    }
+
+    #[test]
+    fn test_choose_target_by_selection() {
+        let (acara, usul, kougra) = (fighter("Acara", 40), fighter("Usul", 40), fighter("Kougra", 40));
+        let order = [&acara, &usul, &kougra];
+        let mut state = BattleState::with_fighters(&order, 10);
+        state.apply_damage("Usul", 30).unwrap();
+        state.apply_damage("Kougra", 10).unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let pick = |state: &BattleState, selection, rng: &mut StdRng| {
+            choose_target(&acara, &order, state, selection, rng).map(|f| f.name.clone())
+        };
+        assert_eq!(pick(&state, TargetSelection::Weakest, &mut rng).as_deref(), Some("Usul"));
+        assert_eq!(pick(&state, TargetSelection::Strongest, &mut rng).as_deref(), Some("Kougra"));
+        for _ in 0..20 {
+            assert_ne!(pick(&state, TargetSelection::Random, &mut rng).as_deref(), Some("Acara"));
+        }
+
+        state.apply_damage("Usul", 10).unwrap();
+        assert_eq!(pick(&state, TargetSelection::Strongest, &mut rng).as_deref(), Some("Kougra"));
+        state.apply_damage("Kougra", 30).unwrap();
+        assert_eq!(pick(&state, TargetSelection::Random, &mut rng), None);
+    }
+
+    #[test]
+    fn test_initiative_order_covers_everyone_once() {
+        let fighters = [fighter("Acara", 40), fighter("Usul", 40), fighter("Kougra", 40), fighter("Lupe", 40)];
+        for seed in 0..20 {
+            let (events, order) = roll_initiative_order(&fighters, &BattleConfig::default(), &mut StdRng::seed_from_u64(seed));
+            let mut names: Vec<&str> = order.iter().map(|f| f.name.as_str()).collect();
+            names.sort();
+            assert_eq!(names, vec!["Acara", "Kougra", "Lupe", "Usul"]);
+            assert!(events.len() >= fighters.len());
+        }
+    }
+
+    #[test]
+    fn test_free_for_all_rejects_bad_input() {
+        let config = BattleConfig::default();
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(
+            free_for_all(&[fighter("Acara", 40)], &config, &mut rng),
+            Err(BattleError::NotEnoughFighters(1))
+        );
+        assert_eq!(
+            free_for_all(&[fighter("Acara", 40), fighter("Usul", 40), fighter("Acara", 40)], &config, &mut rng),
+            Err(BattleError::DuplicateFighter("Acara".to_string()))
+        );
+        let config = BattleConfig { max_turns: 0, ..BattleConfig::default() };
+        assert_eq!(
+            free_for_all(&[fighter("Acara", 40), fighter("Usul", 40)], &config, &mut rng),
+            Err(BattleError::NoTurns)
+        );
+    }
+}
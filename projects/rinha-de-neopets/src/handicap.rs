@@ -0,0 +1,234 @@
+// src/handicap.rs
+use crate::neopets::Neopet;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A fighter stat a handicap can change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stat {
+    Hp,
+    Attack,
+    Defense,
+    Heal,
+    Speed,
+    Block,
+}
+
+impl fmt::Display for Stat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Stat::Hp => "hp",
+            Stat::Attack => "attack",
+            Stat::Defense => "defense",
+            Stat::Heal => "heal",
+            Stat::Speed => "speed",
+            Stat::Block => "block",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Stat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "hp" | "health" => Ok(Stat::Hp),
+            "attack" => Ok(Stat::Attack),
+            "defense" => Ok(Stat::Defense),
+            "heal" => Ok(Stat::Heal),
+            "speed" => Ok(Stat::Speed),
+            "block" => Ok(Stat::Block),
+            other => Err(format!("Unknown stat '{}', use hp, attack, defense, heal, speed or block", other)),
+        }
+    }
+}
+
+/// How much a stat changes: by a flat amount, or scaled by a percentage of itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Flat(i32),
+    Percent(i32),
+}
+
+/// One stat change, e.g. `attack=-2` or `hp=+20%`, stored as written.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct StatModifier {
+    pub stat: Stat,
+    pub change: Change,
+}
+
+impl StatModifier {
+    /// Changes the stat on `neopet`. Stats never drop below 0, nor HP below 1.
+    pub fn apply(&self, neopet: &mut Neopet) {
+        let stat = match self.stat {
+            Stat::Hp => &mut neopet.health,
+            Stat::Attack => &mut neopet.base_attack,
+            Stat::Defense => &mut neopet.base_defense,
+            Stat::Heal => &mut neopet.heal_delta,
+            Stat::Speed => &mut neopet.speed,
+            Stat::Block => &mut neopet.block_value,
+        };
+        *stat = match self.change {
+            Change::Flat(delta) => stat.saturating_add_signed(delta),
+            Change::Percent(percent) => (f64::from(*stat) * (100.0 + f64::from(percent)) / 100.0).round().max(0.0) as u32,
+        };
+        if self.stat == Stat::Hp {
+            neopet.health = neopet.health.max(1);
+        }
+    }
+}
+
+impl fmt::Display for StatModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.change {
+            Change::Flat(delta) => write!(f, "{}={:+}", self.stat, delta),
+            Change::Percent(percent) => write!(f, "{}={:+}%", self.stat, percent),
+        }
+    }
+}
+
+impl From<StatModifier> for String {
+    fn from(modifier: StatModifier) -> Self {
+        modifier.to_string()
+    }
+}
+
+impl TryFrom<String> for StatModifier {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl FromStr for StatModifier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (stat, amount) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid stat change '{}', expected e.g. attack=-2 or hp=+20%", s))?;
+        let stat = stat.parse()?;
+        let amount = amount.trim();
+        let (number, percent) = match amount.strip_suffix('%') {
+            Some(number) => (number, true),
+            None => (amount, false),
+        };
+        let number: i32 = number
+            .trim_start_matches('+')
+            .parse()
+            .map_err(|_| format!("Invalid amount '{}' for {}, expected e.g. -2, +20 or +20%", amount, stat))?;
+        let change = if percent { Change::Percent(number) } else { Change::Flat(number) };
+        Ok(StatModifier { stat, change })
+    }
+}
+
+/// Stat changes for each side of a battle, applied to the fighters as the battle
+/// starts so a mismatched pair can still make a close bout.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Handicap {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fighter1: Vec<StatModifier>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fighter2: Vec<StatModifier>,
+}
+
+impl Handicap {
+    pub fn is_empty(&self) -> bool {
+        self.fighter1.is_empty() && self.fighter2.is_empty()
+    }
+
+    /// Parses specs like `fighter1:attack=-2,hp=+20`. The side is `fighter1`, `fighter2`
+    /// or the fighter's name, and a side can be given more than once.
+    pub fn parse(specs: &[String], fighter1_name: &str, fighter2_name: &str) -> Result<Self, String> {
+        let mut handicap = Handicap::default();
+        for spec in specs {
+            let (side, changes) = spec
+                .split_once(':')
+                .ok_or_else(|| format!("Invalid handicap '{}', expected e.g. fighter1:attack=-2,hp=+20", spec))?;
+            let side = side.trim();
+            let modifiers = if side == "fighter1" || side == fighter1_name {
+                &mut handicap.fighter1
+            } else if side == "fighter2" || side == fighter2_name {
+                &mut handicap.fighter2
+            } else {
+                return Err(format!(
+                    "Unknown side '{}' in handicap, use fighter1, fighter2, {} or {}",
+                    side, fighter1_name, fighter2_name
+                ));
+            };
+            for change in changes.split(',') {
+                modifiers.push(change.parse()?);
+            }
+        }
+        Ok(handicap)
+    }
+
+    /// Applies each side's changes to its fighter, in order.
+    pub fn apply(&self, fighter1: &mut Neopet, fighter2: &mut Neopet) {
+        for (neopet, modifiers) in [(fighter1, &self.fighter1), (fighter2, &self.fighter2)] {
+            for modifier in modifiers {
+                modifier.apply(neopet);
+            }
+        }
+    }
+}
+
+/// Joins a side's changes for display, e.g. `attack=-2, hp=+20%`.
+pub fn describe_modifiers(modifiers: &[StatModifier]) -> String {
+    modifiers.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neopets::Behavior;
+
+    fn neopet(name: &str) -> Neopet {
+        Neopet {
+            health: 100,
+            heal_delta: 10,
+            base_attack: 5,
+            behavior: Behavior::attack_or_heal(0.5),
+            ..Neopet::test_fighter(name)
+        }
+    }
+
+    fn specs(specs: &[&str]) -> Vec<String> {
+        specs.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_handicap_sides() {
+        let handicap = Handicap::parse(&specs(&["fighter1:attack=-2,hp=+20", "Usul:defense=+10%"]), "Acara", "Usul").unwrap();
+        assert_eq!(
+            handicap.fighter1,
+            vec![
+                StatModifier { stat: Stat::Attack, change: Change::Flat(-2) },
+                StatModifier { stat: Stat::Hp, change: Change::Flat(20) },
+            ]
+        );
+        assert_eq!(handicap.fighter2, vec![StatModifier { stat: Stat::Defense, change: Change::Percent(10) }]);
+        assert_eq!(describe_modifiers(&handicap.fighter1), "attack=-2, hp=+20");
+        assert_eq!(serde_json::to_string(&handicap).unwrap(), r#"{"fighter1":["attack=-2","hp=+20"],"fighter2":["defense=+10%"]}"#);
+        assert_eq!(serde_json::from_str::<Handicap>(r#"{"fighter2":["defense=+10%"]}"#).unwrap().fighter2, handicap.fighter2);
+        assert!(Handicap::parse(&[], "Acara", "Usul").unwrap().is_empty());
+
+        for bad in ["attack=-2", "Kacheek:attack=-2", "fighter1:luck=+2", "fighter1:attack=lots", "fighter1:attack"] {
+            assert!(Handicap::parse(&specs(&[bad]), "Acara", "Usul").is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_apply_handicap() {
+        let handicap = Handicap::parse(&specs(&["fighter1:attack=-9,hp=-50%", "fighter2:hp=-500,heal=+5,speed=+3"]), "Acara", "Usul").unwrap();
+        let (mut acara, mut usul) = (neopet("Acara"), neopet("Usul"));
+        handicap.apply(&mut acara, &mut usul);
+        assert_eq!((acara.base_attack, acara.health), (0, 50));
+        assert_eq!((usul.health, usul.heal_delta, usul.speed), (1, 15, 3));
+        assert_eq!(usul.base_defense, 3);
+    }
+}
@@ -0,0 +1,257 @@
+// src/ids.rs
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Everything that gets an ID, each with its own counter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdKind {
+    Battle,
+    Event,
+    Bet,
+    Transaction,
+    Tournament,
+    Series,
+    Promotion,
+}
+
+impl IdKind {
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            IdKind::Battle => "battle",
+            IdKind::Event => "event",
+            IdKind::Bet => "bet",
+            IdKind::Transaction => "tx",
+            IdKind::Tournament => "tournament",
+            IdKind::Series => "series",
+            IdKind::Promotion => "promo",
+        }
+    }
+}
+
+/// Shared by every generator in the process, so ULIDs made within the same millisecond
+/// still sort in the order they were made.
+static ULIDS: Mutex<ulid::Generator> = Mutex::new(ulid::Generator::new());
+
+/// How long to wait for another process holding the counters before giving up.
+const LOCK_ATTEMPTS: u32 = 500;
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+/// Hands out IDs like `battle_42` from monotonic counters persisted in a JSON file.
+/// The file is guarded by a lock file, so colosseum and cassino running at the same
+/// time never hand out the same ID.
+#[derive(Debug, Clone)]
+pub struct IdGenerator {
+    counters: Counters,
+}
+
+#[derive(Debug, Clone)]
+enum Counters {
+    File(PathBuf),
+    /// Shared by the generator's clones, like the file is.
+    Memory(Arc<Mutex<HashMap<String, u64>>>),
+}
+
+/// Removes the lock file when dropped, even if updating the counters failed.
+struct CounterLock {
+    path: PathBuf,
+}
+
+impl Drop for CounterLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl IdGenerator {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            counters: Counters::File(path.as_ref().to_path_buf()),
+        }
+    }
+
+    /// Counters kept in memory only, starting from zero, for storage that never
+    /// touches the disk.
+    pub fn in_memory() -> Self {
+        Self {
+            counters: Counters::Memory(Arc::default()),
+        }
+    }
+
+    /// The next ID of `kind`.
+    pub fn next(&self, kind: IdKind) -> Result<String, Box<dyn std::error::Error>> {
+        self.next_unused(kind, |_| false)
+    }
+
+    /// The next ID of `kind` for which `is_taken` is false, skipping IDs already
+    /// handed out by older schemes.
+    pub fn next_unused(
+        &self,
+        kind: IdKind,
+        is_taken: impl Fn(&str) -> bool,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let path = match &self.counters {
+            Counters::File(path) => path,
+            Counters::Memory(counters) => {
+                let mut counters = counters.lock().unwrap_or_else(|e| e.into_inner());
+                return Ok(advance(&mut counters, kind, is_taken));
+            }
+        };
+        let _lock = Self::lock(path)?;
+
+        let mut counters = Self::load_counters(path)?;
+        let id = advance(&mut counters, kind, is_taken);
+        Self::save_counters(path, &counters)?;
+
+        Ok(id)
+    }
+
+    /// An ID of `kind` like `battle_01HZX3V8J9QK4W7T2M5N6P8R0S`, a ULID instead of a
+    /// counter: it sorts by creation time and needs no counters file to stay unique
+    /// across processes, its 80 random bits taking care of that. `is_taken` still
+    /// skips any ID already in use.
+    pub fn next_ulid(
+        &self,
+        kind: IdKind,
+        is_taken: impl Fn(&str) -> bool,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        loop {
+            let ulid = ULIDS.lock().unwrap_or_else(|e| e.into_inner()).generate()?;
+            let id = format!("{}_{}", kind.prefix(), ulid);
+            if !is_taken(&id) {
+                return Ok(id);
+            }
+        }
+    }
+
+    fn lock(path: &Path) -> Result<CounterLock, Box<dyn std::error::Error>> {
+        let lock_path = path.with_extension("lock");
+        for _ in 0..LOCK_ATTEMPTS {
+            match OpenOptions::new().write(true).create_new(true).open(&lock_path) {
+                Ok(_) => return Ok(CounterLock { path: lock_path }),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => thread::sleep(LOCK_RETRY_DELAY),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Err(format!(
+            "Timed out waiting for '{}', delete it if no other command is running",
+            lock_path.display()
+        ).into())
+    }
+
+    fn load_counters(path: &Path) -> Result<HashMap<String, u64>, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    fn save_counters(path: &Path, counters: &HashMap<String, u64>) -> Result<(), Box<dyn std::error::Error>> {
+        // Write then rename, so a crash never leaves half a counters file behind
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(counters)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+/// Bumps `kind`'s counter until it gives an ID `is_taken` is false for.
+fn advance(counters: &mut HashMap<String, u64>, kind: IdKind, is_taken: impl Fn(&str) -> bool) -> String {
+    let counter = counters.entry(kind.prefix().to_string()).or_insert(0);
+    loop {
+        *counter += 1;
+        let id = format!("{}_{}", kind.prefix(), counter);
+        if !is_taken(&id) {
+            break id;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_ids_are_sequential_per_kind() {
+        let temp_dir = tempdir().unwrap();
+        let ids = IdGenerator::new(temp_dir.path().join("ids.json"));
+
+        assert_eq!(ids.next(IdKind::Battle).unwrap(), "battle_1");
+        assert_eq!(ids.next(IdKind::Battle).unwrap(), "battle_2");
+        assert_eq!(ids.next(IdKind::Event).unwrap(), "event_1");
+        assert_eq!(ids.next(IdKind::Transaction).unwrap(), "tx_1");
+    }
+
+    #[test]
+    fn test_counters_persist_between_generators() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("ids.json");
+
+        IdGenerator::new(&path).next(IdKind::Bet).unwrap();
+        assert_eq!(IdGenerator::new(&path).next(IdKind::Bet).unwrap(), "bet_2");
+        assert!(!path.with_extension("lock").exists());
+    }
+
+    #[test]
+    fn test_next_unused_skips_taken_ids() {
+        let temp_dir = tempdir().unwrap();
+        let ids = IdGenerator::new(temp_dir.path().join("ids.json"));
+
+        let taken = ["event_1", "event_2"];
+        assert_eq!(ids.next_unused(IdKind::Event, |id| taken.contains(&id)).unwrap(), "event_3");
+        assert_eq!(ids.next(IdKind::Event).unwrap(), "event_4");
+    }
+
+    #[test]
+    fn test_in_memory_counters_are_shared_by_clones() {
+        let ids = IdGenerator::in_memory();
+        assert_eq!(ids.next(IdKind::Battle).unwrap(), "battle_1");
+        assert_eq!(ids.clone().next(IdKind::Battle).unwrap(), "battle_2");
+        assert_eq!(IdGenerator::in_memory().next(IdKind::Battle).unwrap(), "battle_1");
+    }
+
+    #[test]
+    fn test_ulids_sort_in_creation_order() {
+        let ids = IdGenerator::in_memory();
+        let made: Vec<String> = (0..50).map(|_| ids.next_ulid(IdKind::Battle, |_| false).unwrap()).collect();
+        assert!(made.iter().all(|id| id.starts_with("battle_") && id.len() == "battle_".len() + 26));
+        assert!(made.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // The first one made is taken, the next one isn't
+        let tries = std::cell::Cell::new(0);
+        ids.next_ulid(IdKind::Battle, |_| {
+            tries.set(tries.get() + 1);
+            tries.get() == 1
+        }).unwrap();
+        assert_eq!(tries.get(), 2);
+    }
+
+    #[test]
+    fn test_concurrent_generators_never_repeat_ids() {
+        let temp_dir = tempdir().unwrap();
+        let path = Arc::new(temp_dir.path().join("ids.json"));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let path = Arc::clone(&path);
+                thread::spawn(move || {
+                    let ids = IdGenerator::new(path.as_path());
+                    (0..20).map(|_| ids.next(IdKind::Battle).unwrap()).collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let all: Vec<String> = handles.into_iter().flat_map(|h| h.join().unwrap()).collect();
+        let unique: HashSet<&String> = all.iter().collect();
+        assert_eq!(all.len(), 80);
+        assert_eq!(unique.len(), 80);
+    }
+}
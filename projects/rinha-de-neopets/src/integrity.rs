@@ -0,0 +1,113 @@
+// src/integrity.rs
+use crate::battle::{battle_loop_with_config, drop_loot, BattleConfig, BattleError, BattleEvent};
+use crate::loot::LootTables;
+use crate::neopets::Neopet;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use sha2::{Digest, Sha256};
+
+/// SHA-256 of `events`, as lowercase hex. Each event is hashed as its compact JSON
+/// followed by a newline, so the same events always give the same hash.
+pub fn battle_hash(events: &[BattleEvent]) -> String {
+    let mut hasher = Sha256::new();
+    for event in events {
+        // Serializing plain data to memory can't fail
+        hasher.update(serde_json::to_vec(event).unwrap_or_default());
+        hasher.update(b"\n");
+    }
+    hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Fights the battle behind `events` again with `seed`, to check it was really fought
+/// that way. Win-probability predictions, rollout estimates and loot are only rolled
+/// again if `events` have them, like battles that were watched live or that dropped loot, and rolls
+/// only name their dice if those of `events` do.
+pub fn resimulate(
+    events: &[BattleEvent],
+    fighter1: &Neopet,
+    fighter2: &Neopet,
+    config: &BattleConfig,
+    loot_tables: &LootTables,
+    seed: u64,
+) -> Result<Vec<BattleEvent>, BattleError> {
+    // The estimates are spaced out as they were when the battle was fought
+    let estimates: Vec<(u32, u32)> = events
+        .iter()
+        .filter_map(|e| match e {
+            BattleEvent::WinProbability { turn, rollouts, .. } => Some((*turn, *rollouts)),
+            _ => None,
+        })
+        .collect();
+    let config = BattleConfig {
+        predictions: events.iter().any(|e| matches!(e, BattleEvent::Prediction { .. })),
+        win_probability_every: match estimates.as_slice() {
+            [] => None,
+            [(first, _), (second, _), ..] => Some(second - first),
+            [_] => Some(config.max_turns.max(1)),
+        },
+        rollouts: estimates.first().map_or(config.rollouts, |(_, rollouts)| *rollouts),
+        ..config.clone()
+    };
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut rerun = battle_loop_with_config(fighter1, fighter2, &config, &mut rng)?;
+    if events.iter().any(|e| matches!(e, BattleEvent::LootDropped { .. })) {
+        drop_loot(&mut rerun, loot_tables, &mut rng);
+    }
+    if events.iter().any(|e| matches!(e, BattleEvent::Roll { dice_expr: None, .. })) {
+        for event in &mut rerun {
+            if let BattleEvent::Roll { dice_expr, .. } = event {
+                *dice_expr = None;
+            }
+        }
+    }
+    Ok(rerun)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fight(seed: u64, config: &BattleConfig) -> Vec<BattleEvent> {
+        battle_loop_with_config(&Neopet::test_fighter("Acara"), &Neopet::test_fighter("Usul"), config, &mut StdRng::seed_from_u64(seed)).unwrap()
+    }
+
+    #[test]
+    fn test_hash_changes_with_any_event() {
+        let events = fight(1, &BattleConfig::default());
+        let hash = battle_hash(&events);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(battle_hash(&events.clone()), hash);
+
+        let mut tampered = events.clone();
+        if let Some(BattleEvent::BattleComplete { winner_final_hp, .. }) = tampered.last_mut() {
+            *winner_final_hp += 1;
+        }
+        assert_ne!(battle_hash(&tampered), hash);
+        assert_ne!(battle_hash(&events[1..]), hash);
+    }
+
+    #[test]
+    fn test_resimulating_the_seed_gives_the_same_battle() {
+        let tables = LootTables::default();
+        for (predictions, win_probability_every) in [(false, None), (true, None), (false, Some(4)), (false, Some(500))] {
+            let config = BattleConfig { predictions, win_probability_every, rollouts: 5, ..BattleConfig::default() };
+            let events = fight(7, &config);
+            let rerun = resimulate(&events, &Neopet::test_fighter("Acara"), &Neopet::test_fighter("Usul"), &BattleConfig::default(), &tables, 7);
+            assert_eq!(battle_hash(&rerun.unwrap()), battle_hash(&events));
+        }
+
+        let events = fight(7, &BattleConfig::default());
+        let rerun = resimulate(&events, &Neopet::test_fighter("Acara"), &Neopet::test_fighter("Usul"), &BattleConfig::default(), &tables, 8);
+        assert_ne!(battle_hash(&rerun.unwrap()), battle_hash(&events));
+
+        // Battles saved before rolls named their dice still check out
+        let mut legacy = fight(7, &BattleConfig::default());
+        for event in &mut legacy {
+            if let BattleEvent::Roll { dice_expr, .. } = event {
+                *dice_expr = None;
+            }
+        }
+        let rerun = resimulate(&legacy, &Neopet::test_fighter("Acara"), &Neopet::test_fighter("Usul"), &BattleConfig::default(), &tables, 7);
+        assert_eq!(battle_hash(&rerun.unwrap()), battle_hash(&legacy));
+    }
+}
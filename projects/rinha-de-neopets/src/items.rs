@@ -0,0 +1,216 @@
+// src/items.rs
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::str::FromStr;
+use crate::neopets::Neopet;
+
+/// Where an item is worn. A fighter has at most one item in each slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Slot {
+    Weapon,
+    Armor,
+    Trinket,
+}
+
+impl fmt::Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Slot::Weapon => "weapon",
+            Slot::Armor => "armor",
+            Slot::Trinket => "trinket",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Slot {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "weapon" => Ok(Slot::Weapon),
+            "armor" => Ok(Slot::Armor),
+            "trinket" => Ok(Slot::Trinket),
+            _ => Err(format!("Unknown slot '{}', expected weapon, armor or trinket", s)),
+        }
+    }
+}
+
+/// An item fighters can equip. The bonuses are added to the fighter's stats while
+/// it is worn and can be negative, e.g. heavy armor that slows attacks down.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Equipment {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub slot: Slot,
+    #[serde(default)]
+    pub attack: i32,
+    #[serde(default)]
+    pub defense: i32,
+    #[serde(default)]
+    pub heal: i32,
+}
+
+impl Equipment {
+    /// The bonuses as shown to players, e.g. "+3 ATK, -1 DEF".
+    pub fn bonuses(&self) -> String {
+        let bonuses: Vec<String> = [(self.attack, "ATK"), (self.defense, "DEF"), (self.heal, "Heal")]
+            .iter()
+            .filter(|(amount, _)| *amount != 0)
+            .map(|(amount, stat)| format!("{:+} {}", amount, stat))
+            .collect();
+        if bonuses.is_empty() { "no bonuses".to_string() } else { bonuses.join(", ") }
+    }
+}
+
+impl fmt::Display for Equipment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let icon = match self.slot {
+            Slot::Weapon => "🗡️",
+            Slot::Armor => "🛡️",
+            Slot::Trinket => "📿",
+        };
+        write!(f, "{} {} ({}): {}", icon, self.name, self.slot, self.bonuses())
+    }
+}
+
+/// The item names a fighter has equipped, by slot.
+pub type Loadout = BTreeMap<Slot, String>;
+
+/// Every item fighters can equip, referenced by name.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(transparent)]
+pub struct ItemCatalog {
+    items: Vec<Equipment>,
+}
+
+impl ItemCatalog {
+    /// Loads the catalog, or no items at all if the file doesn't exist.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Equipment> {
+        self.items.iter().find(|item| item.name == name)
+    }
+
+    pub fn items(&self) -> &[Equipment] {
+        &self.items
+    }
+
+    /// The fighter as they enter battle, with the bonuses of every item in the
+    /// loadout applied. Stats never drop below 0. Fails on the first item the
+    /// catalog doesn't know about.
+    pub fn equip(&self, neopet: &Neopet, loadout: &Loadout) -> Result<Neopet, String> {
+        let mut equipped = neopet.clone();
+        for name in loadout.values() {
+            let item = self
+                .get(name)
+                .ok_or_else(|| format!("Neopet {}: unknown item '{}'", neopet.name, name))?;
+            equipped.base_attack = add_bonus(equipped.base_attack, item.attack);
+            equipped.base_defense = add_bonus(equipped.base_defense, item.defense);
+            equipped.heal_delta = add_bonus(equipped.heal_delta, item.heal);
+        }
+        Ok(equipped)
+    }
+}
+
+fn add_bonus(stat: u32, bonus: i32) -> u32 {
+    stat.saturating_add_signed(bonus)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neopets::Behavior;
+
+    fn neopet() -> Neopet {
+        Neopet {
+            health: 100,
+            heal_delta: 10,
+            base_attack: 5,
+            behavior: Behavior::attack_or_heal(0.5),
+            ..Neopet::test_fighter("Acara")
+        }
+    }
+
+    fn item(name: &str, slot: Slot, attack: i32, defense: i32, heal: i32) -> Equipment {
+        Equipment { name: name.to_string(), description: String::new(), slot, attack, defense, heal }
+    }
+
+    fn catalog() -> ItemCatalog {
+        ItemCatalog {
+            items: vec![
+                item("Sword", Slot::Weapon, 4, 0, 0),
+                item("Plate", Slot::Armor, -2, 6, 0),
+                item("Charm", Slot::Trinket, 0, 0, 5),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_equip_adds_every_bonus() {
+        let loadout = Loadout::from([
+            (Slot::Weapon, "Sword".to_string()),
+            (Slot::Armor, "Plate".to_string()),
+            (Slot::Trinket, "Charm".to_string()),
+        ]);
+        let equipped = catalog().equip(&neopet(), &loadout).unwrap();
+        assert_eq!(equipped.base_attack, 7);
+        assert_eq!(equipped.base_defense, 9);
+        assert_eq!(equipped.heal_delta, 15);
+        assert_eq!(equipped.health, 100);
+    }
+
+    #[test]
+    fn test_equip_never_drops_stats_below_zero() {
+        let catalog = ItemCatalog { items: vec![item("Anvil", Slot::Armor, -20, 1, -20)] };
+        let equipped = catalog.equip(&neopet(), &Loadout::from([(Slot::Armor, "Anvil".to_string())])).unwrap();
+        assert_eq!(equipped.base_attack, 0);
+        assert_eq!(equipped.heal_delta, 0);
+    }
+
+    #[test]
+    fn test_equip_rejects_unknown_items() {
+        let loadout = Loadout::from([(Slot::Weapon, "Excalibur".to_string())]);
+        assert!(catalog().equip(&neopet(), &loadout).unwrap_err().contains("Excalibur"));
+        assert_eq!(catalog().equip(&neopet(), &Loadout::new()).unwrap(), neopet());
+    }
+
+    #[test]
+    fn test_bonuses_skip_zero_stats() {
+        assert_eq!(item("Plate", Slot::Armor, -2, 6, 0).bonuses(), "-2 ATK, +6 DEF");
+        assert_eq!(item("Pebble", Slot::Trinket, 0, 0, 0).bonuses(), "no bonuses");
+    }
+
+    #[test]
+    fn test_slot_parses_case_insensitively() {
+        assert_eq!("Weapon".parse::<Slot>(), Ok(Slot::Weapon));
+        assert!("helmet".parse::<Slot>().is_err());
+    }
+
+    #[test]
+    fn test_assets_items_load() {
+        let catalog = ItemCatalog::load("assets/items.json").unwrap();
+        assert!(!catalog.items().is_empty());
+        for slot in [Slot::Weapon, Slot::Armor, Slot::Trinket] {
+            assert!(catalog.items().iter().any(|item| item.slot == slot), "no {}", slot);
+        }
+    }
+
+    #[test]
+    fn test_load_missing_file_has_no_items() {
+        assert!(ItemCatalog::load("/nonexistent/items.json").unwrap().items().is_empty());
+    }
+}
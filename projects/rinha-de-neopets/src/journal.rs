@@ -0,0 +1,183 @@
+// src/journal.rs
+use crate::battle::BattleEvent;
+use crate::battle_log::BattleLog;
+use crate::observer::Observer;
+use crate::storage::BattleRecord;
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Append-only record of a battle being fought: the pending `BattleRecord` on the first
+/// line, then every event as it happens, one JSON object per line. Events only reach
+/// the store once the battle is filed, so a crash mid-battle used to lose all of them;
+/// with a journal `read` rebuilds the battle from what was written.
+///
+/// The file stays locked while the journal is open, which tells a battle still being
+/// fought (by another command, or the cassino's) from one whose process died.
+pub struct Journal {
+    file: File,
+    path: PathBuf,
+    error: Option<io::Error>,
+}
+
+/// Where the journal of battle `battle_id` goes in `dir`.
+pub fn journal_path(dir: &Path, battle_id: &str) -> PathBuf {
+    dir.join(format!("{}.jsonl", battle_id))
+}
+
+impl Journal {
+    /// Starts the journal of `battle` in `dir`. Fails if the battle already has one,
+    /// because it is being fought elsewhere or its last fight needs recovering.
+    pub fn create(dir: &Path, battle: &BattleRecord) -> Result<Self, Box<dyn std::error::Error>> {
+        fs::create_dir_all(dir)?;
+        let path = journal_path(dir, &battle.id);
+        let file = match OpenOptions::new().append(true).create_new(true).open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                return Err(format!("Battle '{}' already has a journal at {}, it is being fought or needs recovering", battle.id, path.display()).into());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        file.try_lock()?;
+        let mut journal = Self { file, path, error: None };
+        journal.append(&BattleRecord { events: Vec::new(), ..battle.clone() })?;
+        Ok(journal)
+    }
+
+    fn append<T: Serialize>(&mut self, value: &T) -> io::Result<()> {
+        let mut line = serde_json::to_vec(value)?;
+        line.push(b'\n');
+        // A single write, so in append mode no other write lands inside the line
+        self.file.write_all(&line)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Why events stopped being written, if they did.
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+}
+
+impl Observer for Journal {
+    fn on_event(&mut self, event: &BattleEvent) {
+        if self.error.is_none() {
+            self.error = self.append(event).err();
+        }
+    }
+
+    fn on_battle_end(&mut self) {
+        if self.error.is_none() {
+            self.error = self.file.sync_data().err();
+        }
+    }
+}
+
+/// A battle rebuilt from its journal, with the events written before it stopped.
+#[derive(Debug, Clone)]
+pub struct JournaledBattle {
+    pub battle: BattleRecord,
+    /// Whether the journal got to the `BattleComplete` event.
+    pub finished: bool,
+}
+
+/// Reads a journal back. A last line cut short, as a crash mid-write leaves it, is
+/// dropped; anything else unreadable is an error.
+pub fn read(path: &Path) -> Result<JournaledBattle, Box<dyn std::error::Error>> {
+    let mut lines = BufReader::new(File::open(path)?).lines();
+    let header = lines.next().ok_or_else(|| format!("Journal {} is empty", path.display()))??;
+    let mut battle: BattleRecord = serde_json::from_str(&header)
+        .map_err(|e| format!("Journal {}: {}", path.display(), e))?;
+    let lines: Vec<String> = lines.collect::<Result<_, _>>()?;
+    for (i, line) in lines.iter().enumerate() {
+        match serde_json::from_str(line) {
+            Ok(event) => battle.events.push(event),
+            Err(_) if i + 1 == lines.len() => break,
+            Err(e) => return Err(format!("Journal {} line {}: {}", path.display(), i + 2, e).into()),
+        }
+    }
+    let finished = BattleLog::from(&battle.events).completion().is_some();
+    Ok(JournaledBattle { battle, finished })
+}
+
+/// Journals in `dir` that no process holds open, left behind by fights that crashed
+/// or by battles not saved since. Sorted by path.
+pub fn abandoned(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "jsonl") {
+            continue;
+        }
+        match File::open(&path)?.try_lock() {
+            Ok(()) => paths.push(path),
+            Err(TryLockError::WouldBlock) => {}
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::BattleCompletionReason;
+    use tempfile::tempdir;
+
+    fn battle() -> BattleRecord {
+        BattleRecord {
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            seed: Some(7),
+            ..BattleRecord::test_battle("battle_1", "Acara", "Usul")
+        }
+    }
+
+    #[test]
+    fn test_journal_rebuilds_the_battle_written_so_far() {
+        let dir = tempdir().unwrap();
+        let mut journal = Journal::create(dir.path(), &battle()).unwrap();
+        assert!(Journal::create(dir.path(), &battle()).is_err());
+        journal.on_event(&BattleEvent::Heal { turn: 1, actor: "Acara".into(), amount: 5 });
+        // Still being written to, so not abandoned
+        assert!(abandoned(dir.path()).unwrap().is_empty());
+
+        let path = journal.path().to_path_buf();
+        drop(journal);
+        let mut torn = fs::read_to_string(&path).unwrap();
+        torn.push_str(r#"{"type":"heal","tu"#);
+        fs::write(&path, torn).unwrap();
+
+        assert_eq!(abandoned(dir.path()).unwrap(), vec![path.clone()]);
+        let journaled = read(&path).unwrap();
+        assert!(!journaled.finished);
+        assert_eq!(journaled.battle.seed, Some(7));
+        assert_eq!(journaled.battle.events, [BattleEvent::Heal { turn: 1, actor: "Acara".into(), amount: 5 }]);
+    }
+
+    #[test]
+    fn test_journal_knows_a_finished_battle() {
+        let dir = tempdir().unwrap();
+        let mut journal = Journal::create(dir.path(), &battle()).unwrap();
+        journal.on_event(&BattleEvent::BattleComplete {
+            turn: 3,
+            winner: "Usul".into(),
+            loser: "Acara".into(),
+            winner_final_hp: 10,
+            loser_final_hp: 0,
+            completion_reason: BattleCompletionReason::HpDepleted("Acara".into()),
+        });
+        journal.on_battle_end();
+        assert!(journal.error().is_none());
+
+        let journaled = read(journal.path()).unwrap();
+        assert!(journaled.finished);
+        assert_eq!(journaled.battle.events.len(), 1);
+    }
+}
@@ -0,0 +1,280 @@
+// src/ledger.rs
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// Money moving in or out of the bettor's bankroll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionKind {
+    /// Money put on a bet when it is placed.
+    Stake,
+    /// Money won when a bet is settled.
+    Payout,
+    /// Winnings owed to a promotion: everything a free bet wins, or the extra
+    /// from an odds boost.
+    Bonus,
+    /// Stake given back when the event a bet is on is voided.
+    Refund,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transaction {
+    pub id: String,
+    pub kind: TransactionKind,
+    pub amount: f64, // always positive, `kind` tells the direction
+    pub bet_id: String,
+    pub timestamp: String, // RFC 3339
+}
+
+impl Transaction {
+    /// The amount's effect on the bankroll.
+    pub fn signed_amount(&self) -> f64 {
+        match self.kind {
+            TransactionKind::Stake => -self.amount,
+            TransactionKind::Payout | TransactionKind::Bonus | TransactionKind::Refund => self.amount,
+        }
+    }
+}
+
+/// Every transaction in the order it happened, stored in `assets/ledger.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(transparent)]
+pub struct Ledger {
+    pub transactions: Vec<Transaction>,
+}
+
+/// Bankroll at the end of a day that had transactions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyBankroll {
+    pub date: NaiveDate,
+    pub balance: f64,
+    pub change: f64,
+}
+
+impl Ledger {
+    /// Loads the ledger, or an empty one if the file doesn't exist.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(path)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    pub fn total(&self, kind: TransactionKind) -> f64 {
+        self.transactions.iter().filter(|t| t.kind == kind).map(|t| t.amount).sum()
+    }
+
+    /// Real money staked on `bet_id`, what voiding its event gives back. Free bets
+    /// staked none.
+    pub fn staked_on(&self, bet_id: &str) -> f64 {
+        self.transactions.iter()
+            .filter(|t| t.kind == TransactionKind::Stake && t.bet_id == bet_id)
+            .map(|t| t.amount)
+            .sum()
+    }
+
+    /// Net profit, i.e. the bankroll relative to where it started.
+    pub fn balance(&self) -> f64 {
+        self.transactions.iter().map(|t| t.signed_amount()).sum()
+    }
+
+    /// Closing bankroll of each day with transactions, oldest first. Transactions with an
+    /// unreadable timestamp are skipped.
+    pub fn daily_bankroll(&self) -> Vec<DailyBankroll> {
+        let mut dated: Vec<(NaiveDate, f64)> = self.transactions
+            .iter()
+            .filter_map(|t| {
+                let date = DateTime::parse_from_rfc3339(&t.timestamp).ok()?.date_naive();
+                Some((date, t.signed_amount()))
+            })
+            .collect();
+        dated.sort_by_key(|(date, _)| *date);
+
+        let mut days: Vec<DailyBankroll> = Vec::new();
+        let mut balance = 0.0;
+        for (date, amount) in dated {
+            balance += amount;
+            match days.last_mut() {
+                Some(day) if day.date == date => {
+                    day.balance = balance;
+                    day.change += amount;
+                }
+                _ => days.push(DailyBankroll { date, balance, change: amount }),
+            }
+        }
+        days
+    }
+}
+
+/// One row of the bankroll chart: the axis label and one cell per day, so that
+/// each day's segment can be colored by its profit or loss.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartRow {
+    pub label: String,
+    pub cells: Vec<String>,
+}
+
+/// Line chart of the daily closing bankroll, `height` rows tall, highest row first.
+pub fn bankroll_chart(days: &[DailyBankroll], height: usize) -> Vec<ChartRow> {
+    const DAY_WIDTH: usize = 3;
+
+    if days.is_empty() || height == 0 {
+        return Vec::new();
+    }
+
+    let min = days.iter().map(|d| d.balance).fold(f64::INFINITY, f64::min);
+    let max = days.iter().map(|d| d.balance).fold(f64::NEG_INFINITY, f64::max);
+    let rows = if max > min { height } else { 1 };
+    let row_of = |balance: f64| -> usize {
+        if rows == 1 {
+            0
+        } else {
+            ((balance - min) / (max - min) * (rows - 1) as f64).round() as usize
+        }
+    };
+
+    (0..rows)
+        .rev()
+        .map(|row| {
+            let label = if row == rows - 1 {
+                format!("{:>9.2} ┤", max)
+            } else if row == 0 {
+                format!("{:>9.2} ┤", min)
+            } else {
+                format!("{:>9} │", "")
+            };
+            let cells = days
+                .iter()
+                .enumerate()
+                .map(|(i, day)| {
+                    let level = row_of(day.balance);
+                    let previous = if i == 0 { level } else { row_of(days[i - 1].balance) };
+                    let (low, high) = (level.min(previous), level.max(previous));
+                    // Climb or drop from the previous close, then hold the new one
+                    let joint = if row == level {
+                        '●'
+                    } else if row > low && row < high || row == previous && row != level {
+                        '│'
+                    } else {
+                        ' '
+                    };
+                    let hold = if row == level { '─' } else { ' ' };
+                    std::iter::once(joint).chain(std::iter::repeat_n(hold, DAY_WIDTH - 1)).collect()
+                })
+                .collect();
+            ChartRow { label, cells }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx(kind: TransactionKind, amount: f64, timestamp: &str) -> Transaction {
+        Transaction {
+            id: "tx_1".to_string(),
+            kind,
+            amount,
+            bet_id: "bet_1".to_string(),
+            timestamp: timestamp.to_string(),
+        }
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn render(rows: &[ChartRow]) -> Vec<String> {
+        rows.iter().map(|r| format!("{}{}", r.label, r.cells.concat())).collect()
+    }
+
+    #[test]
+    fn test_balance_and_totals() {
+        let ledger = Ledger {
+            transactions: vec![
+                tx(TransactionKind::Stake, 10.0, "2024-01-01T10:00:00+00:00"),
+                tx(TransactionKind::Payout, 25.0, "2024-01-01T12:00:00+00:00"),
+                tx(TransactionKind::Stake, 5.0, "2024-01-02T10:00:00+00:00"),
+                tx(TransactionKind::Bonus, 4.0, "2024-01-02T12:00:00+00:00"),
+            ],
+        };
+        assert_eq!(ledger.total(TransactionKind::Stake), 15.0);
+        assert_eq!(ledger.total(TransactionKind::Payout), 25.0);
+        assert_eq!(ledger.total(TransactionKind::Bonus), 4.0);
+        assert_eq!(ledger.balance(), 14.0);
+    }
+
+    #[test]
+    fn test_daily_bankroll_aggregates_by_day() {
+        let ledger = Ledger {
+            transactions: vec![
+                tx(TransactionKind::Stake, 10.0, "2024-01-01T10:00:00+00:00"),
+                tx(TransactionKind::Payout, 25.0, "2024-01-01T12:00:00+00:00"),
+                tx(TransactionKind::Stake, 5.0, "2024-01-03T10:00:00+00:00"),
+                tx(TransactionKind::Stake, 5.0, "not a timestamp"),
+            ],
+        };
+        assert_eq!(
+            ledger.daily_bankroll(),
+            vec![
+                DailyBankroll { date: date("2024-01-01"), balance: 15.0, change: 15.0 },
+                DailyBankroll { date: date("2024-01-03"), balance: 10.0, change: -5.0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_refund_gives_back_the_stake() {
+        let stake = tx(TransactionKind::Stake, 10.0, "2024-01-01T10:00:00+00:00");
+        let mut ledger = Ledger {
+            transactions: vec![stake.clone(), Transaction { bet_id: "bet_2".to_string(), ..stake }],
+        };
+        assert_eq!(ledger.staked_on("bet_1"), 10.0);
+        assert_eq!(ledger.staked_on("free_bet"), 0.0);
+
+        ledger.transactions.push(tx(TransactionKind::Refund, 10.0, "2024-01-02T10:00:00+00:00"));
+        assert_eq!(ledger.balance(), -10.0);
+    }
+
+    #[test]
+    fn test_bankroll_chart_empty() {
+        assert!(bankroll_chart(&[], 5).is_empty());
+    }
+
+    #[test]
+    fn test_bankroll_chart_has_one_cell_per_day() {
+        let days = vec![
+            DailyBankroll { date: date("2024-01-01"), balance: -10.0, change: -10.0 },
+            DailyBankroll { date: date("2024-01-02"), balance: 20.0, change: 30.0 },
+        ];
+        let rows = bankroll_chart(&days, 3);
+        assert!(rows.iter().all(|r| r.cells.len() == 2));
+        assert_eq!(
+            render(&rows),
+            vec![
+                "    20.00 ┤   ●──",
+                "          │   │  ",
+                "   -10.00 ┤●──│  ",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_missing_ledger_is_empty() {
+        let ledger = Ledger::load("/nonexistent/ledger.json").unwrap();
+        assert!(ledger.transactions.is_empty());
+    }
+}
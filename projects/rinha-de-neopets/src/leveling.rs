@@ -0,0 +1,176 @@
+// src/leveling.rs
+use crate::handicap::StatModifier;
+use crate::neopets::Neopet;
+use crate::storage::BattleRecord;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// How fighters grow with experience, stored in `assets/leveling_rules.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelingRules {
+    /// Experience the winner of a battle gets.
+    pub win_xp: u32,
+    /// Experience the loser gets, or both fighters when nobody won.
+    pub loss_xp: u32,
+    /// Experience from level 1 to level 2. Every level after that takes this much
+    /// more than the one before it.
+    pub base_xp: u32,
+    pub max_level: u32,
+    /// Stat changes a fighter gets for every level past 1, percentages compounding.
+    pub per_level: Vec<StatModifier>,
+}
+
+impl Default for LevelingRules {
+    fn default() -> Self {
+        Self {
+            win_xp: 50,
+            loss_xp: 20,
+            base_xp: 100,
+            max_level: 20,
+            per_level: ["hp=+5", "attack=+1", "defense=+1"]
+                .iter()
+                .filter_map(|modifier| modifier.parse().ok())
+                .collect(),
+        }
+    }
+}
+
+/// Where a fighter stands on the leveling curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub level: u32,
+    pub xp: u32,
+    /// Total experience the next level takes, `None` at the max level.
+    pub next_level_xp: Option<u32>,
+}
+
+impl Progress {
+    /// Share of the way from this level to the next, 1.0 at the max level.
+    pub fn fraction(&self, rules: &LevelingRules) -> f64 {
+        let Some(next) = self.next_level_xp else { return 1.0 };
+        let current = rules.xp_to_reach(self.level);
+        f64::from(self.xp - current) / f64::from((next - current).max(1))
+    }
+}
+
+impl LevelingRules {
+    /// Loads the rules, or the defaults if the file doesn't exist.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(path)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// Total experience `level` takes: 0 for level 1, then `base_xp`, `3 * base_xp`,
+    /// `6 * base_xp`, ...
+    pub fn xp_to_reach(&self, level: u32) -> u32 {
+        let level = level.clamp(1, self.max_level.max(1));
+        self.base_xp.saturating_mul(level * (level - 1) / 2)
+    }
+
+    /// The level `xp` experience points get a fighter to.
+    pub fn level(&self, xp: u32) -> u32 {
+        (1..self.max_level.max(1)).take_while(|&level| self.xp_to_reach(level + 1) <= xp).count() as u32 + 1
+    }
+
+    pub fn progress(&self, xp: u32) -> Progress {
+        let level = self.level(xp);
+        let next_level_xp = (level < self.max_level).then(|| self.xp_to_reach(level + 1));
+        Progress { level, xp, next_level_xp }
+    }
+
+    /// Experience each fighter of `battle` earned in it.
+    pub fn award<'a>(&self, battle: &'a BattleRecord) -> [(&'a str, u32); 2] {
+        let xp = |name: &str| match &battle.winner {
+            Some(winner) if winner == name => self.win_xp,
+            _ => self.loss_xp,
+        };
+        [
+            (battle.fighter1_name.as_str(), xp(&battle.fighter1_name)),
+            (battle.fighter2_name.as_str(), xp(&battle.fighter2_name)),
+        ]
+    }
+
+    /// `neopet` grown to `level`.
+    pub fn apply(&self, neopet: &mut Neopet, level: u32) {
+        for _ in 1..level.min(self.max_level) {
+            for modifier in &self.per_level {
+                modifier.apply(neopet);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn neopet() -> Neopet {
+        Neopet {
+            health: 100,
+            heal_delta: 10,
+            base_attack: 8,
+            base_defense: 4,
+            ..Neopet::test_fighter("Acara")
+        }
+    }
+
+    #[test]
+    fn test_levels_take_more_experience_each_time() {
+        let rules = LevelingRules::default();
+        assert_eq!(rules.xp_to_reach(1), 0);
+        assert_eq!(rules.xp_to_reach(2), 100);
+        assert_eq!(rules.xp_to_reach(3), 300);
+        assert_eq!(rules.xp_to_reach(4), 600);
+
+        assert_eq!(rules.level(0), 1);
+        assert_eq!(rules.level(99), 1);
+        assert_eq!(rules.level(100), 2);
+        assert_eq!(rules.level(599), 3);
+        assert_eq!(rules.level(u32::MAX), rules.max_level);
+
+        let progress = rules.progress(200);
+        assert_eq!(progress, Progress { level: 2, xp: 200, next_level_xp: Some(300) });
+        assert!((progress.fraction(&rules) - 0.5).abs() < 1e-9);
+        assert_eq!(rules.progress(u32::MAX).next_level_xp, None);
+    }
+
+    #[test]
+    fn test_levels_grow_the_stats() {
+        let rules = LevelingRules::default();
+        let mut grown = neopet();
+        rules.apply(&mut grown, 1);
+        assert_eq!(grown, neopet());
+
+        rules.apply(&mut grown, 3);
+        assert_eq!(grown.health, 110);
+        assert_eq!(grown.base_attack, 10);
+        assert_eq!(grown.base_defense, 6);
+        assert_eq!(grown.heal_delta, 10);
+    }
+
+    #[test]
+    fn test_rules_fill_in_defaults() {
+        let rules: LevelingRules = serde_json::from_str(r#"{ "win_xp": 80, "per_level": ["hp=+10%"] }"#).unwrap();
+        assert_eq!(rules.win_xp, 80);
+        assert_eq!(rules.base_xp, LevelingRules::default().base_xp);
+
+        let mut grown = neopet();
+        rules.apply(&mut grown, 3);
+        assert_eq!(grown.health, 121);
+        assert_eq!(grown.base_attack, 8);
+    }
+}
@@ -1,7 +1,64 @@
 // src/lib.rs
+pub mod arena;
+#[cfg(feature = "backup")]
+pub mod backup;
 pub mod battle;
+pub mod battle_log;
+pub mod batch;
+#[cfg(feature = "audit")]
+pub mod audit;
+pub mod classes;
+#[cfg(feature = "cli")]
+pub mod config;
+pub mod dice;
+pub mod doctor;
+pub mod engine;
+pub mod event_schema;
+pub mod export;
+#[cfg(feature = "display")]
 pub mod display;
+pub mod free_for_all;
+pub mod handicap;
+pub mod ids;
+pub mod integrity;
+pub mod items;
+pub mod journal;
+#[cfg(feature = "async")]
+pub mod live;
+#[cfg(feature = "cassino")]
+pub mod ledger;
+pub mod leveling;
+#[cfg(feature = "cli")]
+pub mod logging;
+pub mod loot;
+pub mod matchmaking;
+pub mod migrations;
 pub mod neopets;
+pub mod observer;
+#[cfg(any(feature = "display", feature = "tui"))]
+pub mod playback;
+pub mod predictor;
+pub mod prelude;
+#[cfg(feature = "cassino")]
+pub mod promotions;
+pub mod ratings;
+pub mod render;
+pub mod runner;
+pub mod season;
+pub mod series;
+pub mod simulation;
+pub mod spells;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod stats;
+pub mod store;
 pub mod storage;
+pub mod strategy;
+pub mod streaks;
+pub mod tournament;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "cassino")]
 pub mod cassino;
-pub mod cassino_display;
\ No newline at end of file
+#[cfg(all(feature = "cassino", feature = "display"))]
+pub mod cassino_display;
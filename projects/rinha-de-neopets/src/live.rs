@@ -0,0 +1,121 @@
+// src/live.rs
+use crate::battle::{BattleConfig, BattleError, BattleEvent};
+use crate::neopets::Neopet;
+use crate::runner::BattleRunner;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Async `battle_loop_with_config`: fights the battle and sends every event over
+/// `events` as soon as it happens, waiting whenever the channel is full. Stops early,
+/// without an error, once the receiving side is gone.
+pub async fn battle_loop_async<R: Rng>(
+    fighter1: &Neopet,
+    fighter2: &Neopet,
+    config: &BattleConfig,
+    rng: &mut R,
+    events: &mpsc::Sender<BattleEvent>,
+) -> Result<(), BattleError> {
+    let mut runner = BattleRunner::new(fighter1, fighter2, config, rng)?;
+    for event in runner.by_ref() {
+        if events.send(event).await.is_err() {
+            return Ok(());
+        }
+    }
+    runner.error().cloned().map_or(Ok(()), Err)
+}
+
+/// Fights the battle on tokio's blocking thread pool, rolling with `seed`, and
+/// streams its events through the returned receiver, which holds up to `buffer` of
+/// them. The simulation never waits on the async side beyond a full channel, and the
+/// handle resolves once the battle is over or the receiver is dropped.
+pub fn spawn_battle(
+    fighter1: Neopet,
+    fighter2: Neopet,
+    config: BattleConfig,
+    seed: u64,
+    buffer: usize,
+) -> (mpsc::Receiver<BattleEvent>, JoinHandle<Result<(), BattleError>>) {
+    let (sender, receiver) = mpsc::channel(buffer.max(1));
+    let handle = tokio::task::spawn_blocking(move || {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut runner = BattleRunner::new(&fighter1, &fighter2, &config, &mut rng)?;
+        for event in runner.by_ref() {
+            if sender.blocking_send(event).is_err() {
+                return Ok(());
+            }
+        }
+        runner.error().cloned().map_or(Ok(()), Err)
+    });
+    (receiver, handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::battle_loop_with_config;
+    use tokio::runtime::{Builder, Runtime};
+
+    fn runtime() -> Runtime {
+        Builder::new_current_thread().build().unwrap()
+    }
+
+    fn expected(seed: u64) -> Vec<BattleEvent> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        battle_loop_with_config(&Neopet::test_fighter("Acara"), &Neopet::test_fighter("Usul"), &BattleConfig::default(), &mut rng).unwrap()
+    }
+
+    #[test]
+    fn test_async_loop_streams_the_same_battle() {
+        let (sender, mut receiver) = mpsc::channel(4);
+        let received = runtime().block_on(async {
+            let fight = async {
+                let mut rng = StdRng::seed_from_u64(3);
+                let result = battle_loop_async(&Neopet::test_fighter("Acara"), &Neopet::test_fighter("Usul"), &BattleConfig::default(), &mut rng, &sender).await;
+                drop(sender);
+                result
+            };
+            let collect = async {
+                let mut received = Vec::new();
+                while let Some(event) = receiver.recv().await {
+                    received.push(event);
+                }
+                received
+            };
+            let (result, received) = tokio::join!(fight, collect);
+            result.unwrap();
+            received
+        });
+        assert_eq!(received, expected(3));
+    }
+
+    #[test]
+    fn test_spawned_battle_streams_the_same_battle() {
+        let received = runtime().block_on(async {
+            let (mut receiver, handle) = spawn_battle(Neopet::test_fighter("Acara"), Neopet::test_fighter("Usul"), BattleConfig::default(), 5, 2);
+            let mut received = Vec::new();
+            while let Some(event) = receiver.recv().await {
+                received.push(event);
+            }
+            handle.await.unwrap().unwrap();
+            received
+        });
+        assert_eq!(received, expected(5));
+    }
+
+    #[test]
+    fn test_battles_stop_when_nobody_listens() {
+        runtime().block_on(async {
+            let (mut receiver, handle) = spawn_battle(Neopet::test_fighter("Acara"), Neopet::test_fighter("Usul"), BattleConfig::default(), 5, 1);
+            assert!(receiver.recv().await.is_some());
+            drop(receiver);
+            assert_eq!(handle.await.unwrap(), Ok(()));
+
+            let (sender, _) = mpsc::channel(1);
+            let result =
+                battle_loop_async(&Neopet::test_fighter("Acara"), &Neopet::test_fighter("Acara"), &BattleConfig::default(), &mut rand::rng(), &sender).await;
+            assert_eq!(result, Err(BattleError::DuplicateFighter("Acara".to_string())));
+        });
+    }
+}
@@ -0,0 +1,54 @@
+// src/logging.rs
+use clap::Args;
+use std::io;
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::fmt;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::prelude::*;
+
+/// The logging flags both CLIs take, see `init`.
+#[derive(Args, Debug, Clone)]
+pub struct LogArgs {
+    /// Most verbose log level written to stderr: off, error, warn, info, debug or trace
+    #[arg(long, global = true, default_value = "warn")]
+    pub log_level: LevelFilter,
+    /// Write the logs as one JSON object per line
+    #[arg(long, global = true)]
+    pub log_json: bool,
+}
+
+impl LogArgs {
+    /// The log filter for these flags. Battle state changes are left to the audit
+    /// trail, they would drown everything else at `info`.
+    pub fn filter(&self) -> Targets {
+        Targets::new()
+            .with_default(self.log_level)
+            .with_target(crate::audit::AUDIT_TARGET, LevelFilter::OFF)
+    }
+
+    /// Installs the global subscriber writing the logs to stderr, so they never get in
+    /// the way of a command's output. Spans are logged as they close, with their timings.
+    pub fn init(&self) {
+        let registry = tracing_subscriber::registry().with(self.filter());
+        if self.log_json {
+            registry.with(fmt::layer().json().with_span_events(FmtSpan::CLOSE).with_writer(io::stderr)).init();
+        } else {
+            registry.with(fmt::layer().with_span_events(FmtSpan::CLOSE).with_writer(io::stderr)).init();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::Level;
+
+    #[test]
+    fn test_filter_keeps_the_audit_trail_out() {
+        let args = LogArgs { log_level: LevelFilter::INFO, log_json: false };
+        let filter = args.filter();
+        assert!(filter.would_enable("rinha_de_neopets::battle", &Level::INFO));
+        assert!(!filter.would_enable("rinha_de_neopets::battle", &Level::DEBUG));
+        assert!(!filter.would_enable(crate::audit::AUDIT_TARGET, &Level::INFO));
+    }
+}
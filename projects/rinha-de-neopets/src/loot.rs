@@ -0,0 +1,156 @@
+// src/loot.rs
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Rarity {
+    Common,
+    Rare,
+    Legendary,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Item {
+    pub name: String,
+    pub rarity: Rarity,
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let icon = match self.rarity {
+            Rarity::Common => "⚪",
+            Rarity::Rare => "🔷",
+            Rarity::Legendary => "🌟",
+        };
+        write!(f, "{} {}", icon, self.name)
+    }
+}
+
+/// An item that drops with probability `chance` (0.0-1.0).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LootEntry {
+    pub item: Item,
+    pub chance: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(transparent)]
+pub struct LootTable {
+    pub entries: Vec<LootEntry>,
+}
+
+impl LootTable {
+    /// Rolls every entry independently, so a single victory can drop several items or none.
+    pub fn roll<R: Rng>(&self, rng: &mut R) -> Vec<Item> {
+        self.entries
+            .iter()
+            .filter(|entry| rng.random::<f64>() < entry.chance)
+            .map(|entry| entry.item.clone())
+            .collect()
+    }
+}
+
+/// Loot tables keyed by the defeated opponent, with a fallback for opponents without one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct LootTables {
+    #[serde(default)]
+    pub default: LootTable,
+    #[serde(default)]
+    pub by_opponent: HashMap<String, LootTable>,
+}
+
+impl LootTables {
+    /// Loads the loot tables, or no loot at all if the file doesn't exist.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// The table rolled when `opponent` is defeated.
+    pub fn for_opponent(&self, opponent: &str) -> &LootTable {
+        self.by_opponent.get(opponent).unwrap_or(&self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn entry(name: &str, chance: f64) -> LootEntry {
+        LootEntry {
+            item: Item {
+                name: name.to_string(),
+                rarity: Rarity::Common,
+            },
+            chance,
+        }
+    }
+
+    #[test]
+    fn test_roll_respects_certain_and_impossible_drops() {
+        let table = LootTable {
+            entries: vec![entry("Always", 1.0), entry("Never", 0.0)],
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..50 {
+            let items = table.roll(&mut rng);
+            assert_eq!(items.len(), 1);
+            assert_eq!(items[0].name, "Always");
+        }
+    }
+
+    #[test]
+    fn test_empty_table_drops_nothing() {
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!(LootTable::default().roll(&mut rng).is_empty());
+    }
+
+    #[test]
+    fn test_for_opponent_falls_back_to_default() {
+        let tables = LootTables {
+            default: LootTable {
+                entries: vec![entry("Default Drop", 1.0)],
+            },
+            by_opponent: HashMap::from([(
+                "Usul".to_string(),
+                LootTable {
+                    entries: vec![entry("Usul Drop", 1.0)],
+                },
+            )]),
+        };
+        assert_eq!(tables.for_opponent("Usul").entries[0].item.name, "Usul Drop");
+        assert_eq!(tables.for_opponent("Acara").entries[0].item.name, "Default Drop");
+    }
+
+    #[test]
+    fn test_load_missing_file_has_no_loot() {
+        let tables = LootTables::load("/nonexistent/loot_tables.json").unwrap();
+        assert!(tables.default.entries.is_empty());
+        assert!(tables.by_opponent.is_empty());
+    }
+
+    #[test]
+    fn test_assets_loot_tables_load() {
+        let tables = LootTables::load("assets/loot_tables.json").unwrap();
+        assert!(!tables.default.entries.is_empty());
+        for table in tables.by_opponent.values().chain(std::iter::once(&tables.default)) {
+            for entry in &table.entries {
+                assert!((0.0..=1.0).contains(&entry.chance));
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+// src/matchmaking.rs
+use std::collections::{HashMap, HashSet};
+use crate::storage::BattleRecord;
+
+/// Fighter names of a matchup, in a fixed order so that "A vs B" and "B vs A" are the same pair.
+pub fn pair_key(fighter1: &str, fighter2: &str) -> (String, String) {
+    if fighter1 <= fighter2 {
+        (fighter1.to_string(), fighter2.to_string())
+    } else {
+        (fighter2.to_string(), fighter1.to_string())
+    }
+}
+
+/// Matchups of the last `recent` completed battles plus every pending battle.
+pub fn recent_pairs(
+    complete: &[BattleRecord],
+    pending: &[BattleRecord],
+    recent: usize,
+) -> HashSet<(String, String)> {
+    let recent_complete = &complete[complete.len().saturating_sub(recent)..];
+    recent_complete
+        .iter()
+        .chain(pending.iter())
+        .map(|b| pair_key(&b.fighter1_name, &b.fighter2_name))
+        .collect()
+}
+
+/// Picks up to `count` matchups, closest ratings first. Pairs in `excluded` are never
+/// picked and no pair is picked twice, so fewer come back when there aren't enough.
+pub fn closest_pairs(
+    ratings: &HashMap<String, f64>,
+    excluded: &HashSet<(String, String)>,
+    count: usize,
+) -> Result<Vec<(String, String)>, String> {
+    let mut fighters: Vec<&String> = ratings.keys().collect();
+    fighters.sort();
+
+    let mut candidates = Vec::new();
+    for (i, fighter1) in fighters.iter().enumerate() {
+        for fighter2 in &fighters[i + 1..] {
+            let pair = pair_key(fighter1, fighter2);
+            if !excluded.contains(&pair) {
+                candidates.push(pair);
+            }
+        }
+    }
+
+    if candidates.is_empty() {
+        return Err("No eligible matchups: need at least 2 fighters that haven't met recently".to_string());
+    }
+
+    // Stable sort keeps alphabetical order between equally close pairs
+    candidates.sort_by(|a, b| {
+        let gap_a = (ratings[&a.0] - ratings[&a.1]).abs();
+        let gap_b = (ratings[&b.0] - ratings[&b.1]).abs();
+        gap_a.total_cmp(&gap_b)
+    });
+
+    candidates.truncate(count);
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratings_of(entries: &[(&str, f64)]) -> HashMap<String, f64> {
+        entries.iter().map(|(n, r)| (n.to_string(), *r)).collect()
+    }
+
+    fn battle(fighter1: &str, fighter2: &str) -> BattleRecord {
+        BattleRecord {
+            is_completed: true,
+            ..BattleRecord::test_battle(&format!("battle_{}_{}", fighter1, fighter2), fighter1, fighter2)
+        }
+    }
+
+    #[test]
+    fn test_pair_key_ignores_order() {
+        assert_eq!(pair_key("Usul", "Acara"), pair_key("Acara", "Usul"));
+    }
+
+    #[test]
+    fn test_closest_pairs_prefers_small_rating_gap() {
+        let ratings = ratings_of(&[("Acara", 1000.0), ("Usul", 1010.0), ("Xweetok", 1200.0)]);
+        let pairs = closest_pairs(&ratings, &HashSet::new(), 1).unwrap();
+        assert_eq!(pairs, vec![pair_key("Acara", "Usul")]);
+    }
+
+    #[test]
+    fn test_closest_pairs_never_repeats_a_pair() {
+        let ratings = ratings_of(&[("Acara", 1000.0), ("Usul", 1010.0), ("Xweetok", 1050.0)]);
+        let pairs = closest_pairs(&ratings, &HashSet::new(), 5).unwrap();
+        assert_eq!(pairs, vec![pair_key("Acara", "Usul"), pair_key("Usul", "Xweetok"), pair_key("Acara", "Xweetok")]);
+    }
+
+    #[test]
+    fn test_closest_pairs_skips_excluded() {
+        let ratings = ratings_of(&[("Acara", 1000.0), ("Usul", 1010.0), ("Xweetok", 1050.0)]);
+        let excluded = HashSet::from([pair_key("Acara", "Usul")]);
+        let pairs = closest_pairs(&ratings, &excluded, 1).unwrap();
+        assert_eq!(pairs, vec![pair_key("Usul", "Xweetok")]);
+    }
+
+    #[test]
+    fn test_closest_pairs_errors_without_candidates() {
+        let ratings = ratings_of(&[("Acara", 1000.0), ("Usul", 1010.0)]);
+        let excluded = HashSet::from([pair_key("Acara", "Usul")]);
+        assert!(closest_pairs(&ratings, &excluded, 1).is_err());
+        assert!(closest_pairs(&ratings_of(&[("Acara", 1000.0)]), &HashSet::new(), 1).is_err());
+    }
+
+    #[test]
+    fn test_recent_pairs_only_looks_at_latest_battles() {
+        let complete = vec![battle("Acara", "Usul"), battle("Usul", "Xweetok")];
+        let pending = vec![battle("Xweetok", "Acara")];
+        let recent = recent_pairs(&complete, &pending, 1);
+        assert!(!recent.contains(&pair_key("Acara", "Usul")));
+        assert!(recent.contains(&pair_key("Usul", "Xweetok")));
+        assert!(recent.contains(&pair_key("Acara", "Xweetok")));
+    }
+}
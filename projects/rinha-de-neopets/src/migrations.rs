@@ -0,0 +1,185 @@
+// src/migrations.rs
+use serde::de::DeserializeOwned;
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::io::{Read, Write};
+
+/// Asset files written with a schema `version`, so that their format can change
+/// without hand-editing the files: older ones are upgraded as they load and written
+/// back in the current format by the next save changing them (or `Storage::flush_all`).
+/// Battle events carry their own version on top of this, see `event_schema`.
+///
+/// Version 1 is every file written before files carried their version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetFile {
+    /// `neopets.json`, the fighter definitions
+    Neopets,
+    /// `complete_battles.json` and `pending_battles.json`
+    Battles,
+    /// The cassino's `bets.json`
+    Bets,
+    /// The cassino's `accumulated_bets.json`
+    AccumulatedBets,
+}
+
+impl AssetFile {
+    /// Version the file is written with. Bump it whenever a change would break
+    /// reading older files, and add the step bringing the previous version up to
+    /// date to `migrate`.
+    pub fn current_version(self) -> u32 {
+        match self {
+            AssetFile::Neopets | AssetFile::Battles | AssetFile::Bets | AssetFile::AccumulatedBets => 2,
+        }
+    }
+
+    /// Key the records are under once versioned. Files that were a bare list get
+    /// wrapped in an object to have somewhere to put the version.
+    fn key(self) -> &'static str {
+        match self {
+            AssetFile::Neopets => "neopets",
+            AssetFile::Battles => "battles",
+            AssetFile::Bets => "bets",
+            AssetFile::AccumulatedBets => "accumulated_bets",
+        }
+    }
+
+    /// Whether version 1 of the file was a bare list rather than an object.
+    fn was_list(self) -> bool {
+        matches!(self, AssetFile::Neopets | AssetFile::Battles)
+    }
+
+    /// Brings the records of a file of version `from` to version `from + 1`.
+    fn migrate(self, from: u32, records: Value) -> Result<Value, String> {
+        match (self, from) {
+            // v2 only put the records in the versioned envelope, see `unwrap`
+            (_, 1) => Ok(records),
+            _ => Err(format!("No migration from {:?} v{}", self, from)),
+        }
+    }
+}
+
+/// The version of a file and its records, in the shape version 1 had them.
+fn unwrap(file: AssetFile, value: Value) -> Result<(u32, Value), String> {
+    let Value::Object(mut map) = value else {
+        return Ok((1, value));
+    };
+    let Some(version) = map.remove("version") else {
+        return Ok((1, Value::Object(map)));
+    };
+    let version = version
+        .as_u64()
+        .and_then(|v| u32::try_from(v).ok())
+        .ok_or_else(|| format!("{:?} file has an invalid version {}", file, version))?;
+    if file.was_list() {
+        let records = map.remove(file.key()).unwrap_or(Value::Array(Vec::new()));
+        Ok((version, records))
+    } else {
+        Ok((version, Value::Object(map)))
+    }
+}
+
+/// Brings the contents of a file of any known version up to the current one and
+/// returns its records, as `T` reads them.
+pub fn upgrade(file: AssetFile, value: Value) -> Result<Value, String> {
+    let (version, mut records) = unwrap(file, value)?;
+    let current = file.current_version();
+    if version == 0 || version > current {
+        return Err(format!(
+            "{:?} file schema v{} is unknown, this build reads up to v{}",
+            file, version, current
+        ));
+    }
+    for from in version..current {
+        records = file.migrate(from, records)?;
+    }
+    Ok(records)
+}
+
+/// Reads a file of `file`'s kind, upgrading it if it was written by an older build.
+pub fn read<T: DeserializeOwned, R: Read>(file: AssetFile, reader: R) -> Result<T, Box<dyn std::error::Error>> {
+    let value: Value = serde_json::from_reader(reader)?;
+    let records = upgrade(file, value)?;
+    Ok(serde_json::from_value(records).map_err(|e| format!("{:?} file: {}", file, e))?)
+}
+
+/// `records` as a file of `file`'s kind is written: behind the current version, under
+/// the file's key if they are a list, next to it if they are an object.
+pub struct Versioned<'a, T> {
+    file: AssetFile,
+    records: &'a T,
+}
+
+impl<'a, T: Serialize> Versioned<'a, T> {
+    pub fn new(file: AssetFile, records: &'a T) -> Self {
+        Self { file, records }
+    }
+}
+
+impl<T: Serialize> Serialize for Versioned<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Flattened<'a, T> {
+            version: u32,
+            #[serde(flatten)]
+            records: &'a T,
+        }
+
+        let version = self.file.current_version();
+        if self.file.was_list() {
+            let mut map = serializer.serialize_map(Some(2))?;
+            map.serialize_entry("version", &version)?;
+            map.serialize_entry(self.file.key(), self.records)?;
+            map.end()
+        } else {
+            Flattened { version, records: self.records }.serialize(serializer)
+        }
+    }
+}
+
+/// Writes `records` as a file of `file`'s kind, pretty-printed.
+pub fn write<T: Serialize, W: Write>(file: AssetFile, records: &T, writer: W) -> Result<(), Box<dyn std::error::Error>> {
+    serde_json::to_writer_pretty(writer, &Versioned::new(file, records))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Bets {
+        bets: Vec<u32>,
+    }
+
+    #[test]
+    fn test_lists_are_wrapped_with_their_version() {
+        let value = serde_json::to_value(Versioned::new(AssetFile::Neopets, &vec!["Acara", "Usul"])).unwrap();
+        assert_eq!(value, json!({ "version": 2, "neopets": ["Acara", "Usul"] }));
+        let names: Vec<String> = read(AssetFile::Neopets, value.to_string().as_bytes()).unwrap();
+        assert_eq!(names, ["Acara", "Usul"]);
+
+        // Version 1 was the bare list
+        let v1: Vec<String> = read(AssetFile::Neopets, r#"["Acara"]"#.as_bytes()).unwrap();
+        assert_eq!(v1, ["Acara"]);
+    }
+
+    #[test]
+    fn test_objects_get_a_version_field() {
+        let bets = Bets { bets: vec![1, 2] };
+        let value = serde_json::to_value(Versioned::new(AssetFile::Bets, &bets)).unwrap();
+        assert_eq!(value, json!({ "version": 2, "bets": [1, 2] }));
+        assert_eq!(read::<Bets, _>(AssetFile::Bets, value.to_string().as_bytes()).unwrap(), bets);
+        assert_eq!(read::<Bets, _>(AssetFile::Bets, r#"{ "bets": [3] }"#.as_bytes()).unwrap(), Bets { bets: vec![3] });
+    }
+
+    #[test]
+    fn test_unknown_versions_are_rejected() {
+        let future = json!({ "version": 99, "battles": [] });
+        assert!(upgrade(AssetFile::Battles, future).unwrap_err().contains("v99"));
+        assert!(upgrade(AssetFile::Battles, json!({ "version": 0, "battles": [] })).is_err());
+        assert!(upgrade(AssetFile::Battles, json!({ "version": "two", "battles": [] })).is_err());
+    }
+}
@@ -1,12 +1,59 @@
+use crate::classes::FighterClass;
+use crate::migrations::{self, AssetFile};
+use crate::strategy::StrategyKind;
 use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use std::fmt;
 use std::fs::File;
 
+/// A spell known by a fighter. Fighters only store the spell name, the effect
+/// comes from the shared spell catalog (see `crate::spells`).
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(from = "SpellRef", into = "SpellRef")]
 pub struct Spell {
     pub name: String,
     pub effect: serde_json::Value,
+    pub cooldown: u32, // Turns after a cast before the spell can be cast again
+    pub inline: bool, // Saved with its effect and cooldown rather than by name, see `SpellRef`
+}
+
+/// Spells are referenced by name, but fighters saved before the spell catalog
+/// existed embed the whole spell inline, and so do spells the catalog doesn't know
+/// and the fighters of battle checkpoints.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum SpellRef {
+    Name(String),
+    Inline {
+        name: String,
+        effect: serde_json::Value,
+        #[serde(default, skip_serializing_if = "is_zero")]
+        cooldown: u32,
+    },
+}
+
+impl From<SpellRef> for Spell {
+    fn from(spell_ref: SpellRef) -> Self {
+        match spell_ref {
+            SpellRef::Name(name) => Spell {
+                name,
+                effect: serde_json::Value::Object(serde_json::Map::new()),
+                cooldown: 0,
+                inline: false,
+            },
+            SpellRef::Inline { name, effect, cooldown } => Spell { name, effect, cooldown, inline: true },
+        }
+    }
+}
+
+impl From<Spell> for SpellRef {
+    fn from(spell: Spell) -> Self {
+        if spell.inline {
+            SpellRef::Inline { name: spell.name, effect: spell.effect, cooldown: spell.cooldown }
+        } else {
+            SpellRef::Name(spell.name)
+        }
+    }
 }
 
 impl fmt::Display for Spell {
@@ -20,37 +67,65 @@ pub struct BehaviorDef {
     pub attack_chance: f64,
     pub spell_chances: Vec<f64>,
     pub heal_chance: f64,
+    #[serde(default)]
+    pub defend_chance: f64,
+    #[serde(default)]
+    pub taunt_chance: f64,
 }
 
-#[derive(Debug, Serialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(try_from = "BehaviorDef")]
 pub struct Behavior {
     pub attack_chance: f64,
     pub spell_chances: Vec<f64>,
     pub heal_chance: f64,
+    /// Chance to brace and double the next defense rolls, left out of the JSON when 0.
+    #[serde(skip_serializing_if = "is_zero_chance")]
+    pub defend_chance: f64,
+    /// Chance to goad the opponent into a weakened attack, left out of the JSON when 0.
+    #[serde(skip_serializing_if = "is_zero_chance")]
+    pub taunt_chance: f64,
 }
 
 impl TryFrom<BehaviorDef> for Behavior {
     type Error = String;
 
     fn try_from(def: BehaviorDef) -> Result<Self, Self::Error> {
-        let total = def.attack_chance + def.heal_chance + def.spell_chances.iter().sum::<f64>();
+        let total = def.attack_chance
+            + def.heal_chance
+            + def.defend_chance
+            + def.taunt_chance
+            + def.spell_chances.iter().sum::<f64>();
 
         if (total - 1.0).abs() > f64::EPSILON {
             Err(format!(
-                "Behavior probabilities sum to {} but must equal 1.0 (attack: {}, heal: {}, spells: {:?})",
-                total, def.attack_chance, def.heal_chance, def.spell_chances
+                "Behavior probabilities sum to {} but must equal 1.0 (attack: {}, heal: {}, defend: {}, taunt: {}, spells: {:?})",
+                total, def.attack_chance, def.heal_chance, def.defend_chance, def.taunt_chance, def.spell_chances
             ))
         } else {
             Ok(Behavior {
                 attack_chance: def.attack_chance,
                 spell_chances: def.spell_chances,
                 heal_chance: def.heal_chance,
+                defend_chance: def.defend_chance,
+                taunt_chance: def.taunt_chance,
             })
         }
     }
 }
 
+impl From<Behavior> for BehaviorDef {
+    fn from(behavior: Behavior) -> Self {
+        BehaviorDef {
+            attack_chance: behavior.attack_chance,
+            spell_chances: behavior.spell_chances,
+            heal_chance: behavior.heal_chance,
+            defend_chance: behavior.defend_chance,
+            taunt_chance: behavior.taunt_chance,
+        }
+    }
+}
+
 impl fmt::Display for Behavior {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -62,7 +137,14 @@ impl fmt::Display for Behavior {
                 .map(|c| format!("{:.0}%", c * 100.0))
                 .collect::<Vec<_>>(),
             self.heal_chance * 100.0
-        )
+        )?;
+        if self.defend_chance > 0.0 {
+            write!(f, " | 🛡️ {:.0}%", self.defend_chance * 100.0)?;
+        }
+        if self.taunt_chance > 0.0 {
+            write!(f, " | 📣 {:.0}%", self.taunt_chance * 100.0)?;
+        }
+        Ok(())
     }
 }
 
@@ -75,9 +157,19 @@ pub struct NeopetDef {
     pub base_defense: u32,
     pub spells: Vec<Spell>,
     pub behavior: BehaviorDef,
+    #[serde(default)]
+    pub strategy: StrategyKind,
+    #[serde(default)]
+    pub dodge_chance: f64,
+    #[serde(default)]
+    pub block_value: u32,
+    #[serde(default)]
+    pub speed: u32,
+    #[serde(default)]
+    pub class: Option<FighterClass>,
 }
 
-#[derive(Debug, Serialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(try_from = "NeopetDef")]
 pub struct Neopet {
     pub name: String,
@@ -87,6 +179,30 @@ pub struct Neopet {
     pub base_defense: u32,
     pub spells: Vec<Spell>,
     pub behavior: Behavior,
+    /// How the fighter picks actions, probabilistic (left out of the JSON) by default.
+    #[serde(skip_serializing_if = "StrategyKind::is_probabilistic")]
+    pub strategy: StrategyKind,
+    /// Chance, from 0.0 to 1.0, to fully evade an attack before rolling defense.
+    #[serde(skip_serializing_if = "is_zero_chance")]
+    pub dodge_chance: f64,
+    /// Attack points shaved off every hit that isn't dodged, before the defense roll.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub block_value: u32,
+    /// Added to every initiative roll, and buys extra actions against much slower
+    /// opponents when `BattleConfig::extra_action_speed_gap` is set.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub speed: u32,
+    /// Class whose passive ability the fighter gets in battle, classless by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class: Option<FighterClass>,
+}
+
+fn is_zero_chance(chance: &f64) -> bool {
+    *chance == 0.0
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
 }
 
 impl TryFrom<NeopetDef> for Neopet {
@@ -102,6 +218,13 @@ impl TryFrom<NeopetDef> for Neopet {
             ));
         }
 
+        if !(0.0..=1.0).contains(&def.dodge_chance) {
+            return Err(format!(
+                "Neopet {}: dodge chance {} must be between 0.0 and 1.0",
+                def.name, def.dodge_chance
+            ));
+        }
+
         let behavior = Behavior::try_from(def.behavior)?;
 
         Ok(Neopet {
@@ -112,10 +235,35 @@ impl TryFrom<NeopetDef> for Neopet {
             base_defense: def.base_defense,
             spells: def.spells,
             behavior,
+            strategy: def.strategy,
+            dodge_chance: def.dodge_chance,
+            block_value: def.block_value,
+            speed: def.speed,
+            class: def.class,
         })
     }
 }
 
+/// The definition back, to validate a fighter built or changed in code with `Neopet::try_from`.
+impl From<Neopet> for NeopetDef {
+    fn from(neopet: Neopet) -> Self {
+        NeopetDef {
+            name: neopet.name,
+            health: neopet.health,
+            heal_delta: neopet.heal_delta,
+            base_attack: neopet.base_attack,
+            base_defense: neopet.base_defense,
+            spells: neopet.spells,
+            behavior: neopet.behavior.into(),
+            strategy: neopet.strategy,
+            dodge_chance: neopet.dodge_chance,
+            block_value: neopet.block_value,
+            speed: neopet.speed,
+            class: neopet.class,
+        }
+    }
+}
+
 impl fmt::Display for Neopet {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let spell_list = self
@@ -124,30 +272,146 @@ impl fmt::Display for Neopet {
             .map(|s| s.to_string())
             .collect::<Vec<_>>()
             .join(", ");
+        let mut evasion = String::new();
+        if self.dodge_chance > 0.0 {
+            evasion += &format!(" | Dodge: {:.0}%", self.dodge_chance * 100.0);
+        }
+        if self.block_value > 0 {
+            evasion += &format!(" | Block: {}", self.block_value);
+        }
+        if self.speed > 0 {
+            evasion += &format!(" | Speed: {}", self.speed);
+        }
+        let class = self.class.map(|class| format!(" ({})", class)).unwrap_or_default();
 
         write!(
             f,
-            "{}\nHP: {} | ATK: {} | DEF: {} | Heal: +{}\nSpells: {}\nBehavior: {}",
+            "{}{}\nHP: {} | ATK: {} | DEF: {} | Heal: +{}{}\nSpells: {}\nBehavior: {}",
             self.name,
+            class,
             self.health,
             self.base_attack,
             self.base_defense,
             self.heal_delta,
+            evasion,
             spell_list,
             self.behavior
         )
     }
 }
 
+/// The formats a single fighter is exported and imported in, laid out like an entry
+/// of `neopets.json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FighterFormat {
+    Json,
+    /// Needs the `toml` feature.
+    Toml,
+}
+
+impl FighterFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FighterFormat::Json => "json",
+            FighterFormat::Toml => "toml",
+        }
+    }
+
+    /// The format a file is in going by its extension.
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+
+    /// `neopet` written in this format.
+    pub fn write(&self, neopet: &Neopet) -> Result<String, String> {
+        match self {
+            FighterFormat::Json => serde_json::to_string_pretty(neopet).map_err(|e| e.to_string()),
+            #[cfg(feature = "toml")]
+            FighterFormat::Toml => toml::to_string_pretty(neopet).map_err(|e| e.to_string()),
+            #[cfg(not(feature = "toml"))]
+            FighterFormat::Toml => Err(NO_TOML.to_string()),
+        }
+    }
+
+    /// A fighter read from `text`, validated like those in `neopets.json`.
+    pub fn read(&self, text: &str) -> Result<Neopet, String> {
+        match self {
+            FighterFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+            #[cfg(feature = "toml")]
+            FighterFormat::Toml => toml::from_str(text).map_err(|e| e.to_string()),
+            #[cfg(not(feature = "toml"))]
+            FighterFormat::Toml => Err(NO_TOML.to_string()),
+        }
+    }
+}
+
+#[cfg(not(feature = "toml"))]
+const NO_TOML: &str = "This build has no TOML support, enable the `toml` feature";
+
+impl fmt::Display for FighterFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+impl std::str::FromStr for FighterFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(FighterFormat::Json),
+            "toml" => Ok(FighterFormat::Toml),
+            _ => Err(format!("Unknown fighter format '{}', expected json or toml", s)),
+        }
+    }
+}
+
 pub fn load_neopets(path: &str) -> Vec<Neopet> {
     let file = File::open(path).expect("Failed to open file");
-    let neopets_def: Vec<NeopetDef> = serde_json::from_reader(file).expect("Failed to deserialize");
+    let neopets_def: Vec<NeopetDef> = migrations::read(AssetFile::Neopets, file).expect("Failed to deserialize");
     neopets_def
         .into_iter()
         .map(|def| Neopet::try_from(def).expect("Failed to validate neopet"))
         .collect()
 }
 
+#[cfg(test)]
+impl Behavior {
+    /// Attacks with `attack_chance` and heals the rest of the time, for tests.
+    pub(crate) fn attack_or_heal(attack_chance: f64) -> Self {
+        Behavior {
+            attack_chance,
+            spell_chances: vec![],
+            heal_chance: 1.0 - attack_chance,
+            defend_chance: 0.0,
+            taunt_chance: 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Neopet {
+    /// A spell-less fighter for tests, with 50 HP, attacking 70% of the time and healing
+    /// otherwise. Tests set what they need on top, `Neopet { health: 80, ..Neopet::test_fighter(name) }`,
+    /// so a new stat only has a default to add here.
+    pub(crate) fn test_fighter(name: &str) -> Self {
+        Neopet {
+            name: name.to_string(),
+            health: 50,
+            heal_delta: 8,
+            base_attack: 9,
+            base_defense: 3,
+            spells: vec![],
+            behavior: Behavior::attack_or_heal(0.7),
+            strategy: StrategyKind::Probabilistic,
+            dodge_chance: 0.0,
+            block_value: 0,
+            speed: 0,
+            class: None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,6 +436,7 @@ mod tests {
 
         let original_value: serde_json::Value =
             serde_json::from_str(&original_json).expect("Failed to parse original JSON");
+        let original_value = migrations::upgrade(AssetFile::Neopets, original_value).expect("Failed to upgrade original JSON");
         let roundtrip_value: serde_json::Value =
             serde_json::from_str(&roundtrip_json).expect("Failed to parse roundtrip JSON");
 
@@ -184,6 +449,8 @@ mod tests {
             attack_chance: 0.5,
             spell_chances: vec![0.1, 0.15],
             heal_chance: 0.25,
+            defend_chance: 0.0,
+            taunt_chance: 0.0,
         };
         let result = Behavior::try_from(def);
         assert!(result.is_ok());
@@ -195,6 +462,8 @@ mod tests {
             attack_chance: 0.5 + 1e-17,
             spell_chances: vec![0.1, 0.15],
             heal_chance: 0.25,
+            defend_chance: 0.0,
+            taunt_chance: 0.0,
         };
         let result = Behavior::try_from(def);
         assert!(result.is_ok());
@@ -206,6 +475,8 @@ mod tests {
             attack_chance: 0.5,
             spell_chances: vec![],
             heal_chance: 0.5,
+            defend_chance: 0.0,
+            taunt_chance: 0.0,
         };
         let result = Behavior::try_from(def);
         assert!(result.is_ok());
@@ -217,6 +488,8 @@ mod tests {
             attack_chance: 0.5,
             spell_chances: vec![0.1, 0.15],
             heal_chance: 0.1,
+            defend_chance: 0.0,
+            taunt_chance: 0.0,
         };
         let result = Behavior::try_from(def);
         assert!(result.is_err());
@@ -228,6 +501,8 @@ mod tests {
             attack_chance: 0.5,
             spell_chances: vec![0.1, 0.15],
             heal_chance: 0.4,
+            defend_chance: 0.0,
+            taunt_chance: 0.0,
         };
         let result = Behavior::try_from(def);
         assert!(result.is_err());
@@ -239,6 +514,8 @@ mod tests {
             attack_chance: 1.5,
             spell_chances: vec![0.5, 0.5],
             heal_chance: 0.5,
+            defend_chance: 0.0,
+            taunt_chance: 0.0,
         };
         let result = Behavior::try_from(def);
         assert!(result.is_err());
@@ -250,6 +527,8 @@ mod tests {
             attack_chance: 0.5,
             spell_chances: vec![0.1, 0.15],
             heal_chance: 0.1,
+            defend_chance: 0.0,
+            taunt_chance: 0.0,
         };
         let result = Behavior::try_from(def);
         let error_msg = result.unwrap_err();
@@ -271,17 +550,28 @@ mod tests {
                 Spell {
                     name: "Spell1".to_string(),
                     effect: serde_json::Value::Object(serde_json::Map::new()),
+                    cooldown: 0,
+                    inline: false,
                 },
                 Spell {
                     name: "Spell2".to_string(),
                     effect: serde_json::Value::Object(serde_json::Map::new()),
+                    cooldown: 0,
+                    inline: false,
                 },
             ],
             behavior: BehaviorDef {
                 attack_chance: 0.5,
                 spell_chances: vec![0.1, 0.15],
                 heal_chance: 0.25,
+                defend_chance: 0.0,
+                taunt_chance: 0.0,
             },
+            strategy: StrategyKind::Probabilistic,
+            dodge_chance: 0.0,
+            block_value: 0,
+            speed: 0,
+            class: None,
         };
         let result = Neopet::try_from(def);
         assert!(result.is_ok());
@@ -300,7 +590,14 @@ mod tests {
                 attack_chance: 0.5,
                 spell_chances: vec![],
                 heal_chance: 0.5,
+                defend_chance: 0.0,
+                taunt_chance: 0.0,
             },
+            strategy: StrategyKind::Probabilistic,
+            dodge_chance: 0.0,
+            block_value: 0,
+            speed: 0,
+            class: None,
         };
         let result = Neopet::try_from(def);
         assert!(result.is_ok());
@@ -318,17 +615,28 @@ mod tests {
                 Spell {
                     name: "Spell1".to_string(),
                     effect: serde_json::Value::Object(serde_json::Map::new()),
+                    cooldown: 0,
+                    inline: false,
                 },
                 Spell {
                     name: "Spell2".to_string(),
                     effect: serde_json::Value::Object(serde_json::Map::new()),
+                    cooldown: 0,
+                    inline: false,
                 },
             ],
             behavior: BehaviorDef {
                 attack_chance: 0.5,
                 spell_chances: vec![0.1],
                 heal_chance: 0.25,
+                defend_chance: 0.0,
+                taunt_chance: 0.0,
             },
+            strategy: StrategyKind::Probabilistic,
+            dodge_chance: 0.0,
+            block_value: 0,
+            speed: 0,
+            class: None,
         };
         let result = Neopet::try_from(def);
         assert!(result.is_err());
@@ -345,12 +653,21 @@ mod tests {
             spells: vec![Spell {
                 name: "Spell1".to_string(),
                 effect: serde_json::Value::Object(serde_json::Map::new()),
+                cooldown: 0,
+                inline: false,
             }],
             behavior: BehaviorDef {
                 attack_chance: 0.5,
                 spell_chances: vec![0.1, 0.15],
                 heal_chance: 0.25,
+                defend_chance: 0.0,
+                taunt_chance: 0.0,
             },
+            strategy: StrategyKind::Probabilistic,
+            dodge_chance: 0.0,
+            block_value: 0,
+            speed: 0,
+            class: None,
         };
         let result = Neopet::try_from(def);
         assert!(result.is_err());
@@ -368,17 +685,28 @@ mod tests {
                 Spell {
                     name: "Spell1".to_string(),
                     effect: serde_json::Value::Object(serde_json::Map::new()),
+                    cooldown: 0,
+                    inline: false,
                 },
                 Spell {
                     name: "Spell2".to_string(),
                     effect: serde_json::Value::Object(serde_json::Map::new()),
+                    cooldown: 0,
+                    inline: false,
                 },
             ],
             behavior: BehaviorDef {
                 attack_chance: 0.5,
                 spell_chances: vec![0.1],
                 heal_chance: 0.25,
+                defend_chance: 0.0,
+                taunt_chance: 0.0,
             },
+            strategy: StrategyKind::Probabilistic,
+            dodge_chance: 0.0,
+            block_value: 0,
+            speed: 0,
+            class: None,
         };
         let result = Neopet::try_from(def);
         let error_msg = result.unwrap_err();
@@ -399,17 +727,28 @@ mod tests {
                 Spell {
                     name: "Spell1".to_string(),
                     effect: serde_json::Value::Object(serde_json::Map::new()),
+                    cooldown: 0,
+                    inline: false,
                 },
                 Spell {
                     name: "Spell2".to_string(),
                     effect: serde_json::Value::Object(serde_json::Map::new()),
+                    cooldown: 0,
+                    inline: false,
                 },
             ],
             behavior: BehaviorDef {
                 attack_chance: 0.5,
                 spell_chances: vec![0.1, 0.15],
                 heal_chance: 0.1,
+                defend_chance: 0.0,
+                taunt_chance: 0.0,
             },
+            strategy: StrategyKind::Probabilistic,
+            dodge_chance: 0.0,
+            block_value: 0,
+            speed: 0,
+            class: None,
         };
         let result = Neopet::try_from(def);
         assert!(result.is_err());
@@ -473,6 +812,37 @@ mod tests {
         let _neopets = load_neopets(temp_file.path().to_str().unwrap());
     }
 
+    #[test]
+    fn test_spell_accepts_name_or_inline_definition() {
+        let by_name: Spell = serde_json::from_str(r#""Fire Ball""#).unwrap();
+        assert_eq!(by_name.name, "Fire Ball");
+        assert_eq!(by_name.effect, serde_json::json!({}));
+
+        let inline: Spell =
+            serde_json::from_str(r#"{"name": "Fire Ball", "effect": {"type": "cosmetic"}}"#).unwrap();
+        assert_eq!(inline.name, "Fire Ball");
+        assert_eq!(inline.effect, serde_json::json!({"type": "cosmetic"}));
+    }
+
+    #[test]
+    fn test_spell_serializes_as_name() {
+        let spell = Spell {
+            name: "Fire Ball".to_string(),
+            effect: serde_json::json!({"type": "damage", "amount": 10}),
+            cooldown: 0,
+            inline: false,
+        };
+        assert_eq!(serde_json::to_string(&spell).unwrap(), r#""Fire Ball""#);
+    }
+
+    #[test]
+    fn test_inline_spell_round_trips_inline() {
+        let json = r#"{"name":"Hex","effect":{"type":"damage","amount":4},"cooldown":2}"#;
+        let spell: Spell = serde_json::from_str(json).unwrap();
+        assert!(spell.inline);
+        assert_eq!(serde_json::to_value(&spell).unwrap(), serde_json::from_str::<serde_json::Value>(json).unwrap());
+    }
+
     #[test]
     fn test_load_neopets_all_validation_passes() {
         let neopets = load_neopets("assets/neopets.json");
@@ -485,4 +855,118 @@ mod tests {
             assert!((total - 1.0).abs() <= f64::EPSILON);
         }
     }
+
+    #[test]
+    fn test_strategy_is_optional_in_json() {
+        let json = r#"{
+            "name": "TestPet", "health": 100, "heal_delta": 10, "base_attack": 5, "base_defense": 3,
+            "spells": [], "behavior": { "attack_chance": 0.5, "spell_chances": [], "heal_chance": 0.5 }
+        }"#;
+        let neopet: Neopet = serde_json::from_str(json).unwrap();
+        assert_eq!(neopet.strategy, StrategyKind::Probabilistic);
+        assert!(!serde_json::to_string(&neopet).unwrap().contains("strategy"));
+
+        let json = json.replacen("\"spells\"", "\"strategy\": \"heal_when_low\", \"spells\"", 1);
+        let neopet: Neopet = serde_json::from_str(&json).unwrap();
+        assert_eq!(neopet.strategy, StrategyKind::HealWhenLow);
+        assert!(serde_json::to_string(&neopet).unwrap().contains("\"strategy\":\"heal_when_low\""));
+    }
+
+    #[test]
+    fn test_dodge_and_block_are_optional_in_json() {
+        let json = r#"{
+            "name": "TestPet", "health": 100, "heal_delta": 10, "base_attack": 5, "base_defense": 3,
+            "spells": [], "behavior": { "attack_chance": 0.5, "spell_chances": [], "heal_chance": 0.5 }
+        }"#;
+        let neopet: Neopet = serde_json::from_str(json).unwrap();
+        assert_eq!((neopet.dodge_chance, neopet.block_value), (0.0, 0));
+        let serialized = serde_json::to_string(&neopet).unwrap();
+        assert!(!serialized.contains("dodge_chance") && !serialized.contains("block_value"));
+
+        let json = json.replacen("\"spells\"", "\"dodge_chance\": 0.25, \"block_value\": 3, \"spells\"", 1);
+        let neopet: Neopet = serde_json::from_str(&json).unwrap();
+        assert_eq!((neopet.dodge_chance, neopet.block_value), (0.25, 3));
+        assert_eq!(serde_json::from_str::<Neopet>(&serde_json::to_string(&neopet).unwrap()).unwrap(), neopet);
+
+        let json = json.replacen("0.25", "1.5", 1);
+        assert!(serde_json::from_str::<Neopet>(&json).unwrap_err().to_string().contains("dodge chance"));
+    }
+
+    #[test]
+    fn test_speed_is_optional_in_json() {
+        let json = r#"{
+            "name": "TestPet", "health": 100, "heal_delta": 10, "base_attack": 5, "base_defense": 3,
+            "spells": [], "behavior": { "attack_chance": 0.5, "spell_chances": [], "heal_chance": 0.5 }
+        }"#;
+        let neopet: Neopet = serde_json::from_str(json).unwrap();
+        assert_eq!(neopet.speed, 0);
+        assert!(!serde_json::to_string(&neopet).unwrap().contains("speed"));
+
+        let json = json.replacen("\"spells\"", "\"speed\": 7, \"spells\"", 1);
+        let neopet: Neopet = serde_json::from_str(&json).unwrap();
+        assert_eq!(neopet.speed, 7);
+        assert!(neopet.to_string().contains("Speed: 7"));
+        assert_eq!(serde_json::from_str::<Neopet>(&serde_json::to_string(&neopet).unwrap()).unwrap(), neopet);
+    }
+
+    #[test]
+    fn test_class_is_optional_in_json() {
+        let json = r#"{
+            "name": "TestPet", "health": 100, "heal_delta": 10, "base_attack": 5, "base_defense": 3,
+            "spells": [], "behavior": { "attack_chance": 0.5, "spell_chances": [], "heal_chance": 0.5 }
+        }"#;
+        let neopet: Neopet = serde_json::from_str(json).unwrap();
+        assert_eq!(neopet.class, None);
+        assert!(!serde_json::to_string(&neopet).unwrap().contains("class"));
+        assert!(neopet.to_string().starts_with("TestPet\n"));
+
+        let json = json.replacen("\"spells\"", "\"class\": \"tank\", \"spells\"", 1);
+        let neopet: Neopet = serde_json::from_str(&json).unwrap();
+        assert_eq!(neopet.class, Some(FighterClass::Tank));
+        assert!(neopet.to_string().starts_with("TestPet (Tank)\n"));
+        assert_eq!(serde_json::from_str::<Neopet>(&serde_json::to_string(&neopet).unwrap()).unwrap(), neopet);
+
+        let json = json.replacen("tank", "bard", 1);
+        assert!(serde_json::from_str::<Neopet>(&json).is_err());
+    }
+
+    #[test]
+    fn test_stance_chances_are_optional_in_json() {
+        let json = r#"{ "attack_chance": 0.5, "spell_chances": [], "heal_chance": 0.5 }"#;
+        let behavior: Behavior = serde_json::from_str(json).unwrap();
+        assert_eq!((behavior.defend_chance, behavior.taunt_chance), (0.0, 0.0));
+        assert_eq!(serde_json::to_string(&behavior).unwrap(), r#"{"attack_chance":0.5,"spell_chances":[],"heal_chance":0.5}"#);
+
+        let json = r#"{ "attack_chance": 0.5, "spell_chances": [], "heal_chance": 0.2, "defend_chance": 0.2, "taunt_chance": 0.1 }"#;
+        let behavior: Behavior = serde_json::from_str(json).unwrap();
+        assert_eq!((behavior.defend_chance, behavior.taunt_chance), (0.2, 0.1));
+        assert!(behavior.to_string().ends_with("💚 20% | 🛡️ 20% | 📣 10%"));
+        assert_eq!(serde_json::from_str::<Behavior>(&serde_json::to_string(&behavior).unwrap()).unwrap(), behavior);
+
+        let json = json.replace("0.1 }", "0.2 }");
+        assert!(serde_json::from_str::<Behavior>(&json).unwrap_err().to_string().contains("taunt: 0.2"));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_fighters_round_trip_in_every_format() {
+        let neopets = load_neopets("assets/neopets.json");
+        for format in [FighterFormat::Json, FighterFormat::Toml] {
+            for neopet in &neopets {
+                let text = format.write(neopet).unwrap();
+                assert_eq!(&format.read(&text).unwrap(), neopet, "{} in {}", neopet.name, format);
+            }
+        }
+
+        let toml = FighterFormat::Toml.write(&neopets[0]).unwrap();
+        assert!(toml.contains("[behavior]"), "{}", toml);
+        // Both go through the `NeopetDef` validation
+        let unbalanced = toml.replace("heal_chance = ", "heal_chance = 9");
+        assert!(FighterFormat::Toml.read(&unbalanced).unwrap_err().contains("must equal 1.0"));
+        let unbalanced = FighterFormat::Json.write(&neopets[0]).unwrap().replace("\"heal_chance\": ", "\"heal_chance\": 9");
+        assert!(FighterFormat::Json.read(&unbalanced).unwrap_err().contains("must equal 1.0"));
+
+        assert_eq!(FighterFormat::from_path(std::path::Path::new("fighters/acara.TOML")), Some(FighterFormat::Toml));
+        assert_eq!(FighterFormat::from_path(std::path::Path::new("acara.yaml")), None);
+    }
 }
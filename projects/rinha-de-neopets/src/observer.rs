@@ -0,0 +1,86 @@
+// src/observer.rs
+use crate::battle::BattleEvent;
+use crate::export::describe;
+use std::io::{self, Write};
+
+/// Reacts to a battle's events as they happen, so consumers don't each walk the
+/// whole `Vec<BattleEvent>` afterwards. Hand observers to
+/// `battle::battle_loop_with_observers`.
+pub trait Observer {
+    fn on_event(&mut self, event: &BattleEvent);
+
+    /// Called once after the battle's last event.
+    fn on_battle_end(&mut self) {}
+}
+
+impl<O: Observer + ?Sized> Observer for &mut O {
+    fn on_event(&mut self, event: &BattleEvent) {
+        (**self).on_event(event)
+    }
+
+    fn on_battle_end(&mut self) {
+        (**self).on_battle_end()
+    }
+}
+
+/// Writes a line to `out` for every event worth reading, e.g. `T3: Acara casts Ember on Usul`.
+/// Events stop being written after the first write error, see `error`.
+pub struct LogObserver<W: Write> {
+    out: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> LogObserver<W> {
+    pub fn new(out: W) -> Self {
+        Self { out, error: None }
+    }
+
+    /// Why the log stopped being written, if it did.
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<W: Write> Observer for LogObserver<W> {
+    fn on_event(&mut self, event: &BattleEvent) {
+        if self.error.is_some() {
+            return;
+        }
+        let Some(line) = describe(event) else { return };
+        self.error = writeln!(self.out, "T{}: {}", event.turn(), line).err();
+    }
+
+    fn on_battle_end(&mut self) {
+        if self.error.is_none() {
+            self.error = self.out.flush().err();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_observer_writes_a_line_per_readable_event() {
+        let mut log = LogObserver::new(Vec::new());
+        let observer: &mut dyn Observer = &mut log;
+        observer.on_event(&BattleEvent::Heal { turn: 2, actor: "Acara".into(), amount: 5 });
+        observer.on_event(&BattleEvent::Prediction {
+            turn: 2,
+            fighter1: "Acara".into(),
+            fighter2: "Usul".into(),
+            fighter1_win_probability: 0.5,
+        });
+        observer.on_event(&BattleEvent::Dodge { turn: 3, actor: "Usul".into(), attacker: "Acara".into() });
+        observer.on_battle_end();
+
+        assert!(log.error().is_none());
+        let text = String::from_utf8(log.into_inner()).unwrap();
+        assert_eq!(text, "T2: Acara heals for 5\nT3: Usul dodges Acara's attack\n");
+    }
+}
@@ -0,0 +1,84 @@
+// src/playback.rs
+use crossterm::event::KeyCode;
+
+/// What the viewer controls while a battle plays: the speed, pausing and skipping to
+/// the end. `display::KeyboardClock` and `tui::BattleView` both read the keys through
+/// it, so the animated display and the full-screen view take the same ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Playback {
+    speed: f64,
+    paused: bool,
+    skipping: bool,
+}
+
+impl Playback {
+    /// Slowest and fastest speeds `-` and `+` go to.
+    pub const MIN_SPEED: f64 = 0.25;
+    pub const MAX_SPEED: f64 = 16.0;
+
+    pub fn new(speed: f64) -> Self {
+        Self { speed: speed.clamp(Self::MIN_SPEED, Self::MAX_SPEED), paused: false, skipping: false }
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Whether the viewer asked to skip to the end.
+    pub fn is_skipping(&self) -> bool {
+        self.skipping
+    }
+
+    pub fn skip(&mut self) {
+        self.skipping = true;
+    }
+
+    /// Space or `p` pauses and resumes, `+`/`-` (or the arrows) double or halve the
+    /// speed and `s` or Enter skip to the end. Returns whether `key` was one of them.
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Char(' ') | KeyCode::Char('p') => self.paused = !self.paused,
+            KeyCode::Char('+') | KeyCode::Char('=') | KeyCode::Right => self.speed = (self.speed * 2.0).min(Self::MAX_SPEED),
+            KeyCode::Char('-') | KeyCode::Left => self.speed = (self.speed / 2.0).max(Self::MIN_SPEED),
+            KeyCode::Char('s') | KeyCode::Enter => self.skip(),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playback_keys_and_speed_limits() {
+        let mut playback = Playback::new(100.0);
+        assert_eq!(playback.speed(), Playback::MAX_SPEED);
+        assert!(playback.handle_key(KeyCode::Char('-')));
+        assert!(playback.handle_key(KeyCode::Left));
+        assert_eq!(playback.speed(), 4.0);
+        playback.handle_key(KeyCode::Char('='));
+        assert_eq!(playback.speed(), 8.0);
+
+        playback.handle_key(KeyCode::Char(' '));
+        assert!(playback.is_paused());
+        playback.handle_key(KeyCode::Char('p'));
+        assert!(!playback.is_paused());
+
+        assert!(!playback.handle_key(KeyCode::Char('q')));
+        assert!(!playback.is_skipping());
+        playback.handle_key(KeyCode::Enter);
+        assert!(playback.is_skipping());
+    }
+}
@@ -0,0 +1,152 @@
+// src/predictor.rs
+use crate::battle::{BattleConfig, BattleError, BattleEvent};
+use crate::neopets::Neopet;
+use crate::runner::{BattleCheckpoint, BattleRunner};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+/// How a battle is expected to end from where it stands, see `rollout_estimate`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WinEstimate {
+    /// A draw counts half for each fighter.
+    pub fighter1_win_probability: f64,
+    pub fighter1_expected_hp: f64,
+    pub fighter2_expected_hp: f64,
+}
+
+/// Estimates the end of a paused battle by playing it out `rollouts` times with dice
+/// seeded by `seed`, each fighter acting on the strategy in their definition. Unlike
+/// `battle::estimate_win_probability` it accounts for everything the engine does
+/// (spells, effects, classes, the arena) at the cost of running whole battles.
+pub fn rollout_estimate<R>(
+    fighter1: &Neopet,
+    fighter2: &Neopet,
+    config: &BattleConfig,
+    checkpoint: &BattleCheckpoint<R>,
+    rollouts: u32,
+    seed: u64,
+) -> Result<WinEstimate, BattleError> {
+    // The rollouts don't predict anything themselves
+    let config = BattleConfig { predictions: false, win_probability_every: None, ..config.clone() };
+    let mut seeds = StdRng::seed_from_u64(seed);
+    let (mut wins, mut hp1, mut hp2) = (0.0, 0.0, 0.0);
+    for _ in 0..rollouts {
+        let mut rollout = checkpoint.with_rng(StdRng::from_rng(&mut seeds));
+        let mut runner = BattleRunner::resume(fighter1, fighter2, &config, &mut rollout)?;
+        let winner = runner.by_ref().fold(None, |winner, event| match event {
+            BattleEvent::BattleComplete { winner, .. } => Some(winner),
+            _ => winner,
+        });
+        if let Some(error) = runner.error() {
+            return Err(error.clone());
+        }
+        wins += match winner {
+            Some(winner) if *winner == fighter1.name => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        };
+        hp1 += f64::from(runner.state().get_hp(&fighter1.name)?);
+        hp2 += f64::from(runner.state().get_hp(&fighter2.name)?);
+    }
+
+    let rollouts = f64::from(rollouts.max(1));
+    Ok(WinEstimate {
+        fighter1_win_probability: wins / rollouts,
+        fighter1_expected_hp: hp1 / rollouts,
+        fighter2_expected_hp: hp2 / rollouts,
+    })
+}
+
+/// Whether `config` asks for a `WinProbability` event on `turn`.
+pub fn is_due(config: &BattleConfig, turn: u32) -> bool {
+    config.win_probability_every.is_some_and(|every| every > 0 && turn > 0 && (turn - 1).is_multiple_of(every))
+}
+
+/// The `WinProbability` event of `turn`, its rollouts seeded by the turn and the HP
+/// left so that fighting a battle again from its seed gives the same estimates.
+pub fn win_probability_event<R>(
+    fighter1: &Neopet,
+    fighter2: &Neopet,
+    config: &BattleConfig,
+    checkpoint: &BattleCheckpoint<R>,
+) -> Result<BattleEvent, BattleError> {
+    let state = &checkpoint.state;
+    let seed = u64::from(checkpoint.turn) << 32
+        ^ u64::from(state.get_hp(&fighter1.name)?) << 16
+        ^ u64::from(state.get_hp(&fighter2.name)?);
+    let estimate = rollout_estimate(fighter1, fighter2, config, checkpoint, config.rollouts, seed)?;
+    Ok(BattleEvent::WinProbability {
+        turn: checkpoint.turn,
+        fighter1: state.name(&fighter1.name)?,
+        fighter2: state.name(&fighter2.name)?,
+        fighter1_win_probability: estimate.fighter1_win_probability,
+        fighter1_expected_hp: estimate.fighter1_expected_hp,
+        fighter2_expected_hp: estimate.fighter2_expected_hp,
+        rollouts: config.rollouts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::battle_loop_with_config;
+    use crate::neopets::Behavior;
+    use crate::runner::CheckpointRng;
+
+    fn fighter(name: &str, health: u32, base_attack: u32) -> Neopet {
+        Neopet {
+            health,
+            heal_delta: 5,
+            base_attack,
+            behavior: Behavior::attack_or_heal(0.8),
+            ..Neopet::test_fighter(name)
+        }
+    }
+
+    fn start(fighter1: &Neopet, fighter2: &Neopet, config: &BattleConfig) -> BattleCheckpoint<CheckpointRng> {
+        let mut rng = CheckpointRng::seed_from_u64(7);
+        let mut runner = BattleRunner::new(fighter1, fighter2, config, &mut rng).unwrap();
+        runner.by_ref().take(2).for_each(drop); // The initiative rolls
+        runner.checkpoint().unwrap()
+    }
+
+    #[test]
+    fn test_rollouts_favor_the_stronger_fighter() {
+        let (strong, weak) = (fighter("Strong", 80, 14), fighter("Weak", 40, 6));
+        let config = BattleConfig { max_turns: 60, ..BattleConfig::default() };
+        let checkpoint = start(&strong, &weak, &config);
+
+        let estimate = rollout_estimate(&strong, &weak, &config, &checkpoint, 50, 1).unwrap();
+        assert!(estimate.fighter1_win_probability > 0.9, "{:?}", estimate);
+        assert!(estimate.fighter1_expected_hp > estimate.fighter2_expected_hp);
+        assert!(estimate.fighter1_expected_hp <= 80.0);
+        assert_eq!(rollout_estimate(&strong, &weak, &config, &checkpoint, 50, 1).unwrap(), estimate);
+
+        let reversed = rollout_estimate(&weak, &strong, &config, &checkpoint, 50, 1).unwrap();
+        assert!(reversed.fighter1_win_probability < 0.1, "{:?}", reversed);
+    }
+
+    #[test]
+    fn test_win_probability_events_are_due_every_few_turns() {
+        let config = BattleConfig { win_probability_every: Some(3), rollouts: 10, ..BattleConfig::default() };
+        let due: Vec<u32> = (0..=10).filter(|&turn| is_due(&config, turn)).collect();
+        assert_eq!(due, vec![1, 4, 7, 10]);
+        assert!(!is_due(&BattleConfig::default(), 1));
+        assert!(!is_due(&BattleConfig { win_probability_every: Some(0), ..config.clone() }, 1));
+
+        let (acara, usul) = (fighter("Acara", 50, 9), fighter("Usul", 50, 9));
+        let mut rng = StdRng::seed_from_u64(3);
+        let events = battle_loop_with_config(&acara, &usul, &config, &mut rng).unwrap();
+        let estimates: Vec<&BattleEvent> = events.iter().filter(|e| matches!(e, BattleEvent::WinProbability { .. })).collect();
+        assert!(!estimates.is_empty());
+        for event in estimates {
+            let BattleEvent::WinProbability { turn, fighter1_win_probability, rollouts, .. } = event else {
+                unreachable!();
+            };
+            assert!(is_due(&config, *turn));
+            assert!((0.0..=1.0).contains(fighter1_win_probability));
+            assert_eq!(*rollouts, 10);
+        }
+        assert_eq!(battle_loop_with_config(&acara, &usul, &config, &mut StdRng::seed_from_u64(3)).unwrap(), events);
+    }
+}
@@ -0,0 +1,29 @@
+// src/prelude.rs
+// The types most programs using the library need, in one import:
+// `use rinha_de_neopets::prelude::*;`
+
+pub use crate::arena::{Arena, Arenas};
+pub use crate::battle::{
+    battle_loop, battle_loop_with_config, battle_loop_with_observers, battle_loop_with_strategies, Action,
+    BattleCompletionReason, BattleConfig, BattleError, BattleEvent, BattleState,
+};
+pub use crate::battle_log::BattleLog;
+pub use crate::engine::Engine;
+pub use crate::handicap::Handicap;
+pub use crate::loot::Item;
+pub use crate::neopets::{Behavior, Neopet, Spell};
+pub use crate::observer::{LogObserver, Observer};
+pub use crate::render::{render_battle, BattleRenderer, JsonRenderer, PlainRenderer};
+pub use crate::runner::BattleRunner;
+pub use crate::stats::{BattleStats, StatsObserver};
+pub use crate::storage::{BattleRecord, Storage};
+pub use crate::strategy::{BattleContext, BattleStrategy, StrategyKind};
+
+#[cfg(feature = "cassino")]
+pub use crate::cassino::{BattleOutcome, CassinoEvent, Market, VictoryMethod};
+#[cfg(feature = "display")]
+pub use crate::display::{BattleDisplay, BattleDisplayConfig, Clock, DisplayMode, InstantClock, KeyboardClock, SystemClock};
+#[cfg(any(feature = "display", feature = "tui"))]
+pub use crate::playback::Playback;
+#[cfg(feature = "async")]
+pub use crate::live::{battle_loop_async, spawn_battle};
@@ -0,0 +1,232 @@
+// src/promotions.rs
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// Credit the house grants to the bettor. Promotional credit is never part of the
+/// real balance: only what it wins is paid out, as a bonus.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum PromotionKind {
+    /// Bet `amount` on the house. If the bet wins only the winnings are paid,
+    /// the stake is not returned.
+    FreeBet { amount: f64 },
+    /// Raises the winnings of one real-money bet by `boost`, e.g. 0.25 for +25%.
+    OddsBoost { boost: f64 },
+}
+
+impl fmt::Display for PromotionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PromotionKind::FreeBet { amount } => write!(f, "Free bet of ${:.2}", amount),
+            PromotionKind::OddsBoost { boost } => write!(f, "Odds boost of +{:.0}%", boost * 100.0),
+        }
+    }
+}
+
+/// How a bet is paid for and paid out once a promotion is applied to it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PromotedBet {
+    /// Taken from the real balance when the bet is placed.
+    pub real_stake: f64,
+    pub odd: f64,
+    /// Paid if the bet wins, `bonus_win` included.
+    pub potential_win: f64,
+    /// The part of `potential_win` owed to the promotion, accounted as a bonus.
+    pub bonus_win: f64,
+}
+
+impl PromotionKind {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            PromotionKind::FreeBet { amount } if amount <= 0.0 => {
+                Err("A free bet must be worth more than 0".to_string())
+            }
+            PromotionKind::OddsBoost { boost } if boost <= 0.0 => {
+                Err("An odds boost must be greater than 0".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Applies the promotion to a bet of `stake` at `odd`. A free bet must be
+    /// placed for exactly its amount.
+    pub fn apply(&self, stake: f64, odd: f64) -> Result<PromotedBet, String> {
+        match *self {
+            PromotionKind::FreeBet { amount } => {
+                if (stake - amount).abs() > f64::EPSILON {
+                    return Err(format!("This free bet is worth ${:.2}, bet exactly that amount", amount));
+                }
+                // Only the winnings, the house keeps its stake
+                let winnings = stake * (odd - 1.0);
+                Ok(PromotedBet { real_stake: 0.0, odd, potential_win: winnings, bonus_win: winnings })
+            }
+            PromotionKind::OddsBoost { boost } => {
+                // The boost applies to the winnings, so the stake is still returned as is
+                let boosted_odd = 1.0 + (odd - 1.0) * (1.0 + boost);
+                let potential_win = stake * boosted_odd;
+                Ok(PromotedBet {
+                    real_stake: stake,
+                    odd: boosted_odd,
+                    potential_win,
+                    bonus_win: potential_win - stake * odd,
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Promotion {
+    pub id: String, // e.g. "promo_1"
+    #[serde(flatten)]
+    pub kind: PromotionKind,
+    pub granted_at: String,         // RFC 3339
+    pub expires_at: Option<String>, // RFC 3339, None for never
+    #[serde(default)]
+    pub used_on: Option<String>,    // ID of the bet it was applied to
+}
+
+impl Promotion {
+    /// Not used yet and not expired at `now`. An unreadable expiry never expires.
+    pub fn is_available(&self, now: DateTime<FixedOffset>) -> bool {
+        let expired = self.expires_at
+            .as_deref()
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .is_some_and(|at| at <= now);
+        self.used_on.is_none() && !expired
+    }
+}
+
+/// Every promotion granted so far, stored in `assets/promotions.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(transparent)]
+pub struct Promotions {
+    pub promotions: Vec<Promotion>,
+}
+
+impl Promotions {
+    /// Loads the promotions, or none if the file doesn't exist.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(path)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    pub fn available(&self, now: DateTime<FixedOffset>) -> impl Iterator<Item = &Promotion> {
+        self.promotions.iter().filter(move |p| p.is_available(now))
+    }
+
+    /// Total of the free bets that can still be placed.
+    pub fn free_bet_credit(&self, now: DateTime<FixedOffset>) -> f64 {
+        self.available(now)
+            .map(|p| match p.kind {
+                PromotionKind::FreeBet { amount } => amount,
+                PromotionKind::OddsBoost { .. } => 0.0,
+            })
+            .fold(0.0, |total, amount| total + amount)
+    }
+
+    /// Applies promotion `id` to bet `bet_id` and marks it as used.
+    pub fn redeem(
+        &mut self,
+        id: &str,
+        bet_id: &str,
+        stake: f64,
+        odd: f64,
+        now: DateTime<FixedOffset>,
+    ) -> Result<PromotedBet, String> {
+        let promotion = self.promotions
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or_else(|| format!("Promotion '{}' not found", id))?;
+        if !promotion.is_available(now) {
+            return Err(format!("Promotion '{}' was already used or has expired", id));
+        }
+        let bet = promotion.kind.apply(stake, odd)?;
+        promotion.used_on = Some(bet_id.to_string());
+        Ok(bet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(s).unwrap()
+    }
+
+    fn promotion(id: &str, kind: PromotionKind, expires_at: Option<&str>) -> Promotion {
+        Promotion {
+            id: id.to_string(),
+            kind,
+            granted_at: "2024-01-01T00:00:00+00:00".to_string(),
+            expires_at: expires_at.map(|s| s.to_string()),
+            used_on: None,
+        }
+    }
+
+    #[test]
+    fn test_free_bet_does_not_return_the_stake() {
+        let bet = PromotionKind::FreeBet { amount: 10.0 }.apply(10.0, 3.0).unwrap();
+        assert_eq!(bet.real_stake, 0.0);
+        assert_eq!(bet.potential_win, 20.0);
+        assert_eq!(bet.bonus_win, 20.0);
+        assert!(PromotionKind::FreeBet { amount: 10.0 }.apply(5.0, 3.0).is_err());
+    }
+
+    #[test]
+    fn test_odds_boost_raises_the_winnings() {
+        let bet = PromotionKind::OddsBoost { boost: 0.5 }.apply(10.0, 3.0).unwrap();
+        assert_eq!(bet.real_stake, 10.0);
+        assert_eq!(bet.odd, 4.0);
+        assert_eq!(bet.potential_win, 40.0);
+        assert_eq!(bet.bonus_win, 10.0);
+    }
+
+    #[test]
+    fn test_redeem_only_once_and_before_expiry() {
+        let now = at("2024-01-05T00:00:00+00:00");
+        let mut promotions = Promotions {
+            promotions: vec![
+                promotion("promo_1", PromotionKind::FreeBet { amount: 10.0 }, None),
+                promotion("promo_2", PromotionKind::FreeBet { amount: 5.0 }, Some("2024-01-02T00:00:00+00:00")),
+                promotion("promo_3", PromotionKind::OddsBoost { boost: 0.1 }, Some("2024-02-01T00:00:00+00:00")),
+            ],
+        };
+        assert_eq!(promotions.available(now).count(), 2);
+        assert_eq!(promotions.free_bet_credit(now), 10.0);
+
+        promotions.redeem("promo_1", "bet_1", 10.0, 2.0, now).unwrap();
+        assert_eq!(promotions.promotions[0].used_on.as_deref(), Some("bet_1"));
+        assert_eq!(promotions.free_bet_credit(now), 0.0);
+        assert!(promotions.redeem("promo_1", "bet_2", 10.0, 2.0, now).is_err());
+        assert!(promotions.redeem("promo_2", "bet_2", 5.0, 2.0, now).is_err());
+        assert!(promotions.redeem("promo_9", "bet_2", 5.0, 2.0, now).is_err());
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let promotions = Promotions {
+            promotions: vec![promotion("promo_1", PromotionKind::OddsBoost { boost: 0.25 }, None)],
+        };
+        let json = serde_json::to_string(&promotions).unwrap();
+        assert!(json.contains("\"kind\":\"odds_boost\""));
+        assert_eq!(serde_json::from_str::<Promotions>(&json).unwrap(), promotions);
+        assert!(PromotionKind::OddsBoost { boost: 0.0 }.validate().is_err());
+    }
+}
@@ -0,0 +1,448 @@
+// src/ratings.rs
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use crate::storage::BattleRecord;
+
+/// Rating every fighter starts with before their first battle.
+pub const INITIAL_RATING: f64 = 1000.0;
+/// How many points a single battle can move a rating.
+pub const K_FACTOR: f64 = 32.0;
+
+/// Probability that a fighter rated `rating` beats one rated `opponent_rating`.
+pub fn expected_score(rating: f64, opponent_rating: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent_rating - rating) / 400.0))
+}
+
+/// Ratings after a battle, `score1` being 1.0 if fighter 1 won, 0.5 for a draw
+/// and 0.0 if they lost.
+pub fn update_ratings(rating1: f64, rating2: f64, score1: f64) -> (f64, f64) {
+    let expected1 = expected_score(rating1, rating2);
+    let delta = K_FACTOR * (score1 - expected1);
+    (rating1 + delta, rating2 - delta)
+}
+
+/// ELO rating of every fighter, replaying completed battles in the order they were stored.
+/// Fighters without battles keep the initial rating. This is plain Elo without decay, the
+/// ratings the CLI shows and pairs fighters on are `RatingsSnapshot`'s.
+pub fn compute_ratings(fighters: &[String], battles: &[BattleRecord]) -> HashMap<String, f64> {
+    let mut ratings: HashMap<String, f64> = fighters
+        .iter()
+        .map(|name| (name.clone(), INITIAL_RATING))
+        .collect();
+
+    for battle in battles.iter().filter(|b| b.is_completed) {
+        let score1 = match &battle.winner {
+            Some(winner) if *winner == battle.fighter1_name => 1.0,
+            Some(winner) if *winner == battle.fighter2_name => 0.0,
+            _ => 0.5,
+        };
+        let rating1 = *ratings.get(&battle.fighter1_name).unwrap_or(&INITIAL_RATING);
+        let rating2 = *ratings.get(&battle.fighter2_name).unwrap_or(&INITIAL_RATING);
+        let (new1, new2) = update_ratings(rating1, rating2, score1);
+        ratings.insert(battle.fighter1_name.clone(), new1);
+        ratings.insert(battle.fighter2_name.clone(), new2);
+    }
+
+    ratings
+}
+
+/// Tunable rating rules, stored in `assets/rating_rules.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RatingRules {
+    /// Points per day an inactive rating drifts back towards `INITIAL_RATING`.
+    pub decay_per_day: f64,
+    /// Days without battles before decay kicks in.
+    pub inactivity_grace_days: i64,
+    /// Completed battles needed to show up on the leaderboard.
+    pub min_battles: usize,
+}
+
+impl Default for RatingRules {
+    fn default() -> Self {
+        Self {
+            decay_per_day: 2.0,
+            inactivity_grace_days: 14,
+            min_battles: 3,
+        }
+    }
+}
+
+impl RatingRules {
+    /// Loads the rules, or the defaults if the file doesn't exist.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(path)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// `rating` after `days_inactive` days without battles, moved towards the initial
+    /// rating but never past it.
+    pub fn decay(&self, rating: f64, days_inactive: i64) -> f64 {
+        self.decay_between(rating, 0, days_inactive)
+    }
+
+    /// Like `decay`, for a rating that already decayed for the first `from_days`
+    /// of the `to_days` of inactivity.
+    pub fn decay_between(&self, rating: f64, from_days: i64, to_days: i64) -> f64 {
+        let decaying_days = |days: i64| (days - self.inactivity_grace_days).max(0) as f64;
+        let decay = (self.decay_per_day * (decaying_days(to_days) - decaying_days(from_days)).max(0.0))
+            .min((rating - INITIAL_RATING).abs());
+        if rating > INITIAL_RATING {
+            rating - decay
+        } else {
+            rating + decay
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FighterRating {
+    pub rating: f64,
+    pub battles: usize,
+    pub last_battle_at: Option<String>, // RFC 3339, creation time of the last battle
+    #[serde(default)]
+    pub wins: usize,
+    #[serde(default)]
+    pub losses: usize,
+    #[serde(default)]
+    pub draws: usize,
+    /// Days since `last_battle_at` for which `rating` was already decayed.
+    #[serde(default)]
+    pub decayed_days: i64,
+}
+
+impl Default for FighterRating {
+    fn default() -> Self {
+        Self {
+            rating: INITIAL_RATING,
+            battles: 0,
+            last_battle_at: None,
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            decayed_days: 0,
+        }
+    }
+}
+
+/// Ratings of every fighter, stored in `assets/ratings.json`. Kept up to date as
+/// battles complete and rebuilt from the full history by `recompute`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RatingsSnapshot {
+    pub computed_at: String,
+    pub rules: RatingRules,
+    pub ratings: BTreeMap<String, FighterRating>,
+}
+
+impl RatingsSnapshot {
+    /// Replays every completed battle under `rules`, applying inactivity decay before each
+    /// battle and once more up to `now`.
+    pub fn recompute(
+        fighters: &[String],
+        battles: &[BattleRecord],
+        rules: &RatingRules,
+        now: DateTime<FixedOffset>,
+    ) -> Self {
+        let mut snapshot = Self {
+            computed_at: now.to_rfc3339(),
+            rules: rules.clone(),
+            ratings: fighters.iter().map(|name| (name.clone(), FighterRating::default())).collect(),
+        };
+
+        for battle in battles {
+            snapshot.record_battle(battle);
+        }
+
+        for entry in snapshot.ratings.values_mut() {
+            if let Some(days) = days_since(entry, now) {
+                entry.rating = rules.decay_between(entry.rating, entry.decayed_days, days);
+                entry.decayed_days = days;
+            }
+        }
+
+        snapshot
+    }
+
+    /// Updates both fighters' ratings and records with a completed battle, decaying
+    /// their ratings for the time they were inactive first. Unfinished battles are ignored.
+    pub fn record_battle(&mut self, battle: &BattleRecord) {
+        if !battle.is_completed {
+            return;
+        }
+
+        let played_at = DateTime::parse_from_rfc3339(&battle.created_at).ok();
+        for name in [&battle.fighter1_name, &battle.fighter2_name] {
+            let entry = self.ratings.entry(name.clone()).or_default();
+            if let Some(days) = played_at.and_then(|played_at| days_since(entry, played_at)) {
+                entry.rating = self.rules.decay_between(entry.rating, entry.decayed_days, days);
+            }
+        }
+
+        let score1 = match &battle.winner {
+            Some(winner) if *winner == battle.fighter1_name => 1.0,
+            Some(winner) if *winner == battle.fighter2_name => 0.0,
+            _ => 0.5,
+        };
+        let (new1, new2) = update_ratings(
+            self.ratings[&battle.fighter1_name].rating,
+            self.ratings[&battle.fighter2_name].rating,
+            score1,
+        );
+        for (name, rating, score) in [
+            (&battle.fighter1_name, new1, score1),
+            (&battle.fighter2_name, new2, 1.0 - score1),
+        ] {
+            let entry = self.ratings.get_mut(name).unwrap();
+            entry.rating = rating;
+            entry.battles += 1;
+            entry.last_battle_at = Some(battle.created_at.clone());
+            entry.decayed_days = 0;
+            if score == 1.0 {
+                entry.wins += 1;
+            } else if score == 0.0 {
+                entry.losses += 1;
+            } else {
+                entry.draws += 1;
+            }
+        }
+    }
+
+    /// The rating of each of `fighters`, as the leaderboard shows it, for matchmaking and
+    /// seeding. Fighters without battles have the initial rating.
+    pub fn current(&self, fighters: &[String]) -> HashMap<String, f64> {
+        fighters
+            .iter()
+            .map(|name| (name.clone(), self.ratings.get(name).map_or(INITIAL_RATING, |r| r.rating)))
+            .collect()
+    }
+
+    /// Fighters with enough battles to be ranked, best rating first.
+    pub fn leaderboard(&self) -> Vec<(&String, &FighterRating)> {
+        let mut eligible: Vec<_> = self.ratings
+            .iter()
+            .filter(|(_, r)| r.battles >= self.rules.min_battles)
+            .collect();
+        eligible.sort_by(|a, b| b.1.rating.total_cmp(&a.1.rating));
+        eligible
+    }
+
+    /// Loads the last computed ratings, if they were ever computed.
+    pub fn load(path: &str) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        if !Path::new(path).exists() {
+            return Ok(None);
+        }
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(Some(serde_json::from_reader(reader)?))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(path)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+}
+
+/// Whole days between the fighter's last battle and `at`, if they ever fought.
+fn days_since(rating: &FighterRating, at: DateTime<FixedOffset>) -> Option<i64> {
+    let last = DateTime::parse_from_rfc3339(rating.last_battle_at.as_ref()?).ok()?;
+    Some((at - last).num_days())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completed_battle(fighter1: &str, fighter2: &str, winner: Option<&str>) -> BattleRecord {
+        BattleRecord {
+            winner: winner.map(|w| w.to_string()),
+            is_completed: true,
+            ..BattleRecord::test_battle(&format!("battle_{}_{}", fighter1, fighter2), fighter1, fighter2)
+        }
+    }
+
+    fn battle_at(fighter1: &str, fighter2: &str, winner: &str, created_at: &str) -> BattleRecord {
+        BattleRecord {
+            created_at: created_at.to_string(),
+            ..completed_battle(fighter1, fighter2, Some(winner))
+        }
+    }
+
+    fn at(timestamp: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap()
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_expected_score_even_match() {
+        assert_eq!(expected_score(1200.0, 1200.0), 0.5);
+        assert!(expected_score(1400.0, 1000.0) > 0.9);
+    }
+
+    #[test]
+    fn test_update_ratings_is_zero_sum() {
+        let (a, b) = update_ratings(1000.0, 1000.0, 1.0);
+        assert_eq!(a, 1016.0);
+        assert_eq!(b, 984.0);
+    }
+
+    #[test]
+    fn test_compute_ratings_without_battles() {
+        let ratings = compute_ratings(&names(&["Acara", "Usul"]), &[]);
+        assert_eq!(ratings["Acara"], INITIAL_RATING);
+        assert_eq!(ratings["Usul"], INITIAL_RATING);
+    }
+
+    #[test]
+    fn test_compute_ratings_rewards_winner() {
+        let battles = vec![
+            completed_battle("Acara", "Usul", Some("Acara")),
+            completed_battle("Usul", "Acara", Some("Acara")),
+        ];
+        let ratings = compute_ratings(&names(&["Acara", "Usul"]), &battles);
+        assert!(ratings["Acara"] > INITIAL_RATING);
+        assert!(ratings["Usul"] < INITIAL_RATING);
+    }
+
+    #[test]
+    fn test_compute_ratings_draw_between_equals_changes_nothing() {
+        let battles = vec![completed_battle("Acara", "Usul", None)];
+        let ratings = compute_ratings(&names(&["Acara", "Usul"]), &battles);
+        assert_eq!(ratings["Acara"], INITIAL_RATING);
+    }
+
+    #[test]
+    fn test_compute_ratings_ignores_unfinished_battles() {
+        let mut battle = completed_battle("Acara", "Usul", Some("Acara"));
+        battle.is_completed = false;
+        let ratings = compute_ratings(&names(&["Acara", "Usul"]), &[battle]);
+        assert_eq!(ratings["Acara"], INITIAL_RATING);
+    }
+
+    #[test]
+    fn test_decay_moves_towards_initial_rating_after_grace() {
+        let rules = RatingRules { decay_per_day: 2.0, inactivity_grace_days: 10, min_battles: 0 };
+        assert_eq!(rules.decay(1100.0, 10), 1100.0);
+        assert_eq!(rules.decay(1100.0, 15), 1090.0);
+        assert_eq!(rules.decay(900.0, 15), 910.0);
+        // Never overshoots the initial rating
+        assert_eq!(rules.decay(1005.0, 100), INITIAL_RATING);
+    }
+
+    #[test]
+    fn test_recompute_without_decay_matches_compute_ratings() {
+        let rules = RatingRules { decay_per_day: 0.0, ..RatingRules::default() };
+        let battles = vec![
+            completed_battle("Acara", "Usul", Some("Acara")),
+            completed_battle("Usul", "Xweetok", Some("Xweetok")),
+        ];
+        let fighters = names(&["Acara", "Usul", "Xweetok"]);
+        let snapshot = RatingsSnapshot::recompute(&fighters, &battles, &rules, at("2024-01-01T00:00:00Z"));
+        let expected = compute_ratings(&fighters, &battles);
+        for name in &fighters {
+            assert_eq!(snapshot.ratings[name].rating, expected[name]);
+        }
+        assert_eq!(snapshot.ratings["Usul"].battles, 2);
+    }
+
+    #[test]
+    fn test_recompute_decays_inactive_fighters_until_now() {
+        let rules = RatingRules { decay_per_day: 1.0, inactivity_grace_days: 0, min_battles: 0 };
+        let battles = vec![battle_at("Acara", "Usul", "Acara", "2024-01-01T00:00:00Z")];
+        let fighters = names(&["Acara", "Usul"]);
+
+        let fresh = RatingsSnapshot::recompute(&fighters, &battles, &rules, at("2024-01-01T00:00:00Z"));
+        let later = RatingsSnapshot::recompute(&fighters, &battles, &rules, at("2024-01-06T00:00:00Z"));
+        assert_eq!(fresh.ratings["Acara"].rating, 1016.0);
+        assert_eq!(later.ratings["Acara"].rating, 1011.0);
+        assert_eq!(later.ratings["Usul"].rating, 989.0);
+    }
+
+    #[test]
+    fn test_current_ratings_are_the_decayed_ones() {
+        let rules = RatingRules { decay_per_day: 1.0, inactivity_grace_days: 0, min_battles: 0 };
+        let battles = vec![battle_at("Acara", "Usul", "Acara", "2024-01-01T00:00:00Z")];
+        let snapshot = RatingsSnapshot::recompute(&names(&["Acara", "Usul"]), &battles, &rules, at("2024-01-06T00:00:00Z"));
+
+        let current = snapshot.current(&names(&["Acara", "Kacheek"]));
+        assert_eq!(current.len(), 2);
+        assert_eq!(current["Acara"], 1011.0);
+        assert_ne!(current["Acara"], compute_ratings(&names(&["Acara", "Usul"]), &battles)["Acara"]);
+        assert_eq!(current["Kacheek"], INITIAL_RATING);
+    }
+
+    #[test]
+    fn test_leaderboard_requires_min_battles() {
+        let rules = RatingRules { decay_per_day: 0.0, inactivity_grace_days: 0, min_battles: 2 };
+        let battles = vec![
+            completed_battle("Acara", "Usul", Some("Acara")),
+            completed_battle("Acara", "Xweetok", Some("Acara")),
+        ];
+        let snapshot = RatingsSnapshot::recompute(
+            &names(&["Acara", "Usul", "Xweetok"]),
+            &battles,
+            &rules,
+            at("2024-01-01T00:00:00Z"),
+        );
+        let board: Vec<&String> = snapshot.leaderboard().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(board, vec!["Acara"]);
+    }
+
+    #[test]
+    fn test_rules_load_missing_file_uses_defaults() {
+        assert_eq!(RatingRules::load("/nonexistent/rating_rules.json").unwrap(), RatingRules::default());
+        assert!(RatingsSnapshot::load("/nonexistent/ratings.json").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_record_battle_tracks_win_loss_records() {
+        let rules = RatingRules { decay_per_day: 0.0, ..RatingRules::default() };
+        let fighters = names(&["Acara", "Usul"]);
+        let mut snapshot = RatingsSnapshot::recompute(&fighters, &[], &rules, at("2024-01-01T00:00:00Z"));
+
+        snapshot.record_battle(&completed_battle("Acara", "Usul", Some("Acara")));
+        snapshot.record_battle(&completed_battle("Usul", "Acara", Some("Acara")));
+        snapshot.record_battle(&completed_battle("Usul", "Acara", None));
+
+        let acara = &snapshot.ratings["Acara"];
+        assert_eq!((acara.wins, acara.losses, acara.draws, acara.battles), (2, 0, 1, 3));
+        let usul = &snapshot.ratings["Usul"];
+        assert_eq!((usul.wins, usul.losses, usul.draws), (0, 2, 1));
+        assert!(acara.rating > usul.rating);
+    }
+
+    #[test]
+    fn test_record_battle_matches_recompute() {
+        let rules = RatingRules { decay_per_day: 1.0, inactivity_grace_days: 0, min_battles: 0 };
+        let battles = vec![
+            battle_at("Acara", "Usul", "Acara", "2024-01-01T00:00:00Z"),
+            battle_at("Usul", "Acara", "Usul", "2024-01-09T00:00:00Z"),
+        ];
+        let fighters = names(&["Acara", "Usul"]);
+        let now = at("2024-01-09T00:00:00Z");
+
+        let mut incremental = RatingsSnapshot::recompute(&fighters, &battles[..1], &rules, now);
+        incremental.record_battle(&battles[1]);
+        let full = RatingsSnapshot::recompute(&fighters, &battles, &rules, now);
+        assert_eq!(incremental.ratings, full.ratings);
+    }
+}
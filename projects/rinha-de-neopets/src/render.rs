@@ -0,0 +1,283 @@
+// src/render.rs
+use crate::battle::BattleEvent;
+use crate::battle_log::BattleLog;
+use crate::export::describe;
+use crate::observer::Observer;
+use serde::Serialize;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// Shows a battle one event at a time, then sums it up, so the terminal, scripts and
+/// other frontends all consume the same event stream. `display::BattleDisplay` is the
+/// animated terminal one (with `display`), `PlainRenderer` and `JsonRenderer` write text.
+pub trait BattleRenderer {
+    fn render_event(&mut self, event: &BattleEvent);
+
+    /// Called once after the last event, with all of them.
+    fn render_summary(&mut self, events: &[BattleEvent]);
+}
+
+impl<R: BattleRenderer + ?Sized> BattleRenderer for &mut R {
+    fn render_event(&mut self, event: &BattleEvent) {
+        (**self).render_event(event)
+    }
+
+    fn render_summary(&mut self, events: &[BattleEvent]) {
+        (**self).render_summary(events)
+    }
+}
+
+impl<R: BattleRenderer + ?Sized> BattleRenderer for Box<R> {
+    fn render_event(&mut self, event: &BattleEvent) {
+        (**self).render_event(event)
+    }
+
+    fn render_summary(&mut self, events: &[BattleEvent]) {
+        (**self).render_summary(events)
+    }
+}
+
+/// Hands every event of a finished battle to `renderer`, then the summary.
+pub fn render_battle<R: BattleRenderer + ?Sized>(renderer: &mut R, events: &[BattleEvent]) {
+    for event in events {
+        renderer.render_event(event);
+    }
+    renderer.render_summary(events);
+}
+
+/// Renders a battle's events while it is fought, handed to
+/// `battle::battle_loop_with_observers`. The summary is left to the caller, who has all
+/// the events once the battle is over.
+pub struct RenderObserver<R: BattleRenderer> {
+    renderer: R,
+}
+
+impl<R: BattleRenderer> RenderObserver<R> {
+    pub fn new(renderer: R) -> Self {
+        Self { renderer }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.renderer
+    }
+}
+
+impl<R: BattleRenderer> Observer for RenderObserver<R> {
+    fn on_event(&mut self, event: &BattleEvent) {
+        self.renderer.render_event(event);
+    }
+}
+
+/// The renderers `battle replay --format` and `battle start --output` pick from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderFormat {
+    /// `display::BattleDisplay`, spinners, delays and health bars.
+    #[default]
+    Animated,
+    /// `PlainRenderer`.
+    Plain,
+    /// `JsonRenderer`.
+    Json,
+}
+
+impl fmt::Display for RenderFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RenderFormat::Animated => "animated",
+            RenderFormat::Plain => "plain",
+            RenderFormat::Json => "json",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for RenderFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "animated" => Ok(RenderFormat::Animated),
+            "plain" => Ok(RenderFormat::Plain),
+            "json" => Ok(RenderFormat::Json),
+            _ => Err(format!("Unknown render format '{}', expected animated, plain or json", s)),
+        }
+    }
+}
+
+/// The last turn played and who won, for the renderers' summaries.
+fn outcome(events: &[BattleEvent]) -> (u32, Option<&str>) {
+    let turns = events.iter().map(BattleEvent::turn).max().unwrap_or(0);
+    (turns, BattleLog::from(events).winner())
+}
+
+/// Writes a line per event worth reading, e.g. `T3: Acara casts Ember on Usul`, and
+/// who won. Events stop being written after the first write error, see `error`.
+pub struct PlainRenderer<W: Write> {
+    out: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> PlainRenderer<W> {
+    pub fn new(out: W) -> Self {
+        Self { out, error: None }
+    }
+
+    /// Why the output stopped being written, if it did.
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<W: Write> BattleRenderer for PlainRenderer<W> {
+    fn render_event(&mut self, event: &BattleEvent) {
+        if self.error.is_some() {
+            return;
+        }
+        let Some(line) = describe(event) else { return };
+        self.error = writeln!(self.out, "T{}: {}", event.turn(), line).err();
+    }
+
+    fn render_summary(&mut self, events: &[BattleEvent]) {
+        if self.error.is_some() {
+            return;
+        }
+        let (turns, winner) = outcome(events);
+        let result = match winner {
+            Some(winner) => writeln!(self.out, "Winner: {} after {} turns", winner, turns),
+            None => writeln!(self.out, "No winner after {} turns", turns),
+        };
+        self.error = result.and_then(|_| self.out.flush()).err();
+    }
+}
+
+/// The last line `JsonRenderer` writes.
+#[derive(Serialize)]
+#[serde(tag = "type", rename = "summary")]
+struct Summary<'a> {
+    winner: Option<&'a str>,
+    turns: u32,
+    events: usize,
+}
+
+/// Writes every event as a line of JSON, as `export::write_ndjson` does, then a
+/// `{"type": "summary", ...}` line with the winner, the turns and the event count.
+/// Events stop being written after the first write error, see `error`.
+pub struct JsonRenderer<W: Write> {
+    out: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> JsonRenderer<W> {
+    pub fn new(out: W) -> Self {
+        Self { out, error: None }
+    }
+
+    /// Why the output stopped being written, if it did.
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    fn write_line<T: Serialize>(&mut self, value: &T) {
+        if self.error.is_none() {
+            self.error = serde_json::to_writer(&mut self.out, value)
+                .map_err(io::Error::from)
+                .and_then(|_| writeln!(self.out))
+                .err();
+        }
+    }
+}
+
+impl<W: Write> BattleRenderer for JsonRenderer<W> {
+    fn render_event(&mut self, event: &BattleEvent) {
+        self.write_line(event);
+    }
+
+    fn render_summary(&mut self, events: &[BattleEvent]) {
+        let (turns, winner) = outcome(events);
+        self.write_line(&Summary { winner, turns, events: events.len() });
+        if self.error.is_none() {
+            self.error = self.out.flush().err();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::BattleCompletionReason;
+    use serde_json::json;
+
+    fn events() -> Vec<BattleEvent> {
+        vec![
+            BattleEvent::Heal { turn: 1, actor: "Acara".into(), amount: 5 },
+            BattleEvent::Prediction { turn: 2, fighter1: "Acara".into(), fighter2: "Usul".into(), fighter1_win_probability: 0.5 },
+            BattleEvent::BattleComplete {
+                turn: 2,
+                winner: "Usul".into(),
+                loser: "Acara".into(),
+                winner_final_hp: 12,
+                loser_final_hp: 0,
+                completion_reason: BattleCompletionReason::HpDepleted("Acara".into()),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_plain_renderer_writes_readable_lines() {
+        let mut renderer = PlainRenderer::new(Vec::new());
+        render_battle(&mut renderer as &mut dyn BattleRenderer, &events());
+        assert!(renderer.error().is_none());
+        let text = String::from_utf8(renderer.into_inner()).unwrap();
+        assert_eq!(text, "T1: Acara heals for 5\nWinner: Usul after 2 turns\n");
+    }
+
+    #[test]
+    fn test_json_renderer_streams_events_then_the_summary() {
+        let events = events();
+        let mut renderer = JsonRenderer::new(Vec::new());
+        render_battle(&mut renderer, &events);
+        let text = String::from_utf8(renderer.into_inner()).unwrap();
+        let lines: Vec<serde_json::Value> = text.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(serde_json::from_value::<BattleEvent>(lines[0].clone()).unwrap(), events[0]);
+        assert_eq!(lines[3], json!({ "type": "summary", "winner": "Usul", "turns": 2, "events": 3 }));
+    }
+
+    #[test]
+    fn test_render_observer_streams_a_fought_battle() {
+        use crate::battle::{battle_loop_with_observers, BattleConfig};
+        use crate::neopets::Neopet;
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+
+        let fighter = |name: &str| Neopet::test_fighter(name);
+        let mut observer = RenderObserver::new(JsonRenderer::new(Vec::new()));
+        let events = battle_loop_with_observers(
+            &fighter("Acara"),
+            &fighter("Usul"),
+            &BattleConfig::default(),
+            &mut [&mut observer],
+            &mut StdRng::seed_from_u64(3),
+        ).unwrap();
+
+        let text = String::from_utf8(observer.into_inner().into_inner()).unwrap();
+        let streamed: Vec<BattleEvent> = text.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(streamed, events);
+    }
+
+    #[test]
+    fn test_render_format_parses() {
+        assert_eq!("JSON".parse::<RenderFormat>(), Ok(RenderFormat::Json));
+        assert_eq!(RenderFormat::Plain.to_string(), "plain");
+        assert!("html".parse::<RenderFormat>().is_err());
+    }
+}
@@ -0,0 +1,463 @@
+// src/runner.rs
+use crate::battle::{
+    apply_fatigue, predict, process_turn_with_state, roll_for_initiative, BattleConfig, BattleError,
+    BattleEvent, BattleState,
+};
+use crate::neopets::Neopet;
+use crate::predictor;
+use crate::strategy::{BattleContext, BattleStrategy, TakenAction};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// The RNG to run a battle with when it may be checkpointed. Seeded with
+/// `SeedableRng::seed_from_u64` it rolls the same dice as `StdRng`, and unlike
+/// `StdRng` its state can be saved.
+pub type CheckpointRng = rand_chacha::ChaCha12Rng;
+
+/// A battle paused between two actions: everything a `BattleRunner` needs to go on
+/// exactly as if it had never stopped, see `BattleRunner::checkpoint`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BattleCheckpoint<R> {
+    pub first: String, // Who won the initiative
+    pub state: BattleState,
+    pub turn: u32,     // The next turn to play
+    pub first_to_act: bool,
+    #[serde(default)]
+    pub extra_action_taken: bool,
+    #[serde(with = "crate::event_schema::versioned")]
+    pub history: Vec<BattleEvent>,
+    pub actions: Vec<TakenAction>,
+    pub rng: R,
+}
+
+impl<R> BattleCheckpoint<R> {
+    /// The same checkpoint rolling with `rng` instead, to play it out in more than one way.
+    pub fn with_rng<S>(&self, rng: S) -> BattleCheckpoint<S> {
+        BattleCheckpoint {
+            first: self.first.clone(),
+            state: self.state.clone(),
+            turn: self.turn,
+            first_to_act: self.first_to_act,
+            extra_action_taken: self.extra_action_taken,
+            history: self.history.clone(),
+            actions: self.actions.clone(),
+            rng,
+        }
+    }
+}
+
+/// A battle played one action at a time, yielding its events as they happen instead
+/// of returning them all at the end. Collecting it gives the same events as
+/// `battle::battle_loop_with_strategies` with the same RNG.
+///
+/// Only the last `config.history_window` events and actions are kept for the
+/// strategies, so a runner never buffers the whole fight.
+pub struct BattleRunner<'a, R: Rng> {
+    fighter1: &'a Neopet,
+    fighter2: &'a Neopet,
+    first: &'a Neopet,
+    second: &'a Neopet,
+    first_strategy: Box<dyn BattleStrategy + 'a>,
+    second_strategy: Box<dyn BattleStrategy + 'a>,
+    config: &'a BattleConfig,
+    rng: &'a mut R,
+    state: BattleState,
+    turn: u32,
+    first_to_act: bool,
+    extra_action_taken: bool, // Whether the actor already took the extra action of their turn
+    history: Vec<BattleEvent>,
+    actions: Vec<TakenAction>,
+    pending: VecDeque<BattleEvent>,
+    finished: bool,
+    error: Option<BattleError>,
+}
+
+impl<'a, R: Rng> BattleRunner<'a, R> {
+    /// A battle where each fighter acts on the strategy picked in their definition.
+    pub fn new(
+        fighter1: &'a Neopet,
+        fighter2: &'a Neopet,
+        config: &'a BattleConfig,
+        rng: &'a mut R,
+    ) -> Result<Self, BattleError> {
+        Self::with_strategies(fighter1, fighter2, config, fighter1.strategy.build(), fighter2.strategy.build(), rng)
+    }
+
+    /// A battle where each fighter's actions are picked by their own strategy.
+    /// Initiative is rolled right away, its events are the first ones yielded.
+    pub fn with_strategies(
+        fighter1: &'a Neopet,
+        fighter2: &'a Neopet,
+        config: &'a BattleConfig,
+        strategy1: Box<dyn BattleStrategy + 'a>,
+        strategy2: Box<dyn BattleStrategy + 'a>,
+        rng: &'a mut R,
+    ) -> Result<Self, BattleError> {
+        if fighter1.name == fighter2.name {
+            return Err(BattleError::DuplicateFighter(fighter1.name.clone()));
+        }
+        if config.max_turns == 0 {
+            return Err(BattleError::NoTurns);
+        }
+
+        let (initiative_events, first, second) = roll_for_initiative(fighter1, fighter2, config, rng);
+        let (first_strategy, second_strategy) = if std::ptr::eq(first, fighter1) {
+            (strategy1, strategy2)
+        } else {
+            (strategy2, strategy1)
+        };
+        let state = BattleState::new(fighter1, fighter2, config.max_turns)
+            .with_dice(config.dice_for(&fighter1.name), config.dice_for(&fighter2.name));
+
+        let mut runner = Self {
+            fighter1,
+            fighter2,
+            first,
+            second,
+            first_strategy,
+            second_strategy,
+            config,
+            rng,
+            state,
+            turn: 1, // Turn 0 is the initiative
+            first_to_act: true,
+            extra_action_taken: false,
+            history: Vec::new(),
+            actions: Vec::new(),
+            pending: VecDeque::new(),
+            finished: false,
+            error: None,
+        };
+        runner.emit(initiative_events);
+        Ok(runner)
+    }
+
+    /// The battle as it stands after the events yielded so far.
+    pub fn state(&self) -> &BattleState {
+        &self.state
+    }
+
+    /// Snapshot of the battle between two actions, `None` while events of the last
+    /// action are still waiting to be yielded or once the battle is over. The events
+    /// yielded so far aren't part of it.
+    pub fn checkpoint(&self) -> Option<BattleCheckpoint<R>>
+    where
+        R: Clone,
+    {
+        if !self.pending.is_empty() || self.finished {
+            return None;
+        }
+        Some(BattleCheckpoint {
+            first: self.first.name.clone(),
+            state: self.state.clone(),
+            turn: self.turn,
+            first_to_act: self.first_to_act,
+            extra_action_taken: self.extra_action_taken,
+            history: self.history.clone(),
+            actions: self.actions.clone(),
+            rng: self.rng.clone(),
+        })
+    }
+
+    /// The battle as it stands, without its RNG.
+    fn snapshot(&self) -> BattleCheckpoint<()> {
+        BattleCheckpoint {
+            first: self.first.name.clone(),
+            state: self.state.clone(),
+            turn: self.turn,
+            first_to_act: self.first_to_act,
+            extra_action_taken: self.extra_action_taken,
+            history: self.history.clone(),
+            actions: self.actions.clone(),
+            rng: (),
+        }
+    }
+
+    /// Picks up a checkpointed battle between the same fighters, rolling with the
+    /// checkpoint's RNG. Each fighter acts on the strategy picked in their definition.
+    pub fn resume(
+        fighter1: &'a Neopet,
+        fighter2: &'a Neopet,
+        config: &'a BattleConfig,
+        checkpoint: &'a mut BattleCheckpoint<R>,
+    ) -> Result<Self, BattleError> {
+        for name in &checkpoint.state.fighter_names {
+            if **name != fighter1.name && **name != fighter2.name {
+                return Err(BattleError::UnknownFighter(name.to_string()));
+            }
+        }
+        let (first, second) = if checkpoint.first == fighter1.name {
+            (fighter1, fighter2)
+        } else if checkpoint.first == fighter2.name {
+            (fighter2, fighter1)
+        } else {
+            return Err(BattleError::UnknownFighter(checkpoint.first.clone()));
+        };
+
+        Ok(Self {
+            fighter1,
+            fighter2,
+            first,
+            second,
+            first_strategy: first.strategy.build(),
+            second_strategy: second.strategy.build(),
+            config,
+            state: checkpoint.state.clone(),
+            turn: checkpoint.turn,
+            first_to_act: checkpoint.first_to_act,
+            extra_action_taken: checkpoint.extra_action_taken,
+            history: checkpoint.history.clone(),
+            actions: checkpoint.actions.clone(),
+            pending: VecDeque::new(),
+            finished: false,
+            error: None,
+            rng: &mut checkpoint.rng,
+        })
+    }
+
+    /// Why the battle stopped early, if it did. The engine validates its input up
+    /// front, so this only happens if the state and the fighters disagree.
+    pub fn error(&self) -> Option<&BattleError> {
+        self.error.as_ref()
+    }
+
+    fn emit(&mut self, events: Vec<BattleEvent>) {
+        self.history.extend(events.iter().cloned());
+        let excess = self.history.len().saturating_sub(self.config.history_window);
+        self.history.drain(..excess);
+        self.pending.extend(events);
+    }
+
+    /// Plays the next action, or closes the battle once it is over.
+    fn advance(&mut self) -> Result<(), BattleError> {
+        if self.state.is_complete || self.turn > self.config.max_turns {
+            return self.complete();
+        }
+
+        let (actor, target) = if self.first_to_act { (self.first, self.second) } else { (self.second, self.first) };
+        let mut events = Vec::new();
+        apply_fatigue(self.config, self.turn, &mut self.state, &mut events);
+        predict(self.config, self.fighter1, self.fighter2, self.turn, &self.state, &mut events)?;
+        if predictor::is_due(self.config, self.turn) {
+            events.push(predictor::win_probability_event(self.fighter1, self.fighter2, self.config, &self.snapshot())?);
+        }
+        self.emit(events);
+
+        let context = BattleContext::new(
+            actor, target, self.turn, &self.state, &self.history, &self.actions, self.config.history_window,
+        );
+        let strategy = if self.first_to_act { &mut self.first_strategy } else { &mut self.second_strategy };
+        let action = strategy.choose_action(&context, &mut *self.rng);
+        self.actions.push(TakenAction { turn: self.turn, actor: actor.name.clone(), action: action.clone() });
+        let excess = self.actions.len().saturating_sub(self.config.history_window);
+        self.actions.drain(..excess);
+
+        let events = process_turn_with_state(
+            actor, target, &action, self.turn, &mut self.state, self.config, self.rng,
+        )?;
+        self.emit(events);
+        self.state.check_battle_completion();
+
+        // Fighters take turns alternately, each action uses up a turn, and a much
+        // faster fighter acts twice before passing the turn on
+        self.turn += 1;
+        if !self.extra_action_taken && self.config.has_extra_action(actor, target) {
+            self.extra_action_taken = true;
+        } else {
+            self.extra_action_taken = false;
+            self.first_to_act = !self.first_to_act;
+        }
+        Ok(())
+    }
+
+    fn complete(&mut self) -> Result<(), BattleError> {
+        self.finished = true;
+        // A battle cut short by the turn limit has neither a winner nor a reason
+        if let (Some((winner, loser)), Some(completion_reason)) =
+            (self.state.get_winner_loser(), self.state.completion_reason.clone())
+        {
+            let winner_final_hp = self.state.get_hp(&winner)?;
+            let loser_final_hp = self.state.get_hp(&loser)?;
+            self.emit(vec![BattleEvent::BattleComplete {
+                turn: self.state.current_turn,
+                winner: self.state.name(&winner)?,
+                loser: self.state.name(&loser)?,
+                winner_final_hp,
+                loser_final_hp,
+                completion_reason,
+            }]);
+        }
+        Ok(())
+    }
+}
+
+impl<R: Rng> Iterator for BattleRunner<'_, R> {
+    type Item = BattleEvent;
+
+    fn next(&mut self) -> Option<BattleEvent> {
+        while self.pending.is_empty() && !self.finished {
+            if let Err(error) = self.advance() {
+                self.error = Some(error);
+                self.finished = true;
+            }
+        }
+        self.pending.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::battle_loop_with_config;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn fighter(name: &str, health: u32) -> Neopet {
+        Neopet { health, heal_delta: 10, base_attack: 8, base_defense: 4, ..Neopet::test_fighter(name) }
+    }
+
+    #[test]
+    fn test_runner_yields_the_same_events_as_battle_loop() {
+        let (acara, usul) = (fighter("Acara", 60), fighter("Usul", 50));
+        let config = BattleConfig { predictions: true, ..BattleConfig::default() };
+        for seed in 0..20 {
+            let expected = battle_loop_with_config(&acara, &usul, &config, &mut StdRng::seed_from_u64(seed)).unwrap();
+            let mut rng = StdRng::seed_from_u64(seed);
+            let streamed: Vec<BattleEvent> = BattleRunner::new(&acara, &usul, &config, &mut rng).unwrap().collect();
+            assert_eq!(streamed, expected, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_runner_plays_lazily() {
+        let (acara, usul) = (fighter("Acara", 1000), fighter("Usul", 1000));
+        let config = BattleConfig { max_turns: 500, ..BattleConfig::default() };
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut runner = BattleRunner::new(&acara, &usul, &config, &mut rng).unwrap();
+
+        // Nothing is played before the initiative rolls are consumed
+        let initiative: Vec<BattleEvent> = runner.by_ref().take(2).collect();
+        assert!(initiative.iter().all(|e| matches!(e, BattleEvent::Roll { turn: 0, .. })));
+        assert_eq!(runner.state().current_turn, 0);
+
+        assert_eq!(runner.by_ref().take(30).count(), 30);
+        assert!(runner.state().current_turn < 30);
+        assert!(runner.history.len() <= config.history_window);
+        assert!(runner.error().is_none());
+    }
+
+    #[test]
+    fn test_runner_ends_with_battle_complete() {
+        let (acara, usul) = (fighter("Acara", 30), fighter("Usul", 30));
+        let config = BattleConfig::default();
+        let mut rng = StdRng::seed_from_u64(7);
+        let last = BattleRunner::new(&acara, &usul, &config, &mut rng).unwrap().last();
+        assert!(matches!(last, Some(BattleEvent::BattleComplete { .. })));
+    }
+
+    #[test]
+    fn test_runner_rejects_bad_input() {
+        let acara = fighter("Acara", 30);
+        let mut rng = StdRng::seed_from_u64(7);
+        assert!(matches!(
+            BattleRunner::new(&acara, &acara, &BattleConfig::default(), &mut rng),
+            Err(BattleError::DuplicateFighter(_))
+        ));
+        let config = BattleConfig { max_turns: 0, ..BattleConfig::default() };
+        assert!(matches!(
+            BattleRunner::new(&acara, &fighter("Usul", 30), &config, &mut rng),
+            Err(BattleError::NoTurns)
+        ));
+    }
+
+    #[test]
+    fn test_checkpoint_rng_rolls_like_std_rng() {
+        let (acara, usul) = (fighter("Acara", 60), fighter("Usul", 50));
+        let config = BattleConfig::default();
+        for seed in 0..10 {
+            let expected = battle_loop_with_config(&acara, &usul, &config, &mut StdRng::seed_from_u64(seed)).unwrap();
+            let events = battle_loop_with_config(&acara, &usul, &config, &mut CheckpointRng::seed_from_u64(seed)).unwrap();
+            assert_eq!(events, expected, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_resumed_battle_plays_out_the_same() {
+        let (acara, usul) = (fighter("Acara", 60), fighter("Usul", 50));
+        let config = BattleConfig { predictions: true, ..BattleConfig::default() };
+        for seed in 0..10 {
+            let expected = battle_loop_with_config(&acara, &usul, &config, &mut StdRng::seed_from_u64(seed)).unwrap();
+
+            let mut rng = CheckpointRng::seed_from_u64(seed);
+            let mut runner = BattleRunner::new(&acara, &usul, &config, &mut rng).unwrap();
+            let mut events: Vec<BattleEvent> = runner.by_ref().take(12).collect();
+            while runner.checkpoint().is_none() {
+                events.extend(runner.next());
+            }
+            // Through JSON, like a checkpoint saved to disk
+            let saved = serde_json::to_string(&runner.checkpoint().unwrap()).unwrap();
+            drop(runner);
+
+            let mut checkpoint: BattleCheckpoint<CheckpointRng> = serde_json::from_str(&saved).unwrap();
+            events.extend(BattleRunner::resume(&acara, &usul, &config, &mut checkpoint).unwrap());
+            assert_eq!(events, expected, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_much_faster_fighter_acts_twice_in_a_row() {
+        let acara = Neopet { speed: 8, ..fighter("Acara", 200) };
+        let usul = Neopet { speed: 2, ..fighter("Usul", 200) };
+        let actors = |config: &BattleConfig| {
+            let mut rng = StdRng::seed_from_u64(5);
+            let mut runner = BattleRunner::new(&acara, &usul, config, &mut rng).unwrap();
+            runner.by_ref().count();
+            runner.actions.iter().map(|a| a.actor.clone()).collect::<Vec<_>>()
+        };
+
+        let config = BattleConfig { max_turns: 12, history_window: 12, ..BattleConfig::default() };
+        let alternating = actors(&config);
+        assert!(alternating.windows(2).all(|pair| pair[0] != pair[1]));
+
+        let config = BattleConfig { extra_action_speed_gap: Some(6), ..config };
+        let acted = actors(&config);
+        assert_eq!(acted.len(), 12);
+        let runs: Vec<(&String, usize)> = acted.chunk_by(|a, b| a == b).map(|run| (&run[0], run.len())).collect();
+        for (actor, length) in &runs[..runs.len() - 1] {
+            assert_eq!(*length, if *actor == "Acara" { 2 } else { 1 }, "{:?}", acted);
+        }
+
+        // A gap of 7 is more than Acara has on Usul
+        let config = BattleConfig { extra_action_speed_gap: Some(7), ..config };
+        assert_eq!(actors(&config), alternating);
+    }
+
+    #[test]
+    fn test_checkpoint_only_between_actions() {
+        let (acara, usul) = (fighter("Acara", 60), fighter("Usul", 50));
+        let config = BattleConfig::default();
+        let mut rng = CheckpointRng::seed_from_u64(3);
+        let mut runner = BattleRunner::new(&acara, &usul, &config, &mut rng).unwrap();
+        // The initiative rolls are waiting to be yielded
+        assert!(runner.checkpoint().is_none());
+        runner.by_ref().take(2).count();
+        assert_eq!(runner.checkpoint().unwrap().turn, 1);
+        runner.by_ref().count();
+        assert!(runner.checkpoint().is_none());
+    }
+
+    #[test]
+    fn test_resume_rejects_other_fighters() {
+        let (acara, usul) = (fighter("Acara", 60), fighter("Usul", 50));
+        let config = BattleConfig::default();
+        let mut rng = CheckpointRng::seed_from_u64(3);
+        let mut runner = BattleRunner::new(&acara, &usul, &config, &mut rng).unwrap();
+        runner.by_ref().take(2).count();
+        let mut checkpoint = runner.checkpoint().unwrap();
+        assert!(matches!(
+            BattleRunner::resume(&acara, &fighter("Kacheek", 50), &config, &mut checkpoint),
+            Err(BattleError::UnknownFighter(_))
+        ));
+    }
+}
@@ -0,0 +1,97 @@
+// src/season.rs
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// The league season battles are being fought in. Seasons opt into extra rules.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct Season {
+    pub name: String,
+    /// `None` for a regular season, where every battle starts at full health.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hardcore: Option<HardcoreRules>,
+}
+
+/// Hardcore league rules: part of the HP a fighter loses stays lost in their next
+/// battles, until they sit out a round to recover.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HardcoreRules {
+    /// Share of the HP lost in a battle that carries over, from 0.0 to 1.0.
+    pub injury_carry_over: f64,
+}
+
+impl Default for HardcoreRules {
+    fn default() -> Self {
+        Self { injury_carry_over: 0.5 }
+    }
+}
+
+impl HardcoreRules {
+    /// The injury a fighter takes home after losing `hp_lost` HP in a battle.
+    pub fn injury(&self, hp_lost: u32) -> u32 {
+        (hp_lost as f64 * self.injury_carry_over.clamp(0.0, 1.0)).round() as u32
+    }
+}
+
+impl Season {
+    /// Loads the current season, or an unnamed regular one if the file doesn't exist.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(path)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = if self.name.is_empty() { "Unnamed season" } else { &self.name };
+        match self.hardcore {
+            Some(rules) => write!(
+                f,
+                "{} (hardcore league, {:.0}% of lost HP carries over)",
+                name,
+                rules.injury_carry_over * 100.0
+            ),
+            None => write!(f, "{}", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_injury_is_the_carried_over_share() {
+        let rules = HardcoreRules { injury_carry_over: 0.25 };
+        assert_eq!(rules.injury(40), 10);
+        assert_eq!(rules.injury(0), 0);
+        assert_eq!(HardcoreRules { injury_carry_over: 3.0 }.injury(40), 40);
+    }
+
+    #[test]
+    fn test_season_round_trips_through_file() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("season.json");
+        let path = path.to_str().unwrap();
+        assert_eq!(Season::load(path).unwrap(), Season::default());
+
+        let season = Season { name: "Season 2".to_string(), hardcore: Some(HardcoreRules::default()) };
+        season.save(path).unwrap();
+        assert_eq!(Season::load(path).unwrap(), season);
+        assert_eq!(season.to_string(), "Season 2 (hardcore league, 50% of lost HP carries over)");
+    }
+}
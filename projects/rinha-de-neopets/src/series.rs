@@ -0,0 +1,227 @@
+// src/series.rs
+use crate::battle::{battle_loop_with_config, BattleConfig};
+use crate::battle_log::BattleLog;
+use crate::integrity::battle_hash;
+use crate::storage::{BattleRecord, Storage};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Tag put on every battle fought in a series, next to the series' ID.
+pub const SERIES_TAG: &str = "series";
+
+/// One game of a series. Its battle is stored like any other completed battle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeriesGame {
+    pub number: u32, // Starts at 1
+    pub battle_id: String,
+    pub seed: u64, // `StdRng` seed the game ran with, also kept on its battle
+    pub winner: String,
+}
+
+/// Two fighters playing a best-of-N: the first to win a majority of the games
+/// takes the series, and the remaining games are never played.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeriesRecord {
+    pub id: String,         // e.g. "series_2"
+    pub fighter1: String,
+    pub fighter2: String,
+    pub best_of: u32,       // Always odd, so there are no tied series
+    pub created_at: String, // RFC 3339 timestamp
+    #[serde(default)]
+    pub games: Vec<SeriesGame>,
+}
+
+impl SeriesRecord {
+    pub fn new(id: &str, fighter1: &str, fighter2: &str, best_of: u32, created_at: &str) -> Result<Self, String> {
+        if best_of.is_multiple_of(2) {
+            return Err(format!("A series is played over an odd number of games, not {}", best_of));
+        }
+        if fighter1 == fighter2 {
+            return Err(format!("Fighter '{}' can't play a series against themselves", fighter1));
+        }
+        Ok(Self {
+            id: id.to_string(),
+            fighter1: fighter1.to_string(),
+            fighter2: fighter2.to_string(),
+            best_of,
+            created_at: created_at.to_string(),
+            games: Vec::new(),
+        })
+    }
+
+    /// Games a fighter has to win to take the series.
+    pub fn wins_needed(&self) -> u32 {
+        self.best_of / 2 + 1
+    }
+
+    /// Games won by fighter 1 and fighter 2 so far.
+    pub fn score(&self) -> (u32, u32) {
+        let wins = |fighter: &str| self.games.iter().filter(|g| g.winner == fighter).count() as u32;
+        (wins(&self.fighter1), wins(&self.fighter2))
+    }
+
+    /// The fighter who took the series, `None` while it is still being played.
+    pub fn winner(&self) -> Option<&str> {
+        let (wins1, wins2) = self.score();
+        if wins1 >= self.wins_needed() {
+            Some(&self.fighter1)
+        } else if wins2 >= self.wins_needed() {
+            Some(&self.fighter2)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.winner().is_some()
+    }
+}
+
+impl fmt::Display for SeriesRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (wins1, wins2) = self.score();
+        write!(
+            f,
+            "{}: {} {} - {} {} (best of {})",
+            self.id, self.fighter1, wins1, wins2, self.fighter2, self.best_of
+        )
+    }
+}
+
+/// Plays the remaining games of the series `id` until one fighter takes it. Every
+/// game gets its own seed, drawn from `rng`, and is saved to `storage` as a completed
+/// `BattleRecord` tagged with the series ID; saving `storage` to disk is left to the
+/// caller. Returns the finished series.
+pub fn play_series<R: Rng>(
+    storage: &mut Storage,
+    id: &str,
+    config: &BattleConfig,
+    created_at: &str,
+    rng: &mut R,
+) -> Result<SeriesRecord, Box<dyn std::error::Error>> {
+    let mut series = storage.find_series(id).cloned().ok_or_else(|| format!("Series '{}' not found", id))?;
+    if let Some(winner) = series.winner() {
+        return Err(format!("Series '{}' is over, {} won it", id, winner).into());
+    }
+
+    while !series.is_complete() {
+        let number = series.games.len() as u32 + 1;
+        let seed = rng.random();
+        let neopet1 = storage.battle_fighter(&series.fighter1)?;
+        let neopet2 = storage.battle_fighter(&series.fighter2)?;
+        let events = battle_loop_with_config(&neopet1, &neopet2, config, &mut StdRng::seed_from_u64(seed))?;
+        let winner = BattleLog::from(&events)
+            .winner()
+            .map(str::to_string)
+            .ok_or_else(|| format!("Game {} of {} ended without a winner", number, id))?;
+
+        let battle_id = storage.generate_battle_id()?;
+        let hash = battle_hash(&events);
+        storage.add_complete_battle(BattleRecord {
+            events,
+            winner: Some(winner.clone()),
+            is_completed: true,
+            tags: vec![SERIES_TAG.to_string(), id.to_string()],
+            notes: vec![format!("Game {} of {}", number, id)],
+            seed: Some(seed),
+            hash: Some(hash),
+            ..BattleRecord::pending(battle_id.clone(), &series.fighter1, &series.fighter2, created_at)
+        })?;
+        series.games.push(SeriesGame { number, battle_id, seed, winner });
+    }
+
+    storage.update_series(series.clone())?;
+    Ok(series)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::StorageConfig;
+    use crate::neopets::{Behavior, Neopet};
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn fighter(name: &str) -> Neopet {
+        Neopet {
+            health: 60,
+            heal_delta: 5,
+            base_attack: 8,
+            base_defense: 4,
+            behavior: Behavior::attack_or_heal(0.8),
+            ..Neopet::test_fighter(name)
+        }
+    }
+
+    fn storage_with(dir: &Path, fighters: &[&str]) -> Storage {
+        let mut storage = Storage::open(&StorageConfig::in_dir(dir)).unwrap();
+        for name in fighters {
+            storage.add_neopet(fighter(name)).unwrap();
+        }
+        storage
+    }
+
+    fn game(number: u32, winner: &str) -> SeriesGame {
+        SeriesGame { number, battle_id: format!("battle_{}", number), seed: number as u64, winner: winner.to_string() }
+    }
+
+    #[test]
+    fn test_series_is_won_by_the_first_to_a_majority() {
+        let mut series = SeriesRecord::new("series_1", "A", "B", 5, "").unwrap();
+        assert_eq!(series.wins_needed(), 3);
+        series.games = vec![game(1, "A"), game(2, "B"), game(3, "A")];
+        assert_eq!(series.score(), (2, 1));
+        assert_eq!(series.winner(), None);
+
+        series.games.push(game(4, "A"));
+        assert_eq!(series.winner(), Some("A"));
+        assert_eq!(series.to_string(), "series_1: A 3 - 1 B (best of 5)");
+    }
+
+    #[test]
+    fn test_new_series_rejects_even_lengths_and_mirror_matches() {
+        assert!(SeriesRecord::new("series_1", "A", "B", 4, "").is_err());
+        assert!(SeriesRecord::new("series_1", "A", "A", 3, "").is_err());
+        assert_eq!(SeriesRecord::new("series_1", "A", "B", 1, "").unwrap().wins_needed(), 1);
+    }
+
+    #[test]
+    fn test_play_series_stores_every_game_with_its_seed() {
+        let temp_dir = tempdir().unwrap();
+        let mut storage = storage_with(temp_dir.path(), &["A", "B"]);
+        storage.add_series(SeriesRecord::new("series_1", "A", "B", 7, "").unwrap()).unwrap();
+        let config = BattleConfig::default();
+
+        let series = play_series(&mut storage, "series_1", &config, "", &mut StdRng::seed_from_u64(7)).unwrap();
+        let (wins1, wins2) = series.score();
+        assert_eq!(wins1.max(wins2), 4);
+        assert_eq!(series.games.len() as u32, wins1 + wins2);
+        assert_eq!(storage.find_series("series_1"), Some(&series));
+
+        let stored = storage.complete_battle_records();
+        assert_eq!(stored.len(), series.games.len());
+        let rules = storage.leveling_rules();
+        let mut experience = [0, 0];
+        for (battle, game) in stored.iter().zip(&series.games) {
+            assert_eq!(battle.id, game.battle_id);
+            assert_eq!(battle.seed, Some(game.seed));
+            assert_eq!(battle.winner.as_ref(), Some(&game.winner));
+            assert!(battle.has_tag(SERIES_TAG) && battle.has_tag("series_1"));
+
+            // The seed replays the game exactly, with the fighters at the level they had
+            let [mut a, mut b] = [fighter("A"), fighter("B")];
+            rules.apply(&mut a, rules.level(experience[0]));
+            rules.apply(&mut b, rules.level(experience[1]));
+            let replayed = battle_loop_with_config(&a, &b, &config, &mut StdRng::seed_from_u64(game.seed)).unwrap();
+            assert_eq!(replayed, battle.events);
+            for (total, (_, xp)) in experience.iter_mut().zip(rules.award(battle)) {
+                *total += xp;
+            }
+        }
+
+        assert!(play_series(&mut storage, "series_1", &config, "", &mut StdRng::seed_from_u64(7)).is_err());
+        assert!(play_series(&mut storage, "series_9", &config, "", &mut StdRng::seed_from_u64(7)).is_err());
+    }
+}
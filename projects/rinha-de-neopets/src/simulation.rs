@@ -0,0 +1,274 @@
+// src/simulation.rs
+use crate::battle::{battle_loop_with_config, BattleConfig};
+use crate::battle_log::BattleLog;
+use crate::integrity::battle_hash;
+use crate::storage::{BattleRecord, Storage};
+use crate::streaks::{Rivalry, Streak, Streaks, MIN_STREAK};
+use chrono::{DateTime, Duration, FixedOffset};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+
+/// Tag put on every battle fought in a simulated day.
+pub const SIMULATION_TAG: &str = "simulation";
+
+/// One battle of a simulated day. `winner` is `None` for a draw.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedBattle {
+    pub battle_id: String,
+    pub fighter1: String,
+    pub fighter2: String,
+    pub winner: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedDay {
+    pub day: u32, // Starts at 1
+    pub battles: Vec<SimulatedBattle>,
+}
+
+/// How a fighter did over the simulated days.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub fighter: String,
+    pub rating: f64,
+    /// Rating gained (or lost, if negative) over the simulation.
+    pub rating_change: f64,
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+    pub level: u32,
+}
+
+/// Everything that happened in `simulate_days`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationSummary {
+    pub days: Vec<SimulatedDay>,
+    /// Every fighter, best rating first.
+    pub standings: Vec<Standing>,
+    /// Longest winning streak reached during the simulation, and who went on it. A
+    /// streak going into the simulation counts the wins before it too.
+    pub longest_win_streak: Option<(String, usize)>,
+    /// Streaks and rivalries still going at the end, longest first.
+    pub active_streaks: Vec<(String, Streak)>,
+    pub active_rivalries: Vec<Rivalry>,
+}
+
+impl SimulationSummary {
+    pub fn battles(&self) -> usize {
+        self.days.iter().map(|day| day.battles.len()).sum()
+    }
+}
+
+/// Simulates `days` days of the season starting on `start`. Every day the fighters are
+/// paired at random, each fighting at most once, and up to `battles_per_day` of the
+/// pairs fight (all of them if `None`). Each battle gets its own seed, drawn from `rng`,
+/// is dated on its day and saved to `storage` as a completed `BattleRecord`, which
+/// updates ratings, injuries and experience; fighters left out of a day sit it out.
+/// Saving `storage` to disk is left to the caller.
+pub fn simulate_days<R: Rng>(
+    storage: &mut Storage,
+    days: u32,
+    battles_per_day: Option<usize>,
+    config: &BattleConfig,
+    start: DateTime<FixedOffset>,
+    rng: &mut R,
+) -> Result<SimulationSummary, Box<dyn std::error::Error>> {
+    let mut fighters = storage.list_fighters();
+    if fighters.len() < 2 {
+        return Err("Simulating a season takes at least 2 fighters".into());
+    }
+    fighters.sort();
+    let ratings_before = storage.ratings().ratings.clone();
+    let mut streaks = Streaks::from_battles(storage.complete_battle_records());
+    let mut longest_win_streak: Option<(String, usize)> = None;
+    let mut records: BTreeMap<String, [usize; 3]> = BTreeMap::new();
+
+    let mut simulated = Vec::new();
+    for day in 1..=days {
+        let created_at = (start + Duration::days(i64::from(day - 1))).to_rfc3339();
+        let mut lineup = fighters.clone();
+        lineup.shuffle(rng);
+        let pairs = lineup.len() / 2;
+        let pairs = battles_per_day.map_or(pairs, |count| count.min(pairs));
+
+        let mut battles = Vec::new();
+        for pair in lineup.chunks_exact(2).take(pairs) {
+            let battle = fight(storage, (&pair[0], &pair[1]), config, &created_at, day, rng)?;
+            streaks.record_battle(&battle);
+            let win_streak = battle.winner.as_ref().and_then(|winner| match streaks.fighter(winner) {
+                Some(Streak::Wins(n)) => Some((winner.clone(), n)),
+                _ => None,
+            });
+            if let Some(streak) = win_streak.filter(|(_, n)| longest_win_streak.as_ref().is_none_or(|(_, longest)| n > longest)) {
+                longest_win_streak = Some(streak);
+            }
+            for name in [&battle.fighter1_name, &battle.fighter2_name] {
+                let record = records.entry(name.clone()).or_default();
+                match &battle.winner {
+                    Some(winner) if winner == name => record[0] += 1,
+                    Some(_) => record[1] += 1,
+                    None => record[2] += 1,
+                }
+            }
+            battles.push(SimulatedBattle {
+                battle_id: battle.id,
+                fighter1: battle.fighter1_name,
+                fighter2: battle.fighter2_name,
+                winner: battle.winner,
+            });
+        }
+
+        let fought: Vec<String> = battles.iter().flat_map(|b| [b.fighter1.clone(), b.fighter2.clone()]).collect();
+        storage.sit_out_round(&fought);
+        simulated.push(SimulatedDay { day, battles });
+    }
+
+    let ratings = &storage.ratings().ratings;
+    let mut standings: Vec<Standing> = fighters
+        .iter()
+        .map(|name| {
+            let rating = ratings.get(name).cloned().unwrap_or_default();
+            let before = ratings_before.get(name).map_or(rating.rating, |before| before.rating);
+            let [wins, losses, draws] = records.get(name).copied().unwrap_or_default();
+            Standing {
+                fighter: name.clone(),
+                rating: rating.rating,
+                rating_change: rating.rating - before,
+                wins,
+                losses,
+                draws,
+                level: storage.level(name),
+            }
+        })
+        .collect();
+    standings.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+
+    Ok(SimulationSummary {
+        days: simulated,
+        standings,
+        longest_win_streak,
+        active_streaks: streaks
+            .active_fighter_streaks(MIN_STREAK)
+            .into_iter()
+            .map(|(name, streak)| (name.clone(), streak))
+            .collect(),
+        active_rivalries: streaks.active_rivalries(MIN_STREAK).into_iter().cloned().collect(),
+    })
+}
+
+/// Fights one battle of simulated day `day` and stores it.
+fn fight<R: Rng>(
+    storage: &mut Storage,
+    (fighter1, fighter2): (&str, &str),
+    config: &BattleConfig,
+    created_at: &str,
+    day: u32,
+    rng: &mut R,
+) -> Result<BattleRecord, Box<dyn std::error::Error>> {
+    let seed = rng.random();
+    let neopet1 = storage.battle_fighter(fighter1)?;
+    let neopet2 = storage.battle_fighter(fighter2)?;
+    let events = battle_loop_with_config(&neopet1, &neopet2, config, &mut StdRng::seed_from_u64(seed))?;
+    let winner = BattleLog::from(&events).winner().map(str::to_string);
+
+    let battle = BattleRecord {
+        hash: Some(battle_hash(&events)),
+        events,
+        winner,
+        is_completed: true,
+        tags: vec![SIMULATION_TAG.to_string()],
+        notes: vec![format!("Simulated day {}", day)],
+        seed: Some(seed),
+        ..BattleRecord::pending(storage.generate_battle_id()?, fighter1, fighter2, created_at)
+    };
+    storage.add_complete_battle(battle.clone())?;
+    Ok(battle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::StorageConfig;
+    use crate::neopets::{Behavior, Neopet};
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn fighter(name: &str) -> Neopet {
+        Neopet {
+            health: 60,
+            heal_delta: 5,
+            base_attack: 8,
+            base_defense: 4,
+            behavior: Behavior::attack_or_heal(0.8),
+            ..Neopet::test_fighter(name)
+        }
+    }
+
+    fn storage_with(dir: &Path, fighters: &[&str]) -> Storage {
+        let mut storage = Storage::open(&StorageConfig::in_dir(dir)).unwrap();
+        for name in fighters {
+            storage.add_neopet(fighter(name)).unwrap();
+        }
+        storage
+    }
+
+    fn start() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2024-03-01T12:00:00+00:00").unwrap()
+    }
+
+    #[test]
+    fn test_simulate_days_pairs_everyone_once_a_day() {
+        let temp_dir = tempdir().unwrap();
+        let mut storage = storage_with(temp_dir.path(), &["A", "B", "C", "D", "E"]);
+        let config = BattleConfig::default();
+
+        let summary = simulate_days(&mut storage, 6, None, &config, start(), &mut StdRng::seed_from_u64(3)).unwrap();
+        assert_eq!(summary.days.len(), 6);
+        assert_eq!(summary.battles(), 12);
+        for day in &summary.days {
+            let mut fought: Vec<&String> = day.battles.iter().flat_map(|b| [&b.fighter1, &b.fighter2]).collect();
+            fought.sort();
+            fought.dedup();
+            assert_eq!(fought.len(), 4, "everyone fights at most once on day {}", day.day);
+        }
+
+        // Every battle is stored on its day and counts towards the ratings
+        let stored = storage.complete_battle_records();
+        assert_eq!(stored.len(), 12);
+        assert!(stored[11].created_at.starts_with("2024-03-06"));
+        assert!(stored.iter().all(|b| b.has_tag(SIMULATION_TAG) && b.seed.is_some()));
+        let battles: usize = storage.ratings().ratings.values().map(|r| r.battles).sum();
+        assert_eq!(battles, 24);
+
+        assert_eq!(summary.standings.len(), 5);
+        assert!(summary.standings.windows(2).all(|w| w[0].rating >= w[1].rating));
+        let results: usize = summary.standings.iter().map(|s| s.wins + s.losses + s.draws).sum();
+        assert_eq!(results, 24);
+        let (_, longest) = summary.longest_win_streak.clone().unwrap();
+        assert!(summary.active_streaks.iter().all(|(_, streak)| !matches!(streak, Streak::Wins(n) if *n > longest)));
+    }
+
+    #[test]
+    fn test_simulate_days_caps_the_battles_and_replays_from_its_seed() {
+        let config = BattleConfig::default();
+        let run = || {
+            let temp_dir = tempdir().unwrap();
+            let mut storage = storage_with(temp_dir.path(), &["A", "B", "C", "D"]);
+            simulate_days(&mut storage, 3, Some(1), &config, start(), &mut StdRng::seed_from_u64(9)).unwrap()
+        };
+        // Battle IDs are new every run, everything else replays
+        let without_ids = |mut summary: SimulationSummary| {
+            summary.days.iter_mut().flat_map(|day| &mut day.battles).for_each(|battle| battle.battle_id.clear());
+            summary
+        };
+        let summary = run();
+        assert_eq!(summary.battles(), 3);
+        assert_eq!(without_ids(run()), without_ids(summary));
+
+        let temp_dir = tempdir().unwrap();
+        let mut storage = storage_with(temp_dir.path(), &["A"]);
+        assert!(simulate_days(&mut storage, 3, None, &config, start(), &mut StdRng::seed_from_u64(9)).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+// src/spells.rs
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use crate::neopets::{Neopet, Spell};
+
+/// What a spell does when cast.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SpellEffect {
+    /// Deals a fixed amount of damage to the target, ignoring defense.
+    Damage { amount: u32 },
+    /// Restores HP to the caster.
+    Heal { amount: u32 },
+    /// Damages the target and heals the caster by the same amount.
+    Drain { amount: u32 },
+    /// Deals `damage` to the target at the start of each of its next `turns` turns.
+    /// Casting it again refreshes the burn instead of adding a second one.
+    Burn { damage: u32, turns: u32 },
+    /// Like `Burn`, but every cast adds a stack, up to `battle::MAX_POISON_STACKS`.
+    Poison { damage: u32, turns: u32 },
+    /// Heals the caster by `amount` at the start of each of its next `turns` turns.
+    /// Casting it again refreshes it.
+    Regen { amount: u32, turns: u32 },
+    /// Flavor only, no mechanical effect.
+    Cosmetic,
+}
+
+impl fmt::Display for SpellEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpellEffect::Damage { amount } => write!(f, "💥 {} damage", amount),
+            SpellEffect::Heal { amount } => write!(f, "💚 +{} HP", amount),
+            SpellEffect::Drain { amount } => write!(f, "🩸 drains {} HP", amount),
+            SpellEffect::Burn { damage, turns } => write!(f, "🔥 {} damage a turn for {} turns", damage, turns),
+            SpellEffect::Poison { damage, turns } => {
+                write!(f, "☠️ {} damage a turn for {} turns, stacks", damage, turns)
+            }
+            SpellEffect::Regen { amount, turns } => write!(f, "🌿 +{} HP a turn for {} turns", amount, turns),
+            SpellEffect::Cosmetic => write!(f, "✨ cosmetic"),
+        }
+    }
+}
+
+/// A spell as defined once in the shared catalog.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct SpellEntry {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub effect: SpellEffect,
+    /// Turns after a cast before the caster can cast it again, every fighter's
+    /// turn counting. 0, the default, lets it be cast every turn.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub cooldown: u32,
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+impl SpellEntry {
+    /// The spell as fighters carry it into battle.
+    pub fn to_spell(&self) -> Spell {
+        Spell {
+            name: self.name.clone(),
+            effect: serde_json::to_value(&self.effect).expect("Spell effects always serialize"),
+            cooldown: self.cooldown,
+            inline: false,
+        }
+    }
+}
+
+impl fmt::Display for SpellEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\nEffect: {}", self.name, self.effect)?;
+        if self.cooldown > 0 {
+            write!(f, " | Cooldown: {} turns", self.cooldown)?;
+        }
+        if !self.description.is_empty() {
+            write!(f, "\n{}", self.description)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every spell fighters can reference by name.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(transparent)]
+pub struct SpellCatalog {
+    spells: Vec<SpellEntry>,
+}
+
+impl SpellCatalog {
+    /// Loads the catalog, starting an empty one if the file doesn't exist yet.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(path)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    pub fn add(&mut self, spell: SpellEntry) -> Result<(), String> {
+        if self.get(&spell.name).is_some() {
+            return Err(format!("A spell named '{}' already exists", spell.name));
+        }
+        self.spells.push(spell);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SpellEntry> {
+        self.spells.iter().find(|s| s.name == name)
+    }
+
+    pub fn list_spells(&self) -> Vec<String> {
+        self.spells.iter().map(|s| s.name.clone()).collect()
+    }
+
+    /// Fills in the effects of a fighter's spells from the catalog.
+    /// Fails on the first spell the catalog doesn't know about.
+    pub fn resolve(&self, neopet: &mut Neopet) -> Result<(), String> {
+        for spell in neopet.spells.iter_mut() {
+            let entry = self.get(&spell.name).ok_or_else(|| {
+                format!("Neopet {}: unknown spell '{}'", neopet.name, spell.name)
+            })?;
+            *spell = entry.to_spell();
+        }
+        Ok(())
+    }
+
+    /// Like `resolve`, but leaves the spells the catalog doesn't know as they were loaded,
+    /// so `Storage` can resolve every fighter it loads: fighters saved before the catalog
+    /// keep their inline effects, and a spell missing from it doesn't lock its fighter out.
+    pub fn resolve_known(&self, neopet: &mut Neopet) {
+        for spell in neopet.spells.iter_mut() {
+            if let Some(entry) = self.get(&spell.name) {
+                *spell = entry.to_spell();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neopets::{Behavior, load_neopets};
+    use tempfile::tempdir;
+
+    fn entry(name: &str, effect: SpellEffect) -> SpellEntry {
+        SpellEntry {
+            name: name.to_string(),
+            description: String::new(),
+            effect,
+            cooldown: 0,
+        }
+    }
+
+    fn neopet_with_spells(names: &[&str]) -> Neopet {
+        Neopet {
+            health: 100,
+            heal_delta: 10,
+            base_attack: 5,
+            spells: names
+                .iter()
+                .map(|name| Spell {
+                    name: name.to_string(),
+                    effect: serde_json::json!({}),
+                    cooldown: 0,
+                    inline: false,
+                })
+                .collect(),
+            behavior: Behavior {
+                spell_chances: vec![0.5 / names.len() as f64; names.len()],
+                ..Behavior::attack_or_heal(0.5)
+            },
+            ..Neopet::test_fighter("TestPet")
+        }
+    }
+
+    #[test]
+    fn test_effect_json_is_tagged() {
+        let json = serde_json::to_value(SpellEffect::Damage { amount: 12 }).unwrap();
+        assert_eq!(json, serde_json::json!({"type": "damage", "amount": 12}));
+
+        let cosmetic: SpellEffect = serde_json::from_str(r#"{"type": "cosmetic"}"#).unwrap();
+        assert_eq!(cosmetic, SpellEffect::Cosmetic);
+    }
+
+    #[test]
+    fn test_cooldown_is_optional_and_carried_into_battle() {
+        let entry: SpellEntry = serde_json::from_str(r#"{"name": "Nova", "effect": {"type": "cosmetic"}}"#).unwrap();
+        assert_eq!(entry.cooldown, 0);
+        assert!(!serde_json::to_string(&entry).unwrap().contains("cooldown"));
+
+        let entry = SpellEntry { cooldown: 3, ..entry };
+        assert_eq!(entry.to_spell().cooldown, 3);
+        assert!(entry.to_string().contains("Cooldown: 3 turns"));
+        assert_eq!(serde_json::from_str::<SpellEntry>(&serde_json::to_string(&entry).unwrap()).unwrap(), entry);
+    }
+
+    #[test]
+    fn test_add_rejects_duplicate_name() {
+        let mut catalog = SpellCatalog::default();
+        assert!(catalog.add(entry("Fire Ball", SpellEffect::Damage { amount: 10 })).is_ok());
+
+        let result = catalog.add(entry("Fire Ball", SpellEffect::Cosmetic));
+        assert!(result.unwrap_err().contains("already exists"));
+        assert_eq!(catalog.list_spells(), vec!["Fire Ball".to_string()]);
+    }
+
+    #[test]
+    fn test_load_missing_file_is_empty() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("missing_spells.json");
+        let catalog = SpellCatalog::load(path.to_str().unwrap()).unwrap();
+        assert!(catalog.list_spells().is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("spells.json");
+        let mut catalog = SpellCatalog::default();
+        catalog.add(entry("Fire Ball", SpellEffect::Damage { amount: 10 })).unwrap();
+        catalog.add(entry("Leech", SpellEffect::Drain { amount: 4 })).unwrap();
+
+        catalog.save(path.to_str().unwrap()).unwrap();
+        assert_eq!(SpellCatalog::load(path.to_str().unwrap()).unwrap(), catalog);
+    }
+
+    #[test]
+    fn test_resolve_fills_effects() {
+        let mut catalog = SpellCatalog::default();
+        catalog.add(entry("Fire Ball", SpellEffect::Damage { amount: 10 })).unwrap();
+        let mut neopet = neopet_with_spells(&["Fire Ball"]);
+
+        catalog.resolve(&mut neopet).unwrap();
+        assert_eq!(
+            neopet.spells[0].effect,
+            serde_json::json!({"type": "damage", "amount": 10})
+        );
+    }
+
+    #[test]
+    fn test_resolve_unknown_spell_fails() {
+        let catalog = SpellCatalog::default();
+        let mut neopet = neopet_with_spells(&["Forbidden Spell"]);
+
+        let error_msg = catalog.resolve(&mut neopet).unwrap_err();
+        assert!(error_msg.contains("TestPet"));
+        assert!(error_msg.contains("Forbidden Spell"));
+    }
+
+    #[test]
+    fn test_assets_catalog_covers_every_fighter_spell() {
+        let catalog = SpellCatalog::load("assets/spells.json").unwrap();
+        for mut neopet in load_neopets("assets/neopets.json") {
+            assert!(catalog.resolve(&mut neopet).is_ok());
+        }
+    }
+}
@@ -0,0 +1,253 @@
+// src/sqlite.rs
+use crate::battle::BattleEvent;
+use crate::event_schema::{upgrade, EVENT_SCHEMA_VERSION};
+use crate::neopets::{Neopet, NeopetDef};
+use crate::storage::BattleRecord;
+use crate::store::{BattleStore, StoreSnapshot};
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS fighters (
+    position   INTEGER NOT NULL,
+    name       TEXT PRIMARY KEY,
+    definition TEXT NOT NULL  -- The fighter as written to neopets.json
+);
+CREATE TABLE IF NOT EXISTS battles (
+    position   INTEGER NOT NULL,
+    id         TEXT PRIMARY KEY,
+    pending    INTEGER NOT NULL,
+    fighter1   TEXT NOT NULL,
+    fighter2   TEXT NOT NULL,
+    winner     TEXT,
+    created_at TEXT NOT NULL,
+    record     TEXT NOT NULL  -- The `BattleRecord`, without its events once complete
+);
+CREATE TABLE IF NOT EXISTS events (
+    battle_id  TEXT NOT NULL,
+    position   INTEGER NOT NULL,
+    turn       INTEGER NOT NULL,
+    version    INTEGER NOT NULL,  -- `event_schema` version the event was written with
+    event      TEXT NOT NULL,
+    PRIMARY KEY (battle_id, position)
+);
+";
+
+/// Fighters, battles and events in a SQLite database, one row each. Events are written
+/// with their schema version and upgraded as they are read, like the JSON files.
+pub struct SqliteStore {
+    connection: Connection,
+}
+
+impl SqliteStore {
+    /// Opens the database at `path`, creating it and its tables if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// A database that only lives as long as the store, for tests.
+    pub fn open_in_memory() -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self, Box<dyn std::error::Error>> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    fn load_battles(&self, pending: bool) -> Result<Vec<BattleRecord>, Box<dyn std::error::Error>> {
+        let mut statement = self.connection.prepare("SELECT record FROM battles WHERE pending = ?1 ORDER BY position")?;
+        let records = statement.query_map(params![pending], |row| row.get::<_, String>(0))?;
+        records.map(|record| Ok(serde_json::from_str(&record?)?)).collect()
+    }
+}
+
+impl BattleStore for SqliteStore {
+    fn load_fighters(&self) -> Result<Vec<Neopet>, Box<dyn std::error::Error>> {
+        let mut statement = self.connection.prepare("SELECT definition FROM fighters ORDER BY position")?;
+        let definitions = statement.query_map([], |row| row.get::<_, String>(0))?;
+        definitions
+            .map(|definition| {
+                let def: NeopetDef = serde_json::from_str(&definition?)?;
+                Ok(Neopet::try_from(def)?)
+            })
+            .collect()
+    }
+
+    fn load_complete_battles(&self) -> Result<Vec<BattleRecord>, Box<dyn std::error::Error>> {
+        self.load_battles(false)
+    }
+
+    fn load_pending_battles(&self) -> Result<Vec<BattleRecord>, Box<dyn std::error::Error>> {
+        self.load_battles(true)
+    }
+
+    fn read_events(&self, battle_id: &str, offset: usize, limit: usize) -> Result<Vec<BattleEvent>, Box<dyn std::error::Error>> {
+        let mut statement = self.connection.prepare(
+            "SELECT version, event FROM events WHERE battle_id = ?1 ORDER BY position LIMIT ?2 OFFSET ?3",
+        )?;
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let offset = i64::try_from(offset).unwrap_or(i64::MAX);
+        let rows = statement.query_map(params![battle_id, limit, offset], |row| {
+            Ok((row.get::<_, u32>(0)?, row.get::<_, String>(1)?))
+        })?;
+        rows.map(|row| {
+            let (version, event) = row?;
+            Ok(upgrade(version, serde_json::from_str(&event)?)?)
+        })
+        .collect()
+    }
+
+    fn count_events(&self, battle_id: &str) -> Result<usize, Box<dyn std::error::Error>> {
+        let count: i64 = self.connection.query_row(
+            "SELECT COUNT(*) FROM events WHERE battle_id = ?1",
+            params![battle_id],
+            |row| row.get(0),
+        )?;
+        Ok(usize::try_from(count)?)
+    }
+
+    fn save(&self, snapshot: &StoreSnapshot) -> Result<(), Box<dyn std::error::Error>> {
+        let changed = &snapshot.changed;
+        let transaction = self.connection.unchecked_transaction()?;
+
+        if changed.fighters {
+            transaction.execute("DELETE FROM fighters", [])?;
+            let mut insert = transaction.prepare("INSERT INTO fighters (position, name, definition) VALUES (?1, ?2, ?3)")?;
+            for (position, neopet) in snapshot.fighters.iter().enumerate() {
+                insert.execute(params![position as i64, neopet.name, serde_json::to_string(neopet)?])?;
+            }
+        }
+
+        // Complete and pending battles share the table, and its positions
+        if changed.complete_battles || changed.pending_battles {
+            transaction.execute("DELETE FROM battles", [])?;
+            let mut insert = transaction.prepare(
+                "INSERT INTO battles (position, id, pending, fighter1, fighter2, winner, created_at, record)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            let complete = snapshot.complete_battles.iter().map(|b| (false, b.summary()));
+            let pending = snapshot.pending_battles.iter().map(|b| (true, b.clone()));
+            for (position, (pending, battle)) in complete.chain(pending).enumerate() {
+                insert.execute(params![
+                    position as i64,
+                    battle.id,
+                    pending,
+                    battle.fighter1_name,
+                    battle.fighter2_name,
+                    battle.winner,
+                    battle.created_at,
+                    serde_json::to_string(&battle)?,
+                ])?;
+            }
+        }
+
+        let mut insert = transaction.prepare(
+            "INSERT INTO events (battle_id, position, turn, version, event) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        let written = snapshot.complete_battles.iter()
+            .filter(|b| !b.events.is_empty() && changed.events.contains(b.id.as_str()));
+        for battle in written {
+            transaction.execute("DELETE FROM events WHERE battle_id = ?1", params![battle.id])?;
+            for (position, event) in battle.events.iter().enumerate() {
+                insert.execute(params![
+                    battle.id,
+                    position as i64,
+                    event.turn(),
+                    EVENT_SCHEMA_VERSION,
+                    serde_json::to_string(event)?,
+                ])?;
+            }
+        }
+        drop(insert);
+
+        if changed.removed_events {
+            let stored: Vec<String> = transaction
+                .prepare("SELECT DISTINCT battle_id FROM events")?
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<_, _>>()?;
+            for id in stored.iter().filter(|id| !snapshot.kept_events.contains(id.as_str())) {
+                transaction.execute("DELETE FROM events WHERE battle_id = ?1", params![id])?;
+            }
+        }
+
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Storage;
+    use crate::store::{StorageConfig, StoreChanges};
+    use tempfile::tempdir;
+
+    fn neopet(name: &str) -> Neopet {
+        Neopet { health: 100, heal_delta: 10, base_attack: 8, base_defense: 4, ..Neopet::test_fighter(name) }
+    }
+
+    fn battle(id: &str, is_completed: bool) -> BattleRecord {
+        let events = if is_completed {
+            (1..=4).map(|turn| BattleEvent::Heal { turn, actor: "Acara".into(), amount: turn }).collect()
+        } else {
+            vec![]
+        };
+        BattleRecord {
+            created_at: "2024-01-01T00:00:00+00:00".to_string(),
+            events,
+            winner: is_completed.then(|| "Acara".to_string()),
+            is_completed,
+            tags: vec!["grudge-match".to_string()],
+            seed: Some(7),
+            ..BattleRecord::test_battle(id, "Acara", "Usul")
+        }
+    }
+
+    #[test]
+    fn test_store_round_trips_fighters_battles_and_events() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let fighters = [neopet("Usul"), neopet("Acara")];
+        let complete = [battle("battle_1", true), battle("battle_2", true)];
+        let pending = [battle("battle_3", false)];
+        let snapshot = StoreSnapshot {
+            fighters: &fighters,
+            complete_battles: &complete,
+            pending_battles: &pending,
+            kept_events: ["battle_1", "battle_2"].into_iter().collect(),
+            changed: StoreChanges::everything(&complete),
+        };
+        store.save(&snapshot).unwrap();
+
+        assert_eq!(store.load_fighters().unwrap(), fighters);
+        let loaded = store.load_complete_battles().unwrap();
+        assert_eq!(loaded.iter().map(|b| b.id.as_str()).collect::<Vec<_>>(), ["battle_1", "battle_2"]);
+        assert!(loaded.iter().all(|b| b.events.is_empty() && b.has_tag("grudge-match")));
+        assert_eq!(store.load_pending_battles().unwrap()[0].id, "battle_3");
+        assert_eq!(store.count_events("battle_1").unwrap(), 4);
+        assert_eq!(store.read_events("battle_1", 2, 10).unwrap(), complete[0].events[2..]);
+
+        // Saving summaries leaves the stored events alone, unless the battle is gone
+        let summaries = [complete[0].summary()];
+        store.save(&StoreSnapshot { complete_battles: &summaries, kept_events: ["battle_1"].into_iter().collect(), ..snapshot }).unwrap();
+        assert_eq!(store.count_events("battle_1").unwrap(), 4);
+        assert_eq!(store.count_events("battle_2").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_storage_runs_on_sqlite() {
+        let temp_dir = tempdir().unwrap();
+        let database = temp_dir.path().join("rinha.db");
+        let mut storage = Storage::with_store(Box::new(SqliteStore::open(&database).unwrap()), &StorageConfig::in_dir(temp_dir.path())).unwrap();
+        storage.add_neopet(neopet("Acara")).unwrap();
+        storage.add_complete_battle(battle("battle_1", true)).unwrap();
+        storage.save().unwrap();
+
+        let reopened = Storage::with_store(Box::new(SqliteStore::open(&database).unwrap()), &StorageConfig::in_dir(temp_dir.path())).unwrap();
+        assert_eq!(reopened.list_fighters(), ["Acara"]);
+        assert!(reopened.complete_battle_records()[0].events.is_empty());
+        assert_eq!(reopened.load_battle("battle_1").unwrap().unwrap().events, battle("battle_1", true).events);
+        assert_eq!(reopened.ratings().ratings["Acara"].battles, 1);
+        assert!(!temp_dir.path().join("neopets.json").exists());
+    }
+}
@@ -0,0 +1,325 @@
+// src/stats.rs
+use std::collections::BTreeMap;
+use crate::battle::BattleEvent;
+use crate::observer::Observer;
+use crate::storage::BattleRecord;
+
+/// How one fighter did over every completed battle they fought.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FighterStats {
+    pub battles: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+    pub damage_dealt: u32, // HP opponents lost, to attacks, spells and effects alike
+    pub damage_taken: u32,
+    pub rolls: usize,      // Attack, defense and heal rolls, initiative doesn't count
+    pub crits: usize,
+    pub killing_blows: usize, // Attacks that knocked the opponent out
+    pub exact_kills: usize,   // Killing blows that left no damage to spare
+    pub max_overkill: u32,
+    pub actions: BTreeMap<String, usize>, // "attack", "heal", "defend", "taunt" or the spell cast
+    pub longest_battle: Option<(String, u32)>, // Battle ID and turns played
+}
+
+impl FighterStats {
+    pub fn win_rate(&self) -> f64 {
+        ratio(self.wins, self.battles)
+    }
+
+    pub fn avg_damage_dealt(&self) -> f64 {
+        ratio(self.damage_dealt as usize, self.battles)
+    }
+
+    pub fn avg_damage_taken(&self) -> f64 {
+        ratio(self.damage_taken as usize, self.battles)
+    }
+
+    pub fn crit_rate(&self) -> f64 {
+        ratio(self.crits, self.rolls)
+    }
+
+    /// The action taken most often and how many times, the first by name on a tie.
+    pub fn favorite_action(&self) -> Option<(&str, usize)> {
+        self.actions
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+            .map(|(action, count)| (action.as_str(), *count))
+    }
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// `FighterStats` of every fighter, gathered from completed battles.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BattleStats {
+    fighters: BTreeMap<String, FighterStats>,
+}
+
+impl BattleStats {
+    /// Stats over every completed battle in `battles`, pending ones are skipped.
+    pub fn from_battles(battles: &[BattleRecord]) -> Self {
+        let mut stats = Self::default();
+        for battle in battles {
+            stats.record_battle(battle);
+        }
+        stats
+    }
+
+    /// Adds one more battle to the stats of both fighters.
+    pub fn record_battle(&mut self, battle: &BattleRecord) {
+        if !battle.is_completed {
+            return;
+        }
+        let mut observer = StatsObserver::new(self, &battle.id, &battle.fighter1_name, &battle.fighter2_name);
+        for event in &battle.events {
+            observer.on_event(event);
+        }
+        observer.winner = battle.winner.clone();
+        observer.on_battle_end();
+    }
+
+    pub fn fighter(&self, name: &str) -> Option<&FighterStats> {
+        self.fighters.get(name)
+    }
+
+    /// Every fighter with at least one completed battle, by name.
+    pub fn fighters(&self) -> impl Iterator<Item = (&String, &FighterStats)> {
+        self.fighters.iter()
+    }
+}
+
+/// Adds a battle to `BattleStats` as it is fought, an `Observer` for
+/// `battle::battle_loop_with_observers`. Damage, rolls and actions count as they
+/// happen, the battle itself once it ends.
+pub struct StatsObserver<'a> {
+    stats: &'a mut BattleStats,
+    battle_id: String,
+    fighters: [String; 2],
+    winner: Option<String>,
+    turns: u32,
+}
+
+impl<'a> StatsObserver<'a> {
+    pub fn new(stats: &'a mut BattleStats, battle_id: &str, fighter1: &str, fighter2: &str) -> Self {
+        Self {
+            stats,
+            battle_id: battle_id.to_string(),
+            fighters: [fighter1.to_string(), fighter2.to_string()],
+            winner: None,
+            turns: 0,
+        }
+    }
+}
+
+impl Observer for StatsObserver<'_> {
+    fn on_event(&mut self, event: &BattleEvent) {
+        self.turns = self.turns.max(event.turn());
+        if let BattleEvent::BattleComplete { winner, .. } = event {
+            self.winner = Some(winner.to_string());
+        }
+        let [fighter1, fighter2] = &self.fighters;
+        for (fighter, opponent) in [(fighter1, fighter2), (fighter2, fighter1)] {
+            let stats = self.stats.fighters.entry(fighter.clone()).or_default();
+            let (is_fighter, is_opponent) = (|name: &str| name == fighter, |name: &str| name == opponent);
+            match event {
+                BattleEvent::HealthUpdate { fighter_name, from, to, .. } if to < from => {
+                    if is_fighter(fighter_name) {
+                        stats.damage_taken += from - to;
+                    } else if is_opponent(fighter_name) {
+                        stats.damage_dealt += from - to;
+                    }
+                }
+                BattleEvent::Roll { actor, is_positive_crit, goal, .. } if is_fighter(actor) && goal != "initiative" => {
+                    stats.rolls += 1;
+                    stats.crits += usize::from(*is_positive_crit);
+                }
+                BattleEvent::Attack { actor, is_killing_blow, overkill_amount, .. } if is_fighter(actor) => {
+                    *stats.actions.entry("attack".to_string()).or_default() += 1;
+                    if *is_killing_blow {
+                        stats.killing_blows += 1;
+                        stats.exact_kills += usize::from(*overkill_amount == 0);
+                        stats.max_overkill = stats.max_overkill.max(*overkill_amount);
+                    }
+                }
+                BattleEvent::Heal { actor, .. } if is_fighter(actor) => {
+                    *stats.actions.entry("heal".to_string()).or_default() += 1;
+                }
+                BattleEvent::Defend { actor, .. } if is_fighter(actor) => {
+                    *stats.actions.entry("defend".to_string()).or_default() += 1;
+                }
+                BattleEvent::Taunt { actor, .. } if is_fighter(actor) => {
+                    *stats.actions.entry("taunt".to_string()).or_default() += 1;
+                }
+                BattleEvent::SpellCast { actor, spell_name, .. } if is_fighter(actor) => {
+                    *stats.actions.entry(spell_name.clone()).or_default() += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn on_battle_end(&mut self) {
+        for fighter in &self.fighters {
+            let stats = self.stats.fighters.entry(fighter.clone()).or_default();
+            stats.battles += 1;
+            match &self.winner {
+                Some(winner) if winner == fighter => stats.wins += 1,
+                Some(_) => stats.losses += 1,
+                None => stats.draws += 1,
+            }
+            if stats.longest_battle.as_ref().is_none_or(|(_, longest)| self.turns > *longest) {
+                stats.longest_battle = Some((self.battle_id.clone(), self.turns));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::BattleCompletionReason;
+
+    fn roll(turn: u32, actor: &str, goal: &str, is_positive_crit: bool) -> BattleEvent {
+        BattleEvent::Roll {
+            turn,
+            actor: actor.into(),
+            dice: 10,
+            dice_expr: None,
+            final_value: 10,
+            is_positive_crit,
+            is_negative_crit: false,
+            goal: goal.to_string(),
+        }
+    }
+
+    fn attack(turn: u32, actor: &str, target: &str, damage: u32, hp: u32) -> Vec<BattleEvent> {
+        vec![
+            roll(turn, actor, "attack", damage > 5),
+            BattleEvent::Attack {
+                turn,
+                actor: actor.into(),
+                target: target.into(),
+                raw_damage: damage,
+                shield_value: 0,
+                actual_damage: damage,
+                is_killing_blow: damage == hp,
+                overkill_amount: 0,
+            },
+            BattleEvent::HealthUpdate { fighter_name: target.into(), from: hp, to: hp - damage, turn, source: None },
+        ]
+    }
+
+    fn battle(id: &str, winner: Option<&str>, events: Vec<BattleEvent>) -> BattleRecord {
+        BattleRecord {
+            events,
+            winner: winner.map(|w| w.to_string()),
+            is_completed: true,
+            ..BattleRecord::test_battle(id, "Acara", "Usul")
+        }
+    }
+
+    #[test]
+    fn test_fighter_aggregates() {
+        let mut first = vec![roll(0, "Acara", "initiative", true)];
+        first.extend(attack(1, "Acara", "Usul", 8, 50));
+        first.extend(attack(2, "Usul", "Acara", 4, 50));
+        first.push(BattleEvent::SpellCast {
+            turn: 3,
+            actor: "Acara".into(),
+            target: "Usul".into(),
+            spell_name: "Fireball".to_string(),
+        });
+        first.push(BattleEvent::HealthUpdate { fighter_name: "Usul".into(), from: 42, to: 40, turn: 3, source: None });
+        let mut second = attack(1, "Acara", "Usul", 2, 50);
+        second.push(BattleEvent::Heal { turn: 2, actor: "Usul".into(), amount: 2 });
+        second.push(BattleEvent::HealthUpdate { fighter_name: "Usul".into(), from: 48, to: 50, turn: 2, source: None });
+
+        let mut pending = battle("battle_3", None, attack(1, "Usul", "Acara", 9, 50));
+        pending.is_completed = false;
+        let stats = BattleStats::from_battles(&[
+            battle("battle_1", Some("Acara"), first),
+            battle("battle_2", None, second),
+            pending,
+        ]);
+
+        let acara = stats.fighter("Acara").unwrap();
+        assert_eq!((acara.battles, acara.wins, acara.losses, acara.draws), (2, 1, 0, 1));
+        assert_eq!(acara.win_rate(), 0.5);
+        assert_eq!((acara.damage_dealt, acara.damage_taken), (12, 4));
+        assert_eq!((acara.avg_damage_dealt(), acara.avg_damage_taken()), (6.0, 2.0));
+        // The initiative crit doesn't count
+        assert_eq!((acara.rolls, acara.crits, acara.crit_rate()), (2, 1, 0.5));
+        assert_eq!(acara.favorite_action(), Some(("attack", 2)));
+        assert_eq!(acara.longest_battle, Some(("battle_1".to_string(), 3)));
+
+        let usul = stats.fighter("Usul").unwrap();
+        assert_eq!((usul.wins, usul.losses, usul.damage_dealt, usul.damage_taken), (0, 1, 4, 12));
+        // Tied with the attack, and first by name
+        assert_eq!(usul.favorite_action(), Some(("attack", 1)));
+        assert_eq!(stats.fighters().count(), 2);
+    }
+
+    #[test]
+    fn test_killing_blows() {
+        let mut first = attack(1, "Acara", "Usul", 8, 50);
+        first.extend(attack(2, "Acara", "Usul", 42, 42));
+        let mut second = attack(1, "Usul", "Acara", 30, 50);
+        second.push(BattleEvent::Attack {
+            turn: 2,
+            actor: "Acara".into(),
+            target: "Usul".into(),
+            raw_damage: 19,
+            shield_value: 7,
+            actual_damage: 12,
+            is_killing_blow: true,
+            overkill_amount: 7,
+        });
+        let stats = BattleStats::from_battles(&[battle("battle_1", Some("Acara"), first), battle("battle_2", Some("Acara"), second)]);
+
+        let acara = stats.fighter("Acara").unwrap();
+        assert_eq!((acara.killing_blows, acara.exact_kills, acara.max_overkill), (2, 1, 7));
+        let usul = stats.fighter("Usul").unwrap();
+        assert_eq!((usul.killing_blows, usul.exact_kills, usul.max_overkill), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_observer_matches_recorded_battle() {
+        let mut events = attack(1, "Acara", "Usul", 8, 50);
+        events.extend(attack(2, "Usul", "Acara", 4, 50));
+        events.push(BattleEvent::BattleComplete {
+            turn: 2,
+            winner: "Acara".into(),
+            loser: "Usul".into(),
+            winner_final_hp: 46,
+            loser_final_hp: 42,
+            completion_reason: BattleCompletionReason::MaxTurnsReached(2),
+        });
+        let recorded = BattleStats::from_battles(&[battle("battle_1", Some("Acara"), events.clone())]);
+
+        let mut observed = BattleStats::default();
+        let mut observer = StatsObserver::new(&mut observed, "battle_1", "Acara", "Usul");
+        for event in &events {
+            observer.on_event(event);
+        }
+        observer.on_battle_end();
+        assert_eq!(observed, recorded);
+        assert_eq!(observed.fighter("Acara").unwrap().wins, 1);
+    }
+
+    #[test]
+    fn test_fighters_without_battles() {
+        let stats = BattleStats::from_battles(&[]);
+        assert!(stats.fighter("Acara").is_none());
+
+        let idle = FighterStats::default();
+        assert_eq!((idle.win_rate(), idle.avg_damage_dealt(), idle.crit_rate()), (0.0, 0.0, 0.0));
+        assert_eq!(idle.favorite_action(), None);
+    }
+}
@@ -1,93 +1,747 @@
 // src/storage.rs
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::fs::File;
 use std::io::{BufReader, BufWriter};
 use std::path::Path;
-use crate::neopets::{Neopet, load_neopets};
-use crate::battle::BattleEvent;
+use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, Duration, FixedOffset};
+use crate::neopets::{Neopet, NeopetDef};
+use crate::battle::{BattleConfig, BattleEvent};
+use crate::battle_log::BattleLog;
+use crate::handicap::Handicap;
+use crate::loot::Item;
+use crate::items::{ItemCatalog, Loadout, Slot};
+use crate::ids::{IdGenerator, IdKind};
+use crate::leveling::{LevelingRules, Progress};
+use crate::integrity::battle_hash;
+use crate::ratings::{RatingRules, RatingsSnapshot};
+use crate::runner::{BattleCheckpoint, CheckpointRng};
+use crate::season::Season;
+use crate::series::SeriesRecord;
+use crate::spells::SpellCatalog;
+use crate::store::{BattleStore, MemoryStore, StorageConfig, StoreChanges, StoreSnapshot};
+
+/// What `Storage::import_neopet` does with a fighter named like one it already has.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImportConflict {
+    /// Keep the existing fighter and fail the import.
+    #[default]
+    Refuse,
+    /// Add the imported fighter as "Name 2" (or 3, ...), the first name not taken.
+    Rename,
+    /// Replace the existing fighter's definition, keeping their battles, items and rating.
+    Overwrite,
+}
 
 /// Serializable battle record
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BattleRecord {
-    pub id: String,                    // Unique ID (timestamp)
+    pub id: String,                    // Unique ID (see `IdGenerator`)
     pub fighter1_name: String,
     pub fighter2_name: String,
     pub created_at: String,            // ISO 8601 timestamp
-    pub events: Vec<BattleEvent>,      // Full battle history
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "crate::event_schema::versioned")]
+    pub events: Vec<BattleEvent>,      // Full battle history, left on disk for stored complete battles
     pub winner: Option<String>,        // None if battle hasn't been run
     pub is_completed: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,             // e.g. "grudge-match"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,            // Free-form, oldest first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,             // `StdRng` seed the battle ran with, replays it exactly
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,          // `integrity::battle_hash` of the events once completed
+    #[serde(default, skip_serializing_if = "Handicap::is_empty")]
+    pub handicap: Handicap,            // Stat changes applied to each side when the battle starts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arena: Option<String>,         // Name of the `arena::Arena` the battle is fought in
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,                // Left out of listings and stats, see `Storage::archive_battle`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled_at: Option<String>,  // RFC 3339 time a pending battle is due, see `Storage::schedule_battle`
+}
+
+impl BattleRecord {
+    /// A battle between two fighters waiting to be run, with no tags, notes, handicap or
+    /// arena. Battles recorded once fought set what they carry on top,
+    /// `BattleRecord { events, winner, is_completed: true, ..BattleRecord::pending(..) }`.
+    pub fn pending(id: String, fighter1: &str, fighter2: &str, created_at: &str) -> Self {
+        BattleRecord {
+            id,
+            fighter1_name: fighter1.to_string(),
+            fighter2_name: fighter2.to_string(),
+            created_at: created_at.to_string(),
+            events: Vec::new(), // Empty until battle is run
+            winner: None,
+            is_completed: false,
+            tags: Vec::new(),
+            notes: Vec::new(),
+            seed: None,
+            hash: None,
+            handicap: Handicap::default(),
+            arena: None,
+            archived: false,
+            scheduled_at: None,
+        }
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Adds tags not already present and appends the notes, ignoring blank ones.
+    pub fn annotate(&mut self, tags: &[String], notes: &[String]) {
+        for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            if !self.has_tag(tag) {
+                self.tags.push(tag.to_string());
+            }
+        }
+        self.notes.extend(notes.iter().map(|n| n.trim()).filter(|n| !n.is_empty()).map(String::from));
+    }
+
+    pub fn involves(&self, fighter: &str) -> bool {
+        self.fighter1_name == fighter || self.fighter2_name == fighter
+    }
+
+    /// Replaces `from` with `to` in the fighters, the winner and the events, hashing
+    /// the events again if they were hashed. Stored events have to be loaded first.
+    pub fn rename_fighter(&mut self, from: &str, to: &str) {
+        for name in [&mut self.fighter1_name, &mut self.fighter2_name].into_iter().chain(self.winner.as_mut()) {
+            if name == from {
+                *name = to.to_string();
+            }
+        }
+        for event in &mut self.events {
+            event.rename_fighter(from, to);
+        }
+        if self.hash.is_some() {
+            self.hash = Some(battle_hash(&self.events));
+        }
+    }
+
+    /// The record without its events.
+    pub fn summary(&self) -> BattleRecord {
+        BattleRecord {
+            id: self.id.clone(),
+            fighter1_name: self.fighter1_name.clone(),
+            fighter2_name: self.fighter2_name.clone(),
+            created_at: self.created_at.clone(),
+            events: Vec::new(),
+            winner: self.winner.clone(),
+            is_completed: self.is_completed,
+            tags: self.tags.clone(),
+            notes: self.notes.clone(),
+            seed: self.seed,
+            hash: self.hash.clone(),
+            handicap: self.handicap.clone(),
+            arena: self.arena.clone(),
+            archived: self.archived,
+            scheduled_at: self.scheduled_at.clone(),
+        }
+    }
+
+    /// How long until the battle is due, `None` once it is or if it isn't scheduled. An
+    /// unreadable `scheduled_at` counts as due, rather than keeping the battle waiting forever.
+    pub fn due_in(&self, now: DateTime<FixedOffset>) -> Option<Duration> {
+        let scheduled_at = DateTime::parse_from_rfc3339(self.scheduled_at.as_deref()?).ok()?;
+        (scheduled_at > now).then(|| scheduled_at - now)
+    }
+
+    /// Whether the battle may start at `now`: it isn't scheduled or its time has come.
+    pub fn is_due(&self, now: DateTime<FixedOffset>) -> bool {
+        self.due_in(now).is_none()
+    }
+}
+
+#[cfg(test)]
+impl BattleRecord {
+    /// A pending battle for tests, created at the start of 2023. Tests set what they need
+    /// on top, `BattleRecord { winner, is_completed: true, ..BattleRecord::test_battle(id, f1, f2) }`.
+    pub(crate) fn test_battle(id: &str, fighter1: &str, fighter2: &str) -> Self {
+        BattleRecord::pending(id.to_string(), fighter1, fighter2, "2023-01-01T00:00:00Z")
+    }
+}
+
+/// Which battles `Storage::find_battles` returns, built up from `BattleFilter::default()`
+/// (every battle) one condition at a time:
+/// `BattleFilter::default().fighter("Acara").winner("Acara").completed(true)`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BattleFilter {
+    fighter: Option<String>,
+    winner: Option<String>,
+    since: Option<DateTime<FixedOffset>>,
+    until: Option<DateTime<FixedOffset>>,
+    completed: Option<bool>,
+    tag: Option<String>,
+    archived: Option<bool>,
+}
+
+impl BattleFilter {
+    /// Battles `name` fought in, on either side.
+    pub fn fighter(mut self, name: &str) -> Self {
+        self.fighter = Some(name.to_string());
+        self
+    }
+
+    /// Completed battles `name` won.
+    pub fn winner(mut self, name: &str) -> Self {
+        self.winner = Some(name.to_string());
+        self
+    }
+
+    /// Battles created at `at` or later.
+    pub fn since(mut self, at: DateTime<FixedOffset>) -> Self {
+        self.since = Some(at);
+        self
+    }
+
+    /// Battles created before `at`.
+    pub fn until(mut self, at: DateTime<FixedOffset>) -> Self {
+        self.until = Some(at);
+        self
+    }
+
+    /// Only completed battles, or only pending ones.
+    pub fn completed(mut self, completed: bool) -> Self {
+        self.completed = Some(completed);
+        self
+    }
+
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tag = Some(tag.to_string());
+        self
+    }
+
+    /// Only archived battles, or only those that aren't.
+    pub fn archived(mut self, archived: bool) -> Self {
+        self.archived = Some(archived);
+        self
+    }
+
+    /// Whether `battle` meets every condition. Battles whose creation time doesn't
+    /// parse never match a date range.
+    pub fn matches(&self, battle: &BattleRecord) -> bool {
+        let created_at = || DateTime::parse_from_rfc3339(&battle.created_at).ok();
+        self.fighter.as_deref().is_none_or(|name| battle.involves(name))
+            && self.winner.as_ref().is_none_or(|name| battle.winner.as_ref() == Some(name))
+            && self.since.is_none_or(|since| created_at().is_some_and(|at| at >= since))
+            && self.until.is_none_or(|until| created_at().is_some_and(|at| at < until))
+            && self.completed.is_none_or(|completed| battle.is_completed == completed)
+            && self.tag.as_deref().is_none_or(|tag| battle.has_tag(tag))
+            && self.archived.is_none_or(|archived| battle.archived == archived)
+    }
+}
+
+/// A pending battle paused at a turn boundary, to be resumed later with
+/// `BattleRunner::resume`. The fighters and config are kept as the battle started
+/// with them, so changes made in the meantime don't alter how it ends.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CheckpointRecord {
+    pub saved_at: String,              // RFC 3339 timestamp
+    pub seed: u64,                     // Recorded with the battle once it completes
+    pub fighter1: Neopet,
+    pub fighter2: Neopet,
+    pub config: BattleConfig,
+    #[serde(with = "crate::event_schema::versioned")]
+    pub events: Vec<BattleEvent>,      // Yielded before the checkpoint
+    pub checkpoint: BattleCheckpoint<CheckpointRng>,
+}
+
+/// How long deleted fighters and battles stay restorable before `purge` removes them.
+pub const TRASH_RETENTION_DAYS: i64 = 30;
+
+/// Something deleted by the user, kept around until the trash is purged.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TrashedItem {
+    Fighter { neopet: Neopet },
+    CompleteBattle { battle: BattleRecord },
+    PendingBattle { battle: BattleRecord },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrashEntry {
+    pub deleted_at: String,            // RFC 3339 timestamp
+    pub item: TrashedItem,
+}
+
+impl TrashEntry {
+    /// What the user refers to the entry by: the fighter name or the battle ID.
+    pub fn key(&self) -> &str {
+        match &self.item {
+            TrashedItem::Fighter { neopet } => &neopet.name,
+            TrashedItem::CompleteBattle { battle } | TrashedItem::PendingBattle { battle } => &battle.id,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self.item {
+            TrashedItem::Fighter { .. } => "fighter",
+            TrashedItem::CompleteBattle { .. } => "complete battle",
+            TrashedItem::PendingBattle { .. } => "pending battle",
+        }
+    }
+
+    /// Whether the entry outlived `retention`. Entries with an unreadable timestamp are kept.
+    pub fn is_expired(&self, now: DateTime<FixedOffset>, retention: Duration) -> bool {
+        DateTime::parse_from_rfc3339(&self.deleted_at)
+            .map(|deleted_at| now - deleted_at >= retention)
+            .unwrap_or(false)
+    }
+}
+
+/// The JSON files `Storage` keeps everything but the fighters and battles in.
+struct StorageFiles {
+    inventories: String,
+    equipment: String,
+    season: String,
+    injuries: String,
+    series: String,
+    trash: String,
+    ratings: String,
+    checkpoints: String,
+    experience: String,
+}
+
+/// What changed since `Storage` was opened or last saved, for `save` to write only that:
+/// each file, and the events of each complete battle.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Changes {
+    fighters: bool,
+    complete_battles: bool,
+    pending_battles: bool,
+    events: HashSet<String>, // Complete battles whose events in memory aren't stored yet
+    inventories: bool,
+    equipment: bool,
+    season: bool,
+    injuries: bool,
+    series: bool,
+    trash: bool,
+    ratings: bool,
+    checkpoints: bool,
+    experience: bool,
+}
+
+impl Changes {
+    /// Every file, and the events of every battle holding them in memory.
+    fn everything(complete_battles: &[BattleRecord]) -> Self {
+        Self {
+            fighters: true,
+            complete_battles: true,
+            pending_battles: true,
+            events: complete_battles.iter().filter(|b| !b.events.is_empty()).map(|b| b.id.clone()).collect(),
+            inventories: true,
+            equipment: true,
+            season: true,
+            injuries: true,
+            series: true,
+            trash: true,
+            ratings: true,
+            checkpoints: true,
+            experience: true,
+        }
+    }
 }
 
 pub struct Storage {
-    neopets_path: String,
-    complete_battles_path: String,
-    pending_battles_path: String,
+    store: Box<dyn BattleStore>,             // Fighters, battles and their events
+    files: Option<StorageFiles>,             // None when kept in memory only
+    changes: RefCell<Changes>,               // Cleared by `save`, which only takes `&self`
     neopets: Vec<Neopet>,
     complete_battles: Vec<BattleRecord>,
     pending_battles: Vec<BattleRecord>,
+    inventories: HashMap<String, Vec<Item>>, // Items owned by each fighter
+    items: ItemCatalog,
+    spells: SpellCatalog,                    // Fighters store spell names, their effects come from here
+    equipment: HashMap<String, Loadout>,      // Items each fighter has equipped
+    season: Season,
+    injuries: HashMap<String, u32>,          // HP each fighter is still missing, hardcore seasons only
+    series: Vec<SeriesRecord>,
+    trash: Vec<TrashEntry>,
+    ratings: RatingsSnapshot,
+    checkpoints: HashMap<String, CheckpointRecord>, // By pending battle ID
+    experience: HashMap<String, u32>,        // Experience points each fighter earned in battle
+    leveling: LevelingRules,
+    ids: IdGenerator,
 }
 
 impl Storage {
+    /// Storage kept in JSON files, `complete_battles_path` aside all next to `neopets_path`.
     pub fn new(neopets_path: &str, complete_battles_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let neopets = if Path::new(neopets_path).exists() {
-            load_neopets(neopets_path)
+        let dir = Path::new(neopets_path).parent().unwrap_or(Path::new(""));
+        Self::open(&StorageConfig {
+            neopets: neopets_path.into(),
+            complete_battles: complete_battles_path.into(),
+            ..StorageConfig::in_dir(dir)
+        })
+    }
+
+    /// Storage in the files and backend `config` names.
+    pub fn open(config: &StorageConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_store(config.open()?, config)
+    }
+
+    /// Storage keeping the fighters and battles in `store` and everything else in the
+    /// JSON files `config` names.
+    pub fn with_store(store: Box<dyn BattleStore>, config: &StorageConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut neopets = store.load_fighters()?;
+
+        // Complete battles are kept as summaries, their events staying in the store until
+        // a battle is loaded. Battles saved with their events inline (and events from
+        // before they carried a schema version, see `event_schema`) load as they are and
+        // have the store keep their events separately on `save`
+        let complete_battles = store.load_complete_battles()?;
+        let pending_battles = store.load_pending_battles()?;
+
+        // Inventories live next to the fighters they belong to, unless configured otherwise
+        let inventories_path = config.path(&config.inventories)
+            .to_string_lossy()
+            .to_string();
+        let inventories = if Path::new(&inventories_path).exists() {
+            let file = File::open(&inventories_path)?;
+            let reader = BufReader::new(file);
+            serde_json::from_reader(reader)?
         } else {
-            Vec::new()
+            HashMap::new()
         };
-        
-        let complete_battles = if Path::new(complete_battles_path).exists() {
-            let file = File::open(complete_battles_path)?;
+
+        // So do the equippable items and what each fighter wears
+        let items_path = config.path(&config.items);
+        let items = ItemCatalog::load(&items_path.to_string_lossy())?;
+        let spells = SpellCatalog::load(&config.path(&config.spells).to_string_lossy())?;
+        for neopet in &mut neopets {
+            spells.resolve_known(neopet);
+        }
+        let equipment_path = config.path(&config.equipment)
+            .to_string_lossy()
+            .to_string();
+        let equipment = if Path::new(&equipment_path).exists() {
+            let file = File::open(&equipment_path)?;
+            let reader = BufReader::new(file);
+            serde_json::from_reader(reader)?
+        } else {
+            HashMap::new()
+        };
+
+        // The season decides whether injuries carry over between battles
+        let season_path = config.path(&config.season)
+            .to_string_lossy()
+            .to_string();
+        let season = Season::load(&season_path)?;
+        let injuries_path = config.path(&config.injuries)
+            .to_string_lossy()
+            .to_string();
+        let injuries = if Path::new(&injuries_path).exists() {
+            let file = File::open(&injuries_path)?;
+            let reader = BufReader::new(file);
+            serde_json::from_reader(reader)?
+        } else {
+            HashMap::new()
+        };
+
+        let series_path = config.path(&config.series)
+            .to_string_lossy()
+            .to_string();
+        let series = if Path::new(&series_path).exists() {
+            let file = File::open(&series_path)?;
             let reader = BufReader::new(file);
             serde_json::from_reader(reader)?
         } else {
             Vec::new()
         };
 
-        let pending_battles_path = "assets/pending_battles.json";
-        let pending_battles = if Path::new(pending_battles_path).exists() {
-            let file = File::open(pending_battles_path)?;
+        let trash_path = config.path(&config.trash)
+            .to_string_lossy()
+            .to_string();
+        let trash = if Path::new(&trash_path).exists() {
+            let file = File::open(&trash_path)?;
             let reader = BufReader::new(file);
             serde_json::from_reader(reader)?
         } else {
             Vec::new()
         };
 
+        // Ratings follow every completed battle; rebuild them from history the first time
+        let ratings_path = config.path(&config.ratings)
+            .to_string_lossy()
+            .to_string();
+        let mut changes = Changes::default();
+        let ratings = match RatingsSnapshot::load(&ratings_path)? {
+            Some(ratings) => ratings,
+            None => {
+                changes.ratings = true;
+                let rules_path = config.path(&config.rating_rules);
+                let rules = RatingRules::load(&rules_path.to_string_lossy())?;
+                let fighters: Vec<String> = neopets.iter().map(|n| n.name.clone()).collect();
+                RatingsSnapshot::recompute(&fighters, &complete_battles, &rules, chrono::Utc::now().fixed_offset())
+            }
+        };
+
+        let checkpoints_path = config.path(&config.checkpoints)
+            .to_string_lossy()
+            .to_string();
+        let checkpoints = if Path::new(&checkpoints_path).exists() {
+            let file = File::open(&checkpoints_path)?;
+            let reader = BufReader::new(file);
+            serde_json::from_reader(reader)?
+        } else {
+            HashMap::new()
+        };
+
+        // Fighters grow from the battles completed since leveling was introduced
+        let experience_path = config.path(&config.experience)
+            .to_string_lossy()
+            .to_string();
+        let experience = if Path::new(&experience_path).exists() {
+            let file = File::open(&experience_path)?;
+            let reader = BufReader::new(file);
+            serde_json::from_reader(reader)?
+        } else {
+            HashMap::new()
+        };
+        let leveling_path = config.path(&config.leveling_rules);
+        let leveling = LevelingRules::load(&leveling_path.to_string_lossy())?;
+
+        // Shared with the cassino, which keeps its files in the same directory
+        let ids = IdGenerator::new(config.path(&config.ids));
+
+        // Events saved inline by an older build move to the store on the next save
+        let inline = Changes::everything(&complete_battles).events;
+        if !inline.is_empty() {
+            changes.complete_battles = true;
+            changes.events = inline;
+        }
+
         Ok(Self {
-            neopets_path: neopets_path.to_string(),
-            complete_battles_path: complete_battles_path.to_string(),
-            pending_battles_path: pending_battles_path.to_string(),
+            store,
+            changes: RefCell::new(changes),
+            files: Some(StorageFiles {
+                inventories: inventories_path,
+                equipment: equipment_path,
+                season: season_path,
+                injuries: injuries_path,
+                series: series_path,
+                trash: trash_path,
+                ratings: ratings_path,
+                checkpoints: checkpoints_path,
+                experience: experience_path,
+            }),
             neopets,
             complete_battles,
             pending_battles,
+            inventories,
+            items,
+            spells,
+            equipment,
+            season,
+            injuries,
+            series,
+            trash,
+            ratings,
+            checkpoints,
+            experience,
+            leveling,
+            ids,
         })
     }
 
+    /// Storage that never touches the disk, for tests of anything built on `Storage`: no
+    /// fighters, battles or items to start with, the default season and rules, and `save`
+    /// keeping the fighters and battles in a `MemoryStore`.
+    pub fn in_memory() -> Self {
+        Self {
+            store: Box::new(MemoryStore::new()),
+            files: None,
+            changes: RefCell::default(),
+            neopets: Vec::new(),
+            complete_battles: Vec::new(),
+            pending_battles: Vec::new(),
+            inventories: HashMap::new(),
+            items: ItemCatalog::default(),
+            spells: SpellCatalog::default(),
+            equipment: HashMap::new(),
+            season: Season::default(),
+            injuries: HashMap::new(),
+            series: Vec::new(),
+            trash: Vec::new(),
+            ratings: RatingsSnapshot::recompute(&[], &[], &RatingRules::default(), chrono::Utc::now().fixed_offset()),
+            checkpoints: HashMap::new(),
+            experience: HashMap::new(),
+            leveling: LevelingRules::default(),
+            ids: IdGenerator::in_memory(),
+        }
+    }
+
+    #[cfg_attr(
+        feature = "log",
+        tracing::instrument(level = "debug", skip_all, err, fields(complete = self.complete_battles.len(), pending = self.pending_battles.len()))
+    )]
+    /// Writes what changed since the storage was opened or last saved; files nothing
+    /// changed in are left as they are. See `flush_all` to write everything.
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        // Save neopets
-        let neopets_file = File::create(&self.neopets_path)?;
-        let writer = BufWriter::new(neopets_file);
-        serde_json::to_writer_pretty(writer, &self.neopets)?;
-        
-        // Save complete battles
-        let complete_battles_file = File::create(&self.complete_battles_path)?;
-        let writer = BufWriter::new(complete_battles_file);
-        serde_json::to_writer_pretty(writer, &self.complete_battles)?;
-        
-        // Save pending battles
-        let pending_battles_file = File::create(&self.pending_battles_path)?;
-        let writer = BufWriter::new(pending_battles_file);
-        serde_json::to_writer_pretty(writer, &self.pending_battles)?;
-        
+        let changes = self.changes.borrow().clone();
+
+        // Save neopets and battles, with the events of those added or changed since
+        // opening. Trashed battles keep their events until purged
+        self.store.save(&StoreSnapshot {
+            fighters: &self.neopets,
+            complete_battles: &self.complete_battles,
+            pending_battles: &self.pending_battles,
+            kept_events: self.complete_battles.iter().map(|b| b.id.as_str())
+                .chain(self.trash.iter().filter(|e| matches!(e.item, TrashedItem::CompleteBattle { .. })).map(TrashEntry::key))
+                .collect::<HashSet<&str>>(),
+            changed: StoreChanges {
+                fighters: changes.fighters,
+                complete_battles: changes.complete_battles,
+                pending_battles: changes.pending_battles,
+                events: changes.events.iter().map(String::as_str).collect(),
+                removed_events: changes.complete_battles || changes.trash,
+            },
+        })?;
+        if let Some(files) = &self.files {
+            self.save_files(files, &changes)?;
+        }
+        *self.changes.borrow_mut() = Changes::default();
+        Ok(())
+    }
+
+    /// Writes every file, changed or not, with the events of every battle holding them
+    /// in memory. Events only stored are left where they are.
+    pub fn flush_all(&self) -> Result<(), Box<dyn std::error::Error>> {
+        *self.changes.borrow_mut() = Changes::everything(&self.complete_battles);
+        self.save()
+    }
+
+    /// Whether anything changed since the storage was opened or last saved.
+    pub fn has_unsaved_changes(&self) -> bool {
+        *self.changes.borrow() != Changes::default()
+    }
+
+    fn save_files(&self, files: &StorageFiles, changes: &Changes) -> Result<(), Box<dyn std::error::Error>> {
+        if changes.inventories {
+            let inventories_file = File::create(&files.inventories)?;
+            let writer = BufWriter::new(inventories_file);
+            serde_json::to_writer_pretty(writer, &self.inventories)?;
+        }
+
+        if changes.equipment {
+            let equipment_file = File::create(&files.equipment)?;
+            let writer = BufWriter::new(equipment_file);
+            serde_json::to_writer_pretty(writer, &self.equipment)?;
+        }
+
+        // The season and the injuries carried over in it
+        if changes.season {
+            self.season.save(&files.season)?;
+        }
+        if changes.injuries {
+            let injuries_file = File::create(&files.injuries)?;
+            let writer = BufWriter::new(injuries_file);
+            serde_json::to_writer_pretty(writer, &self.injuries)?;
+        }
+
+        if changes.series {
+            let series_file = File::create(&files.series)?;
+            let writer = BufWriter::new(series_file);
+            serde_json::to_writer_pretty(writer, &self.series)?;
+        }
+
+        if changes.trash {
+            let trash_file = File::create(&files.trash)?;
+            let writer = BufWriter::new(trash_file);
+            serde_json::to_writer_pretty(writer, &self.trash)?;
+        }
+
+        if changes.ratings {
+            self.ratings.save(&files.ratings)?;
+        }
+
+        // Checkpoints of paused battles
+        if changes.checkpoints {
+            let checkpoints_file = File::create(&files.checkpoints)?;
+            let writer = BufWriter::new(checkpoints_file);
+            serde_json::to_writer_pretty(writer, &self.checkpoints)?;
+        }
+
+        if changes.experience {
+            let experience_file = File::create(&files.experience)?;
+            let writer = BufWriter::new(experience_file);
+            serde_json::to_writer_pretty(writer, &self.experience)?;
+        }
+
+        Ok(())
+    }
+
+    /// Where mutations note what they changed.
+    fn changed(&mut self) -> &mut Changes {
+        self.changes.get_mut()
+    }
+
+    /// Up to `limit` events of a complete battle starting at the `offset`-th one, read
+    /// from disk unless the battle has them in memory.
+    pub fn events_page(&self, battle_id: &str, offset: usize, limit: usize) -> Result<Vec<BattleEvent>, Box<dyn std::error::Error>> {
+        let battle = self.complete_battles.iter().find(|b| b.id == battle_id)
+            .ok_or_else(|| format!("Complete battle '{}' not found", battle_id))?;
+        if !battle.events.is_empty() {
+            return Ok(battle.events.iter().skip(offset).take(limit).cloned().collect());
+        }
+        self.store.read_events(battle_id, offset, limit)
+    }
+
+    /// How many events a complete battle has, without loading them.
+    pub fn event_count(&self, battle_id: &str) -> Result<usize, Box<dyn std::error::Error>> {
+        let battle = self.complete_battles.iter().find(|b| b.id == battle_id)
+            .ok_or_else(|| format!("Complete battle '{}' not found", battle_id))?;
+        if !battle.events.is_empty() {
+            return Ok(battle.events.len());
+        }
+        self.store.count_events(battle_id)
+    }
+
+    /// A pending or complete battle by ID with all its events, reading them from disk
+    /// for a complete battle that doesn't have them in memory.
+    pub fn load_battle(&self, id: &str) -> Result<Option<BattleRecord>, Box<dyn std::error::Error>> {
+        let Some(battle) = self.find_battle(id) else { return Ok(None) };
+        let mut battle = battle.clone();
+        if battle.is_completed && battle.events.is_empty() {
+            battle.events = self.events_page(id, 0, usize::MAX)?;
+        }
+        Ok(Some(battle))
+    }
+
+    /// Moves the fighters, battles and events to `store`, which the next `save` writes them to.
+    pub fn move_to_store(&mut self, store: Box<dyn BattleStore>) -> Result<(), Box<dyn std::error::Error>> {
+        self.complete_battles = self.load_complete_battles()?;
+        let trashed = self.trash.iter_mut().filter_map(|entry| match &mut entry.item {
+            TrashedItem::CompleteBattle { battle } => Some(battle),
+            _ => None,
+        });
+        for battle in trashed.filter(|b| b.events.is_empty()) {
+            battle.events = self.store.read_events(&battle.id, 0, usize::MAX)?;
+        }
+        self.store = store;
+        *self.changed() = Changes::everything(&self.complete_battles);
         Ok(())
     }
 
+    /// Every complete battle with its events, for going through whole battle histories.
+    pub fn load_complete_battles(&self) -> Result<Vec<BattleRecord>, Box<dyn std::error::Error>> {
+        self.complete_battles.iter()
+            .map(|b| Ok(self.load_battle(&b.id)?.unwrap_or_else(|| b.clone())))
+            .collect()
+    }
+
     // Fighter operations
-    pub fn add_neopet(&mut self, neopet: Neopet) -> Result<(), String> {
+    pub fn add_neopet(&mut self, mut neopet: Neopet) -> Result<(), String> {
         // Check for duplicate name
         if self.neopets.iter().any(|n| n.name == neopet.name) {
             return Err(format!("A fighter named '{}' already exists", neopet.name));
         }
+        self.spells.resolve_known(&mut neopet);
         self.neopets.push(neopet);
+        self.changed().fighters = true;
         Ok(())
     }
 
@@ -99,9 +753,362 @@ impl Storage {
         self.neopets.iter().find(|n| n.name == name)
     }
 
+    /// Adds an imported fighter, doing as `on_conflict` says when one with the same name
+    /// exists. Returns the name the fighter was added under.
+    pub fn import_neopet(&mut self, mut neopet: Neopet, on_conflict: ImportConflict) -> Result<String, String> {
+        if self.get_fighter(&neopet.name).is_some() {
+            match on_conflict {
+                ImportConflict::Refuse => {
+                    return Err(format!("A fighter named '{}' already exists, rename or overwrite them", neopet.name));
+                }
+                ImportConflict::Overwrite => {
+                    let name = neopet.name.clone();
+                    self.update_neopet(neopet)?;
+                    return Ok(name);
+                }
+                ImportConflict::Rename => {
+                    let free = (2..).map(|n| format!("{} {}", neopet.name, n))
+                        .find(|name| self.get_fighter(name).is_none());
+                    neopet.name = free.unwrap_or_default();
+                }
+            }
+        }
+        let name = neopet.name.clone();
+        self.add_neopet(Neopet::try_from(NeopetDef::from(neopet))?)?;
+        Ok(name)
+    }
+
+    /// Replaces the fighter named like `neopet`, returning them as they were. `neopet` is
+    /// validated like a fighter read from `neopets.json`, so its behavior still sums to 1
+    /// with a chance for each spell. Renaming goes through `rename_neopet`.
+    pub fn update_neopet(&mut self, neopet: Neopet) -> Result<Neopet, String> {
+        let pos = self.neopets.iter().position(|n| n.name == neopet.name)
+            .ok_or_else(|| format!("Fighter '{}' not found", neopet.name))?;
+        let mut neopet = Neopet::try_from(NeopetDef::from(neopet))?;
+        self.spells.resolve_known(&mut neopet);
+        self.changed().fighters = true;
+        Ok(std::mem::replace(&mut self.neopets[pos], neopet))
+    }
+
+    /// Permanently removes a fighter, with their items, equipment, injuries, experience
+    /// and rating. Fighters still named in a battle or a series are refused, their
+    /// history needing them: delete those first, or move the fighter to the trash.
+    pub fn remove_neopet(&mut self, name: &str) -> Result<Neopet, String> {
+        let pos = self.neopets.iter().position(|n| n.name == name)
+            .ok_or_else(|| format!("Fighter '{}' not found", name))?;
+        let battles: Vec<&str> = self.pending_battles.iter().chain(self.complete_battles.iter())
+            .filter(|b| b.involves(name))
+            .map(|b| b.id.as_str())
+            .collect();
+        if !battles.is_empty() {
+            return Err(format!("Fighter '{}' is in {} battle(s) ({}), delete them first", name, battles.len(), battles.join(", ")));
+        }
+        if let Some(series) = self.series.iter().find(|s| s.fighter1 == name || s.fighter2 == name) {
+            return Err(format!("Fighter '{}' is in series '{}'", name, series.id));
+        }
+
+        self.inventories.remove(name);
+        self.equipment.remove(name);
+        self.injuries.remove(name);
+        self.experience.remove(name);
+        self.ratings.ratings.remove(name);
+        let changed = self.changed();
+        (changed.fighters, changed.inventories, changed.equipment) = (true, true, true);
+        (changed.injuries, changed.experience, changed.ratings) = (true, true, true);
+        Ok(self.neopets.remove(pos))
+    }
+
+    /// Renames a fighter everywhere they are named: their battles (events included,
+    /// which are loaded from disk and hashed again), series, items, equipment, injuries,
+    /// experience and rating. Refused while one of their battles is paused, the
+    /// checkpoint holding the fighter as the battle started.
+    pub fn rename_neopet(&mut self, name: &str, new_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            return Err("A fighter's name can't be empty".into());
+        }
+        if self.get_fighter(new_name).is_some() {
+            return Err(format!("A fighter named '{}' already exists", new_name).into());
+        }
+        let pos = self.neopets.iter().position(|n| n.name == name)
+            .ok_or_else(|| format!("Fighter '{}' not found", name))?;
+        let paused = self.pending_battles.iter()
+            .find(|b| b.involves(name) && self.checkpoints.contains_key(&b.id));
+        if let Some(battle) = paused {
+            return Err(format!("Battle '{}' of '{}' is paused, finish it first", battle.id, name).into());
+        }
+
+        for battle in self.complete_battles.iter_mut().filter(|b| b.involves(name)) {
+            if battle.events.is_empty() {
+                battle.events = self.store.read_events(&battle.id, 0, usize::MAX)?;
+            }
+            battle.rename_fighter(name, new_name);
+        }
+        for battle in self.pending_battles.iter_mut().filter(|b| b.involves(name)) {
+            battle.rename_fighter(name, new_name);
+        }
+        let trashed = self.trash.iter_mut().filter_map(|entry| match &mut entry.item {
+            TrashedItem::CompleteBattle { battle } | TrashedItem::PendingBattle { battle } => Some(battle),
+            TrashedItem::Fighter { .. } => None,
+        });
+        for battle in trashed.filter(|b| b.involves(name)) {
+            if battle.is_completed && battle.events.is_empty() {
+                battle.events = self.store.read_events(&battle.id, 0, usize::MAX)?;
+            }
+            battle.rename_fighter(name, new_name);
+        }
+
+        for series in &mut self.series {
+            for fighter in [&mut series.fighter1, &mut series.fighter2].into_iter()
+                .chain(series.games.iter_mut().map(|game| &mut game.winner))
+            {
+                if fighter == name {
+                    *fighter = new_name.to_string();
+                }
+            }
+        }
+        if let Some(items) = self.inventories.remove(name) {
+            self.inventories.insert(new_name.to_string(), items);
+        }
+        if let Some(loadout) = self.equipment.remove(name) {
+            self.equipment.insert(new_name.to_string(), loadout);
+        }
+        if let Some(injury) = self.injuries.remove(name) {
+            self.injuries.insert(new_name.to_string(), injury);
+        }
+        if let Some(experience) = self.experience.remove(name) {
+            self.experience.insert(new_name.to_string(), experience);
+        }
+        if let Some(rating) = self.ratings.ratings.remove(name) {
+            self.ratings.ratings.insert(new_name.to_string(), rating);
+        }
+        self.neopets[pos].name = new_name.to_string();
+        // Just about everything names the fighter, and their battles' events were hashed again
+        *self.changed() = Changes::everything(&self.complete_battles);
+        Ok(())
+    }
+
+    // Inventory operations
+    pub fn add_items(&mut self, fighter_name: &str, items: Vec<Item>) {
+        self.inventories.entry(fighter_name.to_string()).or_default().extend(items);
+        self.changed().inventories = true;
+    }
+
+    pub fn inventory(&self, fighter_name: &str) -> &[Item] {
+        self.inventories.get(fighter_name).map(|items| items.as_slice()).unwrap_or(&[])
+    }
+
+    // Equipment operations
+    pub fn item_catalog(&self) -> &ItemCatalog {
+        &self.items
+    }
+
+    /// The catalog the fighters' spells were resolved against when they were loaded.
+    pub fn spell_catalog(&self) -> &SpellCatalog {
+        &self.spells
+    }
+
+    pub fn loadout(&self, fighter_name: &str) -> &Loadout {
+        static EMPTY: Loadout = Loadout::new();
+        self.equipment.get(fighter_name).unwrap_or(&EMPTY)
+    }
+
+    /// Puts an item from the catalog in its slot, returning the item it replaced.
+    pub fn equip(&mut self, fighter_name: &str, item_name: &str) -> Result<Option<String>, String> {
+        if self.get_fighter(fighter_name).is_none() {
+            return Err(format!("Fighter '{}' not found", fighter_name));
+        }
+        let item = self.items.get(item_name).ok_or_else(|| format!("Item '{}' not found", item_name))?;
+        let replaced = self.equipment.entry(fighter_name.to_string()).or_default().insert(item.slot, item.name.clone());
+        self.changed().equipment = true;
+        Ok(replaced)
+    }
+
+    /// Empties a slot, returning the item that was in it.
+    pub fn unequip(&mut self, fighter_name: &str, slot: Slot) -> Option<String> {
+        let loadout = self.equipment.get_mut(fighter_name)?;
+        let removed = loadout.remove(&slot);
+        if loadout.is_empty() {
+            self.equipment.remove(fighter_name);
+        }
+        self.changed().equipment = true;
+        removed
+    }
+
+    /// The fighter grown to their level, see `leveling`.
+    pub fn leveled_fighter(&self, name: &str) -> Result<Neopet, String> {
+        let mut neopet = self.get_fighter(name).ok_or_else(|| format!("Fighter '{}' not found", name))?.clone();
+        self.leveling.apply(&mut neopet, self.level(name));
+        Ok(neopet)
+    }
+
+    /// The fighter as they enter battle, at their level and with their equipment's
+    /// bonuses applied.
+    pub fn equipped_fighter(&self, name: &str) -> Result<Neopet, String> {
+        self.items.equip(&self.leveled_fighter(name)?, self.loadout(name))
+    }
+
+    /// The fighter as they enter battle: leveled, wearing their equipment and, in a hardcore
+    /// season, still missing the HP of their injury (they always keep at least 1).
+    pub fn battle_fighter(&self, name: &str) -> Result<Neopet, String> {
+        let mut neopet = self.equipped_fighter(name)?;
+        neopet.health = neopet.health.saturating_sub(self.injury(name)).max(1);
+        Ok(neopet)
+    }
+
+    /// Both fighters of `battle` as they enter it, see `battle_fighter`, with the
+    /// battle's handicap applied.
+    pub fn battle_fighters(&self, battle: &BattleRecord) -> Result<(Neopet, Neopet), String> {
+        let mut fighter1 = self.battle_fighter(&battle.fighter1_name)?;
+        let mut fighter2 = self.battle_fighter(&battle.fighter2_name)?;
+        battle.handicap.apply(&mut fighter1, &mut fighter2);
+        Ok((fighter1, fighter2))
+    }
+
+    // Season operations
+    pub fn season(&self) -> &Season {
+        &self.season
+    }
+
+    /// Replaces the current season. Everyone starts the new one healthy.
+    pub fn start_season(&mut self, season: Season) {
+        self.season = season;
+        self.injuries.clear();
+        let changed = self.changed();
+        (changed.season, changed.injuries) = (true, true);
+    }
+
+    /// HP the fighter carries over as an injury, 0 when healthy.
+    pub fn injury(&self, fighter_name: &str) -> u32 {
+        self.injuries.get(fighter_name).copied().unwrap_or(0)
+    }
+
+    /// Every injured fighter not among `participants` sat the round out and
+    /// recovers. Returns the recovered fighters, sorted by name.
+    pub fn sit_out_round(&mut self, participants: &[String]) -> Vec<String> {
+        let mut recovered: Vec<String> =
+            self.injuries.keys().filter(|name| !participants.contains(name)).cloned().collect();
+        recovered.sort();
+        for name in &recovered {
+            self.injuries.remove(name);
+        }
+        if !recovered.is_empty() {
+            self.changed().injuries = true;
+        }
+        recovered
+    }
+
+    /// In a hardcore season, adds the share of HP both fighters lost in `battle`
+    /// to their injuries.
+    fn record_injuries(&mut self, battle: &BattleRecord) {
+        let Some(rules) = self.season.hardcore else { return };
+        let Some(final_hps) = BattleLog::from(&battle.events).final_hps() else {
+            return;
+        };
+
+        for (name, final_hp) in final_hps {
+            let Ok(health) = self.leveled_fighter(name).map(|n| n.health) else { continue };
+            let injury = self.injury(name);
+            // They started the battle already missing their injury
+            let hp_lost = health.saturating_sub(injury).saturating_sub(final_hp);
+            let injury = (injury + rules.injury(hp_lost)).min(health.saturating_sub(1));
+            if injury > 0 {
+                self.injuries.insert(name.to_string(), injury);
+                self.changed().injuries = true;
+            }
+        }
+    }
+
+    // Leveling operations
+    pub fn leveling_rules(&self) -> &LevelingRules {
+        &self.leveling
+    }
+
+    /// Experience points the fighter earned, 0 before their first battle.
+    pub fn experience(&self, fighter_name: &str) -> u32 {
+        self.experience.get(fighter_name).copied().unwrap_or(0)
+    }
+
+    pub fn level(&self, fighter_name: &str) -> u32 {
+        self.leveling.level(self.experience(fighter_name))
+    }
+
+    pub fn progress(&self, fighter_name: &str) -> Progress {
+        self.leveling.progress(self.experience(fighter_name))
+    }
+
+    /// Awards both fighters of `battle` their experience.
+    fn record_experience(&mut self, battle: &BattleRecord) {
+        for (name, xp) in self.leveling.award(battle) {
+            if self.get_fighter(name).is_some() {
+                let total = self.experience.entry(name.to_string()).or_insert(0);
+                *total = total.saturating_add(xp);
+                self.changed().experience = true;
+            }
+        }
+    }
+
+    // Series operations
+    pub fn add_series(&mut self, series: SeriesRecord) -> Result<(), String> {
+        for name in [&series.fighter1, &series.fighter2] {
+            if self.get_fighter(name).is_none() {
+                return Err(format!("Fighter '{}' not found", name));
+            }
+        }
+        if self.find_series(&series.id).is_some() {
+            return Err(format!("A series with ID '{}' already exists", series.id));
+        }
+        self.series.push(series);
+        self.changed().series = true;
+        Ok(())
+    }
+
+    pub fn series_records(&self) -> &[SeriesRecord] {
+        &self.series
+    }
+
+    pub fn find_series(&self, id: &str) -> Option<&SeriesRecord> {
+        self.series.iter().find(|s| s.id == id)
+    }
+
+    /// Replaces the stored series with the same ID, e.g. after playing its games.
+    pub fn update_series(&mut self, series: SeriesRecord) -> Result<(), String> {
+        let stored = self.series.iter_mut().find(|s| s.id == series.id)
+            .ok_or_else(|| format!("Series '{}' not found", series.id))?;
+        *stored = series;
+        self.changed().series = true;
+        Ok(())
+    }
+
+    /// The ID generator, shared with the cassino.
+    pub fn ids(&self) -> &IdGenerator {
+        &self.ids
+    }
+
+    /// Next series ID, never reusing one still around.
+    pub fn generate_series_id(&self) -> Result<String, Box<dyn std::error::Error>> {
+        self.ids.next_unused(IdKind::Series, |id| self.series.iter().any(|s| s.id == id))
+    }
+
     // Complete battle operations
-    pub fn add_complete_battle(&mut self, battle: BattleRecord) {
+    /// Files a complete battle, refusing an ID another battle has (see `has_battle_id`).
+    pub fn add_complete_battle(&mut self, battle: BattleRecord) -> Result<(), String> {
+        self.check_unique_id(&battle.id)?;
+        self.ratings.record_battle(&battle);
+        self.record_injuries(&battle);
+        self.record_experience(&battle);
+        let changed = self.changed();
+        (changed.complete_battles, changed.ratings) = (true, true);
+        if !battle.events.is_empty() {
+            changed.events.insert(battle.id.clone());
+        }
         self.complete_battles.push(battle);
+        Ok(())
+    }
+
+    /// Current rating and win/loss record of every fighter.
+    pub fn ratings(&self) -> &RatingsSnapshot {
+        &self.ratings
     }
 
     pub fn list_complete_battles(&self) -> Vec<(String, String, String)> {
@@ -112,17 +1119,78 @@ impl Storage {
         }).collect()
     }
 
-    pub fn get_complete_battle(&mut self, id: &str) -> Option<&mut BattleRecord> {
-        self.complete_battles.iter_mut().find(|b| b.id == id)
+    /// Every complete battle, without the events of those not loaded yet (see
+    /// `load_complete_battles`).
+    pub fn complete_battle_records(&self) -> &[BattleRecord] {
+        &self.complete_battles
+    }
+
+    /// A complete battle by ID, its events loaded from disk and kept in memory.
+    pub fn get_complete_battle(&mut self, id: &str) -> Result<Option<&mut BattleRecord>, Box<dyn std::error::Error>> {
+        let Some(pos) = self.complete_battles.iter().position(|b| b.id == id) else { return Ok(None) };
+        if self.complete_battles[pos].events.is_empty() {
+            let events = self.events_page(id, 0, usize::MAX)?;
+            self.complete_battles[pos].events = events;
+        }
+        // Whatever the caller changes in it gets saved, events included
+        let changed = self.changed();
+        changed.complete_battles = true;
+        changed.events.insert(id.to_string());
+        Ok(Some(&mut self.complete_battles[pos]))
     }
 
     pub fn clear_complete_battles(&mut self) {
         self.complete_battles.clear();
+        self.changed().complete_battles = true;
+    }
+
+    /// Marks a complete battle archived: it stays stored, events and all, but listings
+    /// and stats leave it out until `unarchive_battle`. Ratings, streaks and series
+    /// still count it.
+    pub fn archive_battle(&mut self, id: &str) -> Result<(), String> {
+        self.set_archived(id, true)
+    }
+
+    pub fn unarchive_battle(&mut self, id: &str) -> Result<(), String> {
+        self.set_archived(id, false)
+    }
+
+    fn set_archived(&mut self, id: &str, archived: bool) -> Result<(), String> {
+        let Some(battle) = self.complete_battles.iter_mut().find(|b| b.id == id) else {
+            return Err(if self.pending_battles.iter().any(|b| b.id == id) {
+                format!("Battle '{}' is still pending, only completed battles can be archived", id)
+            } else {
+                format!("Complete battle '{}' not found", id)
+            });
+        };
+        if battle.archived == archived {
+            return Err(format!("Battle '{}' is {}", id, if archived { "already archived" } else { "not archived" }));
+        }
+        battle.archived = archived;
+        self.changed().complete_battles = true;
+        Ok(())
     }
 
     // Pending battle operations
-    pub fn add_pending_battle(&mut self, battle: BattleRecord) {
+    /// Queues a battle, refusing an ID another battle has (see `has_battle_id`).
+    pub fn add_pending_battle(&mut self, battle: BattleRecord) -> Result<(), String> {
+        self.check_unique_id(&battle.id)?;
         self.pending_battles.push(battle);
+        self.changed().pending_battles = true;
+        Ok(())
+    }
+
+    /// Whether a pending, complete or trashed battle has the ID.
+    pub fn has_battle_id(&self, id: &str) -> bool {
+        self.complete_battles.iter().chain(self.pending_battles.iter()).any(|b| b.id == id)
+            || self.trash.iter().any(|e| matches!(e.item, TrashedItem::PendingBattle { .. } | TrashedItem::CompleteBattle { .. }) && e.key() == id)
+    }
+
+    fn check_unique_id(&self, id: &str) -> Result<(), String> {
+        if self.has_battle_id(id) {
+            return Err(format!("A battle with ID '{}' already exists", id));
+        }
+        Ok(())
     }
 
     pub fn list_pending_battles(&self) -> Vec<(String, String, String)> {
@@ -132,8 +1200,58 @@ impl Storage {
         }).collect()
     }
 
+    pub fn pending_battle_records(&self) -> &[BattleRecord] {
+        &self.pending_battles
+    }
+
+    /// Completed battles then pending ones matching `filter`, each in the order they were
+    /// added. Completed battles come without the events not loaded yet.
+    pub fn find_battles(&self, filter: &BattleFilter) -> Vec<&BattleRecord> {
+        self.complete_battles.iter().chain(self.pending_battles.iter())
+            .filter(|b| filter.matches(b))
+            .collect()
+    }
+
+    /// A pending or completed battle by ID. Completed battles come without the events
+    /// not loaded yet, see `load_battle`.
+    pub fn find_battle(&self, id: &str) -> Option<&BattleRecord> {
+        self.pending_battles.iter().chain(self.complete_battles.iter()).find(|b| b.id == id)
+    }
+
+    pub fn annotate_battle(&mut self, id: &str, tags: &[String], notes: &[String]) -> Result<(), String> {
+        let battle = self.pending_battles.iter_mut().chain(self.complete_battles.iter_mut())
+            .find(|b| b.id == id)
+            .ok_or_else(|| format!("Battle '{}' not found", id))?;
+        battle.annotate(tags, notes);
+        let changed = self.changed();
+        (changed.complete_battles, changed.pending_battles) = (true, true);
+        Ok(())
+    }
+
     pub fn clear_pending_battles(&mut self) {
         self.pending_battles.clear();
+        self.changed().pending_battles = true;
+    }
+
+    /// Sets when the pending battle `id` is due, `battle start` refusing it until then.
+    pub fn schedule_battle(&mut self, id: &str, at: DateTime<FixedOffset>) -> Result<(), String> {
+        let battle = self.pending_battles.iter_mut()
+            .find(|b| b.id == id)
+            .ok_or_else(|| format!("Pending battle '{}' not found", id))?;
+        battle.scheduled_at = Some(at.to_rfc3339());
+        self.changed().pending_battles = true;
+        Ok(())
+    }
+
+    /// Scheduled pending battles whose time has come by `now`, soonest first, for the
+    /// watch-pending worker. Paused ones are left for `battle resume` and battles without
+    /// a schedule for `battle start`.
+    pub fn due_battles(&self, now: DateTime<FixedOffset>) -> Vec<&BattleRecord> {
+        let mut due: Vec<&BattleRecord> = self.pending_battles.iter()
+            .filter(|b| b.scheduled_at.is_some() && b.is_due(now) && self.checkpoint(&b.id).is_none())
+            .collect();
+        due.sort_by_key(|b| b.scheduled_at.as_deref().and_then(|at| DateTime::parse_from_rfc3339(at).ok()));
+        due
     }
 
     // Battle execution operations
@@ -142,31 +1260,201 @@ impl Storage {
     }
 
     pub fn remove_pending_battle(&mut self, id: &str) -> Option<BattleRecord> {
-        if let Some(pos) = self.pending_battles.iter().position(|b| b.id == id) {
-            Some(self.pending_battles.remove(pos))
-        } else {
-            None
+        let pos = self.pending_battles.iter().position(|b| b.id == id)?;
+        self.changed().pending_battles = true;
+        Some(self.pending_battles.remove(pos))
+    }
+
+    /// Permanently removes the pending battles created before `before`, with their
+    /// checkpoints, returning their IDs. Battles with an unreadable `created_at` are kept.
+    pub fn expire_pending(&mut self, before: DateTime<FixedOffset>) -> Vec<String> {
+        let is_stale = |battle: &BattleRecord| {
+            DateTime::parse_from_rfc3339(&battle.created_at).is_ok_and(|created_at| created_at < before)
+        };
+        let expired: Vec<String> = self.pending_battles.iter().filter(|b| is_stale(b)).map(|b| b.id.clone()).collect();
+        if expired.is_empty() {
+            return expired;
+        }
+        self.pending_battles.retain(|b| !is_stale(b));
+        for id in &expired {
+            self.take_checkpoint(id);
+        }
+        self.changed().pending_battles = true;
+        expired
+    }
+
+    /// Saves where a pending battle was paused, replacing any earlier checkpoint of it.
+    /// The fighters' spells are saved in full, not by name, so the battle resumes with
+    /// them as they were even if the spell catalog changes in the meantime.
+    pub fn save_checkpoint(&mut self, battle_id: &str, mut checkpoint: CheckpointRecord) -> Result<(), String> {
+        if !self.pending_battles.iter().any(|b| b.id == battle_id) {
+            return Err(format!("Pending battle '{}' not found", battle_id));
         }
+        for spell in checkpoint.fighter1.spells.iter_mut().chain(checkpoint.fighter2.spells.iter_mut()) {
+            spell.inline = true;
+        }
+        self.checkpoints.insert(battle_id.to_string(), checkpoint);
+        self.changed().checkpoints = true;
+        Ok(())
     }
 
-    pub fn move_battle_to_complete(&mut self, mut battle: BattleRecord, events: Vec<BattleEvent>, winner: Option<String>) -> BattleRecord {
+    pub fn checkpoint(&self, battle_id: &str) -> Option<&CheckpointRecord> {
+        self.checkpoints.get(battle_id)
+    }
+
+    /// Removes and returns a battle's checkpoint, to resume it.
+    pub fn take_checkpoint(&mut self, battle_id: &str) -> Option<CheckpointRecord> {
+        let checkpoint = self.checkpoints.remove(battle_id)?;
+        self.changed().checkpoints = true;
+        Some(checkpoint)
+    }
+
+    /// Files `battle` as complete with its results, taking it off the pending battles if
+    /// it was there. Fails, changing nothing, if another battle has its ID.
+    pub fn move_battle_to_complete(&mut self, mut battle: BattleRecord, events: Vec<BattleEvent>, winner: Option<String>) -> Result<BattleRecord, String> {
+        if let Some(pos) = self.pending_battles.iter().position(|b| b.id == battle.id) {
+            let pending = self.pending_battles.remove(pos);
+            if let Err(e) = self.check_unique_id(&battle.id) {
+                self.pending_battles.insert(pos, pending);
+                return Err(e);
+            }
+            self.changed().pending_battles = true;
+        }
+
+        // A completed battle can't be resumed
+        self.take_checkpoint(&battle.id);
+
         // Update the battle record with execution results
+        battle.hash = Some(battle_hash(&events));
         battle.events = events;
         battle.winner = winner;
         battle.is_completed = true;
         
-        // Add to complete battles
-        self.complete_battles.push(battle.clone());
-        battle
+        // Add to complete battles, updating both fighters' ratings
+        self.add_complete_battle(battle.clone())?;
+        Ok(battle)
+    }
+
+    // Trash operations
+    pub fn trash_fighter(&mut self, name: &str, deleted_at: &str) -> Result<(), String> {
+        let pos = self.neopets.iter().position(|n| n.name == name)
+            .ok_or_else(|| format!("Fighter '{}' not found", name))?;
+        let neopet = self.neopets.remove(pos);
+        self.changed().fighters = true;
+        self.push_trash(TrashedItem::Fighter { neopet }, deleted_at);
+        Ok(())
+    }
+
+    pub fn trash_battle(&mut self, id: &str, deleted_at: &str) -> Result<(), String> {
+        if let Some(battle) = self.remove_pending_battle(id) {
+            self.push_trash(TrashedItem::PendingBattle { battle }, deleted_at);
+        } else if let Some(pos) = self.complete_battles.iter().position(|b| b.id == id) {
+            let battle = self.complete_battles.remove(pos);
+            self.changed().complete_battles = true;
+            self.push_trash(TrashedItem::CompleteBattle { battle }, deleted_at);
+        } else {
+            return Err(format!("Battle '{}' not found", id));
+        }
+        Ok(())
+    }
+
+    /// Moves every complete and pending battle to the trash, returning how many were moved.
+    pub fn trash_all_battles(&mut self, deleted_at: &str) -> usize {
+        let complete: Vec<BattleRecord> = self.complete_battles.drain(..).collect();
+        let pending: Vec<BattleRecord> = self.pending_battles.drain(..).collect();
+        let count = complete.len() + pending.len();
+        let changed = self.changed();
+        (changed.complete_battles, changed.pending_battles) = (true, true);
+        for battle in complete {
+            self.push_trash(TrashedItem::CompleteBattle { battle }, deleted_at);
+        }
+        for battle in pending {
+            self.push_trash(TrashedItem::PendingBattle { battle }, deleted_at);
+        }
+        count
     }
 
-    pub fn generate_battle_id(&self) -> String {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
-        format!("battle_{}", timestamp)
+    fn push_trash(&mut self, item: TrashedItem, deleted_at: &str) {
+        self.trash.push(TrashEntry {
+            deleted_at: deleted_at.to_string(),
+            item,
+        });
+        self.changed().trash = true;
+    }
+
+    pub fn trash_entries(&self) -> &[TrashEntry] {
+        &self.trash
+    }
+
+    /// Puts the most recently deleted entry named `key` back where it came from.
+    pub fn restore_from_trash(&mut self, key: &str) -> Result<TrashEntry, String> {
+        let pos = self.trash.iter().rposition(|e| e.key() == key)
+            .ok_or_else(|| format!("Nothing named '{}' in the trash", key))?;
+
+        match &self.trash[pos].item {
+            TrashedItem::Fighter { neopet } => {
+                if self.get_fighter(&neopet.name).is_some() {
+                    return Err(format!("A fighter named '{}' already exists", neopet.name));
+                }
+            }
+            TrashedItem::CompleteBattle { battle } | TrashedItem::PendingBattle { battle } => {
+                let taken = self.complete_battles.iter().chain(self.pending_battles.iter())
+                    .any(|b| b.id == battle.id);
+                if taken {
+                    return Err(format!("A battle with ID '{}' already exists", battle.id));
+                }
+            }
+        }
+
+        let entry = self.trash.remove(pos);
+        let changed = self.changed();
+        changed.trash = true;
+        match entry.item.clone() {
+            TrashedItem::Fighter { mut neopet } => {
+                changed.fighters = true;
+                self.spells.resolve_known(&mut neopet);
+                self.neopets.push(neopet);
+            }
+            TrashedItem::CompleteBattle { battle } => {
+                changed.complete_battles = true;
+                if !battle.events.is_empty() {
+                    changed.events.insert(battle.id.clone());
+                }
+                self.complete_battles.push(battle);
+            }
+            TrashedItem::PendingBattle { battle } => {
+                changed.pending_battles = true;
+                self.pending_battles.push(battle);
+            }
+        }
+        Ok(entry)
+    }
+
+    /// Permanently removes entries older than `retention`, returning how many were removed.
+    pub fn purge_expired_trash(&mut self, now: DateTime<FixedOffset>, retention: Duration) -> usize {
+        let before = self.trash.len();
+        self.trash.retain(|e| !e.is_expired(now, retention));
+        let purged = before - self.trash.len();
+        if purged > 0 {
+            self.changed().trash = true;
+        }
+        purged
+    }
+
+    /// Permanently removes everything in the trash, returning how many entries were removed.
+    pub fn purge_all_trash(&mut self) -> usize {
+        let count = self.trash.len();
+        self.trash.clear();
+        self.changed().trash = true;
+        count
+    }
+
+    /// A new battle ID, a ULID (see `IdGenerator::next_ulid`) never reusing one still
+    /// around in the battle lists or the trash. Battles keep whatever ID they were
+    /// given, so the `battle_<counter>` and `battle_<timestamp>` IDs of older battles
+    /// keep working.
+    pub fn generate_battle_id(&self) -> Result<String, Box<dyn std::error::Error>> {
+        self.ids.next_ulid(IdKind::Battle, |id| self.has_battle_id(id))
     }
 }
 
@@ -180,63 +1468,83 @@ mod tests {
     // Helper function to create a test Neopet
     fn create_test_neopet(name: &str) -> Neopet {
         Neopet {
-            name: name.to_string(),
             health: 100,
             heal_delta: 10,
             base_attack: 5,
-            base_defense: 3,
             spells: vec![
                 Spell {
                     name: "Fireball".to_string(),
                     effect: serde_json::Value::Object(serde_json::Map::new()),
+                    cooldown: 0,
+                    inline: false,
                 },
             ],
-            behavior: Behavior {
-                attack_chance: 0.5,
-                spell_chances: vec![0.1],
-                heal_chance: 0.4,
-            },
-        }
-    }
-
-    // Helper function to create a test BattleRecord
-    fn create_test_battle_record(id: &str, fighter1: &str, fighter2: &str) -> BattleRecord {
-        BattleRecord {
-            id: id.to_string(),
-            fighter1_name: fighter1.to_string(),
-            fighter2_name: fighter2.to_string(),
-            created_at: "2023-01-01T00:00:00Z".to_string(),
-            events: vec![],
-            winner: None,
-            is_completed: false,
+            behavior: Behavior { spell_chances: vec![0.1], heal_chance: 0.4, ..Behavior::attack_or_heal(0.5) },
+            ..Neopet::test_fighter(name)
         }
     }
 
     // Helper function to create a clean test storage
     fn create_test_storage() -> Storage {
-        let temp_dir = tempdir().unwrap();
-        let neopets_path = temp_dir.path().join("test_neopets.json");
-        let battles_path = temp_dir.path().join("test_battles.json");
-        let pending_path = temp_dir.path().join("test_pending.json");
-        
-        // Create empty JSON files
-        fs::write(&neopets_path, "[]").unwrap();
-        fs::write(&battles_path, "[]").unwrap();
-        fs::write(&pending_path, "[]").unwrap();
-        
-        // Create a storage with custom paths by modifying the implementation
-        let neopets = Vec::new();
-        let complete_battles = Vec::new();
-        let pending_battles = Vec::new();
-        
-        Storage {
-            neopets_path: neopets_path.to_str().unwrap().to_string(),
-            complete_battles_path: battles_path.to_str().unwrap().to_string(),
-            pending_battles_path: pending_path.to_str().unwrap().to_string(),
-            neopets,
-            complete_battles,
-            pending_battles,
-        }
+        Storage { items: ItemCatalog::load("assets/items.json").unwrap(), ..Storage::in_memory() }
+    }
+
+    #[test]
+    fn test_in_memory_storage_saves_to_its_store() {
+        let mut storage = Storage::in_memory();
+        storage.add_neopet(create_test_neopet("Fighter1")).unwrap();
+        storage.add_neopet(create_test_neopet("Fighter2")).unwrap();
+        let events = vec![
+            BattleEvent::Heal { turn: 1, actor: "Fighter1".into(), amount: 5 },
+            BattleEvent::Heal { turn: 2, actor: "Fighter2".into(), amount: 3 },
+        ];
+        let battle = BattleRecord::test_battle("battle_1", "Fighter1", "Fighter2");
+        storage.move_battle_to_complete(battle, events.clone(), Some("Fighter1".to_string())).unwrap();
+        storage.save().unwrap();
+
+        assert_eq!(storage.store.load_fighters().unwrap().len(), 2);
+        let saved = storage.store.load_complete_battles().unwrap();
+        assert_eq!(saved.len(), 1);
+        assert!(saved[0].events.is_empty());
+        assert_eq!(storage.store.read_events("battle_1", 1, 10).unwrap(), events[1..]);
+        assert_eq!(storage.store.count_events("battle_1").unwrap(), 2);
+
+        storage.trash_battle("battle_1", "2024-01-01T00:00:00Z").unwrap();
+        storage.purge_all_trash();
+        storage.save().unwrap();
+        assert_eq!(storage.store.count_events("battle_1").unwrap(), 0);
+        assert_eq!(storage.ids().next(IdKind::Battle).unwrap(), "battle_1");
+    }
+
+    #[test]
+    fn test_archived_battles_are_kept_but_filtered_out() {
+        let mut storage = create_test_storage();
+        let battle = BattleRecord::test_battle("battle_1", "Fighter1", "Fighter2");
+        storage.move_battle_to_complete(battle, vec![], Some("Fighter1".to_string())).unwrap();
+        storage.add_complete_battle(BattleRecord::test_battle("battle_2", "Fighter1", "Fighter2")).unwrap();
+        storage.add_pending_battle(BattleRecord::test_battle("battle_3", "Fighter2", "Fighter1")).unwrap();
+
+        storage.archive_battle("battle_1").unwrap();
+        assert!(storage.archive_battle("battle_1").unwrap_err().contains("already archived"));
+        assert!(storage.archive_battle("battle_3").unwrap_err().contains("pending"));
+        assert!(storage.archive_battle("battle_404").is_err());
+        assert!(storage.find_battle("battle_1").unwrap().archived);
+
+        let ids = |storage: &Storage, filter: BattleFilter| -> Vec<String> {
+            storage.find_battles(&filter).iter().map(|b| b.id.clone()).collect()
+        };
+        assert_eq!(ids(&storage, BattleFilter::default().archived(false)), ["battle_2", "battle_3"]);
+        assert_eq!(ids(&storage, BattleFilter::default().archived(true)), ["battle_1"]);
+        assert_eq!(ids(&storage, BattleFilter::default()).len(), 3);
+
+        let json = serde_json::to_string(storage.find_battle("battle_2").unwrap()).unwrap();
+        assert!(!json.contains("archived"));
+        let json = serde_json::to_string(storage.find_battle("battle_1").unwrap()).unwrap();
+        assert!(serde_json::from_str::<BattleRecord>(&json).unwrap().archived);
+
+        storage.unarchive_battle("battle_1").unwrap();
+        assert!(storage.unarchive_battle("battle_1").unwrap_err().contains("not archived"));
+        assert_eq!(ids(&storage, BattleFilter::default().archived(false)).len(), 3);
     }
 
     #[test]
@@ -379,17 +1687,117 @@ mod tests {
         fs::write(&battles_path, "[]").unwrap();
         
         let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
-        let neopet = create_test_neopet("TestFighter");
-        storage.add_neopet(neopet.clone()).unwrap();
-        
-        // Should find existing fighter
-        let found = storage.get_fighter("TestFighter");
-        assert!(found.is_some());
-        assert_eq!(found.unwrap().name, "TestFighter");
-        
-        // Should not find non-existing fighter
-        let not_found = storage.get_fighter("NonExistent");
-        assert!(not_found.is_none());
+        let neopet = create_test_neopet("TestFighter");
+        storage.add_neopet(neopet.clone()).unwrap();
+        
+        // Should find existing fighter
+        let found = storage.get_fighter("TestFighter");
+        assert!(found.is_some());
+        assert_eq!(found.unwrap().name, "TestFighter");
+        
+        // Should not find non-existing fighter
+        let not_found = storage.get_fighter("NonExistent");
+        assert!(not_found.is_none());
+    }
+
+    #[test]
+    fn test_update_neopet_revalidates_the_fighter() {
+        let mut storage = create_test_storage();
+        storage.add_neopet(create_test_neopet("Fighter1")).unwrap();
+        storage.add_neopet(create_test_neopet("Fighter2")).unwrap();
+
+        let mut stronger = create_test_neopet("Fighter1");
+        stronger.base_attack += 10;
+        let before = storage.update_neopet(stronger.clone()).unwrap();
+        assert_eq!(before, create_test_neopet("Fighter1"));
+        assert_eq!(storage.get_fighter("Fighter1"), Some(&stronger));
+        assert_eq!(storage.list_fighters(), ["Fighter1", "Fighter2"]);
+
+        let mut unbalanced = create_test_neopet("Fighter2");
+        unbalanced.behavior.heal_chance += 0.5;
+        assert!(storage.update_neopet(unbalanced).unwrap_err().contains("must equal 1.0"));
+        assert_eq!(storage.get_fighter("Fighter2"), Some(&create_test_neopet("Fighter2")));
+        assert!(storage.update_neopet(create_test_neopet("Ghost")).is_err());
+    }
+
+    #[test]
+    fn test_import_neopet_resolves_name_conflicts() {
+        let mut storage = create_test_storage();
+        storage.add_neopet(create_test_neopet("Acara")).unwrap();
+        storage.add_items("Acara", vec![Item { name: "Healing Potion".to_string(), rarity: crate::loot::Rarity::Common }]);
+        let mut imported = create_test_neopet("Acara");
+        imported.health = 150;
+
+        assert!(storage.import_neopet(imported.clone(), ImportConflict::Refuse).is_err());
+        assert_eq!(storage.import_neopet(imported.clone(), ImportConflict::Rename).unwrap(), "Acara 2");
+        assert_eq!(storage.import_neopet(imported.clone(), ImportConflict::Rename).unwrap(), "Acara 3");
+        assert_eq!(storage.get_fighter("Acara 2").unwrap().health, 150);
+
+        assert_eq!(storage.import_neopet(imported, ImportConflict::Overwrite).unwrap(), "Acara");
+        assert_eq!(storage.get_fighter("Acara").unwrap().health, 150);
+        assert_eq!(storage.inventory("Acara").len(), 1);
+        assert_eq!(storage.import_neopet(create_test_neopet("Usul"), ImportConflict::Refuse).unwrap(), "Usul");
+        assert_eq!(storage.list_fighters(), ["Acara", "Acara 2", "Acara 3", "Usul"]);
+    }
+
+    #[test]
+    fn test_remove_neopet_refuses_fighters_with_battles() {
+        let mut storage = create_test_storage();
+        storage.add_neopet(create_test_neopet("Fighter1")).unwrap();
+        storage.add_neopet(create_test_neopet("Fighter2")).unwrap();
+        storage.add_items("Fighter2", vec![Item { name: "Healing Potion".to_string(), rarity: crate::loot::Rarity::Common }]);
+        storage.add_pending_battle(BattleRecord::test_battle("battle_1", "Fighter1", "Fighter3")).unwrap();
+
+        let error = storage.remove_neopet("Fighter1").unwrap_err();
+        assert!(error.contains("battle_1"), "{}", error);
+        assert!(storage.remove_neopet("Ghost").is_err());
+
+        assert_eq!(storage.remove_neopet("Fighter2").unwrap().name, "Fighter2");
+        assert_eq!(storage.list_fighters(), ["Fighter1"]);
+        assert!(storage.inventory("Fighter2").is_empty());
+    }
+
+    #[test]
+    fn test_rename_neopet_renames_their_battles() {
+        let temp_dir = tempdir().unwrap();
+        let neopets_path = temp_dir.path().join("neopets.json");
+        let battles_path = temp_dir.path().join("complete_battles.json");
+        let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        storage.add_neopet(create_test_neopet("Fighter1")).unwrap();
+        storage.add_neopet(create_test_neopet("Fighter2")).unwrap();
+        let events = vec![
+            BattleEvent::Heal { turn: 1, actor: "Fighter1".into(), amount: 5 },
+            BattleEvent::Attack {
+                turn: 2,
+                actor: "Fighter2".into(),
+                target: "Fighter1".into(),
+                raw_damage: 6,
+                shield_value: 1,
+                actual_damage: 5,
+                is_killing_blow: false,
+                overkill_amount: 0,
+            },
+        ];
+        let battle = BattleRecord::test_battle("battle_1", "Fighter1", "Fighter2");
+        storage.move_battle_to_complete(battle, events, Some("Fighter1".to_string())).unwrap();
+        storage.add_pending_battle(BattleRecord::test_battle("battle_2", "Fighter2", "Fighter1")).unwrap();
+        storage.save().unwrap();
+
+        let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        assert!(storage.rename_neopet("Fighter1", "Fighter2").is_err());
+        assert!(storage.rename_neopet("Ghost", "Nova").is_err());
+        storage.rename_neopet("Fighter1", "Nova").unwrap();
+        storage.save().unwrap();
+
+        let reopened = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        assert_eq!(reopened.list_fighters(), ["Nova", "Fighter2"]);
+        let battle = reopened.load_battle("battle_1").unwrap().unwrap();
+        assert_eq!((battle.fighter1_name.as_str(), battle.winner.as_deref()), ("Nova", Some("Nova")));
+        assert!(battle.events.iter().all(|e| !e.involves("Fighter1") && e.involves("Nova")));
+        assert_eq!(battle.hash, Some(battle_hash(&battle.events)));
+        assert_eq!(reopened.pending_battle_records()[0].fighter2_name, "Nova");
+        assert_eq!(reopened.ratings().ratings["Nova"].wins, 1);
+        assert!(!reopened.ratings().ratings.contains_key("Fighter1"));
     }
 
     #[test]
@@ -402,9 +1810,9 @@ mod tests {
         fs::write(&battles_path, "[]").unwrap();
         
         let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
-        let battle = create_test_battle_record("battle_123", "Fighter1", "Fighter2");
+        let battle = BattleRecord::test_battle("battle_123", "Fighter1", "Fighter2");
         
-        storage.add_complete_battle(battle);
+        storage.add_complete_battle(battle).unwrap();
         assert_eq!(storage.list_complete_battles().len(), 1);
     }
 
@@ -420,8 +1828,8 @@ mod tests {
         let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
         
         // Add multiple battles
-        storage.add_complete_battle(create_test_battle_record("battle_1", "Fighter1", "Fighter2"));
-        storage.add_complete_battle(create_test_battle_record("battle_2", "Fighter3", "Fighter4"));
+        storage.add_complete_battle(BattleRecord::test_battle("battle_1", "Fighter1", "Fighter2")).unwrap();
+        storage.add_complete_battle(BattleRecord::test_battle("battle_2", "Fighter3", "Fighter4")).unwrap();
         
         let battles = storage.list_complete_battles();
         assert_eq!(battles.len(), 2);
@@ -442,16 +1850,16 @@ mod tests {
         fs::write(&battles_path, "[]").unwrap();
         
         let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
-        let battle = create_test_battle_record("battle_get_123", "Fighter1", "Fighter2");
-        storage.add_complete_battle(battle);
+        let battle = BattleRecord::test_battle("battle_get_123", "Fighter1", "Fighter2");
+        storage.add_complete_battle(battle).unwrap();
         
         // Should find existing battle
-        let found = storage.get_complete_battle("battle_get_123");
+        let found = storage.get_complete_battle("battle_get_123").unwrap();
         assert!(found.is_some());
         assert_eq!(found.unwrap().id, "battle_get_123");
         
         // Should not find non-existing battle
-        let not_found = storage.get_complete_battle("nonexistent");
+        let not_found = storage.get_complete_battle("nonexistent").unwrap();
         assert!(not_found.is_none());
     }
 
@@ -467,8 +1875,8 @@ mod tests {
         let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
         
         // Add battles
-        storage.add_complete_battle(create_test_battle_record("battle_1", "Fighter1", "Fighter2"));
-        storage.add_complete_battle(create_test_battle_record("battle_2", "Fighter3", "Fighter4"));
+        storage.add_complete_battle(BattleRecord::test_battle("battle_1", "Fighter1", "Fighter2")).unwrap();
+        storage.add_complete_battle(BattleRecord::test_battle("battle_2", "Fighter3", "Fighter4")).unwrap();
         assert_eq!(storage.list_complete_battles().len(), 2);
         
         // Clear battles
@@ -479,9 +1887,9 @@ mod tests {
     #[test]
     fn test_add_pending_battle() {
         let mut storage = create_test_storage();
-        let battle = create_test_battle_record("pending_123", "Fighter1", "Fighter2");
+        let battle = BattleRecord::test_battle("pending_123", "Fighter1", "Fighter2");
         
-        storage.add_pending_battle(battle);
+        storage.add_pending_battle(battle).unwrap();
         let pending = storage.list_pending_battles();
         assert_eq!(pending.len(), 1);
         assert_eq!(pending[0].0, "pending_123");
@@ -497,8 +1905,8 @@ mod tests {
         fs::write(&battles_path, "[]").unwrap();
         
         let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
-        let battle = create_test_battle_record("find_123", "Fighter1", "Fighter2");
-        storage.add_pending_battle(battle);
+        let battle = BattleRecord::test_battle("find_123", "Fighter1", "Fighter2");
+        storage.add_pending_battle(battle).unwrap();
         
         // Should find existing battle
         let found = storage.find_pending_battle("find_123");
@@ -513,8 +1921,8 @@ mod tests {
     #[test]
     fn test_remove_pending_battle() {
         let mut storage = create_test_storage();
-        let battle = create_test_battle_record("remove_123", "Fighter1", "Fighter2");
-        storage.add_pending_battle(battle);
+        let battle = BattleRecord::test_battle("remove_123", "Fighter1", "Fighter2");
+        storage.add_pending_battle(battle).unwrap();
         
         assert_eq!(storage.list_pending_battles().len(), 1);
         
@@ -539,14 +1947,15 @@ mod tests {
         fs::write(&battles_path, "[]").unwrap();
         
         let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
-        let battle = create_test_battle_record("move_123", "Fighter1", "Fighter2");
+        let battle = BattleRecord::test_battle("move_123", "Fighter1", "Fighter2");
         
         // Create some test events
         let events = vec![
             BattleEvent::Roll {
                 turn: 1,
-                actor: "Fighter1".to_string(),
+                actor: "Fighter1".into(),
                 dice: 15,
+                dice_expr: None,
                 final_value: 20,
                 is_positive_crit: false,
                 is_negative_crit: false,
@@ -555,16 +1964,200 @@ mod tests {
         ];
         
         // Move to complete
-        let completed = storage.move_battle_to_complete(battle, events.clone(), Some("Fighter1".to_string()));
+        let completed = storage.move_battle_to_complete(battle, events.clone(), Some("Fighter1".to_string())).unwrap();
         
         assert_eq!(completed.events.len(), 1);
         assert_eq!(completed.winner, Some("Fighter1".to_string()));
         assert!(completed.is_completed);
+        assert_eq!(completed.hash, Some(battle_hash(&events)));
         
         // Should be in complete battles
         assert_eq!(storage.list_complete_battles().len(), 1);
     }
 
+    #[test]
+    fn test_completed_battles_update_persisted_ratings() {
+        let temp_dir = tempdir().unwrap();
+        let neopets_path = temp_dir.path().join("neopets.json");
+        let battles_path = temp_dir.path().join("complete_battles.json");
+        let (neopets_path, battles_path) = (neopets_path.to_str().unwrap(), battles_path.to_str().unwrap());
+
+        let mut storage = Storage::new(neopets_path, battles_path).unwrap();
+        for id in ["battle_1", "battle_2"] {
+            let battle = BattleRecord::test_battle(id, "Fighter1", "Fighter2");
+            storage.move_battle_to_complete(battle, vec![], Some("Fighter1".to_string())).unwrap();
+        }
+        storage.save().unwrap();
+
+        let reloaded = Storage::new(neopets_path, battles_path).unwrap();
+        let fighter1 = &reloaded.ratings().ratings["Fighter1"];
+        let fighter2 = &reloaded.ratings().ratings["Fighter2"];
+        assert_eq!((fighter1.wins, fighter1.losses), (2, 0));
+        assert_eq!((fighter2.wins, fighter2.losses), (0, 2));
+        assert!(fighter1.rating > fighter2.rating);
+    }
+
+    #[test]
+    fn test_missing_ratings_are_rebuilt_from_history() {
+        let temp_dir = tempdir().unwrap();
+        let neopets_path = temp_dir.path().join("neopets.json");
+        let battles_path = temp_dir.path().join("complete_battles.json");
+        let mut battle = BattleRecord::test_battle("battle_1", "Fighter1", "Fighter2");
+        battle.winner = Some("Fighter2".to_string());
+        battle.is_completed = true;
+        fs::write(&battles_path, serde_json::to_string(&vec![battle]).unwrap()).unwrap();
+
+        let storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        assert_eq!(storage.ratings().ratings["Fighter2"].wins, 1);
+    }
+
+    #[test]
+    fn test_stored_spells_fight_with_their_catalog_effect() {
+        use crate::battle::{battle_loop, TickKind};
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+
+        let temp_dir = tempdir().unwrap();
+        let neopets_path = temp_dir.path().join("neopets.json");
+        let battles_path = temp_dir.path().join("complete_battles.json");
+        fs::write(temp_dir.path().join("spells.json"), serde_json::json!([
+            { "name": "Ember", "effect": { "type": "burn", "damage": 3, "turns": 2 }, "cooldown": 3 },
+        ]).to_string()).unwrap();
+        let behavior = |spell_chances: &[f64]| serde_json::json!({
+            "attack_chance": 0.5, "spell_chances": spell_chances, "heal_chance": 0.5 - spell_chances.iter().sum::<f64>(),
+        });
+        fs::write(&neopets_path, serde_json::json!([
+            { "name": "Acara", "health": 100, "heal_delta": 10, "base_attack": 5, "base_defense": 3,
+              "spells": ["Ember"], "behavior": behavior(&[0.5]) },
+            { "name": "Usul", "health": 100, "heal_delta": 10, "base_attack": 5, "base_defense": 3,
+              "spells": [], "behavior": behavior(&[]) },
+        ]).to_string()).unwrap();
+
+        let storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        let acara = storage.get_fighter("Acara").unwrap();
+        assert_eq!(acara.spells[0].effect, serde_json::json!({ "type": "burn", "damage": 3, "turns": 2 }));
+        assert_eq!(acara.spells[0].cooldown, 3);
+
+        let events = battle_loop(acara, storage.get_fighter("Usul").unwrap(), &mut StdRng::seed_from_u64(1)).unwrap();
+        assert!(events.iter().any(|e| matches!(e,
+            BattleEvent::HealthUpdate { source: Some(source), .. } if source.kind == TickKind::Burn && source.spell == "Ember"
+        )));
+        assert!(events.iter().any(|e| matches!(e, BattleEvent::SpellOnCooldown { spell_name, .. } if spell_name == "Ember")));
+    }
+
+    #[test]
+    fn test_spells_missing_from_the_catalog_are_saved_inline() {
+        let temp_dir = tempdir().unwrap();
+        let neopets_path = temp_dir.path().join("neopets.json");
+        let battles_path = temp_dir.path().join("complete_battles.json");
+        fs::write(temp_dir.path().join("spells.json"), serde_json::json!([
+            { "name": "Ember", "effect": { "type": "burn", "damage": 3, "turns": 2 }, "cooldown": 3 },
+        ]).to_string()).unwrap();
+        let hex = serde_json::json!({ "name": "Hex", "effect": { "type": "damage", "amount": 4 }, "cooldown": 2 });
+        fs::write(&neopets_path, serde_json::json!([
+            { "name": "Acara", "health": 100, "heal_delta": 10, "base_attack": 5, "base_defense": 3,
+              "spells": ["Ember", hex], "behavior": { "attack_chance": 0.5, "spell_chances": [0.1, 0.1], "heal_chance": 0.3 } },
+        ]).to_string()).unwrap();
+
+        let storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        storage.flush_all().unwrap();
+        let saved: serde_json::Value = serde_json::from_str(&fs::read_to_string(&neopets_path).unwrap()).unwrap();
+        assert_eq!(saved["neopets"][0]["spells"], serde_json::json!(["Ember", hex]));
+
+        let reloaded = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        let acara = reloaded.get_fighter("Acara").unwrap();
+        assert_eq!(acara.spells[0].cooldown, 3);
+        assert_eq!(acara.spells[1].effect, serde_json::json!({ "type": "damage", "amount": 4 }));
+        assert_eq!(acara.spells[1].cooldown, 2);
+    }
+
+    #[test]
+    fn test_unversioned_battles_are_upgraded_on_load() {
+        let temp_dir = tempdir().unwrap();
+        let neopets_path = temp_dir.path().join("neopets.json");
+        let battles_path = temp_dir.path().join("complete_battles.json");
+        let mut battle = BattleRecord::test_battle("battle_1", "Fighter1", "Fighter2");
+        battle.events = vec![BattleEvent::Heal { turn: 1, actor: "Fighter1".into(), amount: 4 }];
+        let mut v1 = serde_json::to_value(vec![&battle]).unwrap();
+        v1[0]["events"] = serde_json::json!([{ "Heal": { "turn": 1, "actor": "Fighter1", "amount": 4 } }]);
+        fs::write(&battles_path, v1.to_string()).unwrap();
+
+        let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        assert_eq!(storage.get_complete_battle("battle_1").unwrap().unwrap().events, battle.events);
+
+        storage.save().unwrap();
+        let saved = fs::read_to_string(temp_dir.path().join("battle_events").join("battle_1.jsonl")).unwrap();
+        let saved: serde_json::Value = serde_json::from_str(saved.lines().next().unwrap()).unwrap();
+        assert_eq!(saved["version"], crate::event_schema::EVENT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_storage_config_places_every_file() {
+        let temp_dir = tempdir().unwrap();
+        let queue_dir = tempdir().unwrap();
+        let config = StorageConfig {
+            pending_battles: queue_dir.path().join("queue.json"),
+            trash: "deleted/trash.json".into(),
+            ..StorageConfig::in_dir(temp_dir.path())
+        };
+        fs::create_dir(temp_dir.path().join("deleted")).unwrap();
+        let mut storage = Storage::open(&config).unwrap();
+        storage.add_neopet(create_test_neopet("Acara")).unwrap();
+        storage.add_pending_battle(BattleRecord::test_battle("battle_1", "Acara", "Usul")).unwrap();
+        storage.trash_fighter("Acara", "2024-01-01T00:00:00Z").unwrap();
+        storage.save().unwrap();
+
+        assert!(queue_dir.path().join("queue.json").exists());
+        assert!(!temp_dir.path().join("pending_battles.json").exists());
+        assert!(temp_dir.path().join("deleted").join("trash.json").exists());
+        assert!(temp_dir.path().join("neopets.json").exists());
+
+        let reopened = Storage::open(&config).unwrap();
+        assert_eq!(reopened.pending_battle_records()[0].id, "battle_1");
+        assert_eq!(reopened.trash_entries().len(), 1);
+    }
+
+    #[test]
+    fn test_complete_battle_events_load_on_demand() {
+        let temp_dir = tempdir().unwrap();
+        let neopets_path = temp_dir.path().join("neopets.json");
+        let battles_path = temp_dir.path().join("complete_battles.json");
+        let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        for id in ["battle_1", "battle_2"] {
+            let mut battle = BattleRecord::test_battle(id, "Fighter1", "Fighter2");
+            battle.is_completed = true;
+            battle.events = (1..=5).map(|turn| BattleEvent::Heal { turn, actor: "Fighter1".into(), amount: turn }).collect();
+            storage.add_complete_battle(battle).unwrap();
+        }
+        storage.save().unwrap();
+        let saved = fs::read_to_string(&battles_path).unwrap();
+        assert!(!saved.contains("\"events\""), "Only summaries stay in {}", saved);
+
+        let mut reopened = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        assert!(reopened.complete_battle_records().iter().all(|b| b.events.is_empty()));
+        assert_eq!(reopened.event_count("battle_1").unwrap(), 5);
+        let page = reopened.events_page("battle_1", 3, 10).unwrap();
+        assert_eq!(page, vec![
+            BattleEvent::Heal { turn: 4, actor: "Fighter1".into(), amount: 4 },
+            BattleEvent::Heal { turn: 5, actor: "Fighter1".into(), amount: 5 },
+        ]);
+        assert_eq!(reopened.load_battle("battle_1").unwrap().unwrap().events.len(), 5);
+        assert_eq!(reopened.get_complete_battle("battle_2").unwrap().unwrap().events.len(), 5);
+        assert!(reopened.events_page("missing", 0, 10).is_err());
+
+        // Trashed battles keep their events until the trash is purged
+        reopened.trash_battle("battle_1", "2024-01-01T00:00:00Z").unwrap();
+        reopened.save().unwrap();
+        let events_path = temp_dir.path().join("battle_events").join("battle_1.jsonl");
+        assert!(events_path.exists());
+        reopened.restore_from_trash("battle_1").unwrap();
+        assert_eq!(reopened.load_battle("battle_1").unwrap().unwrap().events.len(), 5);
+        reopened.trash_battle("battle_1", "2024-01-01T00:00:00Z").unwrap();
+        reopened.purge_all_trash();
+        reopened.save().unwrap();
+        assert!(!events_path.exists());
+    }
+
     #[test]
     fn test_generate_battle_id() {
         let temp_dir = tempdir().unwrap();
@@ -577,8 +2170,8 @@ mod tests {
         let storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
         
         // Generate multiple IDs
-        let id1 = storage.generate_battle_id();
-        let id2 = storage.generate_battle_id();
+        let id1 = storage.generate_battle_id().unwrap();
+        let id2 = storage.generate_battle_id().unwrap();
         
         // Should be different
         assert_ne!(id1, id2);
@@ -588,17 +2181,626 @@ mod tests {
         assert!(id2.starts_with("battle_"));
     }
 
+    #[test]
+    fn test_find_battles_by_fighter_winner_date_and_status() {
+        let mut storage = create_test_storage();
+        let battles = [
+            ("battle_1", "Acara", "Usul", Some("Acara"), "2024-03-01T10:00:00+00:00"),
+            ("battle_2", "Usul", "Kougra", Some("Usul"), "2024-03-05T10:00:00+00:00"),
+            ("battle_3", "Kougra", "Acara", None, "2024-03-09T10:00:00+00:00"),
+        ];
+        for (id, fighter1, fighter2, winner, created_at) in battles {
+            let mut battle = BattleRecord::test_battle(id, fighter1, fighter2);
+            battle.created_at = created_at.to_string();
+            battle.winner = winner.map(String::from);
+            battle.is_completed = true;
+            storage.add_complete_battle(battle).unwrap();
+        }
+        let mut pending = BattleRecord::test_battle("battle_4", "Acara", "Kougra");
+        pending.created_at = "2024-03-10T10:00:00+00:00".to_string();
+        pending.tags = vec!["grudge-match".to_string()];
+        storage.add_pending_battle(pending).unwrap();
+
+        let ids = |filter: BattleFilter| -> Vec<String> {
+            storage.find_battles(&filter).iter().map(|b| b.id.clone()).collect()
+        };
+        let date = |s: &str| DateTime::parse_from_rfc3339(s).unwrap();
+        assert_eq!(ids(BattleFilter::default()).len(), 4);
+        assert_eq!(ids(BattleFilter::default().fighter("Acara")), ["battle_1", "battle_3", "battle_4"]);
+        assert_eq!(ids(BattleFilter::default().fighter("Acara").completed(true)), ["battle_1", "battle_3"]);
+        assert_eq!(ids(BattleFilter::default().completed(false)), ["battle_4"]);
+        assert_eq!(ids(BattleFilter::default().winner("Usul")), ["battle_2"]);
+        assert_eq!(ids(BattleFilter::default().fighter("Kougra").winner("Acara")), Vec::<String>::new());
+        assert_eq!(
+            ids(BattleFilter::default().since(date("2024-03-05T10:00:00+00:00")).until(date("2024-03-10T00:00:00+00:00"))),
+            ["battle_2", "battle_3"]
+        );
+        assert_eq!(ids(BattleFilter::default().tag("Grudge-Match")), ["battle_4"]);
+    }
+
     #[test]
     fn test_clear_pending_battles() {
         let mut storage = create_test_storage();
         
         // Add pending battles
-        storage.add_pending_battle(create_test_battle_record("pending_1", "Fighter1", "Fighter2"));
-        storage.add_pending_battle(create_test_battle_record("pending_2", "Fighter3", "Fighter4"));
+        storage.add_pending_battle(BattleRecord::test_battle("pending_1", "Fighter1", "Fighter2")).unwrap();
+        storage.add_pending_battle(BattleRecord::test_battle("pending_2", "Fighter3", "Fighter4")).unwrap();
         assert_eq!(storage.list_pending_battles().len(), 2);
         
         // Clear pending battles
         storage.clear_pending_battles();
         assert_eq!(storage.list_pending_battles().len(), 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_inventory_starts_empty() {
+        let storage = create_test_storage();
+        assert!(storage.inventory("Fighter1").is_empty());
+    }
+
+    #[test]
+    fn test_add_items_accumulates() {
+        let mut storage = create_test_storage();
+        let potion = Item {
+            name: "Healing Potion".to_string(),
+            rarity: crate::loot::Rarity::Common,
+        };
+        storage.add_items("Fighter1", vec![potion.clone()]);
+        storage.add_items("Fighter1", vec![potion.clone()]);
+        assert_eq!(storage.inventory("Fighter1"), &[potion.clone(), potion]);
+        assert!(storage.inventory("Fighter2").is_empty());
+    }
+
+    #[test]
+    fn test_inventories_persist_next_to_neopets() {
+        let temp_dir = tempdir().unwrap();
+        let neopets_path = temp_dir.path().join("inventory_neopets.json");
+        let battles_path = temp_dir.path().join("inventory_battles.json");
+        let item = Item {
+            name: "Void Shard".to_string(),
+            rarity: crate::loot::Rarity::Legendary,
+        };
+
+        let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        storage.add_items("Usul", vec![item.clone()]);
+        storage.save().unwrap();
+
+        assert!(temp_dir.path().join("inventories.json").exists());
+        let reloaded = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        assert_eq!(reloaded.inventory("Usul"), &[item]);
+    }
+
+    #[test]
+    fn test_equip_replaces_the_item_in_the_same_slot() {
+        let mut storage = create_test_storage();
+        storage.add_neopet(create_test_neopet("Acara")).unwrap();
+
+        assert_eq!(storage.equip("Acara", "Leather Vest"), Ok(None));
+        assert_eq!(storage.equip("Acara", "Judgement Blade"), Ok(None));
+        assert_eq!(storage.equip("Acara", "Acara Shell Shield"), Ok(Some("Leather Vest".to_string())));
+        assert_eq!(storage.loadout("Acara").len(), 2);
+
+        assert!(storage.equip("Acara", "Excalibur").is_err());
+        assert!(storage.equip("Usul", "Leather Vest").is_err());
+
+        assert_eq!(storage.unequip("Acara", Slot::Weapon), Some("Judgement Blade".to_string()));
+        assert_eq!(storage.unequip("Acara", Slot::Weapon), None);
+        assert_eq!(storage.unequip("Usul", Slot::Armor), None);
+    }
+
+    #[test]
+    fn test_equipped_fighter_has_the_item_bonuses() {
+        let mut storage = create_test_storage();
+        storage.add_neopet(create_test_neopet("Acara")).unwrap();
+        let base = storage.get_fighter("Acara").unwrap().clone();
+
+        storage.equip("Acara", "Acara Shell Shield").unwrap();
+        let equipped = storage.equipped_fighter("Acara").unwrap();
+        assert_eq!(equipped.base_attack, base.base_attack - 1);
+        assert_eq!(equipped.base_defense, base.base_defense + 6);
+        // The stored fighter keeps their base stats
+        assert_eq!(storage.get_fighter("Acara"), Some(&base));
+        assert!(storage.equipped_fighter("Usul").is_err());
+    }
+
+    #[test]
+    fn test_equipment_persists_next_to_neopets() {
+        let temp_dir = tempdir().unwrap();
+        let neopets_path = temp_dir.path().join("neopets.json");
+        let battles_path = temp_dir.path().join("battles.json");
+        fs::copy("assets/items.json", temp_dir.path().join("items.json")).unwrap();
+
+        let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        storage.add_neopet(create_test_neopet("Usul")).unwrap();
+        storage.equip("Usul", "Xweetok Tail Feather").unwrap();
+        storage.save().unwrap();
+
+        let reloaded = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        assert_eq!(reloaded.loadout("Usul").get(&Slot::Trinket).map(String::as_str), Some("Xweetok Tail Feather"));
+        assert_eq!(
+            reloaded.equipped_fighter("Usul").unwrap().heal_delta,
+            reloaded.get_fighter("Usul").unwrap().heal_delta + 5
+        );
+    }
+
+    #[test]
+    fn test_series_persist_next_to_neopets() {
+        let temp_dir = tempdir().unwrap();
+        let neopets_path = temp_dir.path().join("neopets.json");
+        let battles_path = temp_dir.path().join("battles.json");
+
+        let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        storage.add_neopet(create_test_neopet("Usul")).unwrap();
+        storage.add_neopet(create_test_neopet("Acara")).unwrap();
+        let id = storage.generate_series_id().unwrap();
+        assert_eq!(id, "series_1");
+        let series = SeriesRecord::new(&id, "Usul", "Acara", 3, "2024-01-01T00:00:00Z").unwrap();
+        storage.add_series(series.clone()).unwrap();
+        assert!(storage.add_series(series.clone()).is_err());
+        assert!(storage.add_series(SeriesRecord::new("series_2", "Usul", "Kiko", 3, "").unwrap()).is_err());
+        storage.save().unwrap();
+
+        let reloaded = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        assert_eq!(reloaded.series_records(), [series]);
+        assert_eq!(reloaded.generate_series_id().unwrap(), "series_2");
+    }
+
+    #[test]
+    fn test_checkpoints_persist_until_the_battle_completes() {
+        use crate::runner::BattleRunner;
+        use rand::SeedableRng;
+
+        let temp_dir = tempdir().unwrap();
+        let neopets_path = temp_dir.path().join("neopets.json");
+        let battles_path = temp_dir.path().join("battles.json");
+        let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        storage.add_pending_battle(BattleRecord::test_battle("battle_1", "Acara", "Usul")).unwrap();
+
+        let (acara, usul) = (create_test_neopet("Acara"), create_test_neopet("Usul"));
+        let config = BattleConfig::default();
+        let mut rng = CheckpointRng::seed_from_u64(1);
+        let mut runner = BattleRunner::new(&acara, &usul, &config, &mut rng).unwrap();
+        let events: Vec<BattleEvent> = runner.by_ref().take(2).collect();
+        let record = CheckpointRecord {
+            saved_at: "2024-01-01T00:00:00Z".to_string(),
+            seed: 1,
+            fighter1: acara.clone(),
+            fighter2: usul.clone(),
+            config: config.clone(),
+            events,
+            checkpoint: runner.checkpoint().unwrap(),
+        };
+        assert!(storage.save_checkpoint("battle_9", record.clone()).is_err());
+        storage.save_checkpoint("battle_1", record).unwrap();
+        storage.save().unwrap();
+
+        let mut reloaded = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        assert_eq!(reloaded.checkpoint("battle_1").unwrap().checkpoint.turn, 1);
+        let battle = reloaded.remove_pending_battle("battle_1").unwrap();
+        reloaded.move_battle_to_complete(battle, vec![], None).unwrap();
+        assert!(reloaded.checkpoint("battle_1").is_none());
+    }
+
+    #[test]
+    fn test_checkpoints_resume_with_the_spells_they_were_saved_with() {
+        use crate::battle::TickKind;
+        use crate::runner::BattleRunner;
+        use rand::SeedableRng;
+
+        let temp_dir = tempdir().unwrap();
+        let neopets_path = temp_dir.path().join("neopets.json");
+        let battles_path = temp_dir.path().join("battles.json");
+        let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        storage.add_pending_battle(BattleRecord::test_battle("battle_1", "Acara", "Usul")).unwrap();
+
+        let acara = Neopet {
+            spells: vec![Spell {
+                name: "Ember".to_string(),
+                effect: serde_json::json!({ "type": "burn", "damage": 3, "turns": 2 }),
+                cooldown: 3,
+                inline: false,
+            }],
+            behavior: Behavior { spell_chances: vec![0.5], heal_chance: 0.0, ..Behavior::attack_or_heal(0.5) },
+            ..Neopet::test_fighter("Acara")
+        };
+        let usul = create_test_neopet("Usul");
+        let config = BattleConfig::default();
+        let mut rng = CheckpointRng::seed_from_u64(1);
+        let mut runner = BattleRunner::new(&acara, &usul, &config, &mut rng).unwrap();
+        let mut events = vec![runner.next().unwrap()];
+        while runner.checkpoint().is_none() {
+            events.extend(runner.next());
+        }
+        let record = CheckpointRecord {
+            saved_at: "2024-01-01T00:00:00Z".to_string(),
+            seed: 1,
+            fighter1: acara.clone(),
+            fighter2: usul.clone(),
+            config: config.clone(),
+            events,
+            checkpoint: runner.checkpoint().unwrap(),
+        };
+        storage.save_checkpoint("battle_1", record).unwrap();
+        storage.save().unwrap();
+
+        // Not in the (empty) spell catalog, so only the checkpoint knows what Ember does
+        let mut reloaded = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        let mut saved = reloaded.take_checkpoint("battle_1").unwrap();
+        assert_eq!(saved.fighter1.spells[0].effect, acara.spells[0].effect);
+        assert_eq!(saved.fighter1.spells[0].cooldown, 3);
+
+        let events: Vec<BattleEvent> =
+            BattleRunner::resume(&saved.fighter1, &saved.fighter2, &saved.config, &mut saved.checkpoint).unwrap().collect();
+        assert!(events.iter().any(|e| matches!(e,
+            BattleEvent::HealthUpdate { source: Some(source), .. } if source.kind == TickKind::Burn && source.spell == "Ember"
+        )));
+        assert!(events.iter().any(|e| matches!(e, BattleEvent::SpellOnCooldown { spell_name, .. } if spell_name == "Ember")));
+    }
+
+    fn finished_battle(id: &str, winner: &str, winner_final_hp: u32, loser: &str, loser_final_hp: u32) -> BattleRecord {
+        let mut record = BattleRecord::test_battle(id, winner, loser);
+        record.events = vec![BattleEvent::BattleComplete {
+            turn: 5,
+            winner: winner.into(),
+            loser: loser.into(),
+            winner_final_hp,
+            loser_final_hp,
+            completion_reason: crate::battle::BattleCompletionReason::HpDepleted(loser.to_string()),
+        }];
+        record.winner = Some(winner.to_string());
+        record.is_completed = true;
+        record
+    }
+
+    fn hardcore_storage() -> Storage {
+        let mut storage = create_test_storage();
+        storage.add_neopet(create_test_neopet("Acara")).unwrap();
+        storage.add_neopet(create_test_neopet("Usul")).unwrap();
+        storage.start_season(Season {
+            name: "Season 2".to_string(),
+            hardcore: Some(crate::season::HardcoreRules { injury_carry_over: 0.5 }),
+        });
+        storage
+    }
+
+    #[test]
+    fn test_hardcore_injuries_carry_over_until_sitting_out() {
+        let mut storage = hardcore_storage();
+        storage.add_complete_battle(finished_battle("battle_1", "Acara", 80, "Usul", 0)).unwrap();
+        assert_eq!(storage.injury("Acara"), 10);
+        assert_eq!(storage.injury("Usul"), 50);
+        assert_eq!(storage.battle_fighter("Usul").unwrap().health, 50);
+
+        // Usul started the rematch at 50 HP and lost 20 more
+        storage.add_complete_battle(finished_battle("battle_2", "Usul", 30, "Acara", 0)).unwrap();
+        assert_eq!(storage.injury("Usul"), 60);
+        assert_eq!(storage.injury("Acara"), 55);
+
+        assert_eq!(storage.sit_out_round(&["Acara".to_string()]), vec!["Usul".to_string()]);
+        assert_eq!(storage.injury("Usul"), 0);
+        assert_eq!(storage.battle_fighter("Usul").unwrap().health, 100);
+        assert_eq!(storage.injury("Acara"), 55);
+    }
+
+    #[test]
+    fn test_injuries_always_leave_one_hp() {
+        let mut storage = hardcore_storage();
+        storage.start_season(Season {
+            name: "Season 3".to_string(),
+            hardcore: Some(crate::season::HardcoreRules { injury_carry_over: 1.0 }),
+        });
+        storage.add_complete_battle(finished_battle("battle_1", "Acara", 100, "Usul", 0)).unwrap();
+        assert_eq!(storage.injury("Usul"), 99);
+        assert_eq!(storage.battle_fighter("Usul").unwrap().health, 1);
+    }
+
+    #[test]
+    fn test_regular_season_has_no_injuries() {
+        let mut storage = hardcore_storage();
+        storage.add_complete_battle(finished_battle("battle_1", "Acara", 80, "Usul", 0)).unwrap();
+        // A new season heals everyone
+        storage.start_season(Season::default());
+        assert_eq!(storage.injury("Usul"), 0);
+
+        storage.add_complete_battle(finished_battle("battle_2", "Acara", 80, "Usul", 0)).unwrap();
+        assert_eq!(storage.injury("Usul"), 0);
+        assert_eq!(storage.battle_fighter("Usul").unwrap().health, 100);
+    }
+
+    #[test]
+    fn test_battle_fighters_are_handicapped_after_injuries() {
+        let mut storage = hardcore_storage();
+        storage.add_complete_battle(finished_battle("battle_1", "Acara", 80, "Usul", 0)).unwrap();
+        let mut battle = BattleRecord::test_battle("battle_2", "Acara", "Usul");
+        battle.handicap = Handicap::parse(&["fighter2:hp=+50%,attack=-1".to_string()], "Acara", "Usul").unwrap();
+
+        let (acara, usul) = storage.battle_fighters(&battle).unwrap();
+        assert_eq!(acara.health, 90);
+        // Half of what is left after the injury
+        assert_eq!(usul.health, 75);
+        assert_eq!(usul.base_attack, create_test_neopet("Usul").base_attack - 1);
+    }
+
+    #[test]
+    fn test_battles_level_fighters_up() {
+        let temp_dir = tempdir().unwrap();
+        let neopets_path = temp_dir.path().join("neopets.json");
+        let battles_path = temp_dir.path().join("battles.json");
+        let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        storage.add_neopet(create_test_neopet("Acara")).unwrap();
+        storage.add_neopet(create_test_neopet("Usul")).unwrap();
+        storage.add_complete_battle(finished_battle("battle_1", "Acara", 80, "Usul", 0)).unwrap();
+        assert_eq!(storage.experience("Acara"), 50);
+        assert_eq!(storage.experience("Usul"), 20);
+        assert_eq!(storage.level("Acara"), 1);
+
+        storage.add_complete_battle(finished_battle("battle_2", "Acara", 80, "Usul", 0)).unwrap();
+        assert_eq!(storage.level("Acara"), 2);
+        assert_eq!(storage.progress("Usul"), Progress { level: 1, xp: 40, next_level_xp: Some(100) });
+
+        // Battles are fought at the leveled stats, the stored fighter stays as created
+        let base = create_test_neopet("Acara");
+        assert_eq!(storage.battle_fighter("Acara").unwrap().health, base.health + 5);
+        assert_eq!(storage.equipped_fighter("Acara").unwrap().base_attack, base.base_attack + 1);
+        assert_eq!(storage.get_fighter("Acara"), Some(&base));
+
+        storage.save().unwrap();
+        let reloaded = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        assert_eq!(reloaded.experience("Acara"), 100);
+        assert_eq!(reloaded.level("Acara"), 2);
+    }
+
+    #[test]
+    fn test_trash_and_restore_fighter() {
+        let mut storage = create_test_storage();
+        storage.add_neopet(create_test_neopet("Acara")).unwrap();
+
+        storage.trash_fighter("Acara", "2024-01-01T00:00:00Z").unwrap();
+        assert!(storage.get_fighter("Acara").is_none());
+        assert_eq!(storage.trash_entries().len(), 1);
+        assert_eq!(storage.trash_entries()[0].key(), "Acara");
+
+        storage.restore_from_trash("Acara").unwrap();
+        assert!(storage.get_fighter("Acara").is_some());
+        assert!(storage.trash_entries().is_empty());
+    }
+
+    #[test]
+    fn test_trash_missing_fighter_or_battle_fails() {
+        let mut storage = create_test_storage();
+        assert!(storage.trash_fighter("Ghost", "2024-01-01T00:00:00Z").is_err());
+        assert!(storage.trash_battle("battle_404", "2024-01-01T00:00:00Z").is_err());
+        assert!(storage.restore_from_trash("Ghost").is_err());
+    }
+
+    #[test]
+    fn test_restore_battle_returns_to_original_list() {
+        let mut storage = create_test_storage();
+        storage.add_pending_battle(BattleRecord::test_battle("pending_1", "Fighter1", "Fighter2")).unwrap();
+        storage.add_complete_battle(BattleRecord::test_battle("complete_1", "Fighter1", "Fighter2")).unwrap();
+
+        storage.trash_battle("pending_1", "2024-01-01T00:00:00Z").unwrap();
+        storage.trash_battle("complete_1", "2024-01-01T00:00:00Z").unwrap();
+        assert!(storage.list_pending_battles().is_empty());
+        assert!(storage.list_complete_battles().is_empty());
+
+        storage.restore_from_trash("pending_1").unwrap();
+        storage.restore_from_trash("complete_1").unwrap();
+        assert_eq!(storage.list_pending_battles()[0].0, "pending_1");
+        assert_eq!(storage.list_complete_battles()[0].0, "complete_1");
+    }
+
+    #[test]
+    fn test_restore_refuses_to_overwrite_existing_fighter() {
+        let mut storage = create_test_storage();
+        storage.add_neopet(create_test_neopet("Acara")).unwrap();
+        storage.trash_fighter("Acara", "2024-01-01T00:00:00Z").unwrap();
+        storage.add_neopet(create_test_neopet("Acara")).unwrap();
+
+        assert!(storage.restore_from_trash("Acara").is_err());
+        assert_eq!(storage.trash_entries().len(), 1);
+    }
+
+    #[test]
+    fn test_trash_all_battles() {
+        let mut storage = create_test_storage();
+        storage.add_pending_battle(BattleRecord::test_battle("pending_1", "Fighter1", "Fighter2")).unwrap();
+        storage.add_complete_battle(BattleRecord::test_battle("complete_1", "Fighter1", "Fighter2")).unwrap();
+
+        assert_eq!(storage.trash_all_battles("2024-01-01T00:00:00Z"), 2);
+        assert!(storage.list_pending_battles().is_empty());
+        assert!(storage.list_complete_battles().is_empty());
+        assert_eq!(storage.trash_entries().len(), 2);
+    }
+
+    #[test]
+    fn test_expire_pending_removes_only_stale_battles() {
+        let mut storage = create_test_storage();
+        let mut battle = |id: &str, created_at: &str| {
+            let battle = BattleRecord { created_at: created_at.to_string(), ..BattleRecord::test_battle(id, "Fighter1", "Fighter2") };
+            storage.add_pending_battle(battle).unwrap();
+        };
+        battle("stale", "2024-01-01T00:00:00Z");
+        battle("fresh", "2024-01-10T00:00:00Z");
+        battle("unreadable", "last tuesday");
+
+        let before = DateTime::parse_from_rfc3339("2024-01-03T00:00:00Z").unwrap();
+        assert_eq!(storage.expire_pending(before), ["stale"]);
+        let left: Vec<&str> = storage.pending_battle_records().iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(left, ["fresh", "unreadable"]);
+        assert!(storage.expire_pending(before).is_empty());
+    }
+
+    #[test]
+    fn test_scheduled_battle_counts_down_until_due() {
+        let now = DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z").unwrap();
+        let mut battle = BattleRecord::test_battle("battle_1", "Fighter1", "Fighter2");
+        assert!(battle.is_due(now));
+
+        battle.scheduled_at = Some("2024-01-01T14:30:00+01:00".to_string());
+        assert_eq!(battle.due_in(now), Some(Duration::minutes(90)));
+        assert!(!battle.is_due(now));
+        assert!(battle.is_due(now + Duration::minutes(90)));
+
+        battle.scheduled_at = Some("at dawn".to_string());
+        assert!(battle.is_due(now));
+    }
+
+    #[test]
+    fn test_due_battles_are_the_scheduled_ones_whose_time_came() {
+        let mut storage = create_test_storage();
+        for id in ["unscheduled", "later", "soon", "sooner"] {
+            storage.add_pending_battle(BattleRecord::test_battle(id, "Fighter1", "Fighter2")).unwrap();
+        }
+        let now = DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z").unwrap();
+        storage.schedule_battle("later", now + Duration::hours(1)).unwrap();
+        storage.schedule_battle("soon", now - Duration::minutes(5)).unwrap();
+        storage.schedule_battle("sooner", now - Duration::minutes(10)).unwrap();
+        assert!(storage.schedule_battle("battle_9", now).is_err());
+
+        let due: Vec<&str> = storage.due_battles(now).iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(due, ["sooner", "soon"]);
+        let due: Vec<&str> = storage.due_battles(now + Duration::hours(1)).iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(due, ["sooner", "soon", "later"]);
+    }
+
+    #[test]
+    fn test_purge_expired_trash_keeps_recent_entries() {
+        let mut storage = create_test_storage();
+        storage.add_pending_battle(BattleRecord::test_battle("old", "Fighter1", "Fighter2")).unwrap();
+        storage.add_pending_battle(BattleRecord::test_battle("recent", "Fighter1", "Fighter2")).unwrap();
+        storage.trash_battle("old", "2024-01-01T00:00:00Z").unwrap();
+        storage.trash_battle("recent", "2024-01-25T00:00:00Z").unwrap();
+
+        let now = DateTime::parse_from_rfc3339("2024-02-01T00:00:00Z").unwrap();
+        let purged = storage.purge_expired_trash(now, Duration::days(TRASH_RETENTION_DAYS));
+        assert_eq!(purged, 1);
+        assert_eq!(storage.trash_entries()[0].key(), "recent");
+
+        assert_eq!(storage.purge_all_trash(), 1);
+        assert!(storage.trash_entries().is_empty());
+    }
+
+    #[test]
+    fn test_trash_persists_next_to_neopets() {
+        let temp_dir = tempdir().unwrap();
+        let neopets_path = temp_dir.path().join("trash_neopets.json");
+        let battles_path = temp_dir.path().join("trash_battles.json");
+
+        let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        storage.add_neopet(create_test_neopet("Usul")).unwrap();
+        storage.trash_fighter("Usul", "2024-01-01T00:00:00Z").unwrap();
+        storage.save().unwrap();
+
+        assert!(temp_dir.path().join("trash.json").exists());
+        let reloaded = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        assert_eq!(reloaded.trash_entries().len(), 1);
+        assert_eq!(reloaded.trash_entries()[0].kind(), "fighter");
+    }
+
+    #[test]
+    fn test_save_only_rewrites_what_changed() {
+        let temp_dir = tempdir().unwrap();
+        let neopets_path = temp_dir.path().join("neopets.json");
+        let battles_path = temp_dir.path().join("complete_battles.json");
+        let mut storage = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        storage.add_neopet(create_test_neopet("Acara")).unwrap();
+        storage.flush_all().unwrap();
+        assert!(!storage.has_unsaved_changes());
+
+        // Files no save touches again unless their data changes
+        let series_path = temp_dir.path().join("series.json");
+        fs::write(&series_path, "untouched").unwrap();
+        fs::write(&battles_path, "untouched").unwrap();
+
+        storage.add_neopet(create_test_neopet("Usul")).unwrap();
+        assert!(storage.has_unsaved_changes());
+        storage.save().unwrap();
+        assert!(!storage.has_unsaved_changes());
+        assert!(fs::read_to_string(&neopets_path).unwrap().contains("Usul"));
+        assert_eq!(fs::read_to_string(&series_path).unwrap(), "untouched");
+        assert_eq!(fs::read_to_string(&battles_path).unwrap(), "untouched");
+
+        storage.flush_all().unwrap();
+        let reloaded = Storage::new(neopets_path.to_str().unwrap(), battles_path.to_str().unwrap()).unwrap();
+        assert_eq!(reloaded.list_fighters().len(), 2);
+        assert!(reloaded.series_records().is_empty());
+    }
+
+    #[test]
+    fn test_battle_ids_are_sortable_and_unique() {
+        let mut storage = create_test_storage();
+        let first = storage.generate_battle_id().unwrap();
+        let second = storage.generate_battle_id().unwrap();
+        assert!(first.starts_with("battle_") && first < second);
+
+        // Older IDs are kept as they are
+        storage.add_complete_battle(BattleRecord::test_battle("battle_1", "Fighter1", "Fighter2")).unwrap();
+        storage.add_pending_battle(BattleRecord::test_battle("battle_1764730969489383863", "Fighter1", "Fighter2")).unwrap();
+        storage.add_pending_battle(BattleRecord::test_battle(&first, "Fighter1", "Fighter2")).unwrap();
+        storage.add_pending_battle(BattleRecord::test_battle("battle_9", "Fighter1", "Fighter2")).unwrap();
+        storage.trash_battle("battle_9", "2024-01-01T00:00:00Z").unwrap();
+
+        for id in ["battle_1", "battle_1764730969489383863", "battle_9"] {
+            let duplicate = BattleRecord::test_battle(id, "Fighter3", "Fighter4");
+            assert!(storage.add_pending_battle(duplicate.clone()).unwrap_err().contains(id));
+            assert!(storage.add_complete_battle(duplicate).is_err());
+        }
+
+        // Completing a pending battle moves it, unless a complete one has its ID
+        storage.move_battle_to_complete(BattleRecord::test_battle(&first, "Fighter1", "Fighter2"), vec![], None).unwrap();
+        assert!(storage.find_pending_battle(&first).is_none());
+        storage.add_pending_battle(BattleRecord::test_battle(&second, "Fighter1", "Fighter2")).unwrap();
+        storage.add_complete_battle(BattleRecord::test_battle("battle_2", "Fighter1", "Fighter2")).unwrap();
+        let clash = BattleRecord::test_battle("battle_2", "Fighter1", "Fighter2");
+        assert!(storage.move_battle_to_complete(clash, vec![], None).is_err());
+        assert_eq!(storage.complete_battle_records().len(), 3);
+    }
+
+    #[test]
+    fn test_annotate_skips_duplicate_and_blank_tags() {
+        let mut battle = BattleRecord::test_battle("battle_1", "Fighter1", "Fighter2");
+        battle.annotate(&["grudge-match".to_string(), " ".to_string()], &["rematch".to_string()]);
+        battle.annotate(&["Grudge-Match".to_string(), "finals".to_string()], &["".to_string()]);
+
+        assert_eq!(battle.tags, vec!["grudge-match", "finals"]);
+        assert_eq!(battle.notes, vec!["rematch"]);
+        assert!(battle.has_tag("GRUDGE-MATCH"));
+        assert!(!battle.has_tag("friendly"));
+    }
+
+    #[test]
+    fn test_annotate_battle_finds_pending_and_complete() {
+        let mut storage = create_test_storage();
+        storage.add_pending_battle(BattleRecord::test_battle("pending_1", "Fighter1", "Fighter2")).unwrap();
+        storage.add_complete_battle(BattleRecord::test_battle("complete_1", "Fighter1", "Fighter2")).unwrap();
+
+        storage.annotate_battle("pending_1", &["grudge-match".to_string()], &[]).unwrap();
+        storage.annotate_battle("complete_1", &[], &["season 1 final".to_string()]).unwrap();
+        assert!(storage.annotate_battle("battle_404", &[], &[]).is_err());
+
+        assert!(storage.find_battle("pending_1").unwrap().has_tag("grudge-match"));
+        assert_eq!(storage.find_battle("complete_1").unwrap().notes, vec!["season 1 final"]);
+        assert!(storage.find_battle("battle_404").is_none());
+    }
+
+    #[test]
+    fn test_battle_record_without_tags_deserializes() {
+        let json = r#"{"id":"battle_1","fighter1_name":"A","fighter2_name":"B","created_at":"2023-01-01T00:00:00Z","events":[],"winner":null,"is_completed":false}"#;
+        let battle: BattleRecord = serde_json::from_str(json).unwrap();
+        assert!(battle.tags.is_empty());
+        assert!(battle.notes.is_empty());
+        assert!(battle.seed.is_none());
+        let json = serde_json::to_string(&battle).unwrap();
+        assert!(!json.contains("tags"));
+        assert!(!json.contains("seed"));
+    }
+
+    #[test]
+    fn test_battle_seed_round_trips() {
+        let mut battle = BattleRecord::test_battle("battle_1", "A", "B");
+        battle.seed = Some(u64::MAX);
+        let json = serde_json::to_string(&battle).unwrap();
+        assert_eq!(serde_json::from_str::<BattleRecord>(&json).unwrap().seed, Some(u64::MAX));
+    }
+}
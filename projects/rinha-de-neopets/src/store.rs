@@ -0,0 +1,486 @@
+// src/store.rs
+use crate::battle::BattleEvent;
+use crate::event_schema::lines;
+use crate::migrations::{self, AssetFile};
+use crate::neopets::{Neopet, load_neopets};
+use crate::storage::BattleRecord;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Environment variable picking the backend, `json` or `sqlite`. It wins over `storage.json`.
+pub const BACKEND_ENV: &str = "RINHA_STORAGE";
+
+/// What a `BattleStore` writes on `save`.
+pub struct StoreSnapshot<'a> {
+    pub fighters: &'a [Neopet],
+    /// Complete battles, those with events in memory having them written too.
+    pub complete_battles: &'a [BattleRecord],
+    pub pending_battles: &'a [BattleRecord],
+    /// Battles whose stored events are kept; the others' are removed.
+    pub kept_events: HashSet<&'a str>,
+    /// What changed since the last save. The rest the store already has as it is, and
+    /// doesn't write again.
+    pub changed: StoreChanges<'a>,
+}
+
+/// The parts of a `StoreSnapshot` a store writes on `save`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StoreChanges<'a> {
+    pub fighters: bool,
+    pub complete_battles: bool,
+    pub pending_battles: bool,
+    /// Complete battles whose events are written, from those they have in memory.
+    pub events: HashSet<&'a str>,
+    /// Whether battles may have left `kept_events`, their stored events to be removed.
+    pub removed_events: bool,
+}
+
+impl<'a> StoreChanges<'a> {
+    /// Everything, with the events of every battle in `complete_battles` that has them
+    /// in memory.
+    pub fn everything(complete_battles: &'a [BattleRecord]) -> Self {
+        Self {
+            fighters: true,
+            complete_battles: true,
+            pending_battles: true,
+            events: complete_battles.iter().filter(|b| !b.events.is_empty()).map(|b| b.id.as_str()).collect(),
+            removed_events: true,
+        }
+    }
+}
+
+/// Where `Storage` keeps the fighters, the battles and their events. Everything else
+/// (inventories, ratings, the trash, ...) stays in the JSON files `StorageConfig` names.
+pub trait BattleStore: Send {
+    fn load_fighters(&self) -> Result<Vec<Neopet>, Box<dyn std::error::Error>>;
+
+    /// Complete battles, with their events left out unless saved inline by an older build.
+    fn load_complete_battles(&self) -> Result<Vec<BattleRecord>, Box<dyn std::error::Error>>;
+
+    fn load_pending_battles(&self) -> Result<Vec<BattleRecord>, Box<dyn std::error::Error>>;
+
+    /// Up to `limit` stored events of a complete battle starting at the `offset`-th one.
+    fn read_events(&self, battle_id: &str, offset: usize, limit: usize) -> Result<Vec<BattleEvent>, Box<dyn std::error::Error>>;
+
+    fn count_events(&self, battle_id: &str) -> Result<usize, Box<dyn std::error::Error>>;
+
+    fn save(&self, snapshot: &StoreSnapshot) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// The default backend: `neopets.json`, `complete_battles.json` (summaries only) and
+/// `pending_battles.json`, with each complete battle's events in `battle_events/<ID>.jsonl`.
+/// With `compress_battles` set the complete battles and their events are written gzipped
+/// instead, as `complete_battles.json.gz` and `<ID>.jsonl.gz`; either kind loads.
+pub struct JsonStore {
+    neopets_path: PathBuf,
+    complete_battles_path: PathBuf,
+    pending_battles_path: PathBuf,
+    events_dir: PathBuf,
+    compress: bool,
+}
+
+impl JsonStore {
+    /// The files `config` names, whatever its backend.
+    pub fn new(config: &StorageConfig) -> Self {
+        Self {
+            neopets_path: config.path(&config.neopets),
+            complete_battles_path: config.path(&config.complete_battles),
+            pending_battles_path: config.path(&config.pending_battles),
+            events_dir: config.path(&config.battle_events),
+            compress: config.compress_battles,
+        }
+    }
+
+    fn events_path(&self, battle_id: &str) -> PathBuf {
+        self.events_dir.join(format!("{}.jsonl", battle_id))
+    }
+
+    fn load_battles(path: &Path) -> Result<Vec<BattleRecord>, Box<dyn std::error::Error>> {
+        match open_file(path)? {
+            Some(reader) => migrations::read(AssetFile::Battles, reader),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// `path` with `.gz` on the end, where its compressed version is kept.
+fn gz_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".gz");
+    PathBuf::from(name)
+}
+
+/// Opens the compressed version of `path` if there is one and `path` itself otherwise,
+/// `None` if neither exists.
+fn open_file(path: &Path) -> Result<Option<Box<dyn BufRead>>, Box<dyn std::error::Error>> {
+    let compressed = gz_path(path);
+    if compressed.exists() {
+        #[cfg(feature = "compression")]
+        return Ok(Some(Box::new(BufReader::new(flate2::read::GzDecoder::new(File::open(compressed)?)))));
+        #[cfg(not(feature = "compression"))]
+        return Err(format!("Can't read {}, enable the `compression` feature", compressed.display()).into());
+    }
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(Box::new(BufReader::new(File::open(path)?))))
+}
+
+/// Writes `path`, or its compressed version if `compress`, through `write`, then
+/// removes the other one so it can't shadow or outlive what was written.
+fn write_file(
+    path: &Path,
+    compress: bool,
+    write: impl FnOnce(&mut dyn Write) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (target, other) = if compress { (gz_path(path), path.to_path_buf()) } else { (path.to_path_buf(), gz_path(path)) };
+    if compress {
+        #[cfg(feature = "compression")]
+        {
+            let file = BufWriter::new(File::create(&target)?);
+            let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            write(&mut encoder)?;
+            encoder.finish()?.flush()?;
+        }
+        #[cfg(not(feature = "compression"))]
+        return Err(format!("Can't write {}, enable the `compression` feature", target.display()).into());
+    } else {
+        let mut file = BufWriter::new(File::create(&target)?);
+        write(&mut file)?;
+        file.flush()?;
+    }
+    if other.exists() {
+        fs::remove_file(other)?;
+    }
+    Ok(())
+}
+
+/// The battle ID of a file in the events directory, `None` if it isn't an events file.
+fn events_file_id(path: &Path) -> Option<&str> {
+    let name = path.file_name()?.to_str()?;
+    name.strip_suffix(".jsonl.gz").or_else(|| name.strip_suffix(".jsonl"))
+}
+
+impl BattleStore for JsonStore {
+    fn load_fighters(&self) -> Result<Vec<Neopet>, Box<dyn std::error::Error>> {
+        Ok(if self.neopets_path.exists() { load_neopets(&self.neopets_path.to_string_lossy()) } else { Vec::new() })
+    }
+
+    fn load_complete_battles(&self) -> Result<Vec<BattleRecord>, Box<dyn std::error::Error>> {
+        Self::load_battles(&self.complete_battles_path)
+    }
+
+    fn load_pending_battles(&self) -> Result<Vec<BattleRecord>, Box<dyn std::error::Error>> {
+        Self::load_battles(&self.pending_battles_path)
+    }
+
+    fn read_events(&self, battle_id: &str, offset: usize, limit: usize) -> Result<Vec<BattleEvent>, Box<dyn std::error::Error>> {
+        match open_file(&self.events_path(battle_id))? {
+            Some(reader) => Ok(lines::read(reader, offset, limit)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn count_events(&self, battle_id: &str) -> Result<usize, Box<dyn std::error::Error>> {
+        match open_file(&self.events_path(battle_id))? {
+            Some(reader) => Ok(lines::count(reader)?),
+            None => Ok(0),
+        }
+    }
+
+    fn save(&self, snapshot: &StoreSnapshot) -> Result<(), Box<dyn std::error::Error>> {
+        let changed = &snapshot.changed;
+        if changed.fighters {
+            let neopets_file = File::create(&self.neopets_path)?;
+            migrations::write(AssetFile::Neopets, &snapshot.fighters, BufWriter::new(neopets_file))?;
+        }
+
+        if changed.complete_battles {
+            let summaries: Vec<BattleRecord> = snapshot.complete_battles.iter().map(BattleRecord::summary).collect();
+            write_file(&self.complete_battles_path, self.compress, |writer| {
+                migrations::write(AssetFile::Battles, &summaries, writer)
+            })?;
+        }
+
+        if changed.pending_battles {
+            let pending_battles_file = File::create(&self.pending_battles_path)?;
+            migrations::write(AssetFile::Battles, &snapshot.pending_battles, BufWriter::new(pending_battles_file))?;
+        }
+
+        let written = snapshot.complete_battles.iter()
+            .filter(|b| !b.events.is_empty() && changed.events.contains(b.id.as_str()));
+        for battle in written {
+            fs::create_dir_all(&self.events_dir)?;
+            write_file(&self.events_path(&battle.id), self.compress, |writer| lines::write(&battle.events, writer))?;
+        }
+        if !changed.removed_events || !self.events_dir.is_dir() {
+            return Ok(());
+        }
+        for entry in fs::read_dir(&self.events_dir)? {
+            let path = entry?.path();
+            let stale = events_file_id(&path).is_some_and(|id| !snapshot.kept_events.contains(id));
+            if stale {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A backend keeping everything in memory, for tests and throwaway runs: what is saved
+/// to it loads back for as long as it lives, and nothing touches the disk.
+#[derive(Default)]
+pub struct MemoryStore {
+    saved: Mutex<MemoryState>,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    fighters: Vec<Neopet>,
+    complete_battles: Vec<BattleRecord>,
+    pending_battles: Vec<BattleRecord>,
+    events: HashMap<String, Vec<BattleEvent>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MemoryState> {
+        self.saved.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl BattleStore for MemoryStore {
+    fn load_fighters(&self) -> Result<Vec<Neopet>, Box<dyn std::error::Error>> {
+        Ok(self.state().fighters.clone())
+    }
+
+    fn load_complete_battles(&self) -> Result<Vec<BattleRecord>, Box<dyn std::error::Error>> {
+        Ok(self.state().complete_battles.clone())
+    }
+
+    fn load_pending_battles(&self) -> Result<Vec<BattleRecord>, Box<dyn std::error::Error>> {
+        Ok(self.state().pending_battles.clone())
+    }
+
+    fn read_events(&self, battle_id: &str, offset: usize, limit: usize) -> Result<Vec<BattleEvent>, Box<dyn std::error::Error>> {
+        let state = self.state();
+        let events = state.events.get(battle_id).map(Vec::as_slice).unwrap_or_default();
+        Ok(events.iter().skip(offset).take(limit).cloned().collect())
+    }
+
+    fn count_events(&self, battle_id: &str) -> Result<usize, Box<dyn std::error::Error>> {
+        Ok(self.state().events.get(battle_id).map_or(0, Vec::len))
+    }
+
+    fn save(&self, snapshot: &StoreSnapshot) -> Result<(), Box<dyn std::error::Error>> {
+        let changed = &snapshot.changed;
+        let mut state = self.state();
+        if changed.fighters {
+            state.fighters = snapshot.fighters.to_vec();
+        }
+        if changed.complete_battles {
+            state.complete_battles = snapshot.complete_battles.iter().map(BattleRecord::summary).collect();
+        }
+        if changed.pending_battles {
+            state.pending_battles = snapshot.pending_battles.to_vec();
+        }
+        let written = snapshot.complete_battles.iter()
+            .filter(|b| !b.events.is_empty() && changed.events.contains(b.id.as_str()));
+        for battle in written {
+            state.events.insert(battle.id.clone(), battle.events.clone());
+        }
+        if changed.removed_events {
+            state.events.retain(|id, _| snapshot.kept_events.contains(id.as_str()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Backend {
+    #[default]
+    Json,
+    Sqlite,
+}
+
+impl std::str::FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(Backend::Json),
+            "sqlite" => Ok(Backend::Sqlite),
+            other => Err(format!("Unknown storage backend '{}', expected json or sqlite", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Backend::Json => "json",
+            Backend::Sqlite => "sqlite",
+        })
+    }
+}
+
+/// Where `Storage` keeps its files and which backend holds the fighters and battles,
+/// stored in the data directory's `storage.json`. Relative paths are relative to
+/// `data_dir`; every file is in it under its usual name unless set otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// The directory `storage.json` is read from.
+    #[serde(skip)]
+    pub data_dir: PathBuf,
+    pub backend: Backend,
+    pub sqlite_path: PathBuf,
+    pub neopets: PathBuf,
+    pub complete_battles: PathBuf,
+    pub pending_battles: PathBuf,
+    /// Directory of the JSON backend's `<battle ID>.jsonl` event files.
+    pub battle_events: PathBuf,
+    /// Whether the JSON backend gzips the complete battles and their events.
+    pub compress_battles: bool,
+    /// Directory of the `<battle ID>.jsonl` journals of battles being fought, see `journal`.
+    pub journals: PathBuf,
+    pub inventories: PathBuf,
+    pub items: PathBuf,
+    /// The spell catalog the fighters' spells are looked up in, see `spells`.
+    pub spells: PathBuf,
+    pub equipment: PathBuf,
+    pub season: PathBuf,
+    pub injuries: PathBuf,
+    pub series: PathBuf,
+    pub trash: PathBuf,
+    pub ratings: PathBuf,
+    pub rating_rules: PathBuf,
+    pub checkpoints: PathBuf,
+    pub experience: PathBuf,
+    pub leveling_rules: PathBuf,
+    pub ids: PathBuf,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self::in_dir("assets")
+    }
+}
+
+impl StorageConfig {
+    /// Every file in `data_dir` under its usual name, kept as JSON.
+    pub fn in_dir(data_dir: impl AsRef<Path>) -> Self {
+        Self {
+            data_dir: data_dir.as_ref().to_path_buf(),
+            backend: Backend::Json,
+            sqlite_path: "rinha.db".into(),
+            neopets: "neopets.json".into(),
+            complete_battles: "complete_battles.json".into(),
+            pending_battles: "pending_battles.json".into(),
+            battle_events: "battle_events".into(),
+            compress_battles: false,
+            journals: "journals".into(),
+            inventories: "inventories.json".into(),
+            items: "items.json".into(),
+            spells: "spells.json".into(),
+            equipment: "equipment.json".into(),
+            season: "season.json".into(),
+            injuries: "injuries.json".into(),
+            series: "series.json".into(),
+            trash: "trash.json".into(),
+            ratings: "ratings.json".into(),
+            rating_rules: "rating_rules.json".into(),
+            checkpoints: "checkpoints.json".into(),
+            experience: "experience.json".into(),
+            leveling_rules: "leveling_rules.json".into(),
+            ids: "ids.json".into(),
+        }
+    }
+
+    /// The config in `data_dir`'s `storage.json`, or `in_dir` if it has none, with the
+    /// backend overridden by `RINHA_STORAGE` when set.
+    pub fn load(data_dir: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let data_dir = data_dir.as_ref();
+        let path = data_dir.join("storage.json");
+        let mut config = if path.exists() {
+            let file = File::open(path)?;
+            let reader = BufReader::new(file);
+            Self { data_dir: data_dir.to_path_buf(), ..serde_json::from_reader(reader)? }
+        } else {
+            Self::in_dir(data_dir)
+        };
+        if let Ok(backend) = std::env::var(BACKEND_ENV) {
+            config.backend = backend.parse()?;
+        }
+        Ok(config)
+    }
+
+    /// Writes the config to `storage.json` in the data directory.
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(self.data_dir.join("storage.json"))?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// `file` (one of the paths above) resolved against the data directory.
+    pub fn path(&self, file: &Path) -> PathBuf {
+        self.data_dir.join(file)
+    }
+
+    /// Bytes the JSON backend's complete battles and their events take on disk, compressed
+    /// or not.
+    pub fn complete_battles_size(&self) -> std::io::Result<u64> {
+        let path = self.path(&self.complete_battles);
+        let mut files = vec![gz_path(&path), path];
+        let events_dir = self.path(&self.battle_events);
+        if events_dir.is_dir() {
+            for entry in fs::read_dir(events_dir)? {
+                files.push(entry?.path());
+            }
+        }
+        let sizes = files.iter().filter(|file| file.is_file()).map(|file| Ok(fs::metadata(file)?.len()));
+        sizes.sum()
+    }
+
+    /// Opens the configured backend.
+    pub fn open(&self) -> Result<Box<dyn BattleStore>, Box<dyn std::error::Error>> {
+        match self.backend {
+            Backend::Json => Ok(Box::new(JsonStore::new(self))),
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite => Ok(Box::new(crate::sqlite::SqliteStore::open(self.path(&self.sqlite_path))?)),
+            #[cfg(not(feature = "sqlite"))]
+            Backend::Sqlite => Err("This build has no SQLite storage, enable the `sqlite` feature".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_config_defaults_to_json() {
+        let temp_dir = tempdir().unwrap();
+        let config = StorageConfig { backend: Backend::Sqlite, ..StorageConfig::in_dir(temp_dir.path()) };
+        config.save().unwrap();
+        let saved: StorageConfig = serde_json::from_str(&fs::read_to_string(temp_dir.path().join("storage.json")).unwrap()).unwrap();
+        assert_eq!(StorageConfig { data_dir: config.data_dir.clone(), ..saved }, config);
+
+        let partial: StorageConfig = serde_json::from_str(r#"{ "pending_battles": "/queue/pending.json" }"#).unwrap();
+        assert_eq!(partial.backend, Backend::Json);
+        assert_eq!(partial.path(&partial.neopets), Path::new("assets/neopets.json"));
+        assert_eq!(partial.path(&partial.pending_battles), Path::new("/queue/pending.json"));
+        assert_eq!("SQLite".parse::<Backend>().unwrap(), Backend::Sqlite);
+        assert!("postgres".parse::<Backend>().is_err());
+    }
+}
@@ -0,0 +1,300 @@
+// src/strategy.rs
+use crate::battle::{choose_action, Action, BattleEvent, BattleState};
+use crate::neopets::Neopet;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// An action a fighter took, in the order the battle played out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TakenAction {
+    pub turn: u32,
+    pub actor: String,
+    pub action: Action,
+}
+
+/// Everything a strategy can see when picking its next action: both fighters, the
+/// current state and a read-only window over the most recent events and actions.
+#[derive(Debug, Clone, Copy)]
+pub struct BattleContext<'a> {
+    pub actor: &'a Neopet,
+    pub opponent: &'a Neopet,
+    pub turn: u32,
+    pub state: &'a BattleState,
+    events: &'a [BattleEvent],
+    actions: &'a [TakenAction],
+}
+
+impl<'a> BattleContext<'a> {
+    /// `events` and `actions` are the whole battle so far, only the last `window`
+    /// of each are exposed.
+    pub fn new(
+        actor: &'a Neopet,
+        opponent: &'a Neopet,
+        turn: u32,
+        state: &'a BattleState,
+        events: &'a [BattleEvent],
+        actions: &'a [TakenAction],
+        window: usize,
+    ) -> Self {
+        Self {
+            actor,
+            opponent,
+            turn,
+            state,
+            events: &events[events.len().saturating_sub(window)..],
+            actions: &actions[actions.len().saturating_sub(window)..],
+        }
+    }
+
+    /// The most recent events, oldest first.
+    pub fn recent_events(&self) -> &'a [BattleEvent] {
+        self.events
+    }
+
+    /// The most recent actions of both fighters, oldest first.
+    pub fn recent_actions(&self) -> &'a [TakenAction] {
+        self.actions
+    }
+
+    /// The opponent's most recent actions, newest first.
+    pub fn opponent_actions(&self) -> impl Iterator<Item = &'a Action> + 'a {
+        let opponent = self.opponent.name.as_str();
+        self.actions.iter().rev().filter(move |a| a.actor == opponent).map(|a| &a.action)
+    }
+
+    /// How many times in a row the opponent's latest actions were `action`,
+    /// e.g. 2 when they healed on both of their last turns.
+    pub fn opponent_streak(&self, action: &Action) -> usize {
+        self.opponent_actions().take_while(|a| *a == action).count()
+    }
+}
+
+/// Decides what a fighter does on each of their turns.
+pub trait BattleStrategy {
+    fn choose_action(&mut self, context: &BattleContext, rng: &mut dyn RngCore) -> Action;
+}
+
+impl<S: BattleStrategy + ?Sized> BattleStrategy for &mut S {
+    fn choose_action(&mut self, context: &BattleContext, rng: &mut dyn RngCore) -> Action {
+        (**self).choose_action(context, rng)
+    }
+}
+
+/// Rolls against the fighter's `Behavior` chances every turn, ignoring what happened so far.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BehaviorStrategy;
+
+impl BattleStrategy for BehaviorStrategy {
+    fn choose_action(&mut self, context: &BattleContext, rng: &mut dyn RngCore) -> Action {
+        choose_action(context.actor, rng)
+    }
+}
+
+/// Follows the fighter's `Behavior`, but presses the attack once the opponent
+/// has spent `heal_streak` turns in a row healing.
+#[derive(Debug, Clone, Copy)]
+pub struct AdaptiveStrategy {
+    pub heal_streak: usize,
+}
+
+impl Default for AdaptiveStrategy {
+    fn default() -> Self {
+        Self { heal_streak: 2 }
+    }
+}
+
+impl BattleStrategy for AdaptiveStrategy {
+    fn choose_action(&mut self, context: &BattleContext, rng: &mut dyn RngCore) -> Action {
+        if context.opponent_streak(&Action::Heal) >= self.heal_streak {
+            return Action::Attack;
+        }
+        choose_action(context.actor, rng)
+    }
+}
+
+/// Attacks whenever the fighter has more of their health left than the opponent,
+/// and otherwise follows their `Behavior`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AggressiveWhenWinning;
+
+impl BattleStrategy for AggressiveWhenWinning {
+    fn choose_action(&mut self, context: &BattleContext, rng: &mut dyn RngCore) -> Action {
+        match (health_share(context, context.actor), health_share(context, context.opponent)) {
+            (Some(mine), Some(theirs)) if mine > theirs => Action::Attack,
+            _ => choose_action(context.actor, rng),
+        }
+    }
+}
+
+/// Heals whenever the fighter is at or below `threshold` of their health (and
+/// healing does anything), and otherwise follows their `Behavior`.
+#[derive(Debug, Clone, Copy)]
+pub struct HealWhenLow {
+    pub threshold: f64,
+}
+
+impl Default for HealWhenLow {
+    fn default() -> Self {
+        Self { threshold: 0.3 }
+    }
+}
+
+impl BattleStrategy for HealWhenLow {
+    fn choose_action(&mut self, context: &BattleContext, rng: &mut dyn RngCore) -> Action {
+        match health_share(context, context.actor) {
+            Some(share) if share <= self.threshold && context.actor.heal_delta > 0 => Action::Heal,
+            _ => choose_action(context.actor, rng),
+        }
+    }
+}
+
+/// How much of their starting health `fighter` has left, from 0 to 1.
+fn health_share(context: &BattleContext, fighter: &Neopet) -> Option<f64> {
+    let hp = context.state.get_hp(&fighter.name).ok()?;
+    Some(hp as f64 / fighter.health.max(1) as f64)
+}
+
+/// The strategy a fighter uses when none is given explicitly, picked with the
+/// `strategy` field of their definition, e.g. `"strategy": "heal_when_low"`.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategyKind {
+    /// Rolls against the `Behavior` chances, see `BehaviorStrategy`.
+    #[default]
+    Probabilistic,
+    Adaptive,
+    AggressiveWhenWinning,
+    HealWhenLow,
+}
+
+impl StrategyKind {
+    pub fn is_probabilistic(&self) -> bool {
+        *self == StrategyKind::Probabilistic
+    }
+
+    /// A fresh strategy of this kind with its default settings.
+    pub fn build(&self) -> Box<dyn BattleStrategy> {
+        match self {
+            StrategyKind::Probabilistic => Box::new(BehaviorStrategy),
+            StrategyKind::Adaptive => Box::new(AdaptiveStrategy::default()),
+            StrategyKind::AggressiveWhenWinning => Box::new(AggressiveWhenWinning),
+            StrategyKind::HealWhenLow => Box::new(HealWhenLow::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neopets::Behavior;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn healer(name: &str) -> Neopet {
+        Neopet {
+            health: 100,
+            heal_delta: 10,
+            base_attack: 5,
+            base_defense: 5,
+            behavior: Behavior::attack_or_heal(0.0),
+            ..Neopet::test_fighter(name)
+        }
+    }
+
+    fn taken(turn: u32, actor: &str, action: Action) -> TakenAction {
+        TakenAction { turn, actor: actor.to_string(), action }
+    }
+
+    #[test]
+    fn test_context_only_exposes_window() {
+        let (me, them) = (healer("Me"), healer("Them"));
+        let state = BattleState::new(&me, &them, 10);
+        let actions: Vec<TakenAction> = (1..=5).map(|t| taken(t, "Them", Action::Heal)).collect();
+        let events = vec![BattleEvent::Heal { turn: 1, actor: "Them".into(), amount: 10 }; 5];
+
+        let context = BattleContext::new(&me, &them, 6, &state, &events, &actions, 3);
+        assert_eq!(context.recent_events().len(), 3);
+        assert_eq!(context.recent_actions().first().map(|a| a.turn), Some(3));
+        assert_eq!(context.opponent_streak(&Action::Heal), 3);
+    }
+
+    #[test]
+    fn test_opponent_streak_stops_at_other_action() {
+        let (me, them) = (healer("Me"), healer("Them"));
+        let state = BattleState::new(&me, &them, 10);
+        let actions = vec![
+            taken(1, "Them", Action::Heal),
+            taken(2, "Them", Action::Attack),
+            taken(3, "Me", Action::Heal),
+            taken(4, "Them", Action::Heal),
+        ];
+
+        let context = BattleContext::new(&me, &them, 5, &state, &[], &actions, 10);
+        assert_eq!(context.opponent_streak(&Action::Heal), 1);
+        assert_eq!(context.opponent_streak(&Action::Attack), 0);
+    }
+
+    #[test]
+    fn test_adaptive_strategy_presses_the_attack() {
+        // A pure healer only attacks when the opponent keeps healing
+        let (me, them) = (healer("Me"), healer("Them"));
+        let state = BattleState::new(&me, &them, 10);
+        let mut strategy = AdaptiveStrategy::default();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let once = vec![taken(1, "Them", Action::Heal)];
+        let context = BattleContext::new(&me, &them, 2, &state, &[], &once, 10);
+        assert_eq!(strategy.choose_action(&context, &mut rng), Action::Heal);
+
+        let twice = vec![taken(1, "Them", Action::Heal), taken(3, "Them", Action::Heal)];
+        let context = BattleContext::new(&me, &them, 4, &state, &[], &twice, 10);
+        assert_eq!(strategy.choose_action(&context, &mut rng), Action::Attack);
+    }
+
+    #[test]
+    fn test_aggressive_when_winning_attacks_with_the_lead() {
+        let (me, them) = (healer("Me"), healer("Them"));
+        let mut state = BattleState::new(&me, &them, 10);
+        let mut rng = StdRng::seed_from_u64(1);
+
+        state.apply_damage("Them", 30).unwrap();
+        let context = BattleContext::new(&me, &them, 1, &state, &[], &[], 10);
+        assert_eq!(AggressiveWhenWinning.choose_action(&context, &mut rng), Action::Attack);
+
+        state.apply_damage("Me", 50).unwrap();
+        let context = BattleContext::new(&me, &them, 2, &state, &[], &[], 10);
+        assert_eq!(AggressiveWhenWinning.choose_action(&context, &mut rng), Action::Heal);
+    }
+
+    #[test]
+    fn test_heal_when_low_heals_below_threshold() {
+        // Someone who never heals on their own
+        let mut me = healer("Me");
+        me.behavior = Behavior::attack_or_heal(1.0);
+        let them = healer("Them");
+        let mut state = BattleState::new(&me, &them, 10);
+        let mut strategy = HealWhenLow::default();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        state.apply_damage("Me", 60).unwrap();
+        let context = BattleContext::new(&me, &them, 1, &state, &[], &[], 10);
+        assert_eq!(strategy.choose_action(&context, &mut rng), Action::Attack);
+
+        state.apply_damage("Me", 10).unwrap();
+        let context = BattleContext::new(&me, &them, 2, &state, &[], &[], 10);
+        assert_eq!(strategy.choose_action(&context, &mut rng), Action::Heal);
+
+        // Healing for nothing would waste the turn
+        me.heal_delta = 0;
+        let context = BattleContext::new(&me, &them, 3, &state, &[], &[], 10);
+        assert_eq!(strategy.choose_action(&context, &mut rng), Action::Attack);
+    }
+
+    #[test]
+    fn test_strategy_kind_names() {
+        let kind: StrategyKind = serde_json::from_str("\"aggressive_when_winning\"").unwrap();
+        assert_eq!(kind, StrategyKind::AggressiveWhenWinning);
+        assert_eq!(serde_json::to_string(&StrategyKind::HealWhenLow).unwrap(), "\"heal_when_low\"");
+        assert!(serde_json::from_str::<StrategyKind>("\"berserk\"").is_err());
+    }
+}
@@ -0,0 +1,177 @@
+// src/streaks.rs
+use std::collections::BTreeMap;
+use crate::matchmaking::pair_key;
+use crate::storage::BattleRecord;
+
+/// Streaks shorter than this aren't worth a mention.
+pub const MIN_STREAK: usize = 2;
+
+/// Consecutive results of the same kind, the latest battles first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Streak {
+    Wins(usize),
+    Losses(usize),
+}
+
+impl Streak {
+    pub fn count(&self) -> usize {
+        match self {
+            Streak::Wins(n) | Streak::Losses(n) => *n,
+        }
+    }
+}
+
+/// How many times in a row `winner` has beaten `loser` in their latest battles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rivalry {
+    pub winner: String,
+    pub loser: String,
+    pub wins: usize,
+}
+
+impl Rivalry {
+    /// e.g. "Acara has beaten Usul 4 times in a row"
+    pub fn describe(&self) -> String {
+        format!("{} has beaten {} {} times in a row", self.winner, self.loser, self.wins)
+    }
+}
+
+/// Current streak of every fighter and every matchup, replayed from completed battles.
+/// A battle without a winner ends the streaks of both fighters.
+#[derive(Debug, Clone, Default)]
+pub struct Streaks {
+    fighters: BTreeMap<String, Streak>,
+    rivalries: BTreeMap<(String, String), Rivalry>,
+}
+
+impl Streaks {
+    /// Streaks after every completed battle in `battles`, oldest first.
+    pub fn from_battles(battles: &[BattleRecord]) -> Self {
+        let mut streaks = Self::default();
+        for battle in battles {
+            streaks.record_battle(battle);
+        }
+        streaks
+    }
+
+    /// Extends or breaks the streaks of both fighters with one more battle.
+    pub fn record_battle(&mut self, battle: &BattleRecord) {
+        if !battle.is_completed {
+            return;
+        }
+        let pair = pair_key(&battle.fighter1_name, &battle.fighter2_name);
+        let Some(winner) = battle.winner.clone() else {
+            self.fighters.remove(&battle.fighter1_name);
+            self.fighters.remove(&battle.fighter2_name);
+            self.rivalries.remove(&pair);
+            return;
+        };
+        let loser = if winner == battle.fighter1_name {
+            battle.fighter2_name.clone()
+        } else {
+            battle.fighter1_name.clone()
+        };
+
+        let won = match self.fighters.get(&winner) {
+            Some(Streak::Wins(n)) => Streak::Wins(n + 1),
+            _ => Streak::Wins(1),
+        };
+        let lost = match self.fighters.get(&loser) {
+            Some(Streak::Losses(n)) => Streak::Losses(n + 1),
+            _ => Streak::Losses(1),
+        };
+        self.fighters.insert(winner.clone(), won);
+        self.fighters.insert(loser.clone(), lost);
+
+        let wins = match self.rivalries.get(&pair) {
+            Some(rivalry) if rivalry.winner == winner => rivalry.wins + 1,
+            _ => 1,
+        };
+        self.rivalries.insert(pair, Rivalry { winner, loser, wins });
+    }
+
+    pub fn fighter(&self, name: &str) -> Option<Streak> {
+        self.fighters.get(name).copied()
+    }
+
+    /// The current streak between two fighters, in either order.
+    pub fn rivalry(&self, fighter1: &str, fighter2: &str) -> Option<&Rivalry> {
+        self.rivalries.get(&pair_key(fighter1, fighter2))
+    }
+
+    /// Fighter streaks of at least `min` battles, longest first.
+    pub fn active_fighter_streaks(&self, min: usize) -> Vec<(&String, Streak)> {
+        let mut active: Vec<_> = self.fighters
+            .iter()
+            .filter(|(_, s)| s.count() >= min)
+            .map(|(name, s)| (name, *s))
+            .collect();
+        active.sort_by_key(|(_, s)| std::cmp::Reverse(s.count()));
+        active
+    }
+
+    /// Rivalries where one fighter won at least `min` times in a row, longest first.
+    pub fn active_rivalries(&self, min: usize) -> Vec<&Rivalry> {
+        let mut active: Vec<_> = self.rivalries.values().filter(|r| r.wins >= min).collect();
+        active.sort_by_key(|r| std::cmp::Reverse(r.wins));
+        active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn battle(fighter1: &str, fighter2: &str, winner: Option<&str>) -> BattleRecord {
+        BattleRecord {
+            winner: winner.map(|w| w.to_string()),
+            is_completed: true,
+            ..BattleRecord::test_battle(&format!("battle_{}_{}", fighter1, fighter2), fighter1, fighter2)
+        }
+    }
+
+    #[test]
+    fn test_rivalry_counts_wins_in_either_order() {
+        let streaks = Streaks::from_battles(&[
+            battle("Acara", "Usul", Some("Usul")),
+            battle("Acara", "Usul", Some("Acara")),
+            battle("Usul", "Acara", Some("Acara")),
+            battle("Acara", "Kacheek", Some("Kacheek")),
+            battle("Acara", "Usul", Some("Acara")),
+        ]);
+        let rivalry = streaks.rivalry("Usul", "Acara").unwrap();
+        assert_eq!(rivalry.wins, 3);
+        assert_eq!(rivalry.describe(), "Acara has beaten Usul 3 times in a row");
+    }
+
+    #[test]
+    fn test_fighter_streaks_break_on_other_results() {
+        let streaks = Streaks::from_battles(&[
+            battle("Acara", "Usul", Some("Acara")),
+            battle("Acara", "Kacheek", Some("Acara")),
+            battle("Kacheek", "Usul", Some("Kacheek")),
+            battle("Acara", "Kacheek", Some("Kacheek")),
+        ]);
+        assert_eq!(streaks.fighter("Acara"), Some(Streak::Losses(1)));
+        assert_eq!(streaks.fighter("Kacheek"), Some(Streak::Wins(2)));
+        assert_eq!(streaks.fighter("Usul"), Some(Streak::Losses(2)));
+        assert_eq!(streaks.active_fighter_streaks(MIN_STREAK).len(), 2);
+    }
+
+    #[test]
+    fn test_draws_and_pending_battles() {
+        let mut pending = battle("Acara", "Usul", Some("Usul"));
+        pending.is_completed = false;
+        let streaks = Streaks::from_battles(&[
+            battle("Acara", "Usul", Some("Acara")),
+            battle("Acara", "Usul", Some("Acara")),
+            pending,
+        ]);
+        assert_eq!(streaks.active_rivalries(MIN_STREAK).len(), 1);
+
+        let mut streaks = streaks;
+        streaks.record_battle(&battle("Acara", "Usul", None));
+        assert!(streaks.rivalry("Acara", "Usul").is_none());
+        assert!(streaks.fighter("Acara").is_none());
+    }
+}
@@ -0,0 +1,317 @@
+// src/tournament.rs
+use crate::battle::{battle_loop_with_config, BattleConfig};
+use crate::battle_log::BattleLog;
+use crate::integrity::battle_hash;
+use crate::storage::{BattleRecord, Storage};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// Tag put on every battle fought in a tournament, next to the tournament's ID.
+pub const TOURNAMENT_TAG: &str = "tournament";
+
+/// One pairing of a round. A fighter without an opponent got a bye and advances
+/// without fighting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TournamentMatch {
+    pub fighter1: String,
+    pub fighter2: Option<String>,   // None for a bye
+    pub battle_id: Option<String>,  // None for a bye
+    pub winner: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TournamentRound {
+    pub number: usize, // Starts at 1
+    pub matches: Vec<TournamentMatch>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TournamentRecord {
+    pub id: String,              // e.g. "tournament_3"
+    pub created_at: String,      // RFC 3339 timestamp
+    pub fighters: Vec<String>,   // In seed order, best seed first
+    pub rounds: Vec<TournamentRound>,
+    pub champion: String,
+}
+
+/// Every tournament run so far, stored in `assets/tournaments.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(transparent)]
+pub struct Tournaments {
+    pub records: Vec<TournamentRecord>,
+}
+
+impl Tournaments {
+    /// Loads past tournaments, or none if the file doesn't exist.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(path)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+}
+
+/// Bracket positions of seeds 1..=`size` (a power of two), so that adjacent seeds
+/// meet in the first round and the top two seeds can only meet in the final:
+/// 4 gives `[1, 4, 2, 3]`.
+fn seed_order(size: usize) -> Vec<usize> {
+    let mut order = vec![1];
+    while order.len() < size {
+        let next_size = order.len() * 2;
+        order = order.iter().flat_map(|&seed| [seed, next_size + 1 - seed]).collect();
+    }
+    order
+}
+
+/// First round pairings of a single-elimination bracket for `fighters` in seed
+/// order. The bracket is padded to the next power of two with byes, which go to
+/// the best seeds.
+pub fn first_round_pairs(fighters: &[String]) -> Vec<(String, Option<String>)> {
+    let size = fighters.len().next_power_of_two();
+    seed_order(size)
+        .chunks(2)
+        .map(|pair| {
+            let fighter = |seed: usize| fighters.get(seed - 1).cloned();
+            (fighter(pair[0]).expect("top seeds are always present"), fighter(pair[1]))
+        })
+        .collect()
+}
+
+/// Runs a single-elimination tournament between `fighters`, in seed order. Every
+/// battle is saved to `storage` as a completed `BattleRecord` tagged with the
+/// tournament ID; saving `storage` to disk is left to the caller.
+pub fn run_tournament<R: Rng>(
+    storage: &mut Storage,
+    id: &str,
+    fighters: &[String],
+    config: &BattleConfig,
+    created_at: &str,
+    rng: &mut R,
+) -> Result<TournamentRecord, Box<dyn std::error::Error>> {
+    if fighters.len() < 2 {
+        return Err("A tournament needs at least 2 fighters".into());
+    }
+    let mut seen = HashSet::new();
+    for name in fighters {
+        if storage.get_fighter(name).is_none() {
+            return Err(format!("Fighter '{}' not found", name).into());
+        }
+        if !seen.insert(name) {
+            return Err(format!("Fighter '{}' entered the tournament twice", name).into());
+        }
+    }
+
+    let mut rounds = Vec::new();
+    let mut pairs = first_round_pairs(fighters);
+    loop {
+        let number = rounds.len() + 1;
+        let mut matches = Vec::new();
+        for (fighter1, fighter2) in pairs {
+            let tournament_match = match fighter2 {
+                Some(fighter2) => fight(storage, id, number, (fighter1, fighter2), config, created_at, rng)?,
+                None => TournamentMatch { winner: fighter1.clone(), fighter1, fighter2: None, battle_id: None },
+            };
+            matches.push(tournament_match);
+        }
+
+        // Byes and eliminated fighters sat the round out, which heals injuries
+        let fought: Vec<String> = matches
+            .iter()
+            .filter_map(|m| m.fighter2.as_ref().map(|fighter2| [m.fighter1.clone(), fighter2.clone()]))
+            .flatten()
+            .collect();
+        storage.sit_out_round(&fought);
+
+        let winners: Vec<String> = matches.iter().map(|m| m.winner.clone()).collect();
+        rounds.push(TournamentRound { number, matches });
+        if winners.len() == 1 {
+            return Ok(TournamentRecord {
+                id: id.to_string(),
+                created_at: created_at.to_string(),
+                fighters: fighters.to_vec(),
+                rounds,
+                champion: winners[0].clone(),
+            });
+        }
+        pairs = winners
+            .chunks(2)
+            .map(|pair| (pair[0].clone(), pair.get(1).cloned()))
+            .collect();
+    }
+}
+
+/// Fights one match of round `round` and stores its battle.
+fn fight<R: Rng>(
+    storage: &mut Storage,
+    tournament_id: &str,
+    round: usize,
+    (fighter1, fighter2): (String, String),
+    config: &BattleConfig,
+    created_at: &str,
+    rng: &mut R,
+) -> Result<TournamentMatch, Box<dyn std::error::Error>> {
+    let neopet1 = storage.battle_fighter(&fighter1)?;
+    let neopet2 = storage.battle_fighter(&fighter2)?;
+    let events = battle_loop_with_config(&neopet1, &neopet2, config, rng)?;
+    let winner = BattleLog::from(&events)
+        .winner()
+        .map(str::to_string)
+        .ok_or_else(|| format!("Battle between '{}' and '{}' ended without a winner", fighter1, fighter2))?;
+
+    let battle_id = storage.generate_battle_id()?;
+    let hash = battle_hash(&events);
+    storage.add_complete_battle(BattleRecord {
+        events,
+        winner: Some(winner.clone()),
+        is_completed: true,
+        tags: vec![TOURNAMENT_TAG.to_string(), tournament_id.to_string()],
+        notes: vec![format!("Round {} of {}", round, tournament_id)],
+        hash: Some(hash),
+        ..BattleRecord::pending(battle_id.clone(), &fighter1, &fighter2, created_at)
+    })?;
+
+    Ok(TournamentMatch {
+        fighter1,
+        fighter2: Some(fighter2),
+        battle_id: Some(battle_id),
+        winner,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::StorageConfig;
+    use crate::neopets::{Behavior, Neopet};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use tempfile::tempdir;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn fighter(name: &str) -> Neopet {
+        Neopet {
+            health: 60,
+            heal_delta: 5,
+            base_attack: 8,
+            base_defense: 4,
+            behavior: Behavior::attack_or_heal(0.8),
+            ..Neopet::test_fighter(name)
+        }
+    }
+
+    fn storage_with(dir: &Path, fighters: &[String]) -> Storage {
+        let mut storage = Storage::open(&StorageConfig::in_dir(dir)).unwrap();
+        for name in fighters {
+            storage.add_neopet(fighter(name)).unwrap();
+        }
+        storage
+    }
+
+    #[test]
+    fn test_seed_order_keeps_top_seeds_apart() {
+        assert_eq!(seed_order(2), vec![1, 2]);
+        assert_eq!(seed_order(4), vec![1, 4, 2, 3]);
+        assert_eq!(seed_order(8), vec![1, 8, 4, 5, 2, 7, 3, 6]);
+    }
+
+    #[test]
+    fn test_first_round_gives_byes_to_top_seeds() {
+        let pairs = first_round_pairs(&names(&["A", "B", "C", "D", "E", "F"]));
+        assert_eq!(
+            pairs,
+            vec![
+                ("A".to_string(), None),
+                ("D".to_string(), Some("E".to_string())),
+                ("B".to_string(), None),
+                ("C".to_string(), Some("F".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run_tournament_crowns_a_champion() {
+        let temp_dir = tempdir().unwrap();
+        let fighters = names(&["A", "B", "C", "D", "E"]);
+        let mut storage = storage_with(temp_dir.path(), &fighters);
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let record = run_tournament(
+            &mut storage, "tournament_1", &fighters, &BattleConfig::default(), "2024-01-01T00:00:00Z", &mut rng,
+        ).unwrap();
+
+        // 5 fighters fill a bracket of 8: 3 rounds, 4 battles and 3 byes
+        assert_eq!(record.rounds.len(), 3);
+        assert!(fighters.contains(&record.champion));
+        assert_eq!(record.rounds.last().unwrap().matches[0].winner, record.champion);
+        let battles: Vec<&TournamentMatch> = record.rounds.iter()
+            .flat_map(|r| &r.matches)
+            .filter(|m| m.battle_id.is_some())
+            .collect();
+        assert_eq!(battles.len(), 4);
+
+        let stored = storage.complete_battle_records();
+        assert_eq!(stored.len(), 4);
+        for (battle, tournament_match) in stored.iter().zip(battles) {
+            assert_eq!(Some(&battle.id), tournament_match.battle_id.as_ref());
+            assert_eq!(battle.winner.as_ref(), Some(&tournament_match.winner));
+            assert!(battle.has_tag(TOURNAMENT_TAG) && battle.has_tag("tournament_1"));
+        }
+    }
+
+    #[test]
+    fn test_run_tournament_rejects_bad_entries() {
+        let temp_dir = tempdir().unwrap();
+        let mut storage = storage_with(temp_dir.path(), &names(&["A", "B"]));
+        let config = BattleConfig::default();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        for entries in [names(&["A"]), names(&["A", "Z"]), names(&["A", "A"])] {
+            assert!(run_tournament(&mut storage, "tournament_1", &entries, &config, "", &mut rng).is_err());
+        }
+        assert!(storage.complete_battle_records().is_empty());
+    }
+
+    #[test]
+    fn test_tournaments_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("tournaments.json");
+        let path = path.to_str().unwrap();
+        assert!(Tournaments::load(path).unwrap().records.is_empty());
+
+        let tournaments = Tournaments {
+            records: vec![TournamentRecord {
+                id: "tournament_1".to_string(),
+                created_at: "2024-01-01T00:00:00Z".to_string(),
+                fighters: names(&["A", "B"]),
+                rounds: vec![TournamentRound {
+                    number: 1,
+                    matches: vec![TournamentMatch {
+                        fighter1: "A".to_string(),
+                        fighter2: Some("B".to_string()),
+                        battle_id: Some("battle_1".to_string()),
+                        winner: "B".to_string(),
+                    }],
+                }],
+                champion: "B".to_string(),
+            }],
+        };
+        tournaments.save(path).unwrap();
+        assert_eq!(Tournaments::load(path).unwrap(), tournaments);
+    }
+}
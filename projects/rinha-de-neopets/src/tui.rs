@@ -0,0 +1,346 @@
+// src/tui.rs
+use crate::battle::BattleEvent;
+use crate::export::describe;
+use crate::neopets::Neopet;
+use crate::playback::Playback;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Gauge, Paragraph};
+use ratatui::{Frame, Terminal};
+use std::io;
+use std::time::{Duration, Instant};
+
+/// Time between two lines of the log at speed 1.0.
+const EVENT_DELAY: Duration = Duration::from_millis(600);
+
+/// A fighter's HP bar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FighterGauge {
+    pub name: String,
+    pub max_hp: u32,
+    pub hp: u32,
+}
+
+impl FighterGauge {
+    fn new(fighter: &Neopet) -> Self {
+        Self { name: fighter.name.clone(), max_hp: fighter.health, hp: fighter.health }
+    }
+
+    fn ratio(&self) -> f64 {
+        if self.max_hp == 0 { 0.0 } else { (self.hp as f64 / self.max_hp as f64).clamp(0.0, 1.0) }
+    }
+
+    fn color(&self) -> Color {
+        match self.ratio() {
+            r if r > 0.5 => Color::Green,
+            r if r > 0.25 => Color::Yellow,
+            _ => Color::Red,
+        }
+    }
+}
+
+/// What a key pressed in the TUI asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    Continue,
+    Leave,
+}
+
+/// The state of the battle screen: the events played so far and the playback controls.
+/// Kept apart from the terminal so it can be stepped and drawn to a `TestBackend`.
+pub struct BattleView<'a> {
+    events: &'a [BattleEvent],
+    played: usize,
+    fighters: [FighterGauge; 2],
+    log: Vec<String>,
+    turn: u32,
+    /// fighter1's odds of winning, once the battle predicted them.
+    win_probability: Option<f64>,
+    playback: Playback,
+}
+
+impl<'a> BattleView<'a> {
+    pub fn new(fighter1: &Neopet, fighter2: &Neopet, events: &'a [BattleEvent], speed: f64) -> Self {
+        Self {
+            events,
+            played: 0,
+            fighters: [FighterGauge::new(fighter1), FighterGauge::new(fighter2)],
+            log: Vec::new(),
+            turn: 0,
+            win_probability: None,
+            playback: Playback::new(speed),
+        }
+    }
+
+    pub fn fighters(&self) -> &[FighterGauge; 2] {
+        &self.fighters
+    }
+
+    pub fn log(&self) -> &[String] {
+        &self.log
+    }
+
+    pub fn turn(&self) -> u32 {
+        self.turn
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.playback.is_paused()
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.playback.speed()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.played == self.events.len()
+    }
+
+    /// How long to wait before the next `step`, at the current speed.
+    pub fn delay(&self) -> Duration {
+        EVENT_DELAY.div_f64(self.playback.speed())
+    }
+
+    /// Plays events up to and including the next one with a line in the log.
+    pub fn step(&mut self) {
+        while let Some(event) = self.events.get(self.played) {
+            self.played += 1;
+            if self.apply(event) {
+                break;
+            }
+        }
+    }
+
+    /// Plays the rest of the battle at once.
+    pub fn skip(&mut self) {
+        while !self.is_over() {
+            self.step();
+        }
+    }
+
+    /// The `Playback` keys, and `q` or Esc leave.
+    pub fn handle_key(&mut self, key: KeyCode) -> KeyAction {
+        if matches!(key, KeyCode::Char('q') | KeyCode::Esc) {
+            return KeyAction::Leave;
+        }
+        if self.playback.handle_key(key) && self.playback.is_skipping() {
+            self.skip();
+        }
+        KeyAction::Continue
+    }
+
+    /// Updates the bars and the turn with `event`, returning whether it added a line to
+    /// the log.
+    fn apply(&mut self, event: &BattleEvent) -> bool {
+        self.turn = self.turn.max(event.turn());
+        match event {
+            BattleEvent::HealthUpdate { fighter_name, to, .. } => {
+                if let Some(gauge) = self.fighters.iter_mut().find(|g| g.name == **fighter_name) {
+                    gauge.hp = *to;
+                }
+            }
+            BattleEvent::Prediction { fighter1_win_probability, .. }
+            | BattleEvent::WinProbability { fighter1_win_probability, .. } => {
+                self.win_probability = Some(*fighter1_win_probability);
+            }
+            _ => {}
+        }
+        match describe(event) {
+            Some(line) => {
+                self.log.push(format!("T{:>3}  {}", event.turn(), line));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let odds_height = if self.win_probability.is_some() { 3 } else { 0 };
+        let [title, bars, odds, log, help] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Length(odds_height),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let mut heading = format!(
+            "⚔️  {} vs {}   Turn {}   {}x",
+            self.fighters[0].name, self.fighters[1].name, self.turn, self.speed()
+        );
+        if self.is_paused() {
+            heading.push_str("   ⏸ paused");
+        }
+        frame.render_widget(Paragraph::new(heading).style(Style::default().add_modifier(Modifier::BOLD)), title);
+
+        let [left, right] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(bars);
+        for (gauge, area) in self.fighters.iter().zip([left, right]) {
+            let bar = Gauge::default()
+                .block(Block::default().borders(Borders::ALL).title(gauge.name.as_str()))
+                .gauge_style(Style::default().fg(gauge.color()))
+                .ratio(gauge.ratio())
+                .label(format!("{}/{} HP", gauge.hp, gauge.max_hp));
+            frame.render_widget(bar, area);
+        }
+
+        if let Some(probability) = self.win_probability {
+            let bar = Gauge::default()
+                .block(Block::default().borders(Borders::ALL).title("Odds"))
+                .gauge_style(Style::default().fg(Color::Cyan).bg(Color::Magenta))
+                .ratio(probability.clamp(0.0, 1.0))
+                .label(format!(
+                    "{} {:.0}%  ·  {:.0}% {}",
+                    self.fighters[0].name,
+                    probability * 100.0,
+                    (1.0 - probability) * 100.0,
+                    self.fighters[1].name
+                ));
+            frame.render_widget(bar, odds);
+        }
+
+        frame.render_widget(self.log_pane(log), log);
+
+        let keys = if self.is_over() {
+            "Battle over · q leave"
+        } else {
+            "space pause · +/- speed · s skip to the end · q leave"
+        };
+        frame.render_widget(Paragraph::new(keys).style(Style::default().fg(Color::DarkGray)), help);
+    }
+
+    /// The last lines of the log that fit in `area`, the newest at the bottom.
+    fn log_pane(&self, area: Rect) -> Paragraph<'_> {
+        let rows = usize::from(area.height.saturating_sub(2));
+        let lines: Vec<Line> = self.log[self.log.len().saturating_sub(rows)..]
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect();
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Events"))
+    }
+}
+
+/// Raw mode on the alternate screen while the TUI is open, restored when dropped so the
+/// shell comes back intact even if drawing fails.
+struct TuiScreen;
+
+impl TuiScreen {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, cursor::Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TuiScreen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Plays `events` full screen, with HP bars, the odds when the battle has them, a
+/// scrolling log and the turn, until the user leaves. Leaving early only stops the
+/// playback, the battle was already fought.
+pub fn watch(fighter1: &Neopet, fighter2: &Neopet, events: &[BattleEvent], speed: f64) -> io::Result<()> {
+    let _screen = TuiScreen::enter()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    play(&mut terminal, BattleView::new(fighter1, fighter2, events, speed))
+}
+
+fn play<B: Backend>(terminal: &mut Terminal<B>, mut view: BattleView) -> io::Result<()>
+where
+    io::Error: From<B::Error>,
+{
+    let mut next_step = Instant::now();
+    loop {
+        terminal.draw(|frame| view.render(frame))?;
+
+        // Short polls keep key presses responsive while waiting for the next event
+        let waiting = view.is_paused() || view.is_over();
+        let wait = if waiting { Duration::from_millis(250) } else { next_step.saturating_duration_since(Instant::now()) };
+        if event::poll(wait)? {
+            if let Event::Key(key) = event::read()? {
+                let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if key.kind == KeyEventKind::Press && (ctrl_c || view.handle_key(key.code) == KeyAction::Leave) {
+                    return Ok(());
+                }
+            }
+            continue;
+        }
+        if !waiting && Instant::now() >= next_step {
+            view.step();
+            next_step = Instant::now() + view.delay();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::BattleCompletionReason;
+    use ratatui::backend::TestBackend;
+
+    fn events() -> Vec<BattleEvent> {
+        vec![
+            BattleEvent::Prediction { turn: 1, fighter1: "Acara".into(), fighter2: "Usul".into(), fighter1_win_probability: 0.25 },
+            BattleEvent::Attack {
+                turn: 1, actor: "Acara".into(), target: "Usul".into(),
+                raw_damage: 20, shield_value: 5, actual_damage: 15,
+                is_killing_blow: false, overkill_amount: 0,
+            },
+            BattleEvent::HealthUpdate { fighter_name: "Usul".into(), from: 50, to: 35, turn: 1, source: None },
+            BattleEvent::Heal { turn: 2, actor: "Usul".into(), amount: 5 },
+            BattleEvent::BattleComplete {
+                turn: 9,
+                winner: "Usul".into(),
+                loser: "Acara".into(),
+                winner_final_hp: 12,
+                loser_final_hp: 0,
+                completion_reason: BattleCompletionReason::HpDepleted("Acara".into()),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_view_steps_through_logged_events() {
+        let events = events();
+        let mut view = BattleView::new(&Neopet::test_fighter("Acara"), &Neopet::test_fighter("Usul"), &events, 1.0);
+        view.step();
+        // The prediction has no line of its own, the step goes on to the attack
+        assert_eq!(view.log().len(), 1);
+        assert_eq!(view.turn(), 1);
+        assert_eq!(view.fighters()[1].hp, 50);
+        view.step();
+        assert_eq!(view.fighters()[1].hp, 35);
+
+        assert_eq!(view.handle_key(KeyCode::Char(' ')), KeyAction::Continue);
+        assert!(view.is_paused());
+        view.handle_key(KeyCode::Char('+'));
+        assert_eq!(view.delay(), EVENT_DELAY / 2);
+        view.handle_key(KeyCode::Char('s'));
+        assert!(view.is_over());
+        assert_eq!(view.turn(), 9);
+        assert_eq!(view.handle_key(KeyCode::Char('q')), KeyAction::Leave);
+    }
+
+    #[test]
+    fn test_view_renders_bars_odds_and_log() {
+        let events = events();
+        let mut view = BattleView::new(&Neopet::test_fighter("Acara"), &Neopet::test_fighter("Usul"), &events, 1.0);
+        view.skip();
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal.draw(|frame| view.render(frame)).unwrap();
+
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("Turn 9"));
+        assert!(screen.contains("35/50 HP"));
+        assert!(screen.contains("Acara 25%"));
+        assert!(screen.contains("Battle over"));
+    }
+}
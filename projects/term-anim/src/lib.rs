@@ -0,0 +1,82 @@
+//! Paces terminal output for the projects that animate it, so none of them has to
+//! sprinkle raw sleeps around.
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+/// How fast timed output plays.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pacer {
+    /// Every pause is divided by the speed, 2.0 plays twice as fast.
+    Speed(f64),
+    /// Never waits, for tests and piping the output somewhere.
+    Instant,
+}
+
+impl Default for Pacer {
+    fn default() -> Self {
+        Pacer::Speed(1.0)
+    }
+}
+
+impl Pacer {
+    /// How long a pause of `duration` actually lasts.
+    pub fn scaled(&self, duration: Duration) -> Duration {
+        match *self {
+            Pacer::Speed(speed) if speed > 0.0 => duration.div_f64(speed),
+            // A speed of 0 or less would wait forever
+            Pacer::Speed(_) | Pacer::Instant => Duration::ZERO,
+        }
+    }
+
+    pub fn pause(&self, duration: Duration) {
+        let duration = self.scaled(duration);
+        if !duration.is_zero() {
+            thread::sleep(duration);
+        }
+    }
+
+    /// Writes `lines` one at a time, waiting `line_delay` after each one and
+    /// `break_delay` after blank lines.
+    pub fn print_lines<'a, W: Write>(
+        &self,
+        out: &mut W,
+        lines: impl IntoIterator<Item = &'a str>,
+        line_delay: Duration,
+        break_delay: Duration,
+    ) -> io::Result<()> {
+        for line in lines {
+            writeln!(out, "{}", line)?;
+            out.flush()?;
+            self.pause(if line.trim().is_empty() { break_delay } else { line_delay });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn scaled_follows_speed() {
+        let second = Duration::from_secs(1);
+        assert_eq!(Pacer::default().scaled(second), second);
+        assert_eq!(Pacer::Speed(2.0).scaled(second), Duration::from_millis(500));
+        assert_eq!(Pacer::Speed(0.5).scaled(second), Duration::from_secs(2));
+        assert_eq!(Pacer::Speed(0.0).scaled(second), Duration::ZERO);
+        assert_eq!(Pacer::Instant.scaled(second), Duration::ZERO);
+    }
+
+    #[test]
+    fn instant_print_lines_does_not_wait() {
+        let mut out = Vec::new();
+        let start = Instant::now();
+        Pacer::Instant
+            .print_lines(&mut out, ["one", "", "two"], Duration::from_secs(10), Duration::from_secs(10))
+            .unwrap();
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(String::from_utf8(out).unwrap(), "one\n\ntwo\n");
+    }
+}